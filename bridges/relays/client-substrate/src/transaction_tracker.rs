@@ -265,7 +265,8 @@ async fn watch_transaction_status<
 				);
 				return InvalidationStatus::Lost
 			},
-			Some(TransactionStatusOf::<C>::Usurped(new_transaction_hash)) => {
+			Some(TransactionStatusOf::<C>::Usurped(new_transaction_hash)) |
+			Some(TransactionStatusOf::<C>::Replaced(new_transaction_hash)) => {
 				// this may be result of our transaction resubmitter work or some manual
 				// intervention. In both cases - let's start stall timeout, because the meaning
 				// of transaction may have changed
@@ -403,6 +403,10 @@ impl sc_cli::CliConfiguration for NormalizedRunCmd {
 		self.base.force_authoring()
 	}
 
+	fn extrinsic_index(&self) -> sc_cli::Result<bool> {
+		self.base.extrinsic_index()
+	}
+
 	fn prometheus_config(
 		&self,
 		default_listen_port: u16,
@@ -455,6 +459,10 @@ impl sc_cli::CliConfiguration for NormalizedRunCmd {
 		self.base.transaction_pool(is_dev)
 	}
 
+	fn transaction_pool_journal(&self, config_dir: &PathBuf) -> sc_cli::Result<Option<PathBuf>> {
+		self.base.transaction_pool_journal(config_dir)
+	}
+
 	fn max_runtime_instances(&self) -> sc_cli::Result<Option<usize>> {
 		self.base.max_runtime_instances()
 	}
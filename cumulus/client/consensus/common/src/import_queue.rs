@@ -55,6 +55,10 @@ impl<Block: BlockT> Verifier<Block> for VerifyNothing {
 	) -> Result<BlockImportParams<Block>, String> {
 		Ok(params)
 	}
+
+	fn can_verify_ahead(&self) -> bool {
+		true
+	}
 }
 
 /// An import queue which does no verification.
@@ -479,6 +479,7 @@ where
 				origin: None,
 				skip_execution: false,
 				state: None,
+				remote_state: false,
 				indexed_body: None,
 			});
 
@@ -19,8 +19,8 @@ use sp_runtime::traits::NumberFor;
 
 use sc_network::{
 	config::{
-		NetworkConfiguration, NonReservedPeerMode, NotificationHandshake, PeerStore, ProtocolId,
-		SetConfig,
+		NetworkConfiguration, NonReservedPeerMode, NotificationBandwidth, NotificationHandshake,
+		NotificationPriority, PeerStore, ProtocolId, SetConfig,
 	},
 	peer_store::PeerStoreProvider,
 	service::traits::NetworkService,
@@ -154,6 +154,10 @@ fn get_block_announce_proto_config<Network: NetworkBackend<Block, Hash>>(
 			out_peers: 0,
 			reserved_nodes: Vec::new(),
 			non_reserved_mode: NonReservedPeerMode::Deny,
+			bandwidth: NotificationBandwidth {
+				priority: NotificationPriority::High,
+				..Default::default()
+			},
 		},
 		metrics,
 		peer_store_handle,
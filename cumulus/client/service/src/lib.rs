@@ -31,7 +31,8 @@ use cumulus_relay_chain_minimal_node::{
 use futures::{channel::mpsc, StreamExt};
 use polkadot_primitives::{CollatorPair, OccupiedCoreAssumption};
 use sc_client_api::{
-	Backend as BackendT, BlockBackend, BlockchainEvents, Finalizer, ProofProvider, UsageProvider,
+	light::RemoteProofsProvider, AuxStore, Backend as BackendT, BlockBackend, BlockchainEvents,
+	Finalizer, ProofProvider, UsageProvider,
 };
 use sc_consensus::{
 	import_queue::{ImportQueue, ImportQueueService},
@@ -454,6 +455,8 @@ where
 		+ HeaderMetadata<Block, Error = sp_blockchain::Error>
 		+ BlockIdTo<Block, Error = sp_blockchain::Error>
 		+ ProofProvider<Block>
+		+ RemoteProofsProvider<Block>
+		+ AuxStore
 		+ 'static,
 	Client::Api: CollectCollationInfo<Block>
 		+ sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block>,
@@ -463,7 +466,7 @@ where
 	Network: NetworkBackend<Block, <Block as BlockT>::Hash>,
 {
 	let warp_sync_config = match parachain_config.network.sync_mode {
-		SyncMode::Warp => {
+		SyncMode::Warp | SyncMode::Light => {
 			log::debug!(target: LOG_TARGET_SYNC, "waiting for announce block...");
 
 			let target_block =
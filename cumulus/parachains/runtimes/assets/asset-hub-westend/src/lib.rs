@@ -2076,6 +2076,7 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[api_version(3)]
 	impl pallet_revive::ReviveApi<Block, AccountId, Balance, Nonce, BlockNumber, EventRecord> for Runtime
 	{
 		fn balance(address: H160) -> U256 {
@@ -2171,6 +2172,39 @@ impl_runtime_apis! {
 				key
 			)
 		}
+
+		fn trace_block(
+			block: Block,
+			config: pallet_revive::evm::TracerType,
+		) -> Vec<(u32, pallet_revive::evm::Trace)> {
+			Revive::trace_block(
+				block,
+				config,
+				Executive::initialize_block,
+				Executive::apply_extrinsic,
+			)
+		}
+
+		fn trace_tx(
+			block: Block,
+			tx_index: u32,
+			config: pallet_revive::evm::TracerType,
+		) -> Option<pallet_revive::evm::Trace> {
+			Revive::trace_tx(
+				block,
+				tx_index,
+				config,
+				Executive::initialize_block,
+				Executive::apply_extrinsic,
+			)
+		}
+
+		fn trace_call(
+			tx: pallet_revive::evm::GenericTransaction,
+			config: pallet_revive::evm::TracerType,
+		) -> Result<pallet_revive::evm::Trace, pallet_revive::EthTransactError> {
+			Revive::trace_call(tx, config, Self::eth_transact)
+		}
 	}
 }
 
@@ -365,6 +365,10 @@ impl<Config: CliConfig> CliConfiguration<Self> for RelayChainCli<Config> {
 		self.base.base.transaction_pool(is_dev)
 	}
 
+	fn transaction_pool_journal(&self, config_dir: &PathBuf) -> sc_cli::Result<Option<PathBuf>> {
+		self.base.base.transaction_pool_journal(config_dir)
+	}
+
 	fn trie_cache_maximum_size(&self) -> sc_cli::Result<Option<usize>> {
 		self.base.base.trie_cache_maximum_size()
 	}
@@ -389,6 +393,10 @@ impl<Config: CliConfig> CliConfiguration<Self> for RelayChainCli<Config> {
 		self.base.base.force_authoring()
 	}
 
+	fn extrinsic_index(&self) -> sc_cli::Result<bool> {
+		self.base.base.extrinsic_index()
+	}
+
 	fn disable_grandpa(&self) -> sc_cli::Result<bool> {
 		self.base.base.disable_grandpa()
 	}
@@ -208,7 +208,8 @@ impl<NodeSpec: NodeSpecT> ManualSealNode<NodeSpec> {
 				}
 			},
 		};
-		let authorship_future = sc_consensus_manual_seal::run_manual_seal(params);
+		let authorship_future =
+			sc_consensus_manual_seal::run_manual_seal_with_backend(params, backend.clone());
 		task_manager.spawn_essential_handle().spawn_blocking(
 			"manual-seal",
 			None,
@@ -198,7 +198,9 @@ pub fn get_wasm_module() -> Box<dyn sc_executor_common::wasm_runtime::WasmModule
 			wasm_bulk_memory: false,
 			wasm_reference_types: false,
 			wasm_simd: false,
+			max_memory_pages: None,
 		},
+		profiler: None,
 	};
 	Box::new(
 		sc_executor_wasmtime::create_runtime::<sp_io::SubstrateHostFunctions>(blob, config)
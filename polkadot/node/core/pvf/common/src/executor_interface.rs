@@ -91,9 +91,11 @@ pub const DEFAULT_CONFIG: Config = Config {
 		// extensions (e.g., sign extension ops) are enabled by Wasmtime and cannot be disabled.
 		wasm_reference_types: false,
 		wasm_simd: false,
+		max_memory_pages: None,
 		wasm_bulk_memory: false,
 		wasm_multi_value: false,
 	},
+	profiler: None,
 };
 
 /// Executes the given PVF in the form of a compiled artifact and returns the result of
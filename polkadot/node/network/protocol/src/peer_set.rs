@@ -99,6 +99,12 @@ impl PeerSet {
 						out_peers: super::MIN_GOSSIP_PEERS as u32 / 2 - 1,
 						reserved_nodes: Vec::new(),
 						non_reserved_mode: sc_network::config::NonReservedPeerMode::Accept,
+						// approvals and availability gossip must not be starved by the
+						// transactions and statements of the node
+						bandwidth: sc_network::config::NotificationBandwidth {
+							priority: sc_network::config::NotificationPriority::High,
+							..Default::default()
+						},
 					},
 					metrics,
 					peer_store_handle,
@@ -123,6 +129,7 @@ impl PeerSet {
 						} else {
 							sc_network::config::NonReservedPeerMode::Deny
 						},
+						bandwidth: Default::default(),
 					},
 					metrics,
 					peer_store_handle,
@@ -915,11 +915,14 @@ pub fn new_full<
 		Vec::new()
 	};
 
-	let warp_sync = Arc::new(sc_consensus_grandpa::warp_proof::NetworkProvider::new(
-		backend.clone(),
-		import_setup.1.shared_authority_set().clone(),
-		grandpa_hard_forks,
-	));
+	let warp_sync = Arc::new(
+		sc_consensus_grandpa::warp_proof::NetworkProvider::new(
+			backend.clone(),
+			import_setup.1.shared_authority_set().clone(),
+			grandpa_hard_forks,
+		)
+		.with_prometheus_registry(prometheus_registry.as_ref()),
+	);
 
 	let ext_overseer_args = if is_parachain_node.is_running_alongside_parachain_node() {
 		None
@@ -1297,6 +1300,10 @@ pub fn new_full<
 			links: beefy_links,
 			on_demand_justifications_handler: beefy_on_demand_justifications_handler,
 			is_authority: role.is_authority(),
+			offchain_tx_pool_factory: Some(OffchainTransactionPoolFactory::new(
+				transaction_pool.clone(),
+			)),
+			bridged_finality_proofs: None,
 		};
 
 		let gadget = sc_consensus_beefy::start_beefy_gadget::<
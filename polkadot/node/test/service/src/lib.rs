@@ -200,6 +200,7 @@ pub fn node_config(
 		trie_cache_maximum_size: Some(64 * 1024 * 1024),
 		state_pruning: Default::default(),
 		blocks_pruning: BlocksPruning::KeepFinalized,
+		extrinsic_index: false,
 		chain_spec: Box::new(spec),
 		executor: ExecutorConfiguration {
 			wasm_method: WasmExecutionMethod::Compiled {
@@ -275,6 +275,10 @@ impl sc_transaction_pool_api::TransactionPool for Transactions {
 		Default::default()
 	}
 
+	fn clear(&self) -> Vec<Arc<Self::InPoolTransaction>> {
+		Default::default()
+	}
+
 	fn futures(&self) -> Vec<Self::InPoolTransaction> {
 		unimplemented!()
 	}
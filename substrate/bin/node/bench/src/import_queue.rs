@@ -0,0 +1,211 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Import queue benchmark.
+//!
+//! This benchmark measures the import of a chain of blocks through the import queue, in batches
+//! like the blocks downloaded by the sync. The blocks are either verified sequentially with their
+//! import, or verified ahead while the previous blocks are imported.
+
+use std::{
+	borrow::Cow,
+	sync::atomic::{AtomicUsize, Ordering},
+};
+
+use futures::future;
+use node_primitives::Block;
+use node_testing::bench::{BenchDb, BlockType, DatabaseType, KeyTypes};
+use sc_consensus::{
+	BasicQueue, BlockImportError, BlockImportParams, BlockImportStatus, ForkChoiceStrategy,
+	ImportQueue, IncomingBlock, Link, Verifier,
+};
+use sp_consensus::BlockOrigin;
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+use crate::{
+	common::SizeType,
+	core::{self, Mode, Path},
+};
+
+/// Number of blocks sent to the import queue at once.
+const BATCH_SIZE: usize = 16;
+
+pub struct ImportQueueBenchmarkDescription {
+	pub block_type: BlockType,
+	pub size: SizeType,
+	pub database_type: DatabaseType,
+	pub length: usize,
+	pub verify_ahead: bool,
+}
+
+pub struct ImportQueueBenchmark {
+	database: BenchDb,
+	blocks: Vec<Block>,
+	verify_ahead: bool,
+}
+
+impl core::BenchmarkDescription for ImportQueueBenchmarkDescription {
+	fn path(&self) -> Path {
+		let mut path = Path::new(&["node", "import-queue"]);
+
+		match self.block_type {
+			BlockType::RandomTransfersKeepAlive => path.push("transfer_keep_alive"),
+			BlockType::RandomTransfersReaping => path.push("transfer_reaping"),
+			BlockType::Noop => path.push("noop"),
+		}
+
+		match self.database_type {
+			DatabaseType::RocksDb => path.push("rocksdb"),
+			DatabaseType::ParityDb => path.push("paritydb"),
+		}
+
+		path.push(&format!("{}", self.size));
+		path.push(&format!("{}", self.length));
+		path.push(if self.verify_ahead { "pipelined" } else { "sequential" });
+
+		path
+	}
+
+	fn setup(self: Box<Self>) -> Box<dyn core::Benchmark> {
+		let mut database = BenchDb::with_key_types(self.database_type, 50_000, KeyTypes::Sr25519);
+		let blocks = database
+			.generate_chain(self.block_type.to_content(self.size.transactions()), self.length);
+		Box::new(ImportQueueBenchmark { database, blocks, verify_ahead: self.verify_ahead })
+	}
+
+	fn name(&self) -> Cow<'static, str> {
+		format!(
+			"Import queue ({} blocks, {:?}/{}, {:?} backend, {})",
+			self.length,
+			self.block_type,
+			self.size,
+			self.database_type,
+			if self.verify_ahead { "pipelined" } else { "sequential" },
+		)
+		.into()
+	}
+}
+
+impl core::Benchmark for ImportQueueBenchmark {
+	fn run(&mut self, mode: Mode) -> std::time::Duration {
+		let context = self.database.create_context();
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let mut queue = BasicQueue::new(
+			BenchVerifier { verify_ahead: self.verify_ahead },
+			Box::new(context.client.clone()),
+			None,
+			&spawner,
+			None,
+		);
+
+		let blocks = self
+			.blocks
+			.iter()
+			.map(|block| IncomingBlock {
+				hash: block.hash(),
+				header: Some(block.header().clone()),
+				body: Some(block.extrinsics().to_vec()),
+				indexed_body: None,
+				justifications: None,
+				origin: None,
+				allow_missing_state: false,
+				import_existing: false,
+				skip_execution: false,
+				state: None,
+				remote_state: false,
+			})
+			.collect::<Vec<_>>();
+
+		if mode == Mode::Profile {
+			std::thread::park_timeout(std::time::Duration::from_secs(3));
+		}
+
+		let link = BenchLink::default();
+		let start = std::time::Instant::now();
+		for batch in blocks.chunks(BATCH_SIZE) {
+			queue
+				.service_ref()
+				.import_blocks(BlockOrigin::NetworkInitialSync, batch.to_vec());
+		}
+		futures::executor::block_on(future::poll_fn(|cx| {
+			queue.poll_actions(cx, &link);
+			if link.processed.load(Ordering::Relaxed) < blocks.len() {
+				std::task::Poll::Pending
+			} else {
+				std::task::Poll::Ready(())
+			}
+		}));
+		let elapsed = start.elapsed();
+
+		assert_eq!(link.imported.load(Ordering::Relaxed), blocks.len(), "All blocks are imported");
+
+		if mode == Mode::Profile {
+			std::thread::park_timeout(std::time::Duration::from_secs(1));
+		}
+
+		log::info!(
+			target: "bench-logistics",
+			"imported {} blocks through the import queue, took: {:#?}",
+			blocks.len(),
+			elapsed,
+		);
+
+		elapsed
+	}
+}
+
+/// Accepts all the blocks, which are built locally.
+struct BenchVerifier {
+	verify_ahead: bool,
+}
+
+#[async_trait::async_trait]
+impl Verifier<Block> for BenchVerifier {
+	async fn verify(
+		&self,
+		mut block: BlockImportParams<Block>,
+	) -> Result<BlockImportParams<Block>, String> {
+		block.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+		Ok(block)
+	}
+
+	fn can_verify_ahead(&self) -> bool {
+		self.verify_ahead
+	}
+}
+
+#[derive(Default)]
+struct BenchLink {
+	processed: AtomicUsize,
+	imported: AtomicUsize,
+}
+
+impl Link<Block> for BenchLink {
+	fn blocks_processed(
+		&self,
+		imported: usize,
+		count: usize,
+		_results: Vec<(
+			Result<BlockImportStatus<NumberFor<Block>>, BlockImportError>,
+			<Block as BlockT>::Hash,
+		)>,
+	) {
+		self.imported.fetch_add(imported, Ordering::Relaxed);
+		self.processed.fetch_add(count, Ordering::Relaxed);
+	}
+}
@@ -22,6 +22,7 @@ mod construct;
 mod core;
 mod generator;
 mod import;
+mod import_queue;
 mod simple_trie;
 mod state_sizes;
 mod tempdb;
@@ -37,6 +38,7 @@ use crate::{
 	construct::ConstructionBenchmarkDescription,
 	core::{run_benchmark, Mode as BenchmarkMode},
 	import::ImportBenchmarkDescription,
+	import_queue::ImportQueueBenchmarkDescription,
 	tempdb::DatabaseType,
 	trie::{DatabaseSize, TrieReadBenchmarkDescription, TrieWriteBenchmarkDescription},
 	txpool::PoolBenchmarkDescription,
@@ -147,6 +149,14 @@ fn main() {
 			database_type: BenchDataBaseType::RocksDb,
 		},
 		PoolBenchmarkDescription { database_type: BenchDataBaseType::RocksDb },
+		verify_ahead in [false, true] =>
+			ImportQueueBenchmarkDescription {
+				block_type: BlockType::RandomTransfersKeepAlive,
+				size: SizeType::Small,
+				database_type: BenchDataBaseType::RocksDb,
+				length: 20,
+				verify_ahead,
+			},
 	);
 
 	if opt.list {
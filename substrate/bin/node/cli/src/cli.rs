@@ -43,6 +43,13 @@ pub struct Cli {
 	#[arg(long)]
 	pub no_hardware_benchmarks: bool,
 
+	/// Submit the valid `#[pallet::tasks]` of the runtime to the transaction pool.
+	///
+	/// On every new best block the tasks that are currently valid are submitted as unsigned
+	/// `do_task` transactions.
+	#[arg(long)]
+	pub offchain_tasks: bool,
+
 	#[allow(missing_docs)]
 	#[clap(flatten)]
 	pub storage_monitor: sc_storage_monitor::StorageMonitorParams,
@@ -89,9 +96,15 @@ pub enum Subcommand {
 	/// Export the state of a given block into a chain spec.
 	ExportState(sc_cli::ExportStateCmd),
 
+	/// Export the state of a given block into a binary snapshot.
+	ExportSnapshot(sc_cli::ExportSnapshotCmd),
+
 	/// Import blocks.
 	ImportBlocks(sc_cli::ImportBlocksCmd),
 
+	/// Import the state of a block from a binary snapshot.
+	ImportSnapshot(sc_cli::ImportSnapshotCmd),
+
 	/// Remove the whole chain.
 	PurgeChain(sc_cli::PurgeChainCmd),
 
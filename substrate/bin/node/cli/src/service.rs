@@ -26,7 +26,7 @@ use polkadot_sdk::{
 };
 
 use crate::Cli;
-use codec::Encode;
+use codec::{Decode, Encode};
 use frame_benchmarking_cli::SUBSTRATE_REFERENCE_HARDWARE;
 use frame_system_rpc_runtime_api::AccountNonceApi;
 use futures::prelude::*;
@@ -97,6 +97,17 @@ pub fn fetch_nonce(client: &FullClient, account: sp_core::sr25519::Pair) -> u32
 		.expect("Fetching account nonce works; qed")
 }
 
+/// Returns the encoded signer of the given encoded extrinsic.
+///
+/// Used by the transaction pool to limit the number of future transactions of a single sender,
+/// and by the database to index extrinsics by signer.
+fn transaction_sender(xt: &[u8]) -> Option<Vec<u8>> {
+	match kitchensink_runtime::UncheckedExtrinsic::decode(&mut &xt[..]).ok()?.0.preamble {
+		generic::Preamble::Signed(address, ..) => Some(address.encode()),
+		_ => None,
+	}
+}
+
 /// Create a transaction using the given `call`.
 ///
 /// The transaction will be signed by `sender`. If `nonce` is `None` it will be fetched from the
@@ -212,11 +223,24 @@ pub fn new_partial(
 
 	let executor = sc_service::new_wasm_executor(&config.executor);
 
+	let mut db_config = config.db_config();
+	db_config.extrinsic_signer = Some(Arc::new(transaction_sender));
+	let backend = sc_service::new_db_backend(db_config)?;
+	let genesis_block_builder = sc_service::GenesisBlockBuilder::new(
+		config.chain_spec.as_storage_builder(),
+		!config.no_genesis(),
+		backend.clone(),
+		executor.clone(),
+	)?;
+
 	let (client, backend, keystore_container, task_manager) =
-		sc_service::new_full_parts::<Block, RuntimeApi, _>(
+		sc_service::new_full_parts_with_genesis_builder::<Block, RuntimeApi, _, _>(
 			config,
 			telemetry.as_ref().map(|(_, telemetry)| telemetry.handle()),
 			executor,
+			backend,
+			genesis_block_builder,
+			false,
 		)?;
 	let client = Arc::new(client);
 
@@ -235,6 +259,7 @@ pub fn new_partial(
 		)
 		.with_options(config.transaction_pool.clone())
 		.with_prometheus(config.prometheus_registry())
+		.with_transaction_sender(Some(Arc::new(transaction_sender)))
 		.build(),
 	);
 
@@ -507,11 +532,14 @@ pub fn new_full_base<N: NetworkBackend<Block, <Block as BlockT>::Hash>>(
 		notification_service
 	});
 
-	let warp_sync = Arc::new(grandpa::warp_proof::NetworkProvider::new(
-		backend.clone(),
-		import_setup.1.shared_authority_set().clone(),
-		Vec::default(),
-	));
+	let warp_sync = Arc::new(
+		grandpa::warp_proof::NetworkProvider::new(
+			backend.clone(),
+			import_setup.1.shared_authority_set().clone(),
+			Vec::default(),
+		)
+		.with_prometheus_registry(prometheus_registry.as_ref()),
+	);
 
 	let (network, system_rpc_tx, tx_handler_controller, sync_service) =
 		sc_service::build_network(sc_service::BuildNetworkParams {
@@ -696,6 +724,10 @@ pub fn new_full_base<N: NetworkBackend<Block, <Block as BlockT>::Hash>>(
 		links: beefy_links,
 		on_demand_justifications_handler: beefy_on_demand_justifications_handler,
 		is_authority: role.is_authority(),
+		offchain_tx_pool_factory: Some(OffchainTransactionPoolFactory::new(
+			transaction_pool.clone(),
+		)),
+		bridged_finality_proofs: None,
 	};
 
 	let beefy_gadget = beefy::start_beefy_gadget::<_, _, _, _, _, _, _, _>(beefy_params);
@@ -816,28 +848,46 @@ pub fn new_full(config: Configuration, cli: Cli) -> Result<TaskManager, ServiceE
 	let mixnet_config = cli.mixnet_params.config(config.role.is_authority());
 	let database_path = config.database.path().map(Path::to_path_buf);
 
-	let task_manager = match config.network.network_backend {
-		sc_network::config::NetworkBackendType::Libp2p => {
-			let task_manager = new_full_base::<sc_network::NetworkWorker<_, _>>(
-				config,
-				mixnet_config,
-				cli.no_hardware_benchmarks,
-				|_, _| (),
-			)
-			.map(|NewFullBase { task_manager, .. }| task_manager)?;
-			task_manager
-		},
-		sc_network::config::NetworkBackendType::Litep2p => {
-			let task_manager = new_full_base::<sc_network::Litep2pNetworkBackend>(
-				config,
-				mixnet_config,
-				cli.no_hardware_benchmarks,
-				|_, _| (),
-			)
-			.map(|NewFullBase { task_manager, .. }| task_manager)?;
-			task_manager
-		},
-	};
+	let NewFullBase { task_manager, client, transaction_pool, .. } =
+		match config.network.network_backend {
+			sc_network::config::NetworkBackendType::Libp2p =>
+				new_full_base::<sc_network::NetworkWorker<_, _>>(
+					config,
+					mixnet_config,
+					cli.no_hardware_benchmarks,
+					|_, _| (),
+				)?,
+			sc_network::config::NetworkBackendType::Litep2p =>
+				new_full_base::<sc_network::Litep2pNetworkBackend>(
+					config,
+					mixnet_config,
+					cli.no_hardware_benchmarks,
+					|_, _| (),
+				)?,
+		};
+
+	if cli.offchain_tasks {
+		let task_runner = sc_offchain::TaskRunner::<_, _, _, kitchensink_runtime::RuntimeTask>::new(
+			sc_offchain::TaskRunnerOptions {
+				runtime_api_provider: client.clone(),
+				transaction_pool,
+				max_tasks_per_block: sc_offchain::tasks::DEFAULT_MAX_TASKS_PER_BLOCK,
+			},
+		);
+		match task_runner.supports_tasks(client.chain_info().best_hash) {
+			Ok(true) => {},
+			Ok(false) => log::warn!(
+				"⚠️  --offchain-tasks is set, but the runtime doesn't support tasks. \
+				 Tasks will only be submitted once a runtime upgrade enables them."
+			),
+			Err(err) => log::warn!("⚠️  Failed to check whether the runtime supports tasks: {err}"),
+		}
+		task_manager.spawn_handle().spawn(
+			"offchain-tasks-runner",
+			"offchain-work",
+			task_runner.run(client, task_manager.spawn_handle()).boxed(),
+		);
+	}
 
 	if let Some(database_path) = database_path {
 		sc_storage_monitor::StorageMonitorService::try_spawn(
@@ -0,0 +1,114 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use codec::{Decode, Encode};
+use kitchensink_runtime::{Runtime, RuntimeTask, UncheckedExtrinsic};
+use pallet_example_tasks::{Numbers, Task};
+use polkadot_sdk::*;
+use sp_runtime::traits::BlakeTwo256;
+
+pub mod common;
+use self::common::*;
+
+fn add_number_task(i: u32) -> RuntimeTask {
+	RuntimeTask::TasksExample(Task::<Runtime>::AddNumberIntoTotal { i })
+}
+
+fn valid_tasks(t: &mut TestExternalities<BlakeTwo256>, limit: u32) -> Vec<RuntimeTask> {
+	let (result, _) = executor_call(t, "TasksApi_valid_tasks", &limit.encode());
+	Decode::decode(&mut &result.unwrap()[..]).unwrap()
+}
+
+fn task_extrinsic(
+	t: &mut TestExternalities<BlakeTwo256>,
+	task: RuntimeTask,
+) -> Option<UncheckedExtrinsic> {
+	let (result, _) = executor_call(t, "TasksApi_task_extrinsic", &task.encode());
+	Decode::decode(&mut &result.unwrap()[..]).unwrap()
+}
+
+#[test]
+fn tasks_api_enumerates_valid_tasks() {
+	let mut t = new_test_ext(compact_code_unwrap());
+	assert!(valid_tasks(&mut t, 16).is_empty());
+
+	t.execute_with(|| {
+		Numbers::<Runtime>::insert(1, 10);
+		Numbers::<Runtime>::insert(2, 20);
+	});
+
+	let mut tasks = valid_tasks(&mut t, 16);
+	tasks.sort_by_key(|task| task.encode());
+	let mut expected = vec![add_number_task(1), add_number_task(2)];
+	expected.sort_by_key(|task| task.encode());
+	assert_eq!(tasks, expected);
+
+	// The limit is respected.
+	assert_eq!(valid_tasks(&mut t, 1).len(), 1);
+}
+
+#[cfg(not(feature = "experimental"))]
+#[test]
+fn tasks_api_does_not_build_extrinsics_without_do_task() {
+	let mut t = new_test_ext(compact_code_unwrap());
+	t.execute_with(|| Numbers::<Runtime>::insert(1, 10));
+
+	assert!(task_extrinsic(&mut t, add_number_task(1)).is_none());
+}
+
+#[cfg(feature = "experimental")]
+#[test]
+fn task_extrinsics_are_deduplicated_by_validate_unsigned() {
+	use frame_support::pallet_prelude::{TransactionSource, ValidateUnsigned};
+	use kitchensink_runtime::{RuntimeCall, System};
+
+	let mut t = new_test_ext(compact_code_unwrap());
+	t.execute_with(|| {
+		Numbers::<Runtime>::insert(1, 10);
+		Numbers::<Runtime>::insert(2, 20);
+	});
+
+	let xt = task_extrinsic(&mut t, add_number_task(1)).unwrap();
+	assert!(xt.0.is_inherent());
+	let call = match xt.0.function {
+		RuntimeCall::System(call) => call,
+		call => panic!("unexpected call {call:?}"),
+	};
+	assert_eq!(call, frame_system::Call::do_task { task: add_number_task(1) });
+
+	t.execute_with(|| {
+		let validate = |task| {
+			System::validate_unsigned(
+				TransactionSource::Local,
+				&frame_system::Call::do_task { task },
+			)
+		};
+
+		// The same task always provides the same tag, so the pool keeps a single copy.
+		let first = validate(add_number_task(1)).unwrap();
+		let second = validate(add_number_task(1)).unwrap();
+		assert_eq!(first.provides, second.provides);
+
+		// Different tasks don't conflict.
+		let other = validate(add_number_task(2)).unwrap();
+		assert_ne!(first.provides, other.provides);
+
+		// Once the work is done the task is no longer accepted.
+		Numbers::<Runtime>::remove(1);
+		assert!(validate(add_number_task(1)).is_err());
+	});
+}
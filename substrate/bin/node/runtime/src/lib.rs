@@ -2925,6 +2925,15 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl frame_support::view_functions::runtime_api::RuntimeViewFunction<Block> for Runtime {
+		fn execute_view_function(
+			id: frame_support::view_functions::ViewFunctionId,
+			input: Vec<u8>,
+		) -> Result<Vec<u8>, frame_support::view_functions::ViewFunctionDispatchError> {
+			Runtime::execute_view_function(id, input)
+		}
+	}
+
 	impl sp_block_builder::BlockBuilder<Block> for Runtime {
 		fn apply_extrinsic(extrinsic: <Block as BlockT>::Extrinsic) -> ApplyExtrinsicResult {
 			Executive::apply_extrinsic(extrinsic)
@@ -2968,6 +2977,22 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl sp_offchain::TasksApi<Block, RuntimeTask> for Runtime {
+		fn valid_tasks(limit: u32) -> Vec<RuntimeTask> {
+			System::valid_tasks(limit)
+		}
+
+		fn task_extrinsic(task: RuntimeTask) -> Option<<Block as BlockT>::Extrinsic> {
+			use frame_system::offchain::CreateInherent;
+
+			System::task_call(task).map(|call| {
+				<Runtime as CreateInherent<frame_system::Call<Runtime>>>::create_inherent(
+					call.into(),
+				)
+			})
+		}
+	}
+
 	impl sp_consensus_grandpa::GrandpaApi<Block> for Runtime {
 		fn grandpa_authorities() -> sp_consensus_grandpa::AuthorityList {
 			Grandpa::grandpa_authorities()
@@ -3207,6 +3232,7 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[api_version(3)]
 	impl pallet_revive::ReviveApi<Block, AccountId, Balance, Nonce, BlockNumber, EventRecord> for Runtime
 	{
 		fn balance(address: H160) -> U256 {
@@ -3300,6 +3326,39 @@ impl_runtime_apis! {
 				key
 			)
 		}
+
+		fn trace_block(
+			block: Block,
+			config: pallet_revive::evm::TracerType,
+		) -> Vec<(u32, pallet_revive::evm::Trace)> {
+			Revive::trace_block(
+				block,
+				config,
+				Executive::initialize_block,
+				Executive::apply_extrinsic,
+			)
+		}
+
+		fn trace_tx(
+			block: Block,
+			tx_index: u32,
+			config: pallet_revive::evm::TracerType,
+		) -> Option<pallet_revive::evm::Trace> {
+			Revive::trace_tx(
+				block,
+				tx_index,
+				config,
+				Executive::initialize_block,
+				Executive::apply_extrinsic,
+			)
+		}
+
+		fn trace_call(
+			tx: pallet_revive::evm::GenericTransaction,
+			config: pallet_revive::evm::TracerType,
+		) -> Result<pallet_revive::evm::Trace, pallet_revive::EthTransactError> {
+			Revive::trace_call(tx, config, Self::eth_transact)
+		}
 	}
 
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<
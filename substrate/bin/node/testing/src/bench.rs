@@ -389,6 +389,8 @@ impl BenchDb {
 			state_pruning: Some(PruningMode::ArchiveAll),
 			source: database_type.into_settings(dir.into()),
 			blocks_pruning: sc_client_db::BlocksPruning::KeepAll,
+			extrinsic_index: false,
+			extrinsic_signer: None,
 		};
 		let task_executor = TaskExecutor::new();
 
@@ -429,8 +431,15 @@ impl BenchDb {
 	///
 	/// Uses already instantiated Client.
 	pub fn generate_inherents(&mut self, client: &Client) -> Vec<OpaqueExtrinsic> {
+		Self::inherents_at(client, client.chain_info().genesis_hash, 1 * MinimumPeriod::get())
+	}
+
+	fn inherents_at(
+		client: &Client,
+		parent_hash: node_primitives::Hash,
+		timestamp: u64,
+	) -> Vec<OpaqueExtrinsic> {
 		let mut inherent_data = InherentData::new();
-		let timestamp = 1 * MinimumPeriod::get();
 
 		inherent_data
 			.put_data(sp_timestamp::INHERENT_IDENTIFIER, &timestamp)
@@ -438,7 +447,7 @@ impl BenchDb {
 
 		client
 			.runtime_api()
-			.inherent_extrinsics(client.chain_info().genesis_hash, inherent_data)
+			.inherent_extrinsics(parent_hash, inherent_data)
 			.expect("Get inherents failed")
 	}
 
@@ -492,6 +501,49 @@ impl BenchDb {
 		block
 	}
 
+	/// Generate a chain of `length` blocks on top of the best block of this database.
+	///
+	/// The transactions of each block are signed by different accounts, so the content must have
+	/// a fixed size and the keyring must hold enough accounts for the whole chain.
+	pub fn generate_chain(&mut self, content: BlockContent, length: usize) -> Vec<Block> {
+		let size = content.size.expect("The blocks of a chain must have a fixed size");
+		// The blocks are imported into a copy of the database, to build the next ones on top.
+		let context = self.create_context();
+		let client = &*context.client;
+
+		(0..length)
+			.map(|n| {
+				let chain = client.usage_info().chain;
+				let mut block = BlockBuilderBuilder::new(client)
+					.on_parent_block(chain.best_hash)
+					.with_parent_block_number(chain.best_number)
+					.build()
+					.expect("Failed to create block builder.");
+
+				let timestamp = (n as u64 + 1) * MinimumPeriod::get();
+				for extrinsic in Self::inherents_at(client, chain.best_hash, timestamp) {
+					block.push(extrinsic).expect("Push inherent failed");
+				}
+
+				let content = BlockContent { size: Some((n + 1) * size), ..content.clone() };
+				for opaque in self.block_content(content, client).skip(n * size) {
+					block.push(opaque).expect("Push transaction failed");
+				}
+
+				let block = block.build().expect("Block build failed").block;
+
+				let mut import_params =
+					BlockImportParams::new(BlockOrigin::File, block.header.clone());
+				import_params.body = Some(block.extrinsics().to_vec());
+				import_params.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+				executor::block_on(context.client.import_block(import_params))
+					.expect("Failed to import block");
+
+				block
+			})
+			.collect()
+	}
+
 	/// Database path.
 	pub fn path(&self) -> &Path {
 		self.directory_guard.path()
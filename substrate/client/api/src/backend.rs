@@ -35,7 +35,7 @@ use sp_state_machine::{
 use sp_storage::{ChildInfo, StorageData, StorageKey};
 pub use sp_trie::MerkleValue;
 
-use crate::{blockchain::Backend as BlockchainBackend, UsageInfo};
+use crate::{blockchain::Backend as BlockchainBackend, light::RemoteStorageIter, UsageInfo};
 
 pub use sp_state_machine::{Backend as StateBackend, BackendTransaction, KeyValueStates};
 
@@ -311,33 +311,60 @@ pub trait AuxStore {
 	fn get_aux(&self, key: &[u8]) -> sp_blockchain::Result<Option<Vec<u8>>>;
 }
 
-/// An `Iterator` that iterates keys in a given block under a prefix.
-pub struct KeysIter<State, Block>
+/// The source of the entries of a [`KeysIter`] or a [`PairsIter`].
+enum StorageIter<State, Block>
 where
 	State: StateBackend<HashingFor<Block>>,
 	Block: BlockT,
 {
-	inner: <State as StateBackend<HashingFor<Block>>>::RawIter,
-	state: State,
+	/// The state of the block is available locally.
+	Local { inner: <State as StateBackend<HashingFor<Block>>>::RawIter, state: State },
+	/// The state of the block is fetched from remote peers.
+	Remote(RemoteStorageIter<Block>),
 }
 
-impl<State, Block> KeysIter<State, Block>
+impl<State, Block> StorageIter<State, Block>
 where
 	State: StateBackend<HashingFor<Block>>,
 	Block: BlockT,
 {
-	/// Create a new iterator over storage keys.
-	pub fn new(
+	fn new(
 		state: State,
+		child_info: Option<ChildInfo>,
 		prefix: Option<&StorageKey>,
 		start_at: Option<&StorageKey>,
 	) -> Result<Self, State::Error> {
 		let mut args = IterArgs::default();
 		args.prefix = prefix.as_ref().map(|prefix| prefix.0.as_slice());
 		args.start_at = start_at.as_ref().map(|start_at| start_at.0.as_slice());
+		args.child_info = child_info;
 		args.start_at_exclusive = true;
 
-		Ok(Self { inner: state.raw_iter(args)?, state })
+		Ok(Self::Local { inner: state.raw_iter(args)?, state })
+	}
+}
+
+/// An `Iterator` that iterates keys in a given block under a prefix.
+pub struct KeysIter<State, Block>
+where
+	State: StateBackend<HashingFor<Block>>,
+	Block: BlockT,
+{
+	inner: StorageIter<State, Block>,
+}
+
+impl<State, Block> KeysIter<State, Block>
+where
+	State: StateBackend<HashingFor<Block>>,
+	Block: BlockT,
+{
+	/// Create a new iterator over storage keys.
+	pub fn new(
+		state: State,
+		prefix: Option<&StorageKey>,
+		start_at: Option<&StorageKey>,
+	) -> Result<Self, State::Error> {
+		Ok(Self { inner: StorageIter::new(state, None, prefix, start_at)? })
 	}
 
 	/// Create a new iterator over a child storage's keys.
@@ -347,13 +374,12 @@ where
 		prefix: Option<&StorageKey>,
 		start_at: Option<&StorageKey>,
 	) -> Result<Self, State::Error> {
-		let mut args = IterArgs::default();
-		args.prefix = prefix.as_ref().map(|prefix| prefix.0.as_slice());
-		args.start_at = start_at.as_ref().map(|start_at| start_at.0.as_slice());
-		args.child_info = Some(child_info);
-		args.start_at_exclusive = true;
+		Ok(Self { inner: StorageIter::new(state, Some(child_info), prefix, start_at)? })
+	}
 
-		Ok(Self { inner: state.raw_iter(args)?, state })
+	/// Create a new iterator over storage keys fetched from remote peers.
+	pub fn new_remote(iter: RemoteStorageIter<Block>) -> Self {
+		Self { inner: StorageIter::Remote(iter) }
 	}
 }
 
@@ -365,7 +391,10 @@ where
 	type Item = StorageKey;
 
 	fn next(&mut self) -> Option<Self::Item> {
-		self.inner.next_key(&self.state)?.ok().map(StorageKey)
+		match &mut self.inner {
+			StorageIter::Local { inner, state } => inner.next_key(state)?.ok().map(StorageKey),
+			StorageIter::Remote(iter) => iter.next().map(|(key, _)| StorageKey(key)),
+		}
 	}
 }
 
@@ -375,8 +404,7 @@ where
 	State: StateBackend<HashingFor<Block>>,
 	Block: BlockT,
 {
-	inner: <State as StateBackend<HashingFor<Block>>>::RawIter,
-	state: State,
+	inner: StorageIter<State, Block>,
 }
 
 impl<State, Block> Iterator for PairsIter<State, Block>
@@ -387,10 +415,11 @@ where
 	type Item = (StorageKey, StorageData);
 
 	fn next(&mut self) -> Option<Self::Item> {
-		self.inner
-			.next_pair(&self.state)?
-			.ok()
-			.map(|(key, value)| (StorageKey(key), StorageData(value)))
+		let (key, value) = match &mut self.inner {
+			StorageIter::Local { inner, state } => inner.next_pair(state)?.ok()?,
+			StorageIter::Remote(iter) => iter.next()?,
+		};
+		Some((StorageKey(key), StorageData(value)))
 	}
 }
 
@@ -405,12 +434,12 @@ where
 		prefix: Option<&StorageKey>,
 		start_at: Option<&StorageKey>,
 	) -> Result<Self, State::Error> {
-		let mut args = IterArgs::default();
-		args.prefix = prefix.as_ref().map(|prefix| prefix.0.as_slice());
-		args.start_at = start_at.as_ref().map(|start_at| start_at.0.as_slice());
-		args.start_at_exclusive = true;
+		Ok(Self { inner: StorageIter::new(state, None, prefix, start_at)? })
+	}
 
-		Ok(Self { inner: state.raw_iter(args)?, state })
+	/// Create a new iterator over storage key and value pairs fetched from remote peers.
+	pub fn new_remote(iter: RemoteStorageIter<Block>) -> Self {
+		Self { inner: StorageIter::Remote(iter) }
 	}
 }
 
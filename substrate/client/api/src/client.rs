@@ -160,6 +160,31 @@ pub trait BlockBackend<Block: BlockT> {
 		Ok(self.indexed_transaction(hash)?.is_some())
 	}
 
+	/// Get the blocks that include the extrinsic with the given hash, as `(block hash, extrinsic
+	/// index)` pairs.
+	///
+	/// Returns `None` if extrinsics are not indexed.
+	fn extrinsic_locations(
+		&self,
+		_hash: Block::Hash,
+	) -> sp_blockchain::Result<Option<Vec<(Block::Hash, u32)>>> {
+		Ok(None)
+	}
+
+	/// Get up to `limit` of the blocks that include extrinsics signed by the given encoded signer,
+	/// as `(block hash, extrinsic index)` pairs, starting after the opaque `start_key`.
+	///
+	/// Also returns the key to continue from if more pairs follow. Returns `None` if extrinsics
+	/// are not indexed by signer.
+	fn signer_extrinsic_locations(
+		&self,
+		_signer: &[u8],
+		_start_key: Option<&[u8]>,
+		_limit: usize,
+	) -> sp_blockchain::Result<Option<(Vec<(Block::Hash, u32)>, Option<Vec<u8>>)>> {
+		Ok(None)
+	}
+
 	/// Tells whether the current client configuration requires full-sync mode.
 	fn requires_full_sync(&self) -> bool;
 }
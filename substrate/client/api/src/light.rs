@@ -0,0 +1,435 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Light client utilities.
+//!
+//! A node running in light client mode only keeps the headers and justifications of the chain.
+//! The state of a block is fetched from remote peers on demand, as storage and execution proofs
+//! that are checked against the state root of the locally stored header.
+
+use crate::StorageProof;
+use futures::{future::BoxFuture, FutureExt, StreamExt};
+use parking_lot::{Mutex, RwLock};
+use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedSender};
+use schnellru::{ByLength, LruMap};
+use sp_runtime::traits::{Block as BlockT, HashingFor, Header as HeaderT};
+use sp_state_machine::{
+	create_proof_check_backend, read_range_proof_check_on_proving_backend, MemoryDB,
+	OverlayedChanges, TrieBackend,
+};
+use sp_storage::{well_known_keys, ChildInfo, PrefixedStorageKey};
+use std::{collections::VecDeque, fmt, sync::Arc};
+
+const LOG_TARGET: &str = "light-client";
+
+/// Number of blocks for which the proof of the runtime code is kept.
+const CODE_PROOF_CACHE_SIZE: u32 = 32;
+
+/// State backend built from a checked remote proof.
+pub type ProofCheckBackend<Block> = TrieBackend<MemoryDB<HashingFor<Block>>, HashingFor<Block>>;
+
+/// Fetches proofs of the state of a block from remote peers.
+pub trait RemoteProofFetcher<Block: BlockT>: Send + Sync {
+	/// Fetch a proof of the values of `keys` in the main trie at `block`.
+	fn read_proof(
+		&self,
+		block: Block::Hash,
+		keys: Vec<Vec<u8>>,
+	) -> BoxFuture<'static, sp_blockchain::Result<StorageProof>>;
+
+	/// Fetch a proof of the values of `keys` in the child trie `storage_key` at `block`.
+	fn read_child_proof(
+		&self,
+		block: Block::Hash,
+		storage_key: PrefixedStorageKey,
+		keys: Vec<Vec<u8>>,
+	) -> BoxFuture<'static, sp_blockchain::Result<StorageProof>>;
+
+	/// Fetch a proof of the keys and values following `start_at` (inclusive) under `prefix` in
+	/// the main trie, or in the child trie `storage_key`, at `block`.
+	///
+	/// The remote decides how many entries it includes, but the proof always covers at least the
+	/// first entry following `start_at`.
+	fn read_range_proof(
+		&self,
+		block: Block::Hash,
+		storage_key: Option<PrefixedStorageKey>,
+		prefix: Option<Vec<u8>>,
+		start_at: Option<Vec<u8>>,
+	) -> BoxFuture<'static, sp_blockchain::Result<StorageProof>>;
+
+	/// Fetch a proof of the execution of `method` with `call_data` at `block`.
+	///
+	/// The proof doesn't contain the runtime code.
+	fn execution_proof(
+		&self,
+		block: Block::Hash,
+		method: String,
+		call_data: Vec<u8>,
+	) -> BoxFuture<'static, sp_blockchain::Result<StorageProof>>;
+}
+
+/// Provides the [`RemoteProofs`] of a client.
+pub trait RemoteProofsProvider<Block: BlockT> {
+	/// Returns the handle used to fetch the state of the blocks from remote peers, if the client
+	/// runs in light client mode.
+	fn remote_proofs(&self) -> Option<&RemoteProofs<Block>>;
+}
+
+/// The registered fetcher, and the queue of the thread waiting for its requests.
+struct Fetcher<Block: BlockT> {
+	fetcher: Arc<dyn RemoteProofFetcher<Block>>,
+	requests: TracingUnboundedSender<BoxFuture<'static, ()>>,
+}
+
+impl<Block: BlockT> Clone for Fetcher<Block> {
+	fn clone(&self) -> Self {
+		Self { fetcher: self.fetcher.clone(), requests: self.requests.clone() }
+	}
+}
+
+/// Shared handle to the [`RemoteProofFetcher`] of a node running in light client mode.
+///
+/// The client is created before the network, so the fetcher is registered once the network is
+/// built. Until then, all the remote requests fail.
+///
+/// The state is read through the synchronous client methods, so the requests are driven to
+/// completion on a dedicated thread while the caller waits for them. Within a multi-threaded
+/// tokio runtime, the worker thread of the caller is handed over to the other tasks while it
+/// waits.
+pub struct RemoteProofs<Block: BlockT> {
+	fetcher: Arc<RwLock<Option<Fetcher<Block>>>>,
+	/// Proofs of the runtime code of the last blocks it was fetched for.
+	///
+	/// A runtime API call reads the runtime version before executing the call, so both need the
+	/// runtime code of the same block.
+	code_proofs: Arc<Mutex<LruMap<Block::Hash, StorageProof>>>,
+}
+
+impl<Block: BlockT> RemoteProofs<Block> {
+	/// Create a new handle without a fetcher.
+	pub fn new() -> Self {
+		Self {
+			fetcher: Arc::new(RwLock::new(None)),
+			code_proofs: Arc::new(Mutex::new(LruMap::new(ByLength::new(CODE_PROOF_CACHE_SIZE)))),
+		}
+	}
+
+	/// Register the fetcher used to send the remote requests.
+	///
+	/// Spawns the thread driving the requests, which stops once all the clones of this handle
+	/// are dropped.
+	pub fn set_fetcher(&self, fetcher: Arc<dyn RemoteProofFetcher<Block>>) {
+		let (requests, pending) = tracing_unbounded("mpsc_light_client_requests", 100_000);
+		let spawned =
+			std::thread::Builder::new().name("light-client-requests".into()).spawn(move || {
+				futures::executor::block_on(pending.for_each_concurrent(None, |request| request))
+			});
+		if let Err(error) = spawned {
+			log::error!(target: LOG_TARGET, "Failed to spawn the light client requests thread: {error}");
+			return
+		}
+
+		*self.fetcher.write() = Some(Fetcher { fetcher, requests });
+	}
+
+	/// Fetch and check a proof of the values of `keys` at the block of `header`.
+	///
+	/// Blocks the current thread until the proof is received.
+	pub fn read(
+		&self,
+		header: &Block::Header,
+		keys: Vec<Vec<u8>>,
+	) -> sp_blockchain::Result<ProofCheckBackend<Block>> {
+		let Fetcher { fetcher, requests } = self.fetcher()?;
+		let proof = wait(&requests, fetcher.read_proof(header.hash(), keys))?;
+		check_proof::<Block>(header, proof)
+	}
+
+	/// Fetch and check a proof of the values of `keys` in the child trie `child_info` at the
+	/// block of `header`.
+	///
+	/// Blocks the current thread until the proof is received.
+	pub fn read_child(
+		&self,
+		header: &Block::Header,
+		child_info: &ChildInfo,
+		keys: Vec<Vec<u8>>,
+	) -> sp_blockchain::Result<ProofCheckBackend<Block>> {
+		let Fetcher { fetcher, requests } = self.fetcher()?;
+		let proof = wait(
+			&requests,
+			fetcher.read_child_proof(header.hash(), child_info.prefixed_storage_key(), keys),
+		)?;
+		check_proof::<Block>(header, proof)
+	}
+
+	/// Fetch and check the keys and values following `start_at` (inclusive) under `prefix` in the
+	/// main trie, or in the child trie `child_info`, at the block of `header`.
+	///
+	/// Returns the entries covered by the proof, and whether they are the last ones.
+	///
+	/// Blocks the current thread until the proof is received.
+	pub fn read_range(
+		&self,
+		header: &Block::Header,
+		child_info: Option<&ChildInfo>,
+		prefix: Option<&[u8]>,
+		start_at: Option<&[u8]>,
+	) -> sp_blockchain::Result<(Vec<(Vec<u8>, Vec<u8>)>, bool)> {
+		let Fetcher { fetcher, requests } = self.fetcher()?;
+		let proof = wait(
+			&requests,
+			fetcher.read_range_proof(
+				header.hash(),
+				child_info.map(ChildInfo::prefixed_storage_key),
+				prefix.map(<[u8]>::to_vec),
+				start_at.map(<[u8]>::to_vec),
+			),
+		)?;
+		let state = check_proof::<Block>(header, proof)?;
+		read_range_proof_check_on_proving_backend(&state, child_info, prefix, None, start_at)
+			.map_err(sp_blockchain::Error::from_state)
+	}
+
+	/// Iterate over the keys and values following `start_at` (exclusive) under `prefix` in the
+	/// main trie, or in the child trie `child_info`, at the block of `header`.
+	///
+	/// The entries are fetched lazily, a range proof at a time.
+	pub fn iter(
+		&self,
+		header: Block::Header,
+		child_info: Option<ChildInfo>,
+		prefix: Option<Vec<u8>>,
+		start_at: Option<Vec<u8>>,
+	) -> RemoteStorageIter<Block> {
+		RemoteStorageIter {
+			remote_proofs: self.clone(),
+			header,
+			child_info,
+			prefix,
+			start_at,
+			pending: VecDeque::new(),
+			complete: false,
+		}
+	}
+
+	/// Fetch and check the proofs required to execute `method` with `call_data` at the block of
+	/// `header`, including the runtime code.
+	///
+	/// The execution proof is generated by the remote without `changes`, so the proofs of all
+	/// the keys in `changes` are fetched along with it. This lets the call read and merkelize
+	/// the state modified by the previous calls.
+	///
+	/// Blocks the current thread until the proofs are received.
+	pub fn call(
+		&self,
+		header: &Block::Header,
+		method: &str,
+		call_data: &[u8],
+		changes: Option<&OverlayedChanges<HashingFor<Block>>>,
+	) -> sp_blockchain::Result<ProofCheckBackend<Block>> {
+		let Fetcher { fetcher, requests } = self.fetcher()?;
+		let hash = header.hash();
+
+		let mut proofs = vec![fetcher.execution_proof(hash, method.into(), call_data.to_vec())];
+		let code_proof = self.cached_code_proof(hash);
+		if code_proof.is_none() {
+			proofs.push(self.fetch_code_proof(&*fetcher, hash));
+		}
+		if let Some(changes) = changes {
+			let keys = changes.changes().map(|(key, _)| key.clone()).collect::<Vec<_>>();
+			if !keys.is_empty() {
+				proofs.push(fetcher.read_proof(hash, keys));
+			}
+			for (child_changes, child_info) in changes.children() {
+				let keys = child_changes.map(|(key, _)| key.clone()).collect::<Vec<_>>();
+				if !keys.is_empty() {
+					proofs.push(fetcher.read_child_proof(
+						hash,
+						child_info.prefixed_storage_key(),
+						keys,
+					));
+				}
+			}
+		}
+
+		let mut proofs = wait(&requests, futures::future::try_join_all(proofs).boxed())?;
+		let code_proof = match code_proof {
+			Some(code_proof) => code_proof,
+			None => {
+				let code_proof = proofs.remove(1);
+				self.code_proofs.lock().insert(hash, code_proof.clone());
+				code_proof
+			},
+		};
+		check_proof::<Block>(header, StorageProof::merge(proofs.into_iter().chain([code_proof])))
+	}
+
+	/// Fetch and check a proof of the runtime code at the block of `header`.
+	///
+	/// Blocks the current thread until the proof is received.
+	pub fn runtime_code(
+		&self,
+		header: &Block::Header,
+	) -> sp_blockchain::Result<ProofCheckBackend<Block>> {
+		let hash = header.hash();
+		if let Some(proof) = self.cached_code_proof(hash) {
+			return check_proof::<Block>(header, proof)
+		}
+
+		let Fetcher { fetcher, requests } = self.fetcher()?;
+		let proof = wait(&requests, self.fetch_code_proof(&*fetcher, hash))?;
+		let state = check_proof::<Block>(header, proof.clone())?;
+		self.code_proofs.lock().insert(hash, proof);
+		Ok(state)
+	}
+
+	fn fetcher(&self) -> sp_blockchain::Result<Fetcher<Block>> {
+		self.fetcher.read().clone().ok_or(sp_blockchain::Error::RemoteFetchFailed)
+	}
+
+	fn fetch_code_proof(
+		&self,
+		fetcher: &dyn RemoteProofFetcher<Block>,
+		block: Block::Hash,
+	) -> BoxFuture<'static, sp_blockchain::Result<StorageProof>> {
+		let keys = vec![well_known_keys::CODE.to_vec(), well_known_keys::HEAP_PAGES.to_vec()];
+		fetcher.read_proof(block, keys)
+	}
+
+	fn cached_code_proof(&self, block: Block::Hash) -> Option<StorageProof> {
+		self.code_proofs.lock().get(&block).cloned()
+	}
+}
+
+impl<Block: BlockT> Default for RemoteProofs<Block> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<Block: BlockT> Clone for RemoteProofs<Block> {
+	fn clone(&self) -> Self {
+		Self { fetcher: self.fetcher.clone(), code_proofs: self.code_proofs.clone() }
+	}
+}
+
+impl<Block: BlockT> fmt::Debug for RemoteProofs<Block> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("RemoteProofs")
+			.field("has_fetcher", &self.fetcher.read().is_some())
+			.finish()
+	}
+}
+
+/// Iterator over the keys and values of the state of a block fetched from remote peers.
+///
+/// Created by [`RemoteProofs::iter`]. The iteration stops at the first failed request.
+pub struct RemoteStorageIter<Block: BlockT> {
+	remote_proofs: RemoteProofs<Block>,
+	header: Block::Header,
+	child_info: Option<ChildInfo>,
+	prefix: Option<Vec<u8>>,
+	/// The key the next range starts after.
+	start_at: Option<Vec<u8>>,
+	/// The entries of the last range that weren't returned yet.
+	pending: VecDeque<(Vec<u8>, Vec<u8>)>,
+	/// Whether the last range reached the end of the entries.
+	complete: bool,
+}
+
+impl<Block: BlockT> RemoteStorageIter<Block> {
+	fn fetch_next_range(&mut self) -> sp_blockchain::Result<()> {
+		let (entries, complete) = self.remote_proofs.read_range(
+			&self.header,
+			self.child_info.as_ref(),
+			self.prefix.as_deref(),
+			self.start_at.as_deref(),
+		)?;
+
+		let mut entries = VecDeque::from(entries);
+		// The range starts at `start_at` inclusively, which was already returned.
+		if self.start_at.is_some() && entries.front().map(|(key, _)| key) == self.start_at.as_ref()
+		{
+			entries.pop_front();
+		}
+		if entries.is_empty() && !complete {
+			return Err(sp_blockchain::Error::Backend("Remote range proof without entries.".into()))
+		}
+
+		if let Some((key, _)) = entries.back() {
+			self.start_at = Some(key.clone());
+		}
+		self.pending = entries;
+		self.complete = complete;
+		Ok(())
+	}
+}
+
+impl<Block: BlockT> Iterator for RemoteStorageIter<Block> {
+	type Item = (Vec<u8>, Vec<u8>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			if let Some(entry) = self.pending.pop_front() {
+				return Some(entry)
+			}
+			if self.complete {
+				return None
+			}
+
+			if let Err(error) = self.fetch_next_range() {
+				log::debug!(target: LOG_TARGET, "Failed to fetch remote storage range: {error}");
+				self.complete = true;
+			}
+		}
+	}
+}
+
+/// Send `request` to the requests thread and wait for its result.
+fn wait<T: Send + 'static>(
+	requests: &TracingUnboundedSender<BoxFuture<'static, ()>>,
+	request: BoxFuture<'static, sp_blockchain::Result<T>>,
+) -> sp_blockchain::Result<T> {
+	let (result_tx, result_rx) = std::sync::mpsc::sync_channel(1);
+	requests
+		.unbounded_send(
+			async move {
+				let _ = result_tx.send(request.await);
+			}
+			.boxed(),
+		)
+		.map_err(|_| sp_blockchain::Error::RemoteFetchFailed)?;
+
+	let receive = move || result_rx.recv().map_err(|_| sp_blockchain::Error::RemoteFetchFailed)?;
+	match tokio::runtime::Handle::try_current() {
+		Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread =>
+			tokio::task::block_in_place(receive),
+		_ => receive(),
+	}
+}
+
+/// Check `proof` against the state root of `header`.
+fn check_proof<Block: BlockT>(
+	header: &Block::Header,
+	proof: StorageProof,
+) -> sp_blockchain::Result<ProofCheckBackend<Block>> {
+	create_proof_check_backend::<HashingFor<Block>>(*header.state_root(), proof)
+		.map_err(sp_blockchain::Error::from_state)
+}
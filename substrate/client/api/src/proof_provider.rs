@@ -40,6 +40,24 @@ pub trait ProofProvider<Block: BlockT> {
 		keys: &mut dyn Iterator<Item = &[u8]>,
 	) -> sp_blockchain::Result<StorageProof>;
 
+	/// Reads the storage values following `start_at` (inclusive) under `prefix` in the main trie,
+	/// or in the child trie `child_info`, at a given block, returning read proof.
+	///
+	/// Proof is built until `size_limit` is reached and always includes at least one key
+	/// following `start_at`.
+	///
+	/// Not supported by default.
+	fn read_range_proof(
+		&self,
+		_hash: Block::Hash,
+		_child_info: Option<&ChildInfo>,
+		_prefix: Option<&[u8]>,
+		_start_at: Option<&[u8]>,
+		_size_limit: usize,
+	) -> sp_blockchain::Result<StorageProof> {
+		Err(sp_blockchain::Error::UnsupportedOperation("read_range_proof"))
+	}
+
 	/// Execute a call to a contract on top of state in a block of given hash
 	/// AND returning execution proof.
 	///
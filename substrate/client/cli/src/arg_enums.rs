@@ -54,6 +54,12 @@ pub enum WasmExecutionMethod {
 	Interpreted,
 	/// Uses a compiled runtime.
 	Compiled,
+	/// Uses PolkaVM for PolkaVM runtimes, and a compiled runtime otherwise.
+	///
+	/// Running PolkaVM runtimes additionally requires the `SUBSTRATE_ENABLE_POLKAVM`
+	/// environment variable to be set to `1`.
+	#[clap(name = "polkavm")]
+	PolkaVm,
 }
 
 impl std::fmt::Display for WasmExecutionMethod {
@@ -61,6 +67,7 @@ impl std::fmt::Display for WasmExecutionMethod {
 		match self {
 			Self::Interpreted => write!(f, "Interpreted"),
 			Self::Compiled => write!(f, "Compiled"),
+			Self::PolkaVm => write!(f, "PolkaVm"),
 		}
 	}
 }
@@ -77,6 +84,12 @@ pub fn execution_method_from_cli(
 		);
 	}
 
+	if let WasmExecutionMethod::PolkaVm = execution_method {
+		return sc_service::config::WasmExecutionMethod::PolkaVm {
+			backend: sc_service::config::PolkaVmBackend::Auto,
+		}
+	}
+
 	sc_service::config::WasmExecutionMethod::Compiled {
 		instantiation_strategy: match instantiation_strategy {
 			WasmtimeInstantiationStrategy::PoolingCopyOnWrite =>
@@ -291,6 +304,9 @@ pub enum SyncMode {
 	FastUnsafe,
 	/// Prove finality and download the latest state.
 	Warp,
+	/// Prove finality, then download the headers of new blocks only. The state is fetched from
+	/// peers on demand.
+	Light,
 }
 
 impl Into<sc_network::config::SyncMode> for SyncMode {
@@ -306,6 +322,7 @@ impl Into<sc_network::config::SyncMode> for SyncMode {
 				storage_chain_mode: false,
 			},
 			SyncMode::Warp => sc_network::config::SyncMode::Warp,
+			SyncMode::Light => sc_network::config::SyncMode::Light,
 		}
 	}
 }
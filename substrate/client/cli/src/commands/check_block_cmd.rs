@@ -23,8 +23,14 @@ use crate::{
 };
 use clap::Parser;
 use sc_client_api::{BlockBackend, HeaderBackend};
+use sc_service::config::Profiler;
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
-use std::{fmt::Debug, str::FromStr, sync::Arc};
+use std::{
+	fmt::Debug,
+	path::PathBuf,
+	str::FromStr,
+	sync::{Arc, OnceLock},
+};
 
 /// The `check-block` command used to validate blocks.
 #[derive(Debug, Clone, Parser)]
@@ -38,6 +44,19 @@ pub struct CheckBlockCmd {
 	#[arg(long, value_name = "COUNT")]
 	pub default_heap_pages: Option<u32>,
 
+	/// Profile the runtime execution and write the time spent in every runtime function and
+	/// host function to the given file, as folded stacks.
+	///
+	/// The output can be turned into a flamegraph with tools such as `inferno-flamegraph`. The
+	/// names of the runtime functions are only available if the runtime wasn't stripped of its
+	/// name section.
+	#[arg(long, value_name = "PATH")]
+	pub profile_output: Option<PathBuf>,
+
+	/// The profiler created for `profile_output`, shared with the executor.
+	#[arg(skip)]
+	profiler: OnceLock<Arc<Profiler>>,
+
 	#[allow(missing_docs)]
 	#[clap(flatten)]
 	pub shared_params: SharedParams,
@@ -58,11 +77,22 @@ impl CheckBlockCmd {
 		<<B::Header as HeaderT>::Number as FromStr>::Err: Debug,
 	{
 		let start = std::time::Instant::now();
-		sc_service::chain_ops::check_block(client, import_queue, self.input.parse()?).await?;
+		let result =
+			sc_service::chain_ops::check_block(client, import_queue, self.input.parse()?).await;
+		self.write_profile()?;
+		result?;
 		println!("Completed in {} ms.", start.elapsed().as_millis());
 
 		Ok(())
 	}
+
+	/// Write the runtime execution profile, if it was requested.
+	fn write_profile(&self) -> error::Result<()> {
+		if let Some(profiler) = self.profiler.get() {
+			profiler.write_output()?;
+		}
+		Ok(())
+	}
 }
 
 impl CliConfiguration for CheckBlockCmd {
@@ -73,4 +103,10 @@ impl CliConfiguration for CheckBlockCmd {
 	fn import_params(&self) -> Option<&ImportParams> {
 		Some(&self.import_params)
 	}
+
+	fn execution_profiler(&self) -> error::Result<Option<Arc<Profiler>>> {
+		Ok(self.profile_output.as_ref().map(|path| {
+			self.profiler.get_or_init(|| Arc::new(Profiler::with_output(path))).clone()
+		}))
+	}
 }
@@ -23,14 +23,14 @@ use crate::{
 };
 use clap::Parser;
 use sc_client_api::HeaderBackend;
-use sc_service::chain_ops::import_blocks;
+use sc_service::{chain_ops::import_blocks, config::Profiler};
 use sp_runtime::traits::Block as BlockT;
 use std::{
 	fmt::Debug,
 	fs,
 	io::{self, Read},
 	path::PathBuf,
-	sync::Arc,
+	sync::{Arc, OnceLock},
 };
 
 /// The `import-blocks` command used to import blocks.
@@ -49,6 +49,19 @@ pub struct ImportBlocksCmd {
 	#[arg(long)]
 	pub binary: bool,
 
+	/// Profile the runtime execution and write the time spent in every runtime function and
+	/// host function to the given file, as folded stacks.
+	///
+	/// The output can be turned into a flamegraph with tools such as `inferno-flamegraph`. The
+	/// names of the runtime functions are only available if the runtime wasn't stripped of its
+	/// name section.
+	#[arg(long, value_name = "PATH")]
+	pub profile_output: Option<PathBuf>,
+
+	/// The profiler created for `profile_output`, shared with the executor.
+	#[arg(skip)]
+	profiler: OnceLock<Arc<Profiler>>,
+
 	#[allow(missing_docs)]
 	#[clap(flatten)]
 	pub shared_params: SharedParams,
@@ -71,9 +84,17 @@ impl ImportBlocksCmd {
 			None => Box::new(io::stdin()),
 		};
 
-		import_blocks(client, import_queue, file, false, self.binary)
-			.await
-			.map_err(Into::into)
+		let result = import_blocks(client, import_queue, file, false, self.binary).await;
+		self.write_profile()?;
+		result.map_err(Into::into)
+	}
+
+	/// Write the runtime execution profile, if it was requested.
+	fn write_profile(&self) -> error::Result<()> {
+		if let Some(profiler) = self.profiler.get() {
+			profiler.write_output()?;
+		}
+		Ok(())
 	}
 }
 
@@ -85,4 +106,46 @@ impl CliConfiguration for ImportBlocksCmd {
 	fn import_params(&self) -> Option<&ImportParams> {
 		Some(&self.import_params)
 	}
+
+	fn execution_profiler(&self) -> error::Result<Option<Arc<Profiler>>> {
+		Ok(self.profile_output.as_ref().map(|path| {
+			self.profiler.get_or_init(|| Arc::new(Profiler::with_output(path))).clone()
+		}))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn profile_output_is_written_by_the_command() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("profile.folded");
+		let cmd = ImportBlocksCmd::try_parse_from([
+			"import-blocks",
+			"--profile-output",
+			path.to_str().unwrap(),
+		])
+		.unwrap();
+
+		// The executor is given the profiler that is written once the command finishes.
+		let profiler = cmd.execution_profiler().unwrap().expect("the output is set");
+		assert!(Arc::ptr_eq(&profiler, &cmd.execution_profiler().unwrap().unwrap()));
+
+		profiler.record(["execute_block", "ext_hashing_blake2_256_version_1"], 42);
+		profiler.record(["execute_block"], 100);
+		cmd.write_profile().unwrap();
+
+		assert_eq!(
+			fs::read_to_string(&path).unwrap(),
+			"execute_block 100\nexecute_block;ext_hashing_blake2_256_version_1 42\n",
+		);
+	}
+
+	#[test]
+	fn profiling_is_disabled_by_default() {
+		let cmd = ImportBlocksCmd::try_parse_from(["import-blocks"]).unwrap();
+		assert!(cmd.execution_profiler().unwrap().is_none());
+	}
 }
@@ -33,7 +33,7 @@ use sc_service::{
 	ChainSpec, Role,
 };
 use sc_telemetry::TelemetryEndpoints;
-use std::num::NonZeroU32;
+use std::{num::NonZeroU32, path::PathBuf};
 
 /// The `run` command used to run a node.
 #[derive(Debug, Clone, Parser)]
@@ -151,6 +151,16 @@ pub struct RunCmd {
 	#[arg(long)]
 	pub force_authoring: bool,
 
+	/// Index the extrinsics of imported blocks by hash.
+	///
+	/// The index allows looking up the blocks that include an extrinsic, for example with the
+	/// `archive_unstable_transactionByHash` RPC method. Nodes that know how to extract the signer
+	/// of an extrinsic also index signed extrinsics by signer, for the
+	/// `archive_unstable_transactionsByAccount` RPC method. Entries are pruned together with the
+	/// block bodies.
+	#[arg(long)]
+	pub extrinsic_index: bool,
+
 	/// Run a temporary node.
 	///
 	/// A temporary directory will be created to store the configuration and will be deleted
@@ -268,6 +278,10 @@ impl CliConfiguration for RunCmd {
 		Ok(self.shared_params.dev || self.force_authoring)
 	}
 
+	fn extrinsic_index(&self) -> Result<bool> {
+		Ok(self.extrinsic_index)
+	}
+
 	fn prometheus_config(
 		&self,
 		default_listen_port: u16,
@@ -334,6 +348,10 @@ impl CliConfiguration for RunCmd {
 		Ok(self.pool_config.transaction_pool(is_dev))
 	}
 
+	fn transaction_pool_journal(&self, config_dir: &PathBuf) -> Result<Option<PathBuf>> {
+		Ok(self.pool_config.journal_path(config_dir))
+	}
+
 	fn max_runtime_instances(&self) -> Result<Option<usize>> {
 		Ok(Some(self.runtime_params.max_runtime_instances))
 	}
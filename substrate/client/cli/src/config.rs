@@ -28,14 +28,14 @@ use names::{Generator, Name};
 use sc_service::{
 	config::{
 		BasePath, Configuration, DatabaseSource, ExecutorConfiguration, IpNetwork, KeystoreConfig,
-		NetworkConfiguration, NodeKeyConfig, OffchainWorkerConfig, PrometheusConfig, PruningMode,
-		Role, RpcBatchRequestConfig, RpcConfiguration, RpcMethods, TelemetryEndpoints,
+		NetworkConfiguration, NodeKeyConfig, OffchainWorkerConfig, Profiler, PrometheusConfig,
+		PruningMode, Role, RpcBatchRequestConfig, RpcConfiguration, RpcMethods, TelemetryEndpoints,
 		TransactionPoolOptions, WasmExecutionMethod,
 	},
 	BlocksPruning, ChainSpec, TracingReceiver,
 };
 use sc_tracing::logging::LoggerBuilder;
-use std::{num::NonZeroU32, path::PathBuf};
+use std::{num::NonZeroU32, path::PathBuf, sync::Arc};
 
 /// The maximum number of characters for a node name.
 pub(crate) const NODE_NAME_MAX_LENGTH: usize = 64;
@@ -157,6 +157,13 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		Ok(Default::default())
 	}
 
+	/// Get the path of the transaction pool journal, inside the given `config_dir`.
+	///
+	/// By default the journal is disabled.
+	fn transaction_pool_journal(&self, _config_dir: &PathBuf) -> Result<Option<PathBuf>> {
+		Ok(None)
+	}
+
 	/// Get the network configuration
 	///
 	/// By default this is retrieved from `NetworkParams` if it is available otherwise it creates
@@ -272,6 +279,13 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			.unwrap_or_else(|| Ok(BlocksPruning::KeepFinalized))
 	}
 
+	/// Returns `Ok(true)` if the extrinsics of imported blocks should be indexed by hash.
+	///
+	/// By default this is `false`.
+	fn extrinsic_index(&self) -> Result<bool> {
+		Ok(Default::default())
+	}
+
 	/// Get the chain ID (string).
 	///
 	/// By default this is retrieved from `SharedParams`.
@@ -466,6 +480,13 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		Ok(2)
 	}
 
+	/// Get the profiler recording the time spent in the runtime calls.
+	///
+	/// By default this is `None`.
+	fn execution_profiler(&self) -> Result<Option<Arc<Profiler>>> {
+		Ok(None)
+	}
+
 	/// Activate or not the automatic announcing of blocks after import
 	///
 	/// By default this is `false`.
@@ -513,7 +534,9 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			impl_name: C::impl_name(),
 			impl_version: C::impl_version(),
 			tokio_handle,
-			transaction_pool: self.transaction_pool(is_dev)?,
+			transaction_pool: self
+				.transaction_pool(is_dev)?
+				.with_journal_path(self.transaction_pool_journal(&config_dir)?),
 			network: self.network_config(
 				&chain_spec,
 				is_dev,
@@ -530,11 +553,13 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			trie_cache_maximum_size: self.trie_cache_maximum_size()?,
 			state_pruning: self.state_pruning()?,
 			blocks_pruning: self.blocks_pruning()?,
+			extrinsic_index: self.extrinsic_index()?,
 			executor: ExecutorConfiguration {
 				wasm_method: self.wasm_method()?,
 				default_heap_pages: self.default_heap_pages()?,
 				max_runtime_instances,
 				runtime_cache_size,
+				profiler: self.execution_profiler()?,
 			},
 			wasm_runtime_overrides: self.wasm_runtime_overrides(),
 			rpc: RpcConfiguration {
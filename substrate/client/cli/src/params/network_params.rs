@@ -23,7 +23,8 @@ use crate::{
 use clap::Args;
 use sc_network::{
 	config::{
-		NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode, SetConfig, TransportConfig,
+		NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode, ProtocolBandwidthLimits,
+		SetConfig, TransportConfig,
 	},
 	multiaddr::Protocol,
 };
@@ -163,6 +164,13 @@ pub struct NetworkParams {
 	)]
 	pub sync: SyncMode,
 
+	/// Run as a light client.
+	///
+	/// Only the headers and justifications of the finalized chain are stored, the state is
+	/// fetched from peers on demand. Equivalent to `--sync light`.
+	#[arg(long, conflicts_with = "sync")]
+	pub light: bool,
+
 	/// Maximum number of blocks per request.
 	///
 	/// Try reducing this number from the default value if you have a slow network connection
@@ -170,6 +178,36 @@ pub struct NetworkParams {
 	#[arg(long, value_name = "COUNT", default_value_t = 64)]
 	pub max_blocks_per_request: u32,
 
+	/// Maximum number of transactions received from a single peer that are being validated at
+	/// any moment.
+	///
+	/// Further transactions of the peer are ignored until some of the validations finish.
+	#[arg(long, value_name = "COUNT", default_value_t = 512)]
+	pub max_pending_transactions_per_peer: usize,
+
+	/// Maximum rate, in bytes per second, of the notifications sent to all peers.
+	///
+	/// The budget is shared by all notification protocols, with block announcements and
+	/// finality gossip served before transactions and statements.
+	#[arg(long, value_name = "BYTES")]
+	pub max_notification_bandwidth: Option<u64>,
+
+	/// Bandwidth limits of the notification protocols whose name ends with `NAME`.
+	///
+	/// `PROTOCOL_BYTES` limits the rate, in bytes per second, of the notifications sent to all
+	/// peers, `PEER_BYTES` the rate of the ones sent to each peer and `INBOUND_PEER_BYTES` the
+	/// rate of the ones received from each peer. Notifications received over the limit are dropped
+	/// and the peer is penalised. Any limit can be left empty, e.g.
+	/// `--notification-bandwidth transactions/1=1000000:100000` or
+	/// `--notification-bandwidth statement/1=:50000:200000`. The limits replace the defaults of
+	/// the protocol.
+	#[arg(
+		long,
+		value_name = "NAME=PROTOCOL_BYTES[:PEER_BYTES[:INBOUND_PEER_BYTES]]",
+		num_args = 1..
+	)]
+	pub notification_bandwidth: Vec<ProtocolBandwidthLimits>,
+
 	/// Network backend used for P2P networking.
 	///
 	/// litep2p network backend is considered experimental and isn't as stable as the libp2p
@@ -258,6 +296,7 @@ impl NetworkParams {
 				} else {
 					NonReservedPeerMode::Accept
 				},
+				bandwidth: Default::default(),
 			},
 			default_peers_set_num_full: self.in_peers + self.out_peers,
 			listen_addresses,
@@ -271,14 +310,17 @@ impl NetworkParams {
 			},
 			max_parallel_downloads: self.max_parallel_downloads,
 			max_blocks_per_request: self.max_blocks_per_request,
+			max_pending_transactions_per_peer: self.max_pending_transactions_per_peer,
 			enable_dht_random_walk: !self.reserved_only,
 			allow_non_globals_in_dht,
 			kademlia_disjoint_query_paths: self.kademlia_disjoint_query_paths,
 			kademlia_replication_factor: self.kademlia_replication_factor,
 			yamux_window_size: None,
 			ipfs_server: self.ipfs_server,
-			sync_mode: self.sync.into(),
+			sync_mode: if self.light { SyncMode::Light } else { self.sync }.into(),
 			network_backend: self.network_backend.into(),
+			notification_bandwidth: self.max_notification_bandwidth,
+			notification_protocol_bandwidth: self.notification_bandwidth.clone(),
 		}
 	}
 }
@@ -327,6 +369,46 @@ mod tests {
 		assert_eq!(expected, params.network_params.reserved_nodes);
 	}
 
+	#[test]
+	fn notification_bandwidth_limits() {
+		let params = Cli::try_parse_from([
+			"",
+			"--max-notification-bandwidth",
+			"1000000",
+			"--notification-bandwidth",
+			"transactions/1=500000:50000",
+			"statement/1=:10000:40000",
+		])
+		.expect("Parses network params");
+
+		assert_eq!(params.network_params.max_notification_bandwidth, Some(1_000_000));
+		assert_eq!(
+			params.network_params.notification_bandwidth,
+			vec![
+				ProtocolBandwidthLimits {
+					name: "transactions/1".into(),
+					protocol: Some(500_000),
+					peer: Some(50_000),
+					inbound_peer: None,
+				},
+				ProtocolBandwidthLimits {
+					name: "statement/1".into(),
+					protocol: None,
+					peer: Some(10_000),
+					inbound_peer: Some(40_000),
+				},
+			],
+		);
+
+		assert!(Cli::try_parse_from(["", "--notification-bandwidth", "transactions/1"]).is_err());
+		assert!(Cli::try_parse_from(["", "--notification-bandwidth", "=1000"]).is_err());
+		assert!(
+			Cli::try_parse_from(["", "--notification-bandwidth", "transactions/1=many"]).is_err()
+		);
+		assert!(Cli::try_parse_from(["", "--notification-bandwidth", "transactions/1=1:2:3:4"])
+			.is_err());
+	}
+
 	#[test]
 	fn sync_ignores_case() {
 		let params = Cli::try_parse_from(["", "--sync", "wArP"]).expect("Parses network params");
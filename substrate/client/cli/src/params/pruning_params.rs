@@ -19,6 +19,10 @@
 use crate::error;
 use clap::Args;
 use sc_service::{BlocksPruning, PruningMode};
+use std::num::NonZeroU32;
+
+/// The number of finalized blocks kept by the snapshots pruning mode, if not specified.
+const DEFAULT_MAX_BLOCKS: u32 = 256;
 
 /// Parameters to define the pruning mode
 #[derive(Debug, Clone, Args)]
@@ -41,6 +45,10 @@ pub struct PruningParams {
 	///
 	/// - NUMBER: Keep the data of the last NUMBER of finalized blocks.
 	///
+	/// - snapshots-PERIOD[-NUMBER]: Keep the data of every PERIODth finalized block and of the
+	///   last NUMBER of finalized blocks (256 by default). Requires the ParityDb database, and the
+	///   PERIOD can't change between subsequent runs.
+	///
 	/// [default: 256]
 	#[arg(alias = "pruning", long, value_name = "PRUNING_MODE")]
 	pub state_pruning: Option<DatabasePruningMode>,
@@ -74,6 +82,11 @@ impl PruningParams {
 
 	/// Get the block pruning value from the parameters
 	pub fn blocks_pruning(&self) -> error::Result<BlocksPruning> {
+		if let DatabasePruningMode::Snapshots { .. } = self.blocks_pruning {
+			return Err(error::Error::Input(
+				"Snapshots are only supported by the state pruning mode".into(),
+			))
+		}
 		Ok(self.blocks_pruning.into())
 	}
 }
@@ -91,6 +104,9 @@ pub enum DatabasePruningMode {
 	ArchiveCanonical,
 	/// Keep the data of the last number of finalized blocks.
 	Custom(u32),
+	/// Keep the data of every `period`th finalized block and of the last `max_blocks` finalized
+	/// blocks. Only supported for the state.
+	Snapshots { period: NonZeroU32, max_blocks: u32 },
 }
 
 impl std::str::FromStr for DatabasePruningMode {
@@ -100,6 +116,17 @@ impl std::str::FromStr for DatabasePruningMode {
 		match input {
 			"archive" => Ok(Self::Archive),
 			"archive-canonical" => Ok(Self::ArchiveCanonical),
+			snapshots if snapshots.starts_with("snapshots-") => {
+				let invalid = || format!("Invalid snapshots pruning mode specified: {snapshots}");
+				let mut params = snapshots["snapshots-".len()..].splitn(2, '-');
+				let period =
+					params.next().and_then(|period| period.parse().ok()).ok_or_else(invalid)?;
+				let max_blocks = match params.next() {
+					Some(max_blocks) => max_blocks.parse().map_err(|_| invalid())?,
+					None => DEFAULT_MAX_BLOCKS,
+				};
+				Ok(Self::Snapshots { period, max_blocks })
+			},
 			bc => bc
 				.parse()
 				.map_err(|_| "Invalid pruning mode specified".to_string())
@@ -114,6 +141,8 @@ impl Into<PruningMode> for DatabasePruningMode {
 			DatabasePruningMode::Archive => PruningMode::ArchiveAll,
 			DatabasePruningMode::ArchiveCanonical => PruningMode::ArchiveCanonical,
 			DatabasePruningMode::Custom(n) => PruningMode::blocks_pruning(n),
+			DatabasePruningMode::Snapshots { period, max_blocks } =>
+				PruningMode::snapshots_pruning(period, max_blocks),
 		}
 	}
 }
@@ -123,7 +152,8 @@ impl Into<BlocksPruning> for DatabasePruningMode {
 		match self {
 			DatabasePruningMode::Archive => BlocksPruning::KeepAll,
 			DatabasePruningMode::ArchiveCanonical => BlocksPruning::KeepFinalized,
-			DatabasePruningMode::Custom(n) => BlocksPruning::Some(n),
+			DatabasePruningMode::Custom(n) |
+			DatabasePruningMode::Snapshots { max_blocks: n, .. } => BlocksPruning::Some(n),
 		}
 	}
 }
@@ -162,4 +192,32 @@ mod tests {
 		assert!(matches!(dbg!(pruning.state_pruning), Some(DatabasePruningMode::ArchiveCanonical)));
 		assert!(matches!(pruning.blocks_pruning, DatabasePruningMode::ArchiveCanonical));
 	}
+
+	#[test]
+	fn snapshots_pruning_parse_works() {
+		let Cli { pruning } = Cli::parse_from(["", "--state-pruning=snapshots-1000"]);
+		assert_eq!(
+			pruning.state_pruning,
+			Some(DatabasePruningMode::Snapshots {
+				period: NonZeroU32::new(1000).unwrap(),
+				max_blocks: 256
+			})
+		);
+
+		let Cli { pruning } = Cli::parse_from(["", "--state-pruning=snapshots-1000-64"]);
+		assert_eq!(
+			pruning.state_pruning,
+			Some(DatabasePruningMode::Snapshots {
+				period: NonZeroU32::new(1000).unwrap(),
+				max_blocks: 64
+			})
+		);
+
+		for invalid in ["snapshots-", "snapshots-0", "snapshots-1000-", "snapshots-x-64"] {
+			assert!(invalid.parse::<DatabasePruningMode>().is_err());
+		}
+
+		let Cli { pruning } = Cli::parse_from(["", "--blocks-pruning=snapshots-1000"]);
+		assert!(pruning.blocks_pruning().is_err());
+	}
 }
@@ -18,6 +18,11 @@
 
 use clap::{Args, ValueEnum};
 use sc_transaction_pool::TransactionPoolOptions;
+use sp_runtime::Percent;
+use std::path::{Path, PathBuf};
+
+/// The default path of the transaction pool journal inside the configuration dir.
+const DEFAULT_POOL_JOURNAL_PATH: &str = "txpool/journal";
 
 /// Type of transaction pool to be used
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -56,9 +61,36 @@ pub struct TransactionPoolParams {
 	#[arg(long, value_name = "SECONDS")]
 	pub tx_ban_seconds: Option<u64>,
 
+	/// Maximum number of future transactions of a single sender in the transaction pool.
+	///
+	/// The sender is identified by the node, transactions of unknown senders are not limited.
+	/// When the future queue is full, the transactions of the sender occupying the largest part of
+	/// it are dropped first.
+	#[arg(long, value_name = "COUNT")]
+	pub pool_sender_future_limit: Option<usize>,
+
+	/// Minimal priority increase, in percent, required to replace a transaction in the pool.
+	///
+	/// A transaction replaces the ones providing the same tags (e.g. the same sender and nonce)
+	/// only if its priority exceeds theirs by more than this percentage.
+	#[arg(
+		long,
+		value_name = "PERCENT",
+		default_value_t = 0,
+		value_parser = clap::value_parser!(u8).range(0..=100)
+	)]
+	pub pool_replacement_bump: u8,
+
 	/// The type of transaction pool to be instantiated.
 	#[arg(long, value_enum, default_value_t = TransactionPoolType::SingleState)]
 	pub pool_type: TransactionPoolType,
+
+	/// Keep an on-disk journal of the pool transactions.
+	///
+	/// The transactions stored in the journal are restored and revalidated when the node is
+	/// restarted. Only supported by the fork-aware transaction pool.
+	#[arg(long)]
+	pub pool_journal: bool,
 }
 
 impl TransactionPoolParams {
@@ -71,5 +103,12 @@ impl TransactionPoolParams {
 			self.pool_type.into(),
 			is_dev,
 		)
+		.with_future_sender_limit(self.pool_sender_future_limit)
+		.with_replacement_bump(Percent::from_percent(self.pool_replacement_bump))
+	}
+
+	/// Returns the path of the transaction pool journal, if it is enabled.
+	pub fn journal_path(&self, config_dir: &Path) -> Option<PathBuf> {
+		self.pool_journal.then(|| config_dir.join(DEFAULT_POOL_JOURNAL_PATH))
 	}
 }
@@ -285,6 +285,12 @@ where
 			},
 		}
 	}
+
+	// The authorities are read from the state of the parent, and the inherents are checked
+	// against it.
+	fn can_verify_ahead(&self) -> bool {
+		false
+	}
 }
 
 /// Should we check for equivocation of a block author?
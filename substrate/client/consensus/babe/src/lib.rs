@@ -1288,6 +1288,12 @@ where
 			},
 		}
 	}
+
+	// The epoch of the block is only known once its parent is imported, and the inherents are
+	// checked against the state of the parent.
+	fn can_verify_ahead(&self) -> bool {
+		false
+	}
 }
 
 /// A block-import handler for BABE.
@@ -83,6 +83,10 @@ pub fn beefy_peers_set_config<
 			out_peers: 25,
 			reserved_nodes: Vec::new(),
 			non_reserved_mode: sc_network::config::NonReservedPeerMode::Accept,
+			bandwidth: sc_network::config::NotificationBandwidth {
+				priority: sc_network::config::NotificationPriority::High,
+				..Default::default()
+			},
 		},
 		metrics,
 		peer_store_handle,
@@ -16,21 +16,42 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{error::Error, keystore::BeefyKeystore, round::Rounds, LOG_TARGET};
+use crate::{
+	error::Error, justification::BeefyVersionedFinalityProof, keystore::BeefyKeystore,
+	round::Rounds, LOG_TARGET,
+};
 use log::{debug, error, warn};
+use parking_lot::Mutex;
 use sc_client_api::Backend;
-use sp_api::ProvideRuntimeApi;
+use sc_transaction_pool_api::OffchainTransactionPoolFactory;
+use sp_api::{ApiExt, ApiRef, ProvideRuntimeApi};
 use sp_application_crypto::RuntimeAppPublic;
 use sp_blockchain::HeaderBackend;
 use sp_consensus_beefy::{
-	check_double_voting_proof, AuthorityIdBound, BeefyApi, BeefySignatureHasher, DoubleVotingProof,
-	OpaqueKeyOwnershipProof, ValidatorSetId,
+	check_commitment_signature, check_double_voting_proof, AuthorityIdBound, BeefyApi,
+	BeefySignatureHasher, DoubleVotingProof, ForkVotingProof, FutureBlockVotingProof,
+	OpaqueKeyOwnershipProof, Payload, PayloadProvider, ValidatorSetId, VersionedFinalityProof,
+	VoteMessage,
 };
 use sp_runtime::{
 	generic::BlockId,
-	traits::{Block, NumberFor},
+	traits::{Block, Header, NumberFor},
 };
-use std::{marker::PhantomData, sync::Arc};
+use std::sync::Arc;
+
+/// Votes for blocks this many blocks above the local best finalized block are reported as future
+/// block voting.
+///
+/// Honest validators only vote for blocks they have finalized, but their view of finality can still
+/// be a few blocks ahead of the local one. Such votes are kept until the local node catches up
+/// instead. The local best block is not used, since it may be arbitrarily far from finality.
+const FUTURE_BLOCK_VOTING_MARGIN: u32 = 64;
+
+/// Maximum number of votes kept until the blocks they are for are finalized locally.
+const MAX_PENDING_VOTES: usize = 1024;
+
+type Vote<B, AuthorityId> =
+	VoteMessage<NumberFor<B>, AuthorityId, <AuthorityId as RuntimeAppPublic>::Signature>;
 
 /// Helper struct containing the key ownership proof for a validator.
 pub struct ProvedValidator {
@@ -38,16 +59,30 @@ pub struct ProvedValidator {
 }
 
 /// Helper used to check and report equivocations.
-pub struct Fisherman<B, BE, RuntimeApi, AuthorityId: AuthorityIdBound> {
+///
+/// Besides double voting, it detects votes for blocks that are not on the local canonical chain
+/// (fork voting) and votes for blocks that don't exist yet (future block voting). These can be
+/// checked for the votes and justifications received over the network, as well as for signed
+/// commitments seen elsewhere, e.g. in the headers relayed to a bridged chain.
+pub struct Fisherman<B: Block, BE, P, RuntimeApi, AuthorityId: AuthorityIdBound> {
 	backend: Arc<BE>,
 	runtime: Arc<RuntimeApi>,
 	key_store: Arc<BeefyKeystore<AuthorityId>>,
-
-	_phantom: PhantomData<B>,
+	payload_provider: P,
+	offchain_tx_pool_factory: Option<OffchainTransactionPoolFactory<B>>,
+	/// Payload of the last canonical block checked, as votes usually come for the same block.
+	canonical_payload: Mutex<Option<(NumberFor<B>, Option<Payload>)>>,
+	/// Votes for blocks that are not finalized locally yet.
+	pending_votes: Mutex<Vec<Vote<B, AuthorityId>>>,
 }
 
-impl<B: Block, BE: Backend<B>, RuntimeApi: ProvideRuntimeApi<B>, AuthorityId>
-	Fisherman<B, BE, RuntimeApi, AuthorityId>
+impl<
+		B: Block,
+		BE: Backend<B>,
+		P: PayloadProvider<B>,
+		RuntimeApi: ProvideRuntimeApi<B>,
+		AuthorityId,
+	> Fisherman<B, BE, P, RuntimeApi, AuthorityId>
 where
 	RuntimeApi::Api: BeefyApi<B, AuthorityId>,
 	AuthorityId: AuthorityIdBound,
@@ -56,8 +91,51 @@ where
 		backend: Arc<BE>,
 		runtime: Arc<RuntimeApi>,
 		keystore: Arc<BeefyKeystore<AuthorityId>>,
+		payload_provider: P,
+		offchain_tx_pool_factory: Option<OffchainTransactionPoolFactory<B>>,
 	) -> Self {
-		Self { backend, runtime, key_store: keystore, _phantom: Default::default() }
+		Self {
+			backend,
+			runtime,
+			key_store: keystore,
+			payload_provider,
+			offchain_tx_pool_factory,
+			canonical_payload: Mutex::new(None),
+			pending_votes: Mutex::new(Vec::new()),
+		}
+	}
+
+	/// Runtime API instance able to submit unsigned extrinsics through the offchain transaction
+	/// pool, if there is one.
+	fn runtime_api_at(&self, at: B::Hash) -> ApiRef<'_, RuntimeApi::Api> {
+		let mut runtime_api = self.runtime.runtime_api();
+		if let Some(offchain_tx_pool_factory) = &self.offchain_tx_pool_factory {
+			runtime_api.register_extension(offchain_tx_pool_factory.offchain_transaction_pool(at));
+		}
+		runtime_api
+	}
+
+	fn canonical_hash_header_at(
+		&self,
+		number: NumberFor<B>,
+	) -> Result<(B::Hash, B::Header), Error> {
+		let blockchain = self.backend.blockchain();
+		let hash = blockchain.expect_block_hash_from_id(&BlockId::Number(number))?;
+		let header = blockchain.expect_header(hash)?;
+		Ok((hash, header))
+	}
+
+	fn canonical_payload(&self, number: NumberFor<B>) -> Result<Option<Payload>, Error> {
+		let mut cached = self.canonical_payload.lock();
+		match cached.as_ref() {
+			Some((cached_number, payload)) if *cached_number == number => Ok(payload.clone()),
+			_ => {
+				let (_, header) = self.canonical_hash_header_at(number)?;
+				let payload = self.payload_provider.payload(&header);
+				*cached = Some((number, payload.clone()));
+				Ok(payload)
+			},
+		}
 	}
 
 	fn prove_offenders<'a>(
@@ -113,6 +191,11 @@ where
 		Ok(proved_offenders)
 	}
 
+	/// Returns `true` if `offender_id` is the local authority.
+	fn is_local_authority(&self, offender_id: &AuthorityId) -> bool {
+		self.key_store.authority_id(std::slice::from_ref(offender_id)).is_some()
+	}
+
 	/// Report the given equivocation to the BEEFY runtime module. This method
 	/// generates a session membership proof of the offender and then submits an
 	/// extrinsic to report the equivocation. In particular, the session membership
@@ -152,8 +235,7 @@ where
 		// submit equivocation report at **best** block
 		let best_block_hash = self.backend.blockchain().info().best_hash;
 		for ProvedValidator { key_owner_proof, .. } in key_owner_proofs {
-			self.runtime
-				.runtime_api()
+			self.runtime_api_at(best_block_hash)
 				.submit_report_double_voting_unsigned_extrinsic(
 					best_block_hash,
 					proof.clone(),
@@ -164,4 +246,208 @@ where
 
 		Ok(())
 	}
+
+	/// Check `vote` against the local chain and report it if it is for a block that is not on the
+	/// canonical chain, or for a block that doesn't exist yet.
+	///
+	/// Votes for blocks that are not finalized yet can't be told apart from honest votes on a
+	/// competing fork, so they are checked once these blocks are finalized, see
+	/// [`Self::check_pending_votes`]. The signature of `vote` is expected to be valid.
+	pub fn check_vote(&self, vote: Vote<B, AuthorityId>) -> Result<(), Error> {
+		if self.is_local_authority(&vote.id) {
+			return Ok(())
+		}
+
+		let number = vote.commitment.block_number;
+		let info = self.backend.blockchain().info();
+		if number > info.finalized_number.saturating_add(FUTURE_BLOCK_VOTING_MARGIN.into()) {
+			return self.report_future_block_voting(FutureBlockVotingProof { vote })
+		}
+		if number > info.finalized_number {
+			let mut pending_votes = self.pending_votes.lock();
+			if pending_votes.len() < MAX_PENDING_VOTES {
+				pending_votes.push(vote);
+			} else {
+				debug!(target: LOG_TARGET, "🥩 Too many pending votes, skipping check of {:?}", vote);
+			}
+			return Ok(())
+		}
+
+		self.check_finalized_vote(vote)
+	}
+
+	/// Check the votes kept by [`Self::check_vote`] for blocks that have been finalized since.
+	pub fn check_pending_votes(&self) {
+		let finalized_number = self.backend.blockchain().info().finalized_number;
+		let (finalized, pending): (Vec<_>, Vec<_>) =
+			std::mem::take(&mut *self.pending_votes.lock())
+				.into_iter()
+				.partition(|vote| vote.commitment.block_number <= finalized_number);
+		self.pending_votes.lock().extend(pending);
+
+		for vote in finalized {
+			if let Err(e) = self.check_finalized_vote(vote) {
+				debug!(target: LOG_TARGET, "🥩 Error checking pending vote: {}", e);
+			}
+		}
+	}
+
+	/// Check all the votes contained in the signed commitment of `proof`, see
+	/// [`Self::check_vote`].
+	///
+	/// The signatures of `proof` are expected to be valid. The validator set of the commitment has
+	/// to be known at the block it was signed for, or at the best block for commitments of future
+	/// blocks.
+	pub fn check_proof(
+		&self,
+		proof: BeefyVersionedFinalityProof<B, AuthorityId>,
+	) -> Result<(), Error> {
+		self.check_signed_commitment(proof, false)
+	}
+
+	/// Check a finality proof of this chain relayed to a bridged chain, e.g. extracted from a
+	/// header submitted to a BEEFY light client.
+	///
+	/// Unlike [`Self::check_proof`], the signatures of `proof` are verified, as these come from
+	/// outside of the local node. Invalid signatures are ignored.
+	pub fn check_relayed_proof(
+		&self,
+		proof: BeefyVersionedFinalityProof<B, AuthorityId>,
+	) -> Result<(), Error> {
+		self.check_signed_commitment(proof, true)
+	}
+
+	fn check_signed_commitment(
+		&self,
+		proof: BeefyVersionedFinalityProof<B, AuthorityId>,
+		verify_signatures: bool,
+	) -> Result<(), Error> {
+		let VersionedFinalityProof::V1(signed_commitment) = proof;
+		let number = signed_commitment.commitment.block_number;
+		let info = self.backend.blockchain().info();
+		let at = if number > info.best_number {
+			info.best_hash
+		} else {
+			self.canonical_hash_header_at(number)?.0
+		};
+		let validator_set = match self.runtime.runtime_api().validator_set(at) {
+			Ok(Some(validator_set))
+				if validator_set.id() == signed_commitment.commitment.validator_set_id =>
+				validator_set,
+			Ok(_) => {
+				debug!(
+					target: LOG_TARGET,
+					"🥩 Skipping check of commitment for unknown validator set {:?}",
+					signed_commitment.commitment
+				);
+				return Ok(())
+			},
+			Err(e) => return Err(Error::RuntimeApi(e)),
+		};
+
+		let commitment = signed_commitment.commitment;
+		for (id, signature) in validator_set.validators().iter().zip(signed_commitment.signatures) {
+			let Some(signature) = signature else { continue };
+			if verify_signatures &&
+				!check_commitment_signature::<_, _, BeefySignatureHasher>(
+					&commitment,
+					id,
+					&signature,
+				) {
+				debug!(
+					target: LOG_TARGET,
+					"🥩 Skipping check of badly signed vote of {} for {:?}", id, commitment
+				);
+				continue
+			}
+			let vote = VoteMessage { commitment: commitment.clone(), id: id.clone(), signature };
+			self.check_vote(vote)?;
+		}
+		Ok(())
+	}
+
+	/// Report `vote` if it doesn't match the canonical block it was cast for, which has to be
+	/// finalized.
+	fn check_finalized_vote(&self, vote: Vote<B, AuthorityId>) -> Result<(), Error> {
+		match self.canonical_payload(vote.commitment.block_number)? {
+			Some(payload) if payload != vote.commitment.payload => self.report_fork_voting(vote),
+			_ => Ok(()),
+		}
+	}
+
+	/// Report a vote for a block that is not on the canonical chain. The ancestry proof is
+	/// generated at the best block, against which the vote can be checked.
+	fn report_fork_voting(&self, vote: Vote<B, AuthorityId>) -> Result<(), Error> {
+		let number = vote.commitment.block_number;
+		let info = self.backend.blockchain().info();
+		let best_header = self.backend.blockchain().expect_header(info.best_hash)?;
+		let ancestry_proof = match self
+			.runtime
+			.runtime_api()
+			.generate_ancestry_proof(info.best_hash, number, None)
+			.map_err(Error::RuntimeApi)?
+		{
+			Some(ancestry_proof) => ancestry_proof,
+			None => {
+				debug!(
+					target: LOG_TARGET,
+					"🥩 Couldn't generate ancestry proof for block #{:?}. \
+					Skipping report for fork voting",
+					number
+				);
+				return Ok(())
+			},
+		};
+
+		let key_owner_proofs = self.prove_offenders(
+			BlockId::Number(number),
+			std::iter::once(&vote.id),
+			vote.commitment.validator_set_id,
+		)?;
+
+		debug!(target: LOG_TARGET, "🥩 Reporting fork voting of {} at #{:?}", vote.id, number);
+		let proof = ForkVotingProof { vote, ancestry_proof, header: best_header };
+		if let Some(ProvedValidator { key_owner_proof }) = key_owner_proofs.into_iter().next() {
+			self.runtime_api_at(info.best_hash)
+				.submit_report_fork_voting_unsigned_extrinsic(
+					info.best_hash,
+					proof,
+					key_owner_proof,
+				)
+				.map_err(Error::RuntimeApi)?;
+		}
+
+		Ok(())
+	}
+
+	/// Report a vote for a block too far above the best finalized block.
+	fn report_future_block_voting(
+		&self,
+		proof: FutureBlockVotingProof<NumberFor<B>, AuthorityId>,
+	) -> Result<(), Error> {
+		let best_block_hash = self.backend.blockchain().info().best_hash;
+		let key_owner_proofs = self.prove_offenders(
+			BlockId::Hash(best_block_hash),
+			std::iter::once(&proof.vote.id),
+			proof.vote.commitment.validator_set_id,
+		)?;
+
+		debug!(
+			target: LOG_TARGET,
+			"🥩 Reporting future block voting of {} at #{:?}",
+			proof.vote.id,
+			proof.vote.commitment.block_number
+		);
+		if let Some(ProvedValidator { key_owner_proof }) = key_owner_proofs.into_iter().next() {
+			self.runtime_api_at(best_block_hash)
+				.submit_report_future_block_voting_unsigned_extrinsic(
+					best_block_hash,
+					proof,
+					key_owner_proof,
+				)
+				.map_err(Error::RuntimeApi)?;
+		}
+
+		Ok(())
+	}
 }
@@ -39,6 +39,7 @@ use sc_client_api::{Backend, BlockBackend, BlockchainEvents, FinalityNotificatio
 use sc_consensus::BlockImport;
 use sc_network::{NetworkRequest, NotificationService, ProtocolName};
 use sc_network_gossip::{GossipEngine, Network as GossipNetwork, Syncing as GossipSyncing};
+use sc_transaction_pool_api::OffchainTransactionPoolFactory;
 use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedReceiver};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::{Backend as BlockchainBackend, HeaderBackend};
@@ -233,6 +234,15 @@ pub struct BeefyParams<B: Block, BE, C, N, P, R, S, AuthorityId: AuthorityIdBoun
 	pub on_demand_justifications_handler: BeefyJustifsRequestHandler<B, C>,
 	/// Whether running under "Authority" role.
 	pub is_authority: bool,
+	/// The offchain transaction pool factory, used to submit equivocation reports.
+	///
+	/// If `None`, reports can only be submitted if the runtime has access to a transaction pool
+	/// on its own.
+	pub offchain_tx_pool_factory: Option<OffchainTransactionPoolFactory<B>>,
+	/// Stream of finality proofs of this chain relayed to bridged chains, e.g. extracted from the
+	/// headers submitted to a BEEFY light client. These are checked for fork and future block
+	/// voting.
+	pub bridged_finality_proofs: Option<BeefyVersionedFinalityProofStream<B, AuthorityId>>,
 }
 /// Helper object holding BEEFY worker communication/gossip components.
 ///
@@ -315,15 +325,26 @@ where
 		links: BeefyVoterLinks<B, AuthorityId>,
 		pending_justifications: BTreeMap<NumberFor<B>, BeefyVersionedFinalityProof<B, AuthorityId>>,
 		is_authority: bool,
-	) -> BeefyWorker<B, BE, P, R, S, N, AuthorityId> {
+		offchain_tx_pool_factory: Option<OffchainTransactionPoolFactory<B>>,
+	) -> BeefyWorker<B, BE, P, R, S, N, AuthorityId>
+	where
+		P: PayloadProvider<B> + Clone,
+	{
 		let key_store = Arc::new(self.key_store);
+		let fisherman = Fisherman::new(
+			self.backend.clone(),
+			self.runtime.clone(),
+			key_store.clone(),
+			payload_provider.clone(),
+			offchain_tx_pool_factory,
+		);
 		BeefyWorker {
-			backend: self.backend.clone(),
-			runtime: self.runtime.clone(),
-			key_store: key_store.clone(),
+			backend: self.backend,
+			runtime: self.runtime,
+			key_store,
 			payload_provider,
 			sync,
-			fisherman: Arc::new(Fisherman::new(self.backend, self.runtime, key_store)),
+			fisherman: Arc::new(fisherman),
 			metrics: self.metrics,
 			persisted_state: self.persisted_state,
 			comms,
@@ -542,6 +563,8 @@ pub async fn start_beefy_gadget<B, BE, C, N, P, R, S, AuthorityId>(
 		links,
 		mut on_demand_justifications_handler,
 		is_authority,
+		offchain_tx_pool_factory,
+		bridged_finality_proofs,
 	} = beefy_params;
 
 	let BeefyNetworkParams {
@@ -556,6 +579,8 @@ pub async fn start_beefy_gadget<B, BE, C, N, P, R, S, AuthorityId>(
 	let metrics = register_metrics(prometheus_registry.clone());
 
 	let mut block_import_justif = links.from_block_import_justif_stream.subscribe(100_000).fuse();
+	let mut bridged_finality_proofs =
+		bridged_finality_proofs.map(|proofs| proofs.subscribe(100_000).fuse());
 
 	// Subscribe to finality notifications and justifications before waiting for runtime pallet and
 	// reuse the streams, so we don't miss notifications while waiting for pallet to be available.
@@ -629,10 +654,15 @@ pub async fn start_beefy_gadget<B, BE, C, N, P, R, S, AuthorityId>(
 			links.clone(),
 			BTreeMap::new(),
 			is_authority,
+			offchain_tx_pool_factory.clone(),
 		);
 
 		futures::select! {
-			result = worker.run(&mut block_import_justif, &mut finality_notifications).fuse() => {
+			result = worker.run(
+				&mut block_import_justif,
+				&mut finality_notifications,
+				&mut bridged_finality_proofs,
+			).fuse() => {
 				match result {
 					(error::Error::ConsensusReset, reuse_comms) => {
 						error!(target: LOG_TARGET, "🥩 Error: {:?}. Restarting voter.", error::Error::ConsensusReset);
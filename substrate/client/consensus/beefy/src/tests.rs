@@ -30,8 +30,11 @@ use crate::{
 		request_response::{on_demand_justifications_protocol_config, BeefyJustifsRequestHandler},
 	},
 	error::Error,
-	finality_notification_transformer_future, gossip_protocol_name,
+	finality_notification_transformer_future,
+	fisherman::Fisherman,
+	gossip_protocol_name,
 	justification::*,
+	keystore::BeefyKeystore,
 	wait_for_runtime_pallet,
 	worker::PersistedState,
 	BeefyRPCLinks, BeefyVoterLinks, BeefyWorkerBuilder, KnownPeers, UnpinnedFinalityNotification,
@@ -63,10 +66,10 @@ use sp_consensus_beefy::{
 	ecdsa_crypto::{AuthorityId, Signature},
 	known_payloads,
 	mmr::{find_mmr_root_digest, MmrRootProvider},
-	test_utils::Keyring as BeefyKeyring,
-	BeefyApi, Commitment, ConsensusLog, DoubleVotingProof, MmrRootHash, OpaqueKeyOwnershipProof,
-	Payload, SignedCommitment, ValidatorSet, ValidatorSetId, VersionedFinalityProof, VoteMessage,
-	BEEFY_ENGINE_ID,
+	test_utils::{signed_vote, Keyring as BeefyKeyring},
+	BeefyApi, Commitment, ConsensusLog, DoubleVotingProof, ForkVotingProof, FutureBlockVotingProof,
+	MmrRootHash, OpaqueKeyOwnershipProof, Payload, SignedCommitment, ValidatorSet, ValidatorSetId,
+	VersionedFinalityProof, VoteMessage, BEEFY_ENGINE_ID,
 };
 use sp_core::H256;
 use sp_keystore::{testing::MemoryKeystore, Keystore, KeystorePtr};
@@ -74,10 +77,10 @@ use sp_mmr_primitives::{Error as MmrError, MmrApi};
 use sp_runtime::{
 	codec::{Decode, Encode},
 	traits::{Header as HeaderT, NumberFor},
-	BuildStorage, DigestItem, EncodedJustification, Justifications, Storage,
+	BuildStorage, DigestItem, EncodedJustification, Justifications, OpaqueValue, Storage,
 };
 use std::{marker::PhantomData, sync::Arc, task::Poll};
-use substrate_test_runtime_client::{BlockBuilderExt, ClientExt};
+use substrate_test_runtime_client::{runtime::Header, BlockBuilderExt, ClientExt};
 use tokio::time::Duration;
 
 const GENESIS_HASH: H256 = H256::zero();
@@ -266,6 +269,9 @@ pub(crate) struct TestApi {
 	pub mmr_root_hash: MmrRootHash,
 	pub reported_equivocations:
 		Option<Arc<Mutex<Vec<DoubleVotingProof<NumberFor<Block>, AuthorityId, Signature>>>>>,
+	pub reported_fork_votes: Arc<Mutex<Vec<VoteMessage<NumberFor<Block>, AuthorityId, Signature>>>>,
+	pub reported_future_block_votes:
+		Arc<Mutex<Vec<VoteMessage<NumberFor<Block>, AuthorityId, Signature>>>>,
 }
 
 impl TestApi {
@@ -279,6 +285,8 @@ impl TestApi {
 			validator_set: Some(validator_set.clone()),
 			mmr_root_hash,
 			reported_equivocations: None,
+			reported_fork_votes: Default::default(),
+			reported_future_block_votes: Default::default(),
 		}
 	}
 
@@ -288,6 +296,8 @@ impl TestApi {
 			validator_set: Some(validator_set.clone()),
 			mmr_root_hash: GOOD_MMR_ROOT,
 			reported_equivocations: None,
+			reported_fork_votes: Default::default(),
+			reported_future_block_votes: Default::default(),
 		}
 	}
 
@@ -330,10 +340,31 @@ sp_api::mock_impl_runtime_apis! {
 			}
 		}
 
+		fn submit_report_fork_voting_unsigned_extrinsic(
+			proof: ForkVotingProof<Header, AuthorityId, OpaqueValue>,
+			_dummy: OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			self.inner.reported_fork_votes.lock().push(proof.vote);
+			None
+		}
+
+		fn submit_report_future_block_voting_unsigned_extrinsic(
+			proof: FutureBlockVotingProof<NumberFor<Block>, AuthorityId>,
+			_dummy: OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			self.inner.reported_future_block_votes.lock().push(proof.vote);
+			None
+		}
+
 		fn generate_key_ownership_proof(
 			_dummy1: ValidatorSetId,
 			_dummy2: AuthorityId,
 		) -> Option<OpaqueKeyOwnershipProof> { Some(OpaqueKeyOwnershipProof::new(vec![])) }
+
+		fn generate_ancestry_proof(
+			_dummy1: NumberFor<Block>,
+			_dummy2: Option<NumberFor<Block>>,
+		) -> Option<OpaqueValue> { Some(OpaqueValue::new(vec![])) }
 	}
 
 	impl MmrApi<Block, MmrRootHash, NumberFor<Block>> for RuntimeApi {
@@ -460,6 +491,8 @@ where
 			prometheus_registry: None,
 			on_demand_justifications_handler: on_demand_justif_handler,
 			is_authority: true,
+			offchain_tx_pool_factory: None,
+			bridged_finality_proofs: None,
 		};
 		let task = crate::start_beefy_gadget::<_, _, _, _, _, _, _, _>(beefy_params);
 
@@ -1477,6 +1510,90 @@ async fn beefy_reports_equivocations() {
 	streams_empty_after_timeout(versioned_finality_proof, &net, None).await;
 }
 
+#[tokio::test]
+async fn fisherman_reports_fork_and_future_block_voting() {
+	sp_tracing::try_init_simple();
+
+	let peers = [BeefyKeyring::Alice, BeefyKeyring::Bob, BeefyKeyring::Charlie];
+	let validator_set = ValidatorSet::new(make_beefy_ids(&peers), 0).unwrap();
+	let api = Arc::new(TestApi::with_validator_set(&validator_set));
+
+	let mut net = BeefyTestNet::new(1);
+	// The best block is far above the best finalized block.
+	let hashes = net.peer(0).push_blocks(100, false);
+	let backend = net.peer(0).client().as_backend();
+	let key_store: Arc<BeefyKeystore<AuthorityId>> =
+		Arc::new(Some(create_beefy_keystore(&BeefyKeyring::Alice)).into());
+	let fisherman =
+		Fisherman::new(backend, api.clone(), key_store, MmrRootProvider::new(api.clone()), None);
+
+	let good_payload =
+		Payload::from_single_entry(known_payloads::MMR_ROOT_ID, GOOD_MMR_ROOT.encode());
+	let bad_payload =
+		Payload::from_single_entry(known_payloads::MMR_ROOT_ID, BAD_MMR_ROOT.encode());
+
+	// Bob votes for a block far above the best finalized block, Alice's own votes are never
+	// reported.
+	let future_vote = signed_vote(100, good_payload.clone(), 0, &BeefyKeyring::Bob);
+	fisherman.check_vote(future_vote.clone()).unwrap();
+	fisherman
+		.check_vote(signed_vote(100, good_payload.clone(), 0, &BeefyKeyring::Alice))
+		.unwrap();
+	assert_eq!(*api.reported_future_block_votes.lock(), vec![future_vote]);
+
+	// Votes for blocks slightly above the best finalized block may come from validators ahead of
+	// us.
+	fisherman
+		.check_vote(signed_vote(10, good_payload.clone(), 0, &BeefyKeyring::Bob))
+		.unwrap();
+	assert_eq!(api.reported_future_block_votes.lock().len(), 1);
+
+	// Votes for blocks that are not finalized yet may be for a competing fork, so they are only
+	// checked once the block is finalized.
+	let fork_vote = signed_vote(1, bad_payload.clone(), 0, &BeefyKeyring::Bob);
+	fisherman.check_vote(fork_vote.clone()).unwrap();
+	fisherman
+		.check_vote(signed_vote(1, good_payload.clone(), 0, &BeefyKeyring::Charlie))
+		.unwrap();
+	fisherman.check_pending_votes();
+	assert!(api.reported_fork_votes.lock().is_empty());
+
+	// Once block #1 is finalized, only the vote that doesn't match the canonical chain is
+	// reported.
+	net.peer(0).client().as_client().finalize_block(hashes[0], None).unwrap();
+	fisherman.check_pending_votes();
+	assert_eq!(*api.reported_fork_votes.lock(), vec![fork_vote.clone()]);
+	api.reported_fork_votes.lock().clear();
+
+	// Votes for finalized blocks are checked right away.
+	fisherman.check_vote(fork_vote.clone()).unwrap();
+	assert_eq!(*api.reported_fork_votes.lock(), vec![fork_vote.clone()]);
+	api.reported_fork_votes.lock().clear();
+
+	// Justifications are checked vote by vote.
+	let proof = VersionedFinalityProof::<_, Signature>::V1(SignedCommitment {
+		commitment: fork_vote.commitment.clone(),
+		signatures: vec![None, Some(fork_vote.signature.clone()), None],
+	});
+	fisherman.check_proof(proof).unwrap();
+	assert_eq!(*api.reported_fork_votes.lock(), vec![fork_vote.clone()]);
+	api.reported_fork_votes.lock().clear();
+
+	// Finality proofs relayed to a bridged chain are untrusted: a signature of Bob in the slot of
+	// Charlie is ignored.
+	let proof = VersionedFinalityProof::<_, Signature>::V1(SignedCommitment {
+		commitment: fork_vote.commitment.clone(),
+		signatures: vec![
+			None,
+			Some(fork_vote.signature.clone()),
+			Some(fork_vote.signature.clone()),
+		],
+	});
+	fisherman.check_relayed_proof(proof).unwrap();
+	assert_eq!(*api.reported_fork_votes.lock(), vec![fork_vote]);
+	assert_eq!(api.reported_future_block_votes.lock().len(), 1);
+}
+
 #[tokio::test]
 async fn gossipped_finality_proofs() {
 	sp_tracing::try_init_simple();
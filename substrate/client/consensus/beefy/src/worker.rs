@@ -387,7 +387,7 @@ pub(crate) struct BeefyWorker<B: Block, BE, P, RuntimeApi, S, N, AuthorityId: Au
 	pub key_store: Arc<BeefyKeystore<AuthorityId>>,
 	pub payload_provider: P,
 	pub sync: Arc<S>,
-	pub fisherman: Arc<Fisherman<B, BE, RuntimeApi, AuthorityId>>,
+	pub fisherman: Arc<Fisherman<B, BE, P, RuntimeApi, AuthorityId>>,
 
 	// communication (created once, but returned and reused if worker is restarted/reinitialized)
 	pub comms: BeefyComms<B, N, AuthorityId>,
@@ -511,6 +511,11 @@ where
 			}
 		}
 
+		// Votes for the newly finalized blocks can now be checked against the canonical chain.
+		if !self.sync.is_major_syncing() {
+			self.fisherman.check_pending_votes();
+		}
+
 		Ok(())
 	}
 
@@ -522,6 +527,13 @@ where
 	where
 		<AuthorityId as RuntimeAppPublic>::Signature: Encode + Decode,
 	{
+		// While syncing, the local chain can't tell which votes are for future blocks.
+		if !self.sync.is_major_syncing() {
+			if let Err(e) = self.fisherman.check_vote(vote.clone()) {
+				debug!(target: LOG_TARGET, "🥩 Error checking vote {:?}: {}", vote, e);
+			}
+		}
+
 		let block_num = vote.commitment.block_number;
 		match self.voting_oracle().triage_round(block_num)? {
 			RoundAction::Process =>
@@ -541,6 +553,16 @@ where
 		Ok(())
 	}
 
+	/// Check a finality proof relayed to a bridged chain with the fisherman.
+	fn check_relayed_proof(&self, proof: BeefyVersionedFinalityProof<B, AuthorityId>) {
+		if self.sync.is_major_syncing() {
+			return
+		}
+		if let Err(e) = self.fisherman.check_relayed_proof(proof.clone()) {
+			debug!(target: LOG_TARGET, "🥩 Error checking relayed proof {:?}: {}", proof, e);
+		}
+	}
+
 	/// Based on [VoterOracle] this justification is either processed here or enqueued for later.
 	///
 	/// Expects `justification` to be valid.
@@ -548,6 +570,15 @@ where
 		&mut self,
 		justification: BeefyVersionedFinalityProof<B, AuthorityId>,
 	) -> Result<(), Error> {
+		if !self.sync.is_major_syncing() {
+			if let Err(e) = self.fisherman.check_proof(justification.clone()) {
+				debug!(
+					target: LOG_TARGET,
+					"🥩 Error checking justification {:?}: {}", justification, e
+				);
+			}
+		}
+
 		let signed_commitment = match justification {
 			VersionedFinalityProof::V1(ref sc) => sc,
 		};
@@ -851,6 +882,9 @@ where
 			NotificationReceiver<BeefyVersionedFinalityProof<B, AuthorityId>>,
 		>,
 		finality_notifications: &mut Fuse<crate::FinalityNotifications<B>>,
+		bridged_finality_proofs: &mut Option<
+			Fuse<NotificationReceiver<BeefyVersionedFinalityProof<B, AuthorityId>>>,
+		>,
 	) -> (Error, BeefyComms<B, N, AuthorityId>) {
 		info!(
 			target: LOG_TARGET,
@@ -958,6 +992,19 @@ where
 						break Error::VotesGossipStreamTerminated;
 					}
 				},
+				// Relayed finality proofs are only checked by the fisherman.
+				proof = async {
+					match bridged_finality_proofs.as_mut() {
+						Some(proofs) => proofs.next().await,
+						None => futures::future::pending().await,
+					}
+				}.fuse() => {
+					if let Some(proof) = proof {
+						self.check_relayed_proof(proof);
+					} else {
+						*bridged_finality_proofs = None;
+					}
+				},
 			}
 
 			// Act on changed 'state'.
@@ -1148,6 +1195,13 @@ pub(crate) mod tests {
 		let comms = BeefyComms { gossip_engine, gossip_validator, on_demand_justifications };
 		let key_store: Arc<BeefyKeystore<ecdsa_crypto::AuthorityId>> =
 			Arc::new(Some(keystore).into());
+		let fisherman = Fisherman::new(
+			backend.clone(),
+			api.clone(),
+			key_store.clone(),
+			payload_provider.clone(),
+			None,
+		);
 		BeefyWorker {
 			backend: backend.clone(),
 			runtime: api.clone(),
@@ -1155,7 +1209,7 @@ pub(crate) mod tests {
 			metrics,
 			payload_provider,
 			sync: Arc::new(sync),
-			fisherman: Arc::new(Fisherman::new(backend, api, key_store)),
+			fisherman: Arc::new(fisherman),
 			links,
 			comms,
 			pending_justifications: BTreeMap::new(),
@@ -1582,6 +1636,8 @@ pub(crate) mod tests {
 			worker.backend.clone(),
 			worker.runtime.clone(),
 			worker.key_store.clone(),
+			MmrRootProvider::new(api_alice.clone()),
+			None,
 		));
 
 		// let there be a block with num = 1:
@@ -124,6 +124,8 @@ pub enum StorageChanges<Block: BlockT> {
 	Changes(sp_state_machine::StorageChanges<HashingFor<Block>>),
 	/// Whole new state.
 	Import(ImportedState<Block>),
+	/// The state is not stored locally, but fetched from remote peers on demand.
+	Remote,
 }
 
 /// Imported state data. A vector of key-value pairs that should form a trie.
@@ -299,7 +301,10 @@ impl<Block: BlockT> BlockImportParams<Block> {
 
 	/// Check if this block contains state import action
 	pub fn with_state(&self) -> bool {
-		matches!(self.state_action, StateAction::ApplyChanges(StorageChanges::Import(_)))
+		matches!(
+			self.state_action,
+			StateAction::ApplyChanges(StorageChanges::Import(_) | StorageChanges::Remote)
+		)
 	}
 }
 
@@ -93,6 +93,8 @@ pub struct IncomingBlock<B: BlockT> {
 	pub import_existing: bool,
 	/// Do not compute new state, but rather set it to the given set.
 	pub state: Option<ImportedState<B>>,
+	/// Do not compute or download the state, it is fetched from remote peers on demand.
+	pub remote_state: bool,
 }
 
 /// Verify a justification of a block
@@ -101,6 +103,17 @@ pub trait Verifier<B: BlockT>: Send + Sync {
 	/// Verify the given block data and return the `BlockImportParams` to
 	/// continue the block import process.
 	async fn verify(&self, block: BlockImportParams<B>) -> Result<BlockImportParams<B>, String>;
+
+	/// Returns `true` if a block can be verified before its parent is imported.
+	///
+	/// The import queue then verifies the next blocks while the previous ones are imported. This
+	/// only holds for verifiers that don't read the parent block or its state, e.g. to look up
+	/// the authorities or epoch of the block, or to check its inherents. Checks that need the
+	/// parent must then be done by the block import. Whether the parent is known is checked again
+	/// before the import.
+	fn can_verify_ahead(&self) -> bool {
+		false
+	}
 }
 
 /// Blocks import queue API.
@@ -227,11 +240,10 @@ pub async fn import_single_block<B: BlockT, V: Verifier<B>>(
 	block: IncomingBlock<B>,
 	verifier: &V,
 ) -> BlockImportResult<B> {
-	match verify_single_block_metered(import_handle, block_origin, block, verifier, None).await? {
-		SingleBlockVerificationOutcome::Imported(import_status) => Ok(import_status),
-		SingleBlockVerificationOutcome::Verified(import_parameters) =>
-			import_single_block_metered(import_handle, import_parameters, None).await,
-	}
+	let verification =
+		verify_single_block_metered(import_handle, block_origin, block, verifier, false, None)
+			.await;
+	import_verified_block(import_handle, verification, None).await
 }
 
 fn import_handler<Block>(
@@ -288,14 +300,20 @@ pub(crate) struct SingleBlockImportParameters<Block: BlockT> {
 	hash: Block::Hash,
 	block_origin: Option<RuntimeOrigin>,
 	verification_time: Duration,
+	/// Checks to repeat before the import, if the block was verified before its parent was
+	/// imported.
+	recheck: Option<BlockCheckParams<Block>>,
 }
 
-/// Single block import function with metering.
+/// Single block verification function with metering.
+///
+/// `verify_ahead` is set when the block is verified before its parent is imported.
 pub(crate) async fn verify_single_block_metered<B: BlockT, V: Verifier<B>>(
 	import_handle: &impl BlockImport<B, Error = ConsensusError>,
 	block_origin: BlockOrigin,
 	block: IncomingBlock<B>,
 	verifier: &V,
+	verify_ahead: bool,
 	metrics: Option<&Metrics>,
 ) -> Result<SingleBlockVerificationOutcome<B>, BlockImportError> {
 	let peer = block.origin;
@@ -316,6 +334,19 @@ pub(crate) async fn verify_single_block_metered<B: BlockT, V: Verifier<B>>(
 	let hash = block.hash;
 	let parent_hash = *header.parent_hash();
 
+	let check_params = BlockCheckParams {
+		hash,
+		number,
+		parent_hash,
+		allow_missing_state: block.allow_missing_state,
+		import_existing: block.import_existing,
+		allow_missing_parent: block.state.is_some() || block.remote_state,
+	};
+	// The parent of a block verified ahead may not be imported yet, it is checked again once
+	// the block is about to be imported.
+	let recheck =
+		(verify_ahead && !check_params.allow_missing_parent).then(|| check_params.clone());
+
 	match import_handler::<B>(
 		number,
 		hash,
@@ -323,12 +354,8 @@ pub(crate) async fn verify_single_block_metered<B: BlockT, V: Verifier<B>>(
 		peer,
 		import_handle
 			.check_block(BlockCheckParams {
-				hash,
-				number,
-				parent_hash,
-				allow_missing_state: block.allow_missing_state,
-				import_existing: block.import_existing,
-				allow_missing_parent: block.state.is_some(),
+				allow_missing_parent: verify_ahead || check_params.allow_missing_parent,
+				..check_params
 			})
 			.await,
 	)? {
@@ -351,6 +378,9 @@ pub(crate) async fn verify_single_block_metered<B: BlockT, V: Verifier<B>>(
 	if let Some(state) = block.state {
 		let changes = crate::block_import::StorageChanges::Import(state);
 		import_block.state_action = StateAction::ApplyChanges(changes);
+	} else if block.remote_state {
+		import_block.state_action =
+			StateAction::ApplyChanges(crate::block_import::StorageChanges::Remote);
 	} else if block.skip_execution {
 		import_block.state_action = StateAction::Skip;
 	} else if block.allow_missing_state {
@@ -386,22 +416,54 @@ pub(crate) async fn verify_single_block_metered<B: BlockT, V: Verifier<B>>(
 		hash,
 		block_origin: peer,
 		verification_time,
+		recheck,
 	}))
 }
 
+/// Import a block verified by [`verify_single_block_metered`].
+pub(crate) async fn import_verified_block<Block: BlockT>(
+	import_handle: &impl BlockImport<Block, Error = ConsensusError>,
+	verification: Result<SingleBlockVerificationOutcome<Block>, BlockImportError>,
+	metrics: Option<&Metrics>,
+) -> BlockImportResult<Block> {
+	match verification? {
+		SingleBlockVerificationOutcome::Imported(import_status) => Ok(import_status),
+		SingleBlockVerificationOutcome::Verified(import_parameters) =>
+			import_single_block_metered(import_handle, import_parameters, metrics).await,
+	}
+}
+
 pub(crate) async fn import_single_block_metered<Block: BlockT>(
-	import_handle: &mut impl BlockImport<Block, Error = ConsensusError>,
+	import_handle: &impl BlockImport<Block, Error = ConsensusError>,
 	import_parameters: SingleBlockImportParameters<Block>,
 	metrics: Option<&Metrics>,
 ) -> BlockImportResult<Block> {
 	let started = Instant::now();
 
-	let SingleBlockImportParameters { import_block, hash, block_origin, verification_time } =
-		import_parameters;
+	let SingleBlockImportParameters {
+		import_block,
+		hash,
+		block_origin,
+		verification_time,
+		recheck,
+	} = import_parameters;
 
 	let number = *import_block.header.number();
 	let parent_hash = *import_block.header.parent_hash();
 
+	if let Some(check_params) = recheck {
+		match import_handler::<Block>(
+			number,
+			hash,
+			parent_hash,
+			block_origin,
+			import_handle.check_block(check_params).await,
+		)? {
+			BlockImportStatus::ImportedUnknown { .. } => (),
+			r => return Ok(r),
+		}
+	}
+
 	let imported = import_handle.import_block(import_block).await;
 	if let Some(metrics) = metrics {
 		metrics.report_verification_and_import(started.elapsed() + verification_time);
@@ -16,6 +16,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 use futures::{
+	channel::mpsc,
+	future::BoxFuture,
 	prelude::*,
 	task::{Context, Poll},
 };
@@ -27,20 +29,26 @@ use sp_runtime::{
 	traits::{Block as BlockT, Header as HeaderT, NumberFor},
 	Justification, Justifications,
 };
-use std::pin::Pin;
+use std::{pin::Pin, sync::Arc, time::Instant};
 
 use crate::{
 	import_queue::{
 		buffered_link::{self, BufferedLinkReceiver, BufferedLinkSender},
-		import_single_block_metered, verify_single_block_metered, BlockImportError,
-		BlockImportStatus, BoxBlockImport, BoxJustificationImport, ImportQueue, ImportQueueService,
-		IncomingBlock, Link, RuntimeOrigin, SingleBlockVerificationOutcome, Verifier, LOG_TARGET,
+		import_verified_block, verify_single_block_metered, BlockImportError, BlockImportStatus,
+		BoxBlockImport, BoxJustificationImport, ImportQueue, ImportQueueService, IncomingBlock,
+		Link, RuntimeOrigin, SingleBlockVerificationOutcome, Verifier, LOG_TARGET,
 	},
 	metrics::Metrics,
 };
 
+/// Maximum number of batches of blocks verified ahead of their import.
+const MAX_VERIFIED_BATCHES: usize = 4;
+
 /// Interface to a basic block import queue that is importing blocks sequentially in a separate
 /// task, with plugable verification.
+///
+/// If the verifier [can verify ahead](Verifier::can_verify_ahead), the blocks are verified in
+/// another task while the previous blocks are imported.
 pub struct BasicQueue<B: BlockT> {
 	/// Handle for sending justification and block import messages to the background task.
 	handle: BasicQueueHandle<B>,
@@ -80,19 +88,27 @@ impl<B: BlockT> BasicQueue<B> {
 				.ok()
 		});
 
-		let (future, justification_sender, block_import_sender) = BlockImportWorker::new(
-			result_sender,
-			verifier,
-			block_import,
-			justification_import,
-			metrics,
-		);
+		let (future, verification_future, justification_sender, block_import_sender) =
+			BlockImportWorker::new(
+				result_sender,
+				verifier,
+				block_import,
+				justification_import,
+				metrics,
+			);
 
 		spawner.spawn_essential_blocking(
 			"basic-block-import-worker",
 			Some("block-import"),
 			future.boxed(),
 		);
+		if let Some(verification_future) = verification_future {
+			spawner.spawn_essential_blocking(
+				"basic-block-verification-worker",
+				Some("block-import"),
+				verification_future,
+			);
+		}
 
 		Self {
 			handle: BasicQueueHandle::new(justification_sender, block_import_sender),
@@ -211,6 +227,9 @@ mod worker_messages {
 		pub NumberFor<B>,
 		pub Justification,
 	);
+	pub struct VerifiedBlocks<B: BlockT>(
+		pub Vec<(B::Hash, Result<SingleBlockVerificationOutcome<B>, BlockImportError>)>,
+	);
 }
 
 /// The process of importing blocks.
@@ -247,6 +266,117 @@ async fn block_import_process<B: BlockT>(
 	}
 }
 
+/// The verification stage of the pipelined block import.
+///
+/// This polls the `block_import_receiver` for new blocks to import and verifies them, without
+/// waiting for the previous blocks to be imported. The verified blocks are sent to
+/// [`verified_block_import_process`].
+///
+/// Returns when `block_import_receiver` is closed or the import stage ended.
+async fn block_verification_process<B: BlockT>(
+	block_import: Arc<BoxBlockImport<B>>,
+	verifier: impl Verifier<B>,
+	mut block_import_receiver: TracingUnboundedReceiver<worker_messages::ImportBlocks<B>>,
+	mut verified_sender: mpsc::Sender<worker_messages::VerifiedBlocks<B>>,
+	metrics: Option<Metrics>,
+) {
+	loop {
+		let worker_messages::ImportBlocks(origin, blocks) = match block_import_receiver.next().await
+		{
+			Some(blocks) => blocks,
+			None => {
+				log::debug!(
+					target: LOG_TARGET,
+					"Stopping block verification because the import channel was closed!",
+				);
+				return
+			},
+		};
+
+		let mut verified = Vec::with_capacity(blocks.len());
+		let mut has_error = false;
+		for block in blocks {
+			let block_hash = block.hash;
+			let verification = if has_error {
+				Err(BlockImportError::Cancelled)
+			} else {
+				verify_single_block_metered(
+					&*block_import,
+					origin,
+					block,
+					&verifier,
+					true,
+					metrics.as_ref(),
+				)
+				.await
+			};
+			has_error |= verification.is_err();
+			verified.push((block_hash, verification));
+
+			Yield::new().await
+		}
+
+		if verified_sender.send(worker_messages::VerifiedBlocks(verified)).await.is_err() {
+			log::debug!(
+				target: LOG_TARGET,
+				"Stopping block verification because the import stage ended!",
+			);
+			return
+		}
+	}
+}
+
+/// The import stage of the pipelined block import.
+///
+/// Imports the blocks verified by [`block_verification_process`] in order.
+///
+/// Returns when the verification stage ended.
+async fn verified_block_import_process<B: BlockT>(
+	block_import: Arc<BoxBlockImport<B>>,
+	result_sender: BufferedLinkSender<B>,
+	mut verified_receiver: mpsc::Receiver<worker_messages::VerifiedBlocks<B>>,
+	metrics: Option<Metrics>,
+) {
+	while let Some(worker_messages::VerifiedBlocks(blocks)) = verified_receiver.next().await {
+		let started = Instant::now();
+		let block_count = blocks.len();
+		let mut imported = 0;
+		let mut results = Vec::with_capacity(block_count);
+		let mut has_error = false;
+
+		for (block_hash, verification) in blocks {
+			let import_result = if has_error {
+				Err(BlockImportError::Cancelled)
+			} else {
+				import_verified_block(&*block_import, verification, metrics.as_ref()).await
+			};
+
+			if let Some(metrics) = metrics.as_ref() {
+				metrics.report_import::<B>(&import_result);
+			}
+
+			if import_result.is_ok() {
+				imported += 1;
+			} else {
+				has_error = true;
+			}
+			results.push((import_result, block_hash));
+
+			Yield::new().await
+		}
+
+		if let Some(metrics) = metrics.as_ref() {
+			metrics.report_batch_import(imported, started.elapsed());
+		}
+		result_sender.blocks_processed(imported, block_count, results);
+	}
+
+	log::debug!(
+		target: LOG_TARGET,
+		"Stopping block import because the verification stage ended!",
+	);
+}
+
 struct BlockImportWorker<B: BlockT> {
 	result_sender: BufferedLinkSender<B>,
 	justification_import: Option<BoxJustificationImport<B>>,
@@ -254,6 +384,8 @@ struct BlockImportWorker<B: BlockT> {
 }
 
 impl<B: BlockT> BlockImportWorker<B> {
+	/// Returns the worker and, if the blocks are verified ahead of their import, the verification
+	/// worker that must be spawned in another task.
 	fn new<V>(
 		result_sender: BufferedLinkSender<B>,
 		verifier: V,
@@ -262,6 +394,7 @@ impl<B: BlockT> BlockImportWorker<B> {
 		metrics: Option<Metrics>,
 	) -> (
 		impl Future<Output = ()> + Send,
+		Option<BoxFuture<'static, ()>>,
 		TracingUnboundedSender<worker_messages::ImportJustification<B>>,
 		TracingUnboundedSender<worker_messages::ImportBlocks<B>>,
 	)
@@ -276,6 +409,34 @@ impl<B: BlockT> BlockImportWorker<B> {
 		let (block_import_sender, block_import_receiver) =
 			tracing_unbounded("mpsc_import_queue_worker_blocks", 100_000);
 
+		let (block_import_process, block_verification_process) = if verifier.can_verify_ahead() {
+			let block_import = Arc::new(block_import);
+			let (verified_sender, verified_receiver) = mpsc::channel(MAX_VERIFIED_BATCHES);
+			let block_verification_process = block_verification_process(
+				block_import.clone(),
+				verifier,
+				block_import_receiver,
+				verified_sender,
+				metrics.clone(),
+			);
+			let block_import_process = verified_block_import_process(
+				block_import,
+				result_sender.clone(),
+				verified_receiver,
+				metrics.clone(),
+			);
+			(block_import_process.boxed(), Some(block_verification_process.boxed()))
+		} else {
+			let block_import_process = block_import_process(
+				block_import,
+				verifier,
+				result_sender.clone(),
+				block_import_receiver,
+				metrics.clone(),
+			);
+			(block_import_process.boxed(), None)
+		};
+
 		let mut worker = BlockImportWorker { result_sender, justification_import, metrics };
 
 		let future = async move {
@@ -286,13 +447,6 @@ impl<B: BlockT> BlockImportWorker<B> {
 				}
 			}
 
-			let block_import_process = block_import_process(
-				block_import,
-				verifier,
-				worker.result_sender.clone(),
-				block_import_receiver,
-				worker.metrics.clone(),
-			);
 			futures::pin_mut!(block_import_process);
 
 			loop {
@@ -330,7 +484,7 @@ impl<B: BlockT> BlockImportWorker<B> {
 			}
 		};
 
-		(future, justification_sender, block_import_sender)
+		(future, block_verification_process, justification_sender, block_import_sender)
 	}
 
 	async fn import_justification(
@@ -403,6 +557,7 @@ async fn import_many_blocks<B: BlockT, V: Verifier<B>>(
 
 	trace!(target: LOG_TARGET, "Starting import of {} blocks {}", count, blocks_range);
 
+	let started = Instant::now();
 	let mut imported = 0;
 	let mut results = vec![];
 	let mut has_error = false;
@@ -415,6 +570,9 @@ async fn import_many_blocks<B: BlockT, V: Verifier<B>>(
 			Some(b) => b,
 			None => {
 				// No block left to import, success!
+				if let Some(metrics) = metrics.as_ref() {
+					metrics.report_batch_import(imported, started.elapsed());
+				}
 				return ImportManyBlocksResult { block_count: count, imported, results }
 			},
 		};
@@ -424,22 +582,17 @@ async fn import_many_blocks<B: BlockT, V: Verifier<B>>(
 		let import_result = if has_error {
 			Err(BlockImportError::Cancelled)
 		} else {
-			let verification_fut = verify_single_block_metered(
+			let verification = verify_single_block_metered(
 				import_handle,
 				blocks_origin,
 				block,
 				verifier,
+				false,
 				metrics.as_ref(),
-			);
-			match verification_fut.await {
-				Ok(SingleBlockVerificationOutcome::Imported(import_status)) => Ok(import_status),
-				Ok(SingleBlockVerificationOutcome::Verified(import_parameters)) => {
-					// The actual import.
-					import_single_block_metered(import_handle, import_parameters, metrics.as_ref())
-						.await
-				},
-				Err(e) => Err(e),
-			}
+			)
+			.await;
+			// The actual import.
+			import_verified_block(import_handle, verification, metrics.as_ref()).await
 		};
 
 		if let Some(metrics) = metrics.as_ref() {
@@ -514,6 +667,22 @@ mod tests {
 		}
 	}
 
+	struct VerifyAhead;
+
+	#[async_trait::async_trait]
+	impl Verifier<Block> for VerifyAhead {
+		async fn verify(
+			&self,
+			block: BlockImportParams<Block>,
+		) -> Result<BlockImportParams<Block>, String> {
+			Ok(BlockImportParams::new(block.origin, block.header))
+		}
+
+		fn can_verify_ahead(&self) -> bool {
+			true
+		}
+	}
+
 	#[async_trait::async_trait]
 	impl BlockImport<Block> for () {
 		type Error = sp_consensus::Error;
@@ -589,9 +758,10 @@ mod tests {
 	fn prioritizes_finality_work_over_block_import() {
 		let (result_sender, mut result_port) = buffered_link::buffered_link(100_000);
 
-		let (worker, finality_sender, block_import_sender) =
+		let (worker, verification_worker, finality_sender, block_import_sender) =
 			BlockImportWorker::new(result_sender, (), Box::new(()), Some(Box::new(())), None);
 		futures::pin_mut!(worker);
+		assert!(verification_worker.is_none());
 
 		let import_block = |n| {
 			let header = Header {
@@ -617,6 +787,7 @@ mod tests {
 						allow_missing_state: false,
 						import_existing: false,
 						state: None,
+						remote_state: false,
 						skip_execution: false,
 					}],
 				))
@@ -682,4 +853,80 @@ mod tests {
 			]
 		);
 	}
+
+	#[test]
+	fn verifies_blocks_ahead_of_import() {
+		let (result_sender, mut result_port) = buffered_link::buffered_link(100_000);
+
+		let (worker, verification_worker, _finality_sender, block_import_sender) =
+			BlockImportWorker::new(result_sender, VerifyAhead, Box::new(()), None, None);
+		futures::pin_mut!(worker);
+		let mut verification_worker =
+			verification_worker.expect("The verifier can verify ahead; qed");
+
+		let import_blocks = |numbers: std::ops::Range<BlockNumber>| {
+			let blocks = numbers
+				.map(|n| {
+					let header = Header {
+						parent_hash: Hash::random(),
+						number: n,
+						extrinsics_root: Hash::random(),
+						state_root: Default::default(),
+						digest: Default::default(),
+					};
+					IncomingBlock {
+						hash: header.hash(),
+						header: Some(header),
+						body: None,
+						indexed_body: None,
+						justifications: None,
+						origin: None,
+						allow_missing_state: false,
+						import_existing: false,
+						state: None,
+						remote_state: false,
+						skip_execution: false,
+					}
+				})
+				.collect::<Vec<_>>();
+			let hash = blocks[0].hash;
+
+			block_import_sender
+				.unbounded_send(worker_messages::ImportBlocks(BlockOrigin::Own, blocks))
+				.unwrap();
+
+			hash
+		};
+
+		let link = TestLink::default();
+
+		let batch1 = import_blocks(1..4);
+		let batch2 = import_blocks(4..7);
+		let batch3 = import_blocks(7..10);
+
+		block_on(futures::future::poll_fn(|cx| {
+			while link.events.lock().len() < 3 {
+				if let Poll::Ready(()) = Future::poll(Pin::new(&mut worker), cx) {
+					panic!("import queue worker should not conclude.");
+				}
+				if let Poll::Ready(()) = verification_worker.poll_unpin(cx) {
+					panic!("verification worker should not conclude.");
+				}
+
+				result_port.poll_actions(cx, &link).unwrap();
+			}
+
+			Poll::Ready(())
+		}));
+
+		// The batches are imported in order.
+		assert_eq!(
+			&*link.events.lock(),
+			&[
+				Event::BlockImported(batch1),
+				Event::BlockImported(batch2),
+				Event::BlockImported(batch3),
+			]
+		);
+	}
 }
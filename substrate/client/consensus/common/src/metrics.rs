@@ -19,8 +19,8 @@
 //! Metering tools for consensus
 
 use prometheus_endpoint::{
-	register, CounterVec, Histogram, HistogramOpts, HistogramVec, Opts, PrometheusError, Registry,
-	U64,
+	register, CounterVec, Gauge, Histogram, HistogramOpts, HistogramVec, Opts, PrometheusError,
+	Registry, F64, U64,
 };
 
 use sp_runtime::traits::{Block as BlockT, NumberFor};
@@ -34,6 +34,7 @@ pub(crate) struct Metrics {
 	pub block_verification_time: HistogramVec,
 	pub block_verification_and_import_time: Histogram,
 	pub justification_import_time: Histogram,
+	pub import_queue_throughput: Gauge<F64>,
 }
 
 impl Metrics {
@@ -73,6 +74,13 @@ impl Metrics {
 				))?,
 				registry,
 			)?,
+			import_queue_throughput: register(
+				Gauge::new(
+					"substrate_import_queue_throughput",
+					"Blocks imported per second in the last batch of blocks",
+				)?,
+				registry,
+			)?,
 		})
 	}
 
@@ -103,4 +111,10 @@ impl Metrics {
 	pub fn report_verification_and_import(&self, time: std::time::Duration) {
 		self.block_verification_and_import_time.observe(time.as_secs_f64());
 	}
+
+	pub fn report_batch_import(&self, imported: usize, time: std::time::Duration) {
+		if !time.is_zero() {
+			self.import_queue_throughput.set(imported as f64 / time.as_secs_f64());
+		}
+	}
 }
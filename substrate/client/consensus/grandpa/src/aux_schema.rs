@@ -46,6 +46,8 @@ const SET_STATE_KEY: &[u8] = b"grandpa_completed_round";
 const CONCLUDED_ROUNDS: &[u8] = b"grandpa_concluded_rounds";
 const AUTHORITY_SET_KEY: &[u8] = b"grandpa_voters";
 const BEST_JUSTIFICATION: &[u8] = b"grandpa_best_justification";
+const WARP_SYNC_FRAGMENT_INDEX: &[u8] = b"grandpa_warp_sync_fragment_index";
+const WARP_SYNC_FRAGMENT: &[u8] = b"grandpa_warp_sync_fragment";
 
 const CURRENT_VERSION: u32 = 3;
 
@@ -471,6 +473,47 @@ where
 	load_decode::<_, GrandpaJustification<Block>>(backend, BEST_JUSTIFICATION)
 }
 
+fn warp_sync_fragment_key(set_id: SetId) -> Vec<u8> {
+	let mut key = WARP_SYNC_FRAGMENT.to_vec();
+	set_id.using_encoded(|n| key.extend(n));
+	key
+}
+
+/// Load the index of the cached warp sync fragments.
+pub(crate) fn load_warp_sync_fragment_index<B: AuxStore, T: Decode>(
+	backend: &B,
+) -> ClientResult<Option<T>> {
+	load_decode(backend, WARP_SYNC_FRAGMENT_INDEX)
+}
+
+/// Load the encoded warp sync fragment cached for the given set.
+pub(crate) fn load_warp_sync_fragment<B: AuxStore>(
+	backend: &B,
+	set_id: SetId,
+) -> ClientResult<Option<Vec<u8>>> {
+	backend.get_aux(&warp_sync_fragment_key(set_id))
+}
+
+/// Write the encoded warp sync fragments of the given sets, along with the updated index.
+pub(crate) fn write_warp_sync_fragments<B: AuxStore, T: Encode>(
+	backend: &B,
+	index: &T,
+	fragments: &[(SetId, Vec<u8>)],
+) -> ClientResult<()> {
+	let keys = fragments
+		.iter()
+		.map(|(set_id, _)| warp_sync_fragment_key(*set_id))
+		.collect::<Vec<_>>();
+	let encoded_index = index.encode();
+	let insert = keys
+		.iter()
+		.zip(fragments)
+		.map(|(key, (_, fragment))| (&key[..], &fragment[..]))
+		.chain(std::iter::once((WARP_SYNC_FRAGMENT_INDEX, &encoded_index[..])))
+		.collect::<Vec<_>>();
+	backend.insert_aux(&insert, &[])
+}
+
 /// Write voter set state.
 pub(crate) fn write_voter_set_state<Block: BlockT, B: AuxStore>(
 	backend: &B,
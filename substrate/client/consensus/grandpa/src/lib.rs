@@ -724,6 +724,10 @@ pub fn grandpa_peers_set_config<B: BlockT, N: NetworkBackend<B, <B as BlockT>::H
 			out_peers: 0,
 			reserved_nodes: Vec::new(),
 			non_reserved_mode: sc_network::config::NonReservedPeerMode::Deny,
+			bandwidth: sc_network::config::NotificationBandwidth {
+				priority: sc_network::config::NotificationPriority::High,
+				..Default::default()
+			},
 		},
 		metrics,
 		peer_store_handle,
@@ -19,9 +19,12 @@
 use codec::{Decode, DecodeAll, Encode};
 
 use crate::{
-	best_justification, find_scheduled_change, AuthoritySetChanges, AuthoritySetHardFork,
-	BlockNumberOps, GrandpaJustification, SharedAuthoritySet,
+	aux_schema, best_justification, find_scheduled_change, AuthoritySetChanges,
+	AuthoritySetHardFork, BlockNumberOps, GrandpaJustification, SharedAuthoritySet, LOG_TARGET,
 };
+use log::debug;
+use parking_lot::Mutex;
+use prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
 use sc_client_api::Backend as ClientBackend;
 use sc_network_sync::strategy::warp::{EncodedProof, VerificationResult, WarpSyncProvider};
 use sp_blockchain::{Backend as BlockchainBackend, HeaderBackend};
@@ -31,7 +34,10 @@ use sp_runtime::{
 	traits::{Block as BlockT, Header as HeaderT, NumberFor, One},
 };
 
-use std::{collections::HashMap, sync::Arc};
+use std::{
+	collections::{BTreeMap, HashMap},
+	sync::Arc,
+};
 
 /// Warp proof processing error.
 #[derive(Debug, thiserror::Error)]
@@ -67,6 +73,118 @@ pub struct WarpSyncFragment<Block: BlockT> {
 	pub justification: GrandpaJustification<Block>,
 }
 
+/// Index entry of a cached [`WarpSyncFragment`].
+#[derive(Clone, Decode, Encode)]
+struct CachedFragment<Hash> {
+	/// Hash of the last block of the set.
+	hash: Hash,
+	/// Encoded size of the fragment, `None` if the set didn't end with a standard change and
+	/// there is no fragment for it.
+	size: Option<u32>,
+}
+
+/// Cache of the fragments proving the authority set changes, which never change once the
+/// change is finalized.
+///
+/// The encoded fragments are kept in aux storage, while their index is kept in memory so that
+/// the size of a proof is known without reading the fragments that don't fit in it. The index is
+/// loaded from aux storage on the first lookup, and is only locked while it is looked up or
+/// updated, so that proofs can be generated concurrently.
+pub(crate) struct WarpSyncFragmentCache<Block: BlockT> {
+	state: Mutex<Option<CacheState<Block::Hash>>>,
+}
+
+/// The index of a [`WarpSyncFragmentCache`], with the changes not written to aux storage yet.
+struct CacheState<Hash> {
+	index: BTreeMap<SetId, CachedFragment<Hash>>,
+	/// Fragments to write to aux storage.
+	pending: Vec<(SetId, Vec<u8>)>,
+	/// Whether the index has to be written to aux storage.
+	dirty: bool,
+}
+
+impl<Block: BlockT> Default for WarpSyncFragmentCache<Block> {
+	fn default() -> Self {
+		Self { state: Mutex::new(None) }
+	}
+}
+
+impl<Block: BlockT> WarpSyncFragmentCache<Block> {
+	/// Look up the fragment of the set `set_id`, whose last block is `hash`. The fragment is only
+	/// read if it is smaller than `max_size`.
+	fn get<Backend: ClientBackend<Block>>(
+		&self,
+		backend: &Backend,
+		set_id: SetId,
+		hash: Block::Hash,
+		max_size: usize,
+	) -> Result<CacheLookup<Block>, Error> {
+		let size = {
+			let mut state = self.state.lock();
+			if state.is_none() {
+				let index = aux_schema::load_warp_sync_fragment_index(backend)?.unwrap_or_default();
+				*state = Some(CacheState { index, pending: Vec::new(), dirty: false });
+			}
+			let state = state.as_ref().expect("state is loaded above; qed");
+			// The hash differs if the set was cached on a chain that has been reverted since.
+			match state.index.get(&set_id).filter(|cached| cached.hash == hash) {
+				Some(cached) => cached.size,
+				None => return Ok(CacheLookup::Miss),
+			}
+		};
+		let Some(size) = size.map(|size| size as usize) else { return Ok(CacheLookup::NoFragment) };
+		if size >= max_size {
+			return Ok(CacheLookup::TooLarge)
+		}
+		match aux_schema::load_warp_sync_fragment(backend, set_id)? {
+			Some(encoded) =>
+				Ok(CacheLookup::Fragment(WarpSyncFragment::decode_all(&mut &encoded[..])?, size)),
+			None => Ok(CacheLookup::Miss),
+		}
+	}
+
+	/// Cache the `fragment` of the set `set_id`, `None` if the set has no fragment.
+	///
+	/// Nothing is cached if the index hasn't been loaded by a lookup yet.
+	fn insert(&self, set_id: SetId, hash: Block::Hash, fragment: Option<&WarpSyncFragment<Block>>) {
+		let encoded = fragment.map(Encode::encode);
+		let size = encoded.as_ref().map(|encoded| encoded.len() as u32);
+		if let Some(state) = self.state.lock().as_mut() {
+			state.index.insert(set_id, CachedFragment { hash, size });
+			state.pending.extend(encoded.map(|encoded| (set_id, encoded)));
+			state.dirty = true;
+		}
+	}
+
+	/// Write the fragments cached since the last flush to aux storage.
+	///
+	/// Concurrent flushes may write an older index last, which only causes cache misses once the
+	/// index is loaded again.
+	fn flush<Backend: ClientBackend<Block>>(&self, backend: &Backend) -> Result<(), Error> {
+		let (index, pending) = match self.state.lock().as_mut() {
+			Some(state) if state.dirty => {
+				state.dirty = false;
+				(state.index.clone(), std::mem::take(&mut state.pending))
+			},
+			_ => return Ok(()),
+		};
+		aux_schema::write_warp_sync_fragments(backend, &index, &pending)?;
+		Ok(())
+	}
+}
+
+/// Result of a lookup in the [`WarpSyncFragmentCache`].
+enum CacheLookup<Block: BlockT> {
+	/// The fragment isn't cached.
+	Miss,
+	/// The set didn't end with a standard change.
+	NoFragment,
+	/// The fragment doesn't fit in the proof.
+	TooLarge,
+	/// The fragment, with its encoded size.
+	Fragment(WarpSyncFragment<Block>, usize),
+}
+
 /// An accumulated proof of multiple authority set changes.
 #[derive(Decode, Encode)]
 pub struct WarpSyncProof<Block: BlockT> {
@@ -78,15 +196,19 @@ impl<Block: BlockT> WarpSyncProof<Block> {
 	/// Generates a warp sync proof starting at the given block. It will generate authority set
 	/// change proofs for all changes that happened from `begin` until the current authority set
 	/// (capped by MAX_WARP_SYNC_PROOF_SIZE).
+	///
+	/// The authority set change proofs are taken from `cache` when possible, and the ones that
+	/// had to be generated are added to it.
 	fn generate<Backend>(
 		backend: &Backend,
 		begin: Block::Hash,
 		set_changes: &AuthoritySetChanges<NumberFor<Block>>,
+		cache: &WarpSyncFragmentCache<Block>,
+		metrics: Option<&Metrics>,
 	) -> Result<WarpSyncProof<Block>, Error>
 	where
 		Backend: ClientBackend<Block>,
 	{
-		// TODO: cache best response (i.e. the one with lowest begin_number)
 		let blockchain = backend.blockchain();
 
 		let begin_number = blockchain
@@ -115,45 +237,76 @@ impl<Block: BlockT> WarpSyncProof<Block> {
 
 		let set_changes = set_changes.iter_from(begin_number).ok_or(Error::MissingData)?;
 
-		for (_, last_block) in set_changes {
+		for (set_id, last_block) in set_changes {
 			let hash = blockchain.block_hash_from_id(&BlockId::Number(*last_block))?
 				.expect("header number comes from previously applied set changes; corresponding hash must exist in db; qed.");
 
-			let header = blockchain
-				.header(hash)?
-				.expect("header hash obtained from header number exists in db; corresponding header must exist in db too; qed.");
-
-			// the last block in a set is the one that triggers a change to the next set,
-			// therefore the block must have a digest that signals the authority set change
-			if find_scheduled_change::<Block>(&header).is_none() {
-				// if it doesn't contain a signal for standard change then the set must have changed
-				// through a forced changed, in which case we stop collecting proofs as the chain of
-				// trust in authority handoffs was broken.
-				break
-			}
-
-			let justification = blockchain
-				.justifications(header.hash())?
-				.and_then(|just| just.into_justification(GRANDPA_ENGINE_ID))
-				.ok_or_else(|| Error::MissingData)?;
-
-			let justification = GrandpaJustification::<Block>::decode_all(&mut &justification[..])?;
+			// We remove some bytes from the maximum size, because we're only counting the size of
+			// the `WarpSyncFragment`s. The extra margin is here to leave room for rest of the data
+			// (the size of the `Vec` and the boolean).
+			let max_size = MAX_WARP_SYNC_PROOF_SIZE - 50 - proofs_encoded_len;
 
-			let proof = WarpSyncFragment { header: header.clone(), justification };
-			let proof_size = proof.encoded_size();
-
-			// Check for the limit. We remove some bytes from the maximum size, because we're only
-			// counting the size of the `WarpSyncFragment`s. The extra margin is here to leave
-			// room for rest of the data (the size of the `Vec` and the boolean).
-			if proofs_encoded_len + proof_size >= MAX_WARP_SYNC_PROOF_SIZE - 50 {
-				proof_limit_reached = true;
-				break
+			let cached = cache.get(backend, *set_id, hash, max_size)?;
+			if let Some(metrics) = metrics {
+				match cached {
+					CacheLookup::Miss => metrics.warp_sync_fragment_cache_misses.inc(),
+					_ => metrics.warp_sync_fragment_cache_hits.inc(),
+				}
 			}
 
+			let (proof, proof_size) = match cached {
+				CacheLookup::Fragment(proof, proof_size) => (proof, proof_size),
+				CacheLookup::TooLarge => {
+					proof_limit_reached = true;
+					break
+				},
+				// the set is known to have changed through a forced change.
+				CacheLookup::NoFragment => break,
+				CacheLookup::Miss => {
+					let header = blockchain
+						.header(hash)?
+						.expect("header hash obtained from header number exists in db; corresponding header must exist in db too; qed.");
+
+					// the last block in a set is the one that triggers a change to the next set,
+					// therefore the block must have a digest that signals the authority set change
+					if find_scheduled_change::<Block>(&header).is_none() {
+						// if it doesn't contain a signal for standard change then the set must have
+						// changed through a forced changed, in which case we stop collecting proofs
+						// as the chain of trust in authority handoffs was broken.
+						cache.insert(*set_id, hash, None);
+						break
+					}
+
+					let justification = blockchain
+						.justifications(header.hash())?
+						.and_then(|just| just.into_justification(GRANDPA_ENGINE_ID))
+						.ok_or_else(|| Error::MissingData)?;
+
+					let justification =
+						GrandpaJustification::<Block>::decode_all(&mut &justification[..])?;
+
+					let proof = WarpSyncFragment { header: header.clone(), justification };
+					cache.insert(*set_id, hash, Some(&proof));
+					let proof_size = proof.encoded_size();
+
+					// Check for the limit.
+					if proof_size >= max_size {
+						proof_limit_reached = true;
+						break
+					}
+
+					(proof, proof_size)
+				},
+			};
+
 			proofs_encoded_len += proof_size;
 			proofs.push(proof);
 		}
 
+		if let Err(err) = cache.flush(backend) {
+			debug!(target: LOG_TARGET, "Failed to write warp sync fragments to aux storage: {}", err);
+		}
+
 		let is_finished = if proof_limit_reached {
 			false
 		} else {
@@ -235,6 +388,33 @@ impl<Block: BlockT> WarpSyncProof<Block> {
 	}
 }
 
+/// Prometheus metrics for serving warp sync proofs.
+pub(crate) struct Metrics {
+	warp_sync_fragment_cache_hits: Counter<U64>,
+	warp_sync_fragment_cache_misses: Counter<U64>,
+}
+
+impl Metrics {
+	pub(crate) fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			warp_sync_fragment_cache_hits: register(
+				Counter::new(
+					"substrate_finality_grandpa_warp_sync_fragment_cache_hits_total",
+					"Total number of warp sync proof fragments served from the cache.",
+				)?,
+				registry,
+			)?,
+			warp_sync_fragment_cache_misses: register(
+				Counter::new(
+					"substrate_finality_grandpa_warp_sync_fragment_cache_misses_total",
+					"Total number of warp sync proof fragments generated from the database.",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
 /// Implements network API for warp sync.
 pub struct NetworkProvider<Block: BlockT, Backend: ClientBackend<Block>>
 where
@@ -243,6 +423,8 @@ where
 	backend: Arc<Backend>,
 	authority_set: SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
 	hard_forks: HashMap<(Block::Hash, NumberFor<Block>), (SetId, AuthorityList)>,
+	cache: WarpSyncFragmentCache<Block>,
+	metrics: Option<Metrics>,
 }
 
 impl<Block: BlockT, Backend: ClientBackend<Block>> NetworkProvider<Block, Backend>
@@ -262,8 +444,23 @@ where
 				.into_iter()
 				.map(|fork| (fork.block, (fork.set_id, fork.authorities)))
 				.collect(),
+			cache: Default::default(),
+			metrics: None,
 		}
 	}
+
+	/// Report the metrics of the warp sync fragment cache to the given registry.
+	pub fn with_prometheus_registry(mut self, prometheus_registry: Option<&Registry>) -> Self {
+		self.metrics = match prometheus_registry.map(Metrics::register) {
+			Some(Ok(metrics)) => Some(metrics),
+			Some(Err(e)) => {
+				debug!(target: LOG_TARGET, "Failed to register metrics: {:?}", e);
+				None
+			},
+			None => None,
+		};
+		self
+	}
 }
 
 impl<Block: BlockT, Backend: ClientBackend<Block>> WarpSyncProvider<Block>
@@ -279,6 +476,8 @@ where
 			&*self.backend,
 			start,
 			&self.authority_set.authority_set_changes(),
+			&self.cache,
+			self.metrics.as_ref(),
 		)
 		.map_err(Box::new)?;
 		Ok(EncodedProof(proof.encode()))
@@ -318,7 +517,7 @@ where
 
 #[cfg(test)]
 mod tests {
-	use super::WarpSyncProof;
+	use super::{Metrics, WarpSyncFragmentCache, WarpSyncProof};
 	use crate::{AuthoritySetChanges, GrandpaJustification};
 	use codec::Encode;
 	use rand::prelude::*;
@@ -329,26 +528,25 @@ mod tests {
 	use sp_keyring::Ed25519Keyring;
 	use std::sync::Arc;
 	use substrate_test_runtime_client::{
-		BlockBuilderExt, ClientBlockImportExt, ClientExt, DefaultTestClientBuilderExt,
-		TestClientBuilder, TestClientBuilderExt,
+		runtime::Block, Backend, BlockBuilderExt, Client, ClientBlockImportExt, ClientExt,
+		DefaultTestClientBuilderExt, TestClientBuilder, TestClientBuilderExt,
 	};
 
-	#[test]
-	fn warp_sync_proof_generate_verify() {
+	/// Import 100 blocks, with an authority set change every 10 blocks.
+	///
+	/// Returns the authority set changes, along with the last set id and authorities.
+	fn import_blocks_with_set_changes(
+		client: &Arc<Client<Backend>>,
+	) -> (AuthoritySetChanges<u64>, u64, Vec<Ed25519Keyring>) {
 		let mut rng = rand::rngs::StdRng::from_seed([0; 32]);
-		let builder = TestClientBuilder::new();
-		let backend = builder.backend();
-		let client = Arc::new(builder.build());
-
 		let available_authorities = Ed25519Keyring::iter().collect::<Vec<_>>();
-		let genesis_authorities = vec![(Ed25519Keyring::Alice.public().into(), 1)];
 
 		let mut current_authorities = vec![Ed25519Keyring::Alice];
 		let mut current_set_id = 0;
 		let mut authority_set_changes = Vec::new();
 
 		for n in 1..=100 {
-			let mut builder = BlockBuilderBuilder::new(&*client)
+			let mut builder = BlockBuilderBuilder::new(&**client)
 				.on_parent_block(client.chain_info().best_hash)
 				.with_parent_block_number(client.chain_info().best_number)
 				.build()
@@ -413,7 +611,7 @@ mod tests {
 
 				let commit = finality_grandpa::Commit { target_hash, target_number, precommits };
 
-				let justification = GrandpaJustification::from_commit(&client, 42, commit).unwrap();
+				let justification = GrandpaJustification::from_commit(client, 42, commit).unwrap();
 
 				client
 					.finalize_block(target_hash, Some((GRANDPA_ENGINE_ID, justification.encode())))
@@ -426,13 +624,30 @@ mod tests {
 			}
 		}
 
-		let authority_set_changes = AuthoritySetChanges::from(authority_set_changes);
+		(AuthoritySetChanges::from(authority_set_changes), current_set_id, current_authorities)
+	}
+
+	#[test]
+	fn warp_sync_proof_generate_verify() {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let client = Arc::new(builder.build());
+
+		let genesis_authorities = vec![(Ed25519Keyring::Alice.public().into(), 1)];
+		let (authority_set_changes, current_set_id, current_authorities) =
+			import_blocks_with_set_changes(&client);
 
 		// generate a warp sync proof
 		let genesis_hash = client.hash(0).unwrap().unwrap();
 
-		let warp_sync_proof =
-			WarpSyncProof::generate(&*backend, genesis_hash, &authority_set_changes).unwrap();
+		let warp_sync_proof = WarpSyncProof::generate(
+			&*backend,
+			genesis_hash,
+			&authority_set_changes,
+			&Default::default(),
+			None,
+		)
+		.unwrap();
 
 		// verifying the proof should yield the last set id and authorities
 		let (new_set_id, new_authorities) =
@@ -446,4 +661,52 @@ mod tests {
 		assert_eq!(new_set_id, current_set_id);
 		assert_eq!(new_authorities, expected_authorities);
 	}
+
+	#[test]
+	fn warp_sync_proof_fragments_are_cached() {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let client = Arc::new(builder.build());
+		let (authority_set_changes, _, _) = import_blocks_with_set_changes(&client);
+		let genesis_hash = client.hash(0).unwrap().unwrap();
+
+		let registry = prometheus_endpoint::Registry::new();
+		let metrics = Metrics::register(&registry).unwrap();
+		let generate = |cache: &WarpSyncFragmentCache<Block>| {
+			WarpSyncProof::generate(
+				&*backend,
+				genesis_hash,
+				&authority_set_changes,
+				cache,
+				Some(&metrics),
+			)
+			.unwrap()
+			.encode()
+		};
+
+		// all the fragments are generated on the first request.
+		let cache = WarpSyncFragmentCache::default();
+		let proof = generate(&cache);
+		assert_eq!(metrics.warp_sync_fragment_cache_hits.get(), 0);
+		assert_eq!(metrics.warp_sync_fragment_cache_misses.get(), 10);
+
+		// and served from the cache afterwards, also after a restart.
+		assert_eq!(generate(&cache), proof);
+		assert_eq!(generate(&WarpSyncFragmentCache::default()), proof);
+		assert_eq!(metrics.warp_sync_fragment_cache_hits.get(), 20);
+		assert_eq!(metrics.warp_sync_fragment_cache_misses.get(), 10);
+
+		// proofs starting later only use the fragments of the following sets.
+		let hash_of_55 = client.hash(55).unwrap().unwrap();
+		let proof = WarpSyncProof::<Block>::generate(
+			&*backend,
+			hash_of_55,
+			&authority_set_changes,
+			&cache,
+			Some(&metrics),
+		)
+		.unwrap();
+		assert_eq!(proof.proofs.len(), 5);
+		assert_eq!(metrics.warp_sync_fragment_cache_hits.get(), 25);
+	}
 }
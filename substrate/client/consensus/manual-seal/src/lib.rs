@@ -35,11 +35,13 @@ use sp_consensus::{Environment, Proposer, SelectChain};
 use sp_core::traits::SpawnNamed;
 use sp_inherents::CreateInherentDataProviders;
 use sp_runtime::{traits::Block as BlockT, ConsensusEngineId};
-use std::{marker::PhantomData, sync::Arc, time::Duration};
+use std::{collections::BTreeMap, marker::PhantomData, sync::Arc, time::Duration};
 
 mod error;
 mod finalize_block;
+mod revert;
 mod seal_block;
+mod time_travel;
 
 pub mod consensus;
 pub mod rpc;
@@ -51,7 +53,7 @@ pub use self::{
 	rpc::{CreatedBlock, EngineCommand},
 	seal_block::{seal_block, SealBlockParams, MAX_PROPOSAL_DURATION},
 };
-use sc_transaction_pool_api::TransactionPool;
+use sc_transaction_pool_api::{MaintainedTransactionPool, TransactionPool};
 use sp_api::ProvideRuntimeApi;
 
 const LOG_TARGET: &str = "manual-seal";
@@ -59,6 +61,9 @@ const LOG_TARGET: &str = "manual-seal";
 /// The `ConsensusEngineId` of Manual Seal.
 pub const MANUAL_SEAL_ENGINE_ID: ConsensusEngineId = [b'm', b'a', b'n', b'l'];
 
+/// The maximum number of blocks sealed by a single [`EngineCommand::Mine`].
+pub const MAX_MINED_BLOCKS: u32 = 1024;
+
 /// The verifier for the manual seal engine; instantly finalizes.
 struct ManualSealVerifier;
 
@@ -72,6 +77,10 @@ impl<B: BlockT> Verifier<B> for ManualSealVerifier {
 		block.fork_choice = Some(ForkChoiceStrategy::LongestChain);
 		Ok(block)
 	}
+
+	fn can_verify_ahead(&self) -> bool {
+		true
+	}
 }
 
 /// Instantiate the import queue for the manual seal consensus engine.
@@ -111,6 +120,10 @@ pub struct ManualSealParams<B: BlockT, BI, E, C: ProvideRuntimeApi<B>, TP, SC, C
 	pub consensus_data_provider: Option<Box<dyn ConsensusDataProvider<B, Proof = P>>>,
 
 	/// Something that can create the inherent data providers.
+	///
+	/// The timestamp inherent they provide is adjusted by [`EngineCommand::IncreaseTime`] and
+	/// [`EngineCommand::SetNextBlockTimestamp`]. Consensus data providers that check the timestamp
+	/// against the slot, like the aura and babe ones, may reject blocks with adjusted timestamps.
 	pub create_inherent_data_providers: CIDP,
 }
 
@@ -151,7 +164,57 @@ pub struct DelayedFinalizeParams<C, S> {
 }
 
 /// Creates the background authorship task for the manually seal engine.
+///
+/// [`EngineCommand::RevertTo`] and [`EngineCommand::RevertToSnapshot`] need access to the client
+/// backend, and fail unless the task is created with [`run_manual_seal_with_backend`].
 pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
+	params: ManualSealParams<B, BI, E, C, TP, SC, CS, CIDP, P>,
+) where
+	B: BlockT + 'static,
+	BI: BlockImport<B, Error = sp_consensus::Error> + Send + Sync + 'static,
+	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + 'static,
+	CB: ClientBackend<B> + 'static,
+	E: Environment<B> + 'static,
+	E::Proposer: Proposer<B, Proof = P>,
+	CS: Stream<Item = EngineCommand<<B as BlockT>::Hash>> + Unpin + 'static,
+	SC: SelectChain<B> + 'static,
+	TP: TransactionPool<Block = B>,
+	CIDP: CreateInherentDataProviders<B, ()>,
+	P: codec::Encode + Send + Sync + 'static,
+{
+	run_manual_seal_inner(params, None).await
+}
+
+/// Creates the background authorship task for the manually seal engine, with access to the client
+/// backend for reverting blocks.
+///
+/// The transaction pool is maintained on reverts, so that it takes back the transactions of the
+/// reverted blocks.
+pub async fn run_manual_seal_with_backend<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
+	params: ManualSealParams<B, BI, E, C, TP, SC, CS, CIDP, P>,
+	backend: Arc<CB>,
+) where
+	B: BlockT + 'static,
+	BI: BlockImport<B, Error = sp_consensus::Error> + Send + Sync + 'static,
+	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + 'static,
+	CB: ClientBackend<B> + 'static,
+	E: Environment<B> + 'static,
+	E::Proposer: Proposer<B, Proof = P>,
+	CS: Stream<Item = EngineCommand<<B as BlockT>::Hash>> + Unpin + 'static,
+	SC: SelectChain<B> + 'static,
+	TP: MaintainedTransactionPool<Block = B> + 'static,
+	CIDP: CreateInherentDataProviders<B, ()>,
+	P: codec::Encode + Send + Sync + 'static,
+{
+	let pool = params.pool.clone();
+	let notify_pool: revert::NotifyPool<B> = Box::new(move |event| {
+		let pool = pool.clone();
+		async move { pool.maintain(event).await }.boxed()
+	});
+	run_manual_seal_inner(params, Some(revert::Reverter { backend, notify_pool })).await
+}
+
+async fn run_manual_seal_inner<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 	ManualSealParams {
 		mut block_import,
 		mut env,
@@ -162,6 +225,7 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 		consensus_data_provider,
 		create_inherent_data_providers,
 	}: ManualSealParams<B, BI, E, C, TP, SC, CS, CIDP, P>,
+	reverter: Option<revert::Reverter<B, CB>>,
 ) where
 	B: BlockT + 'static,
 	BI: BlockImport<B, Error = sp_consensus::Error> + Send + Sync + 'static,
@@ -175,6 +239,15 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 	CIDP: CreateInherentDataProviders<B, ()>,
 	P: codec::Encode + Send + Sync + 'static,
 {
+	let time_travel = time_travel::SharedTimeTravel::default();
+	let create_inherent_data_providers = time_travel::TimeTravelInherentDataProviders::new(
+		create_inherent_data_providers,
+		time_travel.clone(),
+	);
+	// Snapshots of the best block and of the timestamp overrides, by id.
+	let mut snapshots = BTreeMap::new();
+	let mut next_snapshot_id = 0u64;
+
 	while let Some(command) = commands_stream.next().await {
 		match command {
 			EngineCommand::SealNewBlock { create_empty, finalize, parent_hash, sender } => {
@@ -193,6 +266,91 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 				})
 				.await;
 			},
+			EngineCommand::Mine { blocks, mut sender, .. } if blocks > MAX_MINED_BLOCKS => {
+				rpc::send_result(
+					&mut sender,
+					Err(Error::StringError(format!(
+						"Can't mine {} blocks, at most {} blocks can be mined at once",
+						blocks, MAX_MINED_BLOCKS,
+					))),
+				);
+			},
+			EngineCommand::Mine { blocks, interval, mut sender } => {
+				let mut created = Vec::new();
+				let mut result = Ok(());
+				for _ in 0..blocks {
+					if let Some(interval) = interval.filter(|_| !created.is_empty()) {
+						time_travel.lock().set_next_block_interval(interval);
+					}
+
+					let (block_sender, block_receiver) = futures::channel::oneshot::channel();
+					seal_block(SealBlockParams {
+						sender: Some(block_sender),
+						parent_hash: None,
+						finalize: false,
+						create_empty: true,
+						env: &mut env,
+						select_chain: &select_chain,
+						block_import: &mut block_import,
+						consensus_data_provider: consensus_data_provider.as_deref(),
+						pool: pool.clone(),
+						client: client.clone(),
+						create_inherent_data_providers: &create_inherent_data_providers,
+					})
+					.await;
+
+					match block_receiver.await {
+						Ok(Ok(block)) => created.push(block),
+						Ok(Err(e)) => {
+							result = Err(e);
+							break
+						},
+						Err(e) => {
+							result = Err(e.into());
+							break
+						},
+					}
+				}
+				rpc::send_result(&mut sender, result.map(|()| created));
+			},
+			EngineCommand::SetNextBlockTimestamp { timestamp, mut sender } => {
+				time_travel.lock().set_next_block_timestamp(timestamp);
+				rpc::send_result(&mut sender, Ok(()));
+			},
+			EngineCommand::IncreaseTime { by, mut sender } => {
+				let offset = time_travel.lock().increase_time(by);
+				rpc::send_result(&mut sender, Ok(offset));
+			},
+			EngineCommand::RevertTo { hash, mut sender } => {
+				let result = revert::revert_to(&*client, reverter.as_ref(), hash).await;
+				rpc::send_result(&mut sender, result);
+			},
+			EngineCommand::Snapshot { mut sender } => {
+				let id = next_snapshot_id;
+				next_snapshot_id += 1;
+				let snapshot = time_travel.lock().clone();
+				snapshots.insert(id, (client.info().best_hash, snapshot));
+				rpc::send_result(&mut sender, Ok(id));
+			},
+			EngineCommand::RevertToSnapshot { id, mut sender } => {
+				let result = match snapshots.get(&id).cloned() {
+					Some((hash, snapshot)) =>
+						revert::revert_to(&*client, reverter.as_ref(), hash).await.map(|()| {
+							*time_travel.lock() = snapshot;
+							let cleared = pool.clear();
+							log::info!(
+								target: LOG_TARGET,
+								"📸 Reverted to snapshot {}, cleared {} transactions",
+								id,
+								cleared.len(),
+							);
+							snapshots.split_off(&id);
+							true
+						}),
+					None => Ok(false),
+				};
+				rpc::send_result(&mut sender, result);
+			},
 			EngineCommand::FinalizeBlock { hash, sender, justification } => {
 				let justification = justification.map(|j| (MANUAL_SEAL_ENGINE_ID, j));
 				finalize_block(FinalizeBlockParams {
@@ -800,4 +958,264 @@ mod tests {
 		let header = client.header(created_block.hash).unwrap().unwrap();
 		assert_eq!(header.number, 1);
 	}
+
+	#[tokio::test]
+	async fn manual_seal_mine_and_revert() {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let genesis_hash = client.info().genesis_hash;
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			api(),
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+			genesis_hash,
+			genesis_hash,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+
+		let (mut sink, commands_stream) = futures::channel::mpsc::channel(1024);
+
+		// spawn the background authorship task
+		tokio::spawn(run_manual_seal_with_backend(
+			ManualSealParams {
+				block_import: client.clone(),
+				env,
+				client: client.clone(),
+				pool: pool.clone(),
+				commands_stream,
+				select_chain,
+				consensus_data_provider: None,
+				create_inherent_data_providers: |_, _| async { Ok(()) },
+			},
+			backend,
+		));
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::IncreaseTime { by: 1_000, sender: Some(tx) })
+			.await
+			.unwrap();
+		assert_eq!(rx.await.unwrap().unwrap(), 1_000);
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::Mine {
+			blocks: MAX_MINED_BLOCKS + 1,
+			interval: None,
+			sender: Some(tx),
+		})
+		.await
+		.unwrap();
+		assert!(rx.await.unwrap().is_err());
+		assert_eq!(client.info().best_number, 0);
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::Mine { blocks: 5, interval: Some(6_000), sender: Some(tx) })
+			.await
+			.unwrap();
+		let created_blocks = rx.await.unwrap().unwrap();
+		assert_eq!(created_blocks.len(), 5);
+		assert_eq!(client.info().best_number, 5);
+		assert_eq!(client.info().best_hash, created_blocks[4].hash);
+
+		// finalize the first block, which can't be reverted anymore.
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::FinalizeBlock {
+			hash: created_blocks[0].hash,
+			sender: Some(tx),
+			justification: None,
+		})
+		.await
+		.unwrap();
+		rx.await.unwrap().unwrap();
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::RevertTo { hash: created_blocks[1].hash, sender: Some(tx) })
+			.await
+			.unwrap();
+		rx.await.unwrap().unwrap();
+		assert_eq!(client.info().best_number, 2);
+		assert_eq!(client.info().best_hash, created_blocks[1].hash);
+		assert_eq!(client.hash(3).unwrap(), None);
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::RevertTo { hash: genesis_hash, sender: Some(tx) })
+			.await
+			.unwrap();
+		assert!(rx.await.unwrap().is_err());
+		assert_eq!(client.info().best_number, 2);
+	}
+
+	#[tokio::test]
+	async fn manual_seal_revert_resubmits_transactions_and_removes_forks() {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let genesis_hash = client.info().genesis_hash;
+		let pool_api = Arc::new(FullChainApi::new(client.clone(), None, &spawner.clone()));
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			pool_api,
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+			genesis_hash,
+			genesis_hash,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+
+		let (mut sink, commands_stream) = futures::channel::mpsc::channel(1024);
+
+		// spawn the background authorship task
+		tokio::spawn(run_manual_seal_with_backend(
+			ManualSealParams {
+				block_import: client.clone(),
+				env,
+				client: client.clone(),
+				pool: pool.clone(),
+				commands_stream,
+				select_chain,
+				consensus_data_provider: None,
+				create_inherent_data_providers: |_, _| async { Ok(()) },
+			},
+			backend,
+		));
+
+		let seal = |parent_hash, create_empty| {
+			let mut sink = sink.clone();
+			async move {
+				let (tx, rx) = futures::channel::oneshot::channel();
+				sink.send(EngineCommand::SealNewBlock {
+					parent_hash,
+					sender: Some(tx),
+					create_empty,
+					finalize: false,
+				})
+				.await
+				.unwrap();
+				rx.await.unwrap().unwrap().hash
+			}
+		};
+
+		// an empty block, then a block with a transaction and an empty fork next to it.
+		let block1 = seal(None, true).await;
+		pool.maintain(sc_transaction_pool_api::ChainEvent::NewBestBlock {
+			hash: block1,
+			tree_route: None,
+		})
+		.await;
+		assert!(pool.submit_one(block1, SOURCE, uxt(Alice, 0)).await.is_ok());
+		let block2 = seal(None, false).await;
+		pool.maintain(sc_transaction_pool_api::ChainEvent::NewBestBlock {
+			hash: block2,
+			tree_route: None,
+		})
+		.await;
+		assert_eq!(pool.status().ready, 0);
+		let fork = seal(Some(block1), true).await;
+		assert_eq!(client.info().best_hash, block2);
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::RevertTo { hash: block1, sender: Some(tx) })
+			.await
+			.unwrap();
+		rx.await.unwrap().unwrap();
+		assert_eq!(client.info().best_hash, block1);
+		assert_eq!(client.header(block2).unwrap(), None);
+		assert_eq!(client.header(fork).unwrap(), None);
+
+		// the transaction of the reverted block is back in the pool.
+		assert_eq!(pool.status().ready, 1);
+	}
+
+	#[tokio::test]
+	async fn manual_seal_snapshot_and_revert() {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let genesis_hash = client.info().genesis_hash;
+		let pool_api = Arc::new(FullChainApi::new(client.clone(), None, &spawner.clone()));
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			pool_api,
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+			genesis_hash,
+			genesis_hash,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+
+		let (mut sink, commands_stream) = futures::channel::mpsc::channel(1024);
+
+		// spawn the background authorship task
+		tokio::spawn(run_manual_seal_with_backend(
+			ManualSealParams {
+				block_import: client.clone(),
+				env,
+				client: client.clone(),
+				pool: pool.clone(),
+				commands_stream,
+				select_chain,
+				consensus_data_provider: None,
+				create_inherent_data_providers: |_, _| async { Ok(()) },
+			},
+			backend,
+		));
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::Snapshot { sender: Some(tx) }).await.unwrap();
+		let snapshot = rx.await.unwrap().unwrap();
+
+		// seal a block with a transaction, and leave another one in the pool.
+		assert!(pool.submit_one(genesis_hash, SOURCE, uxt(Alice, 0)).await.is_ok());
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealNewBlock {
+			parent_hash: None,
+			sender: Some(tx),
+			create_empty: false,
+			finalize: false,
+		})
+		.await
+		.unwrap();
+		let created_block = rx.await.unwrap().unwrap();
+		pool.maintain(sc_transaction_pool_api::ChainEvent::NewBestBlock {
+			hash: created_block.hash,
+			tree_route: None,
+		})
+		.await;
+		assert!(pool.submit_one(created_block.hash, SOURCE, uxt(Alice, 1)).await.is_ok());
+		assert_eq!(pool.status().ready, 1);
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::RevertToSnapshot { id: snapshot, sender: Some(tx) })
+			.await
+			.unwrap();
+		assert!(rx.await.unwrap().unwrap());
+		assert_eq!(client.info().best_hash, genesis_hash);
+		assert_eq!(pool.status().ready, 0);
+
+		// the transaction of the reverted block can be submitted again.
+		assert!(pool.submit_one(genesis_hash, SOURCE, uxt(Alice, 0)).await.is_ok());
+
+		// the snapshot is discarded once reverted to.
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::RevertToSnapshot { id: snapshot, sender: Some(tx) })
+			.await
+			.unwrap();
+		assert!(!rx.await.unwrap().unwrap());
+	}
 }
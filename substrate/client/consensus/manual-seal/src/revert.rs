@@ -0,0 +1,110 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Block reverting utilities
+
+use crate::Error;
+use futures::future::BoxFuture;
+use sc_client_api::backend::Backend as ClientBackend;
+use sc_transaction_pool_api::ChainEvent;
+use sp_blockchain::{Backend as _, HeaderBackend};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, Saturating};
+use std::sync::Arc;
+
+/// Notifies the transaction pool of a chain event.
+pub(crate) type NotifyPool<B> = Box<dyn Fn(ChainEvent<B>) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// What the authorship task needs to revert blocks.
+pub(crate) struct Reverter<B: BlockT, CB> {
+	pub(crate) backend: Arc<CB>,
+	pub(crate) notify_pool: NotifyPool<B>,
+}
+
+/// Reverts the chain to the block with the given hash.
+///
+/// The block must be in the canonical chain and not older than the last finalized block. All the
+/// blocks above it, including the ones on forks, are removed from the backend. The transaction
+/// pool is told about the new best block beforehand, so that it takes back the transactions of the
+/// reverted canonical blocks while their bodies are still available.
+pub(crate) async fn revert_to<B, C, CB>(
+	client: &C,
+	reverter: Option<&Reverter<B, CB>>,
+	hash: B::Hash,
+) -> Result<(), Error>
+where
+	B: BlockT,
+	C: HeaderBackend<B>,
+	CB: ClientBackend<B>,
+{
+	let Reverter { backend, notify_pool } = reverter.ok_or_else(|| {
+		Error::StringError(
+			"Reverting blocks requires the authorship task to be started with \
+			`run_manual_seal_with_backend`"
+				.into(),
+		)
+	})?;
+
+	let number = *client
+		.header(hash)?
+		.ok_or_else(|| Error::BlockNotFound(format!("{}", hash)))?
+		.number();
+	if client.hash(number)? != Some(hash) {
+		return Err(Error::StringError(format!("Block {} is not in the canonical chain", hash)))
+	}
+
+	let info = client.info();
+	if number < info.finalized_number {
+		return Err(Error::StringError(format!(
+			"Block {} is older than the last finalized block #{}",
+			hash, info.finalized_number,
+		)))
+	}
+
+	let blockchain = backend.blockchain();
+	let tree_route = sp_blockchain::tree_route(blockchain, info.best_hash, hash)?;
+	notify_pool(ChainEvent::NewBestBlock { hash, tree_route: Some(Arc::new(tree_route)) }).await;
+
+	// The backend only reverts the canonical chain, so the forks are removed first.
+	let mut removed = 0;
+	for leaf in blockchain.leaves()? {
+		let mut leaf = leaf;
+		loop {
+			let header = blockchain.expect_header(leaf)?;
+			if *header.number() <= number || blockchain.hash(*header.number())? == Some(leaf) {
+				break
+			}
+			backend.remove_leaf_block(leaf)?;
+			removed += 1;
+			leaf = *header.parent_hash();
+			if !blockchain.children(leaf)?.is_empty() {
+				break
+			}
+		}
+	}
+
+	let (reverted, _) = backend.revert(info.best_number.saturating_sub(number), false)?;
+	log::info!(
+		target: crate::LOG_TARGET,
+		"⏪ Reverted {} blocks to {}, removed {} fork blocks",
+		reverted,
+		hash,
+		removed,
+	);
+
+	Ok(())
+}
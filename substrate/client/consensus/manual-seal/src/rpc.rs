@@ -59,6 +59,58 @@ pub enum EngineCommand<Hash> {
 		/// finalization justification
 		justification: Option<EncodedJustification>,
 	},
+	/// Tells the engine to use the supplied timestamp for the next block.
+	///
+	/// The timestamps of the following blocks advance from it.
+	SetNextBlockTimestamp {
+		/// timestamp of the next block, in milliseconds.
+		timestamp: u64,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<()>,
+	},
+	/// Tells the engine to move the timestamps of the next blocks forward.
+	IncreaseTime {
+		/// milliseconds to add to the timestamps.
+		by: u64,
+		/// sender to report the total offset of the timestamps to the rpc.
+		sender: Sender<i64>,
+	},
+	/// Tells the engine to revert the chain to the block with the supplied hash.
+	///
+	/// The block must be in the canonical chain, and must not be older than the last finalized
+	/// block.
+	RevertTo {
+		/// hash of the block
+		hash: Hash,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<()>,
+	},
+	/// Tells the engine to seal a number of blocks on top of the best block.
+	///
+	/// Empty blocks are created if there are no transactions in the transaction pool. At most
+	/// [`MAX_MINED_BLOCKS`](crate::MAX_MINED_BLOCKS) blocks can be sealed at once.
+	Mine {
+		/// number of blocks to seal.
+		blocks: u32,
+		/// milliseconds between the timestamps of the sealed blocks, if set.
+		interval: Option<u64>,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<Vec<CreatedBlock<Hash>>>,
+	},
+	/// Tells the engine to record the best block and the timestamp overrides as a snapshot.
+	Snapshot {
+		/// sender to report the id of the snapshot to the rpc.
+		sender: Sender<u64>,
+	},
+	/// Tells the engine to revert the chain to a snapshot and to clear the transaction pool.
+	///
+	/// The snapshot and all the ones taken after it are discarded.
+	RevertToSnapshot {
+		/// id of the snapshot.
+		id: u64,
+		/// sender to report to the rpc whether the snapshot was found.
+		sender: Sender<bool>,
+	},
 }
 
 /// RPC trait that provides methods for interacting with the manual-seal authorship task over rpc.
@@ -80,6 +132,40 @@ pub trait ManualSealApi<Hash> {
 		hash: Hash,
 		justification: Option<EncodedJustification>,
 	) -> Result<bool, Error>;
+
+	/// Instructs the manual-seal authorship task to use the given timestamp, in milliseconds, for
+	/// the next block
+	#[method(name = "engine_setNextBlockTimestamp")]
+	async fn set_next_block_timestamp(&self, timestamp: u64) -> Result<bool, Error>;
+
+	/// Instructs the manual-seal authorship task to move the timestamps of the next blocks forward
+	/// by the given number of milliseconds. Returns the total offset of the timestamps.
+	#[method(name = "engine_increaseTime")]
+	async fn increase_time(&self, by: u64) -> Result<i64, Error>;
+
+	/// Instructs the manual-seal authorship task to revert the chain to the given unfinalized
+	/// block
+	#[method(name = "engine_revertTo")]
+	async fn revert_to(&self, hash: Hash) -> Result<bool, Error>;
+
+	/// Instructs the manual-seal authorship task to seal a number of blocks, optionally spaced by
+	/// the given number of milliseconds
+	#[method(name = "engine_mine")]
+	async fn mine(
+		&self,
+		blocks: u32,
+		interval: Option<u64>,
+	) -> Result<Vec<CreatedBlock<Hash>>, Error>;
+
+	/// Instructs the manual-seal authorship task to record the current best block as a snapshot.
+	/// Returns the id of the snapshot.
+	#[method(name = "engine_snapshot")]
+	async fn snapshot(&self) -> Result<u64, Error>;
+
+	/// Instructs the manual-seal authorship task to revert the chain to the given snapshot and to
+	/// clear the transaction pool. Returns false if the snapshot doesn't exist.
+	#[method(name = "engine_revertToSnapshot")]
+	async fn revert_to_snapshot(&self, id: u64) -> Result<bool, Error>;
 }
 
 /// A struct that implements the [`ManualSealApiServer`].
@@ -103,6 +189,17 @@ impl<Hash> ManualSeal<Hash> {
 	pub fn new(import_block_channel: mpsc::Sender<EngineCommand<Hash>>) -> Self {
 		Self { import_block_channel }
 	}
+
+	/// Send the command built from `sender` to the authorship task and wait for its result.
+	async fn request<T>(
+		&self,
+		command: impl FnOnce(Sender<T>) -> EngineCommand<Hash>,
+	) -> Result<T, Error> {
+		let mut sink = self.import_block_channel.clone();
+		let (sender, receiver) = oneshot::channel();
+		sink.send(command(Some(sender))).await?;
+		receiver.await?
+	}
 }
 
 #[async_trait]
@@ -143,6 +240,38 @@ impl<Hash: Send + 'static> ManualSealApiServer<Hash> for ManualSeal<Hash> {
 		sink.send(command).await?;
 		receiver.await.map(|_| true).map_err(Into::into)
 	}
+
+	async fn set_next_block_timestamp(&self, timestamp: u64) -> Result<bool, Error> {
+		self.request(|sender| EngineCommand::SetNextBlockTimestamp { timestamp, sender })
+			.await
+			.map(|_| true)
+	}
+
+	async fn increase_time(&self, by: u64) -> Result<i64, Error> {
+		self.request(|sender| EngineCommand::IncreaseTime { by, sender }).await
+	}
+
+	async fn revert_to(&self, hash: Hash) -> Result<bool, Error> {
+		self.request(|sender| EngineCommand::RevertTo { hash, sender })
+			.await
+			.map(|_| true)
+	}
+
+	async fn mine(
+		&self,
+		blocks: u32,
+		interval: Option<u64>,
+	) -> Result<Vec<CreatedBlock<Hash>>, Error> {
+		self.request(|sender| EngineCommand::Mine { blocks, interval, sender }).await
+	}
+
+	async fn snapshot(&self) -> Result<u64, Error> {
+		self.request(|sender| EngineCommand::Snapshot { sender }).await
+	}
+
+	async fn revert_to_snapshot(&self, id: u64) -> Result<bool, Error> {
+		self.request(|sender| EngineCommand::RevertToSnapshot { id, sender }).await
+	}
 }
 
 /// report any errors or successes encountered by the authorship task back
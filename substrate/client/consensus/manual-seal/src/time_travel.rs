@@ -0,0 +1,176 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Control over the timestamps of the sealed blocks.
+//!
+//! The timestamp inherent provided by the inherent data providers of the node is shifted by an
+//! offset, which can be moved forward with [`EngineCommand::IncreaseTime`], or overridden for the
+//! next block with [`EngineCommand::SetNextBlockTimestamp`].
+//!
+//! [`EngineCommand::IncreaseTime`]: crate::EngineCommand::IncreaseTime
+//! [`EngineCommand::SetNextBlockTimestamp`]: crate::EngineCommand::SetNextBlockTimestamp
+
+use parking_lot::Mutex;
+use sp_inherents::{
+	CreateInherentDataProviders, InherentData, InherentDataProvider, InherentIdentifier,
+};
+use sp_runtime::traits::Block as BlockT;
+use sp_timestamp::{InherentType, INHERENT_IDENTIFIER};
+use std::sync::Arc;
+
+/// Timestamp overrides of the sealed blocks.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TimeTravel {
+	/// Milliseconds added to the provided timestamps.
+	offset: i64,
+	/// Timestamp of the next block, if set.
+	next: Option<u64>,
+	/// Timestamp of the last sealed block.
+	last: Option<u64>,
+}
+
+impl TimeTravel {
+	/// Use `timestamp` for the next block. The following blocks keep the same offset.
+	pub(crate) fn set_next_block_timestamp(&mut self, timestamp: u64) {
+		self.next = Some(timestamp);
+	}
+
+	/// Move the timestamps of the next blocks forward by `millis`.
+	///
+	/// Returns the total offset of the timestamps, in milliseconds.
+	pub(crate) fn increase_time(&mut self, millis: u64) -> i64 {
+		self.offset = self.offset.saturating_add(millis.try_into().unwrap_or(i64::MAX));
+		self.offset
+	}
+
+	/// Use the timestamp of the last block plus `interval` for the next block.
+	///
+	/// Does nothing if no block has been sealed yet.
+	pub(crate) fn set_next_block_interval(&mut self, interval: u64) {
+		if let Some(last) = self.last {
+			self.next = Some(last.saturating_add(interval));
+		}
+	}
+
+	/// Apply the overrides to the `provided` timestamp of a new block.
+	fn apply(&mut self, provided: Option<u64>) -> Option<u64> {
+		let timestamp = match (self.next.take(), provided) {
+			(Some(next), Some(provided)) => {
+				// Following blocks continue from the timestamp of this one.
+				self.offset = (next as i128 - provided as i128) as i64;
+				next
+			},
+			(Some(next), None) => next,
+			(None, Some(provided)) => provided.saturating_add_signed(self.offset),
+			(None, None) => return None,
+		};
+		self.last = Some(timestamp);
+		Some(timestamp)
+	}
+}
+
+/// [`TimeTravel`] shared between the authorship task and the inherent data providers.
+pub(crate) type SharedTimeTravel = Arc<Mutex<TimeTravel>>;
+
+/// Wraps the inherent data providers of the node to apply the timestamp overrides.
+pub(crate) struct TimeTravelInherentDataProviders<CIDP> {
+	inner: CIDP,
+	time_travel: SharedTimeTravel,
+}
+
+impl<CIDP> TimeTravelInherentDataProviders<CIDP> {
+	pub(crate) fn new(inner: CIDP, time_travel: SharedTimeTravel) -> Self {
+		Self { inner, time_travel }
+	}
+}
+
+#[async_trait::async_trait]
+impl<B, CIDP> CreateInherentDataProviders<B, ()> for TimeTravelInherentDataProviders<CIDP>
+where
+	B: BlockT,
+	CIDP: CreateInherentDataProviders<B, ()>,
+{
+	type InherentDataProviders = (CIDP::InherentDataProviders, TimestampOverride);
+
+	async fn create_inherent_data_providers(
+		&self,
+		parent: B::Hash,
+		extra_args: (),
+	) -> Result<Self::InherentDataProviders, Box<dyn std::error::Error + Send + Sync>> {
+		let inner = self.inner.create_inherent_data_providers(parent, extra_args).await?;
+		Ok((inner, TimestampOverride(self.time_travel.clone())))
+	}
+}
+
+/// Replaces the timestamp inherent provided by the node with the overridden one.
+///
+/// Must come after the providers of the node, so that their timestamp is known.
+pub(crate) struct TimestampOverride(SharedTimeTravel);
+
+#[async_trait::async_trait]
+impl InherentDataProvider for TimestampOverride {
+	async fn provide_inherent_data(
+		&self,
+		inherent_data: &mut InherentData,
+	) -> Result<(), sp_inherents::Error> {
+		let provided = inherent_data
+			.get_data::<InherentType>(&INHERENT_IDENTIFIER)?
+			.map(|timestamp| *timestamp);
+		let timestamp = self.0.lock().apply(provided);
+		if let Some(timestamp) = timestamp {
+			inherent_data.replace_data(INHERENT_IDENTIFIER, &InherentType::new(timestamp));
+		}
+		Ok(())
+	}
+
+	async fn try_handle_error(
+		&self,
+		_: &InherentIdentifier,
+		_: &[u8],
+	) -> Option<Result<(), sp_inherents::Error>> {
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn timestamps_are_overridden() {
+		let mut time_travel = TimeTravel::default();
+		assert_eq!(time_travel.apply(Some(1_000)), Some(1_000));
+		assert_eq!(time_travel.apply(None), None);
+
+		assert_eq!(time_travel.increase_time(500), 500);
+		assert_eq!(time_travel.apply(Some(2_000)), Some(2_500));
+
+		// The next blocks continue from the set timestamp.
+		time_travel.set_next_block_timestamp(10_000);
+		assert_eq!(time_travel.apply(Some(3_000)), Some(10_000));
+		assert_eq!(time_travel.apply(Some(4_000)), Some(11_000));
+
+		// Going back in time is up to the caller.
+		time_travel.set_next_block_timestamp(5_000);
+		assert_eq!(time_travel.apply(Some(5_000)), Some(5_000));
+		assert_eq!(time_travel.apply(Some(6_000)), Some(6_000));
+
+		time_travel.set_next_block_interval(100);
+		assert_eq!(time_travel.apply(Some(6_000)), Some(6_100));
+	}
+}
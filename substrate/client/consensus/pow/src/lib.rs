@@ -452,6 +452,11 @@ where
 
 		Ok(block)
 	}
+
+	// Only the seal is checked here, the difficulty is checked by the block import.
+	fn can_verify_ahead(&self) -> bool {
+		true
+	}
 }
 
 /// The PoW import queue type.
@@ -0,0 +1,532 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Sassafras tickets generation and slot claiming.
+
+use crate::{
+	aux_schema, find_next_epoch_digest, find_slot_claim, Epoch, Error, SassafrasConfiguration,
+	SassafrasIntermediate, TicketSecret, INTERMEDIATE_KEY, LOG_TARGET,
+};
+use codec::Encode;
+use futures::prelude::*;
+use log::{debug, info, warn};
+use sc_client_api::{backend::AuxStore, BlockchainEvents};
+use sc_consensus::{
+	block_import::{BlockImport, BlockImportParams, StateAction},
+	JustificationSyncLink,
+};
+use sc_consensus_epochs::{
+	descendent_query, Epoch as EpochT, EpochIdentifier, EpochIdentifierPosition,
+	SharedEpochChanges, ViableEpochDescriptor,
+};
+use sc_consensus_slots::{
+	BackoffAuthoringBlocksStrategy, SlotInfo, SlotProportion, StorageChanges,
+};
+use sc_telemetry::TelemetryHandle;
+use sc_transaction_pool_api::OffchainTransactionPoolFactory;
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_application_crypto::AppCrypto;
+use sp_blockchain::{Error as ClientError, HeaderBackend, HeaderMetadata};
+use sp_consensus::{BlockOrigin, Environment, Error as ConsensusError, Proposer, SyncOracle};
+use sp_consensus_sassafras::{
+	digests::SlotClaim,
+	ticket_id_threshold,
+	vrf::{
+		make_revealed_key_seed, make_ticket_id, revealed_key_input, slot_claim_sign_data,
+		ticket_body_sign_data, ticket_id_input, RingContext,
+	},
+	AuthorityId, AuthorityIndex, AuthoritySignature, SassafrasApi, Slot, TicketBody, TicketClaim,
+	TicketEnvelope, TicketId,
+};
+use sp_core::{crypto::ByteArray, ed25519::Pair as EphemeralPair, traits::SpawnNamed, Pair};
+use sp_keystore::KeystorePtr;
+use sp_runtime::{
+	traits::{Block as BlockT, Header, NumberFor, One},
+	DigestItem,
+};
+use std::{pin::Pin, sync::Arc, time::Duration};
+
+/// Get the authority of a slot without an assigned ticket.
+///
+/// This should always assign the slot to some authority unless the authorities list is empty.
+pub(crate) fn secondary_authority_index(slot: Slot, epoch: &Epoch) -> Option<AuthorityIndex> {
+	if epoch.authorities.is_empty() {
+		return None
+	}
+
+	let rand =
+		u64::from_le_bytes((epoch.randomness, slot).using_encoded(sp_crypto_hashing::blake2_64));
+	Some((rand % epoch.authorities.len() as u64) as AuthorityIndex)
+}
+
+/// Index of the epoch which the given slot belongs to.
+///
+/// The slot may be after the end of the epoch, if some epochs were skipped.
+pub(crate) fn epoch_index_for_slot(epoch: &Epoch, slot: Slot) -> u64 {
+	if epoch.end_slot() <= slot {
+		// Slot doesn't strictly belong to the epoch, create a clone with fixed values.
+		epoch.clone_for_slot(slot).index
+	} else {
+		epoch.index
+	}
+}
+
+/// Tries to claim the given slot number.
+///
+/// If the slot has been assigned a ticket, `maybe_ticket` holds it and the slot can only be
+/// claimed if the ticket has been submitted by this node. Otherwise, the slot is claimed if this
+/// node owns the key of its secondary author.
+pub fn claim_slot(
+	slot: Slot,
+	epoch: &Epoch,
+	maybe_ticket: Option<(TicketId, TicketBody)>,
+	keystore: &KeystorePtr,
+) -> Option<(SlotClaim, AuthorityId)> {
+	if epoch.authorities.is_empty() {
+		return None
+	}
+
+	let epoch_index = epoch_index_for_slot(epoch, slot);
+	let mut vrf_sign_data = slot_claim_sign_data(&epoch.randomness, slot, epoch_index);
+
+	let (authority_idx, ticket_claim) = match maybe_ticket {
+		Some((ticket_id, ticket_body)) => {
+			debug!(target: LOG_TARGET, "Slot {} is assigned to ticket {:032x}", slot, ticket_id);
+			let (authority_idx, ticket_secret) = epoch.tickets_aux.get(&ticket_id)?;
+			debug!(target: LOG_TARGET, "Ticket {:032x} is ours, claiming it", ticket_id);
+
+			// The claim reveals the seed of the revealed ephemeral key as a second VRF output,
+			// and is signed with the erased ephemeral key.
+			let revealed_input =
+				revealed_key_input(&epoch.randomness, ticket_body.attempt_idx, epoch_index);
+			vrf_sign_data.push_vrf_input(revealed_input).ok()?;
+			let challenge = vrf_sign_data.challenge::<32>();
+			let erased_pair = EphemeralPair::from_seed(&ticket_secret.erased_secret);
+			let erased_signature = erased_pair.sign(&challenge);
+
+			(*authority_idx, Some(TicketClaim { erased_signature }))
+		},
+		None => (secondary_authority_index(slot, epoch)?, None),
+	};
+
+	let authority_id = epoch.authorities.get(authority_idx as usize)?;
+	let vrf_signature = keystore
+		.bandersnatch_vrf_sign(AuthorityId::ID, authority_id.as_ref(), &vrf_sign_data)
+		.ok()
+		.flatten()?;
+
+	let claim = SlotClaim { authority_idx, slot, vrf_signature, ticket_claim };
+	Some((claim, authority_id.clone()))
+}
+
+/// Generate the tickets of this node for the given epoch.
+///
+/// Only the tickets with an identifier below the epoch threshold are generated. Their secrets
+/// are stored in the epoch `tickets_aux`, and their envelopes are returned, ready to be submitted
+/// on-chain.
+pub fn generate_epoch_tickets(
+	epoch: &mut Epoch,
+	keystore: &KeystorePtr,
+	ring_context: &RingContext,
+) -> Vec<TicketEnvelope> {
+	let mut tickets = Vec::new();
+	let (index, randomness, config) = (epoch.index, epoch.randomness, epoch.config);
+
+	let threshold = ticket_id_threshold(
+		config.redundancy_factor,
+		epoch.length,
+		config.attempts_number,
+		epoch.authorities.len() as u32,
+	);
+	debug!(target: LOG_TARGET, "Tickets threshold for epoch {}: {:032x}", index, threshold);
+
+	let authorities =
+		epoch.authorities.iter().cloned().map(|id| id.into_inner()).collect::<Vec<_>>();
+
+	for (authority_idx, authority_id) in epoch.inner.authorities.iter().enumerate() {
+		if !keystore.has_keys(&[(authority_id.to_raw_vec(), AuthorityId::ID)]) {
+			continue
+		}
+		let Some(prover) = ring_context.prover(&authorities, authority_idx) else {
+			warn!(target: LOG_TARGET, "Unable to build the ring prover for {:?}", authority_id);
+			continue
+		};
+
+		for attempt_idx in 0..config.attempts_number {
+			let ticket_id_input = ticket_id_input(&randomness, attempt_idx, index);
+			let Ok(Some(ticket_id_pre_output)) = keystore.bandersnatch_vrf_pre_output(
+				AuthorityId::ID,
+				authority_id.as_ref(),
+				&ticket_id_input,
+			) else {
+				continue
+			};
+			let ticket_id = make_ticket_id(&ticket_id_input, &ticket_id_pre_output);
+			if ticket_id >= threshold {
+				continue
+			}
+
+			let (erased_pair, erased_secret) = EphemeralPair::generate();
+
+			let revealed_input = revealed_key_input(&randomness, attempt_idx, index);
+			let Ok(Some(revealed_pre_output)) = keystore.bandersnatch_vrf_pre_output(
+				AuthorityId::ID,
+				authority_id.as_ref(),
+				&revealed_input,
+			) else {
+				continue
+			};
+			let revealed_seed = make_revealed_key_seed(&revealed_input, &revealed_pre_output);
+			let revealed_public = EphemeralPair::from_seed(&revealed_seed).public();
+
+			let body =
+				TicketBody { attempt_idx, erased_public: erased_pair.public(), revealed_public };
+
+			debug!(target: LOG_TARGET, "Generating ring proof for ticket {:032x}", ticket_id);
+			let sign_data = ticket_body_sign_data(&body, ticket_id_input);
+			let Ok(Some(signature)) = keystore.bandersnatch_ring_vrf_sign(
+				AuthorityId::ID,
+				authority_id.as_ref(),
+				&sign_data,
+				&prover,
+			) else {
+				continue
+			};
+
+			tickets.push(TicketEnvelope { body, signature });
+			epoch.tickets_aux.insert(
+				ticket_id,
+				(authority_idx as AuthorityIndex, TicketSecret { attempt_idx, erased_secret }),
+			);
+		}
+	}
+
+	tickets
+}
+
+/// Generate the tickets of this node for the epoch announced by the block `hash`, and submit
+/// them on-chain.
+fn generate_and_submit_tickets<B, C>(
+	client: &C,
+	keystore: &KeystorePtr,
+	epoch_changes: &SharedEpochChanges<B, Epoch>,
+	offchain_tx_pool_factory: &OffchainTransactionPoolFactory<B>,
+	hash: B::Hash,
+	number: NumberFor<B>,
+) -> Result<(), Error<B>>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B> + AuxStore,
+	C::Api: SassafrasApi<B>,
+{
+	let Some(ring_context) = client.runtime_api().ring_context(hash).map_err(Error::RuntimeApi)?
+	else {
+		debug!(target: LOG_TARGET, "Ring context not available at {:?}, no tickets", hash);
+		return Ok(())
+	};
+
+	// The first block of the chain announces the second genesis epoch.
+	let position = if number == One::one() {
+		EpochIdentifierPosition::Genesis1
+	} else {
+		EpochIdentifierPosition::Regular
+	};
+	let epoch_id = EpochIdentifier { position, hash, number };
+
+	let mut epoch = epoch_changes
+		.shared_data()
+		.epoch(&epoch_id)
+		.cloned()
+		.ok_or(Error::FetchEpoch(hash))?;
+
+	// Generating the ring proofs is expensive, the epoch changes are not locked meanwhile.
+	let tickets = generate_epoch_tickets(&mut epoch, keystore, &ring_context);
+	if tickets.is_empty() {
+		debug!(target: LOG_TARGET, "No tickets for epoch {}", epoch.index);
+		return Ok(())
+	}
+
+	{
+		let mut epoch_changes = epoch_changes.shared_data();
+		let Some(target_epoch) = epoch_changes.epoch_mut(&epoch_id) else {
+			// The epoch has been pruned meanwhile.
+			return Ok(())
+		};
+		target_epoch.tickets_aux = epoch.tickets_aux;
+		aux_schema::write_epoch_changes::<B, _, _>(&*epoch_changes, |insert| {
+			client.insert_aux(insert, [])
+		})
+		.map_err(Error::Client)?;
+	}
+
+	let count = tickets.len();
+	let mut runtime_api = client.runtime_api();
+	// Register the offchain tx pool to be able to use it from the runtime.
+	runtime_api.register_extension(offchain_tx_pool_factory.offchain_transaction_pool(hash));
+	if runtime_api
+		.submit_tickets_unsigned_extrinsic(hash, tickets)
+		.map_err(Error::RuntimeApi)?
+	{
+		info!(target: LOG_TARGET, "🎫 Submitted {} tickets for epoch {}", count, epoch.index);
+	} else {
+		warn!(target: LOG_TARGET, "Unable to submit tickets for epoch {}", epoch.index);
+	}
+
+	Ok(())
+}
+
+/// Worker generating and submitting the tickets of this node.
+///
+/// Tickets for an epoch are generated when its announcement is imported as part of a new best
+/// block. Generating the ring proofs takes a while, so it runs in a blocking task spawned with
+/// `spawner` instead of stalling the slot worker.
+pub(crate) async fn start_tickets_worker<B, C>(
+	client: Arc<C>,
+	keystore: KeystorePtr,
+	epoch_changes: SharedEpochChanges<B, Epoch>,
+	offchain_tx_pool_factory: OffchainTransactionPoolFactory<B>,
+	spawner: Box<dyn SpawnNamed>,
+) where
+	B: BlockT,
+	C: BlockchainEvents<B> + ProvideRuntimeApi<B> + AuxStore + Send + Sync + 'static,
+	C::Api: SassafrasApi<B>,
+{
+	let mut notifications = client.import_notification_stream();
+
+	while let Some(notification) = notifications.next().await {
+		if !notification.is_new_best {
+			continue
+		}
+		match find_next_epoch_digest::<B>(&notification.header) {
+			Ok(Some(_)) => {},
+			_ => continue,
+		}
+
+		let client = client.clone();
+		let keystore = keystore.clone();
+		let epoch_changes = epoch_changes.clone();
+		let offchain_tx_pool_factory = offchain_tx_pool_factory.clone();
+		spawner.spawn_blocking(
+			"sassafras-tickets",
+			Some("sassafras"),
+			async move {
+				if let Err(err) = generate_and_submit_tickets(
+					&*client,
+					&keystore,
+					&epoch_changes,
+					&offchain_tx_pool_factory,
+					notification.hash,
+					*notification.header.number(),
+				) {
+					warn!(target: LOG_TARGET, "Error generating tickets: {}", err);
+				}
+			}
+			.boxed(),
+		);
+	}
+}
+
+pub(crate) struct SassafrasSlotWorker<B: BlockT, C, E, I, SO, L, BS> {
+	pub(crate) client: Arc<C>,
+	pub(crate) block_import: I,
+	pub(crate) env: E,
+	pub(crate) sync_oracle: SO,
+	pub(crate) justification_sync_link: L,
+	pub(crate) force_authoring: bool,
+	pub(crate) backoff_authoring_blocks: Option<BS>,
+	pub(crate) keystore: KeystorePtr,
+	pub(crate) epoch_changes: SharedEpochChanges<B, Epoch>,
+	pub(crate) config: SassafrasConfiguration,
+	pub(crate) block_proposal_slot_portion: SlotProportion,
+	pub(crate) max_block_proposal_slot_portion: Option<SlotProportion>,
+	pub(crate) telemetry: Option<TelemetryHandle>,
+}
+
+#[async_trait::async_trait]
+impl<B, C, E, I, Error, SO, L, BS> sc_consensus_slots::SimpleSlotWorker<B>
+	for SassafrasSlotWorker<B, C, E, I, SO, L, BS>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + HeaderMetadata<B, Error = ClientError>,
+	C::Api: SassafrasApi<B>,
+	E: Environment<B, Error = Error> + Send + Sync,
+	E::Proposer: Proposer<B, Error = Error>,
+	I: BlockImport<B> + Send + Sync + 'static,
+	SO: SyncOracle + Send + Clone + Sync,
+	L: JustificationSyncLink<B>,
+	BS: BackoffAuthoringBlocksStrategy<NumberFor<B>> + Send + Sync,
+	Error: std::error::Error + Send + From<ConsensusError> + From<I::Error> + 'static,
+{
+	type Claim = (SlotClaim, AuthorityId);
+	type SyncOracle = SO;
+	type JustificationSyncLink = L;
+	type CreateProposer =
+		Pin<Box<dyn Future<Output = Result<E::Proposer, ConsensusError>> + Send + 'static>>;
+	type Proposer = E::Proposer;
+	type BlockImport = I;
+	type AuxData = ViableEpochDescriptor<B::Hash, NumberFor<B>, Epoch>;
+
+	fn logging_target(&self) -> &'static str {
+		LOG_TARGET
+	}
+
+	fn block_import(&mut self) -> &mut Self::BlockImport {
+		&mut self.block_import
+	}
+
+	fn aux_data(&self, parent: &B::Header, slot: Slot) -> Result<Self::AuxData, ConsensusError> {
+		self.epoch_changes
+			.shared_data()
+			.epoch_descriptor_for_child_of(
+				descendent_query(&*self.client),
+				&parent.hash(),
+				*parent.number(),
+				slot,
+			)
+			.map_err(|e| ConsensusError::ChainLookup(e.to_string()))?
+			.ok_or(ConsensusError::InvalidAuthoritiesSet)
+	}
+
+	fn authorities_len(&self, epoch_descriptor: &Self::AuxData) -> Option<usize> {
+		self.epoch_changes
+			.shared_data()
+			.viable_epoch(epoch_descriptor, |slot| Epoch::genesis(&self.config, slot))
+			.map(|epoch| epoch.as_ref().authorities.len())
+	}
+
+	async fn claim_slot(
+		&mut self,
+		parent_header: &B::Header,
+		slot: Slot,
+		epoch_descriptor: &ViableEpochDescriptor<B::Hash, NumberFor<B>, Epoch>,
+	) -> Option<Self::Claim> {
+		debug!(target: LOG_TARGET, "Attempting to claim slot {}", slot);
+
+		let maybe_ticket = match self.client.runtime_api().slot_ticket(parent_header.hash(), slot) {
+			Ok(maybe_ticket) => maybe_ticket,
+			Err(err) => {
+				warn!(target: LOG_TARGET, "Unable to fetch the ticket of slot {}: {}", slot, err);
+				return None
+			},
+		};
+
+		let claim = claim_slot(
+			slot,
+			self.epoch_changes
+				.shared_data()
+				.viable_epoch(epoch_descriptor, |slot| Epoch::genesis(&self.config, slot))?
+				.as_ref(),
+			maybe_ticket,
+			&self.keystore,
+		);
+
+		if claim.is_some() {
+			debug!(target: LOG_TARGET, "Claimed slot {}", slot);
+		}
+
+		claim
+	}
+
+	fn pre_digest_data(&self, _slot: Slot, claim: &Self::Claim) -> Vec<DigestItem> {
+		vec![DigestItem::from(&claim.0)]
+	}
+
+	async fn block_import_params(
+		&self,
+		header: B::Header,
+		header_hash: &B::Hash,
+		body: Vec<B::Extrinsic>,
+		storage_changes: StorageChanges<B>,
+		(_, public): Self::Claim,
+		epoch_descriptor: Self::AuxData,
+	) -> Result<BlockImportParams<B>, ConsensusError> {
+		let signature: AuthoritySignature = self
+			.keystore
+			.bandersnatch_sign(
+				<AuthorityId as AppCrypto>::ID,
+				public.as_ref(),
+				header_hash.as_ref(),
+			)
+			.map_err(|e| ConsensusError::CannotSign(format!("{}. Key: {:?}", e, public)))?
+			.ok_or_else(|| {
+				ConsensusError::CannotSign(format!(
+					"Could not find key in keystore. Key: {:?}",
+					public
+				))
+			})?
+			.into();
+
+		let mut import_block = BlockImportParams::new(BlockOrigin::Own, header);
+		import_block.post_digests.push(DigestItem::from(&signature));
+		import_block.body = Some(body);
+		import_block.state_action =
+			StateAction::ApplyChanges(sc_consensus::StorageChanges::Changes(storage_changes));
+		import_block
+			.insert_intermediate(INTERMEDIATE_KEY, SassafrasIntermediate::<B> { epoch_descriptor });
+
+		Ok(import_block)
+	}
+
+	fn force_authoring(&self) -> bool {
+		self.force_authoring
+	}
+
+	fn should_backoff(&self, slot: Slot, chain_head: &B::Header) -> bool {
+		if let Some(ref strategy) = self.backoff_authoring_blocks {
+			if let Ok(chain_head_slot) = find_slot_claim::<B>(chain_head).map(|claim| claim.slot) {
+				return strategy.should_backoff(
+					*chain_head.number(),
+					chain_head_slot,
+					self.client.info().finalized_number,
+					slot,
+					self.logging_target(),
+				)
+			}
+		}
+		false
+	}
+
+	fn sync_oracle(&mut self) -> &mut Self::SyncOracle {
+		&mut self.sync_oracle
+	}
+
+	fn justification_sync_link(&mut self) -> &mut Self::JustificationSyncLink {
+		&mut self.justification_sync_link
+	}
+
+	fn proposer(&mut self, block: &B::Header) -> Self::CreateProposer {
+		Box::pin(self.env.init(block).map_err(|e| ConsensusError::ClientImport(e.to_string())))
+	}
+
+	fn telemetry(&self) -> Option<TelemetryHandle> {
+		self.telemetry.clone()
+	}
+
+	fn proposing_remaining_duration(&self, slot_info: &SlotInfo<B>) -> Duration {
+		let parent_slot = find_slot_claim::<B>(&slot_info.chain_head).ok().map(|c| c.slot);
+
+		sc_consensus_slots::proposing_remaining_duration(
+			parent_slot,
+			slot_info,
+			&self.block_proposal_slot_portion,
+			self.max_block_proposal_slot_portion.as_ref(),
+			sc_consensus_slots::SlotLenienceType::Exponential,
+			self.logging_target(),
+		)
+	}
+}
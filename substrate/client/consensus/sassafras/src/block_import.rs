@@ -0,0 +1,483 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Types and functions related to block import.
+
+use crate::{
+	aux_schema, find_next_epoch_digest, find_slot_claim, sassafras_err, Epoch, Error,
+	SassafrasConfiguration, SassafrasIntermediate, SassafrasLink, INTERMEDIATE_KEY, LOG_TARGET,
+};
+use log::{debug, log, warn};
+use sc_client_api::{backend::AuxStore, PreCommitActions};
+use sc_consensus::block_import::{
+	BlockCheckParams, BlockImport, BlockImportParams, ForkChoiceStrategy, ImportResult,
+};
+use sc_consensus_epochs::{descendent_query, EpochChangesFor, SharedEpochChanges};
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_blockchain::{BlockStatus, HeaderBackend, HeaderMetadata, Result as ClientResult};
+use sp_consensus::{BlockOrigin, Error as ConsensusError};
+use sp_consensus_sassafras::SassafrasApi;
+use sp_runtime::traits::{Block as BlockT, Header as _, Zero};
+use std::sync::Arc;
+
+/// A block-import handler for Sassafras.
+///
+/// This scans each imported block for epoch change announcements. The announcements are
+/// tracked in a tree (of all forks), and the import logic validates all epoch change
+/// transitions, i.e. whether a given epoch change is expected or whether it is missing.
+///
+/// The epoch change tree should be pruned as blocks are finalized.
+pub struct SassafrasBlockImport<Block: BlockT, Client, I> {
+	inner: I,
+	client: Arc<Client>,
+	epoch_changes: SharedEpochChanges<Block, Epoch>,
+	config: SassafrasConfiguration,
+}
+
+impl<Block: BlockT, I: Clone, Client> Clone for SassafrasBlockImport<Block, Client, I> {
+	fn clone(&self) -> Self {
+		SassafrasBlockImport {
+			inner: self.inner.clone(),
+			client: self.client.clone(),
+			epoch_changes: self.epoch_changes.clone(),
+			config: self.config.clone(),
+		}
+	}
+}
+
+impl<Block: BlockT, Client, I> SassafrasBlockImport<Block, Client, I> {
+	fn new(
+		client: Arc<Client>,
+		epoch_changes: SharedEpochChanges<Block, Epoch>,
+		block_import: I,
+		config: SassafrasConfiguration,
+	) -> Self {
+		SassafrasBlockImport { client, inner: block_import, epoch_changes, config }
+	}
+}
+
+impl<Block, Client, Inner> SassafrasBlockImport<Block, Client, Inner>
+where
+	Block: BlockT,
+	Inner: BlockImport<Block> + Send + Sync,
+	Inner::Error: Into<ConsensusError>,
+	Client: HeaderBackend<Block>
+		+ HeaderMetadata<Block, Error = sp_blockchain::Error>
+		+ AuxStore
+		+ ProvideRuntimeApi<Block>
+		+ Send
+		+ Sync,
+	Client::Api: SassafrasApi<Block> + ApiExt<Block>,
+{
+	/// Import whole state after warp sync.
+	// This function makes multiple transactions to the DB. If one of them fails we may
+	// end up in an inconsistent state and have to resync.
+	async fn import_state(
+		&self,
+		mut block: BlockImportParams<Block>,
+	) -> Result<ImportResult, ConsensusError> {
+		let hash = block.post_hash();
+		let parent_hash = *block.header.parent_hash();
+		let number = *block.header.number();
+
+		block.fork_choice = Some(ForkChoiceStrategy::Custom(true));
+		// Reset block weight.
+		aux_schema::write_block_weight(hash, 0, |values| {
+			block
+				.auxiliary
+				.extend(values.iter().map(|(k, v)| (k.to_vec(), Some(v.to_vec()))))
+		});
+
+		// First make the client import the state.
+		let import_result = self.inner.import_block(block).await;
+		let aux = match import_result {
+			Ok(ImportResult::Imported(aux)) => aux,
+			Ok(r) =>
+				return Err(ConsensusError::ClientImport(format!(
+					"Unexpected import result: {:?}",
+					r
+				))),
+			Err(r) => return Err(r.into()),
+		};
+
+		// Read epoch info from the imported state.
+		let current_epoch = self.client.runtime_api().current_epoch(hash).map_err(|e| {
+			ConsensusError::ClientImport(sassafras_err::<Block>(Error::RuntimeApi(e)).into())
+		})?;
+		let next_epoch = self.client.runtime_api().next_epoch(hash).map_err(|e| {
+			ConsensusError::ClientImport(sassafras_err::<Block>(Error::RuntimeApi(e)).into())
+		})?;
+
+		let mut epoch_changes = self.epoch_changes.shared_data_locked();
+		epoch_changes.reset(parent_hash, hash, number, current_epoch.into(), next_epoch.into());
+		aux_schema::write_epoch_changes::<Block, _, _>(&*epoch_changes, |insert| {
+			self.client.insert_aux(insert, [])
+		})
+		.map_err(|e| ConsensusError::ClientImport(e.to_string()))?;
+
+		Ok(ImportResult::Imported(aux))
+	}
+}
+
+#[async_trait::async_trait]
+impl<Block, Client, Inner> BlockImport<Block> for SassafrasBlockImport<Block, Client, Inner>
+where
+	Block: BlockT,
+	Inner: BlockImport<Block> + Send + Sync,
+	Inner::Error: Into<ConsensusError>,
+	Client: HeaderBackend<Block>
+		+ HeaderMetadata<Block, Error = sp_blockchain::Error>
+		+ AuxStore
+		+ ProvideRuntimeApi<Block>
+		+ Send
+		+ Sync,
+	Client::Api: SassafrasApi<Block> + ApiExt<Block>,
+{
+	type Error = ConsensusError;
+
+	async fn import_block(
+		&self,
+		mut block: BlockImportParams<Block>,
+	) -> Result<ImportResult, Self::Error> {
+		let hash = block.post_hash();
+		let number = *block.header.number();
+		let info = self.client.info();
+
+		let block_status = self
+			.client
+			.status(hash)
+			.map_err(|e| ConsensusError::ClientImport(e.to_string()))?;
+
+		// Skip Sassafras logic if block already in chain or importing blocks during initial
+		// sync, otherwise the check for epoch changes will error because trying to re-import
+		// an epoch change or because of missing epoch data in the tree, respectively.
+		if info.block_gap.map_or(false, |gap| gap.start <= number && number <= gap.end) ||
+			block_status == BlockStatus::InChain
+		{
+			// When re-importing existing block strip away intermediates.
+			// In case of initial sync intermediates should not be present...
+			let _ = block.remove_intermediate::<SassafrasIntermediate<Block>>(INTERMEDIATE_KEY);
+			block.fork_choice = Some(ForkChoiceStrategy::Custom(false));
+			return self.inner.import_block(block).await.map_err(Into::into)
+		}
+
+		if block.with_state() {
+			return self.import_state(block).await
+		}
+
+		let claim = find_slot_claim::<Block>(&block.header).expect(
+			"valid sassafras headers must contain a slot claim; header has been already verified; \
+			 qed",
+		);
+		let slot = claim.slot;
+
+		let parent_hash = *block.header.parent_hash();
+		let parent_header = self
+			.client
+			.header(parent_hash)
+			.map_err(|e| ConsensusError::ChainLookup(e.to_string()))?
+			.ok_or_else(|| {
+				ConsensusError::ChainLookup(
+					sassafras_err(Error::<Block>::ParentUnavailable(parent_hash, hash)).into(),
+				)
+			})?;
+
+		let parent_slot = find_slot_claim::<Block>(&parent_header).map(|claim| claim.slot).expect(
+			"parent is non-genesis; valid Sassafras headers contain a slot claim; header has \
+			 already been verified; qed",
+		);
+
+		// make sure that slot number is strictly increasing
+		if slot <= parent_slot {
+			return Err(ConsensusError::ClientImport(
+				sassafras_err(Error::<Block>::SlotMustIncrease(parent_slot, slot)).into(),
+			))
+		}
+
+		// if there's a pending epoch we'll save the previous epoch changes here
+		// this way we can revert it if there's any error
+		let mut old_epoch_changes = None;
+
+		// Use an extra scope to make the compiler happy, because otherwise it complains about the
+		// mutex, even if we dropped it...
+		let mut epoch_changes = {
+			let mut epoch_changes = self.epoch_changes.shared_data_locked();
+
+			// check if there's any epoch change expected to happen at this slot.
+			// `epoch` is the epoch to verify the block under, and `first_in_epoch` is true
+			// if this is the first block in its chain for that epoch.
+			//
+			// also provides the total weight of the chain, including the imported block.
+			let (epoch_descriptor, first_in_epoch, parent_weight) = {
+				let parent_weight = if *parent_header.number() == Zero::zero() {
+					0
+				} else {
+					aux_schema::load_block_weight(&*self.client, parent_hash)
+						.map_err(|e| ConsensusError::ClientImport(e.to_string()))?
+						.ok_or_else(|| {
+							ConsensusError::ClientImport(
+								sassafras_err(Error::<Block>::ParentBlockNoAssociatedWeight(hash))
+									.into(),
+							)
+						})?
+				};
+
+				let intermediate =
+					block.remove_intermediate::<SassafrasIntermediate<Block>>(INTERMEDIATE_KEY)?;
+
+				let epoch_descriptor = intermediate.epoch_descriptor;
+				let first_in_epoch = parent_slot < epoch_descriptor.start_slot();
+				(epoch_descriptor, first_in_epoch, parent_weight)
+			};
+
+			// Only blocks claiming a ticket add to the weight of the chain.
+			let total_weight = parent_weight + claim.ticket_claim.is_some() as u32;
+
+			// search for this all the time so we can reject unexpected announcements.
+			let next_epoch_digest = find_next_epoch_digest::<Block>(&block.header)
+				.map_err(|e| ConsensusError::ClientImport(e.to_string()))?;
+
+			match (first_in_epoch, next_epoch_digest.is_some()) {
+				(true, true) | (false, false) => {},
+				(true, false) =>
+					return Err(ConsensusError::ClientImport(
+						sassafras_err(Error::<Block>::ExpectedEpochChange(hash, slot)).into(),
+					)),
+				(false, true) =>
+					return Err(ConsensusError::ClientImport(
+						sassafras_err(Error::<Block>::UnexpectedEpochChange).into(),
+					)),
+			}
+
+			if let Some(next_epoch_descriptor) = next_epoch_digest {
+				old_epoch_changes = Some((*epoch_changes).clone());
+
+				let mut viable_epoch = epoch_changes
+					.viable_epoch(&epoch_descriptor, |slot| Epoch::genesis(&self.config, slot))
+					.ok_or_else(|| {
+						ConsensusError::ClientImport(Error::<Block>::FetchEpoch(parent_hash).into())
+					})?
+					.into_cloned();
+
+				// restrict info logging during initial sync to avoid spam
+				let log_level = if block.origin == BlockOrigin::NetworkInitialSync {
+					log::Level::Debug
+				} else {
+					log::Level::Info
+				};
+
+				if viable_epoch.as_ref().end_slot() <= slot {
+					// Some epochs must have been skipped as our current slot fits outside the
+					// current epoch. We will figure out which epoch it belongs to and we will
+					// re-use the same data for that epoch.
+					// Only the local copy of the `Epoch` is updated, other forks (that we
+					// haven't imported) may not skip the epoch and need the original data.
+					let epoch = viable_epoch.as_mut();
+					let prev_index = epoch.index;
+					*epoch = epoch.clone_for_slot(slot);
+
+					warn!(
+						target: LOG_TARGET,
+						"👶 Epoch(s) skipped: from {} to {}", prev_index, epoch.index,
+					);
+				}
+
+				log!(
+					target: LOG_TARGET,
+					log_level,
+					"👶 New epoch {} launching at block {} (block slot {} >= start slot {}).",
+					viable_epoch.as_ref().index,
+					hash,
+					slot,
+					viable_epoch.as_ref().start,
+				);
+
+				let next_epoch = viable_epoch.increment(next_epoch_descriptor);
+
+				log!(
+					target: LOG_TARGET,
+					log_level,
+					"👶 Next epoch starts at slot {}",
+					next_epoch.as_ref().start,
+				);
+
+				// prune the tree of epochs not part of the finalized chain or
+				// that are not live anymore, and then track the given epoch change
+				// in the tree.
+				// NOTE: it is important that these operations are done in this
+				// order, otherwise if pruning after import the `is_descendent_of`
+				// used by pruning may not know about the block that is being
+				// imported.
+				let prune_and_import = || {
+					prune_finalized(self.client.clone(), &mut epoch_changes)?;
+
+					epoch_changes
+						.import(
+							descendent_query(&*self.client),
+							hash,
+							number,
+							*block.header.parent_hash(),
+							next_epoch,
+						)
+						.map_err(|e| {
+							ConsensusError::ClientImport(format!(
+								"Error importing epoch changes: {}",
+								e
+							))
+						})?;
+					Ok(())
+				};
+
+				if let Err(e) = prune_and_import() {
+					debug!(target: LOG_TARGET, "Failed to launch next epoch: {}", e);
+					*epoch_changes =
+						old_epoch_changes.expect("set `Some` above and not taken; qed");
+					return Err(e)
+				}
+
+				aux_schema::write_epoch_changes::<Block, _, _>(&*epoch_changes, |insert| {
+					block
+						.auxiliary
+						.extend(insert.iter().map(|(k, v)| (k.to_vec(), Some(v.to_vec()))))
+				});
+			}
+
+			aux_schema::write_block_weight(hash, total_weight, |values| {
+				block
+					.auxiliary
+					.extend(values.iter().map(|(k, v)| (k.to_vec(), Some(v.to_vec()))))
+			});
+
+			// The fork choice rule is that we pick the heaviest chain (i.e.
+			// more blocks claiming a ticket), if there's a tie we go with the
+			// longest chain.
+			block.fork_choice = {
+				let (last_best, last_best_number) = (info.best_hash, info.best_number);
+
+				let last_best_weight = if &last_best == block.header.parent_hash() {
+					// the parent=genesis case is already covered for loading parent weight,
+					// so we don't need to cover again here.
+					parent_weight
+				} else {
+					aux_schema::load_block_weight(&*self.client, last_best)
+						.map_err(|e| ConsensusError::ChainLookup(e.to_string()))?
+						.ok_or_else(|| {
+							ConsensusError::ChainLookup(
+								"No block weight for parent header.".to_string(),
+							)
+						})?
+				};
+
+				Some(ForkChoiceStrategy::Custom(if total_weight > last_best_weight {
+					true
+				} else if total_weight == last_best_weight {
+					number > last_best_number
+				} else {
+					false
+				}))
+			};
+
+			// Release the mutex, but it stays locked
+			epoch_changes.release_mutex()
+		};
+
+		let import_result = self.inner.import_block(block).await;
+
+		// revert to the original epoch changes in case there's an error
+		// importing the block
+		if import_result.is_err() {
+			if let Some(old_epoch_changes) = old_epoch_changes {
+				*epoch_changes.upgrade() = old_epoch_changes;
+			}
+		}
+
+		import_result.map_err(Into::into)
+	}
+
+	async fn check_block(
+		&self,
+		block: BlockCheckParams<Block>,
+	) -> Result<ImportResult, Self::Error> {
+		self.inner.check_block(block).await.map_err(Into::into)
+	}
+}
+
+/// Gets the best finalized block and its slot, and prunes the given epoch tree.
+fn prune_finalized<Block, Client>(
+	client: Arc<Client>,
+	epoch_changes: &mut EpochChangesFor<Block, Epoch>,
+) -> Result<(), ConsensusError>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block> + HeaderMetadata<Block, Error = sp_blockchain::Error>,
+{
+	let info = client.info();
+
+	let finalized_slot = {
+		let finalized_header = client
+			.header(info.finalized_hash)
+			.map_err(|e| ConsensusError::ClientImport(e.to_string()))?
+			.expect(
+				"best finalized hash was given by client; finalized headers must exist in db; qed",
+			);
+
+		find_slot_claim::<Block>(&finalized_header)
+			.expect("finalized header must be valid; valid blocks have a slot claim; qed")
+			.slot
+	};
+
+	epoch_changes
+		.prune_finalized(
+			descendent_query(&*client),
+			&info.finalized_hash,
+			info.finalized_number,
+			finalized_slot,
+		)
+		.map_err(|e| ConsensusError::ClientImport(e.to_string()))?;
+
+	Ok(())
+}
+
+/// Produce a Sassafras block-import object to be used later on in the construction of
+/// an import-queue.
+///
+/// Also returns a link object used to correctly instantiate the import queue
+/// and background worker.
+pub fn block_import<Client, Block: BlockT, I>(
+	config: SassafrasConfiguration,
+	wrapped_block_import: I,
+	client: Arc<Client>,
+) -> ClientResult<(SassafrasBlockImport<Block, Client, I>, SassafrasLink<Block>)>
+where
+	Client: AuxStore
+		+ HeaderBackend<Block>
+		+ HeaderMetadata<Block, Error = sp_blockchain::Error>
+		+ PreCommitActions<Block>
+		+ 'static,
+{
+	let epoch_changes = aux_schema::load_epoch_changes::<Block, _>(&*client)?;
+	let link = SassafrasLink { epoch_changes: epoch_changes.clone(), config: config.clone() };
+
+	prune_finalized(client.clone(), &mut epoch_changes.shared_data())?;
+
+	crate::register_aux_storage_cleanup(&client);
+
+	let import = SassafrasBlockImport::new(client, epoch_changes, wrapped_block_import, config);
+
+	Ok((import, link))
+}
@@ -0,0 +1,804 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! # Sassafras
+//!
+//! Sassafras (Semi-Anonymous Sortition of Staked Assignees For Fixed-time Rhythmic Assignment
+//! of Slots) is a slot-based block production mechanism which assigns every slot of an epoch to
+//! exactly one authority.
+//!
+//! The assignment is the outcome of a lottery run during the previous epoch. Every authority
+//! generates a number of tickets for the next epoch, whose identifiers are the outputs of a VRF
+//! evaluated on the next epoch randomness. The tickets with an identifier below a threshold are
+//! submitted on-chain, anonymously: their bodies are signed with a ring-VRF, which proves that the
+//! ticket comes from one of the next epoch authorities without revealing which one. The runtime
+//! then sorts the submitted tickets and assigns them to the slots of the next epoch.
+//!
+//! When a slot comes, the owner of its ticket claims it by signing with the erased ephemeral key
+//! of the ticket and by revealing, through a VRF, the seed of the revealed ephemeral key of the
+//! ticket. Slots without an assigned ticket fall back to a secondary assignment, to the authority
+//! at index:
+//!
+//! `blake2_64(epoch_randomness ++ slot_number) % authorities_len`.
+//!
+//! As for BABE, epoch changes are announced one epoch in advance and tracked in an epoch changes
+//! tree. The fork choice rule is weight-based, where weight equals the number of blocks claimed
+//! with a ticket in the chain, and we go with the longest chain in case of a tie.
+//!
+//! An in-depth description of the protocol can be found here:
+//! <https://research.web3.foundation/Polkadot/protocols/block-production/SASSAFRAS>
+
+#![forbid(unsafe_code)]
+#![warn(missing_docs)]
+
+use std::{
+	collections::{BTreeMap, HashSet},
+	ops::{Deref, DerefMut},
+	sync::Arc,
+};
+
+use codec::{Decode, Encode};
+use futures::prelude::*;
+use log::{debug, info, trace, warn};
+use prometheus_endpoint::Registry;
+
+use sc_client_api::{
+	backend::AuxStore, AuxDataOperations, Backend as BackendT, BlockchainEvents,
+	FinalityNotification, PreCommitActions, UsageProvider,
+};
+use sc_consensus::{
+	block_import::BlockImport,
+	import_queue::{BasicQueue, BoxJustificationImport, DefaultImportQueue},
+};
+use sc_consensus_epochs::{descendent_query, Epoch as EpochT, EpochChangesFor, SharedEpochChanges};
+use sc_consensus_slots::{BackoffAuthoringBlocksStrategy, InherentDataProviderExt};
+use sc_telemetry::TelemetryHandle;
+use sc_transaction_pool_api::OffchainTransactionPoolFactory;
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_block_builder::BlockBuilder as BlockBuilderApi;
+use sp_blockchain::{
+	Backend as _, Error as ClientError, HeaderBackend, HeaderMetadata, Result as ClientResult,
+};
+use sp_consensus::{Environment, Error as ConsensusError, Proposer, SelectChain};
+use sp_core::traits::{SpawnEssentialNamed, SpawnNamed};
+use sp_inherents::CreateInherentDataProviders;
+use sp_keystore::KeystorePtr;
+use sp_runtime::{
+	generic::OpaqueDigestItemId,
+	traits::{Block as BlockT, Header, NumberFor, SaturatedConversion, Zero},
+};
+
+pub use sc_consensus_slots::SlotProportion;
+pub use sp_consensus::SyncOracle;
+pub use sp_consensus_sassafras::{
+	digests::{ConsensusLog, NextEpochDescriptor, SlotClaim},
+	AuthorityId, AuthorityIndex, AuthorityPair, AuthoritySignature, EpochConfiguration, Randomness,
+	SassafrasApi, SassafrasBlockWeight, Slot, SlotDuration, TicketBody, TicketClaim,
+	TicketEnvelope, TicketId, SASSAFRAS_ENGINE_ID,
+};
+
+pub use aux_schema::load_block_weight as block_weight;
+pub use block_import::{block_import, SassafrasBlockImport};
+pub use verification::SassafrasVerifier;
+
+mod block_import;
+mod verification;
+
+pub mod authorship;
+pub mod aux_schema;
+#[cfg(test)]
+mod tests;
+
+const LOG_TARGET: &str = "sassafras";
+
+/// Secret data of a ticket submitted by this node.
+///
+/// Required to claim the slot the ticket gets assigned to.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct TicketSecret {
+	/// Attempt index of the ticket.
+	pub attempt_idx: u32,
+	/// Seed of the erased ephemeral key of the ticket.
+	pub erased_secret: [u8; 32],
+}
+
+/// Sassafras epoch information.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct Epoch {
+	/// Epoch information as exposed by the runtime.
+	pub inner: sp_consensus_sassafras::Epoch,
+	/// Tickets submitted by this node for the epoch, with the index of the authority owning
+	/// them and their secrets.
+	pub tickets_aux: BTreeMap<TicketId, (AuthorityIndex, TicketSecret)>,
+}
+
+impl Deref for Epoch {
+	type Target = sp_consensus_sassafras::Epoch;
+
+	fn deref(&self) -> &Self::Target {
+		&self.inner
+	}
+}
+
+impl DerefMut for Epoch {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.inner
+	}
+}
+
+impl From<sp_consensus_sassafras::Epoch> for Epoch {
+	fn from(epoch: sp_consensus_sassafras::Epoch) -> Self {
+		Epoch { inner: epoch, tickets_aux: Default::default() }
+	}
+}
+
+impl EpochT for Epoch {
+	type NextEpochDescriptor = NextEpochDescriptor;
+	type Slot = Slot;
+
+	fn increment(&self, descriptor: NextEpochDescriptor) -> Epoch {
+		sp_consensus_sassafras::Epoch {
+			index: self.index + 1,
+			start: self.start + self.length as u64,
+			length: self.length,
+			randomness: descriptor.randomness,
+			authorities: descriptor.authorities,
+			config: descriptor.config.unwrap_or(self.config),
+		}
+		.into()
+	}
+
+	fn start_slot(&self) -> Slot {
+		self.start
+	}
+
+	fn end_slot(&self) -> Slot {
+		self.start + self.length as u64
+	}
+}
+
+impl Epoch {
+	/// Create the genesis epoch (epoch #0).
+	///
+	/// This is defined to start at the slot of the first block, so that has to be provided.
+	pub fn genesis(config: &SassafrasConfiguration, slot: Slot) -> Epoch {
+		let mut epoch: Epoch = config.genesis_epoch.clone().into();
+		epoch.index = 0;
+		epoch.start = slot;
+		epoch
+	}
+
+	/// Clone and tweak epoch information to refer to the specified slot.
+	///
+	/// All the information which depends on the slot value is recomputed and assigned
+	/// to the returned epoch instance.
+	///
+	/// The `slot` must be greater than or equal the original epoch start slot,
+	/// if is less this operation is equivalent to a simple clone.
+	pub fn clone_for_slot(&self, slot: Slot) -> Epoch {
+		let mut epoch = self.clone();
+
+		let skipped_epochs = *slot.saturating_sub(self.start) / self.length as u64;
+
+		let index = epoch.index.checked_add(skipped_epochs).expect(
+			"epoch number is u64; it should be strictly smaller than number of slots; \
+				slots relate in some way to wall clock time; \
+				if u64 is not enough we should crash for safety; qed.",
+		);
+
+		let start = skipped_epochs
+			.checked_mul(epoch.length as u64)
+			.and_then(|skipped_slots| epoch.start.checked_add(skipped_slots))
+			.expect(
+				"slot number is u64; it should relate in some way to wall clock time; \
+				 if u64 is not enough we should crash for safety; qed.",
+			);
+
+		epoch.index = index;
+		epoch.start = Slot::from(start);
+		// Tickets were submitted for the original epoch, they can't be claimed in another one.
+		epoch.tickets_aux.clear();
+
+		epoch
+	}
+}
+
+/// Sassafras configuration.
+#[derive(Clone, Debug)]
+pub struct SassafrasConfiguration {
+	/// Duration of a slot.
+	pub slot_duration: SlotDuration,
+	/// Parameters of the genesis epoch.
+	///
+	/// Only used to verify the first block of the chain, its start slot is ignored.
+	pub genesis_epoch: sp_consensus_sassafras::Epoch,
+}
+
+impl SassafrasConfiguration {
+	/// Duration of a slot.
+	pub fn slot_duration(&self) -> SlotDuration {
+		self.slot_duration
+	}
+}
+
+/// Errors encountered by the Sassafras authorship task.
+#[derive(Debug, thiserror::Error)]
+pub enum Error<B: BlockT> {
+	/// Multiple Sassafras pre-runtime digests
+	#[error("Multiple Sassafras pre-runtime digests, rejecting!")]
+	MultiplePreRuntimeDigests,
+	/// No Sassafras pre-runtime digest found
+	#[error("No Sassafras pre-runtime digest found")]
+	NoPreRuntimeDigest,
+	/// Multiple Sassafras epoch change digests
+	#[error("Multiple Sassafras epoch change digests, rejecting!")]
+	MultipleEpochChangeDigests,
+	/// Could not fetch epoch
+	#[error("Could not fetch epoch at {0:?}")]
+	FetchEpoch(B::Hash),
+	/// Header rejected: too far in the future
+	#[error("Header {0:?} rejected: too far in the future")]
+	TooFarInFuture(B::Hash),
+	/// Parent unavailable. Cannot import
+	#[error("Parent ({0}) of {1} unavailable. Cannot import")]
+	ParentUnavailable(B::Hash, B::Hash),
+	/// Slot number must increase
+	#[error("Slot number must increase: parent slot: {0}, this slot: {1}")]
+	SlotMustIncrease(Slot, Slot),
+	/// Header has a bad seal
+	#[error("Header {0:?} has a bad seal")]
+	HeaderBadSeal(B::Hash),
+	/// Header is unsealed
+	#[error("Header {0:?} is unsealed")]
+	HeaderUnsealed(B::Hash),
+	/// Slot author not found
+	#[error("Slot author not found")]
+	SlotAuthorNotFound,
+	/// Bad signature
+	#[error("Bad signature on {0:?}")]
+	BadSignature(B::Hash),
+	/// Invalid author: Expected secondary author
+	#[error("Invalid author: Expected secondary author: {0:?}, got: {1:?}.")]
+	InvalidAuthor(AuthorityId, AuthorityId),
+	/// VRF verification failed
+	#[error("VRF verification failed")]
+	VrfVerificationFailed,
+	/// The slot has a ticket but the header doesn't claim it.
+	#[error("Expected a ticket claim for slot {0}")]
+	ExpectedTicketClaim(Slot),
+	/// The header claims a ticket but the slot has none.
+	#[error("Unexpected ticket claim for slot {0}")]
+	UnexpectedTicketClaim(Slot),
+	/// Ticket claim verification failed
+	#[error("Ticket claim verification failed for slot {0}")]
+	TicketClaimVerificationFailed(Slot),
+	/// Could not fetch parent header
+	#[error("Could not fetch parent header: {0}")]
+	FetchParentHeader(sp_blockchain::Error),
+	/// Expected epoch change to happen.
+	#[error("Expected epoch change to happen at {0:?}, s{1}")]
+	ExpectedEpochChange(B::Hash, Slot),
+	/// Unexpected epoch change
+	#[error("Unexpected epoch change")]
+	UnexpectedEpochChange,
+	/// Parent block has no associated weight
+	#[error("Parent block of {0} has no associated weight")]
+	ParentBlockNoAssociatedWeight(B::Hash),
+	/// Check inherents error
+	#[error("Checking inherents failed: {0}")]
+	CheckInherents(sp_inherents::Error),
+	/// Unhandled check inherents error
+	#[error("Checking inherents unhandled error: {}", String::from_utf8_lossy(.0))]
+	CheckInherentsUnhandled(sp_inherents::InherentIdentifier),
+	/// Create inherents error.
+	#[error("Creating inherents failed: {0}")]
+	CreateInherents(sp_inherents::Error),
+	/// Client error
+	#[error(transparent)]
+	Client(sp_blockchain::Error),
+	/// Runtime Api error.
+	#[error(transparent)]
+	RuntimeApi(sp_api::ApiError),
+	/// Fork tree error
+	#[error(transparent)]
+	ForkTree(Box<fork_tree::Error<sp_blockchain::Error>>),
+}
+
+impl<B: BlockT> From<Error<B>> for String {
+	fn from(error: Error<B>) -> String {
+		error.to_string()
+	}
+}
+
+fn sassafras_err<B: BlockT>(error: Error<B>) -> Error<B> {
+	debug!(target: LOG_TARGET, "{}", error);
+	error
+}
+
+/// Intermediate value passed to block importer.
+pub struct SassafrasIntermediate<B: BlockT> {
+	/// The epoch descriptor.
+	pub epoch_descriptor: sc_consensus_epochs::ViableEpochDescriptor<B::Hash, NumberFor<B>, Epoch>,
+}
+
+/// Intermediate key for Sassafras engine.
+pub static INTERMEDIATE_KEY: &[u8] = b"sass1";
+
+/// Read the configuration from the runtime state at current best block.
+///
+/// The runtime doesn't expose the slot duration, which has to be provided.
+pub fn configuration<B: BlockT, C>(
+	client: &C,
+	slot_duration: SlotDuration,
+) -> ClientResult<SassafrasConfiguration>
+where
+	C: AuxStore + ProvideRuntimeApi<B> + UsageProvider<B>,
+	C::Api: SassafrasApi<B>,
+{
+	let at_hash = if client.usage_info().chain.finalized_state.is_some() {
+		client.usage_info().chain.best_hash
+	} else {
+		debug!(target: LOG_TARGET, "No finalized state is available. Reading config from genesis");
+		client.usage_info().chain.genesis_hash
+	};
+
+	let genesis_epoch = client.runtime_api().current_epoch(at_hash)?;
+	Ok(SassafrasConfiguration { slot_duration, genesis_epoch })
+}
+
+/// Parameters for Sassafras.
+pub struct SassafrasParams<B: BlockT, C, SC, E, I, SO, L, CIDP, BS> {
+	/// The keystore that manages the keys of the node.
+	pub keystore: KeystorePtr,
+
+	/// The client to use
+	pub client: Arc<C>,
+
+	/// The SelectChain Strategy
+	pub select_chain: SC,
+
+	/// The environment we are producing blocks for.
+	pub env: E,
+
+	/// The underlying block-import object to supply our produced blocks to.
+	/// This must be a `SassafrasBlockImport` or a wrapper of it, otherwise
+	/// critical consensus logic will be omitted.
+	pub block_import: I,
+
+	/// A sync oracle
+	pub sync_oracle: SO,
+
+	/// Hook into the sync module to control the justification sync process.
+	pub justification_sync_link: L,
+
+	/// Something that can create the inherent data providers.
+	pub create_inherent_data_providers: CIDP,
+
+	/// Force authoring of blocks even if we are offline
+	pub force_authoring: bool,
+
+	/// Strategy and parameters for backing off block production.
+	pub backoff_authoring_blocks: Option<BS>,
+
+	/// The source of timestamps for relative slots
+	pub sassafras_link: SassafrasLink<B>,
+
+	/// The proportion of the slot dedicated to proposing.
+	///
+	/// The block proposing will be limited to this proportion of the slot from the starting of the
+	/// slot. However, the proposing can still take longer when there is some lenience factor
+	/// applied, because there were no blocks produced for some slots.
+	pub block_proposal_slot_portion: SlotProportion,
+
+	/// The maximum proportion of the slot dedicated to proposing with any lenience factor applied
+	/// due to no blocks being produced.
+	pub max_block_proposal_slot_portion: Option<SlotProportion>,
+
+	/// Handle use to report telemetries.
+	pub telemetry: Option<TelemetryHandle>,
+
+	/// The offchain transaction pool factory.
+	///
+	/// Will be used when submitting tickets.
+	pub offchain_tx_pool_factory: OffchainTransactionPoolFactory<B>,
+
+	/// Spawner of the blocking tasks generating the tickets.
+	pub spawner: Box<dyn SpawnNamed>,
+}
+
+/// Start the Sassafras worker.
+///
+/// The returned future drives both the slot worker, which claims slots and authors blocks, and
+/// the tickets worker, which submits the tickets of the node for the next epoch.
+pub fn start_sassafras<B, C, SC, E, I, SO, CIDP, BS, L, Error>(
+	SassafrasParams {
+		keystore,
+		client,
+		select_chain,
+		env,
+		block_import,
+		sync_oracle,
+		justification_sync_link,
+		create_inherent_data_providers,
+		force_authoring,
+		backoff_authoring_blocks,
+		sassafras_link,
+		block_proposal_slot_portion,
+		max_block_proposal_slot_portion,
+		telemetry,
+		offchain_tx_pool_factory,
+		spawner,
+	}: SassafrasParams<B, C, SC, E, I, SO, L, CIDP, BS>,
+) -> Result<SassafrasWorker, ConsensusError>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>
+		+ HeaderBackend<B>
+		+ HeaderMetadata<B, Error = ClientError>
+		+ BlockchainEvents<B>
+		+ AuxStore
+		+ Send
+		+ Sync
+		+ 'static,
+	C::Api: SassafrasApi<B>,
+	SC: SelectChain<B> + 'static,
+	E: Environment<B, Error = Error> + Send + Sync + 'static,
+	E::Proposer: Proposer<B, Error = Error>,
+	I: BlockImport<B, Error = ConsensusError> + Send + Sync + 'static,
+	SO: SyncOracle + Send + Sync + Clone + 'static,
+	L: sc_consensus::JustificationSyncLink<B> + 'static,
+	CIDP: CreateInherentDataProviders<B, ()> + Send + Sync + 'static,
+	CIDP::InherentDataProviders: InherentDataProviderExt + Send,
+	BS: BackoffAuthoringBlocksStrategy<NumberFor<B>> + Send + Sync + 'static,
+	Error: std::error::Error + Send + From<ConsensusError> + From<I::Error> + 'static,
+{
+	let worker = authorship::SassafrasSlotWorker {
+		client: client.clone(),
+		block_import,
+		env,
+		sync_oracle: sync_oracle.clone(),
+		justification_sync_link,
+		force_authoring,
+		backoff_authoring_blocks,
+		keystore: keystore.clone(),
+		epoch_changes: sassafras_link.epoch_changes.clone(),
+		config: sassafras_link.config.clone(),
+		block_proposal_slot_portion,
+		max_block_proposal_slot_portion,
+		telemetry,
+	};
+
+	info!(target: LOG_TARGET, "🌳 Starting Sassafras Authorship worker");
+
+	let slot_worker = sc_consensus_slots::start_slot_worker(
+		sassafras_link.config.slot_duration(),
+		select_chain,
+		sc_consensus_slots::SimpleSlotWorkerToSlotWorker(worker),
+		sync_oracle,
+		create_inherent_data_providers,
+	);
+
+	let tickets_worker = authorship::start_tickets_worker(
+		client,
+		keystore,
+		sassafras_link.epoch_changes,
+		offchain_tx_pool_factory,
+		spawner,
+	);
+
+	let inner = future::select(Box::pin(slot_worker), Box::pin(tickets_worker)).map(|_| ());
+	Ok(SassafrasWorker { inner: Box::pin(inner) })
+}
+
+/// Worker for Sassafras which implements `Future<Output=()>`. This must be polled.
+#[must_use]
+pub struct SassafrasWorker {
+	inner: std::pin::Pin<Box<dyn Future<Output = ()> + Send + 'static>>,
+}
+
+impl Future for SassafrasWorker {
+	type Output = ();
+
+	fn poll(
+		mut self: std::pin::Pin<&mut Self>,
+		cx: &mut std::task::Context,
+	) -> std::task::Poll<Self::Output> {
+		self.inner.as_mut().poll(cx)
+	}
+}
+
+// Remove obsolete block's weight data by leveraging finality notifications.
+// This includes data for all finalized blocks (excluding the most recent one)
+// and all stale branches.
+fn aux_storage_cleanup<C: HeaderMetadata<Block> + HeaderBackend<Block>, Block: BlockT>(
+	client: &C,
+	notification: &FinalityNotification<Block>,
+) -> AuxDataOperations {
+	let mut hashes = HashSet::new();
+
+	let first = notification.tree_route.first().unwrap_or(&notification.hash);
+	match client.header_metadata(*first) {
+		Ok(meta) => {
+			hashes.insert(meta.parent);
+		},
+		Err(err) => {
+			warn!(target: LOG_TARGET, "Failed to lookup metadata for block `{:?}`: {}", first, err,)
+		},
+	}
+
+	// Cleans data for finalized block's ancestors
+	hashes.extend(
+		notification
+			.tree_route
+			.iter()
+			// Ensure we don't prune latest finalized block.
+			// This should not happen, but better be safe than sorry!
+			.filter(|h| **h != notification.hash),
+	);
+
+	// Cleans data for stale forks.
+	let stale_forks = match client.expand_forks(&notification.stale_heads) {
+		Ok(stale_forks) => stale_forks,
+		Err(e) => {
+			warn!(target: LOG_TARGET, "{:?}", e);
+
+			Default::default()
+		},
+	};
+	hashes.extend(stale_forks.iter());
+
+	hashes
+		.into_iter()
+		.map(|val| (aux_schema::block_weight_key(val), None))
+		.collect()
+}
+
+/// Extract the Sassafras slot claim from the given header. Slot claims are
+/// mandatory, the function will return `Err` if none is found.
+pub fn find_slot_claim<B: BlockT>(header: &B::Header) -> Result<SlotClaim, Error<B>> {
+	// genesis block doesn't contain a slot claim so let's generate a
+	// dummy one to not break any invariants in the rest of the code
+	if header.number().is_zero() {
+		return Ok(SlotClaim {
+			authority_idx: 0,
+			slot: 0.into(),
+			vrf_signature: sp_consensus_sassafras::vrf::VrfSignature {
+				signature: Default::default(),
+				pre_outputs: Default::default(),
+			},
+			ticket_claim: None,
+		})
+	}
+
+	let mut claim: Option<_> = None;
+	for log in header.digest().logs() {
+		trace!(target: LOG_TARGET, "Checking log {:?}, looking for slot claim", log);
+		match (SlotClaim::try_from(log), claim.is_some()) {
+			(Ok(_), true) => return Err(sassafras_err(Error::MultiplePreRuntimeDigests)),
+			(Err(_), _) => trace!(target: LOG_TARGET, "Ignoring digest not meant for us"),
+			(Ok(c), false) => claim = Some(c),
+		}
+	}
+	claim.ok_or_else(|| sassafras_err(Error::NoPreRuntimeDigest))
+}
+
+/// Extract the Sassafras epoch change digest from the given header, if it exists.
+fn find_next_epoch_digest<B: BlockT>(
+	header: &B::Header,
+) -> Result<Option<NextEpochDescriptor>, Error<B>> {
+	let mut epoch_digest: Option<_> = None;
+	for log in header.digest().logs() {
+		trace!(target: LOG_TARGET, "Checking log {:?}, looking for epoch change digest.", log);
+		let log = log.try_to::<ConsensusLog>(OpaqueDigestItemId::Consensus(&SASSAFRAS_ENGINE_ID));
+		match (log, epoch_digest.is_some()) {
+			(Some(ConsensusLog::NextEpochData(_)), true) =>
+				return Err(sassafras_err(Error::MultipleEpochChangeDigests)),
+			(Some(ConsensusLog::NextEpochData(epoch)), false) => epoch_digest = Some(epoch),
+			_ => trace!(target: LOG_TARGET, "Ignoring digest not meant for us"),
+		}
+	}
+
+	Ok(epoch_digest)
+}
+
+/// State that must be shared between the import queue and the authoring logic.
+#[derive(Clone)]
+pub struct SassafrasLink<Block: BlockT> {
+	epoch_changes: SharedEpochChanges<Block, Epoch>,
+	config: SassafrasConfiguration,
+}
+
+impl<Block: BlockT> SassafrasLink<Block> {
+	/// Get the epoch changes of this link.
+	pub fn epoch_changes(&self) -> &SharedEpochChanges<Block, Epoch> {
+		&self.epoch_changes
+	}
+
+	/// Get the config of this link.
+	pub fn config(&self) -> &SassafrasConfiguration {
+		&self.config
+	}
+}
+
+/// Parameters passed to [`import_queue`].
+pub struct ImportQueueParams<'a, Block: BlockT, BI, Client, CIDP, SelectChain, Spawn> {
+	/// The Sassafras link that is created by [`block_import`].
+	pub link: SassafrasLink<Block>,
+	/// The block import that should be wrapped.
+	pub block_import: BI,
+	/// Optional justification import.
+	pub justification_import: Option<BoxJustificationImport<Block>>,
+	/// The client to interact with the internals of the node.
+	pub client: Arc<Client>,
+	/// A [`SelectChain`] implementation.
+	///
+	/// Used to determine the best block that should be used as basis when sending an equivocation
+	/// report.
+	pub select_chain: SelectChain,
+	/// Used to crate the inherent data providers.
+	///
+	/// These inherent data providers are then used to create the inherent data that is
+	/// passed to the `check_inherents` runtime call.
+	pub create_inherent_data_providers: CIDP,
+	/// Spawner for spawning futures.
+	pub spawner: &'a Spawn,
+	/// Registry for prometheus metrics.
+	pub registry: Option<&'a Registry>,
+	/// Optional telemetry handle to report telemetry events.
+	pub telemetry: Option<TelemetryHandle>,
+	/// The offchain transaction pool factory.
+	///
+	/// Will be used when sending equivocation reports.
+	pub offchain_tx_pool_factory: OffchainTransactionPoolFactory<Block>,
+}
+
+/// Start an import queue for the Sassafras consensus algorithm.
+///
+/// The block import object provided must be the `SassafrasBlockImport` or a wrapper
+/// of it, otherwise crucial import logic will be omitted.
+pub fn import_queue<Block: BlockT, Client, SelectChain, BI, CIDP, Spawn>(
+	ImportQueueParams {
+		link,
+		block_import,
+		justification_import,
+		client,
+		select_chain,
+		create_inherent_data_providers,
+		spawner,
+		registry,
+		telemetry,
+		offchain_tx_pool_factory,
+	}: ImportQueueParams<'_, Block, BI, Client, CIDP, SelectChain, Spawn>,
+) -> ClientResult<DefaultImportQueue<Block>>
+where
+	BI: BlockImport<Block, Error = ConsensusError> + Send + Sync + 'static,
+	Client: ProvideRuntimeApi<Block>
+		+ HeaderBackend<Block>
+		+ HeaderMetadata<Block, Error = sp_blockchain::Error>
+		+ AuxStore
+		+ Send
+		+ Sync
+		+ 'static,
+	Client::Api: BlockBuilderApi<Block> + SassafrasApi<Block> + ApiExt<Block>,
+	SelectChain: sp_consensus::SelectChain<Block> + 'static,
+	CIDP: CreateInherentDataProviders<Block, ()> + Send + Sync + 'static,
+	CIDP::InherentDataProviders: InherentDataProviderExt + Send + Sync,
+	Spawn: SpawnEssentialNamed,
+{
+	let verifier = SassafrasVerifier::new(
+		client,
+		select_chain,
+		create_inherent_data_providers,
+		link.epoch_changes,
+		link.config,
+		telemetry,
+		offchain_tx_pool_factory,
+	);
+
+	Ok(BasicQueue::new(verifier, Box::new(block_import), justification_import, spawner, registry))
+}
+
+/// Reverts protocol aux data to at most the last finalized block.
+/// In particular, epoch-changes and block weights announced after the revert
+/// point are removed.
+pub fn revert<Block, Client, Backend>(
+	client: Arc<Client>,
+	backend: Arc<Backend>,
+	blocks: NumberFor<Block>,
+) -> ClientResult<()>
+where
+	Block: BlockT,
+	Client: AuxStore + HeaderMetadata<Block, Error = sp_blockchain::Error> + HeaderBackend<Block>,
+	Backend: BackendT<Block>,
+{
+	let best_number = client.info().best_number;
+	let finalized = client.info().finalized_number;
+
+	let revertible = blocks.min(best_number - finalized);
+	if revertible == Zero::zero() {
+		return Ok(())
+	}
+
+	let revert_up_to_number = best_number - revertible;
+	let revert_up_to_hash = client.hash(revert_up_to_number)?.ok_or(ClientError::Backend(
+		format!("Unexpected hash lookup failure for block number: {}", revert_up_to_number),
+	))?;
+
+	// Revert epoch changes tree.
+
+	let epoch_changes = aux_schema::load_epoch_changes::<Block, Client>(&*client)?;
+	let mut epoch_changes = epoch_changes.shared_data();
+
+	if revert_up_to_number == Zero::zero() {
+		// Special case, no epoch changes data were present on genesis.
+		*epoch_changes = EpochChangesFor::<Block, Epoch>::default();
+	} else {
+		epoch_changes.revert(descendent_query(&*client), revert_up_to_hash, revert_up_to_number);
+	}
+
+	// Remove block weights added after the revert point.
+
+	let mut weight_keys = HashSet::with_capacity(revertible.saturated_into());
+
+	let leaves = backend.blockchain().leaves()?.into_iter().filter(|&leaf| {
+		sp_blockchain::tree_route(&*client, revert_up_to_hash, leaf)
+			.map(|route| route.retracted().is_empty())
+			.unwrap_or_default()
+	});
+
+	for leaf in leaves {
+		let mut hash = leaf;
+		loop {
+			let meta = client.header_metadata(hash)?;
+			if meta.number <= revert_up_to_number ||
+				!weight_keys.insert(aux_schema::block_weight_key(hash))
+			{
+				// We've reached the revert point or an already processed branch, stop here.
+				break
+			}
+			hash = meta.parent;
+		}
+	}
+
+	let weight_keys: Vec<_> = weight_keys.iter().map(|val| val.as_slice()).collect();
+
+	// Write epoch changes and remove weights in one shot.
+	aux_schema::write_epoch_changes::<Block, _, _>(&epoch_changes, |values| {
+		client.insert_aux(values, weight_keys.iter())
+	})
+}
+
+/// Register the finality action cleaning up the block weights of the finalized and stale blocks.
+fn register_aux_storage_cleanup<Block, Client>(client: &Arc<Client>)
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block>
+		+ HeaderMetadata<Block, Error = sp_blockchain::Error>
+		+ PreCommitActions<Block>
+		+ 'static,
+{
+	let client_weak = Arc::downgrade(client);
+	let on_finality = move |summary: &FinalityNotification<Block>| {
+		if let Some(client) = client_weak.upgrade() {
+			aux_storage_cleanup(client.as_ref(), summary)
+		} else {
+			Default::default()
+		}
+	};
+	client.register_finality_action(Box::new(on_finality));
+}
@@ -0,0 +1,831 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Sassafras testsuite
+
+use super::*;
+use authorship::{claim_slot, generate_epoch_tickets, secondary_authority_index};
+use parking_lot::Mutex;
+use sc_block_builder::{BlockBuilder, BlockBuilderBuilder};
+use sc_consensus::{
+	BlockCheckParams, BlockImportParams, BoxBlockImport, BoxJustificationImport, ImportResult,
+	Verifier,
+};
+use sc_consensus_slots::{BackoffAuthoringOnFinalizedHeadLagging, CheckedHeader};
+use sc_network_test::{Block as TestBlock, *};
+use sc_transaction_pool_api::RejectAllTxPool;
+use sp_application_crypto::AppCrypto;
+use sp_consensus::{BlockOrigin, DisableProofRecording, NoNetwork as DummyOracle, Proposal};
+use sp_consensus_sassafras::{
+	vrf::{self, RingContext},
+	Epoch as RuntimeEpoch,
+};
+use sp_inherents::{InherentData, InherentDataProvider, InherentIdentifier};
+use sp_keyring::BandersnatchKeyring;
+use sp_keystore::{testing::MemoryKeystore, Keystore};
+use sp_runtime::{
+	generic::{Digest, DigestItem},
+	traits::Block as BlockT,
+};
+use sp_timestamp::Timestamp;
+use std::{cell::RefCell, task::Poll, time::Duration};
+use verification::{check_header, VerificationParams};
+
+type Error = sp_blockchain::Error;
+
+/// Client answering the Sassafras runtime API calls on behalf of the test runtime, which doesn't
+/// implement them, and delegating everything else to the full client.
+#[derive(Clone)]
+struct TestClient(Arc<PeersFullClient>);
+
+impl HeaderBackend<TestBlock> for TestClient {
+	fn header(&self, hash: Hash) -> ClientResult<Option<TestHeader>> {
+		self.0.header(hash)
+	}
+
+	fn info(&self) -> sp_blockchain::Info<TestBlock> {
+		self.0.info()
+	}
+
+	fn status(&self, hash: Hash) -> ClientResult<sp_blockchain::BlockStatus> {
+		self.0.status(hash)
+	}
+
+	fn number(&self, hash: Hash) -> ClientResult<Option<NumberFor<TestBlock>>> {
+		self.0.number(hash)
+	}
+
+	fn hash(&self, number: NumberFor<TestBlock>) -> ClientResult<Option<Hash>> {
+		self.0.hash(number)
+	}
+}
+
+impl HeaderMetadata<TestBlock> for TestClient {
+	type Error = ClientError;
+
+	fn header_metadata(
+		&self,
+		hash: Hash,
+	) -> ClientResult<sp_blockchain::CachedHeaderMetadata<TestBlock>> {
+		self.0.header_metadata(hash)
+	}
+
+	fn insert_header_metadata(
+		&self,
+		hash: Hash,
+		header_metadata: sp_blockchain::CachedHeaderMetadata<TestBlock>,
+	) {
+		self.0.insert_header_metadata(hash, header_metadata)
+	}
+
+	fn remove_header_metadata(&self, hash: Hash) {
+		self.0.remove_header_metadata(hash)
+	}
+}
+
+impl AuxStore for TestClient {
+	fn insert_aux<
+		'a,
+		'b: 'a,
+		'c: 'a,
+		I: IntoIterator<Item = &'a (&'c [u8], &'c [u8])>,
+		D: IntoIterator<Item = &'a &'b [u8]>,
+	>(
+		&self,
+		insert: I,
+		delete: D,
+	) -> ClientResult<()> {
+		self.0.insert_aux(insert, delete)
+	}
+
+	fn get_aux(&self, key: &[u8]) -> ClientResult<Option<Vec<u8>>> {
+		self.0.get_aux(key)
+	}
+}
+
+impl BlockchainEvents<TestBlock> for TestClient {
+	fn import_notification_stream(&self) -> sc_client_api::ImportNotifications<TestBlock> {
+		self.0.import_notification_stream()
+	}
+
+	fn every_import_notification_stream(&self) -> sc_client_api::ImportNotifications<TestBlock> {
+		self.0.every_import_notification_stream()
+	}
+
+	fn finality_notification_stream(&self) -> sc_client_api::FinalityNotifications<TestBlock> {
+		self.0.finality_notification_stream()
+	}
+
+	fn storage_changes_notification_stream(
+		&self,
+		filter_keys: Option<&[sc_client_api::StorageKey]>,
+		child_filter_keys: Option<
+			&[(sc_client_api::StorageKey, Option<Vec<sc_client_api::StorageKey>>)],
+		>,
+	) -> ClientResult<sc_client_api::StorageEventStream<Hash>> {
+		self.0.storage_changes_notification_stream(filter_keys, child_filter_keys)
+	}
+}
+
+impl PreCommitActions<TestBlock> for TestClient {
+	fn register_import_action(&self, op: sc_client_api::OnImportAction<TestBlock>) {
+		self.0.register_import_action(op)
+	}
+
+	fn register_finality_action(&self, op: sc_client_api::OnFinalityAction<TestBlock>) {
+		self.0.register_finality_action(op)
+	}
+}
+
+impl ProvideRuntimeApi<TestBlock> for TestClient {
+	type Api = RuntimeApi;
+
+	fn runtime_api(&self) -> sp_api::ApiRef<'_, Self::Api> {
+		RuntimeApi { client: self.0.clone() }.into()
+	}
+}
+
+struct RuntimeApi {
+	client: Arc<PeersFullClient>,
+}
+
+sp_api::mock_impl_runtime_apis! {
+	impl SassafrasApi<TestBlock> for RuntimeApi {
+		fn ring_context() -> Option<RingContext> {
+			None
+		}
+
+		fn submit_tickets_unsigned_extrinsic(_tickets: Vec<TicketEnvelope>) -> bool {
+			false
+		}
+
+		fn slot_ticket_id(_slot: Slot) -> Option<TicketId> {
+			None
+		}
+
+		fn slot_ticket(_slot: Slot) -> Option<(TicketId, TicketBody)> {
+			None
+		}
+
+		fn current_epoch() -> RuntimeEpoch {
+			test_config().genesis_epoch
+		}
+
+		fn next_epoch() -> RuntimeEpoch {
+			let genesis_epoch = test_config().genesis_epoch;
+			RuntimeEpoch {
+				index: 1,
+				start: genesis_epoch.start + u64::from(EPOCH_LENGTH),
+				..genesis_epoch
+			}
+		}
+	}
+
+	impl BlockBuilderApi<TestBlock> for RuntimeApi {
+		#[advanced]
+		fn check_inherents(
+			&self,
+			at: Hash,
+			block: TestBlock,
+			data: InherentData,
+		) -> Result<sp_inherents::CheckInherentsResult, sp_api::ApiError> {
+			self.client.runtime_api().check_inherents(at, block, data)
+		}
+	}
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Stage {
+	PreSeal,
+	PostSeal,
+}
+
+type Mutator = Arc<dyn Fn(&mut TestHeader, Stage) + Send + Sync>;
+
+type SassafrasBlockImport =
+	PanickingBlockImport<crate::SassafrasBlockImport<TestBlock, TestClient, Arc<PeersFullClient>>>;
+
+const SLOT_DURATION_MS: u64 = 1000;
+
+const EPOCH_LENGTH: u32 = 4;
+
+const TEST_AUTHORITIES: [BandersnatchKeyring; 3] =
+	[BandersnatchKeyring::Alice, BandersnatchKeyring::Bob, BandersnatchKeyring::Charlie];
+
+fn test_authorities() -> Vec<AuthorityId> {
+	TEST_AUTHORITIES.iter().map(|keyring| keyring.public().into()).collect()
+}
+
+fn test_config() -> SassafrasConfiguration {
+	SassafrasConfiguration {
+		slot_duration: SlotDuration::from_millis(SLOT_DURATION_MS),
+		genesis_epoch: RuntimeEpoch {
+			index: 0,
+			start: 0.into(),
+			length: EPOCH_LENGTH,
+			randomness: [0; 32],
+			authorities: test_authorities(),
+			config: EpochConfiguration { redundancy_factor: 1, attempts_number: 2 },
+		},
+	}
+}
+
+/// Slot inherent data provider, only used to let the client know the current slot.
+struct SlotProvider(Slot);
+
+impl Deref for SlotProvider {
+	type Target = Slot;
+
+	fn deref(&self) -> &Slot {
+		&self.0
+	}
+}
+
+#[async_trait::async_trait]
+impl InherentDataProvider for SlotProvider {
+	async fn provide_inherent_data(&self, _: &mut InherentData) -> Result<(), sp_inherents::Error> {
+		Ok(())
+	}
+
+	async fn try_handle_error(
+		&self,
+		_: &InherentIdentifier,
+		_: &[u8],
+	) -> Option<Result<(), sp_inherents::Error>> {
+		None
+	}
+}
+
+#[derive(Clone)]
+struct DummyFactory {
+	client: Arc<PeersFullClient>,
+	epoch_changes: SharedEpochChanges<TestBlock, Epoch>,
+	mutator: Mutator,
+}
+
+struct DummyProposer {
+	factory: DummyFactory,
+	parent_header: TestHeader,
+}
+
+impl Environment<TestBlock> for DummyFactory {
+	type CreateProposer = future::Ready<Result<DummyProposer, Error>>;
+	type Proposer = DummyProposer;
+	type Error = Error;
+
+	fn init(&mut self, parent_header: &<TestBlock as BlockT>::Header) -> Self::CreateProposer {
+		future::ready(Ok(DummyProposer {
+			factory: self.clone(),
+			parent_header: parent_header.clone(),
+		}))
+	}
+}
+
+impl DummyProposer {
+	fn propose_with(
+		&mut self,
+		mut pre_digests: Digest,
+	) -> future::Ready<Result<Proposal<TestBlock, ()>, Error>> {
+		// The test runtime doesn't track the Sassafras epochs, announce the next epoch in the
+		// first block of every epoch as the runtime would.
+		let slot = pre_digests
+			.logs
+			.iter()
+			.find_map(|log| SlotClaim::try_from(log).ok())
+			.expect("slot claim is always provided")
+			.slot;
+		let parent_slot = find_slot_claim::<TestBlock>(&self.parent_header).unwrap().slot;
+		let epoch_descriptor = self
+			.factory
+			.epoch_changes
+			.shared_data()
+			.epoch_descriptor_for_child_of(
+				descendent_query(&*self.factory.client),
+				&self.parent_header.hash(),
+				*self.parent_header.number(),
+				slot,
+			)
+			.unwrap()
+			.unwrap();
+		if parent_slot < epoch_descriptor.start_slot() {
+			let next_epoch = NextEpochDescriptor {
+				randomness: [*self.parent_header.number() as u8; 32],
+				authorities: test_authorities(),
+				config: None,
+			};
+			pre_digests.push(DigestItem::Consensus(
+				SASSAFRAS_ENGINE_ID,
+				ConsensusLog::NextEpochData(next_epoch).encode(),
+			));
+		}
+
+		let block_builder = BlockBuilderBuilder::new(&*self.factory.client)
+			.on_parent_block(self.parent_header.hash())
+			.fetch_parent_block_number(&*self.factory.client)
+			.unwrap()
+			.with_inherent_digests(pre_digests)
+			.build()
+			.unwrap();
+
+		let mut block = match block_builder.build().map_err(|e| e.into()) {
+			Ok(b) => b.block,
+			Err(e) => return future::ready(Err(e)),
+		};
+
+		// mutate the block header according to the mutator.
+		(self.factory.mutator)(&mut block.header, Stage::PreSeal);
+
+		future::ready(Ok(Proposal { block, proof: (), storage_changes: Default::default() }))
+	}
+}
+
+impl Proposer<TestBlock> for DummyProposer {
+	type Error = Error;
+	type Proposal = future::Ready<Result<Proposal<TestBlock, ()>, Error>>;
+	type ProofRecording = DisableProofRecording;
+	type Proof = ();
+
+	fn propose(
+		mut self,
+		_: InherentData,
+		pre_digests: Digest,
+		_: Duration,
+		_: Option<usize>,
+	) -> Self::Proposal {
+		self.propose_with(pre_digests)
+	}
+}
+
+thread_local! {
+	static MUTATOR: RefCell<Mutator> = RefCell::new(Arc::new(|_, _|()));
+}
+
+#[derive(Clone)]
+pub struct PanickingBlockImport<B>(B);
+
+#[async_trait::async_trait]
+impl<BI> BlockImport<TestBlock> for PanickingBlockImport<BI>
+where
+	BI: BlockImport<TestBlock> + Send + Sync,
+{
+	type Error = BI::Error;
+
+	async fn import_block(
+		&self,
+		block: BlockImportParams<TestBlock>,
+	) -> Result<ImportResult, Self::Error> {
+		Ok(self.0.import_block(block).await.expect("importing block failed"))
+	}
+
+	async fn check_block(
+		&self,
+		block: BlockCheckParams<TestBlock>,
+	) -> Result<ImportResult, Self::Error> {
+		Ok(self.0.check_block(block).await.expect("checking block failed"))
+	}
+}
+
+type SassafrasPeer = Peer<Option<PeerData>, SassafrasBlockImport>;
+
+#[derive(Default)]
+pub struct SassafrasTestNet {
+	peers: Vec<SassafrasPeer>,
+}
+
+type TestHeader = <TestBlock as BlockT>::Header;
+
+type TestSelectChain =
+	substrate_test_runtime_client::LongestChain<substrate_test_runtime_client::Backend, TestBlock>;
+
+pub struct TestVerifier {
+	inner: SassafrasVerifier<
+		TestBlock,
+		TestClient,
+		TestSelectChain,
+		Box<
+			dyn CreateInherentDataProviders<TestBlock, (), InherentDataProviders = (SlotProvider,)>,
+		>,
+	>,
+	mutator: Mutator,
+}
+
+#[async_trait::async_trait]
+impl Verifier<TestBlock> for TestVerifier {
+	async fn verify(
+		&self,
+		mut block: BlockImportParams<TestBlock>,
+	) -> Result<BlockImportParams<TestBlock>, String> {
+		// apply post-sealing mutations (i.e. stripping seal, if desired).
+		(self.mutator)(&mut block.header, Stage::PostSeal);
+		self.inner.verify(block).await
+	}
+}
+
+pub struct PeerData {
+	link: SassafrasLink<TestBlock>,
+	block_import: Mutex<Option<BoxBlockImport<TestBlock>>>,
+}
+
+impl TestNetFactory for SassafrasTestNet {
+	type Verifier = TestVerifier;
+	type PeerData = Option<PeerData>;
+	type BlockImport = SassafrasBlockImport;
+
+	fn make_block_import(
+		&self,
+		client: PeersClient,
+	) -> (
+		BlockImportAdapter<Self::BlockImport>,
+		Option<BoxJustificationImport<Block>>,
+		Option<PeerData>,
+	) {
+		let client = client.as_client();
+
+		let (block_import, link) =
+			crate::block_import(test_config(), client.clone(), Arc::new(TestClient(client)))
+				.expect("can initialize block-import");
+
+		let block_import = PanickingBlockImport(block_import);
+
+		let data_block_import =
+			Mutex::new(Some(Box::new(block_import.clone()) as BoxBlockImport<_>));
+		(
+			BlockImportAdapter::new(block_import),
+			None,
+			Some(PeerData { link, block_import: data_block_import }),
+		)
+	}
+
+	fn make_verifier(&self, client: PeersClient, maybe_link: &Option<PeerData>) -> Self::Verifier {
+		use substrate_test_runtime_client::DefaultTestClientBuilderExt;
+
+		let client = client.as_client();
+		trace!(target: LOG_TARGET, "Creating a verifier");
+
+		// ensure block import and verifier are linked correctly.
+		let data = maybe_link
+			.as_ref()
+			.expect("sassafras link always provided to verifier instantiation");
+
+		let (_, longest_chain) = TestClientBuilder::new().build_with_longest_chain();
+
+		TestVerifier {
+			inner: SassafrasVerifier::new(
+				Arc::new(TestClient(client)),
+				longest_chain,
+				Box::new(|_, _| async {
+					let slot = Slot::from_timestamp(
+						Timestamp::current(),
+						SlotDuration::from_millis(SLOT_DURATION_MS),
+					);
+					Ok((SlotProvider(slot),))
+				}),
+				data.link.epoch_changes.clone(),
+				data.link.config.clone(),
+				None,
+				OffchainTransactionPoolFactory::new(RejectAllTxPool::default()),
+			),
+			mutator: MUTATOR.with(|m| m.borrow().clone()),
+		}
+	}
+
+	fn peer(&mut self, i: usize) -> &mut SassafrasPeer {
+		trace!(target: LOG_TARGET, "Retrieving a peer");
+		&mut self.peers[i]
+	}
+
+	fn peers(&self) -> &Vec<SassafrasPeer> {
+		trace!(target: LOG_TARGET, "Retrieving peers");
+		&self.peers
+	}
+
+	fn peers_mut(&mut self) -> &mut Vec<SassafrasPeer> {
+		trace!(target: LOG_TARGET, "Retrieving peers, mutable");
+		&mut self.peers
+	}
+
+	fn mut_peers<F: FnOnce(&mut Vec<SassafrasPeer>)>(&mut self, closure: F) {
+		closure(&mut self.peers);
+	}
+}
+
+#[tokio::test]
+#[should_panic(expected = "No Sassafras pre-runtime digest found")]
+async fn rejects_empty_block() {
+	sp_tracing::try_init_simple();
+	let mut net = SassafrasTestNet::new(3);
+	let block_builder = |builder: BlockBuilder<_, _>| builder.build().unwrap().block;
+	net.mut_peers(|peer| {
+		peer[0].generate_blocks(1, BlockOrigin::NetworkInitialSync, block_builder);
+	})
+}
+
+fn create_keystore(authorities: &[BandersnatchKeyring]) -> KeystorePtr {
+	let keystore = MemoryKeystore::new();
+	for authority in authorities {
+		keystore
+			.bandersnatch_generate_new(AuthorityId::ID, Some(&authority.to_seed()))
+			.expect("Generates authority key");
+	}
+	keystore.into()
+}
+
+async fn run_one_test(mutator: impl Fn(&mut TestHeader, Stage) + Send + Sync + 'static) {
+	sp_tracing::try_init_simple();
+	let mutator = Arc::new(mutator) as Mutator;
+
+	MUTATOR.with(|m| *m.borrow_mut() = mutator.clone());
+
+	let net = SassafrasTestNet::new(3);
+
+	let net = Arc::new(Mutex::new(net));
+	let mut import_notifications = Vec::new();
+	let mut sassafras_futures = Vec::new();
+
+	for (peer_id, auth_id) in TEST_AUTHORITIES.iter().enumerate() {
+		let mut net = net.lock();
+		let peer = net.peer(peer_id);
+		let client = peer.client().as_client();
+		let select_chain = peer.select_chain().expect("Full client has select_chain");
+
+		let keystore = create_keystore(&[*auth_id]);
+
+		let mut got_own = false;
+		let mut got_other = false;
+
+		let data = peer.data.as_ref().expect("sassafras link set up during initialization");
+
+		let environ = DummyFactory {
+			client: client.clone(),
+			epoch_changes: data.link.epoch_changes.clone(),
+			mutator: mutator.clone(),
+		};
+
+		import_notifications.push(
+			// run each future until we get one of our own blocks with number higher than 5
+			// that was produced locally.
+			client
+				.import_notification_stream()
+				.take_while(move |n| {
+					future::ready(
+						n.header.number() < &5 || {
+							if n.origin == BlockOrigin::Own {
+								got_own = true;
+							} else {
+								got_other = true;
+							}
+
+							// continue until we have at least one block of our own
+							// and one of another peer.
+							!(got_own && got_other)
+						},
+					)
+				})
+				.for_each(|_| future::ready(())),
+		);
+
+		let client_clone = client.clone();
+		sassafras_futures.push(
+			start_sassafras(SassafrasParams {
+				block_import: data.block_import.lock().take().expect("import set up during init"),
+				select_chain,
+				client: Arc::new(TestClient(client)),
+				env: environ,
+				sync_oracle: DummyOracle,
+				create_inherent_data_providers: Box::new(move |parent, _| {
+					// Get the slot of the parent header and just increase this slot.
+					//
+					// Below we will running everything in one big future. If we would use
+					// time based slot, it can happen that on sassafras instance imports a block
+					// from another sassafras instance and then tries to build a block in the same
+					// slot making this test fail.
+					let parent_header = client_clone.header(parent).ok().flatten().unwrap();
+					let slot = find_slot_claim::<TestBlock>(&parent_header).unwrap().slot + 1;
+
+					async move { Ok((SlotProvider(slot),)) }
+				}),
+				force_authoring: false,
+				backoff_authoring_blocks: Some(BackoffAuthoringOnFinalizedHeadLagging::default()),
+				sassafras_link: data.link.clone(),
+				keystore,
+				justification_sync_link: (),
+				block_proposal_slot_portion: SlotProportion::new(0.5),
+				max_block_proposal_slot_portion: None,
+				telemetry: None,
+				offchain_tx_pool_factory: OffchainTransactionPoolFactory::new(
+					RejectAllTxPool::default(),
+				),
+				spawner: Box::new(sp_core::testing::TaskExecutor::new()),
+			})
+			.expect("Starts sassafras"),
+		);
+	}
+	future::select(
+		futures::future::poll_fn(move |cx| {
+			let mut net = net.lock();
+			net.poll(cx);
+			for p in net.peers() {
+				if let Some((h, e)) = p.failed_verifications().into_iter().next() {
+					panic!("Verification failed for {:?}: {}", h, e);
+				}
+			}
+
+			Poll::<()>::Pending
+		}),
+		future::select(future::join_all(import_notifications), future::join_all(sassafras_futures)),
+	)
+	.await;
+}
+
+#[tokio::test]
+async fn authoring_blocks() {
+	run_one_test(|_, _| ()).await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "has a bad seal")]
+async fn rejects_missing_seals() {
+	run_one_test(|header: &mut TestHeader, stage| {
+		let v = std::mem::take(&mut header.digest_mut().logs);
+		header.digest_mut().logs = v
+			.into_iter()
+			.filter(|v| stage == Stage::PreSeal || AuthoritySignature::try_from(v).is_err())
+			.collect()
+	})
+	.await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "Expected epoch change to happen")]
+async fn rejects_missing_consensus_digests() {
+	run_one_test(|header: &mut TestHeader, stage| {
+		let v = std::mem::take(&mut header.digest_mut().logs);
+		header.digest_mut().logs = v
+			.into_iter()
+			.filter(|v| {
+				stage == Stage::PostSeal ||
+					v.try_to::<ConsensusLog>(OpaqueDigestItemId::Consensus(&SASSAFRAS_ENGINE_ID))
+						.is_none()
+			})
+			.collect()
+	})
+	.await;
+}
+
+// Build a header of block #1 holding the given claim, and seal it with the key of `author`.
+fn sealed_header(claim: &SlotClaim, author: &AuthorityId, keystore: &KeystorePtr) -> TestHeader {
+	let mut header = TestHeader::new(
+		1,
+		Default::default(),
+		Default::default(),
+		Default::default(),
+		Digest { logs: vec![DigestItem::from(claim)] },
+	);
+	let pre_hash = header.hash();
+	let signature: AuthoritySignature = keystore
+		.bandersnatch_sign(AuthorityId::ID, author.as_ref(), pre_hash.as_ref())
+		.unwrap()
+		.unwrap()
+		.into();
+	header.digest_mut().push(DigestItem::from(&signature));
+	header
+}
+
+fn verify_claim(
+	claim: &SlotClaim,
+	author: &AuthorityId,
+	maybe_ticket: Option<(TicketId, TicketBody)>,
+	epoch: &Epoch,
+	keystore: &KeystorePtr,
+) -> Result<(), crate::Error<TestBlock>> {
+	let params = VerificationParams {
+		header: sealed_header(claim, author, keystore),
+		claim: claim.clone(),
+		maybe_ticket,
+		slot_now: claim.slot + 1,
+		epoch,
+	};
+	match check_header::<TestBlock>(params)? {
+		CheckedHeader::Checked(_, info) => {
+			assert_eq!(&info.author, author);
+			Ok(())
+		},
+		CheckedHeader::Deferred(..) => panic!("header is not in the future"),
+	}
+}
+
+#[test]
+fn claim_secondary_slots() {
+	let keystore = create_keystore(&TEST_AUTHORITIES);
+	let epoch = Epoch::genesis(&test_config(), 0.into());
+
+	for slot in (0..u64::from(EPOCH_LENGTH)).map(Slot::from) {
+		let (claim, author) = claim_slot(slot, &epoch, None, &keystore).unwrap();
+		let expected_idx = secondary_authority_index(slot, &epoch).unwrap();
+		assert_eq!(claim.authority_idx, expected_idx);
+		assert_eq!(author, epoch.authorities[expected_idx as usize]);
+		assert!(claim.ticket_claim.is_none());
+		verify_claim(&claim, &author, None, &epoch, &keystore).unwrap();
+
+		// A slot can't be claimed by another authority.
+		let other_idx = (expected_idx + 1) % epoch.authorities.len() as AuthorityIndex;
+		let other_author = epoch.authorities[other_idx as usize].clone();
+		let sign_data = vrf::slot_claim_sign_data(&epoch.randomness, slot, epoch.index);
+		let vrf_signature = keystore
+			.bandersnatch_vrf_sign(AuthorityId::ID, other_author.as_ref(), &sign_data)
+			.unwrap()
+			.unwrap();
+		let other_claim =
+			SlotClaim { authority_idx: other_idx, slot, vrf_signature, ticket_claim: None };
+		assert!(matches!(
+			verify_claim(&other_claim, &other_author, None, &epoch, &keystore),
+			Err(crate::Error::InvalidAuthor(..))
+		));
+	}
+
+	// Without the key of the secondary author the slot can't be claimed.
+	let keystore = create_keystore(&[BandersnatchKeyring::Alice]);
+	let claims = (0..u64::from(EPOCH_LENGTH))
+		.map(Slot::from)
+		.filter(|slot| claim_slot(*slot, &epoch, None, &keystore).is_some())
+		.count();
+	let expected = (0..u64::from(EPOCH_LENGTH))
+		.map(Slot::from)
+		.filter(|slot| secondary_authority_index(*slot, &epoch) == Some(0))
+		.count();
+	assert_eq!(claims, expected);
+}
+
+#[test]
+fn claim_ticket_slots() {
+	let authorities = [BandersnatchKeyring::Alice];
+	let keystore = create_keystore(&authorities);
+	let ring_context = RingContext::new_testing();
+
+	let mut epoch = Epoch::genesis(&test_config(), 0.into());
+	epoch.authorities = authorities.iter().map(|keyring| keyring.public().into()).collect();
+
+	// With a single authority all the attempts are below the threshold.
+	let tickets = generate_epoch_tickets(&mut epoch, &keystore, &ring_context);
+	assert_eq!(tickets.len(), epoch.config.attempts_number as usize);
+	assert_eq!(epoch.tickets_aux.len(), tickets.len());
+
+	let public_keys =
+		epoch.authorities.iter().cloned().map(|id| id.into_inner()).collect::<Vec<_>>();
+	let verifier = ring_context.verifier(&public_keys).unwrap();
+	let tickets = tickets
+		.into_iter()
+		.map(|envelope| {
+			// The ticket is anonymously signed by one of the epoch authorities.
+			let input =
+				vrf::ticket_id_input(&epoch.randomness, envelope.body.attempt_idx, epoch.index);
+			let sign_data = vrf::ticket_body_sign_data(&envelope.body, input.clone());
+			assert!(envelope.signature.ring_vrf_verify(&sign_data, &verifier));
+
+			let ticket_id = vrf::make_ticket_id(&input, &envelope.signature.pre_outputs[0]);
+			assert!(epoch.tickets_aux.contains_key(&ticket_id));
+			(ticket_id, envelope.body)
+		})
+		.collect::<Vec<_>>();
+
+	let slot = Slot::from(1);
+	let (claim, author) = claim_slot(slot, &epoch, Some(tickets[0].clone()), &keystore).unwrap();
+	assert!(claim.ticket_claim.is_some());
+	verify_claim(&claim, &author, Some(tickets[0].clone()), &epoch, &keystore).unwrap();
+
+	// The claim doesn't match another ticket.
+	assert!(matches!(
+		verify_claim(&claim, &author, Some(tickets[1].clone()), &epoch, &keystore),
+		Err(crate::Error::TicketClaimVerificationFailed(_))
+	));
+
+	// A ticket must be claimed if the slot has one, and only then.
+	assert!(matches!(
+		verify_claim(&claim, &author, None, &epoch, &keystore),
+		Err(crate::Error::UnexpectedTicketClaim(_))
+	));
+	let (secondary_claim, _) = claim_slot(slot, &epoch, None, &keystore).unwrap();
+	assert!(matches!(
+		verify_claim(&secondary_claim, &author, Some(tickets[0].clone()), &epoch, &keystore),
+		Err(crate::Error::ExpectedTicketClaim(_))
+	));
+
+	// Tickets submitted by other nodes can't be claimed.
+	epoch.tickets_aux.clear();
+	assert!(claim_slot(slot, &epoch, Some(tickets[0].clone()), &keystore).is_none());
+}
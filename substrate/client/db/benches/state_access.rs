@@ -122,6 +122,8 @@ fn create_backend(config: BenchmarkConfig, temp_dir: &TempDir) -> Backend<Block>
 		state_pruning: Some(PruningMode::ArchiveAll),
 		source: DatabaseSource::ParityDb { path },
 		blocks_pruning: BlocksPruning::KeepAll,
+		extrinsic_index: false,
+		extrinsic_signer: None,
 	};
 
 	Backend::new(settings, 100).expect("Creates backend")
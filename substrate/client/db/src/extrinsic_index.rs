@@ -0,0 +1,254 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Functionality for reading and storing the locations of extrinsics from db.
+//!
+//! Extrinsics are indexed by hash, and optionally by signer.
+//!
+//! The hash index maps the hash of an extrinsic to the list of `(block hash, extrinsic index)`
+//! pairs of the blocks that include it. An extrinsic can be included by several blocks on
+//! different forks.
+//!
+//! The signer index has one entry per signed extrinsic, keyed by the signer followed by the
+//! number and hash of the block and the index of the extrinsic in the body. Encoded signers are
+//! prefix-free, so the extrinsics of a signer are read by iterating over the keys starting with
+//! the signer, in ascending order of block numbers.
+
+use crate::{utils, DbHash, ExtrinsicSignerFn};
+use codec::{Decode, Encode};
+use sp_database::{Change, Database, Transaction};
+use sp_runtime::traits::{Block as BlockT, Hash, HashingFor, NumberFor};
+use std::collections::{hash_map::Entry, HashMap};
+
+/// The length of the extrinsic index at the end of the keys of the signer index.
+const SIGNER_KEY_INDEX_LEN: usize = 4;
+
+/// Returns the `(block hash, extrinsic index)` pairs stored under `key`.
+pub fn read_locations<Block: BlockT>(
+	db: &dyn Database<DbHash>,
+	column: u32,
+	key: &[u8],
+) -> sp_blockchain::Result<Vec<(Block::Hash, u32)>> {
+	match db.get(column, key) {
+		Some(raw_val) => decode_locations::<Block>(&raw_val),
+		None => Ok(Vec::new()),
+	}
+}
+
+fn decode_locations<Block: BlockT>(
+	raw_val: &[u8],
+) -> sp_blockchain::Result<Vec<(Block::Hash, u32)>> {
+	Decode::decode(&mut &raw_val[..])
+		.map_err(|_| sp_blockchain::Error::Backend("Error decoding extrinsic locations".into()))
+}
+
+/// Returns the hashes of the extrinsics of `body`, together with their index in the body.
+pub fn hash_keys<Block: BlockT>(body: &[Block::Extrinsic]) -> Vec<(Vec<u8>, u32)> {
+	body.iter()
+		.enumerate()
+		.map(|(index, extrinsic)| {
+			(HashingFor::<Block>::hash_of(extrinsic).as_ref().to_vec(), index as u32)
+		})
+		.collect()
+}
+
+/// Returns the keys of the signer index for the signed extrinsics of `body`, the body of the
+/// block with `number` and `hash`.
+fn signer_keys<Block: BlockT>(
+	number: NumberFor<Block>,
+	hash: Block::Hash,
+	body: &[Block::Extrinsic],
+	signer: &ExtrinsicSignerFn,
+) -> sp_blockchain::Result<Vec<Vec<u8>>> {
+	let lookup_key = utils::number_and_hash_to_lookup_key(number, hash)?;
+	Ok(body
+		.iter()
+		.enumerate()
+		.filter_map(|(index, extrinsic)| {
+			let mut key = signer(&extrinsic.encode())?;
+			key.extend_from_slice(&lookup_key);
+			key.extend_from_slice(&(index as u32).to_be_bytes());
+			Some(key)
+		})
+		.collect())
+}
+
+/// Prepare transaction to add the signed extrinsics of the block with `number` and `hash` to the
+/// signer index.
+pub fn insert_signers<Block: BlockT>(
+	tx: &mut Transaction<DbHash>,
+	column: u32,
+	number: NumberFor<Block>,
+	hash: Block::Hash,
+	body: &[Block::Extrinsic],
+	signer: &ExtrinsicSignerFn,
+) -> sp_blockchain::Result<()> {
+	for key in signer_keys::<Block>(number, hash, body, signer)? {
+		tx.set(column, &key, &[]);
+	}
+	Ok(())
+}
+
+/// Prepare transaction to remove the signed extrinsics of the block with `number` and `hash` from
+/// the signer index.
+pub fn remove_signers<Block: BlockT>(
+	tx: &mut Transaction<DbHash>,
+	column: u32,
+	number: NumberFor<Block>,
+	hash: Block::Hash,
+	body: &[Block::Extrinsic],
+	signer: &ExtrinsicSignerFn,
+) -> sp_blockchain::Result<()> {
+	for key in signer_keys::<Block>(number, hash, body, signer)? {
+		tx.remove(column, &key);
+	}
+	Ok(())
+}
+
+/// Returns up to `limit` `(block hash, extrinsic index)` pairs of the extrinsics signed by
+/// `signer`, starting after `start_key`.
+///
+/// The keys are opaque to callers. If more extrinsics follow the returned ones, the key to pass
+/// as `start_key` to read them is returned as well.
+pub fn read_signer_locations<Block: BlockT>(
+	db: &dyn Database<DbHash>,
+	column: u32,
+	signer: &[u8],
+	start_key: Option<&[u8]>,
+	limit: usize,
+) -> sp_blockchain::Result<(Vec<(Block::Hash, u32)>, Option<Vec<u8>>)> {
+	let mut locations = Vec::new();
+	let mut last_key = start_key.map(|key| key.to_vec());
+	let mut next_key = None;
+	let mut decode_error = None;
+
+	db.iter_with_prefix(column, signer, &mut |key, _| {
+		// The block number, the block hash and the extrinsic index.
+		let entry = &key[signer.len()..];
+		if start_key.is_some_and(|start_key| entry <= start_key) {
+			return true
+		}
+		if locations.len() >= limit {
+			next_key = last_key.take();
+			return false
+		}
+
+		let (block, index) = entry.split_at(entry.len().saturating_sub(SIGNER_KEY_INDEX_LEN));
+		let (Some(hash), Ok(index)) = (
+			block
+				.get(std::mem::size_of::<utils::NumberIndexKey>()..)
+				.and_then(|hash| Block::Hash::decode(&mut &hash[..]).ok()),
+			<[u8; SIGNER_KEY_INDEX_LEN]>::try_from(index),
+		) else {
+			decode_error =
+				Some(sp_blockchain::Error::Backend("Error decoding signer index entry".into()));
+			return false
+		};
+
+		locations.push((hash, u32::from_be_bytes(index)));
+		last_key = Some(entry.to_vec());
+		true
+	})?;
+
+	match decode_error {
+		Some(error) => Err(error),
+		None => Ok((locations, next_key)),
+	}
+}
+
+/// Prepare transaction to add the extrinsics of the block with `block_hash` to the index.
+///
+/// `keys` are the index keys of the extrinsics, together with their index in the body.
+pub fn insert_block<Block: BlockT>(
+	tx: &mut Transaction<DbHash>,
+	db: &dyn Database<DbHash>,
+	column: u32,
+	block_hash: Block::Hash,
+	keys: Vec<(Vec<u8>, u32)>,
+) -> sp_blockchain::Result<()> {
+	update_block::<Block>(tx, db, column, keys, |locations, index| {
+		if !locations.contains(&(block_hash, index)) {
+			locations.push((block_hash, index));
+		}
+	})
+}
+
+/// Prepare transaction to remove the extrinsics of the block with `block_hash` from the index.
+pub fn remove_block<Block: BlockT>(
+	tx: &mut Transaction<DbHash>,
+	db: &dyn Database<DbHash>,
+	column: u32,
+	block_hash: Block::Hash,
+	keys: Vec<(Vec<u8>, u32)>,
+) -> sp_blockchain::Result<()> {
+	update_block::<Block>(tx, db, column, keys, |locations, _| {
+		locations.retain(|(hash, _)| *hash != block_hash);
+	})
+}
+
+/// Apply `update` to the locations stored under each of `keys` and write the result to the
+/// transaction.
+///
+/// The changes to the index that are already part of the transaction take precedence over the
+/// database, so that several blocks can be updated in the same transaction.
+fn update_block<Block: BlockT>(
+	tx: &mut Transaction<DbHash>,
+	db: &dyn Database<DbHash>,
+	column: u32,
+	keys: Vec<(Vec<u8>, u32)>,
+	mut update: impl FnMut(&mut Vec<(Block::Hash, u32)>, u32),
+) -> sp_blockchain::Result<()> {
+	let mut pending = HashMap::<&[u8], Option<&[u8]>>::new();
+	for change in &tx.0 {
+		match change {
+			Change::Set(col, key, value) if *col == column => {
+				pending.insert(key.as_slice(), Some(value.as_slice()));
+			},
+			Change::Remove(col, key) if *col == column => {
+				pending.insert(key.as_slice(), None);
+			},
+			_ => {},
+		}
+	}
+
+	let mut changes = HashMap::<Vec<u8>, Vec<(Block::Hash, u32)>>::new();
+	for (key, index) in keys {
+		let locations = match changes.entry(key) {
+			Entry::Occupied(entry) => entry.into_mut(),
+			Entry::Vacant(entry) => {
+				let locations = match pending.get(entry.key().as_slice()) {
+					Some(Some(raw_val)) => decode_locations::<Block>(raw_val)?,
+					Some(None) => Vec::new(),
+					None => read_locations::<Block>(db, column, entry.key())?,
+				};
+				entry.insert(locations)
+			},
+		};
+		update(locations, index);
+	}
+
+	for (key, locations) in changes {
+		if locations.is_empty() {
+			tx.remove(column, &key);
+		} else {
+			tx.set_from_vec(column, &key, locations.encode());
+		}
+	}
+
+	Ok(())
+}
@@ -33,6 +33,7 @@ pub mod offchain;
 pub mod bench;
 
 mod children;
+mod extrinsic_index;
 mod parity_db;
 mod pinned_blocks_cache;
 mod record_stats_state;
@@ -115,6 +116,9 @@ const DB_HASH_LEN: usize = 32;
 /// Hash type that this backend uses for the database.
 pub type DbHash = sp_core::H256;
 
+/// Returns the encoded signer of the given encoded extrinsic, or `None` if it is not signed.
+pub type ExtrinsicSignerFn = Arc<dyn Fn(&[u8]) -> Option<Vec<u8>> + Send + Sync>;
+
 /// An extrinsic entry in the database.
 #[derive(Debug, Encode, Decode)]
 enum DbExtrinsic<B: BlockT> {
@@ -312,6 +316,15 @@ pub struct DatabaseSettings {
 	///
 	/// NOTE: only finalized blocks are subject for removal!
 	pub blocks_pruning: BlocksPruning,
+	/// Index the extrinsics of imported blocks by hash.
+	///
+	/// Entries are removed together with the body of the block that includes the extrinsic.
+	pub extrinsic_index: bool,
+	/// Extracts the signer of an extrinsic.
+	///
+	/// If set together with `extrinsic_index`, the extrinsics of imported blocks are also
+	/// indexed by signer.
+	pub extrinsic_signer: Option<ExtrinsicSignerFn>,
 }
 
 /// Block pruning settings.
@@ -438,6 +451,10 @@ pub(crate) mod columns {
 	/// Transactions
 	pub const TRANSACTION: u32 = 11;
 	pub const BODY_INDEX: u32 = 12;
+	/// Maps extrinsic hashes to the blocks that include them.
+	pub const EXTRINSIC_INDEX: u32 = 13;
+	/// Maps extrinsic signers to the blocks that include their extrinsics.
+	pub const SIGNER_INDEX: u32 = 14;
 }
 
 struct PendingBlock<Block: BlockT> {
@@ -487,10 +504,16 @@ pub struct BlockchainDb<Block: BlockT> {
 	header_metadata_cache: Arc<HeaderMetadataCache<Block>>,
 	header_cache: Mutex<LinkedHashMap<Block::Hash, Option<Block::Header>>>,
 	pinned_blocks_cache: Arc<RwLock<PinnedBlocksCache<Block>>>,
+	extrinsic_index: bool,
+	extrinsic_signer: Option<ExtrinsicSignerFn>,
 }
 
 impl<Block: BlockT> BlockchainDb<Block> {
-	fn new(db: Arc<dyn Database<DbHash>>) -> ClientResult<Self> {
+	fn new(
+		db: Arc<dyn Database<DbHash>>,
+		extrinsic_index: bool,
+		extrinsic_signer: Option<ExtrinsicSignerFn>,
+	) -> ClientResult<Self> {
 		let meta = read_meta::<Block>(&*db, columns::HEADER)?;
 		let leaves = LeafSet::read_from_db(&*db, columns::META, meta_keys::LEAF_PREFIX)?;
 		Ok(BlockchainDb {
@@ -500,9 +523,39 @@ impl<Block: BlockT> BlockchainDb<Block> {
 			header_metadata_cache: Arc::new(HeaderMetadataCache::default()),
 			header_cache: Default::default(),
 			pinned_blocks_cache: Arc::new(RwLock::new(PinnedBlocksCache::new())),
+			extrinsic_index,
+			extrinsic_signer: extrinsic_signer.filter(|_| extrinsic_index),
 		})
 	}
 
+	/// Read the extrinsic locations stored under `key` in the index `column`, skipping the blocks
+	/// whose body is no longer stored.
+	fn stored_extrinsic_locations(
+		&self,
+		column: u32,
+		key: &[u8],
+	) -> ClientResult<Vec<(Block::Hash, u32)>> {
+		let mut locations = Vec::new();
+		for (block_hash, index) in extrinsic_index::read_locations::<Block>(&*self.db, column, key)?
+		{
+			// Skip the blocks that were removed without going through pruning.
+			let Some(lookup_key) = utils::block_id_to_lookup_key(
+				&*self.db,
+				columns::KEY_LOOKUP,
+				BlockId::<Block>::Hash(block_hash),
+			)?
+			else {
+				continue
+			};
+			if self.db.contains(columns::BODY, &lookup_key) ||
+				self.db.contains(columns::BODY_INDEX, &lookup_key)
+			{
+				locations.push((block_hash, index));
+			}
+		}
+		Ok(locations)
+	}
+
 	fn update_meta(&self, update: MetaUpdate<Block>) {
 		let MetaUpdate { hash, number, is_best, is_finalized, with_state } = update;
 		let mut meta = self.meta.write();
@@ -784,6 +837,38 @@ impl<Block: BlockT> sc_client_api::blockchain::Backend<Block> for BlockchainDb<B
 				Err(sp_blockchain::Error::Backend(format!("Error decoding body list: {err}"))),
 		}
 	}
+
+	fn extrinsic_locations(
+		&self,
+		hash: Block::Hash,
+	) -> ClientResult<Option<Vec<(Block::Hash, u32)>>> {
+		if !self.extrinsic_index {
+			return Ok(None)
+		}
+
+		self.stored_extrinsic_locations(columns::EXTRINSIC_INDEX, hash.as_ref())
+			.map(Some)
+	}
+
+	fn signer_extrinsic_locations(
+		&self,
+		signer: &[u8],
+		start_key: Option<&[u8]>,
+		limit: usize,
+	) -> ClientResult<Option<(Vec<(Block::Hash, u32)>, Option<Vec<u8>>)>> {
+		if self.extrinsic_signer.is_none() {
+			return Ok(None)
+		}
+
+		extrinsic_index::read_signer_locations::<Block>(
+			&*self.db,
+			columns::SIGNER_INDEX,
+			signer,
+			start_key,
+			limit,
+		)
+		.map(Some)
+	}
 }
 
 impl<Block: BlockT> HeaderMetadata<Block> for BlockchainDb<Block> {
@@ -1158,6 +1243,29 @@ impl<Block: BlockT> Backend<Block> {
 	pub fn new_test_with_tx_storage(
 		blocks_pruning: BlocksPruning,
 		canonicalization_delay: u64,
+	) -> Self {
+		Self::new_test_with_settings(blocks_pruning, canonicalization_delay, false, None)
+	}
+
+	/// Create new memory-backed client backend for tests, indexing the extrinsics of imported
+	/// blocks.
+	///
+	/// The extrinsics are also indexed by signer if `extrinsic_signer` is given.
+	#[cfg(any(test, feature = "test-helpers"))]
+	pub fn new_test_with_extrinsic_index(
+		blocks_pruning: BlocksPruning,
+		canonicalization_delay: u64,
+		extrinsic_signer: Option<ExtrinsicSignerFn>,
+	) -> Self {
+		Self::new_test_with_settings(blocks_pruning, canonicalization_delay, true, extrinsic_signer)
+	}
+
+	#[cfg(any(test, feature = "test-helpers"))]
+	fn new_test_with_settings(
+		blocks_pruning: BlocksPruning,
+		canonicalization_delay: u64,
+		extrinsic_index: bool,
+		extrinsic_signer: Option<ExtrinsicSignerFn>,
 	) -> Self {
 		let db = kvdb_memorydb::create(crate::utils::NUM_COLUMNS);
 		let db = sp_database::as_database(db);
@@ -1171,6 +1279,8 @@ impl<Block: BlockT> Backend<Block> {
 			state_pruning: Some(state_pruning),
 			source: DatabaseSource::Custom { db, require_create_flag: true },
 			blocks_pruning,
+			extrinsic_index,
+			extrinsic_signer,
 		};
 
 		Self::new(db_setting, canonicalization_delay).expect("failed to create test-db")
@@ -1217,7 +1327,8 @@ impl<Block: BlockT> Backend<Block> {
 
 		let state_pruning_used = state_db.pruning_mode();
 		let is_archive_pruning = state_pruning_used.is_archive();
-		let blockchain = BlockchainDb::new(db.clone())?;
+		let blockchain =
+			BlockchainDb::new(db.clone(), config.extrinsic_index, config.extrinsic_signer.clone())?;
 
 		let storage_db =
 			StorageDb { db: db.clone(), state_db, prefix_keys: !db.supports_ref_counting() };
@@ -1499,6 +1610,26 @@ impl<Block: BlockT> Backend<Block> {
 
 			transaction.set_from_vec(columns::HEADER, &lookup_key, pending_block.header.encode());
 			if let Some(body) = pending_block.body {
+				if self.blockchain.extrinsic_index {
+					extrinsic_index::insert_block::<Block>(
+						&mut transaction,
+						&*self.storage.db,
+						columns::EXTRINSIC_INDEX,
+						hash,
+						extrinsic_index::hash_keys::<Block>(&body),
+					)?;
+				}
+				if let Some(signer) = &self.blockchain.extrinsic_signer {
+					extrinsic_index::insert_signers::<Block>(
+						&mut transaction,
+						columns::SIGNER_INDEX,
+						number,
+						hash,
+						&body,
+						signer,
+					)?;
+				}
+
 				// If we have any index operations we save block in the new format with indexed
 				// extrinsic headers Otherwise we save the body as a single blob.
 				if operation.index_ops.is_empty() {
@@ -1929,12 +2060,47 @@ impl<Block: BlockT> Backend<Block> {
 		Ok(())
 	}
 
+	/// Prepare transaction to remove the extrinsics of the block with the given hash from the
+	/// extrinsic index, before its body is removed or the block is reverted.
+	fn remove_from_extrinsic_index(
+		&self,
+		transaction: &mut Transaction<DbHash>,
+		hash: Block::Hash,
+	) -> ClientResult<()> {
+		if !self.blockchain.extrinsic_index {
+			return Ok(())
+		}
+		let Some(body) = self.blockchain.body_uncached(hash)? else { return Ok(()) };
+
+		extrinsic_index::remove_block::<Block>(
+			transaction,
+			&*self.storage.db,
+			columns::EXTRINSIC_INDEX,
+			hash,
+			extrinsic_index::hash_keys::<Block>(&body),
+		)?;
+		if let Some(signer) = &self.blockchain.extrinsic_signer {
+			extrinsic_index::remove_signers::<Block>(
+				transaction,
+				columns::SIGNER_INDEX,
+				self.blockchain.header_metadata(hash)?.number,
+				hash,
+				&body,
+				signer,
+			)?;
+		}
+		Ok(())
+	}
+
 	fn prune_block(
 		&self,
 		transaction: &mut Transaction<DbHash>,
 		id: BlockId<Block>,
 	) -> ClientResult<()> {
 		debug!(target: "db", "Removing block #{id}");
+		if let Some(hash) = self.blockchain.block_hash_from_id(&id)? {
+			self.remove_from_extrinsic_index(transaction, hash)?;
+		}
 		utils::remove_from_db(
 			transaction,
 			&*self.storage.db,
@@ -1983,6 +2149,18 @@ impl<Block: BlockT> Backend<Block> {
 		let state = RefTrackingState::new(db_state, self.storage.clone(), None);
 		RecordStatsState::new(state, None, self.state_usage.clone())
 	}
+
+	/// Returns the error reported when the state of the block `hash` is no longer available.
+	fn state_discarded_error(&self, hash: Block::Hash) -> sp_blockchain::Error {
+		let message = match self.storage.state_db.pruning_mode() {
+			PruningMode::Snapshots { period, max_blocks } => format!(
+				"State already discarded for {hash:?}: only the state of every {period}th finalized \
+				block and of the last {max_blocks} finalized blocks is kept",
+			),
+			_ => format!("State already discarded for {hash:?}"),
+		};
+		sp_blockchain::Error::UnknownBlock(message)
+	}
 }
 
 fn apply_state_commit(
@@ -2352,6 +2530,7 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 							}
 						}
 						transaction.set_from_vec(columns::META, meta_keys::BEST_BLOCK, key);
+						self.remove_from_extrinsic_index(&mut transaction, removed_hash)?;
 						transaction.remove(columns::KEY_LOOKUP, removed.hash().as_ref());
 						children::remove_children(
 							&mut transaction,
@@ -2405,9 +2584,7 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 
 		let hdr = self.blockchain.header_metadata(hash)?;
 		if !self.have_state_at(hash, hdr.number) {
-			return Err(sp_blockchain::Error::UnknownBlock(format!(
-				"State already discarded for {hash:?}",
-			)));
+			return Err(self.state_discarded_error(hash));
 		}
 
 		let mut leaves = self.blockchain.leaves.write();
@@ -2421,6 +2598,7 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 		if let Some(commit) = self.storage.state_db.remove(&hash) {
 			apply_state_commit(&mut transaction, commit);
 		}
+		self.remove_from_extrinsic_index(&mut transaction, hash)?;
 		transaction.remove(columns::KEY_LOOKUP, hash.as_ref());
 
 		let children: Vec<_> = self
@@ -2501,9 +2679,7 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 					let state = RefTrackingState::new(db_state, self.storage.clone(), Some(hash));
 					Ok(RecordStatsState::new(state, Some(hash), self.state_usage.clone()))
 				} else {
-					Err(sp_blockchain::Error::UnknownBlock(format!(
-						"State already discarded for {hash:?}",
-					)))
+					Err(self.state_discarded_error(hash))
 				}
 			},
 			Err(e) => Err(e),
@@ -2547,7 +2723,7 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 	fn requires_full_sync(&self) -> bool {
 		matches!(
 			self.storage.state_db.pruning_mode(),
-			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical
+			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical | PruningMode::Snapshots { .. }
 		)
 	}
 
@@ -2772,6 +2948,8 @@ pub(crate) mod tests {
 				state_pruning: Some(PruningMode::blocks_pruning(1)),
 				source: DatabaseSource::Custom { db: backing, require_create_flag: false },
 				blocks_pruning: BlocksPruning::KeepFinalized,
+				extrinsic_index: false,
+				extrinsic_signer: None,
 			},
 			0,
 		)
@@ -3762,6 +3940,185 @@ pub(crate) mod tests {
 		}
 	}
 
+	#[test]
+	fn extrinsic_index_follows_block_pruning() {
+		let backend =
+			Backend::<Block>::new_test_with_extrinsic_index(BlocksPruning::Some(2), 0, None);
+
+		let mut blocks = Vec::new();
+		let mut prev_hash = Default::default();
+		for i in 0..5 {
+			let hash = insert_block(
+				&backend,
+				i,
+				prev_hash,
+				None,
+				Default::default(),
+				vec![
+					UncheckedXt::new_transaction(i.into(), ()),
+					UncheckedXt::new_transaction(42.into(), ()),
+				],
+				None,
+			)
+			.unwrap();
+			blocks.push(hash);
+			prev_hash = hash;
+		}
+
+		let bc = backend.blockchain();
+		let extrinsic_hash = BlakeTwo256::hash_of(&UncheckedXt::new_transaction(1.into(), ()));
+		assert_eq!(bc.extrinsic_locations(extrinsic_hash).unwrap(), Some(vec![(blocks[1], 0)]));
+		let shared_hash = BlakeTwo256::hash_of(&UncheckedXt::new_transaction(42.into(), ()));
+		assert_eq!(
+			bc.extrinsic_locations(shared_hash).unwrap(),
+			Some(blocks.iter().map(|hash| (*hash, 1)).collect()),
+		);
+
+		{
+			let mut op = backend.begin_operation().unwrap();
+			backend.begin_state_operation(&mut op, blocks[4]).unwrap();
+			for i in 1..5 {
+				op.mark_finalized(blocks[i], None).unwrap();
+			}
+			backend.commit_operation(op).unwrap();
+		}
+
+		let bc = backend.blockchain();
+		assert_eq!(bc.extrinsic_locations(extrinsic_hash).unwrap(), Some(Vec::new()));
+		assert_eq!(
+			bc.extrinsic_locations(shared_hash).unwrap(),
+			Some(vec![(blocks[3], 1), (blocks[4], 1)]),
+		);
+		assert!(backend
+			.storage
+			.db
+			.get(columns::EXTRINSIC_INDEX, extrinsic_hash.as_ref())
+			.is_none());
+
+		// The index is disabled by default.
+		let backend = Backend::<Block>::new_test_with_tx_storage(BlocksPruning::KeepAll, 0);
+		assert_eq!(backend.blockchain().extrinsic_locations(shared_hash).unwrap(), None);
+	}
+
+	#[test]
+	fn signer_index_follows_block_pruning() {
+		let signer: ExtrinsicSignerFn =
+			Arc::new(|xt: &[u8]| match UncheckedXt::decode(&mut &xt[..]).ok()?.preamble {
+				sp_runtime::generic::Preamble::Signed(address, ..) => Some(address.encode()),
+				_ => None,
+			});
+		let backend = Backend::<Block>::new_test_with_extrinsic_index(
+			BlocksPruning::Some(2),
+			0,
+			Some(signer),
+		);
+		let signer_locations = |bc: &BlockchainDb<Block>, signer: u64| {
+			bc.signer_extrinsic_locations(&signer.encode(), None, usize::MAX)
+				.unwrap()
+				.map(|(locations, _)| locations)
+		};
+
+		let mut blocks = Vec::new();
+		let mut prev_hash = Default::default();
+		for i in 0..5 {
+			let hash = insert_block(
+				&backend,
+				i,
+				prev_hash,
+				None,
+				Default::default(),
+				vec![
+					UncheckedXt::new_transaction(i.into(), ()),
+					UncheckedXt::new_signed(i.into(), i % 2, (), ()),
+				],
+				None,
+			)
+			.unwrap();
+			blocks.push(hash);
+			prev_hash = hash;
+		}
+
+		let bc = backend.blockchain();
+		assert_eq!(
+			signer_locations(&bc, 0),
+			Some(vec![(blocks[0], 1), (blocks[2], 1), (blocks[4], 1)]),
+		);
+		assert_eq!(signer_locations(&bc, 1), Some(vec![(blocks[1], 1), (blocks[3], 1)]),);
+		assert_eq!(signer_locations(&bc, 2), Some(Vec::new()));
+
+		{
+			let mut op = backend.begin_operation().unwrap();
+			backend.begin_state_operation(&mut op, blocks[4]).unwrap();
+			for i in 1..5 {
+				op.mark_finalized(blocks[i], None).unwrap();
+			}
+			backend.commit_operation(op).unwrap();
+		}
+
+		let bc = backend.blockchain();
+		assert_eq!(signer_locations(&bc, 0), Some(vec![(blocks[4], 1)]),);
+		assert_eq!(signer_locations(&bc, 1), Some(vec![(blocks[3], 1)]),);
+
+		// Extrinsics are not indexed by signer without a signer extractor.
+		let backend =
+			Backend::<Block>::new_test_with_extrinsic_index(BlocksPruning::KeepAll, 0, None);
+		assert_eq!(signer_locations(backend.blockchain(), 0), None);
+	}
+
+	#[test]
+	fn signer_index_is_paginated_and_follows_revert() {
+		let signer: ExtrinsicSignerFn =
+			Arc::new(|xt: &[u8]| match UncheckedXt::decode(&mut &xt[..]).ok()?.preamble {
+				sp_runtime::generic::Preamble::Signed(address, ..) => Some(address.encode()),
+				_ => None,
+			});
+		let backend = Backend::<Block>::new_test_with_extrinsic_index(
+			BlocksPruning::KeepAll,
+			0,
+			Some(signer),
+		);
+
+		let mut blocks = Vec::new();
+		let mut prev_hash = Default::default();
+		for i in 0..3 {
+			let hash = insert_block(
+				&backend,
+				i,
+				prev_hash,
+				None,
+				Default::default(),
+				vec![UncheckedXt::new_signed(i.into(), 0, (), ())],
+				None,
+			)
+			.unwrap();
+			blocks.push(hash);
+			prev_hash = hash;
+		}
+
+		let bc = backend.blockchain();
+		let signer = 0u64.encode();
+		let (locations, start_key) =
+			bc.signer_extrinsic_locations(&signer, None, 2).unwrap().unwrap();
+		assert_eq!(locations, vec![(blocks[0], 0), (blocks[1], 0)]);
+		let start_key = start_key.unwrap();
+		assert_eq!(
+			bc.signer_extrinsic_locations(&signer, Some(&start_key), 2).unwrap(),
+			Some((vec![(blocks[2], 0)], None)),
+		);
+
+		// Reverted blocks are removed from the index.
+		assert_eq!(backend.revert(1, false).unwrap().0, 1);
+		assert_eq!(
+			backend.blockchain().signer_extrinsic_locations(&signer, None, 2).unwrap(),
+			Some((vec![(blocks[0], 0), (blocks[1], 0)], None)),
+		);
+		let extrinsic_hash = BlakeTwo256::hash_of(&UncheckedXt::new_signed(2.into(), 0, (), ()));
+		assert_eq!(
+			backend.blockchain().extrinsic_locations(extrinsic_hash).unwrap(),
+			Some(Vec::new()),
+		);
+	}
+
 	#[test]
 	fn prune_blocks_on_finalize_with_fork() {
 		sp_tracing::try_init_simple();
@@ -67,6 +67,9 @@ pub fn open<H: Clone + AsRef<[u8]>>(
 			tx_col.ref_counted = true;
 			tx_col.preimage = true;
 			tx_col.uniform = true;
+
+			// The signer index is read by prefix.
+			config.columns[columns::SIGNER_INDEX as usize].btree_index = true;
 		},
 	}
 
@@ -159,4 +162,20 @@ impl<H: Clone + AsRef<[u8]>> Database<H> for DbAdapter {
 	fn sanitize_key(&self, key: &mut Vec<u8>) {
 		let _prefix = key.drain(0..key.len() - crate::DB_HASH_LEN);
 	}
+
+	fn iter_with_prefix(
+		&self,
+		col: ColumnId,
+		prefix: &[u8],
+		f: &mut dyn FnMut(&[u8], &[u8]) -> bool,
+	) -> Result<(), DatabaseError> {
+		let mut iter = self.0.iter(col as u8).map_err(|e| DatabaseError(Box::new(e)))?;
+		iter.seek(prefix).map_err(|e| DatabaseError(Box::new(e)))?;
+		while let Some((key, value)) = iter.next().map_err(|e| DatabaseError(Box::new(e)))? {
+			if !key.starts_with(prefix) || !f(&key, &value) {
+				break
+			}
+		}
+		Ok(())
+	}
 }
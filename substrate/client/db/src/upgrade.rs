@@ -33,12 +33,13 @@ use sp_runtime::traits::Block as BlockT;
 const VERSION_FILE_NAME: &str = "db_version";
 
 /// Current db version.
-const CURRENT_VERSION: u32 = 4;
+const CURRENT_VERSION: u32 = 5;
 
 /// Number of columns in v1.
 const V1_NUM_COLUMNS: u32 = 11;
 const V2_NUM_COLUMNS: u32 = 12;
 const V3_NUM_COLUMNS: u32 = 12;
+const V4_NUM_COLUMNS: u32 = 13;
 
 /// Database upgrade errors.
 #[derive(Debug)]
@@ -95,13 +96,19 @@ pub fn upgrade_db<Block: BlockT>(db_path: &Path, db_type: DatabaseType) -> Upgra
 			migrate_1_to_2::<Block>(db_path, db_type)?;
 			migrate_2_to_3::<Block>(db_path, db_type)?;
 			migrate_3_to_4::<Block>(db_path, db_type)?;
+			migrate_4_to_5::<Block>(db_path, db_type)?;
 		},
 		2 => {
 			migrate_2_to_3::<Block>(db_path, db_type)?;
 			migrate_3_to_4::<Block>(db_path, db_type)?;
+			migrate_4_to_5::<Block>(db_path, db_type)?;
 		},
 		3 => {
 			migrate_3_to_4::<Block>(db_path, db_type)?;
+			migrate_4_to_5::<Block>(db_path, db_type)?;
+		},
+		4 => {
+			migrate_4_to_5::<Block>(db_path, db_type)?;
 		},
 		CURRENT_VERSION => (),
 		_ => return Err(UpgradeError::FutureDatabaseVersion(db_version)),
@@ -159,6 +166,16 @@ fn migrate_3_to_4<Block: BlockT>(db_path: &Path, _db_type: DatabaseType) -> Upgr
 	db.add_column().map_err(Into::into)
 }
 
+/// Migration from version4 to version5:
+/// 1) the number of columns has changed from 13 to 15;
+/// 2) EXTRINSIC_INDEX and SIGNER_INDEX columns are added;
+fn migrate_4_to_5<Block: BlockT>(db_path: &Path, _db_type: DatabaseType) -> UpgradeResult<()> {
+	let db_cfg = DatabaseConfig::with_columns(V4_NUM_COLUMNS);
+	let mut db = Database::open(&db_cfg, db_path)?;
+	db.add_column()?;
+	db.add_column().map_err(Into::into)
+}
+
 /// Reads current database version from the file at given path.
 /// If the file does not exist returns 0.
 fn current_version(path: &Path) -> UpgradeResult<u32> {
@@ -253,4 +270,16 @@ mod tests {
 			assert_eq!(current_version(&db_path).unwrap(), CURRENT_VERSION);
 		}
 	}
+
+	#[test]
+	fn upgrade_to_5_works() {
+		let db_type = DatabaseType::Full;
+		for version_from_file in &[None, Some(1), Some(2), Some(3), Some(4)] {
+			let db_dir = tempfile::TempDir::new().unwrap();
+			let db_path = db_dir.path().join(db_type.as_str());
+			create_db(&db_path, *version_from_file);
+			open_database(&db_path, db_type).unwrap();
+			assert_eq!(current_version(&db_path).unwrap(), CURRENT_VERSION);
+		}
+	}
 }
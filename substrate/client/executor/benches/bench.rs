@@ -66,7 +66,9 @@ fn initialize(
 					wasm_bulk_memory: false,
 					wasm_reference_types: false,
 					wasm_simd: false,
+					max_memory_pages: None,
 				},
+				profiler: None,
 			};
 
 			if precompile {
@@ -99,6 +99,9 @@ pub enum Error {
 
 	#[error("Output exceeds bounds of wasm memory")]
 	OutputExceedsBounds,
+
+	#[error("Execution limit exceeded: {0}")]
+	LimitExceeded(crate::wasm_runtime::ExecutionLimit),
 }
 
 impl From<&'static str> for Error {
@@ -145,6 +148,10 @@ pub enum WasmError {
 	#[error("{0}")]
 	Instantiation(String),
 
+	/// One of the deterministic execution limits cannot be satisfied by the runtime.
+	#[error("Execution limit exceeded: {0}")]
+	LimitExceeded(crate::wasm_runtime::ExecutionLimit),
+
 	/// Other error happened.
 	#[error("Other error happened while constructing the runtime: {0}")]
 	Other(String),
@@ -0,0 +1,176 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Profiling of runtime execution.
+//!
+//! The [`Profiler`] attributes the time spent in runtime calls to call stacks, which are written
+//! out as folded stacks: one `method;frame;...;frame nanoseconds` line per distinct stack. This is
+//! the input format of flamegraph tools such as `inferno-flamegraph` or `flamegraph.pl`.
+//!
+//! The executors report the runtime stack whenever the runtime calls into the host, using the
+//! function names from the name section of the runtime. The time spent in the host function is
+//! attributed to the host function on top of that stack, while the time spent in the runtime since
+//! the previous host call is attributed to the stack itself. Whatever is left after the last host
+//! call is attributed to the called method alone.
+
+use parking_lot::Mutex;
+use std::{
+	collections::HashMap,
+	fs::File,
+	io::{self, BufWriter, Write},
+	path::PathBuf,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc,
+	},
+	time::Instant,
+};
+
+/// Collects the time spent in runtime calls, per call stack.
+#[derive(Default)]
+pub struct Profiler {
+	/// The nanoseconds spent in every folded stack.
+	stacks: Mutex<HashMap<String, u128>>,
+	/// Whether time was recorded since the folded stacks were last written to `output`.
+	dirty: AtomicBool,
+	/// The file the folded stacks are written to.
+	output: Option<PathBuf>,
+}
+
+impl Profiler {
+	/// Creates a new profiler.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Creates a new profiler which writes the folded stacks to `output`.
+	///
+	/// The folded stacks are written with [`Profiler::write_output`], and once the profiler is
+	/// dropped if time was recorded since.
+	pub fn with_output(output: impl Into<PathBuf>) -> Self {
+		Self { stacks: Default::default(), dirty: Default::default(), output: Some(output.into()) }
+	}
+
+	/// Attributes `nanos` to the given stack, outermost frame first.
+	///
+	/// The frames are demangled if they are Rust symbols.
+	pub fn record<'a>(&self, frames: impl IntoIterator<Item = &'a str>, nanos: u128) {
+		let mut stack = String::new();
+		for frame in frames {
+			if !stack.is_empty() {
+				stack.push(';');
+			}
+			// `;` separates the frames, so it can't appear within one.
+			stack.push_str(&format!("{:#}", rustc_demangle::demangle(frame)).replace(';', ":"));
+		}
+
+		*self.stacks.lock().entry(stack).or_default() += nanos;
+		self.dirty.store(true, Ordering::Relaxed);
+	}
+
+	/// Writes the folded stacks gathered so far, sorted by stack.
+	pub fn write_folded(&self, mut writer: impl Write) -> io::Result<()> {
+		let stacks = self.stacks.lock();
+		let mut stacks = stacks.iter().collect::<Vec<_>>();
+		stacks.sort();
+
+		for (stack, nanos) in stacks {
+			writeln!(writer, "{stack} {nanos}")?;
+		}
+
+		writer.flush()
+	}
+
+	/// Writes the folded stacks gathered so far to the output file, if any.
+	pub fn write_output(&self) -> io::Result<()> {
+		let Some(output) = &self.output else { return Ok(()) };
+
+		self.dirty.store(false, Ordering::Relaxed);
+		self.write_folded(BufWriter::new(File::create(output)?))?;
+		log::info!("Wrote the runtime execution profile to {}", output.display());
+		Ok(())
+	}
+}
+
+impl std::fmt::Debug for Profiler {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Profiler").field("output", &self.output).finish_non_exhaustive()
+	}
+}
+
+impl Drop for Profiler {
+	fn drop(&mut self) {
+		let Some(output) = &self.output else { return };
+		if !self.dirty.load(Ordering::Relaxed) {
+			return
+		}
+
+		if let Err(error) = self.write_output() {
+			log::error!(
+				"Failed to write the runtime execution profile to {}: {error}",
+				output.display(),
+			);
+		}
+	}
+}
+
+/// Tracks a single runtime call for a [`Profiler`].
+pub struct CallProfiler {
+	profiler: Arc<Profiler>,
+	method: String,
+	/// The runtime stack of the host call in progress, outermost frame first.
+	stack: Vec<String>,
+	/// When the previous span of time started.
+	last: Instant,
+}
+
+impl CallProfiler {
+	/// Starts tracking a call into the given runtime `method`.
+	pub fn new(profiler: Arc<Profiler>, method: &str) -> Self {
+		Self { profiler, method: method.into(), stack: Vec::new(), last: Instant::now() }
+	}
+
+	/// Marks the start of a host call made from the runtime stack returned by `capture_stack`,
+	/// outermost frame first.
+	pub fn enter_host_function(&mut self, capture_stack: impl FnOnce() -> Vec<String>) {
+		let now = Instant::now();
+		self.stack = capture_stack();
+		self.record(now, None);
+	}
+
+	/// Marks the end of the host call started by [`CallProfiler::enter_host_function`].
+	pub fn exit_host_function(&mut self, host_function: &str) {
+		self.record(Instant::now(), Some(host_function));
+		self.stack.clear();
+	}
+
+	/// Marks the end of the runtime call.
+	pub fn finish(mut self) {
+		self.record(Instant::now(), None);
+	}
+
+	/// Attributes the time until `end` to the current stack.
+	fn record(&mut self, end: Instant, host_function: Option<&str>) {
+		let frames = std::iter::once(self.method.as_str())
+			.chain(self.stack.iter().map(String::as_str))
+			.chain(host_function);
+		self.profiler.record(frames, end.duration_since(self.last).as_nanos());
+		// The time spent capturing the stack and recording it is not attributed to any frame.
+		self.last = Instant::now();
+	}
+}
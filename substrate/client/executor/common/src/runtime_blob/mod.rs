@@ -48,4 +48,4 @@
 
 mod runtime_blob;
 
-pub use runtime_blob::RuntimeBlob;
+pub use runtime_blob::{RuntimeBlob, STACK_HEIGHT_EXPORT};
@@ -19,10 +19,14 @@
 use crate::{error::WasmError, wasm_runtime::HeapAllocStrategy};
 use polkavm::ArcBytes;
 use wasm_instrument::parity_wasm::elements::{
-	deserialize_buffer, serialize, ExportEntry, External, Internal, MemorySection, MemoryType,
-	Module, Section,
+	deserialize_buffer, serialize, ExportEntry, External, ImportCountType, Internal, MemorySection,
+	MemoryType, Module, Section,
 };
 
+/// The name under which the stack height counter is exported by
+/// [`RuntimeBlob::inject_stack_depth_metering`].
+pub const STACK_HEIGHT_EXPORT: &str = "__stack_height";
+
 /// A program blob containing a Substrate runtime.
 #[derive(Clone)]
 pub struct RuntimeBlob(BlobKind);
@@ -77,14 +81,40 @@ impl RuntimeBlob {
 	/// The stack cost of a function is computed based on how much locals there are and the maximum
 	/// depth of the wasm operand stack.
 	///
+	/// The counter is exported under [`STACK_HEIGHT_EXPORT`], so that the executor can tell
+	/// whether a trap was caused by reaching the limit.
+	///
 	/// Only valid for WASM programs; will return an error if the blob is a PolkaVM program.
 	pub fn inject_stack_depth_metering(self, stack_depth_limit: u32) -> Result<Self, WasmError> {
-		let injected_module =
+		let mut injected_module =
 			wasm_instrument::inject_stack_limiter(self.into_webassembly_blob()?, stack_depth_limit)
 				.map_err(|e| {
 					WasmError::Other(format!("cannot inject the stack limiter: {:?}", e))
 				})?;
 
+		// The instrumentation always appends the counter to the global section.
+		let defined_globals =
+			injected_module.global_section().map_or(0, |section| section.entries().len());
+		if defined_globals == 0 {
+			return Err(WasmError::Other("the stack limiter didn't add a global counter".into()))
+		}
+		let counter_index =
+			injected_module.import_count(ImportCountType::Global) + defined_globals - 1;
+
+		if injected_module.export_section().is_none() {
+			injected_module
+				.insert_section(Section::Export(Default::default()))
+				.expect("an export section can be always inserted if it doesn't exist; qed");
+		}
+		injected_module
+			.export_section_mut()
+			.expect("export section already existed or we just added it above, so it always exists; qed")
+			.entries_mut()
+			.push(ExportEntry::new(
+				STACK_HEIGHT_EXPORT.into(),
+				Internal::Global(counter_index as u32),
+			));
+
 		Ok(Self(BlobKind::WebAssembly(injected_module)))
 	}
 
@@ -231,4 +261,12 @@ impl RuntimeBlob {
 			BlobKind::PolkaVM((ref blob, _)) => Some(blob),
 		}
 	}
+
+	/// Gets the raw bytes of the PolkaVM program blob, if this is a PolkaVM program.
+	pub fn as_polkavm_raw_blob(&self) -> Option<&[u8]> {
+		match self.0 {
+			BlobKind::WebAssembly(..) => None,
+			BlobKind::PolkaVM((_, ref raw)) => Some(raw.as_ref()),
+		}
+	}
 }
@@ -18,7 +18,7 @@
 
 //! Definitions for a wasm runtime.
 
-use crate::error::Error;
+use crate::error::{Error, WasmError};
 
 pub use sc_allocator::AllocationStats;
 
@@ -29,6 +29,78 @@ pub const DEFAULT_HEAP_ALLOC_STRATEGY: HeapAllocStrategy =
 /// Default heap allocation pages.
 pub const DEFAULT_HEAP_ALLOC_PAGES: u32 = 2048;
 
+/// Limits enforced while executing a runtime.
+///
+/// Unlike the native stack limit of the executor, which depends on how the runtime was compiled
+/// for the host, these limits are reached at exactly the same point on every machine and in every
+/// executor. Hitting one of them aborts the call with [`Error::LimitExceeded`].
+///
+/// [`Error::LimitExceeded`]: crate::error::Error::LimitExceeded
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ExecutionLimits {
+	/// The maximum height of the stack, in logical values.
+	///
+	/// For WebAssembly runtimes a logical value is a local, an argument or a value pushed on the
+	/// operand stack, and the code is instrumented to count them. For PolkaVM runtimes every
+	/// [`POLKAVM_STACK_VALUE_SIZE`] bytes of the guest stack count as one logical value, and the
+	/// guest stack is sized to the limit, rounded up to the page size of the guest.
+	///
+	/// Limits above [`MAX_STACK_HEIGHT`] are rejected by every executor.
+	///
+	/// `None` only leaves the native stack limit of the executor in place.
+	pub max_stack_height: Option<u32>,
+
+	/// The maximum size of the runtime's memory, in pages of 64KiB.
+	///
+	/// For WebAssembly runtimes this caps the size of the linear memory. For PolkaVM runtimes this
+	/// caps the size of the read-only data, read-write data, the stack and the heap combined.
+	///
+	/// `None` only leaves the limit of the [`HeapAllocStrategy`] in place.
+	pub max_memory_pages: Option<u32>,
+}
+
+impl ExecutionLimits {
+	/// Checks that every executor can enforce these limits.
+	///
+	/// Fails with [`WasmError::LimitExceeded`] if the stack height limit is above
+	/// [`MAX_STACK_HEIGHT`].
+	pub fn validate(&self) -> Result<(), WasmError> {
+		match self.max_stack_height {
+			Some(max) if max > MAX_STACK_HEIGHT =>
+				Err(WasmError::LimitExceeded(ExecutionLimit::StackHeight(max))),
+			_ => Ok(()),
+		}
+	}
+}
+
+/// The largest [`ExecutionLimits::max_stack_height`] the executors accept.
+///
+/// Wasmtime runs the runtime on the native stack of the calling thread, and the native stack it
+/// may use grows with the limit. Larger limits would let it grow past the stack of the threads
+/// calling into the runtime.
+pub const MAX_STACK_HEIGHT: u32 = 8192;
+
+/// The number of bytes of a PolkaVM guest stack which count as one logical stack value.
+pub const POLKAVM_STACK_VALUE_SIZE: u32 = 8;
+
+/// One of the [`ExecutionLimits`], along with its configured value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExecutionLimit {
+	/// The maximum stack height was exceeded.
+	StackHeight(u32),
+	/// The maximum memory size was exceeded.
+	MemoryPages(u32),
+}
+
+impl std::fmt::Display for ExecutionLimit {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::StackHeight(max) => write!(f, "stack height of {max} logical values"),
+			Self::MemoryPages(max) => write!(f, "memory size of {max} pages"),
+		}
+	}
+}
+
 /// A trait that defines an abstract WASM runtime module.
 ///
 /// This can be implemented by an execution engine.
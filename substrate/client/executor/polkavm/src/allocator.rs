@@ -0,0 +1,374 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The allocator backing the `allocate_memory`/`deallocate_memory` host functions.
+//!
+//! Unlike the WebAssembly executor we can't hand the allocator a slice of the whole linear
+//! memory, so the bookkeeping lives on the host instead of in block headers within the guest.
+//! Fresh blocks are carved out of the guest heap with `sbrk`, rounded up to a power of two
+//! between [`MIN_POSSIBLE_ALLOCATION`] and [`MAX_POSSIBLE_ALLOCATION`], and freed blocks are kept
+//! in per-size free lists so that they can be reused by later allocations of the same order.
+//!
+//! The allocator only lives for the duration of a single runtime call, since the guest memory
+//! is reset before every call anyway.
+
+use sc_allocator::{AllocationStats, Error};
+use sc_executor_common::wasm_runtime::HeapAllocStrategy;
+use std::collections::HashMap;
+
+/// The minimum possible allocation size, in bytes.
+///
+/// Every returned pointer is aligned to this value.
+const MIN_POSSIBLE_ALLOCATION: u32 = 8;
+
+/// The maximum possible allocation size, in bytes.
+///
+/// This is the same limit as imposed by the allocator used for WebAssembly runtimes.
+const MAX_POSSIBLE_ALLOCATION: u32 = 32 * 1024 * 1024;
+
+/// The number of orders supported, i.e. the powers of two from 2^3 to 2^25 (both inclusive).
+const N_ORDERS: usize = 23;
+
+/// The size of a WebAssembly page, used to convert page based limits into byte limits.
+pub(crate) const PAGE_SIZE: u64 = 64 * 1024;
+
+/// Grants the allocator access to the heap of a guest program.
+pub(crate) trait GuestHeap {
+	/// Returns the address right past the end of the heap.
+	fn heap_end(&mut self) -> Result<u32, Error>;
+
+	/// Grows the heap by `size` bytes.
+	///
+	/// Returns `false` if the guest doesn't have enough memory left.
+	fn grow(&mut self, size: u32) -> Result<bool, Error>;
+}
+
+impl GuestHeap for polkavm::RawInstance {
+	fn heap_end(&mut self) -> Result<u32, Error> {
+		self.sbrk(0)
+			.map_err(|_| Error::Other("failed to query the guest heap"))?
+			.ok_or(Error::Other("failed to query the guest heap"))
+	}
+
+	fn grow(&mut self, size: u32) -> Result<bool, Error> {
+		self.sbrk(size)
+			.map(|pointer| pointer.is_some())
+			.map_err(|_| Error::Other("failed to grow the guest heap"))
+	}
+}
+
+/// Converts the given heap allocation strategy into the maximum number of bytes the allocator
+/// can take from the guest heap, or `None` if there is no limit.
+pub(crate) fn max_heap_size(heap_alloc_strategy: HeapAllocStrategy) -> Option<u32> {
+	let pages = match heap_alloc_strategy {
+		HeapAllocStrategy::Static { extra_pages } => extra_pages,
+		HeapAllocStrategy::Dynamic { maximum_pages } => maximum_pages?,
+	};
+
+	Some(u32::try_from(u64::from(pages) * PAGE_SIZE).unwrap_or(u32::MAX))
+}
+
+/// Returns the order of the smallest block which fits `size` bytes.
+fn order_from_size(size: u32) -> Result<usize, Error> {
+	if size > MAX_POSSIBLE_ALLOCATION {
+		return Err(Error::RequestedAllocationTooLarge)
+	}
+
+	let size = size.max(MIN_POSSIBLE_ALLOCATION).next_power_of_two();
+	Ok((size.trailing_zeros() - MIN_POSSIBLE_ALLOCATION.trailing_zeros()) as usize)
+}
+
+/// Returns the size of a block of the given order.
+fn size_from_order(order: usize) -> u32 {
+	MIN_POSSIBLE_ALLOCATION << order
+}
+
+/// A power-of-two free list allocator which keeps its bookkeeping on the host.
+pub(crate) struct HeapAllocator {
+	/// Addresses of the free blocks, for each order.
+	free_lists: [Vec<u32>; N_ORDERS],
+	/// The order of every block that is currently handed out to the guest.
+	allocated: HashMap<u32, usize>,
+	/// The maximum number of bytes which can be taken from the guest heap.
+	max_heap_size: Option<u32>,
+	/// The number of bytes left for the heap by the configured memory limit.
+	memory_limit: Option<u32>,
+	/// Whether an allocation failed because of the memory limit.
+	memory_limit_exceeded: bool,
+	stats: AllocationStats,
+}
+
+impl HeapAllocator {
+	/// Creates a new allocator which takes at most `max_heap_size` bytes from the guest heap.
+	pub(crate) fn new(max_heap_size: Option<u32>) -> Self {
+		Self {
+			free_lists: Default::default(),
+			allocated: HashMap::new(),
+			max_heap_size,
+			memory_limit: None,
+			memory_limit_exceeded: false,
+			stats: AllocationStats::default(),
+		}
+	}
+
+	/// Additionally limits the guest heap to `memory_limit` bytes.
+	///
+	/// Unlike the heap size limit, hitting this one is remembered, see
+	/// [`HeapAllocator::memory_limit_exceeded`].
+	pub(crate) fn with_memory_limit(mut self, memory_limit: Option<u32>) -> Self {
+		self.memory_limit = memory_limit;
+		self
+	}
+
+	/// Returns whether an allocation failed because of the memory limit.
+	pub(crate) fn memory_limit_exceeded(&self) -> bool {
+		self.memory_limit_exceeded
+	}
+
+	/// Allocates a block of at least `size` bytes and returns its address.
+	pub(crate) fn allocate(&mut self, heap: &mut impl GuestHeap, size: u32) -> Result<u32, Error> {
+		let order = order_from_size(size)?;
+		let block_size = size_from_order(order);
+
+		let pointer = match self.free_lists[order].pop() {
+			Some(pointer) => pointer,
+			None => self.take_from_heap(heap, block_size)?,
+		};
+
+		self.allocated.insert(pointer, order);
+
+		self.stats.bytes_allocated += block_size;
+		self.stats.bytes_allocated_sum += u128::from(block_size);
+		self.stats.bytes_allocated_peak =
+			self.stats.bytes_allocated_peak.max(self.stats.bytes_allocated);
+
+		Ok(pointer)
+	}
+
+	/// Frees a block previously returned by [`HeapAllocator::allocate`].
+	pub(crate) fn deallocate(&mut self, pointer: u32) -> Result<(), Error> {
+		let order = self
+			.allocated
+			.remove(&pointer)
+			.ok_or(Error::Other("tried to deallocate a pointer which is not allocated"))?;
+
+		self.free_lists[order].push(pointer);
+		self.stats.bytes_allocated -= size_from_order(order);
+
+		Ok(())
+	}
+
+	/// Returns the allocation stats gathered so far.
+	pub(crate) fn stats(&self) -> AllocationStats {
+		self.stats.clone()
+	}
+
+	/// Takes a fresh block of `block_size` bytes from the end of the guest heap.
+	fn take_from_heap(&mut self, heap: &mut impl GuestHeap, block_size: u32) -> Result<u32, Error> {
+		// The guest could have moved the end of the heap by itself, so it's not necessarily
+		// aligned.
+		let heap_end = heap.heap_end()?;
+		let padding = heap_end.wrapping_neg() % MIN_POSSIBLE_ALLOCATION;
+		let needed = block_size.checked_add(padding).ok_or(Error::AllocatorOutOfSpace)?;
+
+		let address_space_used = self
+			.stats
+			.address_space_used
+			.checked_add(needed)
+			.ok_or(Error::AllocatorOutOfSpace)?;
+		if self.max_heap_size.map_or(false, |max| address_space_used > max) {
+			return Err(Error::AllocatorOutOfSpace)
+		}
+		if self.memory_limit.map_or(false, |max| address_space_used > max) {
+			self.memory_limit_exceeded = true;
+			return Err(Error::AllocatorOutOfSpace)
+		}
+
+		if !heap.grow(needed)? {
+			return Err(Error::AllocatorOutOfSpace)
+		}
+
+		self.stats.address_space_used = address_space_used;
+		Ok(heap_end + padding)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A guest heap which only keeps track of its end.
+	struct TestHeap {
+		end: u32,
+		limit: u32,
+	}
+
+	impl TestHeap {
+		fn new(end: u32) -> Self {
+			Self { end, limit: u32::MAX }
+		}
+	}
+
+	impl GuestHeap for TestHeap {
+		fn heap_end(&mut self) -> Result<u32, Error> {
+			Ok(self.end)
+		}
+
+		fn grow(&mut self, size: u32) -> Result<bool, Error> {
+			match self.end.checked_add(size) {
+				Some(end) if end <= self.limit => {
+					self.end = end;
+					Ok(true)
+				},
+				_ => Ok(false),
+			}
+		}
+	}
+
+	#[test]
+	fn should_allocate_properly() {
+		let mut heap = TestHeap::new(0x10000);
+		let mut allocator = HeapAllocator::new(None);
+
+		let first = allocator.allocate(&mut heap, 1).unwrap();
+		let second = allocator.allocate(&mut heap, 9).unwrap();
+		let third = allocator.allocate(&mut heap, 100).unwrap();
+
+		assert_eq!(first, 0x10000);
+		assert_eq!(second, 0x10000 + 8);
+		assert_eq!(third, 0x10000 + 8 + 16);
+		assert_eq!(heap.end, 0x10000 + 8 + 16 + 128);
+	}
+
+	#[test]
+	fn should_align_the_heap_end() {
+		let mut heap = TestHeap::new(0x10003);
+		let mut allocator = HeapAllocator::new(None);
+
+		assert_eq!(allocator.allocate(&mut heap, 8).unwrap(), 0x10008);
+		assert_eq!(allocator.stats().address_space_used, 13);
+
+		// The guest bumps the heap by itself.
+		heap.end += 1;
+		assert_eq!(allocator.allocate(&mut heap, 8).unwrap(), 0x10018);
+	}
+
+	#[test]
+	fn should_reuse_freed_blocks_of_the_same_order() {
+		let mut heap = TestHeap::new(0);
+		let mut allocator = HeapAllocator::new(None);
+
+		let first = allocator.allocate(&mut heap, 30).unwrap();
+		let _second = allocator.allocate(&mut heap, 30).unwrap();
+		allocator.deallocate(first).unwrap();
+
+		// A different order doesn't reuse the block...
+		assert_eq!(allocator.allocate(&mut heap, 8).unwrap(), 64);
+		// ...but the same one does.
+		assert_eq!(allocator.allocate(&mut heap, 17).unwrap(), first);
+		assert_eq!(heap.end, 72);
+	}
+
+	#[test]
+	fn should_reject_invalid_deallocations() {
+		let mut heap = TestHeap::new(0);
+		let mut allocator = HeapAllocator::new(None);
+
+		let pointer = allocator.allocate(&mut heap, 8).unwrap();
+		assert!(allocator.deallocate(pointer + 1).is_err());
+		allocator.deallocate(pointer).unwrap();
+		assert!(allocator.deallocate(pointer).is_err());
+	}
+
+	#[test]
+	fn should_reject_too_large_allocations() {
+		let mut heap = TestHeap::new(0);
+		let mut allocator = HeapAllocator::new(None);
+
+		assert_eq!(
+			allocator.allocate(&mut heap, MAX_POSSIBLE_ALLOCATION + 1),
+			Err(Error::RequestedAllocationTooLarge)
+		);
+		assert!(allocator.allocate(&mut heap, MAX_POSSIBLE_ALLOCATION).is_ok());
+	}
+
+	#[test]
+	fn should_respect_the_heap_limit() {
+		let mut heap = TestHeap::new(0);
+		let mut allocator = HeapAllocator::new(Some(64));
+
+		allocator.allocate(&mut heap, 32).unwrap();
+		allocator.allocate(&mut heap, 32).unwrap();
+		assert_eq!(allocator.allocate(&mut heap, 8), Err(Error::AllocatorOutOfSpace));
+		assert_eq!(heap.end, 64);
+	}
+
+	#[test]
+	fn should_remember_hitting_the_memory_limit() {
+		let mut heap = TestHeap::new(0);
+		let mut allocator = HeapAllocator::new(Some(128)).with_memory_limit(Some(64));
+
+		allocator.allocate(&mut heap, 64).unwrap();
+		assert!(!allocator.memory_limit_exceeded());
+		assert_eq!(allocator.allocate(&mut heap, 8), Err(Error::AllocatorOutOfSpace));
+		assert!(allocator.memory_limit_exceeded());
+
+		// The heap limit is checked first and doesn't count as hitting the memory limit.
+		let mut allocator = HeapAllocator::new(Some(32)).with_memory_limit(Some(64));
+		assert_eq!(allocator.allocate(&mut heap, 64), Err(Error::AllocatorOutOfSpace));
+		assert!(!allocator.memory_limit_exceeded());
+	}
+
+	#[test]
+	fn should_fail_when_the_guest_is_out_of_memory() {
+		let mut heap = TestHeap { end: 0, limit: 16 };
+		let mut allocator = HeapAllocator::new(None);
+
+		allocator.allocate(&mut heap, 16).unwrap();
+		assert_eq!(allocator.allocate(&mut heap, 8), Err(Error::AllocatorOutOfSpace));
+		assert_eq!(allocator.stats().address_space_used, 16);
+	}
+
+	#[test]
+	fn should_track_stats() {
+		let mut heap = TestHeap::new(0);
+		let mut allocator = HeapAllocator::new(None);
+
+		let first = allocator.allocate(&mut heap, 10).unwrap();
+		let second = allocator.allocate(&mut heap, 100).unwrap();
+		allocator.deallocate(first).unwrap();
+		allocator.allocate(&mut heap, 16).unwrap();
+		allocator.deallocate(second).unwrap();
+
+		let stats = allocator.stats();
+		assert_eq!(stats.bytes_allocated, 16);
+		assert_eq!(stats.bytes_allocated_peak, 144);
+		assert_eq!(stats.bytes_allocated_sum, 16 + 128 + 16);
+		assert_eq!(stats.address_space_used, 144);
+	}
+
+	#[test]
+	fn should_convert_heap_alloc_strategy() {
+		assert_eq!(max_heap_size(HeapAllocStrategy::Static { extra_pages: 2 }), Some(128 * 1024));
+		assert_eq!(
+			max_heap_size(HeapAllocStrategy::Dynamic { maximum_pages: Some(65536) }),
+			Some(u32::MAX)
+		);
+		assert_eq!(max_heap_size(HeapAllocStrategy::Dynamic { maximum_pages: None }), None);
+	}
+}
@@ -16,24 +16,120 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+//! PolkaVM-based executor for Substrate runtimes.
+//!
+//! Compiled modules are cached process-wide and shared between every runtime created from the
+//! same program blob. The guest memory is reset before every call, so the instances handed out by
+//! [`InstancePre`] can be safely reused across calls, which is what `sc-executor` does.
+//!
+//! The stack of the program is sized to [`ExecutionLimits::max_stack_height`], with every logical
+//! value taking [`POLKAVM_STACK_VALUE_SIZE`] bytes, so that every function call is checked against
+//! the limit. The memory limit caps the guest heap once the static data and the stack of the
+//! program are accounted for.
+
+mod allocator;
+
+use allocator::{HeapAllocator, PAGE_SIZE};
+use parking_lot::Mutex;
 use polkavm::{CallError, Caller, Reg};
 use sc_executor_common::{
-	error::{Error, WasmError},
-	wasm_runtime::{AllocationStats, WasmInstance, WasmModule},
+	error::{Error, MessageWithBacktrace, WasmError},
+	runtime_blob::RuntimeBlob,
+	wasm_runtime::{
+		AllocationStats, ExecutionLimit, ExecutionLimits, HeapAllocStrategy, WasmInstance,
+		WasmModule, POLKAVM_STACK_VALUE_SIZE,
+	},
 };
+use schnellru::{ByLength, LruMap};
 use sp_wasm_interface::{
 	Function, FunctionContext, HostFunctions, Pointer, Value, ValueType, WordSize,
 };
+use std::{collections::HashSet, sync::OnceLock};
+
+/// The number of compiled modules kept in the process-wide cache.
+const MODULE_CACHE_SIZE: u32 = 8;
+
+/// The PolkaVM backend used to execute a runtime.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Backend {
+	/// Use the recompiler if it's supported on the host, and the interpreter otherwise.
+	///
+	/// This can be overridden with the `POLKAVM_BACKEND` environment variable.
+	#[default]
+	Auto,
+	/// Always use the recompiler.
+	Compiler,
+	/// Always use the interpreter.
+	Interpreter,
+}
+
+/// Configuration used to create a PolkaVM runtime.
+#[derive(Clone, Debug)]
+pub struct Config {
+	/// The backend used to execute the runtime.
+	pub backend: Backend,
+
+	/// The heap allocation strategy to use.
+	///
+	/// Only the allocations made through the allocator host functions count towards the limit.
+	pub heap_alloc_strategy: HeapAllocStrategy,
+
+	/// If true, calls to unresolved imports trap instead of failing the runtime creation.
+	pub allow_missing_func_imports: bool,
+
+	/// The deterministic limits enforced during execution.
+	pub execution_limits: ExecutionLimits,
+}
 
-#[repr(transparent)]
-pub struct InstancePre(polkavm::InstancePre<(), String>);
+/// The state kept for the duration of a single runtime call.
+struct HostState {
+	allocator: HeapAllocator,
+	panic_message: Option<String>,
+}
+
+/// The limits shared by all the instances of a module.
+#[derive(Clone, Copy)]
+struct Limits {
+	max_heap_size: Option<u32>,
+	/// The number of bytes left for the heap by [`ExecutionLimits::max_memory_pages`].
+	heap_memory_limit: Option<u32>,
+	execution_limits: ExecutionLimits,
+}
+
+/// A PolkaVM module ready to be instantiated.
+pub struct InstancePre {
+	instance_pre: polkavm::InstancePre<HostState, String>,
+	limits: Limits,
+}
 
-#[repr(transparent)]
-pub struct Instance(polkavm::Instance<(), String>);
+/// An instance of a PolkaVM module.
+pub struct Instance {
+	instance: polkavm::Instance<HostState, String>,
+	limits: Limits,
+}
 
 impl WasmModule for InstancePre {
 	fn new_instance(&self) -> Result<Box<dyn WasmInstance>, Error> {
-		Ok(Box::new(Instance(self.0.instantiate()?)))
+		Ok(Box::new(Instance { instance: self.instance_pre.instantiate()?, limits: self.limits }))
+	}
+}
+
+impl Instance {
+	/// Returns the execution limit which made the last call fail, if any.
+	fn exceeded_limit(&self, host_state: &HostState) -> Option<ExecutionLimit> {
+		let limits = &self.limits.execution_limits;
+
+		// The stack is sized to the limit, so the guest traps as soon as it goes past it.
+		let stack_size = u64::from(self.instance.module().memory_map().stack_size());
+		if limits.max_stack_height.is_some() && stack_usage(&self.instance) > stack_size {
+			return limits.max_stack_height.map(ExecutionLimit::StackHeight)
+		}
+
+		if host_state.allocator.memory_limit_exceeded() {
+			return limits.max_memory_pages.map(ExecutionLimit::MemoryPages)
+		}
+
+		None
 	}
 }
 
@@ -43,7 +139,7 @@ impl WasmInstance for Instance {
 		name: &str,
 		raw_data: &[u8],
 	) -> (Result<Vec<u8>, Error>, Option<AllocationStats>) {
-		let pc = match self.0.module().exports().find(|e| e.symbol() == name) {
+		let pc = match self.instance.module().exports().find(|e| e.symbol() == name) {
 			Some(export) => export.program_counter(),
 			None =>
 				return (
@@ -59,10 +155,8 @@ impl WasmInstance for Instance {
 			);
 		};
 
-		// TODO: This will leak guest memory; find a better solution.
-
 		// Make sure that the memory is cleared...
-		if let Err(err) = self.0.reset_memory() {
+		if let Err(err) = self.instance.reset_memory() {
 			return (
 				Err(format!(
 					"call into the runtime method '{name}' failed: reset memory failed: {err}"
@@ -73,58 +167,70 @@ impl WasmInstance for Instance {
 		}
 
 		// ... and allocate space for the input payload.
-		if let Err(err) = self.0.sbrk(raw_data_length) {
-			return (
-				Err(format!(
-					"call into the runtime method '{name}' failed: reset memory failed: {err}"
-				)
-				.into()),
-				None,
-			);
-		}
-
-		// Grab the address of where the guest's heap starts; that's where we've just allocated
-		// the memory for the input payload.
-		let data_pointer = self.0.module().memory_map().heap_base();
+		let mut host_state = HostState {
+			allocator: HeapAllocator::new(self.limits.max_heap_size)
+				.with_memory_limit(self.limits.heap_memory_limit),
+			panic_message: None,
+		};
+		let data_pointer = match host_state.allocator.allocate(&mut *self.instance, raw_data_length)
+		{
+			Ok(data_pointer) => data_pointer,
+			Err(err) => {
+				let error = match self.exceeded_limit(&host_state) {
+					Some(limit) => Error::LimitExceeded(limit),
+					None => format!(
+						"call into the runtime method '{name}' failed: failed to allocate the input payload: {err}"
+					)
+					.into(),
+				};
+				return (Err(error), Some(host_state.allocator.stats()))
+			},
+		};
 
-		if let Err(err) = self.0.write_memory(data_pointer, raw_data) {
+		if let Err(err) = self.instance.write_memory(data_pointer, raw_data) {
 			return (Err(format!("call into the runtime method '{name}': failed to write the input payload into guest memory: {err}").into()), None);
 		}
 
-		match self.0.call_typed(&mut (), pc, (data_pointer, raw_data_length)) {
-			Ok(()) => {},
-			Err(CallError::Trap) =>
-				return (
-					Err(format!("call into the runtime method '{name}' failed: trap").into()),
-					None,
-				),
-			Err(CallError::Error(err)) =>
-				return (
-					Err(format!("call into the runtime method '{name}' failed: {err}").into()),
-					None,
-				),
-			Err(CallError::User(err)) =>
-				return (
-					Err(format!("call into the runtime method '{name}' failed: {err}").into()),
-					None,
-				),
-			Err(CallError::NotEnoughGas) => unreachable!("gas metering is never enabled"),
-		};
+		let result = self.instance.call_typed(&mut host_state, pc, (data_pointer, raw_data_length));
+		let allocation_stats = Some(host_state.allocator.stats());
+
+		if let Err(error) = result {
+			// A failure caused by one of the deterministic limits is reported as such, no matter
+			// how the guest reacted to it.
+			if let Some(limit) = self.exceeded_limit(&host_state) {
+				return (Err(Error::LimitExceeded(limit)), allocation_stats)
+			}
+
+			let error = match error {
+				CallError::Trap => match host_state.panic_message.take() {
+					Some(message) =>
+						Error::AbortedDueToPanic(MessageWithBacktrace { message, backtrace: None }),
+					None => Error::AbortedDueToTrap(MessageWithBacktrace {
+						message: format!("call into the runtime method '{name}' failed: trap"),
+						backtrace: None,
+					}),
+				},
+				CallError::Error(err) =>
+					format!("call into the runtime method '{name}' failed: {err}").into(),
+				CallError::User(message) =>
+					Error::AbortedDueToTrap(MessageWithBacktrace { message, backtrace: None }),
+				CallError::NotEnoughGas => unreachable!("gas metering is never enabled"),
+			};
+			return (Err(error), allocation_stats)
+		}
 
-		let result_pointer = self.0.reg(Reg::A0);
-		let result_length = self.0.reg(Reg::A1);
-		let output = match self.0.read_memory(result_pointer as u32, result_length as u32) {
+		let result_pointer = self.instance.reg(Reg::A0);
+		let result_length = self.instance.reg(Reg::A1);
+		let output = match self.instance.read_memory(result_pointer as u32, result_length as u32) {
 			Ok(output) => output,
-			Err(error) => {
-				return (Err(format!("call into the runtime method '{name}' failed: failed to read the return payload: {error}").into()), None)
-			},
+			Err(_) => return (Err(Error::OutputExceedsBounds), allocation_stats),
 		};
 
-		(Ok(output), None)
+		(Ok(output), allocation_stats)
 	}
 }
 
-struct Context<'r, 'a>(&'r mut polkavm::Caller<'a, ()>);
+struct Context<'r, 'a>(&'r mut polkavm::Caller<'a, HostState>);
 
 impl<'r, 'a> FunctionContext for Context<'r, 'a> {
 	fn read_memory_into(
@@ -147,32 +253,37 @@ impl<'r, 'a> FunctionContext for Context<'r, 'a> {
 	}
 
 	fn allocate_memory(&mut self, size: WordSize) -> sp_wasm_interface::Result<Pointer<u8>> {
-		let pointer = match self.0.instance.sbrk(0) {
-			Ok(pointer) => pointer.expect("fetching the current heap pointer never fails"),
-			Err(err) => return Err(format!("sbrk failed: {err}")),
-		};
-
-		// TODO: This will leak guest memory; find a better solution.
-		match self.0.instance.sbrk(size) {
-			Ok(Some(_)) => (),
-			Ok(None) => return Err(String::from("allocation error")),
-			Err(err) => return Err(format!("sbrk failed: {err}")),
-		}
-
-		Ok(Pointer::new(pointer))
+		self.0
+			.user_data
+			.allocator
+			.allocate(&mut *self.0.instance, size)
+			.map(Pointer::new)
+			.map_err(|error| error.to_string())
 	}
 
-	fn deallocate_memory(&mut self, _ptr: Pointer<u8>) -> sp_wasm_interface::Result<()> {
-		// This is only used by the allocator host function, which is unused under PolkaVM.
-		unimplemented!("'deallocate_memory' is never used when running under PolkaVM");
+	fn deallocate_memory(&mut self, ptr: Pointer<u8>) -> sp_wasm_interface::Result<()> {
+		self.0
+			.user_data
+			.allocator
+			.deallocate(u32::from(ptr))
+			.map_err(|error| error.to_string())
 	}
 
-	fn register_panic_error_message(&mut self, _message: &str) {
-		unimplemented!("'register_panic_error_message' is never used when running under PolkaVM");
+	fn register_panic_error_message(&mut self, message: &str) {
+		self.0.user_data.panic_message = Some(message.to_owned());
 	}
 }
 
-fn call_host_function(caller: &mut Caller<()>, function: &dyn Function) -> Result<(), String> {
+/// Returns the number of bytes currently used by the stack of the guest.
+fn stack_usage(instance: &polkavm::RawInstance) -> u64 {
+	let stack_address_high = u64::from(instance.module().memory_map().stack_address_high());
+	stack_address_high.saturating_sub(instance.reg(Reg::SP))
+}
+
+fn call_host_function(
+	caller: &mut Caller<HostState>,
+	function: &dyn Function,
+) -> Result<(), String> {
 	let mut args = [Value::I64(0); Reg::ARG_REGS.len()];
 	let mut nth_reg = 0;
 	for (nth_arg, kind) in function.signature().args.iter().enumerate() {
@@ -226,8 +337,8 @@ fn call_host_function(caller: &mut Caller<()>, function: &dyn Function) -> Resul
 	{
 		Ok(value) => value,
 		Err(error) => {
-			let name = function.name();
-			return Err(format!("call into the host function '{name}' failed: {error}"))
+			log::debug!("Call into the host function '{}' failed: {error}", function.name());
+			return Err(error)
 		},
 	};
 
@@ -259,35 +370,135 @@ fn call_host_function(caller: &mut Caller<()>, function: &dyn Function) -> Resul
 	Ok(())
 }
 
-pub fn create_runtime<H>(blob: &polkavm::ProgramBlob) -> Result<Box<dyn WasmModule>, WasmError>
-where
-	H: HostFunctions,
-{
-	static ENGINE: std::sync::OnceLock<Result<polkavm::Engine, polkavm::Error>> =
-		std::sync::OnceLock::new();
+/// Returns the engine for the given backend, creating it if necessary.
+fn engine(backend: Backend) -> Result<&'static polkavm::Engine, WasmError> {
+	static ENGINES: [OnceLock<Result<polkavm::Engine, String>>; 3] =
+		[OnceLock::new(), OnceLock::new(), OnceLock::new()];
 
-	let engine = ENGINE.get_or_init(|| {
-		let config = polkavm::Config::from_env()?;
-		polkavm::Engine::new(&config)
+	let (index, backend_kind) = match backend {
+		Backend::Auto => (0, None),
+		Backend::Compiler => (1, Some(polkavm::BackendKind::Compiler)),
+		Backend::Interpreter => (2, Some(polkavm::BackendKind::Interpreter)),
+	};
+
+	let engine = ENGINES[index].get_or_init(|| {
+		let mut config = polkavm::Config::from_env().map_err(|error| error.to_string())?;
+		if backend_kind.is_some() {
+			config.set_backend(backend_kind);
+		}
+
+		polkavm::Engine::new(&config).map_err(|error| error.to_string())
 	});
 
-	let engine = match engine {
-		Ok(ref engine) => engine,
-		Err(ref error) => {
-			return Err(WasmError::Other(error.to_string()));
+	engine.as_ref().map_err(|error| WasmError::Other(error.clone()))
+}
+
+/// Compiles the given program blob, or returns the module cached from a previous compilation.
+///
+/// If `stack_size` is given, it replaces the stack size the program was linked with.
+fn compile(
+	blob: &polkavm::ProgramBlob,
+	raw_blob: &[u8],
+	stack_size: Option<u32>,
+	backend: Backend,
+) -> Result<polkavm::Module, WasmError> {
+	static MODULE_CACHE: OnceLock<
+		Mutex<LruMap<([u8; 32], Option<u32>, Backend), polkavm::Module>>,
+	> = OnceLock::new();
+
+	let cache =
+		MODULE_CACHE.get_or_init(|| Mutex::new(LruMap::new(ByLength::new(MODULE_CACHE_SIZE))));
+	let key = (sp_crypto_hashing::blake2_256(raw_blob), stack_size, backend);
+
+	if let Some(module) = cache.lock().get(&key) {
+		return Ok(module.clone())
+	}
+
+	let blob = match stack_size {
+		Some(stack_size) => {
+			let mut parts = polkavm::program::ProgramParts::from_bytes(raw_blob.into())?;
+			parts.stack_size = stack_size;
+			polkavm::ProgramBlob::from_parts(parts)?
 		},
+		None => blob.clone(),
 	};
-
 	let module =
-		polkavm::Module::from_blob(&engine, &polkavm::ModuleConfig::default(), blob.clone())?;
+		polkavm::Module::from_blob(engine(backend)?, &polkavm::ModuleConfig::default(), blob)?;
+	cache.lock().insert(key, module.clone());
+
+	Ok(module)
+}
+
+/// Create a new PolkaVM runtime from the given PolkaVM program blob.
+pub fn create_runtime<H>(
+	blob: &RuntimeBlob,
+	config: Config,
+) -> Result<Box<dyn WasmModule>, WasmError>
+where
+	H: HostFunctions,
+{
+	let (Some(program_blob), Some(raw_blob)) = (blob.as_polkavm_blob(), blob.as_polkavm_raw_blob())
+	else {
+		return Err(WasmError::Other("expected a PolkaVM program blob".into()))
+	};
+
+	config.execution_limits.validate()?;
+
+	// Every logical value of the stack height limit takes `POLKAVM_STACK_VALUE_SIZE` bytes. The
+	// stack size is rounded up to the page size of the guest.
+	let stack_size = config
+		.execution_limits
+		.max_stack_height
+		.map(|max_stack_height| max_stack_height * POLKAVM_STACK_VALUE_SIZE);
+	let module = compile(program_blob, raw_blob, stack_size, config.backend)?;
 
 	let mut linker = polkavm::Linker::new();
 
+	let mut defined = HashSet::new();
 	for function in H::host_functions() {
-		linker.define_untyped(function.name(), |mut caller: Caller<()>| {
+		linker.define_untyped(function.name(), |mut caller: Caller<HostState>| {
 			call_host_function(&mut caller, function)
 		})?;
+		defined.insert(function.name());
+	}
+
+	if config.allow_missing_func_imports {
+		for symbol in program_blob.imports().iter().flatten() {
+			let name = String::from_utf8_lossy(symbol.as_bytes()).into_owned();
+			if defined.contains(name.as_str()) {
+				continue
+			}
+
+			let message = format!("call to a missing function {name}");
+			linker.define_untyped(name.as_str(), move |_caller: Caller<HostState>| {
+				Err(message.clone())
+			})?;
+		}
 	}
+
+	// Whatever the static data and the stack don't use out of the memory limit is left for the
+	// heap.
+	let heap_memory_limit = match config.execution_limits.max_memory_pages {
+		Some(max_memory_pages) => {
+			let memory_map = module.memory_map();
+			let static_size = u64::from(memory_map.ro_data_size()) +
+				u64::from(memory_map.rw_data_size()) +
+				u64::from(memory_map.stack_size());
+			let heap_memory_limit = (u64::from(max_memory_pages) * PAGE_SIZE)
+				.checked_sub(static_size)
+				.ok_or(WasmError::LimitExceeded(ExecutionLimit::MemoryPages(max_memory_pages)))?;
+			Some(u32::try_from(heap_memory_limit).unwrap_or(u32::MAX))
+		},
+		None => None,
+	};
+
 	let instance_pre = linker.instantiate_pre(&module)?;
-	Ok(Box::new(InstancePre(instance_pre)))
+	Ok(Box::new(InstancePre {
+		instance_pre,
+		limits: Limits {
+			max_heap_size: allocator::max_heap_size(config.heap_alloc_strategy),
+			heap_memory_limit,
+			execution_limits: config.execution_limits,
+		},
+	}))
 }
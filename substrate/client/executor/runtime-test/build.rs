@@ -22,6 +22,7 @@ fn main() {
 	{
 		substrate_wasm_builder::WasmBuilder::new()
 			.with_current_project()
+			.set_target(substrate_wasm_builder::RuntimeTarget::Wasm)
 			.export_heap_base()
 			.import_memory()
 			.disable_runtime_version_section_check()
@@ -35,9 +36,24 @@ fn main() {
 			.with_current_project()
 			.export_heap_base()
 			.import_memory()
+			.set_target(substrate_wasm_builder::RuntimeTarget::Wasm)
 			.set_file_name("wasm_binary_with_tracing.rs")
 			.append_to_rust_flags(r#"--cfg feature="with-tracing""#)
 			.disable_runtime_version_section_check()
 			.build();
 	}
+
+	// and building for PolkaVM, if its toolchain is installed
+	#[cfg(feature = "std")]
+	{
+		if substrate_wasm_builder::RuntimeTarget::Riscv.is_toolchain_installed() {
+			substrate_wasm_builder::WasmBuilder::new()
+				.with_current_project()
+				.set_target(substrate_wasm_builder::RuntimeTarget::Riscv)
+				.set_file_name("polkavm_binary.rs")
+				.disable_runtime_version_section_check()
+				.build();
+			println!("cargo:rustc-cfg=polkavm_test_runtime");
+		}
+	}
 }
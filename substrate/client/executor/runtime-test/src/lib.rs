@@ -31,6 +31,48 @@ pub fn wasm_binary_unwrap() -> &'static [u8] {
 	)
 }
 
+// Make the PolkaVM binary available, it is only built if the RISC-V toolchain is installed.
+#[cfg(all(feature = "std", polkavm_test_runtime))]
+mod polkavm {
+	include!(concat!(env!("OUT_DIR"), "/polkavm_binary.rs"));
+}
+
+/// PolkaVM binary unwrapped. If the RISC-V toolchain isn't installed or built with
+/// `SKIP_WASM_BUILD`, the function panics.
+#[cfg(feature = "std")]
+pub fn polkavm_binary_unwrap() -> &'static [u8] {
+	#[cfg(polkavm_test_runtime)]
+	let binary = polkavm::WASM_BINARY;
+	#[cfg(not(polkavm_test_runtime))]
+	let binary = None;
+
+	binary.expect(
+		"Development PolkaVM binary is not available. Testing requires the RISC-V toolchain and \
+		 is only supported with the flag disabled.",
+	)
+}
+
+/// Declares a test executing the PolkaVM binary, ignored if it wasn't built.
+#[cfg(all(feature = "std", polkavm_test_runtime))]
+#[macro_export]
+macro_rules! polkavm_test {
+	($test:item) => {
+		#[test]
+		$test
+	};
+}
+
+/// Declares a test executing the PolkaVM binary, ignored if it wasn't built.
+#[cfg(all(feature = "std", not(polkavm_test_runtime)))]
+#[macro_export]
+macro_rules! polkavm_test {
+	($test:item) => {
+		#[test]
+		#[ignore = "the PolkaVM test runtime requires the RISC-V toolchain"]
+		$test
+	};
+}
+
 #[cfg(not(feature = "std"))]
 extern crate alloc;
 
@@ -51,6 +93,10 @@ use sp_runtime::{
 	traits::{BlakeTwo256, Hash},
 };
 
+#[cfg_attr(
+	all(any(target_arch = "riscv32", target_arch = "riscv64"), not(feature = "std")),
+	sp_runtime_interface::polkavm::polkavm_import(abi = sp_runtime_interface::polkavm::polkavm_abi)
+)]
 extern "C" {
 	#[allow(dead_code)]
 	fn missing_external();
@@ -140,6 +186,21 @@ sp_core::wasm_export_functions! {
 		Vec::with_capacity(size as usize)
 	}
 
+	fn test_recursion(depth: u32) -> u64 {
+		fn recurse(depth: u32) -> u64 {
+			if depth == 0 {
+				return 0
+			}
+
+			// Keep the values on the stack across the recursive call.
+			let values = core::hint::black_box([u64::from(depth); 16]);
+			let sum = recurse(depth - 1);
+			core::hint::black_box(&values).iter().sum::<u64>() + sum
+		}
+
+		recurse(depth)
+	}
+
 	fn test_fp_f32add(a: [u8; 4], b: [u8; 4]) -> [u8; 4] {
 		let a = f32::from_le_bytes(a);
 		let b = f32::from_le_bytes(b);
@@ -363,7 +424,7 @@ sp_core::wasm_export_functions! {
 	}
 
 	fn test_unreachable_intrinsic() {
-		core::arch::wasm32::unreachable()
+		sp_io::unreachable()
 	}
 
 	fn test_return_value() -> u64 {
@@ -375,7 +436,7 @@ sp_core::wasm_export_functions! {
 // Tests that check output validity. We explicitly return the ptr and len, so we avoid using the
 // `wasm_export_functions` macro.
 mod output_validity {
-	#[cfg(not(feature = "std"))]
+	#[cfg(all(not(feature = "std"), target_family = "wasm"))]
 	use super::WASM_PAGE_SIZE;
 
 	#[cfg(not(feature = "std"))]
@@ -384,13 +445,17 @@ mod output_validity {
 	// Returns a huge len. It should result in an error, and not an allocation.
 	#[no_mangle]
 	#[cfg(not(feature = "std"))]
+	#[cfg_attr(
+		any(target_arch = "riscv32", target_arch = "riscv64"),
+		sp_runtime_interface::polkavm::polkavm_export(abi = sp_runtime_interface::polkavm::polkavm_abi)
+	)]
 	pub extern "C" fn test_return_huge_len(_params: *const u8, _len: usize) -> u64 {
 		pack_ptr_and_len(0, u32::MAX)
 	}
 
 	// Returns an offset right before the edge of the wasm memory boundary. It should succeed.
 	#[no_mangle]
-	#[cfg(not(feature = "std"))]
+	#[cfg(all(not(feature = "std"), target_family = "wasm"))]
 	pub extern "C" fn test_return_max_memory_offset(_params: *const u8, _len: usize) -> u64 {
 		let output_ptr = (core::arch::wasm32::memory_size(0) * WASM_PAGE_SIZE) as u32 - 1;
 		let ptr = output_ptr as *mut u8;
@@ -402,7 +467,7 @@ mod output_validity {
 
 	// Returns an offset right after the edge of the wasm memory boundary. It should fail.
 	#[no_mangle]
-	#[cfg(not(feature = "std"))]
+	#[cfg(all(not(feature = "std"), target_family = "wasm"))]
 	pub extern "C" fn test_return_max_memory_offset_plus_one(
 		_params: *const u8,
 		_len: usize,
@@ -413,6 +478,10 @@ mod output_validity {
 	// Returns an output that overflows the u32 range. It should result in an error.
 	#[no_mangle]
 	#[cfg(not(feature = "std"))]
+	#[cfg_attr(
+		any(target_arch = "riscv32", target_arch = "riscv64"),
+		sp_runtime_interface::polkavm::polkavm_export(abi = sp_runtime_interface::polkavm::polkavm_abi)
+	)]
 	pub extern "C" fn test_return_overflow(_params: *const u8, _len: usize) -> u64 {
 		pack_ptr_and_len(u32::MAX, 1)
 	}
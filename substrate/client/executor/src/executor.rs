@@ -31,9 +31,11 @@ use std::{
 
 use codec::Encode;
 use sc_executor_common::{
+	profiler::Profiler,
 	runtime_blob::RuntimeBlob,
 	wasm_runtime::{
-		AllocationStats, HeapAllocStrategy, WasmInstance, WasmModule, DEFAULT_HEAP_ALLOC_STRATEGY,
+		AllocationStats, ExecutionLimits, HeapAllocStrategy, WasmInstance, WasmModule,
+		DEFAULT_HEAP_ALLOC_STRATEGY,
 	},
 };
 use sp_core::traits::{CallContext, CodeExecutor, Externalities, RuntimeCode};
@@ -93,6 +95,8 @@ pub struct WasmExecutorBuilder<H = sp_io::SubstrateHostFunctions> {
 	cache_path: Option<PathBuf>,
 	allow_missing_host_functions: bool,
 	runtime_cache_size: u8,
+	execution_limits: ExecutionLimits,
+	profiler: Option<Arc<Profiler>>,
 }
 
 impl<H> WasmExecutorBuilder<H> {
@@ -110,6 +114,8 @@ impl<H> WasmExecutorBuilder<H> {
 			runtime_cache_size: 4,
 			allow_missing_host_functions: false,
 			cache_path: None,
+			execution_limits: ExecutionLimits::default(),
+			profiler: None,
 		}
 	}
 
@@ -193,6 +199,31 @@ impl<H> WasmExecutorBuilder<H> {
 		self
 	}
 
+	/// Create the wasm executor with the given deterministic `execution_limits`.
+	///
+	/// Calls hitting one of the limits fail with
+	/// [`Error::LimitExceeded`](crate::error::Error::LimitExceeded) in every executor backend, at
+	/// exactly the same point. Limits no executor backend can enforce, such as a stack height above
+	/// [`MAX_STACK_HEIGHT`](crate::MAX_STACK_HEIGHT), fail the creation of the runtime with
+	/// [`WasmError::LimitExceeded`](crate::error::WasmError::LimitExceeded).
+	///
+	/// By default no limits are enforced besides the heap allocation strategies.
+	pub fn with_execution_limits(mut self, execution_limits: ExecutionLimits) -> Self {
+		self.execution_limits = execution_limits;
+		self
+	}
+
+	/// Create the wasm executor with the given `profiler`.
+	///
+	/// The time spent in every call into a WebAssembly runtime is recorded by the profiler, per
+	/// runtime function and host function.
+	///
+	/// By default no profiling is done.
+	pub fn with_profiler(mut self, profiler: Option<Arc<Profiler>>) -> Self {
+		self.profiler = profiler;
+		self
+	}
+
 	/// Build the configured [`WasmExecutor`].
 	pub fn build(self) -> WasmExecutor<H> {
 		WasmExecutor {
@@ -208,9 +239,12 @@ impl<H> WasmExecutorBuilder<H> {
 				self.max_runtime_instances,
 				self.cache_path.clone(),
 				self.runtime_cache_size,
+				self.profiler.clone(),
 			)),
 			cache_path: self.cache_path,
 			allow_missing_host_functions: self.allow_missing_host_functions,
+			execution_limits: self.execution_limits,
+			profiler: self.profiler,
 			phantom: PhantomData,
 		}
 	}
@@ -234,6 +268,10 @@ pub struct WasmExecutor<H = sp_io::SubstrateHostFunctions> {
 	cache_path: Option<PathBuf>,
 	/// Ignore missing function imports.
 	allow_missing_host_functions: bool,
+	/// The deterministic limits enforced during execution.
+	execution_limits: ExecutionLimits,
+	/// Records the time spent in the runtime calls, if set.
+	profiler: Option<Arc<Profiler>>,
 	phantom: PhantomData<H>,
 }
 
@@ -247,6 +285,8 @@ impl<H> Clone for WasmExecutor<H> {
 			cache: self.cache.clone(),
 			cache_path: self.cache_path.clone(),
 			allow_missing_host_functions: self.allow_missing_host_functions,
+			execution_limits: self.execution_limits,
+			profiler: self.profiler.clone(),
 			phantom: self.phantom,
 		}
 	}
@@ -298,9 +338,12 @@ impl<H> WasmExecutor<H> {
 				max_runtime_instances,
 				cache_path.clone(),
 				runtime_cache_size,
+				None,
 			)),
 			cache_path,
 			allow_missing_host_functions: false,
+			execution_limits: ExecutionLimits::default(),
+			profiler: None,
 			phantom: PhantomData,
 		}
 	}
@@ -354,6 +397,7 @@ where
 			ext,
 			self.method,
 			heap_alloc_strategy,
+			self.execution_limits,
 			self.allow_missing_host_functions,
 			|module, instance, version, ext| {
 				let module = AssertUnwindSafe(module);
@@ -427,9 +471,11 @@ where
 		let module = crate::wasm_runtime::create_wasm_runtime_with_code::<H>(
 			self.method,
 			self.default_onchain_heap_alloc_strategy,
+			self.execution_limits,
 			runtime_blob,
 			allow_missing_host_functions,
 			self.cache_path.as_deref(),
+			self.profiler.clone(),
 		)
 		.map_err(|e| format!("Failed to create module: {}", e))?;
 
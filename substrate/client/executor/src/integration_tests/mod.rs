@@ -16,14 +16,15 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use assert_matches::assert_matches;
 use codec::{Decode, Encode};
 use sc_executor_common::{
-	error::Error,
+	error::{Error, WasmError},
 	runtime_blob::RuntimeBlob,
-	wasm_runtime::{HeapAllocStrategy, WasmModule},
+	wasm_runtime::{
+		ExecutionLimit, ExecutionLimits, HeapAllocStrategy, WasmModule, MAX_STACK_HEIGHT,
+	},
 };
-use sc_runtime_test::wasm_binary_unwrap;
+use sc_runtime_test::{polkavm_binary_unwrap, wasm_binary_unwrap};
 use sp_core::{
 	ed25519, map,
 	offchain::{testing, OffchainDbExt, OffchainWorkerExt},
@@ -44,6 +45,10 @@ pub type TestExternalities = CoreTestExternalities<BlakeTwo256>;
 type HostFunctions = sp_io::SubstrateHostFunctions;
 
 /// Simple macro that runs a given method as test with the available wasm execution methods.
+///
+/// The test runtime is always built for WebAssembly, and for PolkaVM only if the RISC-V toolchain
+/// is installed. The PolkaVM tests are ignored otherwise, and running them also requires
+/// `SUBSTRATE_ENABLE_POLKAVM=1`.
 #[macro_export]
 macro_rules! test_wasm_execution {
 	($method_name:ident) => {
@@ -79,10 +84,30 @@ macro_rules! test_wasm_execution {
 					instantiation_strategy: sc_executor_wasmtime::InstantiationStrategy::Pooling
 				});
 			}
+
+			sc_runtime_test::polkavm_test! {
+				fn [<$method_name _polkavm>]() {
+					let _ = sp_tracing::try_init_simple();
+					$method_name(WasmExecutionMethod::PolkaVm {
+						backend: sc_executor_polkavm::Backend::Auto
+					});
+				}
+			}
 		}
 	};
 }
 
+/// Returns the test runtime built for the target executed by `wasm_method`.
+fn test_runtime(wasm_method: WasmExecutionMethod) -> RuntimeBlob {
+	let code = match wasm_method {
+		WasmExecutionMethod::Compiled { .. } => wasm_binary_unwrap(),
+		WasmExecutionMethod::PolkaVm { .. } => polkavm_binary_unwrap(),
+	};
+
+	RuntimeBlob::uncompress_if_needed(code)
+		.expect("failed to create a runtime blob out of test runtime")
+}
+
 fn call_in_wasm<E: Externalities>(
 	function: &str,
 	call_data: &[u8],
@@ -93,13 +118,7 @@ fn call_in_wasm<E: Externalities>(
 		.with_execution_method(execution_method)
 		.build();
 
-	executor.uncached_call(
-		RuntimeBlob::uncompress_if_needed(wasm_binary_unwrap()).unwrap(),
-		ext,
-		true,
-		function,
-		call_data,
-	)
+	executor.uncached_call(test_runtime(wasm_method), ext, true, function, call_data)
 }
 
 test_wasm_execution!(returning_should_work);
@@ -121,6 +140,8 @@ fn call_not_existing_function(wasm_method: WasmExecutionMethod) {
 			let expected = match wasm_method {
 				WasmExecutionMethod::Compiled { .. } =>
 					"call to a missing function env:missing_external",
+				WasmExecutionMethod::PolkaVm { .. } =>
+					"call to a missing function missing_external",
 			};
 			assert_eq!(error.message, expected);
 		},
@@ -140,6 +161,8 @@ fn call_yet_another_not_existing_function(wasm_method: WasmExecutionMethod) {
 			let expected = match wasm_method {
 				WasmExecutionMethod::Compiled { .. } =>
 					"call to a missing function env:yet_another_missing_external",
+				WasmExecutionMethod::PolkaVm { .. } =>
+					"call to a missing function yet_another_missing_external",
 			};
 			assert_eq!(error.message, expected);
 		},
@@ -419,7 +442,7 @@ fn should_trap_when_heap_exhausted(wasm_method: WasmExecutionMethod) {
 
 	let err = executor
 		.uncached_call(
-			RuntimeBlob::uncompress_if_needed(wasm_binary_unwrap()).unwrap(),
+			test_runtime(wasm_method),
 			&mut ext.ext(),
 			true,
 			"test_allocate_vec",
@@ -428,9 +451,7 @@ fn should_trap_when_heap_exhausted(wasm_method: WasmExecutionMethod) {
 		.unwrap_err();
 
 	match err {
-		Error::AbortedDueToTrap(error)
-			if matches!(wasm_method, WasmExecutionMethod::Compiled { .. }) =>
-		{
+		Error::AbortedDueToTrap(error) => {
 			assert_eq!(
 				error.message,
 				r#"host code panicked while being called by the runtime: Failed to allocate memory: "Allocator ran out of space""#
@@ -440,19 +461,170 @@ fn should_trap_when_heap_exhausted(wasm_method: WasmExecutionMethod) {
 	}
 }
 
+test_wasm_execution!(should_abort_when_memory_limit_exceeded);
+fn should_abort_when_memory_limit_exceeded(wasm_method: WasmExecutionMethod) {
+	let mut ext = TestExternalities::default();
+
+	let executor = crate::WasmExecutor::<HostFunctions>::builder()
+		.with_execution_method(wasm_method)
+		.with_onchain_heap_alloc_strategy(HeapAllocStrategy::Dynamic { maximum_pages: None })
+		.with_execution_limits(ExecutionLimits { max_memory_pages: Some(64), ..Default::default() })
+		.build();
+	let mut call = |size: u32| {
+		executor.uncached_call(
+			test_runtime(wasm_method),
+			&mut ext.ext(),
+			true,
+			"test_allocate_vec",
+			&size.encode(),
+		)
+	};
+
+	call(1024 * 1024).unwrap();
+	match call(16 * 1024 * 1024).unwrap_err() {
+		Error::LimitExceeded(ExecutionLimit::MemoryPages(64)) => {},
+		error => panic!("unexpected error: {:?}", error),
+	}
+}
+
+test_wasm_execution!(should_abort_when_stack_limit_exceeded);
+fn should_abort_when_stack_limit_exceeded(wasm_method: WasmExecutionMethod) {
+	let mut ext = TestExternalities::default();
+
+	let executor = crate::WasmExecutor::<HostFunctions>::builder()
+		.with_execution_method(wasm_method)
+		.with_execution_limits(ExecutionLimits {
+			max_stack_height: Some(4096),
+			..Default::default()
+		})
+		.build();
+	let mut call = |depth: u32| {
+		executor.uncached_call(
+			test_runtime(wasm_method),
+			&mut ext.ext(),
+			true,
+			"test_recursion",
+			&depth.encode(),
+		)
+	};
+
+	call(10).unwrap();
+	match call(10_000).unwrap_err() {
+		Error::LimitExceeded(ExecutionLimit::StackHeight(4096)) => {},
+		error => panic!("unexpected error: {:?}", error),
+	}
+}
+
+test_wasm_execution!(should_abort_at_max_stack_height);
+fn should_abort_at_max_stack_height(wasm_method: WasmExecutionMethod) {
+	let mut ext = TestExternalities::default();
+
+	let executor = crate::WasmExecutor::<HostFunctions>::builder()
+		.with_execution_method(wasm_method)
+		.with_execution_limits(ExecutionLimits {
+			max_stack_height: Some(MAX_STACK_HEIGHT),
+			..Default::default()
+		})
+		.build();
+	let error = executor
+		.uncached_call(
+			test_runtime(wasm_method),
+			&mut ext.ext(),
+			true,
+			"test_recursion",
+			&1_000_000_u32.encode(),
+		)
+		.unwrap_err();
+	match error {
+		Error::LimitExceeded(ExecutionLimit::StackHeight(MAX_STACK_HEIGHT)) => {},
+		error => panic!("unexpected error: {:?}", error),
+	}
+}
+
+test_wasm_execution!(should_reject_too_large_stack_limit);
+fn should_reject_too_large_stack_limit(wasm_method: WasmExecutionMethod) {
+	let mut ext = TestExternalities::default();
+
+	for max_stack_height in [MAX_STACK_HEIGHT + 1, u32::MAX] {
+		let executor = crate::WasmExecutor::<HostFunctions>::builder()
+			.with_execution_method(wasm_method)
+			.with_execution_limits(ExecutionLimits {
+				max_stack_height: Some(max_stack_height),
+				..Default::default()
+			})
+			.build();
+		let error = executor
+			.uncached_call(
+				test_runtime(wasm_method),
+				&mut ext.ext(),
+				true,
+				"test_empty_return",
+				&[],
+			)
+			.unwrap_err();
+		match error {
+			Error::RuntimeConstruction(WasmError::LimitExceeded(ExecutionLimit::StackHeight(
+				max,
+			))) if max == max_stack_height => {},
+			error => panic!("unexpected error: {:?}", error),
+		}
+	}
+}
+
+test_wasm_execution!(should_profile_host_calls);
+fn should_profile_host_calls(wasm_method: WasmExecutionMethod) {
+	// Only the calls into WebAssembly runtimes are profiled.
+	if matches!(wasm_method, WasmExecutionMethod::PolkaVm { .. }) {
+		return
+	}
+
+	let mut ext = TestExternalities::default();
+	let profiler = Arc::new(crate::Profiler::new());
+
+	let executor = crate::WasmExecutor::<HostFunctions>::builder()
+		.with_execution_method(wasm_method)
+		.with_profiler(Some(profiler.clone()))
+		.build();
+	executor
+		.uncached_call(
+			test_runtime(wasm_method),
+			&mut ext.ext(),
+			true,
+			"test_blake2_256",
+			&b"Hello world!".to_vec().encode(),
+		)
+		.unwrap();
+
+	let mut folded = Vec::new();
+	profiler.write_folded(&mut folded).unwrap();
+	let folded = String::from_utf8(folded).unwrap();
+	let stacks = folded
+		.lines()
+		.map(|line| line.rsplit_once(' ').expect("every line ends with the time").0)
+		.collect::<Vec<_>>();
+
+	assert!(stacks.contains(&"test_blake2_256"), "{folded}");
+	assert!(
+		stacks.iter().any(|stack| stack.starts_with("test_blake2_256;") &&
+			stack.ends_with(";ext_hashing_blake2_256_version_1")),
+		"{folded}",
+	);
+}
+
 fn mk_test_runtime(
 	wasm_method: WasmExecutionMethod,
 	pages: HeapAllocStrategy,
 ) -> Box<dyn WasmModule> {
-	let blob = RuntimeBlob::uncompress_if_needed(wasm_binary_unwrap())
-		.expect("failed to create a runtime blob out of test runtime");
+	let blob = test_runtime(wasm_method);
 
 	crate::wasm_runtime::create_wasm_runtime_with_code::<HostFunctions>(
 		wasm_method,
 		pages,
+		Default::default(),
 		blob,
 		true,
 		None,
+		None,
 	)
 	.expect("failed to instantiate wasm runtime")
 }
@@ -534,7 +706,7 @@ fn parallel_execution(wasm_method: WasmExecutionMethod) {
 				assert_eq!(
 					executor
 						.uncached_call(
-							RuntimeBlob::uncompress_if_needed(wasm_binary_unwrap()).unwrap(),
+							test_runtime(wasm_method),
 							&mut ext,
 							true,
 							"test_twox_128",
@@ -676,9 +848,11 @@ fn memory_is_cleared_between_invocations(wasm_method: WasmExecutionMethod) {
 	let runtime = crate::wasm_runtime::create_wasm_runtime_with_code::<HostFunctions>(
 		wasm_method,
 		HeapAllocStrategy::Dynamic { maximum_pages: Some(1024) },
+		Default::default(),
 		RuntimeBlob::uncompress_if_needed(&binary[..]).unwrap(),
 		true,
 		None,
+		None,
 	)
 	.unwrap();
 
@@ -730,6 +904,8 @@ fn unreachable_intrinsic(wasm_method: WasmExecutionMethod) {
 			let expected = match wasm_method {
 				WasmExecutionMethod::Compiled { .. } =>
 					"wasm trap: wasm `unreachable` instruction executed",
+				WasmExecutionMethod::PolkaVm { .. } =>
+					"call into the runtime method 'test_unreachable_intrinsic' failed: trap",
 			};
 			assert_eq!(error.message, expected);
 		},
@@ -754,15 +930,18 @@ fn return_huge_len(wasm_method: WasmExecutionMethod) {
 	let mut ext = ext.ext();
 
 	match call_in_wasm("test_return_huge_len", &[], wasm_method, &mut ext).unwrap_err() {
-		Error::OutputExceedsBounds => {
-			assert_matches!(wasm_method, WasmExecutionMethod::Compiled { .. });
-		},
+		Error::OutputExceedsBounds => {},
 		error => panic!("unexpected error: {:?}", error),
 	}
 }
 
 test_wasm_execution!(return_max_memory_offset);
 fn return_max_memory_offset(wasm_method: WasmExecutionMethod) {
+	// PolkaVM has no equivalent of the WebAssembly linear memory size.
+	if matches!(wasm_method, WasmExecutionMethod::PolkaVm { .. }) {
+		return
+	}
+
 	let mut ext = TestExternalities::default();
 	let mut ext = ext.ext();
 
@@ -774,15 +953,18 @@ fn return_max_memory_offset(wasm_method: WasmExecutionMethod) {
 
 test_wasm_execution!(return_max_memory_offset_plus_one);
 fn return_max_memory_offset_plus_one(wasm_method: WasmExecutionMethod) {
+	// PolkaVM has no equivalent of the WebAssembly linear memory size.
+	if matches!(wasm_method, WasmExecutionMethod::PolkaVm { .. }) {
+		return
+	}
+
 	let mut ext = TestExternalities::default();
 	let mut ext = ext.ext();
 
 	match call_in_wasm("test_return_max_memory_offset_plus_one", &[], wasm_method, &mut ext)
 		.unwrap_err()
 	{
-		Error::OutputExceedsBounds => {
-			assert_matches!(wasm_method, WasmExecutionMethod::Compiled { .. });
-		},
+		Error::OutputExceedsBounds => {},
 		error => panic!("unexpected error: {:?}", error),
 	}
 }
@@ -793,9 +975,7 @@ fn return_overflow(wasm_method: WasmExecutionMethod) {
 	let mut ext = ext.ext();
 
 	match call_in_wasm("test_return_overflow", &[], wasm_method, &mut ext).unwrap_err() {
-		Error::OutputExceedsBounds => {
-			assert_matches!(wasm_method, WasmExecutionMethod::Compiled { .. });
-		},
+		Error::OutputExceedsBounds => {},
 		error => panic!("unexpected error: {:?}", error),
 	}
 }
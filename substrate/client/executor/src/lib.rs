@@ -50,8 +50,13 @@ pub use wasm_runtime::{read_embedded_version, WasmExecutionMethod};
 
 pub use sc_executor_common::{
 	error,
-	wasm_runtime::{HeapAllocStrategy, DEFAULT_HEAP_ALLOC_PAGES, DEFAULT_HEAP_ALLOC_STRATEGY},
+	profiler::Profiler,
+	wasm_runtime::{
+		ExecutionLimit, ExecutionLimits, HeapAllocStrategy, DEFAULT_HEAP_ALLOC_PAGES,
+		DEFAULT_HEAP_ALLOC_STRATEGY, MAX_STACK_HEIGHT,
+	},
 };
+pub use sc_executor_polkavm::Backend as PolkaVmBackend;
 pub use sc_executor_wasmtime::InstantiationStrategy as WasmtimeInstantiationStrategy;
 
 /// Extracts the runtime version of a given runtime code.
@@ -26,8 +26,9 @@ use crate::error::{Error, WasmError};
 use codec::Decode;
 use parking_lot::Mutex;
 use sc_executor_common::{
+	profiler::Profiler,
 	runtime_blob::RuntimeBlob,
-	wasm_runtime::{HeapAllocStrategy, WasmInstance, WasmModule},
+	wasm_runtime::{ExecutionLimits, HeapAllocStrategy, WasmInstance, WasmModule},
 };
 use schnellru::{ByLength, LruMap};
 use sp_core::traits::{Externalities, FetchRuntimeCode, RuntimeCode};
@@ -48,13 +49,41 @@ pub enum WasmExecutionMethod {
 		/// The instantiation strategy to use.
 		instantiation_strategy: sc_executor_wasmtime::InstantiationStrategy,
 	},
+	/// Uses PolkaVM.
+	///
+	/// Only PolkaVM runtimes can be executed with PolkaVM, so WebAssembly runtimes are executed
+	/// with the default Wasmtime configuration instead.
+	PolkaVm {
+		/// The PolkaVM backend to use.
+		backend: sc_executor_polkavm::Backend,
+	},
 }
 
+/// The instantiation strategy used for WebAssembly runtimes unless specified otherwise.
+const DEFAULT_INSTANTIATION_STRATEGY: sc_executor_wasmtime::InstantiationStrategy =
+	sc_executor_wasmtime::InstantiationStrategy::PoolingCopyOnWrite;
+
+/// The native stack given to wasmtime for every logical value of a deterministic stack limit.
+///
+/// Assuming a logical value takes 8 bytes, this is 16 times what the logical values need, which
+/// leaves room for the frame of every function on top of its values. The logical limit is then
+/// reached first, and bounds the stack actually used.
+const NATIVE_STACK_BYTES_PER_LOGICAL_VALUE: u32 = 128;
+
+/// The most native stack given to wasmtime.
+///
+/// This is what wasmtime is given without a deterministic stack limit too, so it is known to fit
+/// in the stack of the threads calling into the runtime, with room left for the host functions.
+const MAX_NATIVE_STACK: u32 = 1024 * 1024;
+
+const _: () = assert!(
+	sc_executor_common::wasm_runtime::MAX_STACK_HEIGHT * NATIVE_STACK_BYTES_PER_LOGICAL_VALUE <=
+		MAX_NATIVE_STACK
+);
+
 impl Default for WasmExecutionMethod {
 	fn default() -> Self {
-		Self::Compiled {
-			instantiation_strategy: sc_executor_wasmtime::InstantiationStrategy::PoolingCopyOnWrite,
-		}
+		Self::Compiled { instantiation_strategy: DEFAULT_INSTANTIATION_STRATEGY }
 	}
 }
 
@@ -66,6 +95,8 @@ struct VersionedRuntimeId {
 	wasm_method: WasmExecutionMethod,
 	/// The heap allocation strategy this runtime was created with.
 	heap_alloc_strategy: HeapAllocStrategy,
+	/// The execution limits this runtime was created with.
+	execution_limits: ExecutionLimits,
 }
 
 /// A Wasm runtime object along with its cached runtime version.
@@ -167,6 +198,7 @@ pub struct RuntimeCache {
 	/// The size of the instances cache for each runtime.
 	max_runtime_instances: usize,
 	cache_path: Option<PathBuf>,
+	profiler: Option<Arc<Profiler>>,
 }
 
 impl RuntimeCache {
@@ -180,13 +212,21 @@ impl RuntimeCache {
 	///
 	/// `runtime_cache_size` specifies the number of different runtimes versions preserved in an
 	/// in-memory cache, must always be at least 1.
+	///
+	/// `profiler` records the time spent in the calls into the created runtimes, if set.
 	pub fn new(
 		max_runtime_instances: usize,
 		cache_path: Option<PathBuf>,
 		runtime_cache_size: u8,
+		profiler: Option<Arc<Profiler>>,
 	) -> RuntimeCache {
 		let cap = ByLength::new(runtime_cache_size.max(1) as u32);
-		RuntimeCache { runtimes: Mutex::new(LruMap::new(cap)), max_runtime_instances, cache_path }
+		RuntimeCache {
+			runtimes: Mutex::new(LruMap::new(cap)),
+			max_runtime_instances,
+			cache_path,
+			profiler,
+		}
 	}
 
 	/// Prepares a WASM module instance and executes given function for it.
@@ -202,6 +242,8 @@ impl RuntimeCache {
 	///
 	/// `heap_alloc_strategy` - The heap allocation strategy to use.
 	///
+	/// `execution_limits` - The deterministic execution limits to enforce.
+	///
 	/// `allow_missing_func_imports` - Ignore missing function imports.
 	///
 	/// `f` - Function to execute.
@@ -221,6 +263,7 @@ impl RuntimeCache {
 		ext: &mut dyn Externalities,
 		wasm_method: WasmExecutionMethod,
 		heap_alloc_strategy: HeapAllocStrategy,
+		execution_limits: ExecutionLimits,
 		allow_missing_func_imports: bool,
 		f: F,
 	) -> Result<Result<R, Error>, Error>
@@ -235,8 +278,12 @@ impl RuntimeCache {
 	{
 		let code_hash = &runtime_code.hash;
 
-		let versioned_runtime_id =
-			VersionedRuntimeId { code_hash: code_hash.clone(), heap_alloc_strategy, wasm_method };
+		let versioned_runtime_id = VersionedRuntimeId {
+			code_hash: code_hash.clone(),
+			heap_alloc_strategy,
+			execution_limits,
+			wasm_method,
+		};
 
 		let mut runtimes = self.runtimes.lock(); // this must be released prior to calling f
 		let versioned_runtime = if let Some(versioned_runtime) = runtimes.get(&versioned_runtime_id)
@@ -252,9 +299,11 @@ impl RuntimeCache {
 				ext,
 				wasm_method,
 				heap_alloc_strategy,
+				execution_limits,
 				allow_missing_func_imports,
 				self.max_runtime_instances,
 				self.cache_path.as_deref(),
+				self.profiler.clone(),
 			);
 
 			match result {
@@ -287,42 +336,80 @@ impl RuntimeCache {
 }
 
 /// Create a wasm runtime with the given `code`.
+///
+/// Only the calls into WebAssembly runtimes are recorded by the `profiler`, a warning is logged
+/// when it is set for a PolkaVM runtime.
 pub fn create_wasm_runtime_with_code<H>(
 	wasm_method: WasmExecutionMethod,
 	heap_alloc_strategy: HeapAllocStrategy,
+	execution_limits: ExecutionLimits,
 	blob: RuntimeBlob,
 	allow_missing_func_imports: bool,
 	cache_path: Option<&Path>,
+	profiler: Option<Arc<Profiler>>,
 ) -> Result<Box<dyn WasmModule>, WasmError>
 where
 	H: HostFunctions,
 {
-	if let Some(blob) = blob.as_polkavm_blob() {
-		return sc_executor_polkavm::create_runtime::<H>(blob);
-	}
+	execution_limits.validate()?;
 
-	match wasm_method {
-		WasmExecutionMethod::Compiled { instantiation_strategy } =>
-			sc_executor_wasmtime::create_runtime::<H>(
-				blob,
-				sc_executor_wasmtime::Config {
-					allow_missing_func_imports,
-					cache_path: cache_path.map(ToOwned::to_owned),
-					semantics: sc_executor_wasmtime::Semantics {
-						heap_alloc_strategy,
-						instantiation_strategy,
-						deterministic_stack_limit: None,
-						canonicalize_nans: false,
-						parallel_compilation: true,
-						wasm_multi_value: false,
-						wasm_bulk_memory: false,
-						wasm_reference_types: false,
-						wasm_simd: false,
-					},
-				},
-			)
-			.map(|runtime| -> Box<dyn WasmModule> { Box::new(runtime) }),
+	if blob.as_polkavm_blob().is_some() {
+		if profiler.is_some() {
+			tracing::warn!(
+				target: "wasm-runtime",
+				"Profiling is not supported for PolkaVM runtimes, their execution is not recorded",
+			);
+		}
+
+		let backend = match wasm_method {
+			WasmExecutionMethod::PolkaVm { backend } => backend,
+			WasmExecutionMethod::Compiled { .. } => Default::default(),
+		};
+
+		return sc_executor_polkavm::create_runtime::<H>(
+			&blob,
+			sc_executor_polkavm::Config {
+				backend,
+				heap_alloc_strategy,
+				allow_missing_func_imports,
+				execution_limits,
+			},
+		);
 	}
+
+	let instantiation_strategy = match wasm_method {
+		WasmExecutionMethod::Compiled { instantiation_strategy } => instantiation_strategy,
+		WasmExecutionMethod::PolkaVm { .. } => DEFAULT_INSTANTIATION_STRATEGY,
+	};
+
+	let deterministic_stack_limit = execution_limits.max_stack_height.map(|logical_max| {
+		sc_executor_wasmtime::DeterministicStackLimit {
+			logical_max,
+			native_stack_max: logical_max * NATIVE_STACK_BYTES_PER_LOGICAL_VALUE,
+		}
+	});
+
+	sc_executor_wasmtime::create_runtime::<H>(
+		blob,
+		sc_executor_wasmtime::Config {
+			allow_missing_func_imports,
+			cache_path: cache_path.map(ToOwned::to_owned),
+			semantics: sc_executor_wasmtime::Semantics {
+				heap_alloc_strategy,
+				instantiation_strategy,
+				deterministic_stack_limit,
+				canonicalize_nans: false,
+				parallel_compilation: true,
+				wasm_multi_value: false,
+				wasm_bulk_memory: false,
+				wasm_reference_types: false,
+				wasm_simd: false,
+				max_memory_pages: execution_limits.max_memory_pages,
+			},
+			profiler,
+		},
+	)
+	.map(|runtime| -> Box<dyn WasmModule> { Box::new(runtime) })
 }
 
 fn decode_version(mut version: &[u8]) -> Result<RuntimeVersion, WasmError> {
@@ -386,9 +473,11 @@ fn create_versioned_wasm_runtime<H>(
 	ext: &mut dyn Externalities,
 	wasm_method: WasmExecutionMethod,
 	heap_alloc_strategy: HeapAllocStrategy,
+	execution_limits: ExecutionLimits,
 	allow_missing_func_imports: bool,
 	max_instances: usize,
 	cache_path: Option<&Path>,
+	profiler: Option<Arc<Profiler>>,
 ) -> Result<VersionedRuntime, WasmError>
 where
 	H: HostFunctions,
@@ -405,9 +494,11 @@ where
 	let runtime = create_wasm_runtime_with_code::<H>(
 		wasm_method,
 		heap_alloc_strategy,
+		execution_limits,
 		blob,
 		allow_missing_func_imports,
 		cache_path,
+		profiler,
 	)?;
 
 	// If the runtime blob doesn't embed the runtime version then use the legacy version query
@@ -22,6 +22,7 @@
 use wasmtime::Caller;
 
 use sc_allocator::{AllocationStats, FreeingBumpHeapAllocator};
+use sc_executor_common::profiler::CallProfiler;
 use sp_wasm_interface::{Pointer, WordSize};
 
 use crate::{instance_wrapper::MemoryWrapper, runtime::StoreData, util};
@@ -37,12 +38,16 @@ pub struct HostState {
 	/// once.
 	allocator: Option<FreeingBumpHeapAllocator>,
 	panic_message: Option<String>,
+	/// Set when the call is profiled.
+	///
+	/// Just like the allocator, this is taken out for the duration of a host call.
+	pub(crate) profiler: Option<CallProfiler>,
 }
 
 impl HostState {
 	/// Constructs a new `HostState`.
 	pub fn new(allocator: FreeingBumpHeapAllocator) -> Self {
-		HostState { allocator: Some(allocator), panic_message: None }
+		HostState { allocator: Some(allocator), panic_message: None, profiler: None }
 	}
 
 	/// Takes the error message out of the host state, leaving a `None` in its place.
@@ -104,6 +104,42 @@ impl<'a, 'b> sp_wasm_interface::HostFunctionRegistry for Registry<'a, 'b> {
 		callback(&mut HostContext { caller })
 	}
 
+	fn with_named_function_context<R>(
+		mut caller: wasmtime::Caller<Self::State>,
+		fn_name: &'static str,
+		callback: impl FnOnce(&mut dyn FunctionContext) -> R,
+	) -> R {
+		let profiler = caller
+			.data_mut()
+			.host_state_mut()
+			.and_then(|host_state| host_state.profiler.take());
+		let Some(mut profiler) = profiler else {
+			return Self::with_function_context(caller, callback)
+		};
+
+		profiler.enter_host_function(|| {
+			// The frames are ordered from the innermost one.
+			wasmtime::WasmBacktrace::capture(&caller)
+				.frames()
+				.iter()
+				.rev()
+				.map(|frame| match frame.func_name() {
+					Some(name) => name.to_owned(),
+					None => format!("wasm-function[{}]", frame.func_index()),
+				})
+				.collect()
+		});
+		let mut context = HostContext { caller };
+		let result = callback(&mut context);
+		profiler.exit_host_function(fn_name);
+
+		if let Some(host_state) = context.caller.data_mut().host_state_mut() {
+			host_state.profiler = Some(profiler);
+		}
+
+		result
+	}
+
 	fn register_static<Params, Results>(
 		&mut self,
 		fn_name: &str,
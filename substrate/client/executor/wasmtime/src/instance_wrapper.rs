@@ -21,8 +21,12 @@
 
 use std::sync::Arc;
 
-use crate::runtime::{InstanceCounter, ReleaseInstanceHandle, Store, StoreData};
-use sc_executor_common::error::{Backtrace, Error, MessageWithBacktrace, Result, WasmError};
+use crate::runtime::{InstanceCounter, MemoryLimiter, ReleaseInstanceHandle, Store, StoreData};
+use sc_executor_common::{
+	error::{Backtrace, Error, MessageWithBacktrace, Result, WasmError},
+	runtime_blob::STACK_HEIGHT_EXPORT,
+	wasm_runtime::ExecutionLimit,
+};
 use sp_wasm_interface::{Pointer, WordSize};
 use wasmtime::{AsContext, AsContextMut, Engine, Instance, InstancePre, Memory};
 
@@ -126,14 +130,24 @@ impl InstanceWrapper {
 		engine: &Engine,
 		instance_pre: &InstancePre<StoreData>,
 		instance_counter: Arc<InstanceCounter>,
+		max_memory_pages: Option<u32>,
 	) -> Result<Self> {
 		let _release_instance_handle = instance_counter.acquire_instance();
-		let mut store = Store::new(engine, Default::default());
+		let mut store = Store::new(
+			engine,
+			StoreData { limiter: MemoryLimiter::new(max_memory_pages), ..Default::default() },
+		);
+		store.limiter(|data| &mut data.limiter);
 		let instance = instance_pre.instantiate(&mut store).map_err(|error| {
-			WasmError::Other(format!(
-				"failed to instantiate a new WASM module instance: {:#}",
-				error,
-			))
+			// The initial size of the memory can be over the limit already.
+			match store.data().limiter.exceeded_limit() {
+				Some(limit) => Error::LimitExceeded(limit),
+				None => WasmError::Other(format!(
+					"failed to instantiate a new WASM module instance: {:#}",
+					error,
+				))
+				.into(),
+			}
 		})?;
 
 		let memory = get_linear_memory(&instance, &mut store)?;
@@ -181,6 +195,26 @@ impl InstanceWrapper {
 
 		Ok(heap_base as u32)
 	}
+
+	/// Returns the deterministic limit which was hit by the last call, if any.
+	pub(crate) fn exceeded_limit(
+		&mut self,
+		max_stack_height: Option<u32>,
+	) -> Option<ExecutionLimit> {
+		if let Some(limit) = self.store.data().limiter.exceeded_limit() {
+			return Some(limit)
+		}
+
+		// The stack height counter is left as it was when the instrumentation trapped.
+		let max_stack_height = max_stack_height?;
+		let stack_height = self
+			.instance
+			.get_global(&mut self.store, STACK_HEIGHT_EXPORT)?
+			.get(&mut self.store)
+			.i32()? as u32;
+
+		(stack_height > max_stack_height).then_some(ExecutionLimit::StackHeight(max_stack_height))
+	}
 }
 
 /// Extract linear memory instance from the given instance.
@@ -28,9 +28,10 @@ use parking_lot::Mutex;
 use sc_allocator::{AllocationStats, FreeingBumpHeapAllocator};
 use sc_executor_common::{
 	error::{Error, Result, WasmError},
+	profiler::{CallProfiler, Profiler},
 	runtime_blob::RuntimeBlob,
 	util::checked_range,
-	wasm_runtime::{HeapAllocStrategy, WasmInstance, WasmModule},
+	wasm_runtime::{ExecutionLimit, HeapAllocStrategy, WasmInstance, WasmModule},
 };
 use sp_runtime_interface::unpack_ptr_and_len;
 use sp_wasm_interface::{HostFunctions, Pointer, WordSize};
@@ -51,6 +52,54 @@ pub(crate) struct StoreData {
 	pub(crate) host_state: Option<HostState>,
 	/// This will be always set once the store is initialized.
 	pub(crate) memory: Option<Memory>,
+	/// Enforces [`Semantics::max_memory_pages`].
+	pub(crate) limiter: MemoryLimiter,
+}
+
+/// Caps the size of the linear memory, remembering whether the cap was ever hit.
+#[derive(Default)]
+pub(crate) struct MemoryLimiter {
+	max_memory_pages: Option<u32>,
+	exceeded: bool,
+}
+
+impl MemoryLimiter {
+	pub(crate) fn new(max_memory_pages: Option<u32>) -> Self {
+		Self { max_memory_pages, exceeded: false }
+	}
+
+	/// Returns the limit if it was hit since the limiter was created.
+	pub(crate) fn exceeded_limit(&self) -> Option<ExecutionLimit> {
+		self.max_memory_pages.filter(|_| self.exceeded).map(ExecutionLimit::MemoryPages)
+	}
+}
+
+impl wasmtime::ResourceLimiter for MemoryLimiter {
+	fn memory_growing(
+		&mut self,
+		_current: usize,
+		desired: usize,
+		_maximum: Option<usize>,
+	) -> anyhow::Result<bool> {
+		const WASM_PAGE_SIZE: usize = 65536;
+
+		match self.max_memory_pages {
+			Some(max) if desired > max as usize * WASM_PAGE_SIZE => {
+				self.exceeded = true;
+				Ok(false)
+			},
+			_ => Ok(true),
+		}
+	}
+
+	fn table_growing(
+		&mut self,
+		_current: u32,
+		_desired: u32,
+		_maximum: Option<u32>,
+	) -> anyhow::Result<bool> {
+		Ok(true)
+	}
 }
 
 impl StoreData {
@@ -75,11 +124,17 @@ struct InstanceCreator {
 	engine: Engine,
 	instance_pre: Arc<wasmtime::InstancePre<StoreData>>,
 	instance_counter: Arc<InstanceCounter>,
+	max_memory_pages: Option<u32>,
 }
 
 impl InstanceCreator {
 	fn instantiate(&mut self) -> Result<InstanceWrapper> {
-		InstanceWrapper::new(&self.engine, &self.instance_pre, self.instance_counter.clone())
+		InstanceWrapper::new(
+			&self.engine,
+			&self.instance_pre,
+			self.instance_counter.clone(),
+			self.max_memory_pages,
+		)
 	}
 }
 
@@ -137,6 +192,9 @@ pub struct WasmtimeRuntime {
 	instance_pre: Arc<wasmtime::InstancePre<StoreData>>,
 	instantiation_strategy: InternalInstantiationStrategy,
 	instance_counter: Arc<InstanceCounter>,
+	max_memory_pages: Option<u32>,
+	max_stack_height: Option<u32>,
+	profiler: Option<Arc<Profiler>>,
 }
 
 impl WasmModule for WasmtimeRuntime {
@@ -146,10 +204,15 @@ impl WasmModule for WasmtimeRuntime {
 				engine: self.engine.clone(),
 				instance_pre: self.instance_pre.clone(),
 				instance_counter: self.instance_counter.clone(),
+				max_memory_pages: self.max_memory_pages,
 			}),
 		};
 
-		Ok(Box::new(WasmtimeInstance { strategy }))
+		Ok(Box::new(WasmtimeInstance {
+			strategy,
+			max_stack_height: self.max_stack_height,
+			profiler: self.profiler.clone(),
+		}))
 	}
 }
 
@@ -157,6 +220,8 @@ impl WasmModule for WasmtimeRuntime {
 /// to execute the compiled code.
 pub struct WasmtimeInstance {
 	strategy: Strategy,
+	max_stack_height: Option<u32>,
+	profiler: Option<Arc<Profiler>>,
 }
 
 impl WasmtimeInstance {
@@ -173,7 +238,17 @@ impl WasmtimeInstance {
 				let entrypoint = instance_wrapper.resolve_entrypoint(method)?;
 				let allocator = FreeingBumpHeapAllocator::new(heap_base);
 
-				perform_call(data, &mut instance_wrapper, entrypoint, allocator, allocation_stats)
+				perform_call(
+					data,
+					&mut instance_wrapper,
+					entrypoint,
+					allocator,
+					allocation_stats,
+					self.max_stack_height,
+					self.profiler
+						.as_ref()
+						.map(|profiler| CallProfiler::new(profiler.clone(), method)),
+				)
 			},
 		}
 	}
@@ -456,6 +531,13 @@ pub struct Semantics {
 
 	/// Enables WASM Fixed-Width SIMD proposal
 	pub wasm_simd: bool,
+
+	/// The maximum size of the linear memory, in pages, regardless of the heap allocation
+	/// strategy.
+	///
+	/// Unlike the limits of the [`HeapAllocStrategy`], reaching this one makes the call fail with
+	/// [`Error::LimitExceeded`].
+	pub max_memory_pages: Option<u32>,
 }
 
 #[derive(Clone)]
@@ -471,6 +553,9 @@ pub struct Config {
 
 	/// Tuning of various semantics of the wasmtime executor.
 	pub semantics: Semantics,
+
+	/// If set, the time spent in every call is recorded by this profiler.
+	pub profiler: Option<Arc<Profiler>>,
 }
 
 enum CodeSupplyMode<'a> {
@@ -636,6 +721,13 @@ where
 		instance_pre: Arc::new(instance_pre),
 		instantiation_strategy,
 		instance_counter: Default::default(),
+		max_memory_pages: config.semantics.max_memory_pages,
+		max_stack_height: config
+			.semantics
+			.deterministic_stack_limit
+			.as_ref()
+			.map(|limit| limit.logical_max),
+		profiler: config.profiler,
 	})
 }
 
@@ -682,10 +774,13 @@ fn perform_call(
 	entrypoint: EntryPoint,
 	mut allocator: FreeingBumpHeapAllocator,
 	allocation_stats: &mut Option<AllocationStats>,
+	max_stack_height: Option<u32>,
+	profiler: Option<CallProfiler>,
 ) -> Result<Vec<u8>> {
 	let (data_ptr, data_len) = inject_input_data(instance_wrapper, &mut allocator, data)?;
 
-	let host_state = HostState::new(allocator);
+	let mut host_state = HostState::new(allocator);
+	host_state.profiler = profiler;
 
 	// Set the host state before calling into wasm.
 	instance_wrapper.store_mut().data_mut().host_state = Some(host_state);
@@ -699,6 +794,15 @@ fn perform_call(
 		"the host state is always set before calling into WASM so it can't be None here; qed",
 	);
 	*allocation_stats = Some(host_state.allocation_stats());
+	if let Some(profiler) = host_state.profiler {
+		profiler.finish();
+	}
+
+	// A trap caused by one of the deterministic limits is reported as such.
+	let ret = ret.map_err(|error| match instance_wrapper.exceeded_limit(max_stack_height) {
+		Some(limit) => Error::LimitExceeded(limit),
+		None => error,
+	});
 
 	let (output_ptr, output_len) = ret?;
 	let output = extract_output_data(instance_wrapper, output_ptr, output_len)?;
@@ -20,7 +20,7 @@ use codec::{Decode as _, Encode as _};
 use sc_executor_common::{
 	error::Error,
 	runtime_blob::RuntimeBlob,
-	wasm_runtime::{HeapAllocStrategy, WasmModule, DEFAULT_HEAP_ALLOC_STRATEGY},
+	wasm_runtime::{ExecutionLimit, HeapAllocStrategy, WasmModule, DEFAULT_HEAP_ALLOC_STRATEGY},
 };
 use sc_runtime_test::wasm_binary_unwrap;
 
@@ -69,6 +69,7 @@ struct RuntimeBuilder {
 	canonicalize_nans: bool,
 	deterministic_stack: bool,
 	heap_pages: HeapAllocStrategy,
+	max_memory_pages: Option<u32>,
 	precompile_runtime: bool,
 	tmpdir: Option<tempfile::TempDir>,
 }
@@ -81,6 +82,7 @@ impl RuntimeBuilder {
 			canonicalize_nans: false,
 			deterministic_stack: false,
 			heap_pages: DEFAULT_HEAP_ALLOC_STRATEGY,
+			max_memory_pages: None,
 			precompile_runtime: false,
 			tmpdir: None,
 		}
@@ -111,6 +113,11 @@ impl RuntimeBuilder {
 		self
 	}
 
+	fn max_memory_pages(mut self, max_memory_pages: Option<u32>) -> Self {
+		self.max_memory_pages = max_memory_pages;
+		self
+	}
+
 	fn build(&mut self) -> impl WasmModule + '_ {
 		let blob = {
 			let wasm: Vec<u8>;
@@ -146,7 +153,9 @@ impl RuntimeBuilder {
 				wasm_bulk_memory: false,
 				wasm_reference_types: false,
 				wasm_simd: false,
+				max_memory_pages: self.max_memory_pages,
 			},
+			profiler: None,
 		};
 
 		if self.precompile_runtime {
@@ -295,10 +304,74 @@ fn test_stack_depth_reaching(instantiation_strategy: InstantiationStrategy) {
 	let mut instance = runtime.new_instance().expect("failed to instantiate a runtime");
 
 	match instance.call_export("test-many-locals", &[]).unwrap_err() {
-		Error::AbortedDueToTrap(error) => {
-			let expected = "wasm trap: wasm `unreachable` instruction executed";
-			assert_eq!(error.message, expected);
-		},
+		Error::LimitExceeded(ExecutionLimit::StackHeight(65536)) => {},
+		error => panic!("unexpected error: {:?}", error),
+	}
+}
+
+test_wasm_execution!(test_memory_limit_reaching);
+fn test_memory_limit_reaching(instantiation_strategy: InstantiationStrategy) {
+	let wat = |initial_pages: u32| {
+		format!(
+			r#"
+				(module
+					(memory $0 {initial_pages})
+					(export "memory" (memory $0))
+					(global (export "__heap_base") i32 (i32.const 0))
+					(func (export "main")
+						(param i32 i32) (result i64)
+
+						;; assert(memory.grow returns != -1)
+						(if
+							(i32.eq
+								(memory.grow
+									(i32.const 1)
+								)
+								(i32.const -1)
+							)
+							(then
+								(unreachable)
+							)
+						)
+
+						(i64.const 0)
+					)
+				)
+			"#
+		)
+	};
+	let heap_alloc_strategy = HeapAllocStrategy::Dynamic { maximum_pages: None };
+
+	let mut builder = RuntimeBuilder::new(instantiation_strategy)
+		.use_wat(wat(1))
+		.heap_alloc_strategy(heap_alloc_strategy)
+		.max_memory_pages(Some(2));
+	let runtime = builder.build();
+	let mut instance = runtime.new_instance().unwrap();
+	instance.call_export("main", &[]).unwrap();
+
+	// The instance is recreated for every call, so growing again from the initial size succeeds.
+	instance.call_export("main", &[]).unwrap();
+
+	let mut builder = RuntimeBuilder::new(instantiation_strategy)
+		.use_wat(wat(2))
+		.heap_alloc_strategy(heap_alloc_strategy)
+		.max_memory_pages(Some(2));
+	let runtime = builder.build();
+	let mut instance = runtime.new_instance().unwrap();
+	match instance.call_export("main", &[]).unwrap_err() {
+		Error::LimitExceeded(ExecutionLimit::MemoryPages(2)) => {},
+		error => panic!("unexpected error: {:?}", error),
+	}
+
+	let mut builder = RuntimeBuilder::new(instantiation_strategy)
+		.use_wat(wat(3))
+		.heap_alloc_strategy(heap_alloc_strategy)
+		.max_memory_pages(Some(2));
+	let runtime = builder.build();
+	let mut instance = runtime.new_instance().unwrap();
+	match instance.call_export("main", &[]).unwrap_err() {
+		Error::LimitExceeded(ExecutionLimit::MemoryPages(2)) => {},
 		error => panic!("unexpected error: {:?}", error),
 	}
 }
@@ -473,7 +546,9 @@ fn test_instances_without_reuse_are_not_leaked() {
 				wasm_bulk_memory: false,
 				wasm_reference_types: false,
 				wasm_simd: false,
+				max_memory_pages: None,
 			},
+			profiler: None,
 		},
 	)
 	.unwrap();
@@ -78,6 +78,7 @@ where
 			out_peers: 1,
 			reserved_nodes: vec![],
 			non_reserved_mode: NonReservedPeerMode::Accept,
+			bandwidth: Default::default(),
 		},
 		NotificationMetrics::new(None),
 		network_config.peer_store_handle(),
@@ -34,6 +34,10 @@ pub enum SyncMode {
 	},
 	/// Warp sync - verify authority set transitions and the latest state.
 	Warp,
+	/// Light client - warp sync, then download the headers and justifications of new blocks only.
+	///
+	/// The state is fetched from peers on demand.
+	Light,
 }
 
 impl SyncMode {
@@ -46,6 +50,11 @@ impl SyncMode {
 	pub fn light_state(&self) -> bool {
 		matches!(self, Self::LightState { .. })
 	}
+
+	/// Returns `true` if `self` is [`Self::Light`].
+	pub fn is_light(&self) -> bool {
+		matches!(self, Self::Light)
+	}
 }
 
 impl Default for SyncMode {
@@ -47,6 +47,11 @@ const LOG_TARGET: &str = "light-client-request-handler";
 /// handling in production systems, this value is chosen to match the block request limit.
 const MAX_LIGHT_REQUEST_QUEUE: usize = 20;
 
+/// Size of the range proofs at which the handler stops adding entries.
+///
+/// The actual proof may be bigger, as it always includes at least one entry.
+const MAX_RANGE_PROOF_SIZE: usize = 2 * 1024 * 1024;
+
 /// Handler for incoming light client requests from a remote peer.
 pub struct LightClientRequestHandler<B, Client> {
 	request_receiver: async_channel::Receiver<IncomingRequest>,
@@ -155,6 +160,8 @@ where
 				self.on_remote_read_request(&peer, r)?,
 			Some(schema::v1::light::request::Request::RemoteReadChildRequest(r)) =>
 				self.on_remote_read_child_request(&peer, r)?,
+			Some(schema::v1::light::request::Request::RemoteReadRangeRequest(r)) =>
+				self.on_remote_read_range_request(&peer, r)?,
 			None =>
 				return Err(HandleRequestError::BadRequest("Remote request without request data.")),
 		};
@@ -282,6 +289,58 @@ where
 			response: Some(schema::v1::light::response::Response::RemoteReadResponse(response)),
 		})
 	}
+
+	fn on_remote_read_range_request(
+		&mut self,
+		peer: &PeerId,
+		request: &schema::v1::light::RemoteReadRangeRequest,
+	) -> Result<schema::v1::light::Response, HandleRequestError> {
+		trace!(
+			"Remote read range request from {} ({} {} at {:?}).",
+			peer,
+			fmt_keys(request.prefix.as_ref(), request.prefix.as_ref()),
+			fmt_keys(request.start_at.as_ref(), request.start_at.as_ref()),
+			request.block,
+		);
+
+		let block = Decode::decode(&mut request.block.as_ref())?;
+
+		let child_info = match &request.storage_key {
+			Some(storage_key) =>
+				match ChildType::from_prefixed_key(PrefixedStorageKey::new_ref(storage_key)) {
+					Some((ChildType::ParentKeyId, storage_key)) =>
+						Ok(Some(ChildInfo::new_default(storage_key))),
+					None => Err(sp_blockchain::Error::InvalidChildStorageKey),
+				},
+			None => Ok(None),
+		};
+		let response = match child_info.and_then(|child_info| {
+			self.client.read_range_proof(
+				block,
+				child_info.as_ref(),
+				request.prefix.as_deref(),
+				request.start_at.as_deref(),
+				MAX_RANGE_PROOF_SIZE,
+			)
+		}) {
+			Ok(proof) => schema::v1::light::RemoteReadResponse { proof: Some(proof.encode()) },
+			Err(error) => {
+				trace!(
+					"remote read range request from {} ({} {} at {:?}) failed with: {}",
+					peer,
+					fmt_keys(request.prefix.as_ref(), request.prefix.as_ref()),
+					fmt_keys(request.start_at.as_ref(), request.start_at.as_ref()),
+					request.block,
+					error,
+				);
+				schema::v1::light::RemoteReadResponse { proof: None }
+			},
+		};
+
+		Ok(schema::v1::light::Response {
+			response: Some(schema::v1::light::response::Response::RemoteReadResponse(response)),
+		})
+	}
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -0,0 +1,188 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Helper for outgoing light client requests.
+//!
+//! Fetch storage and execution proofs from remote peers with
+//! [`LightClientRequestSender`](sender::LightClientRequestSender), for a node running in light
+//! client mode.
+
+use crate::schema;
+use codec::{Decode, Encode};
+use futures::{future::BoxFuture, prelude::*};
+use log::{debug, trace};
+use prost::Message;
+use sc_client_api::{light::RemoteProofFetcher, StorageProof};
+use sc_network::{IfDisconnected, NetworkRequest, ProtocolName};
+use sc_network_types::PeerId;
+use sp_core::storage::PrefixedStorageKey;
+use sp_runtime::traits::Block;
+use std::{marker::PhantomData, sync::Arc};
+
+const LOG_TARGET: &str = "light-client-request-sender";
+
+/// Returns the peers to send the requests to, in the order they are tried.
+pub type PeersProvider = Arc<dyn Fn() -> BoxFuture<'static, Vec<PeerId>> + Send + Sync>;
+
+/// Sends light client requests to remote peers.
+pub struct LightClientRequestSender<B> {
+	network: Arc<dyn NetworkRequest + Send + Sync>,
+	protocol_name: ProtocolName,
+	peers: PeersProvider,
+	_block: PhantomData<B>,
+}
+
+impl<B: Block> LightClientRequestSender<B> {
+	/// Create a new [`LightClientRequestSender`].
+	pub fn new<Hash: AsRef<[u8]>>(
+		network: Arc<dyn NetworkRequest + Send + Sync>,
+		genesis_hash: Hash,
+		fork_id: Option<&str>,
+		peers: PeersProvider,
+	) -> Self {
+		Self {
+			network,
+			protocol_name: super::generate_protocol_name(
+				genesis_hash,
+				fork_id,
+				super::RANGE_REQUESTS_PROTOCOL_VERSION,
+			)
+			.into(),
+			peers,
+			_block: PhantomData,
+		}
+	}
+
+	/// Send `request` to the peers in turn, until one of them answers with a proof.
+	fn send(
+		&self,
+		request: schema::v1::light::request::Request,
+	) -> BoxFuture<'static, sp_blockchain::Result<StorageProof>> {
+		let network = self.network.clone();
+		let protocol_name = self.protocol_name.clone();
+		let peers = (self.peers)();
+		let payload = schema::v1::light::Request { request: Some(request) }.encode_to_vec();
+
+		async move {
+			for peer in peers.await {
+				let response = network
+					.request(
+						peer,
+						protocol_name.clone(),
+						payload.clone(),
+						None,
+						IfDisconnected::ImmediateError,
+					)
+					.await;
+				match response {
+					Ok((response, _)) => match decode_response(&response) {
+						Ok(proof) => {
+							trace!(target: LOG_TARGET, "Received proof from {peer}.");
+							return Ok(proof)
+						},
+						Err(e) => debug!(target: LOG_TARGET, "Invalid response from {peer}: {e}"),
+					},
+					Err(e) => debug!(target: LOG_TARGET, "Request to {peer} failed: {e}"),
+				}
+			}
+
+			Err(sp_blockchain::Error::RemoteFetchFailed)
+		}
+		.boxed()
+	}
+}
+
+impl<B: Block> RemoteProofFetcher<B> for LightClientRequestSender<B> {
+	fn read_proof(
+		&self,
+		block: B::Hash,
+		keys: Vec<Vec<u8>>,
+	) -> BoxFuture<'static, sp_blockchain::Result<StorageProof>> {
+		self.send(schema::v1::light::request::Request::RemoteReadRequest(
+			schema::v1::light::RemoteReadRequest { block: block.encode(), keys },
+		))
+	}
+
+	fn read_child_proof(
+		&self,
+		block: B::Hash,
+		storage_key: PrefixedStorageKey,
+		keys: Vec<Vec<u8>>,
+	) -> BoxFuture<'static, sp_blockchain::Result<StorageProof>> {
+		self.send(schema::v1::light::request::Request::RemoteReadChildRequest(
+			schema::v1::light::RemoteReadChildRequest {
+				block: block.encode(),
+				storage_key: storage_key.into_inner(),
+				keys,
+			},
+		))
+	}
+
+	fn read_range_proof(
+		&self,
+		block: B::Hash,
+		storage_key: Option<PrefixedStorageKey>,
+		prefix: Option<Vec<u8>>,
+		start_at: Option<Vec<u8>>,
+	) -> BoxFuture<'static, sp_blockchain::Result<StorageProof>> {
+		self.send(schema::v1::light::request::Request::RemoteReadRangeRequest(
+			schema::v1::light::RemoteReadRangeRequest {
+				block: block.encode(),
+				storage_key: storage_key.map(PrefixedStorageKey::into_inner),
+				prefix,
+				start_at,
+			},
+		))
+	}
+
+	fn execution_proof(
+		&self,
+		block: B::Hash,
+		method: String,
+		call_data: Vec<u8>,
+	) -> BoxFuture<'static, sp_blockchain::Result<StorageProof>> {
+		self.send(schema::v1::light::request::Request::RemoteCallRequest(
+			schema::v1::light::RemoteCallRequest { block: block.encode(), method, data: call_data },
+		))
+	}
+}
+
+/// Extract the proof from an encoded response.
+fn decode_response(response: &[u8]) -> Result<StorageProof, ResponseError> {
+	let response = schema::v1::light::Response::decode(response)?;
+	let proof = match response.response {
+		Some(schema::v1::light::response::Response::RemoteCallResponse(r)) => r.proof,
+		Some(schema::v1::light::response::Response::RemoteReadResponse(r)) => r.proof,
+		None => None,
+	};
+	let proof = proof.ok_or(ResponseError::MissingProof)?;
+
+	Ok(StorageProof::decode(&mut proof.as_ref())?)
+}
+
+#[derive(Debug, thiserror::Error)]
+enum ResponseError {
+	#[error("Failed to decode response: {0}.")]
+	DecodeProto(#[from] prost::DecodeError),
+	/// The remote couldn't answer, for example because the block is pruned.
+	#[error("missing proof")]
+	MissingProof,
+	/// Decoding of the proof failed.
+	#[error("codec error: {0}")]
+	Codec(#[from] codec::Error),
+}
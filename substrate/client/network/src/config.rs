@@ -41,7 +41,11 @@ use sc_network_types::{
 	PeerId,
 };
 
-use crate::service::{ensure_addresses_consistent_with_transport, traits::NetworkBackend};
+use crate::{
+	peer_store::PEER_STORE_FILE,
+	protocol::notification_service_with_bandwidth,
+	service::{ensure_addresses_consistent_with_transport, traits::NetworkBackend},
+};
 use codec::Encode;
 use prometheus_endpoint::Registry;
 use zeroize::Zeroize;
@@ -441,6 +445,9 @@ pub struct SetConfig {
 	/// Whether nodes that aren't in [`SetConfig::reserved_nodes`] are accepted or automatically
 	/// refused.
 	pub non_reserved_mode: NonReservedPeerMode,
+
+	/// Bandwidth limits of the notification protocol of this set.
+	pub bandwidth: NotificationBandwidth,
 }
 
 impl Default for SetConfig {
@@ -450,7 +457,115 @@ impl Default for SetConfig {
 			out_peers: 75,
 			reserved_nodes: Vec::new(),
 			non_reserved_mode: NonReservedPeerMode::Accept,
+			bandwidth: NotificationBandwidth::default(),
+		}
+	}
+}
+
+/// Priority of a notification protocol when the node-wide notification bandwidth, configured
+/// with [`NetworkConfiguration::notification_bandwidth`], is contended.
+///
+/// Protocols of lower priority stop being served while the node-wide budget is running low, which
+/// leaves the rest of it to the protocols of higher priority.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NotificationPriority {
+	/// Served only while at least half of the node-wide budget is left.
+	Low,
+
+	/// Served only while at least a quarter of the node-wide budget is left.
+	#[default]
+	Normal,
+
+	/// Served as long as there is any node-wide budget left.
+	High,
+}
+
+/// Bandwidth configuration of a notification protocol.
+///
+/// Synchronous notifications exceeding the outbound limits are dropped, while asynchronous
+/// notifications are delayed until there is enough bandwidth. Notifications received from a peer
+/// exceeding [`NotificationBandwidth::inbound_peer`] are dropped and the peer is penalised.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NotificationBandwidth {
+	/// Priority of the protocol.
+	pub priority: NotificationPriority,
+
+	/// Maximum rate, in bytes per second, of the notifications sent to all peers.
+	///
+	/// `None` means that the protocol is not limited.
+	pub protocol: Option<u64>,
+
+	/// Maximum rate, in bytes per second, of the notifications sent to each peer.
+	///
+	/// `None` means that the peers are not limited.
+	pub peer: Option<u64>,
+
+	/// Maximum rate, in bytes per second, of the notifications received from each peer.
+	///
+	/// `None` means that the received notifications are not limited.
+	pub inbound_peer: Option<u64>,
+}
+
+/// Bandwidth limits of the notification protocols whose name ends with
+/// [`ProtocolBandwidthLimits::name`], replacing the limits they were registered with.
+///
+/// Parsed from `<NAME>=<PROTOCOL_BYTES>[:<PEER_BYTES>[:<INBOUND_PEER_BYTES>]]`, where an empty
+/// number of bytes leaves that limit unset, e.g. `transactions/1=1000000:100000` or
+/// `statement/1=:50000:200000`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtocolBandwidthLimits {
+	/// End of the protocol name.
+	pub name: String,
+
+	/// Maximum rate, in bytes per second, of the notifications sent to all peers.
+	pub protocol: Option<u64>,
+
+	/// Maximum rate, in bytes per second, of the notifications sent to each peer.
+	pub peer: Option<u64>,
+
+	/// Maximum rate, in bytes per second, of the notifications received from each peer.
+	pub inbound_peer: Option<u64>,
+}
+
+impl ProtocolBandwidthLimits {
+	/// Check if the limits apply to `protocol`.
+	pub fn matches(&self, protocol: &str) -> bool {
+		protocol.ends_with(&self.name)
+	}
+}
+
+impl FromStr for ProtocolBandwidthLimits {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		const FORMAT: &str = "<NAME>=<PROTOCOL_BYTES>[:<PEER_BYTES>[:<INBOUND_PEER_BYTES>]]";
+
+		let parse_limit = |limit: &str| match limit {
+			"" => Ok(None),
+			limit => limit
+				.parse::<u64>()
+				.map(Some)
+				.map_err(|error| format!("Invalid number of bytes `{limit}`: {error}")),
+		};
+
+		let (name, limits) =
+			s.split_once('=').ok_or_else(|| format!("Expected `{FORMAT}`, got `{s}`"))?;
+		if name.is_empty() {
+			return Err(format!("Missing protocol name in `{s}`"))
+		}
+		let mut limits = limits.split(':');
+		let (protocol, peer, inbound_peer) =
+			(limits.next().unwrap_or(""), limits.next().unwrap_or(""), limits.next().unwrap_or(""));
+		if limits.next().is_some() {
+			return Err(format!("Too many limits in `{s}`"))
 		}
+
+		Ok(Self {
+			name: name.to_string(),
+			protocol: parse_limit(protocol)?,
+			peer: parse_limit(peer)?,
+			inbound_peer: parse_limit(inbound_peer)?,
+		})
 	}
 }
 
@@ -509,7 +624,7 @@ impl NonDefaultSetConfig {
 		set_config: SetConfig,
 	) -> (Self, Box<dyn NotificationService>) {
 		let (protocol_handle_pair, notification_service) =
-			notification_service(protocol_name.clone());
+			notification_service_with_bandwidth(protocol_name.clone(), set_config.bandwidth);
 		(
 			Self {
 				protocol_name,
@@ -626,6 +741,10 @@ pub struct NetworkConfiguration {
 	/// Maximum number of blocks per request.
 	pub max_blocks_per_request: u32,
 
+	/// Maximum number of transaction validations triggered by a single peer that are pending at
+	/// any moment.
+	pub max_pending_transactions_per_peer: usize,
+
 	/// Initial syncing mode.
 	pub sync_mode: SyncMode,
 
@@ -673,6 +792,15 @@ pub struct NetworkConfiguration {
 
 	/// Networking backend used for P2P communication.
 	pub network_backend: NetworkBackendType,
+
+	/// Node-wide maximum rate, in bytes per second, of the notifications sent by all protocols.
+	///
+	/// See [`NotificationPriority`] for how the budget is shared between protocols.
+	pub notification_bandwidth: Option<u64>,
+
+	/// Bandwidth limits of individual notification protocols, replacing the limits the
+	/// protocols were registered with.
+	pub notification_protocol_bandwidth: Vec<ProtocolBandwidthLimits>,
 }
 
 impl NetworkConfiguration {
@@ -697,6 +825,7 @@ impl NetworkConfiguration {
 			transport: TransportConfig::Normal { enable_mdns: false, allow_private_ip: true },
 			max_parallel_downloads: 5,
 			max_blocks_per_request: 64,
+			max_pending_transactions_per_peer: 512,
 			sync_mode: SyncMode::Full,
 			enable_dht_random_walk: true,
 			allow_non_globals_in_dht: false,
@@ -706,6 +835,8 @@ impl NetworkConfiguration {
 			yamux_window_size: None,
 			ipfs_server: false,
 			network_backend: NetworkBackendType::Libp2p,
+			notification_bandwidth: None,
+			notification_protocol_bandwidth: Vec::new(),
 		}
 	}
 
@@ -799,7 +930,9 @@ impl<B: BlockT + 'static, H: ExHashT, N: NetworkBackend<B, H>> FullNetworkConfig
 	/// Create new [`FullNetworkConfiguration`].
 	pub fn new(network_config: &NetworkConfiguration, metrics_registry: Option<Registry>) -> Self {
 		let bootnodes = network_config.boot_nodes.iter().map(|bootnode| bootnode.peer_id).collect();
-		let peer_store = N::peer_store(bootnodes, metrics_registry.clone());
+		let persistence_path =
+			network_config.net_config_path.as_ref().map(|path| path.join(PEER_STORE_FILE));
+		let peer_store = N::peer_store(bootnodes, metrics_registry.clone(), persistence_path);
 		let peer_store_handle = peer_store.handle();
 
 		Self {
@@ -130,7 +130,8 @@
 //! - **`/<protocol-id>/light/2`** is a request-response protocol (see below) that lets one perform
 //! light-client-related requests for information about the state. Each request is the encoding of
 //! a `light::Request` and each response is the encoding of a `light::Response`, as defined in the
-//! `light.v1.proto` file in this source tree.
+//! `light.v1.proto` file in this source tree. Version 3 of this protocol, named after the genesis
+//! hash, additionally accepts range read requests.
 //! - **`/<protocol-id>/transactions/1`** is a notifications protocol (see below) where
 //! transactions are pushed to other nodes. The handshake is empty on both sides. The message
 //! format is a SCALE-encoded list of transactions, where each transaction is an opaque list of
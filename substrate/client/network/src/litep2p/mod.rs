@@ -41,6 +41,7 @@ use crate::{
 	peer_store::PeerStoreProvider,
 	protocol,
 	service::{
+		bandwidth::NodeBandwidth,
 		metrics::{register_without_sources, MetricSources, Metrics, NotificationMetrics},
 		out_events,
 		traits::{BandwidthSink, NetworkBackend, NetworkService},
@@ -87,6 +88,7 @@ use std::{
 	fs,
 	future::Future,
 	iter,
+	path::PathBuf,
 	pin::Pin,
 	sync::{
 		atomic::{AtomicUsize, Ordering},
@@ -456,6 +458,13 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 		// pass the protocol configuration to `Litep2pConfigBuilder` and save the TX channel
 		// to the protocol's `Peerset` together with the protocol name to allow other subsystems
 		// of Polkadot SDK to control connectivity of the notification protocol
+		let notification_bandwidth = NodeBandwidth::new(
+			network_config.notification_bandwidth,
+			network_config.notification_protocol_bandwidth.clone(),
+			Some(Arc::clone(&peer_store_handle)),
+		);
+		params.block_announce_config.set_node_bandwidth(notification_bandwidth.clone());
+
 		let block_announce_protocol = params.block_announce_config.protocol_name().clone();
 		let mut notif_protocols = HashMap::from_iter([(
 			params.block_announce_config.protocol_name().clone(),
@@ -467,6 +476,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 			.into_iter()
 			.fold(config_builder, |config_builder, mut config| {
 				config.config.set_handshake(Roles::from(&params.role).encode());
+				config.set_node_bandwidth(notification_bandwidth.clone());
 				notif_protocols.insert(config.protocol_name, config.handle);
 
 				config_builder.with_notification_protocol(config.config)
@@ -639,8 +649,13 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkBackend<B, H> for Litep2pNetworkBac
 	fn peer_store(
 		bootnodes: Vec<sc_network_types::PeerId>,
 		metrics_registry: Option<Registry>,
+		persistence_path: Option<PathBuf>,
 	) -> Self::PeerStore {
-		Peerstore::new(bootnodes, metrics_registry)
+		let peerstore = Peerstore::new(bootnodes, metrics_registry);
+		match persistence_path {
+			Some(path) => peerstore.with_persistence(path),
+			None => peerstore,
+		}
 	}
 
 	fn register_notification_metrics(registry: Option<&Registry>) -> NotificationMetrics {
@@ -22,7 +22,7 @@
 //! such as their addresses, reputations, supported protocols etc.
 
 use crate::{
-	peer_store::{PeerStoreProvider, ProtocolHandle},
+	peer_store::{PeerStorePersistence, PeerStoreProvider, ProtocolHandle, PERSIST_INTERVAL},
 	service::{metrics::PeerStoreMetrics, traits::PeerStore},
 	ObservedRole, ReputationChange,
 };
@@ -35,6 +35,7 @@ use sc_network_types::PeerId;
 
 use std::{
 	collections::{HashMap, HashSet},
+	path::PathBuf,
 	sync::Arc,
 	time::{Duration, Instant},
 };
@@ -118,8 +119,23 @@ impl PeerInfo {
 #[derive(Debug, Default)]
 pub struct PeerstoreHandleInner {
 	peers: HashMap<PeerId, PeerInfo>,
+	/// Peers banned manually.
+	banned: HashSet<PeerId>,
 	protocols: Vec<Arc<dyn ProtocolHandle>>,
 	metrics: Option<PeerStoreMetrics>,
+	/// Persistence of the reputations and the manual bans.
+	persistence: Option<PeerStorePersistence>,
+}
+
+impl PeerstoreHandleInner {
+	fn persist(&self) {
+		if let Some(persistence) = &self.persistence {
+			persistence.persist(
+				self.peers.iter().map(|(peer, info)| (*peer, info.reputation)),
+				self.banned.iter().copied(),
+			);
+		}
+	}
 }
 
 #[derive(Debug, Clone, Default)]
@@ -132,7 +148,13 @@ impl PeerstoreHandle {
 		protocols: Vec<Arc<dyn ProtocolHandle>>,
 		metrics: Option<PeerStoreMetrics>,
 	) -> Self {
-		Self(Arc::new(Mutex::new(PeerstoreHandleInner { peers, protocols, metrics })))
+		Self(Arc::new(Mutex::new(PeerstoreHandleInner {
+			peers,
+			banned: HashSet::new(),
+			protocols,
+			metrics,
+			persistence: None,
+		})))
 	}
 
 	/// Add known peer to [`Peerstore`].
@@ -161,9 +183,11 @@ impl PeerstoreHandle {
 
 		// Retain only entries with non-zero reputation values or not expired ones.
 		let now = Instant::now();
-		let mut num_banned_peers = 0;
-		lock.peers.retain(|_, info| {
-			if info.is_banned() {
+		let lock = &mut *lock;
+		let mut num_banned_peers = lock.banned.len() as u64;
+		let banned = &lock.banned;
+		lock.peers.retain(|peer, info| {
+			if info.is_banned() && !banned.contains(peer) {
 				num_banned_peers += 1;
 			}
 			info.reputation != 0 || info.last_updated + FORGET_AFTER > now
@@ -178,7 +202,8 @@ impl PeerstoreHandle {
 
 impl PeerStoreProvider for PeerstoreHandle {
 	fn is_banned(&self, peer: &PeerId) -> bool {
-		self.0.lock().peers.get(peer).map_or(false, |info| info.is_banned())
+		let lock = self.0.lock();
+		lock.banned.contains(peer) || lock.peers.get(peer).map_or(false, |info| info.is_banned())
 	}
 
 	/// Register a protocol handle to disconnect peers whose reputation drops below the threshold.
@@ -275,7 +300,8 @@ impl PeerStoreProvider for PeerstoreHandle {
 			.peers
 			.iter()
 			.filter_map(|(peer, info)| {
-				(!ignored.contains(&peer) && !info.is_banned()).then_some((*peer, info.reputation))
+				(!ignored.contains(&peer) && !info.is_banned() && !handle.banned.contains(peer))
+					.then_some((*peer, info.reputation))
 			})
 			.collect::<Vec<(PeerId, _)>>();
 		candidates.sort_by(|(_, a), (_, b)| b.cmp(a));
@@ -290,6 +316,35 @@ impl PeerStoreProvider for PeerstoreHandle {
 	fn add_known_peer(&self, peer: PeerId) {
 		self.0.lock().peers.entry(peer).or_default().last_updated = Instant::now();
 	}
+
+	/// Ban the peer until it is unbanned, regardless of its reputation.
+	fn ban_peer(&self, peer: PeerId) {
+		let mut lock = self.0.lock();
+		if !lock.banned.insert(peer) {
+			return
+		}
+
+		log::info!(target: LOG_TARGET, "Peer {peer} is banned manually, disconnecting.");
+		lock.protocols.iter().for_each(|handle| handle.disconnect_peer(peer));
+		lock.persist();
+	}
+
+	/// Lift the ban of a peer banned with [`PeerStoreProvider::ban_peer`].
+	fn unban_peer(&self, peer: &PeerId) -> bool {
+		let mut lock = self.0.lock();
+		if !lock.banned.remove(peer) {
+			return false
+		}
+
+		log::info!(target: LOG_TARGET, "Peer {peer} is unbanned manually.");
+		lock.persist();
+		true
+	}
+
+	/// Get the peers banned with [`PeerStoreProvider::ban_peer`].
+	fn banned_peers(&self) -> Vec<PeerId> {
+		self.0.lock().banned.iter().copied().collect()
+	}
 }
 
 /// `Peerstore` handle for testing.
@@ -329,6 +384,28 @@ impl Peerstore {
 		Self { peerstore_handle }
 	}
 
+	/// Restore the reputations and the manual bans persisted to `path`, and keep persisting them
+	/// there.
+	///
+	/// The restored reputations are decayed by the time elapsed since they were persisted.
+	pub fn with_persistence(self, path: PathBuf) -> Self {
+		let (persistence, persisted) = PeerStorePersistence::open(path);
+		{
+			let mut lock = self.peerstore_handle.0.lock();
+			if let Some(persisted) = persisted {
+				let elapsed = persisted.elapsed_secs();
+				for (peer, reputation) in persisted.reputations() {
+					let info = lock.peers.entry(peer).or_default();
+					info.reputation = reputation;
+					info.decay_reputation(elapsed);
+				}
+				lock.banned.extend(persisted.banned());
+			}
+			lock.persistence = Some(persistence);
+		}
+		self
+	}
+
 	/// Get mutable reference to the underlying [`PeerstoreHandle`].
 	pub fn handle(&mut self) -> &mut PeerstoreHandle {
 		&mut self.peerstore_handle
@@ -340,9 +417,12 @@ impl Peerstore {
 	}
 
 	/// Start [`Peerstore`] event loop.
+	///
+	/// The reputations are persisted every [`PERSIST_INTERVAL`], if enabled.
 	async fn run(self) {
 		let started = Instant::now();
 		let mut latest_time_update = started;
+		let mut latest_persist = started;
 
 		loop {
 			let now = Instant::now();
@@ -356,6 +436,10 @@ impl Peerstore {
 			};
 
 			self.peerstore_handle.progress_time(seconds_passed);
+			if now - latest_persist >= PERSIST_INTERVAL {
+				latest_persist = now;
+				self.peerstore_handle.0.lock().persist();
+			}
 			let _ = Delay::new(Duration::from_secs(1)).await;
 		}
 	}
@@ -377,6 +461,7 @@ impl PeerStore for Peerstore {
 #[cfg(test)]
 mod tests {
 	use super::{PeerInfo, PeerStoreProvider, Peerstore};
+	use crate::peer_store::PEER_STORE_FILE;
 
 	#[test]
 	fn decaying_zero_reputation_yields_zero() {
@@ -478,4 +563,29 @@ mod tests {
 		assert_eq!(metrics.num_discovered.get(), 3);
 		assert_eq!(metrics.num_banned_peers.get(), 2);
 	}
+
+	#[test]
+	fn reputations_and_bans_are_persisted() {
+		let peer_a = sc_network_types::PeerId::random();
+		let peer_b = sc_network_types::PeerId::random();
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join(PEER_STORE_FILE);
+
+		let mut peerstore = Peerstore::new(Vec::new(), None).with_persistence(path.clone());
+		let handle = peerstore.handle();
+		handle.report_peer(
+			peer_a,
+			sc_network_common::types::ReputationChange { value: i32::MIN, reason: "test".into() },
+		);
+		// Banning a peer persists the peer store.
+		handle.ban_peer(peer_b);
+		// Dropping the peer store waits for the pending snapshots to be written.
+		drop(peerstore);
+
+		let mut peerstore = Peerstore::new(Vec::new(), None).with_persistence(path);
+		let handle = peerstore.handle();
+		assert!(handle.is_banned(&peer_a));
+		assert!(handle.is_banned(&peer_b));
+		assert_eq!(handle.banned_peers(), vec![peer_b]);
+	}
 }
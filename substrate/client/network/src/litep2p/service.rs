@@ -389,6 +389,18 @@ impl NetworkPeers for Litep2pNetworkService {
 		self.peer_store_handle.peer_reputation(peer_id)
 	}
 
+	fn ban_peer(&self, peer_id: PeerId) {
+		self.peer_store_handle.ban_peer(peer_id)
+	}
+
+	fn unban_peer(&self, peer_id: &PeerId) -> bool {
+		self.peer_store_handle.unban_peer(peer_id)
+	}
+
+	fn banned_peers(&self) -> Vec<PeerId> {
+		self.peer_store_handle.banned_peers()
+	}
+
 	fn report_peer(&self, peer: PeerId, cost_benefit: ReputationChange) {
 		self.peer_store_handle.report_peer(peer, cost_benefit);
 	}
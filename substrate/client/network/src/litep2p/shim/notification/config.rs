@@ -25,7 +25,11 @@ use crate::{
 		NotificationProtocol,
 	},
 	peer_store::PeerStoreProvider,
-	service::{metrics::NotificationMetrics, traits::NotificationConfig},
+	service::{
+		bandwidth::{NodeBandwidth, ProtocolBandwidth},
+		metrics::NotificationMetrics,
+		traits::NotificationConfig,
+	},
 	NotificationService, ProtocolName,
 };
 
@@ -73,6 +77,9 @@ pub struct NotificationProtocolConfig {
 
 	/// Handle for controlling the notification protocol.
 	pub handle: ProtocolControlHandle,
+
+	/// Bandwidth limiter of the protocol.
+	bandwidth: Arc<ProtocolBandwidth>,
 }
 
 impl NotificationProtocolConfig {
@@ -114,7 +121,16 @@ impl NotificationProtocolConfig {
 		// initialize the actual object implementing `NotificationService` and combine the
 		// `litep2p::NotificationHandle` with `Peerset` to implement a full and independent
 		// notification protocol runner
-		let protocol = NotificationProtocol::new(protocol_name.clone(), handle, peerset, metrics);
+		let bandwidth = ProtocolBandwidth::new(protocol_name.clone(), set_config.bandwidth);
+		bandwidth.set_metrics(metrics.clone());
+
+		let protocol = NotificationProtocol::new(
+			protocol_name.clone(),
+			handle,
+			peerset,
+			metrics,
+			bandwidth.clone(),
+		);
 
 		(
 			Self {
@@ -123,6 +139,7 @@ impl NotificationProtocolConfig {
 				set_config,
 				config,
 				handle: ProtocolControlHandle::new(peerset_tx, connected_peers),
+				bandwidth,
 			},
 			Box::new(protocol),
 		)
@@ -154,6 +171,11 @@ impl NotificationProtocolConfig {
 	pub fn max_notification_size(&self) -> usize {
 		self.max_notification_size
 	}
+
+	/// Set the node-wide notification bandwidth the protocol draws from.
+	pub(crate) fn set_node_bandwidth(&self, node: Arc<NodeBandwidth>) {
+		self.bandwidth.set_node_bandwidth(node);
+	}
 }
 
 impl NotificationConfig for NotificationProtocolConfig {
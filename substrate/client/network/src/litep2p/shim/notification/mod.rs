@@ -23,6 +23,7 @@ use crate::{
 	error::Error,
 	litep2p::shim::notification::peerset::{OpenResult, Peerset, PeersetNotificationCommand},
 	service::{
+		bandwidth::ProtocolBandwidth,
 		metrics::NotificationMetrics,
 		traits::{NotificationEvent as SubstrateNotificationEvent, ValidationResult},
 	},
@@ -38,7 +39,7 @@ use tokio::sync::oneshot;
 
 use sc_network_types::PeerId;
 
-use std::{collections::HashSet, fmt};
+use std::{collections::HashSet, fmt, sync::Arc};
 
 pub mod config;
 pub mod peerset;
@@ -62,6 +63,9 @@ pub struct Litep2pMessageSink {
 
 	/// Notification metrics.
 	metrics: NotificationMetrics,
+
+	/// Bandwidth limiter of the protocol.
+	bandwidth: Arc<ProtocolBandwidth>,
 }
 
 impl Litep2pMessageSink {
@@ -71,8 +75,9 @@ impl Litep2pMessageSink {
 		protocol: ProtocolName,
 		sink: NotificationSink,
 		metrics: NotificationMetrics,
+		bandwidth: Arc<ProtocolBandwidth>,
 	) -> Self {
-		Self { protocol, peer, sink, metrics }
+		Self { protocol, peer, sink, metrics, bandwidth }
 	}
 }
 
@@ -82,6 +87,10 @@ impl MessageSink for Litep2pMessageSink {
 	fn send_sync_notification(&self, notification: Vec<u8>) {
 		let size = notification.len();
 
+		if !self.bandwidth.try_send(&self.peer, size) {
+			return
+		}
+
 		match self.sink.send_sync_notification(notification) {
 			Ok(_) => self.metrics.register_notification_sent(&self.protocol, size),
 			Err(error) => log::trace!(
@@ -99,6 +108,7 @@ impl MessageSink for Litep2pMessageSink {
 	/// Returns an error if the peer does not exist.
 	async fn send_async_notification(&self, notification: Vec<u8>) -> Result<(), Error> {
 		let size = notification.len();
+		self.bandwidth.wait_send(&self.peer, size).await;
 
 		match self.sink.send_async_notification(notification).await {
 			Ok(_) => {
@@ -142,6 +152,9 @@ pub struct NotificationProtocol {
 
 	/// Notification metrics.
 	metrics: NotificationMetrics,
+
+	/// Bandwidth limiter of the protocol.
+	bandwidth: Arc<ProtocolBandwidth>,
 }
 
 impl fmt::Debug for NotificationProtocol {
@@ -160,12 +173,14 @@ impl NotificationProtocol {
 		handle: NotificationHandle,
 		peerset: Peerset,
 		metrics: NotificationMetrics,
+		bandwidth: Arc<ProtocolBandwidth>,
 	) -> Self {
 		Self {
 			protocol,
 			handle,
 			peerset,
 			metrics,
+			bandwidth,
 			pending_cancels: HashSet::new(),
 			pending_validations: FuturesUnordered::new(),
 		}
@@ -201,6 +216,10 @@ impl NotificationService for NotificationProtocol {
 	fn send_sync_notification(&mut self, peer: &PeerId, notification: Vec<u8>) {
 		let size = notification.len();
 
+		if !self.bandwidth.try_send(peer, size) {
+			return
+		}
+
 		if let Ok(_) = self.handle.send_sync_notification(peer.into(), notification) {
 			self.metrics.register_notification_sent(&self.protocol, size);
 		}
@@ -212,6 +231,7 @@ impl NotificationService for NotificationProtocol {
 		notification: Vec<u8>,
 	) -> Result<(), Error> {
 		let size = notification.len();
+		self.bandwidth.wait_send(peer, size).await;
 
 		match self.handle.send_async_notification(peer.into(), notification).await {
 			Ok(_) => {
@@ -258,6 +278,7 @@ impl NotificationService for NotificationProtocol {
 				self.protocol.clone(),
 				sink,
 				self.metrics.clone(),
+				self.bandwidth.clone(),
 			));
 			sink
 		})
@@ -322,6 +343,7 @@ impl NotificationService for NotificationProtocol {
 
 						self.metrics.register_substream_closed(&self.protocol);
 						self.peerset.report_substream_closed(peer.into());
+						self.bandwidth.remove_peer(&peer.into());
 
 						if self.pending_cancels.remove(&peer) {
 							log::debug!(
@@ -347,6 +369,10 @@ impl NotificationService for NotificationProtocol {
 					} => {
 						self.metrics.register_notification_received(&self.protocol, notification.len());
 
+						if !self.bandwidth.try_receive(&peer.into(), notification.len()) {
+							continue
+						}
+
 						if !self.pending_cancels.contains(&peer) {
 							return Some(SubstrateNotificationEvent::NotificationReceived {
 								peer: peer.into(),
@@ -18,6 +18,9 @@
 
 //! [`PeerStore`] manages peer reputations and provides connection candidates to
 //! [`crate::protocol_controller::ProtocolController`].
+//!
+//! The reputations and the manual bans can be persisted to disk with
+//! [`PeerStore::with_persistence`], so that known bad peers stay banned across restarts.
 
 use crate::service::{metrics::PeerStoreMetrics, traits::PeerStore as PeerStoreT};
 
@@ -27,12 +30,16 @@ use parking_lot::Mutex;
 use partial_sort::PartialSort;
 use prometheus_endpoint::Registry;
 use sc_network_common::{role::ObservedRole, types::ReputationChange};
+use serde::{Deserialize, Serialize};
 use std::{
 	cmp::{Ord, Ordering, PartialOrd},
 	collections::{hash_map::Entry, HashMap, HashSet},
 	fmt::Debug,
-	sync::Arc,
-	time::{Duration, Instant},
+	fs,
+	path::{Path, PathBuf},
+	sync::{mpsc, Arc},
+	thread,
+	time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use wasm_timer::Delay;
 
@@ -59,6 +66,10 @@ const INVERSE_DECREMENT: i32 = 200;
 /// Amount of time between the moment we last updated the [`PeerStore`] entry and the moment we
 /// remove it, once the reputation value reaches 0.
 const FORGET_AFTER: Duration = Duration::from_secs(3600);
+/// Name of the file in the network configuration directory the peer store is persisted to.
+pub const PEER_STORE_FILE: &str = "peer_store.json";
+/// Interval between two writes of the peer store to disk.
+pub(crate) const PERSIST_INTERVAL: Duration = Duration::from_secs(60);
 
 /// Trait describing the required functionality from a `Peerset` handle.
 pub trait ProtocolHandle: Debug + Send + Sync {
@@ -98,6 +109,164 @@ pub trait PeerStoreProvider: Debug + Send + Sync {
 
 	/// Add known peer.
 	fn add_known_peer(&self, peer_id: sc_network_types::PeerId);
+
+	/// Ban the peer until it is unbanned, regardless of its reputation.
+	///
+	/// The default implementation ignores the ban.
+	fn ban_peer(&self, _peer_id: sc_network_types::PeerId) {}
+
+	/// Lift the ban of a peer banned with [`PeerStoreProvider::ban_peer`].
+	///
+	/// Returns `false` if the peer wasn't banned. The peer stays banned if its reputation is below
+	/// the threshold.
+	fn unban_peer(&self, _peer_id: &sc_network_types::PeerId) -> bool {
+		false
+	}
+
+	/// Get the peers banned with [`PeerStoreProvider::ban_peer`].
+	fn banned_peers(&self) -> Vec<sc_network_types::PeerId> {
+		Vec::new()
+	}
+}
+
+/// Reputations and manual bans of a peer store, as persisted to disk.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PersistedPeers {
+	/// Unix time in seconds at which the peer store was persisted.
+	saved_at: u64,
+	/// Non-zero reputations of the peers.
+	reputations: Vec<(String, i32)>,
+	/// Manually banned peers.
+	banned: Vec<String>,
+}
+
+impl PersistedPeers {
+	/// Create a snapshot of the given reputations and manual bans.
+	pub(crate) fn new(
+		reputations: impl Iterator<Item = (sc_network_types::PeerId, i32)>,
+		banned: impl Iterator<Item = sc_network_types::PeerId>,
+	) -> Self {
+		Self {
+			saved_at: unix_time(),
+			reputations: reputations
+				.filter(|(_, reputation)| *reputation != 0)
+				.map(|(peer_id, reputation)| (peer_id.to_base58(), reputation))
+				.collect(),
+			banned: banned.map(|peer_id| peer_id.to_base58()).collect(),
+		}
+	}
+
+	/// Load the peers persisted at `path`, if any.
+	pub(crate) fn load(path: &Path) -> Option<Self> {
+		let file = match fs::File::open(path) {
+			Ok(file) => file,
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+			Err(e) => {
+				log::warn!(target: LOG_TARGET, "Failed to open {}: {e}", path.display());
+				return None
+			},
+		};
+		serde_json::from_reader(std::io::BufReader::new(file))
+			.map_err(
+				|e| log::warn!(target: LOG_TARGET, "Failed to load peers from {}: {e}", path.display()),
+			)
+			.ok()
+	}
+
+	/// Write the peers to `path`, replacing the previous file atomically.
+	pub(crate) fn save(&self, path: &Path) {
+		let tmp_path = path.with_extension("tmp");
+		let result = serde_json::to_vec(self)
+			.map_err(std::io::Error::from)
+			.and_then(|encoded| fs::write(&tmp_path, encoded))
+			.and_then(|()| fs::rename(&tmp_path, path));
+		if let Err(e) = result {
+			log::warn!(target: LOG_TARGET, "Failed to persist peers to {}: {e}", path.display());
+		}
+	}
+
+	/// Number of seconds elapsed since the peers were persisted.
+	pub(crate) fn elapsed_secs(&self) -> u64 {
+		unix_time().saturating_sub(self.saved_at)
+	}
+
+	/// Persisted reputations, skipping invalid peer IDs.
+	pub(crate) fn reputations(&self) -> impl Iterator<Item = (sc_network_types::PeerId, i32)> + '_ {
+		self.reputations
+			.iter()
+			.filter_map(|(peer_id, reputation)| Some((peer_id.parse().ok()?, *reputation)))
+	}
+
+	/// Persisted manual bans, skipping invalid peer IDs.
+	pub(crate) fn banned(&self) -> impl Iterator<Item = sc_network_types::PeerId> + '_ {
+		self.banned.iter().filter_map(|peer_id| peer_id.parse().ok())
+	}
+}
+
+/// Writes snapshots of the reputations and the manual bans of a peer store to disk.
+///
+/// The snapshots are taken under the lock of the peer store and written by a dedicated thread, so
+/// that the lock is never held during file I/O. The pending snapshots are written before the
+/// persistence is dropped.
+#[derive(Debug)]
+pub(crate) struct PeerStorePersistence {
+	sender: Option<mpsc::Sender<PersistedPeers>>,
+	writer: Option<thread::JoinHandle<()>>,
+}
+
+impl PeerStorePersistence {
+	/// Start persisting snapshots to `path`.
+	///
+	/// Returns the peers previously persisted there, if any.
+	pub(crate) fn open(path: PathBuf) -> (Self, Option<PersistedPeers>) {
+		let persisted = PersistedPeers::load(&path);
+		let (sender, receiver) = mpsc::channel::<PersistedPeers>();
+		let writer = thread::Builder::new()
+			.name("peer-store-persistence".into())
+			.spawn(move || {
+				while let Ok(mut snapshot) = receiver.recv() {
+					// Only the latest snapshot needs to be written.
+					while let Ok(latest) = receiver.try_recv() {
+						snapshot = latest;
+					}
+					snapshot.save(&path);
+				}
+			})
+			.map_err(
+				|e| log::warn!(target: LOG_TARGET, "Failed to spawn the peer store writer: {e}"),
+			)
+			.ok();
+
+		(Self { sender: Some(sender), writer }, persisted)
+	}
+
+	/// Persist a snapshot of the given reputations and manual bans in the background.
+	pub(crate) fn persist(
+		&self,
+		reputations: impl Iterator<Item = (sc_network_types::PeerId, i32)>,
+		banned: impl Iterator<Item = sc_network_types::PeerId>,
+	) {
+		if let Some(sender) = &self.sender {
+			let _ = sender.send(PersistedPeers::new(reputations, banned));
+		}
+	}
+}
+
+impl Drop for PeerStorePersistence {
+	fn drop(&mut self) {
+		// Closing the channel stops the writer once the pending snapshots are written.
+		self.sender = None;
+		if let Some(writer) = self.writer.take() {
+			let _ = writer.join();
+		}
+	}
+}
+
+fn unix_time() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map_or(0, |elapsed| elapsed.as_secs())
 }
 
 /// Actual implementation of peer reputations and connection candidates provider.
@@ -154,6 +323,18 @@ impl PeerStoreProvider for PeerStoreHandle {
 	fn add_known_peer(&self, peer_id: sc_network_types::PeerId) {
 		self.inner.lock().add_known_peer(peer_id.into());
 	}
+
+	fn ban_peer(&self, peer_id: sc_network_types::PeerId) {
+		self.inner.lock().ban_peer(peer_id.into());
+	}
+
+	fn unban_peer(&self, peer_id: &sc_network_types::PeerId) -> bool {
+		self.inner.lock().unban_peer(&peer_id.into())
+	}
+
+	fn banned_peers(&self) -> Vec<sc_network_types::PeerId> {
+		self.inner.lock().banned.iter().map(|peer_id| peer_id.into()).collect()
+	}
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -232,13 +413,47 @@ impl PeerInfo {
 #[derive(Debug)]
 struct PeerStoreInner {
 	peers: HashMap<PeerId, PeerInfo>,
+	/// Peers banned manually.
+	banned: HashSet<PeerId>,
 	protocols: Vec<Arc<dyn ProtocolHandle>>,
 	metrics: Option<PeerStoreMetrics>,
+	/// Persistence of the reputations and the manual bans.
+	persistence: Option<PeerStorePersistence>,
 }
 
 impl PeerStoreInner {
 	fn is_banned(&self, peer_id: &PeerId) -> bool {
-		self.peers.get(peer_id).map_or(false, |info| info.is_banned())
+		self.banned.contains(peer_id) ||
+			self.peers.get(peer_id).map_or(false, |info| info.is_banned())
+	}
+
+	fn ban_peer(&mut self, peer_id: PeerId) {
+		if !self.banned.insert(peer_id) {
+			return
+		}
+
+		log::info!(target: LOG_TARGET, "Peer {peer_id} is banned manually, disconnecting.");
+		self.protocols.iter().for_each(|handle| handle.disconnect_peer(peer_id.into()));
+		self.persist();
+	}
+
+	fn unban_peer(&mut self, peer_id: &PeerId) -> bool {
+		if !self.banned.remove(peer_id) {
+			return false
+		}
+
+		log::info!(target: LOG_TARGET, "Peer {peer_id} is unbanned manually.");
+		self.persist();
+		true
+	}
+
+	fn persist(&self) {
+		if let Some(persistence) = &self.persistence {
+			persistence.persist(
+				self.peers.iter().map(|(peer_id, info)| ((*peer_id).into(), info.reputation)),
+				self.banned.iter().map(|peer_id| (*peer_id).into()),
+			);
+		}
 	}
 
 	fn register_protocol(&mut self, protocol_handle: Arc<dyn ProtocolHandle>) {
@@ -342,7 +557,8 @@ impl PeerStoreInner {
 			.peers
 			.iter()
 			.filter_map(|(peer_id, info)| {
-				(!info.is_banned() && !ignored.contains(peer_id)).then_some((*peer_id, *info))
+				(!info.is_banned() && !self.banned.contains(peer_id) && !ignored.contains(peer_id))
+					.then_some((*peer_id, *info))
 			})
 			.collect::<Vec<_>>();
 		let count = std::cmp::min(count, candidates.len());
@@ -364,9 +580,10 @@ impl PeerStoreInner {
 
 		// Retain only entries with non-zero reputation values or not expired ones.
 		let now = Instant::now();
-		let mut num_banned_peers: u64 = 0;
-		self.peers.retain(|_, info| {
-			if info.is_banned() {
+		let mut num_banned_peers = self.banned.len() as u64;
+		let banned = &self.banned;
+		self.peers.retain(|peer_id, info| {
+			if info.is_banned() && !banned.contains(peer_id) {
 				num_banned_peers += 1;
 			}
 
@@ -422,21 +639,55 @@ impl PeerStore {
 					.into_iter()
 					.map(|peer_id| (peer_id, PeerInfo::default()))
 					.collect(),
+				banned: HashSet::new(),
 				protocols: Vec::new(),
 				metrics,
+				persistence: None,
 			})),
 		}
 	}
 
+	/// Restore the reputations and the manual bans persisted to `path`, and keep persisting them
+	/// there.
+	///
+	/// The restored reputations are decayed by the time elapsed since they were persisted.
+	pub fn with_persistence(self, path: PathBuf) -> Self {
+		let (persistence, persisted) = PeerStorePersistence::open(path.clone());
+		{
+			let mut inner = self.inner.lock();
+			if let Some(persisted) = persisted {
+				let elapsed = persisted.elapsed_secs();
+				for (peer_id, reputation) in persisted.reputations() {
+					let info = inner.peers.entry(peer_id.into()).or_default();
+					info.reputation = reputation;
+					info.decay_reputation(elapsed);
+				}
+				inner.banned.extend(persisted.banned().map(PeerId::from));
+				log::debug!(
+					target: LOG_TARGET,
+					"Restored {} reputations and {} bans from {}",
+					persisted.reputations.len(),
+					inner.banned.len(),
+					path.display(),
+				);
+			}
+			inner.persistence = Some(persistence);
+		}
+		self
+	}
+
 	/// Get `PeerStoreHandle`.
 	pub fn handle(&self) -> PeerStoreHandle {
 		PeerStoreHandle { inner: self.inner.clone() }
 	}
 
 	/// Drive the `PeerStore`, decaying reputation values over time and removing expired entries.
+	///
+	/// The reputations are persisted every [`PERSIST_INTERVAL`], if enabled.
 	pub async fn run(self) {
 		let started = Instant::now();
 		let mut latest_time_update = started;
+		let mut latest_persist = started;
 
 		loop {
 			let now = Instant::now();
@@ -449,7 +700,15 @@ impl PeerStore {
 				elapsed_now.as_secs() - elapsed_latest.as_secs()
 			};
 
-			self.inner.lock().progress_time(seconds_passed);
+			{
+				let mut inner = self.inner.lock();
+				inner.progress_time(seconds_passed);
+				if now - latest_persist >= PERSIST_INTERVAL {
+					latest_persist = now;
+					inner.persist();
+				}
+			}
+
 			let _ = Delay::new(Duration::from_secs(1)).await;
 		}
 	}
@@ -468,7 +727,10 @@ impl PeerStoreT for PeerStore {
 
 #[cfg(test)]
 mod tests {
-	use super::{PeerInfo, PeerStore, PeerStoreProvider};
+	use super::{
+		PeerInfo, PeerStore, PeerStorePersistence, PeerStoreProvider, BANNED_THRESHOLD,
+		PEER_STORE_FILE,
+	};
 
 	#[test]
 	fn decaying_zero_reputation_yields_zero() {
@@ -570,4 +832,56 @@ mod tests {
 		assert_eq!(metrics.num_discovered.get(), 3);
 		assert_eq!(metrics.num_banned_peers.get(), 2);
 	}
+
+	#[test]
+	fn reputations_and_bans_are_persisted() {
+		let peer_a = sc_network_types::PeerId::random();
+		let peer_b = sc_network_types::PeerId::random();
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join(PEER_STORE_FILE);
+
+		let peerstore = PeerStore::new(Vec::new(), None).with_persistence(path.clone());
+		let handle = peerstore.handle();
+		handle.report_peer(
+			peer_a,
+			sc_network_common::types::ReputationChange { value: i32::MIN, reason: "test".into() },
+		);
+		// Banning a peer persists the peer store.
+		handle.ban_peer(peer_b);
+		assert!(handle.is_banned(&peer_a));
+		assert!(handle.is_banned(&peer_b));
+		// Dropping the peer store waits for the pending snapshots to be written.
+		drop(handle);
+		drop(peerstore);
+
+		let peerstore = PeerStore::new(Vec::new(), None).with_persistence(path);
+		let handle = peerstore.handle();
+		assert!(handle.peer_reputation(&peer_a) < BANNED_THRESHOLD);
+		assert!(handle.is_banned(&peer_b));
+		assert_eq!(handle.banned_peers(), vec![peer_b]);
+
+		assert!(handle.unban_peer(&peer_b));
+		assert!(!handle.unban_peer(&peer_b));
+		assert!(!handle.is_banned(&peer_b));
+		assert!(handle.banned_peers().is_empty());
+	}
+
+	#[test]
+	fn persistence_writes_the_latest_snapshot() {
+		let peer_a = sc_network_types::PeerId::random();
+		let peer_b = sc_network_types::PeerId::random();
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join(PEER_STORE_FILE);
+
+		let (persistence, persisted) = PeerStorePersistence::open(path.clone());
+		assert!(persisted.is_none());
+		persistence.persist([(peer_a, -10), (peer_b, 0)].into_iter(), std::iter::empty());
+		persistence.persist([(peer_a, -20)].into_iter(), [peer_b].into_iter());
+		drop(persistence);
+
+		let (_persistence, persisted) = PeerStorePersistence::open(path);
+		let persisted = persisted.unwrap();
+		assert_eq!(persisted.reputations().collect::<Vec<_>>(), vec![(peer_a, -20)]);
+		assert_eq!(persisted.banned().collect::<Vec<_>>(), vec![peer_b]);
+	}
 }
@@ -20,7 +20,7 @@ use crate::{
 	config, error,
 	peer_store::PeerStoreProvider,
 	protocol_controller::{self, SetId},
-	service::{metrics::NotificationMetrics, traits::Direction},
+	service::{bandwidth::NodeBandwidth, metrics::NotificationMetrics, traits::Direction},
 	types::ProtocolName,
 	MAX_RESPONSE_SIZE,
 };
@@ -45,7 +45,7 @@ use std::{collections::HashSet, iter, sync::Arc, task::Poll};
 
 use notifications::{Notifications, NotificationsOut};
 
-pub(crate) use notifications::ProtocolHandle;
+pub(crate) use notifications::{notification_service_with_bandwidth, ProtocolHandle};
 
 pub use notifications::{notification_service, NotificationsSink, ProtocolHandlePair, Ready};
 
@@ -79,6 +79,7 @@ impl<B: BlockT> Protocol<B> {
 	pub(crate) fn new(
 		roles: Roles,
 		notification_metrics: NotificationMetrics,
+		notification_bandwidth: Arc<NodeBandwidth>,
 		notification_protocols: Vec<config::NonDefaultSetConfig>,
 		block_announces_protocol: config::NonDefaultSetConfig,
 		peer_store_handle: Arc<dyn PeerStoreProvider>,
@@ -122,6 +123,7 @@ impl<B: BlockT> Protocol<B> {
 
 			handles.iter_mut().for_each(|handle| {
 				handle.set_metrics(notification_metrics.clone());
+				handle.set_node_bandwidth(notification_bandwidth.clone());
 			});
 
 			(
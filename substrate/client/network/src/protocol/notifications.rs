@@ -25,7 +25,7 @@ pub use self::{
 	service::{notification_service, ProtocolHandlePair},
 };
 
-pub(crate) use self::service::ProtocolHandle;
+pub(crate) use self::service::{notification_service_with_bandwidth, ProtocolHandle};
 
 mod behaviour;
 mod handler;
@@ -19,9 +19,11 @@
 //! Notification service implementation.
 
 use crate::{
+	config::NotificationBandwidth,
 	error,
 	protocol::notifications::handler::NotificationsSink,
 	service::{
+		bandwidth::{NodeBandwidth, ProtocolBandwidth},
 		metrics::NotificationMetrics,
 		traits::{
 			Direction, MessageSink, NotificationEvent, NotificationService, ValidationResult,
@@ -58,10 +60,11 @@ const COMMAND_QUEUE_SIZE: usize = 64;
 type Subscribers = Arc<Mutex<Vec<TracingUnboundedSender<InnerNotificationEvent>>>>;
 
 /// Type representing a distributable message sink.
-/// Detached message sink must carry the protocol name for registering metrics.
+/// Detached message sink must carry the protocol name for registering metrics and the bandwidth
+/// limiter of the protocol.
 ///
 /// See documentation for [`PeerContext`] for more details.
-type NotificationSink = Arc<Mutex<(NotificationsSink, ProtocolName)>>;
+type NotificationSink = Arc<Mutex<(NotificationsSink, ProtocolName, Arc<ProtocolBandwidth>)>>;
 
 #[async_trait::async_trait]
 impl MessageSink for NotificationSink {
@@ -69,6 +72,11 @@ impl MessageSink for NotificationSink {
 	fn send_sync_notification(&self, notification: Vec<u8>) {
 		let sink = self.lock();
 
+		let peer: sc_network_types::PeerId = (*sink.0.peer_id()).into();
+		if !sink.2.try_send(&peer, notification.len()) {
+			return
+		}
+
 		metrics::register_notification_sent(sink.0.metrics(), &sink.1, notification.len());
 		sink.0.send_sync_notification(notification);
 	}
@@ -83,6 +91,8 @@ impl MessageSink for NotificationSink {
 		// method is also used by `NetworkService` when sending notifications.
 		let notification_len = notification.len();
 		let sink = self.lock().clone();
+		sink.2.wait_send(&(*sink.0.peer_id()).into(), notification_len).await;
+
 		let permit = sink
 			.0
 			.reserve_notification()
@@ -205,6 +215,9 @@ pub struct NotificationHandle {
 
 	/// Connected peers.
 	peers: HashMap<PeerId, PeerContext>,
+
+	/// Bandwidth limiter of the protocol.
+	bandwidth: Arc<ProtocolBandwidth>,
 }
 
 impl NotificationHandle {
@@ -214,8 +227,9 @@ impl NotificationHandle {
 		tx: mpsc::Sender<NotificationCommand>,
 		rx: TracingUnboundedReceiver<InnerNotificationEvent>,
 		subscribers: Arc<Mutex<Vec<TracingUnboundedSender<InnerNotificationEvent>>>>,
+		bandwidth: Arc<ProtocolBandwidth>,
 	) -> Self {
-		Self { protocol, tx, rx, subscribers, peers: HashMap::new() }
+		Self { protocol, tx, rx, subscribers, peers: HashMap::new(), bandwidth }
 	}
 }
 
@@ -234,6 +248,10 @@ impl NotificationService for NotificationHandle {
 	/// Send synchronous `notification` to `peer`.
 	fn send_sync_notification(&mut self, peer: &sc_network_types::PeerId, notification: Vec<u8>) {
 		if let Some(info) = self.peers.get(&((*peer).into())) {
+			if !self.bandwidth.try_send(peer, notification.len()) {
+				return
+			}
+
 			metrics::register_notification_sent(
 				info.sink.metrics(),
 				&self.protocol,
@@ -257,6 +275,8 @@ impl NotificationService for NotificationHandle {
 			.ok_or_else(|| error::Error::PeerDoesntExist((*peer).into()))?
 			.sink;
 
+		self.bandwidth.wait_send(peer, notification_len).await;
+
 		sink.reserve_notification()
 			.await
 			.map_err(|_| error::Error::ConnectionClosed)?
@@ -308,7 +328,11 @@ impl NotificationService for NotificationHandle {
 						peer,
 						PeerContext {
 							sink: sink.clone(),
-							shared_sink: Arc::new(Mutex::new((sink, self.protocol.clone()))),
+							shared_sink: Arc::new(Mutex::new((
+								sink,
+								self.protocol.clone(),
+								self.bandwidth.clone(),
+							))),
 						},
 					);
 					return Some(NotificationEvent::NotificationStreamOpened {
@@ -335,7 +359,8 @@ impl NotificationService for NotificationHandle {
 						),
 						Some(context) => {
 							context.sink = sink.clone();
-							*context.shared_sink.lock() = (sink.clone(), self.protocol.clone());
+							*context.shared_sink.lock() =
+								(sink.clone(), self.protocol.clone(), self.bandwidth.clone());
 						},
 					}
 				},
@@ -356,6 +381,7 @@ impl NotificationService for NotificationHandle {
 			rx: event_rx,
 			peers: self.peers.clone(),
 			subscribers: self.subscribers.clone(),
+			bandwidth: self.bandwidth.clone(),
 		}))
 	}
 
@@ -384,6 +410,9 @@ pub struct ProtocolHandlePair {
 
 	// Receiver for notification commands received from the protocol implementation.
 	rx: mpsc::Receiver<NotificationCommand>,
+
+	/// Bandwidth limiter of the protocol.
+	bandwidth: Arc<ProtocolBandwidth>,
 }
 
 impl ProtocolHandlePair {
@@ -392,8 +421,9 @@ impl ProtocolHandlePair {
 		protocol: ProtocolName,
 		subscribers: Subscribers,
 		rx: mpsc::Receiver<NotificationCommand>,
+		bandwidth: Arc<ProtocolBandwidth>,
 	) -> Self {
-		Self { protocol, subscribers, rx }
+		Self { protocol, subscribers, rx, bandwidth }
 	}
 
 	/// Consume `self` and split [`ProtocolHandlePair`] into a handle which allows it to send events
@@ -402,7 +432,7 @@ impl ProtocolHandlePair {
 		self,
 	) -> (ProtocolHandle, Box<dyn Stream<Item = NotificationCommand> + Send + Unpin>) {
 		(
-			ProtocolHandle::new(self.protocol, self.subscribers),
+			ProtocolHandle::new(self.protocol, self.subscribers, self.bandwidth),
 			Box::new(ReceiverStream::new(self.rx)),
 		)
 	}
@@ -426,6 +456,9 @@ pub(crate) struct ProtocolHandle {
 
 	/// Prometheus metrics.
 	metrics: Option<NotificationMetrics>,
+
+	/// Bandwidth limiter of the protocol.
+	bandwidth: Arc<ProtocolBandwidth>,
 }
 
 pub(crate) enum ValidationCallResult {
@@ -435,15 +468,32 @@ pub(crate) enum ValidationCallResult {
 
 impl ProtocolHandle {
 	/// Create new [`ProtocolHandle`].
-	fn new(protocol: ProtocolName, subscribers: Subscribers) -> Self {
-		Self { protocol, subscribers, num_peers: 0usize, metrics: None, delegate_to_peerset: false }
+	fn new(
+		protocol: ProtocolName,
+		subscribers: Subscribers,
+		bandwidth: Arc<ProtocolBandwidth>,
+	) -> Self {
+		Self {
+			protocol,
+			subscribers,
+			num_peers: 0usize,
+			metrics: None,
+			delegate_to_peerset: false,
+			bandwidth,
+		}
 	}
 
 	/// Set metrics.
 	pub fn set_metrics(&mut self, metrics: NotificationMetrics) {
+		self.bandwidth.set_metrics(metrics.clone());
 		self.metrics = Some(metrics);
 	}
 
+	/// Set the node-wide notification bandwidth the protocol draws from.
+	pub fn set_node_bandwidth(&mut self, node: Arc<NodeBandwidth>) {
+		self.bandwidth.set_node_bandwidth(node);
+	}
+
 	/// Delegate validation to `Peerset`.
 	///
 	/// Protocols that do not do any validation themselves and only rely on `Peerset` handling
@@ -557,6 +607,7 @@ impl ProtocolHandle {
 	/// Substream was closed.
 	pub fn report_substream_closed(&mut self, peer: PeerId) -> Result<(), ()> {
 		metrics::register_substream_closed(&self.metrics, &self.protocol);
+		self.bandwidth.remove_peer(&peer.into());
 
 		let mut subscribers = self.subscribers.lock();
 		log::trace!(target: LOG_TARGET, "{}: substream closed for {peer:?}", self.protocol);
@@ -579,6 +630,10 @@ impl ProtocolHandle {
 	) -> Result<(), ()> {
 		metrics::register_notification_received(&self.metrics, &self.protocol, notification.len());
 
+		if !self.bandwidth.try_receive(&peer.into(), notification.len()) {
+			return Ok(())
+		}
+
 		let mut subscribers = self.subscribers.lock();
 		log::trace!(target: LOG_TARGET, "{}: notification received from {peer:?}", self.protocol);
 
@@ -631,16 +686,32 @@ impl ProtocolHandle {
 /// Handle pair allows `Notifications` and the protocol to communicate with each other directly.
 pub fn notification_service(
 	protocol: ProtocolName,
+) -> (ProtocolHandlePair, Box<dyn NotificationService>) {
+	notification_service_with_bandwidth(protocol, NotificationBandwidth::default())
+}
+
+/// Create new (protocol, notification) handle pair whose outbound traffic is limited by
+/// `bandwidth`.
+pub(crate) fn notification_service_with_bandwidth(
+	protocol: ProtocolName,
+	bandwidth: NotificationBandwidth,
 ) -> (ProtocolHandlePair, Box<dyn NotificationService>) {
 	let (cmd_tx, cmd_rx) = mpsc::channel(COMMAND_QUEUE_SIZE);
 
 	let (event_tx, event_rx) =
 		tracing_unbounded(metric_label_for_protocol(&protocol).leak(), 100_000);
 	let subscribers = Arc::new(Mutex::new(vec![event_tx]));
+	let bandwidth = ProtocolBandwidth::new(protocol.clone(), bandwidth);
 
 	(
-		ProtocolHandlePair::new(protocol.clone(), subscribers.clone(), cmd_rx),
-		Box::new(NotificationHandle::new(protocol.clone(), cmd_tx, event_rx, subscribers)),
+		ProtocolHandlePair::new(protocol.clone(), subscribers.clone(), cmd_rx, bandwidth.clone()),
+		Box::new(NotificationHandle::new(
+			protocol.clone(),
+			cmd_tx,
+			event_rx,
+			subscribers,
+			bandwidth,
+		)),
 	)
 }
 
@@ -879,6 +879,9 @@ mod tests {
 			fn peer_role(&self, peer_id: &sc_network_types::PeerId) -> Option<ObservedRole>;
 			fn outgoing_candidates(&self, count: usize, ignored: HashSet<sc_network_types::PeerId>) -> Vec<sc_network_types::PeerId>;
 			fn add_known_peer(&self, peer_id: sc_network_types::PeerId);
+			fn ban_peer(&self, peer_id: sc_network_types::PeerId);
+			fn unban_peer(&self, peer_id: &sc_network_types::PeerId) -> bool;
+			fn banned_peers(&self) -> Vec<sc_network_types::PeerId>;
 		}
 	}
 
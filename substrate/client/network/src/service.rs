@@ -58,6 +58,7 @@ use crate::{
 	NotificationService, ReputationChange,
 };
 
+use bandwidth::NodeBandwidth;
 use codec::DecodeAll;
 use futures::{channel::oneshot, prelude::*};
 use libp2p::{
@@ -95,6 +96,7 @@ use std::{
 	fs, iter,
 	marker::PhantomData,
 	num::NonZeroUsize,
+	path::PathBuf,
 	pin::Pin,
 	str,
 	sync::{
@@ -104,6 +106,7 @@ use std::{
 	time::{Duration, Instant},
 };
 
+pub(crate) mod bandwidth;
 pub(crate) mod metrics;
 pub(crate) mod out_events;
 
@@ -186,8 +189,14 @@ where
 	fn peer_store(
 		bootnodes: Vec<sc_network_types::PeerId>,
 		metrics_registry: Option<Registry>,
+		persistence_path: Option<PathBuf>,
 	) -> Self::PeerStore {
-		PeerStore::new(bootnodes.into_iter().map(From::from).collect(), metrics_registry)
+		let peer_store =
+			PeerStore::new(bootnodes.into_iter().map(From::from).collect(), metrics_registry);
+		match persistence_path {
+			Some(path) => peer_store.with_persistence(path),
+			None => peer_store,
+		}
 	}
 
 	fn register_notification_metrics(registry: Option<&Registry>) -> NotificationMetrics {
@@ -456,6 +465,11 @@ where
 		let (protocol, notif_protocol_handles) = Protocol::new(
 			From::from(&params.role),
 			params.notification_metrics,
+			NodeBandwidth::new(
+				network_config.notification_bandwidth,
+				network_config.notification_protocol_bandwidth.clone(),
+				Some(Arc::clone(&peer_store_handle)),
+			),
 			notification_protocols,
 			params.block_announce_config,
 			Arc::clone(&peer_store_handle),
@@ -1013,6 +1027,18 @@ where
 		self.peer_store_handle.peer_reputation(peer_id)
 	}
 
+	fn ban_peer(&self, peer_id: sc_network_types::PeerId) {
+		self.peer_store_handle.ban_peer(peer_id)
+	}
+
+	fn unban_peer(&self, peer_id: &sc_network_types::PeerId) -> bool {
+		self.peer_store_handle.unban_peer(peer_id)
+	}
+
+	fn banned_peers(&self) -> Vec<sc_network_types::PeerId> {
+		self.peer_store_handle.banned_peers()
+	}
+
 	fn disconnect_peer(&self, peer_id: sc_network_types::PeerId, protocol: ProtocolName) {
 		let _ = self
 			.to_worker
@@ -0,0 +1,460 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Byte-rate limits of notification protocols.
+//!
+//! Each notification protocol owns a [`ProtocolBandwidth`], shared by all handles of the protocol,
+//! which tracks a token bucket for the protocol as a whole and one for each connected peer. If a
+//! node-wide limit is configured, all protocols additionally draw from the same
+//! [`NodeBandwidth`], where they are served according to their [`NotificationPriority`].
+//!
+//! Inbound notifications have already used the bandwidth by the time they are received, so they
+//! are only accounted per peer: notifications received from a peer over its budget are dropped
+//! and the peer is penalised, so that it eventually gets disconnected.
+
+use crate::{
+	config::{NotificationBandwidth, NotificationPriority, ProtocolBandwidthLimits},
+	peer_store::PeerStoreProvider,
+	service::metrics::NotificationMetrics,
+	types::ProtocolName,
+	ReputationChange,
+};
+
+use parking_lot::Mutex;
+use sc_network_types::PeerId;
+
+use std::{
+	collections::HashMap,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+/// Logging target for the file.
+const LOG_TARGET: &str = "sub-libp2p";
+
+/// Reputation change of a peer sending notifications over its inbound budget.
+const INBOUND_BANDWIDTH_EXCEEDED: ReputationChange =
+	ReputationChange::new(-(1 << 10), "Notification bandwidth exceeded");
+
+/// Fraction of the node-wide budget which must be left for a protocol of `priority` to be served.
+fn reserve(priority: NotificationPriority) -> f64 {
+	match priority {
+		NotificationPriority::Low => 0.5,
+		NotificationPriority::Normal => 0.25,
+		NotificationPriority::High => 0.0,
+	}
+}
+
+/// Token bucket refilled at the configured rate and holding at most one second worth of traffic.
+///
+/// The bucket is allowed to go into debt so that notifications larger than the rate can still be
+/// transferred, after which the traffic is held back until the debt is paid off.
+#[derive(Debug)]
+struct TokenBucket {
+	/// Rate, in bytes per second.
+	rate: f64,
+
+	/// Available bytes.
+	tokens: f64,
+
+	/// When the bucket was last refilled.
+	updated: Instant,
+}
+
+impl TokenBucket {
+	/// Create new full [`TokenBucket`].
+	fn new(rate: u64, now: Instant) -> Self {
+		let rate = rate.max(1) as f64;
+
+		Self { rate, tokens: rate, updated: now }
+	}
+
+	/// Refill the bucket with the bytes accumulated since the last refill.
+	fn refill(&mut self, now: Instant) {
+		let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+
+		self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+		self.updated = now;
+	}
+
+	/// Get the time until the bucket holds more than `reserve` of its capacity, or `None` if it
+	/// already does.
+	fn delay(&self, reserve: f64) -> Option<Duration> {
+		let threshold = reserve * self.rate;
+
+		(self.tokens <= threshold)
+			.then(|| Duration::from_secs_f64((threshold - self.tokens) / self.rate))
+			.map(|delay| delay.max(Duration::from_millis(1)))
+	}
+}
+
+/// Node-wide notification bandwidth, shared by all notification protocols.
+#[derive(Debug)]
+pub(crate) struct NodeBandwidth {
+	/// Node-wide bucket, if limited.
+	bucket: Option<Mutex<TokenBucket>>,
+
+	/// Limits of individual protocols configured by the operator.
+	protocols: Vec<ProtocolBandwidthLimits>,
+
+	/// Peer store handle, used to penalise peers sending over their inbound budget.
+	peer_store: Option<Arc<dyn PeerStoreProvider>>,
+}
+
+impl NodeBandwidth {
+	/// Create new [`NodeBandwidth`].
+	pub fn new(
+		limit: Option<u64>,
+		protocols: Vec<ProtocolBandwidthLimits>,
+		peer_store: Option<Arc<dyn PeerStoreProvider>>,
+	) -> Arc<Self> {
+		let bucket = limit.map(|rate| Mutex::new(TokenBucket::new(rate, Instant::now())));
+
+		Arc::new(Self { bucket, protocols, peer_store })
+	}
+}
+
+/// Mutable state of [`ProtocolBandwidth`].
+#[derive(Debug)]
+struct Inner {
+	/// Bandwidth configuration of the protocol.
+	config: NotificationBandwidth,
+
+	/// Bucket of the protocol, if limited.
+	protocol: Option<TokenBucket>,
+
+	/// Buckets of the connected peers, if limited.
+	peers: HashMap<PeerId, TokenBucket>,
+
+	/// Buckets of the notifications received from the connected peers, if limited.
+	inbound_peers: HashMap<PeerId, TokenBucket>,
+
+	/// Node-wide bandwidth, if set.
+	node: Option<Arc<NodeBandwidth>>,
+
+	/// Notification metrics, if set.
+	metrics: Option<NotificationMetrics>,
+}
+
+/// Bandwidth limiter of a notification protocol.
+#[derive(Debug)]
+pub(crate) struct ProtocolBandwidth {
+	/// Protocol name.
+	protocol: ProtocolName,
+
+	/// Token buckets.
+	inner: Mutex<Inner>,
+}
+
+impl ProtocolBandwidth {
+	/// Create new [`ProtocolBandwidth`].
+	pub fn new(protocol: ProtocolName, config: NotificationBandwidth) -> Arc<Self> {
+		let inner = Inner {
+			config,
+			protocol: config.protocol.map(|rate| TokenBucket::new(rate, Instant::now())),
+			peers: HashMap::new(),
+			inbound_peers: HashMap::new(),
+			node: None,
+			metrics: None,
+		};
+
+		Arc::new(Self { protocol, inner: Mutex::new(inner) })
+	}
+
+	/// Set the node-wide bandwidth the protocol draws from.
+	///
+	/// The limits configured for the protocol by the operator, if any, replace the ones the
+	/// protocol was registered with.
+	pub fn set_node_bandwidth(&self, node: Arc<NodeBandwidth>) {
+		let mut inner = self.inner.lock();
+
+		if let Some(limits) =
+			node.protocols.iter().rev().find(|limits| limits.matches(&self.protocol))
+		{
+			log::debug!(
+				target: LOG_TARGET,
+				"{}: bandwidth limited to {:?} bytes/s per protocol, {:?} bytes/s per peer and \
+				 {:?} bytes/s received per peer",
+				self.protocol,
+				limits.protocol,
+				limits.peer,
+				limits.inbound_peer,
+			);
+
+			inner.config.protocol = limits.protocol;
+			inner.config.peer = limits.peer;
+			inner.config.inbound_peer = limits.inbound_peer;
+			inner.protocol = limits.protocol.map(|rate| TokenBucket::new(rate, Instant::now()));
+			inner.peers.clear();
+			inner.inbound_peers.clear();
+		}
+		inner.node = Some(node);
+	}
+
+	/// Set metrics.
+	pub fn set_metrics(&self, metrics: NotificationMetrics) {
+		self.inner.lock().metrics = Some(metrics);
+	}
+
+	/// Forget the buckets of a disconnected `peer`.
+	pub fn remove_peer(&self, peer: &PeerId) {
+		let mut inner = self.inner.lock();
+		inner.peers.remove(peer);
+		inner.inbound_peers.remove(peer);
+	}
+
+	/// Account `size` bytes sent to `peer` if the limits allow it.
+	///
+	/// Otherwise the notification is registered as dropped and `false` is returned.
+	pub fn try_send(&self, peer: &PeerId, size: usize) -> bool {
+		if self.try_consume(peer, size).is_ok() {
+			return true
+		}
+
+		log::trace!(
+			target: LOG_TARGET,
+			"{}: dropping {size} bytes to {peer:?} over bandwidth limits",
+			self.protocol,
+		);
+
+		if let Some(metrics) = &self.inner.lock().metrics {
+			metrics.register_notification_dropped("out", &self.protocol, size);
+		}
+
+		false
+	}
+
+	/// Account `size` bytes received from `peer` if its inbound budget allows it.
+	///
+	/// Otherwise the notification is registered as dropped, the peer is penalised and `false` is
+	/// returned.
+	pub fn try_receive(&self, peer: &PeerId, size: usize) -> bool {
+		let now = Instant::now();
+		let mut inner = self.inner.lock();
+		let Inner { config, inbound_peers, node, metrics, .. } = &mut *inner;
+
+		let Some(rate) = config.inbound_peer else { return true };
+		let bucket = inbound_peers.entry(*peer).or_insert_with(|| TokenBucket::new(rate, now));
+		bucket.refill(now);
+
+		if bucket.delay(0.0).is_none() {
+			bucket.tokens -= size as f64;
+			return true
+		}
+
+		log::debug!(
+			target: LOG_TARGET,
+			"{}: dropping {size} bytes from {peer:?} over bandwidth limits",
+			self.protocol,
+		);
+
+		if let Some(metrics) = metrics {
+			metrics.register_notification_dropped("in", &self.protocol, size);
+		}
+		let peer_store = node.as_ref().and_then(|node| node.peer_store.clone());
+		drop(inner);
+
+		if let Some(peer_store) = peer_store {
+			peer_store.report_peer(*peer, INBOUND_BANDWIDTH_EXCEEDED);
+		}
+
+		false
+	}
+
+	/// Wait until the limits allow `size` bytes to be sent to `peer` and account them.
+	pub async fn wait_send(&self, peer: &PeerId, size: usize) {
+		let started = Instant::now();
+		let mut delayed = false;
+
+		while let Err(delay) = self.try_consume(peer, size) {
+			delayed = true;
+			futures_timer::Delay::new(delay).await;
+		}
+
+		if delayed {
+			if let Some(metrics) = &self.inner.lock().metrics {
+				metrics.register_notification_delayed(&self.protocol, started.elapsed());
+			}
+		}
+	}
+
+	/// Consume `size` bytes from all buckets limiting the notifications sent to `peer`.
+	///
+	/// If any of the buckets doesn't allow the transfer, nothing is consumed and the time until
+	/// all of them would is returned.
+	fn try_consume(&self, peer: &PeerId, size: usize) -> Result<(), Duration> {
+		let now = Instant::now();
+		let mut inner = self.inner.lock();
+		let Inner { config, protocol, peers, node, .. } = &mut *inner;
+
+		let peer = match config.peer {
+			Some(rate) => Some(peers.entry(*peer).or_insert_with(|| TokenBucket::new(rate, now))),
+			None => None,
+		};
+		let mut node = node.as_ref().and_then(|node| node.bucket.as_ref()).map(|node| node.lock());
+
+		let mut buckets = [
+			(protocol.as_mut(), 0.0),
+			(peer, 0.0),
+			(node.as_deref_mut(), reserve(config.priority)),
+		];
+
+		let delay = buckets
+			.iter_mut()
+			.filter_map(|(bucket, reserve)| {
+				let bucket = bucket.as_mut()?;
+				bucket.refill(now);
+				bucket.delay(*reserve)
+			})
+			.max();
+
+		if let Some(delay) = delay {
+			return Err(delay)
+		}
+
+		buckets.into_iter().filter_map(|(bucket, _)| bucket).for_each(|bucket| {
+			bucket.tokens -= size as f64;
+		});
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn protocol_limit_is_enforced() {
+		let bandwidth = ProtocolBandwidth::new(
+			"/notif/1".into(),
+			NotificationBandwidth { protocol: Some(1000), ..Default::default() },
+		);
+		let peer = PeerId::random();
+
+		// notifications larger than the rate go through if the bucket isn't in debt
+		assert!(bandwidth.try_send(&peer, 1500));
+		assert!(!bandwidth.try_send(&peer, 1));
+		assert!(!bandwidth.try_send(&PeerId::random(), 1));
+	}
+
+	#[test]
+	fn peer_limit_is_enforced() {
+		let bandwidth = ProtocolBandwidth::new(
+			"/notif/1".into(),
+			NotificationBandwidth { peer: Some(1000), ..Default::default() },
+		);
+		let peer = PeerId::random();
+
+		assert!(bandwidth.try_send(&peer, 1500));
+		assert!(!bandwidth.try_send(&peer, 1));
+		assert!(bandwidth.try_send(&PeerId::random(), 1));
+
+		// bucket of the peer is reset once it disconnects
+		bandwidth.remove_peer(&peer);
+		assert!(bandwidth.try_send(&peer, 1));
+	}
+
+	#[test]
+	fn node_budget_is_reserved_for_higher_priorities() {
+		let node = NodeBandwidth::new(Some(1000), Vec::new(), None);
+		let bandwidth = |priority| {
+			let bandwidth = ProtocolBandwidth::new(
+				"/notif/1".into(),
+				NotificationBandwidth { priority, ..Default::default() },
+			);
+			bandwidth.set_node_bandwidth(node.clone());
+			bandwidth
+		};
+		let low = bandwidth(NotificationPriority::Low);
+		let normal = bandwidth(NotificationPriority::Normal);
+		let high = bandwidth(NotificationPriority::High);
+		let peer = PeerId::random();
+
+		assert!(low.try_send(&peer, 600));
+		assert!(!low.try_send(&peer, 1));
+		assert!(normal.try_send(&peer, 200));
+		assert!(!normal.try_send(&peer, 1));
+		assert!(high.try_send(&peer, 250));
+		assert!(!high.try_send(&peer, 1));
+	}
+
+	#[test]
+	fn configured_limits_replace_protocol_limits() {
+		let node = NodeBandwidth::new(
+			None,
+			vec!["transactions/1=:1000".parse().unwrap(), "/other/1=1000".parse().unwrap()],
+			None,
+		);
+		let transactions = ProtocolBandwidth::new(
+			"/genesis/transactions/1".into(),
+			NotificationBandwidth { protocol: Some(1000), ..Default::default() },
+		);
+		let other = ProtocolBandwidth::new("/notif/1".into(), NotificationBandwidth::default());
+		transactions.set_node_bandwidth(node.clone());
+		other.set_node_bandwidth(node);
+		let peer = PeerId::random();
+
+		// the protocol limit is lifted and the peers are limited instead
+		assert!(transactions.try_send(&peer, 1500));
+		assert!(!transactions.try_send(&peer, 1));
+		assert!(transactions.try_send(&PeerId::random(), 1500));
+
+		// protocols not matching any configured limits keep their own
+		assert!(other.try_send(&peer, 1_000_000));
+		assert!(other.try_send(&peer, 1_000_000));
+	}
+
+	#[test]
+	fn inbound_peer_limit_is_enforced() {
+		let peer_store = crate::peer_store::PeerStore::new(Vec::new(), None);
+		let node = NodeBandwidth::new(None, Vec::new(), Some(Arc::new(peer_store.handle())));
+		let bandwidth = ProtocolBandwidth::new(
+			"/notif/1".into(),
+			NotificationBandwidth { inbound_peer: Some(1000), ..Default::default() },
+		);
+		bandwidth.set_node_bandwidth(node);
+		let peer = PeerId::random();
+
+		assert!(bandwidth.try_receive(&peer, 1500));
+		assert_eq!(peer_store.handle().peer_reputation(&peer), 0);
+
+		// notifications over the budget are dropped and the peer is penalised
+		assert!(!bandwidth.try_receive(&peer, 1));
+		assert_eq!(peer_store.handle().peer_reputation(&peer), INBOUND_BANDWIDTH_EXCEEDED.value);
+		assert!(bandwidth.try_receive(&PeerId::random(), 1));
+
+		// the inbound budget doesn't limit the notifications sent
+		assert!(bandwidth.try_send(&peer, 1500));
+	}
+
+	#[tokio::test]
+	async fn async_notifications_are_delayed() {
+		let bandwidth = ProtocolBandwidth::new(
+			"/notif/1".into(),
+			NotificationBandwidth { peer: Some(10_000), ..Default::default() },
+		);
+		let peer = PeerId::random();
+
+		assert!(bandwidth.try_send(&peer, 11_000));
+
+		let started = Instant::now();
+		bandwidth.wait_send(&peer, 1).await;
+		assert!(started.elapsed() >= Duration::from_millis(100));
+	}
+}
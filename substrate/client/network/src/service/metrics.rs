@@ -29,6 +29,7 @@ use std::{
 		atomic::{AtomicUsize, Ordering},
 		Arc,
 	},
+	time::Duration,
 };
 
 pub use prometheus_endpoint::{Histogram, HistogramVec};
@@ -357,6 +358,37 @@ impl NotificationMetrics {
 				.observe(size as f64);
 		}
 	}
+
+	/// Register notification dropped because of bandwidth limits to Prometheus.
+	///
+	/// `direction` is either `"in"` or `"out"`.
+	pub fn register_notification_dropped(
+		&self,
+		direction: &str,
+		protocol: &ProtocolName,
+		size: usize,
+	) {
+		if let Some(metrics) = &self.metrics {
+			metrics
+				.notifications_dropped_total
+				.with_label_values(&[direction, protocol])
+				.inc();
+			metrics
+				.notifications_dropped_bytes_total
+				.with_label_values(&[direction, protocol])
+				.inc_by(size as u64);
+		}
+	}
+
+	/// Register notification delayed because of bandwidth limits to Prometheus.
+	pub fn register_notification_delayed(&self, protocol: &ProtocolName, delay: Duration) {
+		if let Some(metrics) = &self.metrics {
+			metrics
+				.notifications_delay_seconds
+				.with_label_values(&[protocol])
+				.observe(delay.as_secs_f64());
+		}
+	}
 }
 
 /// Notification metrics.
@@ -370,6 +402,15 @@ struct InnerNotificationMetrics {
 
 	/// In/outbound notification sizes.
 	pub notifications_sizes: HistogramVec,
+
+	/// Total number of in/outbound notifications dropped because of bandwidth limits.
+	pub notifications_dropped_total: CounterVec<U64>,
+
+	/// Total size of in/outbound notifications dropped because of bandwidth limits.
+	pub notifications_dropped_bytes_total: CounterVec<U64>,
+
+	/// Delays of outbound notifications held back because of bandwidth limits.
+	pub notifications_delay_seconds: HistogramVec,
 }
 
 impl InnerNotificationMetrics {
@@ -409,6 +450,40 @@ impl InnerNotificationMetrics {
 				)?,
 				registry,
 			)?,
+			notifications_dropped_total: prometheus::register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_sub_libp2p_notifications_dropped_total",
+						"Total number of the notifications dropped because of bandwidth limits",
+					),
+					&["direction", "protocol"],
+				)?,
+				registry,
+			)?,
+			notifications_dropped_bytes_total: prometheus::register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_sub_libp2p_notifications_dropped_bytes_total",
+						"Total size of the notifications dropped because of bandwidth limits",
+					),
+					&["direction", "protocol"],
+				)?,
+				registry,
+			)?,
+			notifications_delay_seconds: prometheus::register(
+				HistogramVec::new(
+					HistogramOpts {
+						common_opts: Opts::new(
+							"substrate_sub_libp2p_notifications_delay_seconds",
+							"Delays of the notifications held back because of bandwidth limits",
+						),
+						buckets: prometheus::exponential_buckets(0.001, 4.0, 8)
+							.expect("parameters are always valid values; qed"),
+					},
+					&["protocol"],
+				)?,
+				registry,
+			)?,
 		})
 	}
 }
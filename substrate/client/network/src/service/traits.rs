@@ -47,6 +47,7 @@ use std::{
 	collections::HashSet,
 	fmt::Debug,
 	future::Future,
+	path::PathBuf,
 	pin::Pin,
 	sync::Arc,
 	time::{Duration, Instant},
@@ -138,7 +139,13 @@ pub trait NetworkBackend<B: BlockT + 'static, H: ExHashT>: Send + 'static {
 	fn network_service(&self) -> Arc<dyn NetworkService>;
 
 	/// Create [`PeerStore`].
-	fn peer_store(bootnodes: Vec<PeerId>, metrics_registry: Option<Registry>) -> Self::PeerStore;
+	///
+	/// The reputations and the manual bans are persisted to `persistence_path`, if any.
+	fn peer_store(
+		bootnodes: Vec<PeerId>,
+		metrics_registry: Option<Registry>,
+		persistence_path: Option<PathBuf>,
+	) -> Self::PeerStore;
 
 	/// Register metrics that are used by the notification protocols.
 	fn register_notification_metrics(registry: Option<&Registry>) -> NotificationMetrics;
@@ -383,6 +390,24 @@ pub trait NetworkPeers {
 	/// Get peer reputation.
 	fn peer_reputation(&self, peer_id: &PeerId) -> i32;
 
+	/// Ban a peer and disconnect from it, until it is unbanned with
+	/// [`NetworkPeers::unban_peer`].
+	///
+	/// The ban persists across restarts. The default implementation ignores the ban.
+	fn ban_peer(&self, _peer_id: PeerId) {}
+
+	/// Lift the ban of a peer banned with [`NetworkPeers::ban_peer`].
+	///
+	/// Returns `false` if the peer wasn't banned.
+	fn unban_peer(&self, _peer_id: &PeerId) -> bool {
+		false
+	}
+
+	/// Returns the peers banned with [`NetworkPeers::ban_peer`].
+	fn banned_peers(&self) -> Vec<PeerId> {
+		Vec::new()
+	}
+
 	/// Disconnect from a node as soon as possible.
 	///
 	/// This triggers the same effects as if the connection had closed itself spontaneously.
@@ -494,6 +519,18 @@ where
 		T::peer_reputation(self, peer_id)
 	}
 
+	fn ban_peer(&self, peer_id: PeerId) {
+		T::ban_peer(self, peer_id)
+	}
+
+	fn unban_peer(&self, peer_id: &PeerId) -> bool {
+		T::unban_peer(self, peer_id)
+	}
+
+	fn banned_peers(&self) -> Vec<PeerId> {
+		T::banned_peers(self)
+	}
+
 	fn disconnect_peer(&self, peer_id: PeerId, protocol: ProtocolName) {
 		T::disconnect_peer(self, peer_id, protocol)
 	}
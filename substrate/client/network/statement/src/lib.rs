@@ -32,7 +32,7 @@ use codec::{Decode, Encode};
 use futures::{channel::oneshot, prelude::*, stream::FuturesUnordered, FutureExt};
 use prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
 use sc_network::{
-	config::{NonReservedPeerMode, SetConfig},
+	config::{NonReservedPeerMode, NotificationBandwidth, NotificationPriority, SetConfig},
 	error, multiaddr,
 	peer_store::PeerStoreProvider,
 	service::{
@@ -138,6 +138,10 @@ impl StatementHandlerPrototype {
 				out_peers: 0,
 				reserved_nodes: Vec::new(),
 				non_reserved_mode: NonReservedPeerMode::Deny,
+				bandwidth: NotificationBandwidth {
+					priority: NotificationPriority::Low,
+					..Default::default()
+				},
 			},
 			metrics,
 			peer_store_handle,
@@ -0,0 +1,105 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Schema for the state sync progress in the aux-db.
+
+use codec::{Decode, Encode};
+use sc_client_api::backend::AuxStore;
+use sp_blockchain::{Error as ClientError, Result as ClientResult};
+
+const STATE_SYNC_PROGRESS: &[u8] = b"sync_state_progress";
+const STATE_SYNC_CHUNK: &[u8] = b"sync_state_chunk";
+
+/// Progress of an interrupted state download.
+#[derive(Debug, Clone, Encode, Decode)]
+pub(crate) struct StateSyncProgress<H> {
+	/// Hash of the block the state is downloaded for.
+	pub target: H,
+	/// Key cursor of the next state request.
+	pub last_key: Vec<Vec<u8>>,
+	/// Size of the state downloaded so far, in bytes.
+	pub imported_bytes: u64,
+	/// Number of chunks stored so far.
+	pub chunks: u32,
+}
+
+/// Key-values of a state response, along with the root of the trie they belong to.
+pub(crate) type StateSyncChunk = Vec<(Vec<u8>, Vec<(Vec<u8>, Vec<u8>)>)>;
+
+fn state_sync_chunk_key(index: u32) -> Vec<u8> {
+	let mut key = STATE_SYNC_CHUNK.to_vec();
+	index.using_encoded(|n| key.extend(n));
+	key
+}
+
+fn load_decode<B: AuxStore, T: Decode>(backend: &B, key: &[u8]) -> ClientResult<Option<T>> {
+	match backend.get_aux(key)? {
+		None => Ok(None),
+		Some(t) => T::decode(&mut &t[..])
+			.map_err(|e| ClientError::Backend(format!("State sync DB is corrupted: {}", e)))
+			.map(Some),
+	}
+}
+
+/// Load the progress of the interrupted state download.
+pub(crate) fn load_state_sync_progress<B: AuxStore, H: Decode>(
+	backend: &B,
+) -> ClientResult<Option<StateSyncProgress<H>>> {
+	load_decode(backend, STATE_SYNC_PROGRESS)
+}
+
+/// Load the chunk stored at `index`.
+pub(crate) fn load_state_sync_chunk<B: AuxStore>(
+	backend: &B,
+	index: u32,
+) -> ClientResult<StateSyncChunk> {
+	load_decode(backend, &state_sync_chunk_key(index))?.ok_or_else(|| {
+		ClientError::Backend(format!("State sync DB is corrupted: missing chunk {}", index))
+	})
+}
+
+/// Write a batch of encoded chunks of state responses, along with the updated progress.
+///
+/// The chunks are stored at the indices preceding `progress.chunks`.
+pub(crate) fn write_state_sync_chunks<B: AuxStore, H: Encode>(
+	backend: &B,
+	progress: &StateSyncProgress<H>,
+	chunks: &[Vec<u8>],
+) -> ClientResult<()> {
+	let first = progress.chunks.saturating_sub(chunks.len() as u32);
+	let keys = (first..progress.chunks).map(state_sync_chunk_key).collect::<Vec<_>>();
+	let encoded_progress = progress.encode();
+	let insert = keys
+		.iter()
+		.zip(chunks)
+		.map(|(key, chunk)| (&key[..], &chunk[..]))
+		.chain(std::iter::once((STATE_SYNC_PROGRESS, &encoded_progress[..])))
+		.collect::<Vec<_>>();
+	backend.insert_aux(&insert, &[])
+}
+
+/// Remove the progress of the state download and all the stored chunks.
+pub(crate) fn clear_state_sync_progress<B: AuxStore>(backend: &B, chunks: u32) -> ClientResult<()> {
+	let keys = (0..chunks).map(state_sync_chunk_key).collect::<Vec<_>>();
+	let delete = keys
+		.iter()
+		.map(|key| &key[..])
+		.chain(std::iter::once(STATE_SYNC_PROGRESS))
+		.collect::<Vec<_>>();
+	backend.insert_aux(&[], &delete)
+}
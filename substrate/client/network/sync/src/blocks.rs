@@ -25,6 +25,7 @@ use std::{
 	cmp,
 	collections::{BTreeMap, HashMap},
 	ops::Range,
+	time::{Duration, Instant},
 };
 
 /// Block data with origin.
@@ -38,9 +39,17 @@ pub struct BlockData<B: BlockT> {
 
 #[derive(Debug)]
 enum BlockRangeState<B: BlockT> {
-	Downloading { len: NumberFor<B>, downloading: u32 },
+	/// The range is being downloaded by `downloading` peers. The last of them was asked for the
+	/// range at `since`.
+	Downloading {
+		len: NumberFor<B>,
+		downloading: u32,
+		since: Instant,
+	},
 	Complete(Vec<BlockData<B>>),
-	Queued { len: NumberFor<B> },
+	Queued {
+		len: NumberFor<B>,
+	},
 }
 
 impl<B: BlockT> BlockRangeState<B> {
@@ -94,6 +103,10 @@ impl<B: BlockT> BlockCollection<B> {
 				trace!(target: LOG_TARGET, "Ignored block data already downloaded: {}", start);
 				return
 			},
+			Some(BlockRangeState::Queued { .. }) => {
+				trace!(target: LOG_TARGET, "Ignored block data already queued: {}", start);
+				return
+			},
 			_ => (),
 		}
 
@@ -184,6 +197,7 @@ impl<B: BlockT> BlockCollection<B> {
 			BlockRangeState::Downloading {
 				len: range.end - range.start,
 				downloading: downloading + 1,
+				since: Instant::now(),
 			},
 		);
 		if range.end <= range.start {
@@ -195,6 +209,41 @@ impl<B: BlockT> BlockCollection<B> {
 		Some(range)
 	}
 
+	/// Returns the first range that is not downloaded yet, if no peer was asked for it in the last
+	/// `timeout` and it is downloaded by less than `max_parallel` peers. The range is marked as
+	/// being downloaded by `who` too.
+	///
+	/// No block above this range can be imported until it is downloaded, so a slow peer stalls the
+	/// whole import.
+	pub fn stalled_range(
+		&mut self,
+		who: PeerId,
+		peer_best: NumberFor<B>,
+		common: NumberFor<B>,
+		timeout: Duration,
+		max_parallel: u32,
+	) -> Option<Range<NumberFor<B>>> {
+		let (&start, range) = self
+			.blocks
+			.iter_mut()
+			.find(|(_, range)| !matches!(range, BlockRangeState::Queued { .. }))?;
+		let BlockRangeState::Downloading { len, downloading, since } = range else { return None };
+		if *downloading >= max_parallel ||
+			since.elapsed() < timeout ||
+			start <= common ||
+			start + *len > peer_best + One::one() ||
+			self.peer_requests.get(&who) == Some(&start)
+		{
+			return None
+		}
+
+		trace!(target: LOG_TARGET, "Range {start} stalled, requesting it from {who}");
+		*downloading += 1;
+		*since = Instant::now();
+		self.peer_requests.insert(who, start);
+		Some(start..start + *len)
+	}
+
 	/// Get a valid chain of blocks ordered in descending order and ready for importing into
 	/// the blockchain.
 	/// `from` is the maximum block number for the start of the range that we are interested in.
@@ -240,22 +289,26 @@ impl<B: BlockT> BlockCollection<B> {
 		}
 	}
 
-	pub fn clear_peer_download(&mut self, who: &PeerId) {
-		if let Some(start) = self.peer_requests.remove(who) {
-			let remove = match self.blocks.get_mut(&start) {
-				Some(&mut BlockRangeState::Downloading { ref mut downloading, .. })
-					if *downloading > 1 =>
-				{
-					*downloading -= 1;
-					false
-				},
-				Some(&mut BlockRangeState::Downloading { .. }) => true,
-				_ => false,
-			};
-			if remove {
-				self.blocks.remove(&start);
-			}
+	/// Clear the download requested from `who`.
+	///
+	/// Returns `true` if the requested range was still being downloaded. Otherwise, the range was
+	/// already downloaded from another peer and the response of `who` should be ignored.
+	pub fn clear_peer_download(&mut self, who: &PeerId) -> bool {
+		let Some(start) = self.peer_requests.remove(who) else { return false };
+		let (pending, remove) = match self.blocks.get_mut(&start) {
+			Some(&mut BlockRangeState::Downloading { ref mut downloading, .. })
+				if *downloading > 1 =>
+			{
+				*downloading -= 1;
+				(true, false)
+			},
+			Some(&mut BlockRangeState::Downloading { .. }) => (true, true),
+			_ => (false, false),
+		};
+		if remove {
+			self.blocks.remove(&start);
 		}
+		pending
 	}
 }
 
@@ -266,6 +319,7 @@ mod test {
 	use sc_network_types::PeerId;
 	use sp_core::H256;
 	use sp_runtime::testing::{Block as RawBlock, MockCallU64, TestXt};
+	use std::time::{Duration, Instant};
 
 	type Block = RawBlock<TestXt<MockCallU64, ()>>;
 
@@ -374,7 +428,10 @@ mod test {
 	#[test]
 	fn large_gap() {
 		let mut bc: BlockCollection<Block> = BlockCollection::new();
-		bc.blocks.insert(100, BlockRangeState::Downloading { len: 128, downloading: 1 });
+		bc.blocks.insert(
+			100,
+			BlockRangeState::Downloading { len: 128, downloading: 1, since: Instant::now() },
+		);
 		let blocks = generate_blocks(10)
 			.into_iter()
 			.map(|b| BlockData { block: b, origin: None })
@@ -646,4 +703,54 @@ mod test {
 			Some(21..26), // not 16..21
 		);
 	}
+
+	#[test]
+	fn stalled_range_is_requested_from_another_peer() {
+		let mut bc: BlockCollection<Block> = BlockCollection::new();
+		let peer0 = PeerId::random();
+		let peer1 = PeerId::random();
+		let peer2 = PeerId::random();
+
+		assert_eq!(bc.needed_blocks(peer0, 10, 100, 0, 1, 200), Some(1..11));
+		assert_eq!(bc.needed_blocks(peer1, 10, 100, 0, 1, 200), Some(11..21));
+		bc.insert(11, generate_blocks(10), peer1);
+
+		// The range was requested recently.
+		assert_eq!(bc.stalled_range(peer2, 100, 0, Duration::from_secs(60), 2), None);
+		// The peer doesn't have the whole range.
+		assert_eq!(bc.stalled_range(peer2, 5, 0, Duration::ZERO, 2), None);
+		// The peer is already downloading the range.
+		assert_eq!(bc.stalled_range(peer0, 100, 0, Duration::ZERO, 2), None);
+
+		assert_eq!(bc.stalled_range(peer2, 100, 0, Duration::ZERO, 2), Some(1..11));
+		// The range is downloaded by `max_parallel` peers already.
+		assert_eq!(bc.stalled_range(PeerId::random(), 100, 0, Duration::ZERO, 2), None);
+
+		bc.insert(1, generate_blocks(10), peer2);
+		bc.clear_peer_download(&peer2);
+		assert_eq!(bc.ready_blocks(1).len(), 20);
+		assert_eq!(bc.stalled_range(peer2, 100, 0, Duration::ZERO, 2), None);
+	}
+
+	#[test]
+	fn late_response_for_reassigned_range_is_ignored() {
+		let mut bc: BlockCollection<Block> = BlockCollection::new();
+		let peer0 = PeerId::random();
+		let peer1 = PeerId::random();
+
+		assert_eq!(bc.needed_blocks(peer0, 10, 100, 0, 1, 200), Some(1..11));
+		assert_eq!(bc.stalled_range(peer1, 100, 0, Duration::ZERO, 2), Some(1..11));
+
+		let blocks = generate_blocks(10);
+		assert!(bc.clear_peer_download(&peer1));
+		bc.insert(1, blocks.clone(), peer1);
+		assert_eq!(bc.ready_blocks(1).len(), 10);
+		assert!(matches!(bc.blocks.get(&1), Some(BlockRangeState::Queued { len: 10 })));
+
+		// The original peer answers after the range was queued.
+		assert!(!bc.clear_peer_download(&peer0));
+		bc.insert(1, blocks, peer0);
+		assert!(matches!(bc.blocks.get(&1), Some(BlockRangeState::Queued { len: 10 })));
+		assert!(bc.ready_blocks(1).is_empty());
+	}
 }
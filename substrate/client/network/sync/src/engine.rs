@@ -45,7 +45,10 @@ use tokio::time::{Interval, MissedTickBehavior};
 use sc_client_api::{BlockBackend, HeaderBackend, ProofProvider};
 use sc_consensus::{import_queue::ImportQueueService, IncomingBlock};
 use sc_network::{
-	config::{FullNetworkConfiguration, NotificationHandshake, ProtocolId, SetConfig},
+	config::{
+		FullNetworkConfiguration, NotificationBandwidth, NotificationHandshake,
+		NotificationPriority, ProtocolId, SetConfig,
+	},
 	peer_store::PeerStoreProvider,
 	request_responses::{OutboundFailure, RequestFailure},
 	service::{
@@ -1082,7 +1085,14 @@ where
 				best_hash,
 				genesis_hash,
 			))),
-			set_config.clone(),
+			// block announcements must not be starved by other notification protocols
+			SetConfig {
+				bandwidth: NotificationBandwidth {
+					priority: NotificationPriority::High,
+					..set_config.bandwidth
+				},
+				..set_config.clone()
+			},
 			metrics,
 			peer_store_handle,
 		)
@@ -86,6 +86,9 @@ mockall::mock! {
 		fn add_known_address(&self, peer_id: PeerId, addr: Multiaddr);
 		fn report_peer(&self, peer_id: PeerId, cost_benefit: ReputationChange);
 		fn peer_reputation(&self, peer_id: &PeerId) -> i32;
+		fn ban_peer(&self, peer_id: PeerId);
+		fn unban_peer(&self, peer_id: &PeerId) -> bool;
+		fn banned_peers(&self) -> Vec<PeerId>;
 		fn disconnect_peer(&self, peer_id: PeerId, protocol: ProtocolName);
 		fn accept_unreserved_peers(&self);
 		fn deny_unreserved_peers(&self);
@@ -36,7 +36,7 @@ use crate::{
 	service::network::NetworkServiceHandle,
 	strategy::{
 		disconnected_peers::DisconnectedPeers,
-		state_sync::{ImportResult, StateSync, StateSyncProvider},
+		state_sync::{ImportResult, StateSync, StateSyncPersistence, StateSyncProvider},
 		warp::{WarpSyncPhase, WarpSyncProgress},
 		StrategyKey, SyncingAction, SyncingStrategy,
 	},
@@ -46,7 +46,7 @@ use crate::{
 
 use futures::{channel::oneshot, FutureExt};
 use log::{debug, error, info, trace, warn};
-use prometheus_endpoint::{register, Gauge, PrometheusError, Registry, U64};
+use prometheus_endpoint::{register, Counter, Gauge, PrometheusError, Registry, U64};
 use prost::Message;
 use sc_client_api::{blockchain::BlockGap, BlockBackend, ProofProvider};
 use sc_consensus::{BlockImportError, BlockImportStatus, IncomingBlock};
@@ -55,6 +55,7 @@ use sc_network_common::sync::message::{
 	BlockAnnounce, BlockAttributes, BlockData, BlockRequest, BlockResponse, Direction, FromBlock,
 };
 use sc_network_types::PeerId;
+use schnellru::{ByLength, LruMap};
 use sp_arithmetic::traits::Saturating;
 use sp_blockchain::{Error as ClientError, HeaderBackend, HeaderMetadata};
 use sp_consensus::{BlockOrigin, BlockStatus};
@@ -70,6 +71,7 @@ use std::{
 	collections::{HashMap, HashSet},
 	ops::Range,
 	sync::Arc,
+	time::{Duration, Instant},
 };
 
 #[cfg(test)]
@@ -88,6 +90,12 @@ const MAX_BLOCKS_TO_LOOK_BACKWARDS: u32 = MAX_DOWNLOAD_AHEAD / 2;
 /// Pick the state to sync as the latest finalized number minus this.
 const STATE_SYNC_FINALITY_THRESHOLD: u32 = 8;
 
+/// Resume an interrupted state download if its block is at most this far behind the latest
+/// finalized block.
+///
+/// Peers with the default pruning only keep the state of the last 256 finalized blocks.
+const STATE_SYNC_RESUME_DISTANCE: u32 = 128;
+
 /// We use a heuristic that with a high likelihood, by the time
 /// `MAJOR_SYNC_BLOCKS` have been imported we'll be on the same
 /// chain as (or at least closer to) the peer so we want to delay
@@ -95,6 +103,13 @@ const STATE_SYNC_FINALITY_THRESHOLD: u32 = 8;
 /// so far behind.
 const MAJOR_SYNC_BLOCKS: u8 = 5;
 
+/// Request the first range that is not downloaded yet from another peer if it wasn't received
+/// within this time. The import is stalled until the range is downloaded.
+const STALLED_RANGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum number of disconnected peers whose `PeerScore` is kept until they reconnect.
+const MAX_DISCONNECTED_PEER_SCORES: u32 = 512;
+
 mod rep {
 	use sc_network::ReputationChange as Rep;
 	/// Reputation change when a peer sent us a message that led to a
@@ -136,6 +151,7 @@ mod rep {
 struct Metrics {
 	queued_blocks: Gauge<U64>,
 	fork_targets: Gauge<U64>,
+	stalled_ranges: Counter<U64>,
 }
 
 impl Metrics {
@@ -150,6 +166,13 @@ impl Metrics {
 				let g = Gauge::new("substrate_sync_fork_targets", "Number of fork sync targets")?;
 				register(g, r)?
 			},
+			stalled_ranges: {
+				let c = Counter::new(
+					"substrate_sync_stalled_ranges_total",
+					"Number of stalled block ranges requested from another peer",
+				)?;
+				register(c, r)?
+			},
 		})
 	}
 }
@@ -218,6 +241,9 @@ pub enum ChainSyncMode {
 		/// Download indexed transactions for recent blocks.
 		storage_chain_mode: bool,
 	},
+	/// Download the headers and justifications only. The state is fetched from remote peers on
+	/// demand.
+	Light,
 }
 
 /// All the data we have about a Peer that we are trying to sync with
@@ -235,6 +261,8 @@ pub(crate) struct PeerSync<B: BlockT> {
 	/// The state of syncing this peer is in for us, generally categories
 	/// into `Available` or "busy" with something as defined by `PeerSyncState`.
 	pub state: PeerSyncState<B>,
+	/// How fast the peer answers our block requests.
+	pub score: PeerScore,
 }
 
 impl<B: BlockT> PeerSync<B> {
@@ -253,6 +281,49 @@ impl<B: BlockT> PeerSync<B> {
 	}
 }
 
+/// How fast a peer answers our block requests.
+///
+/// The peers that answer faster are asked first for the blocks that are needed next for import.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PeerScore {
+	/// When the pending block request was sent.
+	request_sent: Option<Instant>,
+	/// Moving average of the block response time.
+	response_time: Option<Duration>,
+}
+
+impl PeerScore {
+	fn on_request(&mut self) {
+		self.request_sent = Some(Instant::now());
+	}
+
+	fn on_response(&mut self) {
+		let Some(request_sent) = self.request_sent.take() else { return };
+		self.update(request_sent.elapsed());
+	}
+
+	/// The peer disconnected. A pending request counts as answered after twice the
+	/// `STALLED_RANGE_TIMEOUT`, so that peers timing out are asked after unknown peers once they
+	/// reconnect.
+	fn on_disconnect(&mut self) {
+		let Some(request_sent) = self.request_sent.take() else { return };
+		self.update(request_sent.elapsed().max(STALLED_RANGE_TIMEOUT * 2));
+	}
+
+	fn update(&mut self, elapsed: Duration) {
+		self.response_time = Some(match self.response_time {
+			Some(average) => (average * 3 + elapsed) / 4,
+			None => elapsed,
+		});
+	}
+
+	/// Key to order the peers by. Peers without any response yet are assumed to answer within
+	/// `STALLED_RANGE_TIMEOUT`.
+	fn rank(&self) -> Duration {
+		self.response_time.unwrap_or(STALLED_RANGE_TIMEOUT)
+	}
+}
+
 struct ForkTarget<B: BlockT> {
 	number: NumberFor<B>,
 	parent_hash: Option<B::Hash>,
@@ -297,6 +368,8 @@ pub struct ChainSync<B: BlockT, Client> {
 	/// The active peers that we are using to sync and their PeerSync status
 	peers: HashMap<PeerId, PeerSync<B>>,
 	disconnected_peers: DisconnectedPeers,
+	/// Scores of the disconnected peers, restored when they reconnect.
+	peer_scores: LruMap<PeerId, PeerScore>,
 	/// A `BlockCollection` of blocks that are being downloaded from peers
 	blocks: BlockCollection<B>,
 	/// The best block number in our queue of blocks to import
@@ -332,6 +405,8 @@ pub struct ChainSync<B: BlockT, Client> {
 	downloaded_blocks: usize,
 	/// State sync in progress, if any.
 	state_sync: Option<StateSync<B, Client>>,
+	/// Handle to persist the progress of state downloads, if enabled.
+	state_sync_persistence: Option<StateSyncPersistence<B>>,
 	/// Enable importing existing blocks. This is used after the state download to
 	/// catch up to the latest state while re-importing blocks.
 	import_existing: bool,
@@ -370,10 +445,10 @@ where
 	fn remove_peer(&mut self, peer_id: &PeerId) {
 		self.blocks.clear_peer_download(peer_id);
 		if let Some(gap_sync) = &mut self.gap_sync {
-			gap_sync.blocks.clear_peer_download(peer_id)
+			gap_sync.blocks.clear_peer_download(peer_id);
 		}
 
-		if let Some(state) = self.peers.remove(peer_id) {
+		if let Some(mut state) = self.peers.remove(peer_id) {
 			if !state.state.is_available() {
 				if let Some(bad_peer) =
 					self.disconnected_peers.on_disconnect_during_request(*peer_id)
@@ -381,6 +456,8 @@ where
 					self.actions.push(SyncingAction::DropPeer(bad_peer));
 				}
 			}
+			state.score.on_disconnect();
+			self.peer_scores.insert(*peer_id, state.score);
 		}
 
 		self.extra_justifications.peer_disconnected(peer_id);
@@ -711,6 +788,7 @@ where
 					let state_sync_complete =
 						self.state_sync.as_ref().map_or(false, |s| s.target_hash() == hash);
 					if state_sync_complete {
+						self.state_sync.as_mut().map(|s| s.discard_progress());
 						info!(
 							target: LOG_TARGET,
 							"State sync is complete ({} MiB), restarting block sync.",
@@ -772,7 +850,12 @@ where
 				},
 				e @ Err(BlockImportError::UnknownParent) | e @ Err(BlockImportError::Other(_)) => {
 					warn!(target: LOG_TARGET, "💔 Error importing block {hash:?}: {}", e.unwrap_err());
-					self.state_sync = None;
+					if let Some(mut state_sync) = self.state_sync.take() {
+						// The state of the target block may be invalid, e.g. a corrupted download.
+						if state_sync.target_hash() == hash {
+							state_sync.discard_progress();
+						}
+					}
 					self.restart();
 				},
 				Err(BlockImportError::Cancelled) => {},
@@ -954,6 +1037,7 @@ where
 			client,
 			peers: HashMap::new(),
 			disconnected_peers: DisconnectedPeers::new(),
+			peer_scores: LruMap::new(ByLength::new(MAX_DISCONNECTED_PEER_SCORES)),
 			blocks: BlockCollection::new(),
 			best_queued_hash: Default::default(),
 			best_queued_number: Zero::zero(),
@@ -968,6 +1052,7 @@ where
 			state_request_protocol_name,
 			downloaded_blocks: 0,
 			state_sync: None,
+			state_sync_persistence: None,
 			import_existing: false,
 			block_downloader,
 			gap_sync: None,
@@ -992,6 +1077,46 @@ where
 		Ok(sync)
 	}
 
+	/// Persist the progress of state downloads with `persistence`, so that an interrupted
+	/// download resumes where it left off.
+	pub fn with_state_sync_persistence(mut self, persistence: StateSyncPersistence<B>) -> Self {
+		self.state_sync_persistence = Some(persistence);
+		self
+	}
+
+	/// Queue the import of the target block of the warp sync, without its state.
+	///
+	/// Used in light client mode, where the headers of the following blocks are downloaded on
+	/// top of the warp sync target.
+	pub fn import_warp_target(
+		&mut self,
+		header: B::Header,
+		justifications: Option<Justifications>,
+	) {
+		let hash = header.hash();
+		self.best_queued_hash = hash;
+		self.best_queued_number = *header.number();
+		self.queue_blocks.insert(hash);
+
+		debug!(target: LOG_TARGET, "Queueing the warp sync target #{} ({hash})", header.number());
+		self.actions.push(SyncingAction::ImportBlocks {
+			origin: BlockOrigin::NetworkInitialSync,
+			blocks: vec![IncomingBlock {
+				hash,
+				header: Some(header),
+				body: None,
+				indexed_body: None,
+				justifications,
+				origin: None,
+				allow_missing_state: true,
+				import_existing: true,
+				skip_execution: true,
+				state: None,
+				remote_state: true,
+			}],
+		});
+	}
+
 	#[must_use]
 	fn add_peer_inner(
 		&mut self,
@@ -1039,6 +1164,7 @@ where
 							best_hash,
 							best_number,
 							state: PeerSyncState::Available,
+							score: self.peer_scores.remove(&peer_id).unwrap_or_default(),
 						},
 					);
 					return Ok(None);
@@ -1082,6 +1208,7 @@ where
 						best_hash,
 						best_number,
 						state,
+						score: self.peer_scores.remove(&peer_id).unwrap_or_default(),
 					},
 				);
 
@@ -1102,6 +1229,7 @@ where
 						best_hash,
 						best_number,
 						state: PeerSyncState::Available,
+						score: self.peer_scores.remove(&peer_id).unwrap_or_default(),
 					},
 				);
 				self.allowed_requests.add(&peer_id);
@@ -1157,23 +1285,33 @@ where
 			if let Some(request) = request {
 				match &mut peer.state {
 					PeerSyncState::DownloadingNew(_) => {
-						self.blocks.clear_peer_download(peer_id);
+						let pending = self.blocks.clear_peer_download(peer_id);
 						peer.state = PeerSyncState::Available;
+						peer.score.on_response();
 						if let Some(start_block) =
 							validate_blocks::<B>(&blocks, peer_id, Some(request))?
 						{
-							self.blocks.insert(start_block, blocks, *peer_id);
+							if pending {
+								self.blocks.insert(start_block, blocks, *peer_id);
+							} else {
+								trace!(
+									target: LOG_TARGET,
+									"Ignored late response from {peer_id} for block {start_block}",
+								);
+							}
 						}
 						self.ready_blocks()
 					},
 					PeerSyncState::DownloadingGap(_) => {
 						peer.state = PeerSyncState::Available;
 						if let Some(gap_sync) = &mut self.gap_sync {
-							gap_sync.blocks.clear_peer_download(peer_id);
+							let pending = gap_sync.blocks.clear_peer_download(peer_id);
 							if let Some(start_block) =
 								validate_blocks::<B>(&blocks, peer_id, Some(request))?
 							{
-								gap_sync.blocks.insert(start_block, blocks, *peer_id);
+								if pending {
+									gap_sync.blocks.insert(start_block, blocks, *peer_id);
+								}
 							}
 							gap = true;
 							let blocks: Vec<_> = gap_sync
@@ -1198,6 +1336,7 @@ where
 										import_existing: self.import_existing,
 										skip_execution: true,
 										state: None,
+										remote_state: false,
 									}
 								})
 								.collect();
@@ -1237,6 +1376,7 @@ where
 									import_existing: self.import_existing,
 									skip_execution: self.skip_execution(),
 									state: None,
+									remote_state: self.mode == ChainSyncMode::Light,
 								}
 							})
 							.collect()
@@ -1379,6 +1519,7 @@ where
 							import_existing: false,
 							skip_execution: true,
 							state: None,
+							remote_state: self.mode == ChainSyncMode::Light,
 						}
 					})
 					.collect()
@@ -1524,13 +1665,14 @@ where
 				BlockAttributes::HEADER |
 					BlockAttributes::JUSTIFICATION |
 					BlockAttributes::INDEXED_BODY,
+			ChainSyncMode::Light => BlockAttributes::HEADER | BlockAttributes::JUSTIFICATION,
 		}
 	}
 
 	fn skip_execution(&self) -> bool {
 		match self.mode {
 			ChainSyncMode::Full => false,
-			ChainSyncMode::LightState { .. } => true,
+			ChainSyncMode::LightState { .. } | ChainSyncMode::Light => true,
 		}
 	}
 
@@ -1704,7 +1846,10 @@ where
 			}
 		}
 
-		if let Some(BlockGap { start, end, .. }) = info.block_gap {
+		// The gap left by the warp sync is never filled in light client mode.
+		if let Some(BlockGap { start, end, .. }) =
+			info.block_gap.filter(|_| self.mode != ChainSyncMode::Light)
+		{
 			debug!(target: LOG_TARGET, "Starting gap sync #{start} - #{end}");
 			self.gap_sync = Some(GapSync {
 				best_queued_number: start - One::one(),
@@ -1762,6 +1907,7 @@ where
 					import_existing: self.import_existing,
 					skip_execution: self.skip_execution(),
 					state: None,
+					remote_state: self.mode == ChainSyncMode::Light,
 				}
 			})
 			.collect()
@@ -1815,14 +1961,21 @@ where
 		let queue_blocks = &self.queue_blocks;
 		let allowed_requests = self.allowed_requests.clone();
 		let max_parallel = if is_major_syncing { 1 } else { self.max_parallel_downloads };
+		let max_stalled = self.max_parallel_downloads;
 		let max_blocks_per_request = self.max_blocks_per_request;
 		let gap_sync = &mut self.gap_sync;
 		let disconnected_peers = &mut self.disconnected_peers;
 		let metrics = self.metrics.as_ref();
-		let requests = self
-			.peers
-			.iter_mut()
-			.filter_map(move |(&id, peer)| {
+		// Peers that answer faster are asked first, so that they download the blocks needed next
+		// for import.
+		let mut peer_ids =
+			self.peers.iter().map(|(id, peer)| (*id, peer.score.rank())).collect::<Vec<_>>();
+		peer_ids.sort_by_key(|(_, rank)| *rank);
+		let peers = &mut self.peers;
+		let requests = peer_ids
+			.into_iter()
+			.filter_map(move |(id, _)| {
+				let peer = peers.get_mut(&id)?;
 				if !peer.state.is_available() ||
 					!allowed_requests.contains(&id) ||
 					!disconnected_peers.is_peer_available(&id)
@@ -1855,17 +2008,25 @@ where
 						state: AncestorSearchState::ExponentialBackoff(One::one()),
 					};
 					Some((id, ancestry_request::<B>(current)))
-				} else if let Some((range, req)) = peer_block_request(
-					&id,
-					peer,
-					blocks,
-					attrs,
-					max_parallel,
-					max_blocks_per_request,
-					last_finalized,
-					best_queued,
-				) {
+				} else if let Some((range, req)) = is_major_syncing
+					.then(|| {
+						peer_stalled_block_request(&id, peer, blocks, attrs, max_stalled, metrics)
+					})
+					.flatten()
+					.or_else(|| {
+						peer_block_request(
+							&id,
+							peer,
+							blocks,
+							attrs,
+							max_parallel,
+							max_blocks_per_request,
+							last_finalized,
+							best_queued,
+						)
+					}) {
 					peer.state = PeerSyncState::DownloadingNew(range.start);
+					peer.score.on_request();
 					trace!(
 						target: LOG_TARGET,
 						"New block request for {}, (best:{}, common:{}) {:?}",
@@ -2010,6 +2171,7 @@ where
 					import_existing: true,
 					skip_execution: self.skip_execution(),
 					state: Some(state),
+					remote_state: false,
 				};
 				debug!(target: LOG_TARGET, "State download is complete. Import is queued");
 				self.actions.push(SyncingAction::ImportBlocks { origin, blocks: vec![block] });
@@ -2033,13 +2195,22 @@ where
 		heads.sort();
 		let median = heads[heads.len() / 2];
 		if finalized_number + STATE_SYNC_FINALITY_THRESHOLD.saturated_into() >= median {
-			if let Ok(Some(header)) = self.client.header(finalized_hash) {
+			let resumed_header = self.resumable_state_sync_target(finalized_number);
+			if let Some(header) =
+				resumed_header.or_else(|| self.client.header(finalized_hash).ok().flatten())
+			{
 				log::debug!(
 					target: LOG_TARGET,
-					"Starting state sync for #{finalized_number} ({finalized_hash})",
+					"Starting state sync for #{} ({})",
+					header.number(),
+					header.hash(),
 				);
-				self.state_sync =
-					Some(StateSync::new(self.client.clone(), header, None, None, skip_proofs));
+				let state_sync =
+					StateSync::new(self.client.clone(), header, None, None, skip_proofs);
+				self.state_sync = Some(match &self.state_sync_persistence {
+					Some(persistence) => state_sync.with_persistence(persistence.clone()),
+					None => state_sync,
+				});
 				self.allowed_requests.set_all();
 			} else {
 				log::error!(
@@ -2052,6 +2223,18 @@ where
 		}
 	}
 
+	/// Returns the header of the block of an interrupted state download, if the download can be
+	/// resumed.
+	fn resumable_state_sync_target(&self, finalized_number: NumberFor<B>) -> Option<B::Header> {
+		let target = self.state_sync_persistence.as_ref()?.target()?;
+		let header = self.client.header(target).ok().flatten()?;
+		let number = *header.number();
+		let canonical = self.client.hash(number).ok().flatten() == Some(target);
+		let recent = number <= finalized_number &&
+			finalized_number - number <= STATE_SYNC_RESUME_DISTANCE.saturated_into();
+		(canonical && recent).then_some(header)
+	}
+
 	/// A version of `actions()` that doesn't schedule extra requests. For testing only.
 	#[cfg(test)]
 	#[must_use]
@@ -2200,6 +2383,41 @@ fn peer_block_request<B: BlockT>(
 	Some((range, request))
 }
 
+/// Get a request for the stalled range from the peer if any.
+fn peer_stalled_block_request<B: BlockT>(
+	id: &PeerId,
+	peer: &PeerSync<B>,
+	blocks: &mut BlockCollection<B>,
+	attrs: BlockAttributes,
+	max_parallel_downloads: u32,
+	metrics: Option<&Metrics>,
+) -> Option<(Range<NumberFor<B>>, BlockRequest<B>)> {
+	let range = blocks.stalled_range(
+		*id,
+		peer.best_number,
+		peer.common_number,
+		STALLED_RANGE_TIMEOUT,
+		max_parallel_downloads,
+	)?;
+	debug!(target: LOG_TARGET, "Requesting stalled range {range:?} from {id}");
+	if let Some(metrics) = metrics {
+		metrics.stalled_ranges.inc();
+	}
+
+	// The end is not part of the range.
+	let last = range.end.saturating_sub(One::one());
+
+	let request = BlockRequest::<B> {
+		id: 0,
+		fields: attrs,
+		from: FromBlock::Number(last),
+		direction: Direction::Descending,
+		max: Some((range.end - range.start).saturated_into::<u32>()),
+	};
+
+	Some((range, request))
+}
+
 /// Get a new block request for the peer if any.
 fn peer_gap_block_request<B: BlockT>(
 	id: &PeerId,
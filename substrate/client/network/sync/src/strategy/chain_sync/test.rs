@@ -1127,3 +1127,18 @@ fn request_across_forks() {
 		assert!(sync.is_known(&block.header.parent_hash()));
 	}
 }
+
+#[test]
+fn peer_timing_out_is_ranked_after_unknown_peer() {
+	let mut fast = PeerScore::default();
+	fast.on_request();
+	fast.on_response();
+
+	let mut timed_out = PeerScore::default();
+	timed_out.on_request();
+	timed_out.on_disconnect();
+
+	let unknown = PeerScore::default();
+	assert!(fast.rank() < unknown.rank());
+	assert!(unknown.rank() < timed_out.rank());
+}
@@ -26,6 +26,7 @@ use crate::{
 	strategy::{
 		chain_sync::{ChainSync, ChainSyncMode},
 		state::StateStrategy,
+		state_sync::StateSyncPersistence,
 		warp::{WarpSync, WarpSyncConfig},
 		StrategyKey, SyncingAction, SyncingStrategy,
 	},
@@ -40,7 +41,7 @@ use sc_network::ProtocolName;
 use sc_network_common::sync::{message::BlockAnnounce, SyncMode};
 use sc_network_types::PeerId;
 use sp_blockchain::{Error as ClientError, HeaderBackend, HeaderMetadata};
-use sp_runtime::traits::{Block as BlockT, Header, NumberFor};
+use sp_runtime::traits::{Block as BlockT, Header, NumberFor, Zero};
 use std::{any::Any, collections::HashMap, sync::Arc};
 
 /// Corresponding `ChainSync` mode.
@@ -50,6 +51,7 @@ fn chain_sync_mode(sync_mode: SyncMode) -> ChainSyncMode {
 		SyncMode::LightState { skip_proofs, storage_chain_mode } =>
 			ChainSyncMode::LightState { skip_proofs, storage_chain_mode },
 		SyncMode::Warp => ChainSyncMode::Full,
+		SyncMode::Light => ChainSyncMode::Light,
 	}
 }
 
@@ -352,7 +354,15 @@ where
 			config.max_blocks_per_request = MAX_BLOCKS_IN_RESPONSE as u32;
 		}
 
-		if let SyncMode::Warp = config.mode {
+		// A light client only warp syncs on the first start, afterwards it continues downloading
+		// the headers from the last finalized block.
+		let warp = match config.mode {
+			SyncMode::Warp => true,
+			SyncMode::Light => client.info().finalized_number.is_zero(),
+			_ => false,
+		};
+
+		if warp {
 			let warp_sync_config = warp_sync_config
 				.expect("Warp sync configuration must be supplied in warp sync mode.");
 			let warp_sync = WarpSync::new(
@@ -391,11 +401,52 @@ where
 		}
 	}
 
+	/// Persist the progress of the state download of the fast sync with `persistence`, so that an
+	/// interrupted download resumes where it left off.
+	pub fn with_state_sync_persistence(mut self, persistence: StateSyncPersistence<B>) -> Self {
+		self.chain_sync = self
+			.chain_sync
+			.map(|chain_sync| chain_sync.with_state_sync_persistence(persistence));
+		self
+	}
+
 	/// Proceed with the next strategy if the active one finished.
 	pub fn proceed_to_next(&mut self) -> Result<(), ClientError> {
 		// The strategies are switched as `WarpSync` -> `StateStrategy` -> `ChainSync`.
 		if let Some(ref mut warp) = self.warp {
 			match warp.take_result() {
+				Some(res) if self.config.mode.is_light() => {
+					info!(
+						target: LOG_TARGET,
+						"Warp sync is complete, continuing with header sync."
+					);
+					let mut chain_sync = match ChainSync::new(
+						chain_sync_mode(self.config.mode),
+						self.client.clone(),
+						self.config.max_parallel_downloads,
+						self.config.max_blocks_per_request,
+						self.config.state_request_protocol_name.clone(),
+						self.config.block_downloader.clone(),
+						self.config.metrics_registry.as_ref(),
+						std::iter::empty(),
+					) {
+						Ok(chain_sync) => chain_sync,
+						Err(e) => {
+							error!(target: LOG_TARGET, "Failed to start `ChainSync`.");
+							return Err(e)
+						},
+					};
+					// The peers are added once the target block is queued, so that the headers
+					// are requested on top of it.
+					chain_sync.import_warp_target(res.target_header, res.target_justifications);
+					self.peer_best_blocks.iter().for_each(|(peer_id, (best_hash, best_number))| {
+						chain_sync.add_peer(*peer_id, *best_hash, *best_number)
+					});
+
+					self.warp = None;
+					self.chain_sync = Some(chain_sync);
+					Ok(())
+				},
 				Some(res) => {
 					info!(
 						target: LOG_TARGET,
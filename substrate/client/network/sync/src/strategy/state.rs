@@ -230,6 +230,7 @@ impl<B: BlockT> StateStrategy<B> {
 					import_existing: true,
 					skip_execution: true,
 					state: Some(state),
+					remote_state: false,
 				};
 				debug!(target: LOG_TARGET, "State download is complete. Import is queued");
 				self.actions.push(SyncingAction::ImportBlocks { origin, blocks: vec![block] });
@@ -738,6 +739,7 @@ mod test {
 			import_existing: true,
 			skip_execution: true,
 			state: Some(state),
+			remote_state: false,
 		};
 		let expected_blocks = vec![expected_block];
 
@@ -17,22 +17,35 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 //! State sync support.
+//!
+//! With a [`StateSyncPersistence`] handle, the downloaded state is persisted in the aux-db along
+//! with the key cursor, so that an interrupted download of the same state resumes where it left
+//! off. The state root of the assembled state is checked against the target header on import.
 
 use crate::{
+	aux_schema::{self, StateSyncChunk},
 	schema::v1::{KeyValueStateEntry, StateEntry, StateRequest, StateResponse},
 	LOG_TARGET,
 };
 use codec::{Decode, Encode};
-use log::debug;
-use sc_client_api::{CompactProof, KeyValueStates, ProofProvider};
+use log::{debug, info, warn};
+use sc_client_api::{AuxStore, CompactProof, ProofProvider};
 use sc_consensus::ImportedState;
 use smallvec::SmallVec;
+use sp_blockchain::{Error as ClientError, Result as ClientResult};
 use sp_core::storage::well_known_keys;
 use sp_runtime::{
 	traits::{Block as BlockT, Header, NumberFor},
 	Justifications,
 };
-use std::{collections::HashMap, fmt, sync::Arc};
+use std::{
+	collections::HashMap,
+	fmt,
+	sync::{mpsc, Arc},
+};
+
+/// Size of the downloaded chunks buffered before they are persisted, in bytes.
+const PERSISTENCE_BATCH_BYTES: usize = 16 * 1024 * 1024;
 
 /// Generic state sync provider. Used for mocking in tests.
 pub trait StateSyncProvider<B: BlockT>: Send + Sync {
@@ -48,6 +61,8 @@ pub trait StateSyncProvider<B: BlockT>: Send + Sync {
 	fn target_hash(&self) -> B::Hash;
 	/// Returns state sync estimated progress.
 	fn progress(&self) -> StateSyncProgress;
+	/// Discard the persisted progress of the download, once the state is imported or rejected.
+	fn discard_progress(&mut self) {}
 }
 
 // Reported state sync phase.
@@ -136,6 +151,117 @@ impl<B: BlockT> StateSyncMetadata<B> {
 	}
 }
 
+/// Request to the thread persisting the progress of state downloads.
+enum PersistenceRequest<H> {
+	/// Load the progress of the interrupted download.
+	LoadProgress(mpsc::SyncSender<Option<aux_schema::StateSyncProgress<H>>>),
+	/// Load the given number of chunks of the interrupted download.
+	LoadChunks(u32, mpsc::SyncSender<ClientResult<Vec<StateSyncChunk>>>),
+	/// Write a batch of encoded chunks, along with the updated progress.
+	Write(aux_schema::StateSyncProgress<H>, Vec<Vec<u8>>),
+	/// Remove the progress and all the chunks.
+	Clear,
+}
+
+/// Handle to persist the progress of state downloads in the aux-db.
+///
+/// The downloaded chunks are written in batches by a dedicated thread, so that the database
+/// writes don't block the syncing engine. They are removed once the state is imported.
+pub struct StateSyncPersistence<B: BlockT> {
+	requests: mpsc::Sender<PersistenceRequest<B::Hash>>,
+}
+
+impl<B: BlockT> Clone for StateSyncPersistence<B> {
+	fn clone(&self) -> Self {
+		Self { requests: self.requests.clone() }
+	}
+}
+
+impl<B: BlockT> StateSyncPersistence<B> {
+	/// Create a new handle persisting the progress in the aux-db of `backend`.
+	///
+	/// Spawns the thread writing to the aux-db, which ends once all the handles are dropped.
+	pub fn new<A>(backend: Arc<A>) -> std::io::Result<Self>
+	where
+		A: AuxStore + Send + Sync + 'static,
+	{
+		let (requests, receiver) = mpsc::channel();
+		std::thread::Builder::new().name("state-sync-db".into()).spawn(move || {
+			for request in receiver {
+				process_persistence_request(&*backend, request);
+			}
+		})?;
+		Ok(Self { requests })
+	}
+
+	/// Returns the hash of the block of the interrupted state download, if any.
+	pub fn target(&self) -> Option<B::Hash> {
+		self.load_progress().map(|progress| progress.target)
+	}
+
+	/// Discard the progress of the interrupted state download, if any.
+	pub fn clear(&self) {
+		self.send(PersistenceRequest::Clear);
+	}
+
+	fn load_progress(&self) -> Option<aux_schema::StateSyncProgress<B::Hash>> {
+		let (sender, receiver) = mpsc::sync_channel(1);
+		self.send(PersistenceRequest::LoadProgress(sender));
+		receiver.recv().ok().flatten()
+	}
+
+	fn load_chunks(&self, count: u32) -> ClientResult<Vec<StateSyncChunk>> {
+		let (sender, receiver) = mpsc::sync_channel(1);
+		self.send(PersistenceRequest::LoadChunks(count, sender));
+		receiver
+			.recv()
+			.map_err(|_| ClientError::Backend("State sync DB thread has terminated".into()))?
+	}
+
+	fn write(&self, progress: aux_schema::StateSyncProgress<B::Hash>, chunks: Vec<Vec<u8>>) {
+		self.send(PersistenceRequest::Write(progress, chunks));
+	}
+
+	fn send(&self, request: PersistenceRequest<B::Hash>) {
+		if self.requests.send(request).is_err() {
+			warn!(target: LOG_TARGET, "State sync DB thread has terminated");
+		}
+	}
+}
+
+fn process_persistence_request<A: AuxStore, H: Encode + Decode>(
+	backend: &A,
+	request: PersistenceRequest<H>,
+) {
+	let load_progress = || {
+		aux_schema::load_state_sync_progress::<_, H>(backend).unwrap_or_else(|e| {
+			warn!(target: LOG_TARGET, "Failed to load state sync progress: {e}");
+			None
+		})
+	};
+	match request {
+		PersistenceRequest::LoadProgress(reply) => {
+			let _ = reply.send(load_progress());
+		},
+		PersistenceRequest::LoadChunks(count, reply) => {
+			let chunks = (0..count)
+				.map(|index| aux_schema::load_state_sync_chunk(backend, index))
+				.collect();
+			let _ = reply.send(chunks);
+		},
+		PersistenceRequest::Write(progress, chunks) =>
+			if let Err(e) = aux_schema::write_state_sync_chunks(backend, &progress, &chunks) {
+				warn!(target: LOG_TARGET, "Failed to persist state sync progress: {e}");
+			},
+		PersistenceRequest::Clear => {
+			let Some(progress) = load_progress() else { return };
+			if let Err(e) = aux_schema::clear_state_sync_progress(backend, progress.chunks) {
+				warn!(target: LOG_TARGET, "Failed to discard state sync progress: {e}");
+			}
+		},
+	}
+}
+
 /// State sync state machine.
 ///
 /// Accumulates partial state data until it is ready to be imported.
@@ -143,6 +269,14 @@ pub struct StateSync<B: BlockT, Client> {
 	metadata: StateSyncMetadata<B>,
 	state: HashMap<Vec<u8>, (Vec<(Vec<u8>, Vec<u8>)>, Vec<Vec<u8>>)>,
 	client: Arc<Client>,
+	/// Handle to persist the progress of the download, if enabled.
+	persistence: Option<StateSyncPersistence<B>>,
+	/// Number of chunks persisted so far, including the pending ones.
+	persisted_chunks: u32,
+	/// Encoded chunks not yet sent for persistence.
+	pending_chunks: Vec<Vec<u8>>,
+	/// Size of `pending_chunks`, in bytes.
+	pending_bytes: usize,
 }
 
 impl<B, Client> StateSync<B, Client>
@@ -170,6 +304,74 @@ where
 				skip_proof,
 			},
 			state: HashMap::default(),
+			persistence: None,
+			persisted_chunks: 0,
+			pending_chunks: Vec::new(),
+			pending_bytes: 0,
+		}
+	}
+
+	/// Persist the progress of the download with `persistence`.
+	///
+	/// Resumes the interrupted download of the state of the target block, if any.
+	pub fn with_persistence(mut self, persistence: StateSyncPersistence<B>) -> Self {
+		self.persistence = Some(persistence);
+		self.resume();
+		self
+	}
+
+	/// Restore the persisted progress of the download, or discard it if it is for another block.
+	fn resume(&mut self) {
+		let Some(persistence) = &self.persistence else { return };
+		let Some(progress) = persistence.load_progress() else { return };
+
+		if progress.target != self.metadata.target_hash() {
+			debug!(
+				target: LOG_TARGET,
+				"Discarding state sync progress for {}, syncing state of {}",
+				progress.target,
+				self.metadata.target_hash(),
+			);
+			persistence.clear();
+			return
+		}
+
+		let chunks = match persistence.load_chunks(progress.chunks) {
+			Ok(chunks) => chunks,
+			Err(e) => {
+				warn!(target: LOG_TARGET, "Failed to load state sync chunks: {e}");
+				persistence.clear();
+				return
+			},
+		};
+		chunks.into_iter().for_each(|chunk| self.process_state_chunk(chunk));
+		self.persisted_chunks = progress.chunks;
+		self.metadata.last_key = progress.last_key.into();
+		self.metadata.imported_bytes = progress.imported_bytes;
+
+		info!(
+			target: LOG_TARGET,
+			"Resuming state sync for #{} ({}) from {} MiB",
+			self.metadata.target_number(),
+			self.metadata.target_hash(),
+			progress.imported_bytes / (1024 * 1024),
+		);
+	}
+
+	/// Queue the encoded `chunk` of the state for persistence, and send the pending chunks along
+	/// with the current key cursor once they exceed `PERSISTENCE_BATCH_BYTES`.
+	fn persist_chunk(&mut self, chunk: Vec<u8>) {
+		self.persisted_chunks += 1;
+		self.pending_bytes += chunk.len();
+		self.pending_chunks.push(chunk);
+		if self.pending_bytes >= PERSISTENCE_BATCH_BYTES {
+			self.flush_pending_chunks();
+		}
+	}
+
+	fn process_state_chunk(&mut self, chunk: StateSyncChunk) {
+		for (state_root, key_values) in chunk {
+			self.process_state_key_values(state_root, key_values);
 		}
 	}
 
@@ -205,14 +407,9 @@ where
 		}
 	}
 
-	fn process_state_verified(&mut self, values: KeyValueStates) {
-		for values in values.0 {
-			self.process_state_key_values(values.state_root, values.key_values);
-		}
-	}
-
-	fn process_state_unverified(&mut self, response: StateResponse) -> bool {
+	fn process_state_unverified(&mut self, response: StateResponse) -> (bool, StateSyncChunk) {
 		let mut complete = true;
+		let mut chunk = StateSyncChunk::new();
 		// if the trie is a child trie and one of its parent trie is empty,
 		// the parent cursor stays valid.
 		// Empty parent trie content only happens when all the response content
@@ -239,12 +436,12 @@ where
 			}
 
 			let KeyValueStateEntry { state_root, entries, complete: _ } = state;
-			self.process_state_key_values(
+			chunk.push((
 				state_root,
-				entries.into_iter().map(|StateEntry { key, value }| (key, value)),
-			);
+				entries.into_iter().map(|StateEntry { key, value }| (key, value)).collect(),
+			));
 		}
-		complete
+		(complete, chunk)
 	}
 }
 
@@ -263,7 +460,7 @@ where
 			debug!(target: LOG_TARGET, "Missing proof");
 			return ImportResult::BadResponse
 		}
-		let complete = if !self.metadata.skip_proof {
+		let (complete, chunk) = if !self.metadata.skip_proof {
 			debug!(target: LOG_TARGET, "Importing state from {} trie nodes", response.proof.len());
 			let proof_size = response.proof.len() as u64;
 			let proof = match CompactProof::decode(&mut response.proof.as_ref()) {
@@ -295,14 +492,26 @@ where
 				debug!(target: LOG_TARGET, "Error updating key cursor, depth: {}", completed);
 			};
 
-			self.process_state_verified(values);
 			self.metadata.imported_bytes += proof_size;
-			complete
+			let chunk = values
+				.0
+				.into_iter()
+				.map(|values| (values.state_root, values.key_values))
+				.collect::<StateSyncChunk>();
+			(complete, chunk)
 		} else {
 			self.process_state_unverified(response)
 		};
+		// The last chunk is not persisted, it is downloaded again if the import is interrupted.
+		let encoded_chunk = (!complete && self.persistence.is_some()).then(|| chunk.encode());
+		self.process_state_chunk(chunk);
+		if let Some(encoded_chunk) = encoded_chunk {
+			self.persist_chunk(encoded_chunk);
+		}
 		if complete {
 			self.metadata.complete = true;
+			self.pending_chunks.clear();
+			self.pending_bytes = 0;
 			let target_hash = self.metadata.target_hash();
 			ImportResult::Import(
 				target_hash,
@@ -340,4 +549,158 @@ where
 	fn progress(&self) -> StateSyncProgress {
 		self.metadata.progress()
 	}
+
+	/// Discard the persisted progress of the download.
+	fn discard_progress(&mut self) {
+		if let Some(persistence) = &self.persistence {
+			persistence.clear();
+		}
+		self.persisted_chunks = 0;
+		self.pending_chunks.clear();
+		self.pending_bytes = 0;
+	}
+}
+
+impl<B: BlockT, Client> StateSync<B, Client> {
+	/// Send the pending chunks for persistence, along with the current key cursor.
+	fn flush_pending_chunks(&mut self) {
+		let Some(persistence) = &self.persistence else { return };
+		if self.pending_chunks.is_empty() {
+			return
+		}
+		let progress = aux_schema::StateSyncProgress {
+			target: self.metadata.target_hash(),
+			last_key: self.metadata.last_key.to_vec(),
+			imported_bytes: self.metadata.imported_bytes,
+			chunks: self.persisted_chunks,
+		};
+		persistence.write(progress, std::mem::take(&mut self.pending_chunks));
+		self.pending_bytes = 0;
+	}
+}
+
+impl<B: BlockT, Client> Drop for StateSync<B, Client> {
+	fn drop(&mut self) {
+		// Persist the chunks downloaded since the last batch, e.g. on shutdown.
+		self.flush_pending_chunks();
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use sp_blockchain::HeaderBackend;
+	use substrate_test_runtime_client::{DefaultTestClientBuilderExt, TestClientBuilderExt};
+
+	fn partial_response(key: &[u8]) -> StateResponse {
+		StateResponse {
+			entries: vec![KeyValueStateEntry {
+				state_root: Vec::new(),
+				entries: vec![StateEntry { key: key.to_vec(), value: vec![1] }],
+				complete: false,
+			}],
+			proof: Vec::new(),
+		}
+	}
+
+	fn proof_response(
+		client: &substrate_test_runtime_client::TestClient,
+		request: StateRequest,
+	) -> StateResponse {
+		let hash = Decode::decode(&mut request.block.as_ref()).unwrap();
+		// Keep the responses small, so that the state is downloaded in several chunks.
+		let (proof, _) = client.read_proof_collection(hash, &request.start, 1).unwrap();
+		StateResponse { entries: Vec::new(), proof: proof.encode() }
+	}
+
+	/// Download the rest of the state and return the key values of each trie, sorted by root.
+	fn complete_download(
+		state_sync: &mut StateSync<
+			substrate_test_runtime_client::runtime::Block,
+			substrate_test_runtime_client::TestClient,
+		>,
+		client: &substrate_test_runtime_client::TestClient,
+	) -> Vec<(Vec<u8>, Vec<(Vec<u8>, Vec<u8>)>)> {
+		loop {
+			match state_sync.import(proof_response(client, state_sync.next_request())) {
+				ImportResult::Continue => continue,
+				ImportResult::Import(_, _, imported, _, _) => {
+					let mut state = imported
+						.state
+						.0
+						.into_iter()
+						.map(|level| (level.state_root, level.key_values))
+						.collect::<Vec<_>>();
+					state.sort();
+					return state
+				},
+				ImportResult::BadResponse => panic!("Valid response rejected"),
+			}
+		}
+	}
+
+	#[test]
+	fn interrupted_download_is_resumed() {
+		let client = Arc::new(substrate_test_runtime_client::TestClientBuilder::new().build());
+		let header = client.header(client.info().genesis_hash).unwrap().unwrap();
+		let persistence = StateSyncPersistence::new(client.clone()).unwrap();
+
+		let mut state_sync = StateSync::new(client.clone(), header.clone(), None, None, true)
+			.with_persistence(persistence.clone());
+		assert!(matches!(state_sync.import(partial_response(b"a")), ImportResult::Continue));
+		assert!(matches!(state_sync.import(partial_response(b"b")), ImportResult::Continue));
+		let size = state_sync.progress().size;
+		drop(state_sync);
+
+		let resumed = StateSync::new(client.clone(), header.clone(), None, None, true)
+			.with_persistence(persistence.clone());
+		assert_eq!(resumed.next_request().start, vec![b"b".to_vec()]);
+		assert_eq!(resumed.progress().size, size);
+		assert_eq!(
+			resumed.state[&Vec::new()].0,
+			vec![(b"a".to_vec(), vec![1]), (b"b".to_vec(), vec![1])]
+		);
+
+		// Progress of the download of another state is discarded.
+		let mut other_header = header;
+		other_header.set_number(1);
+		let other = StateSync::new(client.clone(), other_header, None, None, true)
+			.with_persistence(persistence.clone());
+		assert!(other.next_request().start.is_empty());
+		assert_eq!(persistence.target(), None);
+	}
+
+	#[test]
+	fn interrupted_download_with_proofs_is_resumed() {
+		let client = Arc::new(substrate_test_runtime_client::TestClientBuilder::new().build());
+		let header = client.header(client.info().genesis_hash).unwrap().unwrap();
+		let persistence = StateSyncPersistence::new(client.clone()).unwrap();
+
+		let mut state_sync = StateSync::new(client.clone(), header.clone(), None, None, false)
+			.with_persistence(persistence.clone());
+		let expected = complete_download(&mut state_sync, &client);
+		state_sync.discard_progress();
+		assert_eq!(persistence.target(), None);
+
+		let mut state_sync = StateSync::new(client.clone(), header.clone(), None, None, false)
+			.with_persistence(persistence.clone());
+		for _ in 0..2 {
+			let response = proof_response(&client, state_sync.next_request());
+			assert!(matches!(state_sync.import(response), ImportResult::Continue));
+		}
+		let next_request = state_sync.next_request();
+		let progress = state_sync.progress();
+		let state = state_sync.state.clone();
+		drop(state_sync);
+
+		// The chunks are persisted in a batch when the download is interrupted.
+		let mut resumed =
+			StateSync::new(client.clone(), header, None, None, false).with_persistence(persistence);
+		assert_eq!(resumed.next_request(), next_request);
+		assert_eq!(resumed.progress().size, progress.size);
+		assert_eq!(resumed.state, state);
+
+		// The resumed download yields the same state as the uninterrupted one.
+		assert_eq!(complete_download(&mut resumed, &client), expected);
+	}
 }
@@ -38,6 +38,12 @@ pub(crate) const MAX_TRANSACTIONS_SIZE: u64 = MAX_RESPONSE_SIZE;
 /// Maximum number of transaction validation request we keep at any moment.
 pub(crate) const MAX_PENDING_TRANSACTIONS: usize = 8192;
 
+/// Default maximum number of transaction validation requests triggered by a single peer that we
+/// keep at any moment.
+///
+/// Prevents a single peer from occupying all the validation slots.
+pub(crate) const MAX_PENDING_TRANSACTIONS_PER_PEER: usize = MAX_PENDING_TRANSACTIONS / 16;
+
 /// Result of the transaction import.
 #[derive(Clone, Copy, Debug)]
 pub enum TransactionImport {
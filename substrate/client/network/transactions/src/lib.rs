@@ -34,7 +34,9 @@ use log::{debug, trace, warn};
 
 use prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
 use sc_network::{
-	config::{NonReservedPeerMode, ProtocolId, SetConfig},
+	config::{
+		NonReservedPeerMode, NotificationBandwidth, NotificationPriority, ProtocolId, SetConfig,
+	},
 	error, multiaddr,
 	peer_store::PeerStoreProvider,
 	service::{
@@ -127,6 +129,9 @@ pub struct TransactionsHandlerPrototype {
 
 	/// Handle that is used to communicate with `sc_network::Notifications`.
 	notification_service: Box<dyn NotificationService>,
+
+	/// Maximum number of pending transaction validations triggered by a single peer.
+	max_pending_transactions_per_peer: usize,
 }
 
 impl TransactionsHandlerPrototype {
@@ -159,12 +164,32 @@ impl TransactionsHandlerPrototype {
 				out_peers: 0,
 				reserved_nodes: Vec::new(),
 				non_reserved_mode: NonReservedPeerMode::Deny,
+				bandwidth: NotificationBandwidth {
+					priority: NotificationPriority::Low,
+					..Default::default()
+				},
 			},
 			metrics,
 			peer_store_handle,
 		);
 
-		(Self { protocol_name, notification_service }, config)
+		(
+			Self {
+				protocol_name,
+				notification_service,
+				max_pending_transactions_per_peer: MAX_PENDING_TRANSACTIONS_PER_PEER,
+			},
+			config,
+		)
+	}
+
+	/// Sets the maximum number of pending transaction validations triggered by a single peer.
+	///
+	/// Further transactions received from the peer are ignored until some of its validations
+	/// finish. Defaults to `512`.
+	pub fn with_max_pending_transactions_per_peer(mut self, limit: usize) -> Self {
+		self.max_pending_transactions_per_peer = limit;
+		self
 	}
 
 	/// Turns the prototype into the actual handler. Returns a controller that allows controlling
@@ -195,6 +220,7 @@ impl TransactionsHandlerPrototype {
 				.fuse(),
 			pending_transactions: FuturesUnordered::new(),
 			pending_transactions_peers: HashMap::new(),
+			max_pending_transactions_per_peer: self.max_pending_transactions_per_peer,
 			network,
 			sync,
 			sync_event_stream: sync_event_stream.fuse(),
@@ -259,6 +285,8 @@ pub struct TransactionsHandler<
 	/// imported. This prevents that we import the same transaction
 	/// multiple times concurrently.
 	pending_transactions_peers: HashMap<H, Vec<PeerId>>,
+	/// Maximum number of pending transaction validations triggered by a single peer.
+	max_pending_transactions_per_peer: usize,
 	/// Network service to use to send messages and manage peers.
 	network: N,
 	/// Syncing service.
@@ -281,6 +309,8 @@ struct Peer<H: ExHashT> {
 	/// Holds a set of transactions known to this peer.
 	known_transactions: LruHashSet<H>,
 	role: ObservedRole,
+	/// Number of pending transaction validations triggered by this peer.
+	pending_transactions: usize,
 }
 
 impl<B, H, N, S> TransactionsHandler<B, H, N, S>
@@ -300,6 +330,10 @@ where
 				},
 				(tx_hash, result) = self.pending_transactions.select_next_some() => {
 					if let Some(peers) = self.pending_transactions_peers.remove(&tx_hash) {
+						// The first peer is the one that triggered the import.
+						if let Some(peer) = peers.first().and_then(|p| self.peers.get_mut(p)) {
+							peer.pending_transactions = peer.pending_transactions.saturating_sub(1);
+						}
 						peers.into_iter().for_each(|p| self.on_handle_transaction_import(p, result));
 					} else {
 						warn!(target: "sub-libp2p", "Inconsistent state, no peers for pending transaction!");
@@ -354,6 +388,7 @@ where
 							NonZeroUsize::new(MAX_KNOWN_TRANSACTIONS).expect("Constant is nonzero"),
 						),
 						role,
+						pending_transactions: 0,
 					},
 				);
 				debug_assert!(_was_in.is_none());
@@ -420,6 +455,16 @@ where
 					break
 				}
 
+				if peer.pending_transactions >= self.max_pending_transactions_per_peer {
+					debug!(
+						target: LOG_TARGET,
+						"Ignoring any further transactions from {} that exceed the per peer limit ({})",
+						who,
+						self.max_pending_transactions_per_peer,
+					);
+					break
+				}
+
 				let hash = self.transaction_pool.hash_of(&t);
 				peer.known_transactions.insert(hash.clone());
 
@@ -427,6 +472,7 @@ where
 
 				match self.pending_transactions_peers.entry(hash.clone()) {
 					Entry::Vacant(entry) => {
+						peer.pending_transactions += 1;
 						self.pending_transactions.push(PendingTransaction {
 							validation: self.transaction_pool.import(t),
 							tx_hash: hash,
@@ -536,3 +582,211 @@ where
 		self.transaction_pool.on_broadcasted(propagated_to);
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sc_network::{
+		config::{MultiaddrWithPeerId, NonDefaultSetConfig},
+		event::Event,
+		ReputationChange,
+	};
+	use sc_network_types::multiaddr::Multiaddr;
+	use sp_runtime::testing::{Block as RawBlock, MockCallU64, TestXt};
+	use std::collections::HashSet;
+
+	type Block = RawBlock<TestXt<MockCallU64, ()>>;
+
+	struct NoOpNetwork;
+
+	#[async_trait::async_trait]
+	impl NetworkPeers for NoOpNetwork {
+		fn set_authorized_peers(&self, _peers: HashSet<PeerId>) {
+			unimplemented!();
+		}
+
+		fn set_authorized_only(&self, _reserved_only: bool) {
+			unimplemented!();
+		}
+
+		fn add_known_address(&self, _peer_id: PeerId, _addr: Multiaddr) {
+			unimplemented!();
+		}
+
+		fn report_peer(&self, _peer_id: PeerId, _cost_benefit: ReputationChange) {}
+
+		fn peer_reputation(&self, _peer_id: &PeerId) -> i32 {
+			unimplemented!()
+		}
+
+		fn disconnect_peer(&self, _peer_id: PeerId, _protocol: ProtocolName) {
+			unimplemented!();
+		}
+
+		fn accept_unreserved_peers(&self) {
+			unimplemented!();
+		}
+
+		fn deny_unreserved_peers(&self) {
+			unimplemented!();
+		}
+
+		fn add_reserved_peer(&self, _peer: MultiaddrWithPeerId) -> Result<(), String> {
+			unimplemented!();
+		}
+
+		fn remove_reserved_peer(&self, _peer_id: PeerId) {
+			unimplemented!();
+		}
+
+		fn set_reserved_peers(
+			&self,
+			_protocol: ProtocolName,
+			_peers: HashSet<Multiaddr>,
+		) -> Result<(), String> {
+			unimplemented!();
+		}
+
+		fn add_peers_to_reserved_set(
+			&self,
+			_protocol: ProtocolName,
+			_peers: HashSet<Multiaddr>,
+		) -> Result<(), String> {
+			unimplemented!();
+		}
+
+		fn remove_peers_from_reserved_set(
+			&self,
+			_protocol: ProtocolName,
+			_peers: Vec<PeerId>,
+		) -> Result<(), String> {
+			unimplemented!();
+		}
+
+		fn sync_num_connected(&self) -> usize {
+			unimplemented!();
+		}
+
+		fn peer_role(&self, _peer_id: PeerId, _handshake: Vec<u8>) -> Option<ObservedRole> {
+			None
+		}
+
+		async fn reserved_peers(&self) -> Result<Vec<PeerId>, ()> {
+			unimplemented!();
+		}
+	}
+
+	impl NetworkEventStream for NoOpNetwork {
+		fn event_stream(&self, _name: &'static str) -> Pin<Box<dyn Stream<Item = Event> + Send>> {
+			unimplemented!();
+		}
+	}
+
+	struct NoOpSync;
+
+	impl SyncEventStream for NoOpSync {
+		fn event_stream(
+			&self,
+			_name: &'static str,
+		) -> Pin<Box<dyn Stream<Item = SyncEvent> + Send>> {
+			Box::pin(stream::pending())
+		}
+	}
+
+	impl sp_consensus::SyncOracle for NoOpSync {
+		fn is_major_syncing(&self) -> bool {
+			false
+		}
+
+		fn is_offline(&self) -> bool {
+			false
+		}
+	}
+
+	/// Transaction pool that never finishes the import of transactions.
+	struct PendingPool;
+
+	impl TransactionPool<u64, Block> for PendingPool {
+		fn transactions(&self) -> Vec<(u64, TestXt<MockCallU64, ()>)> {
+			Vec::new()
+		}
+
+		fn hash_of(&self, transaction: &TestXt<MockCallU64, ()>) -> u64 {
+			transaction.function.0
+		}
+
+		fn import(&self, _transaction: TestXt<MockCallU64, ()>) -> TransactionImportFuture {
+			Box::pin(future::pending())
+		}
+
+		fn on_broadcasted(&self, _propagations: HashMap<u64, Vec<String>>) {}
+
+		fn transaction(&self, _hash: &u64) -> Option<TestXt<MockCallU64, ()>> {
+			None
+		}
+	}
+
+	fn handler(
+		max_pending_transactions_per_peer: usize,
+	) -> TransactionsHandler<Block, u64, NoOpNetwork, NoOpSync> {
+		let protocol_name: ProtocolName = "/transactions/1".into();
+		let (_, notification_service) = NonDefaultSetConfig::new(
+			protocol_name.clone(),
+			Vec::new(),
+			MAX_TRANSACTIONS_SIZE,
+			None,
+			Default::default(),
+		);
+		let prototype = TransactionsHandlerPrototype {
+			protocol_name,
+			notification_service,
+			max_pending_transactions_per_peer: MAX_PENDING_TRANSACTIONS_PER_PEER,
+		};
+
+		prototype
+			.with_max_pending_transactions_per_peer(max_pending_transactions_per_peer)
+			.build(NoOpNetwork, NoOpSync, Arc::new(PendingPool), None)
+			.unwrap()
+			.0
+	}
+
+	fn add_peer(handler: &mut TransactionsHandler<Block, u64, NoOpNetwork, NoOpSync>) -> PeerId {
+		let peer = PeerId::random();
+		handler.peers.insert(
+			peer,
+			Peer {
+				known_transactions: LruHashSet::new(
+					NonZeroUsize::new(MAX_KNOWN_TRANSACTIONS).expect("Constant is nonzero"),
+				),
+				role: ObservedRole::Full,
+				pending_transactions: 0,
+			},
+		);
+		peer
+	}
+
+	fn transactions(range: std::ops::Range<u64>) -> Transactions<TestXt<MockCallU64, ()>> {
+		range.map(|i| TestXt::new_bare(MockCallU64(i))).collect()
+	}
+
+	#[test]
+	fn pending_transactions_are_limited_per_peer() {
+		let mut handler = handler(2);
+		let spammer = add_peer(&mut handler);
+		let honest = add_peer(&mut handler);
+
+		handler.on_transactions(spammer, transactions(0..5));
+		assert_eq!(handler.pending_transactions.len(), 2);
+		assert_eq!(handler.peers[&spammer].pending_transactions, 2);
+
+		// Transactions already being validated do not count against the limit of the peer.
+		handler.on_transactions(honest, transactions(1..4));
+		assert_eq!(handler.pending_transactions.len(), 3);
+		assert_eq!(handler.peers[&honest].pending_transactions, 1);
+		assert_eq!(handler.pending_transactions_peers[&1], vec![spammer, honest]);
+
+		handler.on_transactions(honest, transactions(4..5));
+		assert_eq!(handler.pending_transactions.len(), 4);
+		assert_eq!(handler.peers[&honest].pending_transactions, 2);
+	}
+}
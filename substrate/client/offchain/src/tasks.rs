@@ -0,0 +1,350 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Automatic submission of runtime tasks.
+//!
+//! Pallets declare service work with `#[pallet::tasks_experimental]`. The [`TaskRunner`] discovers
+//! the tasks that are currently valid through the [`TasksApi`] runtime API on every new best block
+//! and submits them as unsigned `do_task` transactions to the local transaction pool.
+//!
+//! Deduplication is left to the runtime: the `validate_unsigned` of `do_task` provides a tag
+//! derived from the task, so a task that is already pending in the pool is rejected instead of
+//! being queued twice, whichever node submitted it.
+
+use std::{
+	fmt,
+	marker::PhantomData,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc,
+	},
+};
+
+use codec::Codec;
+use futures::{future::ready, prelude::*};
+use sc_client_api::BlockchainEvents;
+use sc_transaction_pool_api::{
+	error::{Error as PoolError, IntoPoolError},
+	LocalTransactionPool,
+};
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_core::traits::SpawnNamed;
+use sp_offchain::TasksApi;
+use sp_runtime::traits;
+
+const LOG_TARGET: &str = "offchain-tasks";
+
+/// Default maximum number of tasks submitted on each new best block.
+pub const DEFAULT_MAX_TASKS_PER_BLOCK: u32 = 16;
+
+/// Options for [`TaskRunner`].
+pub struct TaskRunnerOptions<RA, Pool> {
+	/// Provides access to the runtime api.
+	pub runtime_api_provider: Arc<RA>,
+	/// The transaction pool the tasks are submitted to.
+	pub transaction_pool: Arc<Pool>,
+	/// Maximum number of tasks submitted on each new best block.
+	pub max_tasks_per_block: u32,
+}
+
+/// Outcome of submitting the valid tasks at a block.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TaskSubmissions {
+	/// Tasks newly added to the pool.
+	pub submitted: usize,
+	/// Tasks that are already pending in the pool.
+	pub already_pending: usize,
+	/// Tasks rejected by the pool for any other reason.
+	pub rejected: usize,
+}
+
+/// Submits the valid tasks of the runtime on every new best block.
+pub struct TaskRunner<RA, Block, Pool, Task> {
+	runtime_api_provider: Arc<RA>,
+	transaction_pool: Arc<Pool>,
+	max_tasks_per_block: u32,
+	_phantom: PhantomData<fn() -> (Block, Task)>,
+}
+
+impl<RA, Block, Pool, Task> TaskRunner<RA, Block, Pool, Task> {
+	/// Creates new [`TaskRunner`].
+	pub fn new(
+		TaskRunnerOptions { runtime_api_provider, transaction_pool, max_tasks_per_block }: TaskRunnerOptions<RA, Pool>,
+	) -> Self {
+		Self { runtime_api_provider, transaction_pool, max_tasks_per_block, _phantom: PhantomData }
+	}
+}
+
+impl<RA, Block, Pool, Task> fmt::Debug for TaskRunner<RA, Block, Pool, Task> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("TaskRunner")
+			.field("max_tasks_per_block", &self.max_tasks_per_block)
+			.finish()
+	}
+}
+
+impl<RA, Block, Pool, Task> TaskRunner<RA, Block, Pool, Task>
+where
+	Block: traits::Block,
+	RA: ProvideRuntimeApi<Block> + Send + Sync + 'static,
+	RA::Api: TasksApi<Block, Task>,
+	Pool: LocalTransactionPool<Block = Block> + 'static,
+	Task: Codec + fmt::Debug + 'static,
+{
+	/// Submit the valid tasks on every new best block.
+	///
+	/// The submission calls into the runtime and the transaction pool synchronously, so it is
+	/// spawned as a blocking task. Best blocks imported while the previous submission is still
+	/// running are skipped, the next best block catches up on their tasks.
+	pub async fn run<BE: BlockchainEvents<Block>>(
+		self,
+		import_events: Arc<BE>,
+		spawner: impl SpawnNamed,
+	) {
+		let runner = Arc::new(self);
+		let running = Arc::new(AtomicBool::new(false));
+		import_events
+			.import_notification_stream()
+			.for_each(move |n| {
+				if n.is_new_best {
+					if running.swap(true, Ordering::AcqRel) {
+						tracing::trace!(
+							target: LOG_TARGET,
+							"Skipping tasks for {:?}, previous submission still running",
+							n.hash,
+						);
+						return ready(())
+					}
+
+					let runner = runner.clone();
+					let running = RunningGuard(running.clone());
+					spawner.spawn_blocking(
+						"offchain-tasks-on-block",
+						Some("offchain-worker"),
+						async move {
+							runner.submit_valid_tasks(n.hash);
+							drop(running);
+						}
+						.boxed(),
+					);
+				} else {
+					tracing::trace!(
+						target: LOG_TARGET,
+						"Skipping tasks for non-canon block: {:?}",
+						n.hash,
+					)
+				}
+
+				ready(())
+			})
+			.await;
+	}
+
+	/// Returns whether the runtime at `at` exposes the [`TasksApi`].
+	pub fn supports_tasks(&self, at: Block::Hash) -> Result<bool, sp_api::ApiError> {
+		self.runtime_api_provider.runtime_api().has_api::<dyn TasksApi<Block, Task>>(at)
+	}
+
+	/// Submit the tasks that are valid at `at` to the transaction pool.
+	pub fn submit_valid_tasks(&self, at: Block::Hash) -> TaskSubmissions {
+		let mut submissions = TaskSubmissions::default();
+
+		match self.supports_tasks(at) {
+			Ok(true) => {},
+			Ok(false) => {
+				tracing::trace!(target: LOG_TARGET, "Runtime at {at:?} doesn't support tasks");
+				return submissions
+			},
+			Err(e) => {
+				tracing::debug!(target: LOG_TARGET, "Failed to check tasks api at {at:?}: {e}");
+				return submissions
+			},
+		}
+
+		let runtime = self.runtime_api_provider.runtime_api();
+		let tasks = match runtime.valid_tasks(at, self.max_tasks_per_block) {
+			Ok(tasks) => tasks,
+			Err(e) => {
+				tracing::error!(target: LOG_TARGET, "Failed to enumerate tasks at {at:?}: {e}");
+				return submissions
+			},
+		};
+
+		for task in tasks {
+			tracing::trace!(target: LOG_TARGET, "Submitting task {task:?} at {at:?}");
+			let xt = match runtime.task_extrinsic(at, task) {
+				Ok(Some(xt)) => xt,
+				Ok(None) => {
+					tracing::debug!(
+						target: LOG_TARGET,
+						"Runtime at {at:?} enumerates tasks but can't execute them",
+					);
+					break
+				},
+				Err(e) => {
+					tracing::error!(target: LOG_TARGET, "Failed to build task extrinsic: {e}");
+					break
+				},
+			};
+
+			match self.transaction_pool.submit_local(at, xt) {
+				Ok(_) => submissions.submitted += 1,
+				Err(e) => match e.into_pool_error() {
+					Ok(PoolError::AlreadyImported(_)) | Ok(PoolError::TooLowPriority { .. }) =>
+						submissions.already_pending += 1,
+					Ok(e) => {
+						tracing::debug!(target: LOG_TARGET, "Task rejected by the pool: {e}");
+						submissions.rejected += 1;
+					},
+					Err(e) => {
+						tracing::debug!(target: LOG_TARGET, "Task rejected by the pool: {e}");
+						submissions.rejected += 1;
+					},
+				},
+			}
+		}
+
+		if submissions != TaskSubmissions::default() {
+			tracing::debug!(target: LOG_TARGET, "Tasks at {at:?}: {submissions:?}");
+		}
+
+		submissions
+	}
+}
+
+/// Clears the running flag of [`TaskRunner::run`] once a submission ends, even if it panicked.
+struct RunningGuard(Arc<AtomicBool>);
+
+impl Drop for RunningGuard {
+	fn drop(&mut self) {
+		self.0.store(false, Ordering::Release);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use sc_transaction_pool::{BasicPool, FullChainApi};
+	use sc_transaction_pool_api::TransactionPool;
+	use sp_api::ApiRef;
+	use substrate_test_runtime_client::{
+		runtime::{Block, Extrinsic, ExtrinsicBuilder},
+		TestClient,
+	};
+
+	type TestPool = BasicPool<FullChainApi<TestClient, Block>, Block>;
+
+	/// Tasks that the runtime rejects.
+	const INVALID_TASK: u64 = 0;
+
+	#[derive(Clone, Default)]
+	struct TestApi {
+		tasks: Vec<u64>,
+	}
+
+	struct RuntimeApi {
+		inner: TestApi,
+	}
+
+	impl ProvideRuntimeApi<Block> for TestApi {
+		type Api = RuntimeApi;
+
+		fn runtime_api(&self) -> ApiRef<'_, Self::Api> {
+			RuntimeApi { inner: self.clone() }.into()
+		}
+	}
+
+	sp_api::mock_impl_runtime_apis! {
+		impl TasksApi<Block, u64> for RuntimeApi {
+			fn valid_tasks(&self, limit: u32) -> Vec<u64> {
+				self.inner.tasks.iter().take(limit as usize).cloned().collect()
+			}
+
+			fn task_extrinsic(task: u64) -> Option<Extrinsic> {
+				// `storage_change` is accepted unsigned and provides a tag derived from the call,
+				// same as `do_task`.
+				Some(if task == INVALID_TASK {
+					ExtrinsicBuilder::new_include_data(task.encode()).build()
+				} else {
+					ExtrinsicBuilder::new_storage_change(task.encode(), None).build()
+				})
+			}
+		}
+	}
+
+	fn runner(
+		tasks: Vec<u64>,
+		max_tasks_per_block: u32,
+	) -> (TaskRunner<TestApi, Block, TestPool, u64>, Arc<TestPool>, <Block as traits::Block>::Hash)
+	{
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let pool = Arc::new(BasicPool::new_full(
+			Default::default(),
+			true.into(),
+			None,
+			sp_core::testing::TaskExecutor::new(),
+			client.clone(),
+		));
+		let runner = TaskRunner::new(TaskRunnerOptions {
+			runtime_api_provider: Arc::new(TestApi { tasks }),
+			transaction_pool: pool.clone(),
+			max_tasks_per_block,
+		});
+
+		(runner, pool, client.chain_info().genesis_hash)
+	}
+
+	#[test]
+	fn tasks_support_is_detected() {
+		let (runner, _, at) = runner(vec![], 16);
+
+		assert!(runner.supports_tasks(at).unwrap());
+	}
+
+	#[test]
+	fn valid_tasks_are_submitted() {
+		let (runner, pool, at) = runner(vec![1, 2, 3], 2);
+
+		let submissions = runner.submit_valid_tasks(at);
+
+		assert_eq!(submissions, TaskSubmissions { submitted: 2, ..Default::default() });
+		assert_eq!(pool.status().ready, 2);
+	}
+
+	#[test]
+	fn pending_tasks_are_not_submitted_twice() {
+		let (runner, pool, at) = runner(vec![1, 2], 16);
+		runner.submit_valid_tasks(at);
+
+		let submissions = runner.submit_valid_tasks(at);
+
+		assert_eq!(submissions, TaskSubmissions { already_pending: 2, ..Default::default() });
+		assert_eq!(pool.status().ready, 2);
+	}
+
+	#[test]
+	fn rejected_tasks_are_reported() {
+		let (runner, pool, at) = runner(vec![1, INVALID_TASK], 16);
+
+		let submissions = runner.submit_valid_tasks(at);
+
+		assert_eq!(submissions, TaskSubmissions { submitted: 1, already_pending: 0, rejected: 1 });
+		assert_eq!(pool.status().ready, 1);
+	}
+}
@@ -98,6 +98,21 @@ pub trait SystemApi<Hash, Number> {
 	#[method(name = "system_reservedPeers")]
 	async fn system_reserved_peers(&self) -> Result<Vec<String>, Error>;
 
+	/// Ban a peer and disconnect from it, until it is unbanned. The ban persists across restarts.
+	/// The string should encode only the PeerId e.g.
+	/// `QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV`.
+	#[method(name = "system_banPeer", with_extensions)]
+	async fn system_ban_peer(&self, peer_id: String) -> Result<(), Error>;
+
+	/// Lift the ban of a peer banned with `system_banPeer`. Returns `false` if the peer wasn't
+	/// banned.
+	#[method(name = "system_unbanPeer", with_extensions)]
+	async fn system_unban_peer(&self, peer_id: String) -> Result<bool, Error>;
+
+	/// Returns the list of peers banned with `system_banPeer`.
+	#[method(name = "system_listBannedPeers", with_extensions)]
+	async fn system_list_banned_peers(&self) -> Result<Vec<String>, Error>;
+
 	/// Returns the roles the node is running as.
 	#[method(name = "system_nodeRoles")]
 	async fn system_node_roles(&self) -> Result<Vec<NodeRole>, Error>;
@@ -20,7 +20,9 @@
 
 use crate::{
 	common::events::{
-		ArchiveStorageDiffEvent, ArchiveStorageDiffItem, ArchiveStorageEvent, StorageQuery,
+		ArchiveStorageDiffEvent, ArchiveStorageDiffItem, ArchiveStorageDiffRangeEvent,
+		ArchiveStorageEvent, ArchiveTransactionLocation, ArchiveTransactionsByAccountResult,
+		StorageQuery,
 	},
 	MethodResult,
 };
@@ -60,6 +62,49 @@ pub trait ArchiveApi<Hash> {
 	#[method(name = "archive_unstable_header")]
 	fn archive_unstable_header(&self, hash: Hash) -> RpcResult<Option<String>>;
 
+	/// Get the blocks that include the transaction with the given hash.
+	///
+	/// Returns an array (possibly empty) of objects containing the hexadecimal-encoded hash of
+	/// a block and the index of the transaction in the body of that block. Blocks whose body was
+	/// pruned are not returned.
+	///
+	/// The node must be started with the extrinsic index enabled, otherwise an error is returned.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and subject to change in the future.
+	#[method(name = "archive_unstable_transactionByHash")]
+	fn archive_unstable_transaction_by_hash(
+		&self,
+		hash: Hash,
+	) -> RpcResult<Vec<ArchiveTransactionLocation>>;
+
+	/// Get the blocks that include transactions signed by the given account.
+	///
+	/// The account is the hexadecimal-encoded SCALE-encoded signer of the transactions, as it
+	/// appears in the transactions. Returns an object with an array (possibly empty) of objects
+	/// containing the hexadecimal-encoded hash of a block and the index of the transaction in the
+	/// body of that block, in ascending order of block numbers. Blocks whose body was pruned are
+	/// not returned.
+	///
+	/// At most `limit` transactions are returned, and never more than a server-side maximum. If
+	/// more transactions follow, the object contains a `nextStartKey`, to be passed as
+	/// `startKey` to fetch them.
+	///
+	/// The node must be started with the extrinsic index enabled and must index transactions by
+	/// signer, otherwise an error is returned.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and subject to change in the future.
+	#[method(name = "archive_unstable_transactionsByAccount")]
+	fn archive_unstable_transactions_by_account(
+		&self,
+		account: String,
+		limit: Option<usize>,
+		start_key: Option<String>,
+	) -> RpcResult<ArchiveTransactionsByAccountResult>;
+
 	/// Get the height of the current finalized block.
 	///
 	/// Returns an integer height of the current finalized block of the chain.
@@ -127,4 +172,40 @@ pub trait ArchiveApi<Hash> {
 		items: Vec<ArchiveStorageDiffItem<String>>,
 		previous_hash: Option<Hash>,
 	);
+
+	/// Returns the storage differences of each block of a range, relative to its parent.
+	///
+	/// The range starts after the `from_hash` block and ends with the `hash` block, which must
+	/// be a descendant of `from_hash`. The differences of each block are followed by a
+	/// `blockDone` event. When no items are provided, the differences of the child tries are
+	/// reported as well.
+	///
+	/// The server periodically produces a `waitingForContinue` event, and resumes producing
+	/// events once `archive_unstable_storageDiffRange_continue` is called.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and can change in minor or patch releases.
+	#[subscription(
+		name = "archive_unstable_storageDiffRange" => "archive_unstable_storageDiffRangeEvent",
+		unsubscribe = "archive_unstable_storageDiffRange_stopStorageDiffRange",
+		item = ArchiveStorageDiffRangeEvent,
+	)]
+	fn archive_unstable_storage_diff_range(
+		&self,
+		from_hash: Hash,
+		hash: Hash,
+		items: Vec<ArchiveStorageDiffItem<String>>,
+	);
+
+	/// Resume an `archive_unstable_storageDiffRange` subscription that produced a
+	/// `waitingForContinue` event.
+	///
+	/// Returns an error if the subscription does not exist or belongs to another connection.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and can change in minor or patch releases.
+	#[method(name = "archive_unstable_storageDiffRange_continue", with_extensions)]
+	fn archive_unstable_storage_diff_range_continue(&self, subscription: String) -> RpcResult<()>;
 }
@@ -22,9 +22,12 @@ use crate::{
 	archive::{
 		archive_storage::ArchiveStorageDiff, error::Error as ArchiveError, ArchiveApiServer,
 	},
+	chain_head::chain_head::read_subscription_id_as_string,
 	common::{
 		events::{
-			ArchiveStorageDiffEvent, ArchiveStorageDiffItem, ArchiveStorageEvent, StorageQuery,
+			ArchiveStorageDiffEvent, ArchiveStorageDiffItem, ArchiveStorageDiffRangeEvent,
+			ArchiveStorageEvent, ArchiveTransactionLocation, ArchiveTransactionsByAccountResult,
+			StorageQuery,
 		},
 		storage::{QueryResult, StorageSubscriptionClient},
 	},
@@ -35,8 +38,9 @@ use codec::Encode;
 use futures::FutureExt;
 use jsonrpsee::{
 	core::{async_trait, RpcResult},
-	PendingSubscriptionSink,
+	ConnectionId, Extensions, PendingSubscriptionSink,
 };
+use parking_lot::Mutex;
 use sc_client_api::{
 	Backend, BlockBackend, BlockchainEvents, CallExecutor, ChildInfo, ExecutorProvider, StorageKey,
 	StorageProvider,
@@ -51,7 +55,11 @@ use sp_runtime::{
 	traits::{Block as BlockT, Header as HeaderT, NumberFor},
 	SaturatedConversion,
 };
-use std::{collections::HashSet, marker::PhantomData, sync::Arc};
+use std::{
+	collections::{HashMap, HashSet},
+	marker::PhantomData,
+	sync::Arc,
+};
 
 use tokio::sync::mpsc;
 
@@ -63,6 +71,15 @@ pub(crate) const LOG_TARGET: &str = "rpc-spec-v2::archive";
 /// its down buffer capacity per connection as well.
 const STORAGE_QUERY_BUF: usize = 16;
 
+/// The maximum number of blocks of an `archive_storageDiffRange` subscription.
+const MAX_STORAGE_DIFF_RANGE: u64 = 100_000;
+
+/// The maximum number of transactions returned by one `archive_transactionsByAccount` call.
+const MAX_TRANSACTIONS_BY_ACCOUNT: usize = 1024;
+
+/// The `archive_storageDiffRange` subscriptions that can be continued, by subscription ID.
+type StorageDiffRangeOperations = Arc<Mutex<HashMap<String, (ConnectionId, mpsc::Sender<()>)>>>;
+
 /// An API for archive RPC calls.
 pub struct Archive<BE: Backend<Block>, Block: BlockT, Client> {
 	/// Substrate client.
@@ -73,6 +90,8 @@ pub struct Archive<BE: Backend<Block>, Block: BlockT, Client> {
 	executor: SubscriptionTaskExecutor,
 	/// The hexadecimal encoded hash of the genesis block.
 	genesis_hash: String,
+	/// The active `archive_storageDiffRange` subscriptions.
+	storage_diff_range_operations: StorageDiffRangeOperations,
 	/// Phantom member to pin the block type.
 	_phantom: PhantomData<Block>,
 }
@@ -86,7 +105,14 @@ impl<BE: Backend<Block>, Block: BlockT, Client> Archive<BE, Block, Client> {
 		executor: SubscriptionTaskExecutor,
 	) -> Self {
 		let genesis_hash = hex_string(&genesis_hash.as_ref());
-		Self { client, backend, executor, genesis_hash, _phantom: PhantomData }
+		Self {
+			client,
+			backend,
+			executor,
+			genesis_hash,
+			storage_diff_range_operations: Default::default(),
+			_phantom: PhantomData,
+		}
 	}
 }
 
@@ -102,6 +128,59 @@ fn parse_hex_param(param: String) -> Result<Vec<u8>, ArchiveError> {
 	array_bytes::hex2bytes(&param).map_err(|_| ArchiveError::InvalidParam(param))
 }
 
+/// Convert the `(block hash, extrinsic index)` pairs of the extrinsic index to RPC responses.
+fn to_transaction_locations<Block: BlockT>(
+	locations: Vec<(Block::Hash, u32)>,
+) -> Vec<ArchiveTransactionLocation> {
+	locations
+		.into_iter()
+		.map(|(block_hash, index)| ArchiveTransactionLocation {
+			block_hash: hex_string(&block_hash.as_ref()),
+			index,
+		})
+		.collect()
+}
+
+/// Collect the `(parent hash, hash)` pairs of the blocks after `from_hash`, up to and including
+/// `hash`, in ascending order.
+///
+/// Returns an error if `hash` is not a descendant of `from_hash`, or if the range is larger than
+/// [`MAX_STORAGE_DIFF_RANGE`].
+fn block_range<Block: BlockT, Client: HeaderBackend<Block>>(
+	client: &Client,
+	from_hash: Block::Hash,
+	hash: Block::Hash,
+) -> Result<Vec<(Block::Hash, Block::Hash)>, String> {
+	let header = |hash: Block::Hash| match client.header(hash) {
+		Ok(Some(header)) => Ok(header),
+		Ok(None) => Err(format!("Block header is not present: {hash}")),
+		Err(error) => Err(error.to_string()),
+	};
+
+	let from_number = *header(from_hash)?.number();
+	let mut current = header(hash)?;
+	if *current.number() < from_number {
+		return Err(format!("Block {hash} is not a descendant of {from_hash}"))
+	}
+	if (*current.number() - from_number).saturated_into::<u64>() > MAX_STORAGE_DIFF_RANGE {
+		return Err(format!("Block range is larger than {MAX_STORAGE_DIFF_RANGE} blocks"))
+	}
+
+	let mut blocks = Vec::new();
+	while *current.number() > from_number {
+		let parent_hash = *current.parent_hash();
+		blocks.push((parent_hash, current.hash()));
+		current = header(parent_hash)?;
+	}
+
+	if current.hash() != from_hash {
+		return Err(format!("Block {hash} is not a descendant of {from_hash}"))
+	}
+
+	blocks.reverse();
+	Ok(blocks)
+}
+
 #[async_trait]
 impl<BE, Block, Client> ArchiveApiServer<Block::Hash> for Archive<BE, Block, Client>
 where
@@ -140,6 +219,42 @@ where
 		Ok(Some(hex_string(&header.encode())))
 	}
 
+	fn archive_unstable_transaction_by_hash(
+		&self,
+		hash: Block::Hash,
+	) -> RpcResult<Vec<ArchiveTransactionLocation>> {
+		let locations = self
+			.client
+			.extrinsic_locations(hash)
+			.map_err(|error| ArchiveError::FetchTransactionIndex(error.to_string()))?
+			.ok_or(ArchiveError::TransactionIndexDisabled)?;
+
+		Ok(to_transaction_locations::<Block>(locations))
+	}
+
+	fn archive_unstable_transactions_by_account(
+		&self,
+		account: String,
+		limit: Option<usize>,
+		start_key: Option<String>,
+	) -> RpcResult<ArchiveTransactionsByAccountResult> {
+		let account = parse_hex_param(account)?;
+		let start_key = start_key.map(parse_hex_param).transpose()?;
+		let limit = limit
+			.unwrap_or(MAX_TRANSACTIONS_BY_ACCOUNT)
+			.clamp(1, MAX_TRANSACTIONS_BY_ACCOUNT);
+		let (locations, next_start_key) = self
+			.client
+			.signer_extrinsic_locations(&account, start_key.as_deref(), limit)
+			.map_err(|error| ArchiveError::FetchTransactionIndex(error.to_string()))?
+			.ok_or(ArchiveError::TransactionIndexDisabled)?;
+
+		Ok(ArchiveTransactionsByAccountResult {
+			transactions: to_transaction_locations::<Block>(locations),
+			next_start_key: next_start_key.map(|key| hex_string(&key)),
+		})
+	}
+
 	fn archive_unstable_finalized_height(&self) -> RpcResult<u64> {
 		Ok(self.client.info().finalized_number.saturated_into())
 	}
@@ -306,6 +421,103 @@ where
 
 		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
 	}
+
+	fn archive_unstable_storage_diff_range(
+		&self,
+		pending: PendingSubscriptionSink,
+		from_hash: Block::Hash,
+		hash: Block::Hash,
+		items: Vec<ArchiveStorageDiffItem<String>>,
+	) {
+		let storage_client = ArchiveStorageDiff::new(self.client.clone());
+		let client = self.client.clone();
+		let operations = self.storage_diff_range_operations.clone();
+		let connection_id = pending.connection_id();
+
+		let fut = async move {
+			let Ok(mut sink) = pending.accept().await.map(Subscription::from) else { return };
+
+			// Walking the headers of a large range is blocking work.
+			let blocks =
+				tokio::task::spawn_blocking(move || block_range(&*client, from_hash, hash))
+					.await
+					.unwrap_or_else(|error| Err(error.to_string()));
+			let blocks = match blocks {
+				Ok(blocks) => blocks,
+				Err(error) => {
+					let _ = sink.send(&ArchiveStorageDiffRangeEvent::err(error)).await;
+					return
+				},
+			};
+
+			let sub_id = read_subscription_id_as_string(&sink);
+			let (continue_tx, continue_rx) = mpsc::channel(1);
+			operations.lock().insert(sub_id.clone(), (connection_id, continue_tx));
+
+			let (tx, rx) = tokio::sync::mpsc::channel(STORAGE_QUERY_BUF);
+			let storage_fut = storage_client.handle_range_queries(blocks, items, tx, continue_rx);
+			let events_fut = async {
+				process_storage_diff_range_events(rx, &mut sink).await;
+				// Dropping the continue sender stops the storage query if it is paused.
+				operations.lock().remove(&sub_id);
+			};
+
+			let _ = futures::future::join(storage_fut, events_fut).await;
+		};
+
+		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
+	}
+
+	fn archive_unstable_storage_diff_range_continue(
+		&self,
+		ext: &Extensions,
+		subscription: String,
+	) -> RpcResult<()> {
+		let conn_id = ext
+			.get::<ConnectionId>()
+			.copied()
+			.expect("ConnectionId is always set by jsonrpsee; qed");
+
+		match self.storage_diff_range_operations.lock().get(&subscription) {
+			Some((id, continue_tx)) if *id == conn_id => {
+				let _ = continue_tx.try_send(());
+				Ok(())
+			},
+			_ => Err(ArchiveError::InvalidSubscriptionId(subscription).into()),
+		}
+	}
+}
+
+/// Sends all the events of the storage_diff_range method to the sink.
+///
+/// The receiver is dropped when the sink is closed, which stops the producer.
+async fn process_storage_diff_range_events(
+	mut rx: mpsc::Receiver<ArchiveStorageDiffRangeEvent>,
+	sink: &mut Subscription,
+) {
+	loop {
+		tokio::select! {
+			_ = sink.closed() => {
+				return
+			},
+
+			maybe_event = rx.recv() => {
+				let Some(event) = maybe_event else {
+					break;
+				};
+
+				if event.is_done() {
+					log::debug!(target: LOG_TARGET, "Finished processing storage diff range");
+				} else if event.is_err() {
+					log::debug!(target: LOG_TARGET, "Error encountered while processing storage diff range");
+				}
+
+				if sink.send(&event).await.is_err() {
+					return
+				}
+			}
+		}
+	}
 }
 
 /// Sends all the events of the storage_diff method to the sink.
@@ -19,12 +19,13 @@
 //! Implementation of the `archive_storage` method.
 
 use std::{
-	collections::{hash_map::Entry, HashMap},
+	collections::{hash_map::Entry, HashMap, VecDeque},
 	sync::Arc,
 };
 
 use itertools::Itertools;
 use sc_client_api::{Backend, ChildInfo, StorageKey, StorageProvider};
+use sp_core::storage::well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX;
 use sp_runtime::traits::Block as BlockT;
 
 use super::error::Error as ArchiveError;
@@ -33,13 +34,22 @@ use crate::{
 	common::{
 		events::{
 			ArchiveStorageDiffEvent, ArchiveStorageDiffItem, ArchiveStorageDiffOperationType,
-			ArchiveStorageDiffResult, ArchiveStorageDiffType, StorageResult,
+			ArchiveStorageDiffRangeBlock, ArchiveStorageDiffRangeEvent,
+			ArchiveStorageDiffRangeResult, ArchiveStorageDiffResult, ArchiveStorageDiffType,
+			StorageResult,
 		},
 		storage::Storage,
 	},
+	hex_string,
 };
 use tokio::sync::mpsc;
 
+/// The number of storage differences reported by `archive_storageDiffRange` before waiting for
+/// the `archive_storageDiffRange_continue` method to be called.
+///
+/// The server only pauses at block boundaries, so more differences may be reported.
+pub const STORAGE_DIFF_RANGE_ITEMS_BEFORE_CONTINUE: usize = 1024;
+
 /// Parse hex-encoded string parameter as raw bytes.
 ///
 /// If the parsing fails, returns an error propagated to the RPC method.
@@ -169,11 +179,11 @@ where
 		}
 	}
 
-	/// Send the provided result to the `tx` sender.
+	/// Send the provided result to the `on_result` callback.
 	///
-	/// Returns `false` if the sender has been closed.
+	/// Returns `false` if the callback reported that the receiver has been closed.
 	fn send_result(
-		tx: &mpsc::Sender<ArchiveStorageDiffEvent>,
+		on_result: &mut impl FnMut(ArchiveStorageDiffResult) -> bool,
 		result: FetchedStorage,
 		operation_type: ArchiveStorageDiffOperationType,
 		child_trie_key: Option<String>,
@@ -185,13 +195,13 @@ where
 		};
 
 		for item in items {
-			let res = ArchiveStorageDiffEvent::StorageDiff(ArchiveStorageDiffResult {
+			let res = ArchiveStorageDiffResult {
 				key: item.key,
 				result: item.result,
 				operation_type,
 				child_trie_key: child_trie_key.clone(),
-			});
-			if tx.blocking_send(res).is_err() {
+			};
+			if !on_result(res) {
 				return false
 			}
 		}
@@ -199,13 +209,17 @@ where
 		true
 	}
 
+	/// Compare the keys of a single trie between the two blocks and report the differences
+	/// matching the query items to `on_result`.
+	///
+	/// Returns `Ok(false)` if `on_result` reported that the receiver has been closed.
 	fn handle_trie_queries_inner(
 		&self,
 		hash: Block::Hash,
 		previous_hash: Block::Hash,
 		items: Vec<DiffDetails>,
-		tx: &mpsc::Sender<ArchiveStorageDiffEvent>,
-	) -> Result<(), String> {
+		on_result: &mut impl FnMut(ArchiveStorageDiffResult) -> bool,
+	) -> Result<bool, String> {
 		// Parse the child trie key as `ChildInfo` and `String`.
 		let maybe_child_trie = items.first().and_then(|item| item.child_trie_key.clone());
 		let maybe_child_trie_str =
@@ -273,17 +287,17 @@ where
 
 			if let Some(storage_result) = maybe_result {
 				if !Self::send_result(
-					&tx,
+					on_result,
 					storage_result,
 					operation_type,
 					maybe_child_trie_str.clone(),
 				) {
-					return Ok(())
+					return Ok(false)
 				}
 			}
 		}
 
-		Ok(())
+		Ok(true)
 	}
 
 	/// This method will iterate over the keys of the main trie or a child trie and fetch the
@@ -322,7 +336,10 @@ where
 					items
 				);
 
-				let result = this.handle_trie_queries_inner(hash, previous_hash, items, &tx);
+				let result =
+					this.handle_trie_queries_inner(hash, previous_hash, items, &mut |result| {
+						tx.blocking_send(ArchiveStorageDiffEvent::StorageDiff(result)).is_ok()
+					});
 
 				if let Err(error) = result {
 					log::trace!(
@@ -350,6 +367,128 @@ where
 	}
 }
 
+impl<Client, Block, BE> ArchiveStorageDiff<Client, Block, BE>
+where
+	Block: BlockT + 'static,
+	BE: Backend<Block> + 'static,
+	Client: StorageProvider<Block, BE> + Send + Sync + 'static,
+{
+	/// Report the storage differences of each block relative to its parent.
+	///
+	/// The blocks are provided as `(parent hash, hash)` pairs, in the order they are reported.
+	/// When no query items are provided, the differences of all the keys are reported, including
+	/// the keys of the child tries whose root changed.
+	///
+	/// After reporting [`STORAGE_DIFF_RANGE_ITEMS_BEFORE_CONTINUE`] differences, a
+	/// `waitingForContinue` event is sent at the next block boundary, and no more events are
+	/// produced until `continue_rx` is notified.
+	pub async fn handle_range_queries(
+		&self,
+		blocks: Vec<(Block::Hash, Block::Hash)>,
+		items: Vec<ArchiveStorageDiffItem<String>>,
+		tx: mpsc::Sender<ArchiveStorageDiffRangeEvent>,
+		mut continue_rx: mpsc::Receiver<()>,
+	) -> Result<(), tokio::task::JoinError> {
+		let this = ArchiveStorageDiff { client: self.client.clone() };
+
+		tokio::task::spawn_blocking(move || {
+			let all_keys = items.is_empty();
+			let mut trie_items = match deduplicate_storage_diff_items(items) {
+				Ok(items) => items,
+				Err(error) => {
+					let _ = tx.blocking_send(ArchiveStorageDiffRangeEvent::err(error.to_string()));
+					return
+				},
+			};
+			// Default to using the main storage trie if no items are provided.
+			if trie_items.is_empty() {
+				trie_items.push(Vec::new());
+			}
+
+			let mut reported = 0;
+			for (previous_hash, hash) in blocks {
+				let block_hash = hex_string(&hash.as_ref());
+				let mut queries: VecDeque<_> = trie_items.clone().into();
+
+				while let Some(items) = queries.pop_front() {
+					let mut changed_child_tries = Vec::new();
+					let result =
+						this.handle_trie_queries_inner(hash, previous_hash, items, &mut |result| {
+							if all_keys && result.child_trie_key.is_none() {
+								changed_child_tries.extend(child_storage_key(&result.key));
+							}
+							reported += 1;
+							let event = ArchiveStorageDiffRangeEvent::StorageDiff(
+								ArchiveStorageDiffRangeResult {
+									block_hash: block_hash.clone(),
+									result,
+								},
+							);
+							tx.blocking_send(event).is_ok()
+						});
+
+					match result {
+						Ok(true) => {},
+						// The receiver has been closed.
+						Ok(false) => return,
+						Err(error) => {
+							log::trace!(
+								target: LOG_TARGET,
+								"handle_range_queries: sending error={:?}",
+								error,
+							);
+							let _ = tx.blocking_send(ArchiveStorageDiffRangeEvent::err(error));
+							return
+						},
+					}
+
+					queries.extend(changed_child_tries.into_iter().map(|child_key| {
+						vec![DiffDetails {
+							key: StorageKey(Vec::new()),
+							return_type: ArchiveStorageDiffType::Value,
+							child_trie_key_string: Some(hex_string(&child_key)),
+							child_trie_key: Some(ChildInfo::new_default_from_vec(child_key)),
+						}]
+					}));
+				}
+
+				let event = ArchiveStorageDiffRangeEvent::BlockDone(ArchiveStorageDiffRangeBlock {
+					block_hash,
+				});
+				if tx.blocking_send(event).is_err() {
+					return
+				}
+
+				if reported >= STORAGE_DIFF_RANGE_ITEMS_BEFORE_CONTINUE {
+					reported = 0;
+					if tx.blocking_send(ArchiveStorageDiffRangeEvent::WaitingForContinue).is_err() {
+						return
+					}
+					// The operation was stopped.
+					if continue_rx.blocking_recv().is_none() {
+						return
+					}
+				}
+			}
+
+			let _ = tx.blocking_send(ArchiveStorageDiffRangeEvent::StorageDiffDone);
+		})
+		.await?;
+
+		Ok(())
+	}
+}
+
+/// Extract the storage key of a default child trie from the hex-encoded key of its root in the
+/// main trie.
+///
+/// Returns `None` if the key is not the root of a default child trie.
+fn child_storage_key(key: &str) -> Option<Vec<u8>> {
+	let key = parse_hex_param(key.to_string()).ok()?;
+	let child_key = key.strip_prefix(DEFAULT_CHILD_STORAGE_KEY_PREFIX)?;
+	Some(child_key.to_vec())
+}
+
 /// The result of the `lexicographic_diff` method.
 #[derive(Debug, PartialEq)]
 enum Diff<T> {
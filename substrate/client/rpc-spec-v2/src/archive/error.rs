@@ -32,6 +32,15 @@ pub enum Error {
 	/// Failed to fetch leaves.
 	#[error("Failed to fetch leaves of the chain: {0}")]
 	FetchLeaves(String),
+	/// The node does not index transactions.
+	#[error("Transactions are not indexed by this node")]
+	TransactionIndexDisabled,
+	/// Failed to fetch the locations of a transaction.
+	#[error("Failed to fetch the transaction index: {0}")]
+	FetchTransactionIndex(String),
+	/// The subscription does not exist, or belongs to another connection.
+	#[error("Invalid subscription ID: {0}")]
+	InvalidSubscriptionId(String),
 }
 
 // Base code for all `archive` errors.
@@ -42,6 +51,12 @@ const INVALID_PARAM_ERROR: i32 = BASE_ERROR + 1;
 const RUNTIME_CALL_ERROR: i32 = BASE_ERROR + 2;
 /// Failed to fetch leaves.
 const FETCH_LEAVES_ERROR: i32 = BASE_ERROR + 3;
+/// Transaction index disabled error.
+const TRANSACTION_INDEX_DISABLED_ERROR: i32 = BASE_ERROR + 4;
+/// Failed to fetch the transaction index.
+const FETCH_TRANSACTION_INDEX_ERROR: i32 = BASE_ERROR + 5;
+/// Invalid subscription ID error.
+const INVALID_SUBSCRIPTION_ID_ERROR: i32 = BASE_ERROR + 6;
 
 impl From<Error> for ErrorObject<'static> {
 	fn from(e: Error) -> Self {
@@ -51,6 +66,12 @@ impl From<Error> for ErrorObject<'static> {
 			Error::InvalidParam(_) => ErrorObject::owned(INVALID_PARAM_ERROR, msg, None::<()>),
 			Error::RuntimeCall(_) => ErrorObject::owned(RUNTIME_CALL_ERROR, msg, None::<()>),
 			Error::FetchLeaves(_) => ErrorObject::owned(FETCH_LEAVES_ERROR, msg, None::<()>),
+			Error::TransactionIndexDisabled =>
+				ErrorObject::owned(TRANSACTION_INDEX_DISABLED_ERROR, msg, None::<()>),
+			Error::FetchTransactionIndex(_) =>
+				ErrorObject::owned(FETCH_TRANSACTION_INDEX_ERROR, msg, None::<()>),
+			Error::InvalidSubscriptionId(_) =>
+				ErrorObject::owned(INVALID_SUBSCRIPTION_ID_ERROR, msg, None::<()>),
 		}
 		.into()
 	}
@@ -19,13 +19,15 @@
 use crate::{
 	common::events::{
 		ArchiveStorageDiffEvent, ArchiveStorageDiffItem, ArchiveStorageDiffOperationType,
-		ArchiveStorageDiffResult, ArchiveStorageDiffType, ArchiveStorageEvent, StorageQuery,
+		ArchiveStorageDiffRangeBlock, ArchiveStorageDiffRangeEvent, ArchiveStorageDiffRangeResult,
+		ArchiveStorageDiffResult, ArchiveStorageDiffType, ArchiveStorageEvent,
+		ArchiveTransactionLocation, ArchiveTransactionsByAccountResult, StorageQuery,
 		StorageQueryType, StorageResult, StorageResultType,
 	},
 	hex_string, MethodResult,
 };
 
-use super::{archive::Archive, *};
+use super::{archive::Archive, archive_storage::STORAGE_DIFF_RANGE_ITEMS_BEFORE_CONTINUE, *};
 
 use assert_matches::assert_matches;
 use codec::{Decode, Encode};
@@ -36,14 +38,19 @@ use jsonrpsee::{
 
 use sc_block_builder::BlockBuilderBuilder;
 use sc_client_api::ChildInfo;
+use sc_consensus::{
+	BlockImport, BlockImportParams, ForkChoiceStrategy, StateAction, StorageChanges,
+};
 use sc_rpc::testing::TokioTestExecutor;
+use sc_service::BlocksPruning;
 use sp_blockchain::HeaderBackend;
 use sp_consensus::BlockOrigin;
 use sp_core::{Blake2Hasher, Hasher};
 use sp_runtime::{
-	traits::{Block as BlockT, Header as HeaderT},
-	SaturatedConversion,
+	traits::{Block as BlockT, HashingFor, Header as HeaderT},
+	SaturatedConversion, StateVersion,
 };
+use sp_state_machine::OverlayedChanges;
 use std::{collections::HashMap, sync::Arc};
 use substrate_test_runtime::Transfer;
 use substrate_test_runtime_client::{
@@ -81,6 +88,43 @@ fn setup_api() -> (Arc<Client<Backend>>, RpcModule<Archive<Backend, Block, Clien
 	(client, api)
 }
 
+/// Import a block without extrinsics on top of `parent_hash`, applying the given changes of the
+/// main trie and of the child trie created in [`setup_api`] to the state of its parent.
+async fn import_block_with_changes(
+	client: &Client<Backend>,
+	parent_hash: <Block as BlockT>::Hash,
+	main_changes: Vec<(Vec<u8>, Vec<u8>)>,
+	child_changes: Vec<(Vec<u8>, Vec<u8>)>,
+) -> <Block as BlockT>::Hash {
+	let parent_number = client.number(parent_hash).unwrap().unwrap();
+	let child_info = ChildInfo::new_default(CHILD_STORAGE_KEY);
+
+	let mut overlay = OverlayedChanges::<HashingFor<Block>>::default();
+	for (key, value) in main_changes {
+		overlay.set_storage(key, Some(value));
+	}
+	for (key, value) in child_changes {
+		overlay.set_child_storage(&child_info, key, Some(value));
+	}
+	let state = client.state_at(parent_hash).unwrap();
+	let changes = overlay.drain_storage_changes(&state, StateVersion::V1).unwrap();
+
+	let header = Header::new(
+		parent_number + 1,
+		Default::default(),
+		changes.transaction_storage_root,
+		parent_hash,
+		Default::default(),
+	);
+	let mut import = BlockImportParams::new(BlockOrigin::Own, header);
+	import.body = Some(Vec::new());
+	import.state_action = StateAction::ApplyChanges(StorageChanges::Changes(changes));
+	import.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+	let hash = import.post_hash();
+	client.import_block(import).await.unwrap();
+	hash
+}
+
 async fn get_next_event<T: serde::de::DeserializeOwned>(sub: &mut RpcSubscription) -> T {
 	let (event, _sub_id) = tokio::time::timeout(std::time::Duration::from_secs(60), sub.next())
 		.await
@@ -133,6 +177,171 @@ async fn archive_body() {
 	assert_eq!(vec![expected_tx], body);
 }
 
+#[tokio::test]
+async fn archive_transaction_by_hash() {
+	let (_, api) = setup_api();
+
+	// The transaction index is disabled by default.
+	let err = api
+		.call::<_, Vec<ArchiveTransactionLocation>>(
+			"archive_unstable_transactionByHash",
+			[hex_string(&INVALID_HASH)],
+		)
+		.await
+		.unwrap_err();
+	assert_matches!(err,
+		Error::JsonRpc(ref err) if err.message() == "Transactions are not indexed by this node"
+	);
+
+	let backend = Arc::new(Backend::new_test_with_extrinsic_index(BlocksPruning::KeepAll, 0, None));
+	let client = Arc::new(TestClientBuilder::with_backend(backend.clone()).build());
+	let api = Archive::new(
+		client.clone(),
+		backend,
+		CHAIN_GENESIS,
+		Arc::new(TokioTestExecutor::default()),
+	)
+	.into_rpc();
+
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(client.chain_info().genesis_hash)
+		.with_parent_block_number(0)
+		.build()
+		.unwrap();
+	builder
+		.push_transfer(runtime::Transfer {
+			from: Sr25519Keyring::Alice.into(),
+			to: Sr25519Keyring::Ferdie.into(),
+			amount: 42,
+			nonce: 0,
+		})
+		.unwrap();
+	let block = builder.build().unwrap().block;
+	let block_hash = format!("{:?}", block.header.hash());
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	let tx_hash = format!("{:?}", Blake2Hasher::hash(&block.extrinsics[0].encode()));
+	let locations: Vec<ArchiveTransactionLocation> =
+		api.call("archive_unstable_transactionByHash", [tx_hash]).await.unwrap();
+	assert_eq!(locations, vec![ArchiveTransactionLocation { block_hash, index: 0 }]);
+
+	// Unknown transactions are not included by any block.
+	let locations: Vec<ArchiveTransactionLocation> = api
+		.call("archive_unstable_transactionByHash", [hex_string(&INVALID_HASH)])
+		.await
+		.unwrap();
+	assert!(locations.is_empty());
+}
+
+#[tokio::test]
+async fn archive_transactions_by_account() {
+	let (_, api) = setup_api();
+
+	// The transaction index is disabled by default.
+	let alice = hex_string(&Sr25519Keyring::Alice.public().encode());
+	let err = api
+		.call::<_, ArchiveTransactionsByAccountResult>(
+			"archive_unstable_transactionsByAccount",
+			[alice.clone()],
+		)
+		.await
+		.unwrap_err();
+	assert_matches!(err,
+		Error::JsonRpc(ref err) if err.message() == "Transactions are not indexed by this node"
+	);
+
+	let signer: sc_service::ExtrinsicSignerFn =
+		Arc::new(|xt: &[u8]| match runtime::Extrinsic::decode(&mut &xt[..]).ok()?.preamble {
+			sp_runtime::generic::Preamble::Signed(address, ..) => Some(address.encode()),
+			_ => None,
+		});
+	let backend =
+		Arc::new(Backend::new_test_with_extrinsic_index(BlocksPruning::KeepAll, 0, Some(signer)));
+	let client = Arc::new(TestClientBuilder::with_backend(backend.clone()).build());
+	let api = Archive::new(
+		client.clone(),
+		backend,
+		CHAIN_GENESIS,
+		Arc::new(TokioTestExecutor::default()),
+	)
+	.into_rpc();
+
+	let mut parent_hash = client.chain_info().genesis_hash;
+	let mut block_hashes = Vec::new();
+	for (number, transfers) in [
+		vec![(Sr25519Keyring::Alice, 0), (Sr25519Keyring::Bob, 0)],
+		vec![(Sr25519Keyring::Alice, 1)],
+	]
+	.into_iter()
+	.enumerate()
+	{
+		let mut builder = BlockBuilderBuilder::new(&*client)
+			.on_parent_block(parent_hash)
+			.with_parent_block_number(number as u64)
+			.build()
+			.unwrap();
+		for (from, nonce) in transfers {
+			builder
+				.push_transfer(runtime::Transfer {
+					from: from.into(),
+					to: Sr25519Keyring::Ferdie.into(),
+					amount: 42,
+					nonce,
+				})
+				.unwrap();
+		}
+		let block = builder.build().unwrap().block;
+		parent_hash = block.header.hash();
+		block_hashes.push(format!("{:?}", parent_hash));
+		client.import(BlockOrigin::Own, block).await.unwrap();
+	}
+
+	let result: ArchiveTransactionsByAccountResult =
+		api.call("archive_unstable_transactionsByAccount", [&alice]).await.unwrap();
+	assert_eq!(
+		result.transactions,
+		vec![
+			ArchiveTransactionLocation { block_hash: block_hashes[0].clone(), index: 0 },
+			ArchiveTransactionLocation { block_hash: block_hashes[1].clone(), index: 0 },
+		]
+	);
+	assert_eq!(result.next_start_key, None);
+
+	// The transactions can be fetched page by page.
+	let result: ArchiveTransactionsByAccountResult = api
+		.call("archive_unstable_transactionsByAccount", rpc_params![&alice, 1])
+		.await
+		.unwrap();
+	assert_eq!(
+		result.transactions,
+		vec![ArchiveTransactionLocation { block_hash: block_hashes[0].clone(), index: 0 }]
+	);
+	let start_key = result.next_start_key.unwrap();
+	let result: ArchiveTransactionsByAccountResult = api
+		.call("archive_unstable_transactionsByAccount", rpc_params![&alice, 1, start_key])
+		.await
+		.unwrap();
+	assert_eq!(
+		result.transactions,
+		vec![ArchiveTransactionLocation { block_hash: block_hashes[1].clone(), index: 0 }]
+	);
+	assert_eq!(result.next_start_key, None);
+
+	let bob = hex_string(&Sr25519Keyring::Bob.public().encode());
+	let result: ArchiveTransactionsByAccountResult =
+		api.call("archive_unstable_transactionsByAccount", [bob]).await.unwrap();
+	assert_eq!(
+		result.transactions,
+		vec![ArchiveTransactionLocation { block_hash: block_hashes[0].clone(), index: 1 }]
+	);
+
+	// The receiver of a transfer is not its signer.
+	let ferdie = hex_string(&Sr25519Keyring::Ferdie.public().encode());
+	let result: ArchiveTransactionsByAccountResult =
+		api.call("archive_unstable_transactionsByAccount", [ferdie]).await.unwrap();
+	assert!(result.transactions.is_empty());
+}
+
 #[tokio::test]
 async fn archive_header() {
 	let (client, api) = setup_api();
@@ -1003,3 +1212,276 @@ async fn archive_storage_diff_invalid_params() {
 		ArchiveStorageDiffEvent::StorageDiffError(ref err) if err.error.contains("Header was not found")
 	);
 }
+
+#[tokio::test]
+async fn archive_storage_diff_range() {
+	let (client, api) = setup_api();
+	let genesis_hash = format!("{:?}", client.chain_info().genesis_hash);
+
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(client.chain_info().genesis_hash)
+		.with_parent_block_number(0)
+		.build()
+		.unwrap();
+	builder.push_storage_change(b":A".to_vec(), Some(b"B".to_vec())).unwrap();
+	let block_1 = builder.build().unwrap().block;
+	let block_1_hash = format!("{:?}", block_1.header.hash());
+	client.import(BlockOrigin::Own, block_1.clone()).await.unwrap();
+
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(block_1.hash())
+		.with_parent_block_number(1)
+		.build()
+		.unwrap();
+	builder.push_storage_change(b":A".to_vec(), Some(b"11".to_vec())).unwrap();
+	let block_2 = builder.build().unwrap().block;
+	let block_2_hash = format!("{:?}", block_2.header.hash());
+	client.import(BlockOrigin::Own, block_2.clone()).await.unwrap();
+
+	let items = vec![ArchiveStorageDiffItem::<String> {
+		key: hex_string(b":A"),
+		return_type: ArchiveStorageDiffType::Value,
+		child_trie_key: None,
+	}];
+	let mut sub = api
+		.subscribe_unbounded(
+			"archive_unstable_storageDiffRange",
+			rpc_params![&genesis_hash, &block_2_hash, items.clone()],
+		)
+		.await
+		.unwrap();
+
+	let event = get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await;
+	assert_eq!(
+		ArchiveStorageDiffRangeEvent::StorageDiff(ArchiveStorageDiffRangeResult {
+			block_hash: block_1_hash.clone(),
+			result: ArchiveStorageDiffResult {
+				key: hex_string(b":A"),
+				result: StorageResultType::Value(hex_string(b"B")),
+				operation_type: ArchiveStorageDiffOperationType::Added,
+				child_trie_key: None,
+			},
+		}),
+		event,
+	);
+
+	let event = get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await;
+	assert_eq!(
+		ArchiveStorageDiffRangeEvent::BlockDone(ArchiveStorageDiffRangeBlock {
+			block_hash: block_1_hash.clone(),
+		}),
+		event,
+	);
+
+	let event = get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await;
+	assert_eq!(
+		ArchiveStorageDiffRangeEvent::StorageDiff(ArchiveStorageDiffRangeResult {
+			block_hash: block_2_hash.clone(),
+			result: ArchiveStorageDiffResult {
+				key: hex_string(b":A"),
+				result: StorageResultType::Value(hex_string(b"11")),
+				operation_type: ArchiveStorageDiffOperationType::Modified,
+				child_trie_key: None,
+			},
+		}),
+		event,
+	);
+
+	let event = get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await;
+	assert_eq!(
+		ArchiveStorageDiffRangeEvent::BlockDone(ArchiveStorageDiffRangeBlock {
+			block_hash: block_2_hash.clone(),
+		}),
+		event,
+	);
+
+	let event = get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await;
+	assert_eq!(ArchiveStorageDiffRangeEvent::StorageDiffDone, event);
+
+	// The range must go from an ancestor to a descendant.
+	let mut sub = api
+		.subscribe_unbounded(
+			"archive_unstable_storageDiffRange",
+			rpc_params![&block_2_hash, &block_1_hash, items],
+		)
+		.await
+		.unwrap();
+
+	let event = get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await;
+	assert_matches!(event,
+		ArchiveStorageDiffRangeEvent::StorageDiffError(ref err) if err.error.contains("is not a descendant")
+	);
+}
+
+#[tokio::test]
+async fn archive_storage_diff_range_child_trie() {
+	let (client, api) = setup_api();
+	let genesis_hash = client.chain_info().genesis_hash;
+	let child_trie_key = hex_string(&CHILD_STORAGE_KEY);
+
+	let block_hash = import_block_with_changes(
+		&client,
+		genesis_hash,
+		vec![],
+		vec![(KEY.to_vec(), b"new child value".to_vec())],
+	)
+	.await;
+	let genesis_hash = format!("{:?}", genesis_hash);
+	let block_hash = format!("{:?}", block_hash);
+	let child_diff = ArchiveStorageDiffRangeEvent::StorageDiff(ArchiveStorageDiffRangeResult {
+		block_hash: block_hash.clone(),
+		result: ArchiveStorageDiffResult {
+			key: hex_string(&KEY),
+			result: StorageResultType::Value(hex_string(b"new child value")),
+			operation_type: ArchiveStorageDiffOperationType::Modified,
+			child_trie_key: Some(child_trie_key.clone()),
+		},
+	});
+	let block_done = ArchiveStorageDiffRangeEvent::BlockDone(ArchiveStorageDiffRangeBlock {
+		block_hash: block_hash.clone(),
+	});
+
+	// Query the child trie explicitly.
+	let items = vec![ArchiveStorageDiffItem::<String> {
+		key: hex_string(&KEY),
+		return_type: ArchiveStorageDiffType::Value,
+		child_trie_key: Some(child_trie_key.clone()),
+	}];
+	let mut sub = api
+		.subscribe_unbounded(
+			"archive_unstable_storageDiffRange",
+			rpc_params![&genesis_hash, &block_hash, items],
+		)
+		.await
+		.unwrap();
+
+	assert_eq!(child_diff, get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await);
+	assert_eq!(block_done, get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await);
+	assert_eq!(
+		ArchiveStorageDiffRangeEvent::StorageDiffDone,
+		get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await
+	);
+
+	// Without items, the root of the child trie changes in the main trie, and the differences
+	// of the child trie follow.
+	let items: Vec<ArchiveStorageDiffItem<String>> = vec![];
+	let mut sub = api
+		.subscribe_unbounded(
+			"archive_unstable_storageDiffRange",
+			rpc_params![&genesis_hash, &block_hash, items],
+		)
+		.await
+		.unwrap();
+
+	let child_root_key =
+		hex_string(&ChildInfo::new_default(CHILD_STORAGE_KEY).prefixed_storage_key().into_inner());
+	let event = get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await;
+	assert_matches!(event,
+		ArchiveStorageDiffRangeEvent::StorageDiff(ArchiveStorageDiffRangeResult {
+			result: ArchiveStorageDiffResult {
+				ref key,
+				operation_type: ArchiveStorageDiffOperationType::Modified,
+				child_trie_key: None,
+				..
+			},
+			..
+		}) if *key == child_root_key
+	);
+	assert_eq!(child_diff, get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await);
+	assert_eq!(block_done, get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await);
+	assert_eq!(
+		ArchiveStorageDiffRangeEvent::StorageDiffDone,
+		get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await
+	);
+}
+
+#[tokio::test]
+async fn archive_storage_diff_range_continue() {
+	let (client, api) = setup_api();
+	let genesis_hash = client.chain_info().genesis_hash;
+
+	// The first block reports enough differences to pause the subscription.
+	let changes = (0..STORAGE_DIFF_RANGE_ITEMS_BEFORE_CONTINUE)
+		.map(|i| (format!(":key_{i:05}").into_bytes(), b"value".to_vec()))
+		.collect();
+	let block_1_hash = import_block_with_changes(&client, genesis_hash, changes, vec![]).await;
+	let block_2_hash = import_block_with_changes(
+		&client,
+		block_1_hash,
+		vec![(b":key_00000".to_vec(), b"new value".to_vec())],
+		vec![],
+	)
+	.await;
+	let block_1_hash = format!("{:?}", block_1_hash);
+	let block_2_hash = format!("{:?}", block_2_hash);
+
+	let items: Vec<ArchiveStorageDiffItem<String>> = vec![];
+	let mut sub = api
+		.subscribe_unbounded(
+			"archive_unstable_storageDiffRange",
+			rpc_params![&format!("{:?}", genesis_hash), &block_2_hash, items],
+		)
+		.await
+		.unwrap();
+	let sub_id = serde_json::to_string(&sub.subscription_id()).unwrap();
+
+	for _ in 0..STORAGE_DIFF_RANGE_ITEMS_BEFORE_CONTINUE {
+		let event = get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await;
+		assert_matches!(event,
+			ArchiveStorageDiffRangeEvent::StorageDiff(ArchiveStorageDiffRangeResult {
+				ref block_hash,
+				result: ArchiveStorageDiffResult {
+					operation_type: ArchiveStorageDiffOperationType::Added,
+					..
+				},
+			}) if *block_hash == block_1_hash
+		);
+	}
+	assert_eq!(
+		ArchiveStorageDiffRangeEvent::BlockDone(ArchiveStorageDiffRangeBlock {
+			block_hash: block_1_hash,
+		}),
+		get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await
+	);
+	assert_eq!(
+		ArchiveStorageDiffRangeEvent::WaitingForContinue,
+		get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await
+	);
+
+	// No events are produced until the subscription is continued.
+	assert!(tokio::time::timeout(std::time::Duration::from_secs(1), sub.next())
+		.await
+		.is_err());
+
+	// Unknown subscriptions can't be continued.
+	let err = api
+		.call::<_, ()>("archive_unstable_storageDiffRange_continue", ["invalid_sub_id"])
+		.await
+		.unwrap_err();
+	assert_matches!(err, Error::JsonRpc(ref err) if err.code() == 3006);
+
+	let _: () = api.call("archive_unstable_storageDiffRange_continue", [&sub_id]).await.unwrap();
+
+	assert_eq!(
+		ArchiveStorageDiffRangeEvent::StorageDiff(ArchiveStorageDiffRangeResult {
+			block_hash: block_2_hash.clone(),
+			result: ArchiveStorageDiffResult {
+				key: hex_string(b":key_00000"),
+				result: StorageResultType::Value(hex_string(b"new value")),
+				operation_type: ArchiveStorageDiffOperationType::Modified,
+				child_trie_key: None,
+			},
+		}),
+		get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await
+	);
+	assert_eq!(
+		ArchiveStorageDiffRangeEvent::BlockDone(ArchiveStorageDiffRangeBlock {
+			block_hash: block_2_hash,
+		}),
+		get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await
+	);
+	assert_eq!(
+		ArchiveStorageDiffRangeEvent::StorageDiffDone,
+		get_next_event::<ArchiveStorageDiffRangeEvent>(&mut sub).await
+	);
+}
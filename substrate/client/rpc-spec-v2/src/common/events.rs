@@ -262,10 +262,130 @@ impl ArchiveStorageDiffEvent {
 	}
 }
 
+/// The result of an individual storage difference key in a block of a range.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveStorageDiffRangeResult {
+	/// The hex-encoded hash of the block that produced the difference.
+	pub block_hash: String,
+	/// The difference, relative to the parent of the block.
+	#[serde(flatten)]
+	pub result: ArchiveStorageDiffResult,
+}
+
+/// The block reported by the `blockDone` event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveStorageDiffRangeBlock {
+	/// The hex-encoded hash of the block.
+	pub block_hash: String,
+}
+
+/// The event generated by the `archive_storageDiffRange` method.
+///
+/// The `archive_storageDiffRange` can generate the following events:
+///  - `storageDiff` event - generated when a `ArchiveStorageDiffRangeResult` is produced.
+///  - `blockDone` event - generated when all the differences of a block were produced.
+///  - `waitingForContinue` event - generated when the server waits for the
+///    `archive_storageDiffRange_continue` method to be called before producing more events.
+///  - `storageDiffError` event - generated when an error is produced.
+///  - `storageDiffDone` event - generated when the `archive_storageDiffRange` method completed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(tag = "event")]
+pub enum ArchiveStorageDiffRangeEvent {
+	/// The `storageDiff` event.
+	StorageDiff(ArchiveStorageDiffRangeResult),
+	/// The `blockDone` event.
+	BlockDone(ArchiveStorageDiffRangeBlock),
+	/// The `waitingForContinue` event.
+	WaitingForContinue,
+	/// The `storageDiffError` event.
+	StorageDiffError(ArchiveStorageMethodErr),
+	/// The `storageDiffDone` event.
+	StorageDiffDone,
+}
+
+impl ArchiveStorageDiffRangeEvent {
+	/// Create a new `ArchiveStorageDiffRangeEvent::StorageDiffError` event.
+	pub fn err(error: String) -> Self {
+		Self::StorageDiffError(ArchiveStorageMethodErr { error })
+	}
+
+	/// Checks if the event is a `StorageDiffDone` event.
+	pub fn is_done(&self) -> bool {
+		matches!(self, Self::StorageDiffDone)
+	}
+
+	/// Checks if the event is a `StorageDiffError` event.
+	pub fn is_err(&self) -> bool {
+		matches!(self, Self::StorageDiffError(_))
+	}
+}
+
+/// A block that includes a transaction, returned by the `archive_transactionByHash` and
+/// `archive_transactionsByAccount` methods.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveTransactionLocation {
+	/// The hex-encoded hash of the block.
+	pub block_hash: String,
+	/// The index of the transaction in the body of the block.
+	pub index: u32,
+}
+
+/// A page of the blocks that include transactions of an account, returned by the
+/// `archive_transactionsByAccount` method.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveTransactionsByAccountResult {
+	/// The blocks that include transactions of the account, in ascending order of block numbers.
+	pub transactions: Vec<ArchiveTransactionLocation>,
+	/// The hex-encoded key to pass as `startKey` to fetch the next page, if there is one.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	#[serde(default)]
+	pub next_start_key: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
 
+	#[test]
+	fn archive_diff_range_event() {
+		let event = ArchiveStorageDiffRangeEvent::StorageDiff(ArchiveStorageDiffRangeResult {
+			block_hash: "0x2".into(),
+			result: ArchiveStorageDiffResult {
+				key: "0x1".into(),
+				result: StorageResultType::Value("res".into()),
+				operation_type: ArchiveStorageDiffOperationType::Added,
+				child_trie_key: None,
+			},
+		});
+		// Encode
+		let ser = serde_json::to_string(&event).unwrap();
+		let exp =
+			r#"{"event":"storageDiff","blockHash":"0x2","key":"0x1","value":"res","type":"added"}"#;
+		assert_eq!(ser, exp);
+		// Decode
+		let dec: ArchiveStorageDiffRangeEvent = serde_json::from_str(exp).unwrap();
+		assert_eq!(dec, event);
+
+		let event = ArchiveStorageDiffRangeEvent::BlockDone(ArchiveStorageDiffRangeBlock {
+			block_hash: "0x2".into(),
+		});
+		// Encode
+		let ser = serde_json::to_string(&event).unwrap();
+		let exp = r#"{"event":"blockDone","blockHash":"0x2"}"#;
+		assert_eq!(ser, exp);
+		// Decode
+		let dec: ArchiveStorageDiffRangeEvent = serde_json::from_str(exp).unwrap();
+		assert_eq!(dec, event);
+
+		let ser = serde_json::to_string(&ArchiveStorageDiffRangeEvent::WaitingForContinue).unwrap();
+		assert_eq!(ser, r#"{"event":"waitingForContinue"}"#);
+	}
+
 	#[test]
 	fn archive_diff_input() {
 		// Item with Value.
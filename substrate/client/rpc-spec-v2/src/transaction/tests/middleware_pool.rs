@@ -141,6 +141,10 @@ impl TransactionPool for MiddlewarePool {
 		self.inner_pool.remove_invalid(hashes)
 	}
 
+	fn clear(&self) -> Vec<Arc<Self::InPoolTransaction>> {
+		self.inner_pool.clear()
+	}
+
 	fn status(&self) -> PoolStatus {
 		self.inner_pool.status()
 	}
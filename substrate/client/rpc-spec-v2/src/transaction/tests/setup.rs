@@ -120,6 +120,33 @@ pub fn setup_api_tx() -> (
 	(api, pool, client_mock, tx_api, executor_recv, pool_state)
 }
 
+/// Set up the `transactionWatch` API on top of a fork-aware transaction pool.
+pub fn setup_api_tx_fork_aware() -> (
+	Arc<TestApi>,
+	Arc<ForkAwareTxPool<TestApi, Block>>,
+	Arc<ChainHeadMockClient<Client<Backend>>>,
+	RpcModule<
+		RpcTransaction<ForkAwareTxPool<TestApi, Block>, ChainHeadMockClient<Client<Backend>>>,
+	>,
+	TaskExecutorState,
+) {
+	let api = Arc::new(TestApi::with_alice_nonce(ALICE_NONCE));
+	let genesis_hash = api.genesis_hash();
+	let (pool, pool_task) = ForkAwareTxPool::new_test(api.clone(), genesis_hash, genesis_hash);
+	tokio::spawn(pool_task);
+	let pool = Arc::new(pool);
+
+	let builder = TestClientBuilder::new();
+	let client = Arc::new(builder.build());
+	let client_mock = Arc::new(ChainHeadMockClient::new(client.clone()));
+	let (task_executor, executor_recv) = TaskExecutorBroadcast::new();
+
+	let tx_api =
+		RpcTransaction::new(client_mock.clone(), pool.clone(), Arc::new(task_executor)).into_rpc();
+
+	(api, pool, client_mock, tx_api, executor_recv)
+}
+
 /// Get the next event from the provided middleware in at most 5 seconds.
 macro_rules! get_next_event {
 	($middleware:expr) => {
@@ -18,19 +18,21 @@
 
 use crate::{
 	hex_string,
-	transaction::{TransactionBlock, TransactionEvent},
+	transaction::{TransactionBlock, TransactionDropped, TransactionEvent},
 };
 use assert_matches::assert_matches;
 use codec::Encode;
 use jsonrpsee::rpc_params;
-use sc_transaction_pool_api::{ChainEvent, MaintainedTransactionPool};
+use sc_transaction_pool_api::{
+	ChainEvent, MaintainedTransactionPool, TransactionPool, TransactionSource,
+};
 use sp_core::H256;
 use std::{sync::Arc, vec};
 use substrate_test_runtime_client::Sr25519Keyring::*;
 use substrate_test_runtime_transaction_pool::uxt;
 
 // Test helpers.
-use crate::transaction::tests::setup::{setup_api_tx, ALICE_NONCE};
+use crate::transaction::tests::setup::{setup_api_tx, setup_api_tx_fork_aware, ALICE_NONCE};
 
 #[tokio::test]
 async fn tx_invalid_bytes() {
@@ -235,3 +237,41 @@ async fn tx_slow_client_replace_old_messages() {
 
 	assert_eq!(res, exp);
 }
+
+#[tokio::test]
+async fn tx_replaced_in_fork_aware_pool() {
+	let (api, pool, client, tx_api, _exec_middleware) = setup_api_tx_fork_aware();
+	let block_1_header = api.push_block(1, vec![], true);
+	let block_1 = block_1_header.hash();
+	let event = ChainEvent::NewBestBlock { hash: block_1, tree_route: None };
+	pool.maintain(event).await;
+	client.set_best_block(block_1, 1);
+
+	// Two transactions with the same sender and nonce, the second one with a higher priority.
+	let original = uxt(Alice, ALICE_NONCE);
+	let replacement = uxt(Alice, ALICE_NONCE);
+	api.set_priority(&original, 2);
+	api.set_priority(&replacement, 3);
+	let xt = hex_string(&original.encode());
+
+	let mut sub = tx_api
+		.subscribe_unbounded("transactionWatch_v1_submitAndWatch", rpc_params![&xt])
+		.await
+		.unwrap();
+
+	let event: TransactionEvent<H256> = get_next_event_sub!(&mut sub);
+	assert_eq!(event, TransactionEvent::Validated);
+
+	let replacement_hash = pool
+		.submit_one(block_1, TransactionSource::External, replacement)
+		.await
+		.unwrap();
+
+	let event: TransactionEvent<H256> = get_next_event_sub!(&mut sub);
+	assert_eq!(
+		event,
+		TransactionEvent::Dropped(TransactionDropped {
+			error: format!("Extrinsic was replaced by {}", hex_string(&replacement_hash.encode())),
+		})
+	);
+}
@@ -19,6 +19,7 @@
 //! API implementation for submitting transactions.
 
 use crate::{
+	hex_string,
 	transaction::{
 		api::TransactionApiServer,
 		error::Error,
@@ -27,7 +28,7 @@ use crate::{
 	SubscriptionTaskExecutor,
 };
 
-use codec::Decode;
+use codec::{Decode, Encode};
 use futures::{StreamExt, TryFutureExt};
 use jsonrpsee::{core::async_trait, PendingSubscriptionSink};
 use sc_rpc::utils::{RingBuffer, Subscription};
@@ -134,7 +135,7 @@ where
 /// Handle events generated by the transaction-pool and convert them
 /// to the new API expected state.
 #[inline]
-pub fn handle_event<Hash: Clone, BlockHash: Clone>(
+pub fn handle_event<Hash: Encode, BlockHash: Clone>(
 	event: TransactionStatus<Hash, BlockHash>,
 ) -> Option<TransactionEvent<BlockHash>> {
 	match event {
@@ -152,6 +153,9 @@ pub fn handle_event<Hash: Clone, BlockHash: Clone>(
 		TransactionStatus::Usurped(_) => Some(TransactionEvent::Invalid(TransactionError {
 			error: "Extrinsic was rendered invalid by another extrinsic".into(),
 		})),
+		TransactionStatus::Replaced(by) => Some(TransactionEvent::Dropped(TransactionDropped {
+			error: format!("Extrinsic was replaced by {}", hex_string(&by.encode())),
+		})),
 		TransactionStatus::Dropped => Some(TransactionEvent::Dropped(TransactionDropped {
 			error: "Extrinsic dropped from the pool due to exceeding limits".into(),
 		})),
@@ -149,7 +149,7 @@ async fn author_should_watch_extrinsic() {
 		.unwrap()
 		.unwrap()
 		.unwrap();
-	assert_eq!(tx, TransactionStatus::Usurped(xt_hash.into()));
+	assert_eq!(tx, TransactionStatus::Replaced(xt_hash.into()));
 	assert_eq!(&sub_id, sub.subscription_id());
 }
 
@@ -59,6 +59,12 @@ pub enum Request<B: traits::Block> {
 	NetworkRemoveReservedPeer(String, oneshot::Sender<error::Result<()>>),
 	/// Must return the list of reserved peers
 	NetworkReservedPeers(oneshot::Sender<Vec<String>>),
+	/// Must return any potential parse error.
+	NetworkBanPeer(String, oneshot::Sender<error::Result<()>>),
+	/// Must return whether the peer was banned, or any potential parse error.
+	NetworkUnbanPeer(String, oneshot::Sender<error::Result<bool>>),
+	/// Must return the list of banned peers.
+	NetworkBannedPeers(oneshot::Sender<Vec<String>>),
 	/// Must return the node role.
 	NodeRoles(oneshot::Sender<Vec<NodeRole>>),
 	/// Must return the state of the node syncing.
@@ -164,6 +170,35 @@ impl<B: traits::Block> SystemApiServer<B::Hash, <B::Header as HeaderT>::Number>
 		rx.await.map_err(|e| Error::Internal(e.to_string()))
 	}
 
+	async fn system_ban_peer(&self, ext: &Extensions, peer: String) -> Result<(), Error> {
+		check_if_safe(ext)?;
+		let (tx, rx) = oneshot::channel();
+		let _ = self.send_back.unbounded_send(Request::NetworkBanPeer(peer, tx));
+		match rx.await {
+			Ok(Ok(())) => Ok(()),
+			Ok(Err(e)) => Err(e),
+			Err(e) => Err(Error::Internal(e.to_string())),
+		}
+	}
+
+	async fn system_unban_peer(&self, ext: &Extensions, peer: String) -> Result<bool, Error> {
+		check_if_safe(ext)?;
+		let (tx, rx) = oneshot::channel();
+		let _ = self.send_back.unbounded_send(Request::NetworkUnbanPeer(peer, tx));
+		match rx.await {
+			Ok(Ok(unbanned)) => Ok(unbanned),
+			Ok(Err(e)) => Err(e),
+			Err(e) => Err(Error::Internal(e.to_string())),
+		}
+	}
+
+	async fn system_list_banned_peers(&self, ext: &Extensions) -> Result<Vec<String>, Error> {
+		check_if_safe(ext)?;
+		let (tx, rx) = oneshot::channel();
+		let _ = self.send_back.unbounded_send(Request::NetworkBannedPeers(tx));
+		rx.await.map_err(|e| Error::Internal(e.to_string()))
+	}
+
 	async fn system_node_roles(&self) -> Result<Vec<NodeRole>, Error> {
 		let (tx, rx) = oneshot::channel();
 		let _ = self.send_back.unbounded_send(Request::NodeRoles(tx));
@@ -31,8 +31,11 @@ use log::info;
 use prometheus_endpoint::Registry;
 use sc_chain_spec::{get_extension, ChainSpec};
 use sc_client_api::{
-	execution_extensions::ExecutionExtensions, proof_provider::ProofProvider, BadBlocks,
-	BlockBackend, BlockchainEvents, ExecutorProvider, ForkBlocks, StorageProvider, UsageProvider,
+	execution_extensions::ExecutionExtensions,
+	light::{RemoteProofs, RemoteProofsProvider},
+	proof_provider::ProofProvider,
+	AuxStore, BadBlocks, BlockBackend, BlockchainEvents, ExecutorProvider, ForkBlocks,
+	StorageProvider, UsageProvider,
 };
 use sc_client_db::{Backend, BlocksPruning, DatabaseSettings, PruningMode};
 use sc_consensus::import_queue::{ImportQueue, ImportQueueService};
@@ -51,7 +54,9 @@ use sc_network::{
 	NetworkBackend, NetworkStateInfo,
 };
 use sc_network_common::role::{Role, Roles};
-use sc_network_light::light_client_requests::handler::LightClientRequestHandler;
+use sc_network_light::light_client_requests::{
+	handler::LightClientRequestHandler, sender::LightClientRequestSender,
+};
 use sc_network_sync::{
 	block_relay_protocol::{BlockDownloader, BlockRelayParams},
 	block_request_handler::BlockRequestHandler,
@@ -60,6 +65,7 @@ use sc_network_sync::{
 	state_request_handler::StateRequestHandler,
 	strategy::{
 		polkadot::{PolkadotSyncingStrategy, PolkadotSyncingStrategyConfig},
+		state_sync::StateSyncPersistence,
 		SyncingStrategy,
 	},
 	warp_request_handler::RequestHandler as WarpSyncRequestHandler,
@@ -260,6 +266,7 @@ where
 				no_genesis: config.no_genesis(),
 				wasm_runtime_substitutes,
 				enable_import_proof_recording,
+				remote_proofs: config.network.sync_mode.is_light().then(RemoteProofs::new),
 			},
 		)?;
 
@@ -291,6 +298,7 @@ pub fn new_wasm_executor<H: HostFunctions>(config: &ExecutorConfiguration) -> Wa
 		.with_offchain_heap_alloc_strategy(strategy)
 		.with_max_runtime_instances(config.max_runtime_instances)
 		.with_runtime_cache_size(config.runtime_cache_size)
+		.with_profiler(config.profiler.clone())
 		.build()
 }
 
@@ -745,9 +753,12 @@ where
 
 	// Part of the RPC v2 spec.
 	// An archive node that can respond to the `archive` RPC-v2 queries is a node with:
-	// - state pruning in archive mode: The storage of blocks is kept around
+	// - state pruning in archive or snapshots mode: The storage of (some) blocks is kept around
 	// - block pruning in archive mode: The block's body is kept around
-	let is_archive_node = state_pruning.as_ref().map(|sp| sp.is_archive()).unwrap_or(false) &&
+	let is_archive_node = state_pruning
+		.as_ref()
+		.map(|sp| sp.is_archive() || matches!(sp, PruningMode::Snapshots { .. }))
+		.unwrap_or(false) &&
 		blocks_pruning.is_archive();
 	let genesis_hash = client.hash(Zero::zero()).ok().flatten().expect("Genesis block exists; qed");
 	if is_archive_node {
@@ -857,8 +868,10 @@ where
 		+ BlockBackend<Block>
 		+ BlockIdTo<Block, Error = sp_blockchain::Error>
 		+ ProofProvider<Block>
+		+ AuxStore
 		+ HeaderBackend<Block>
 		+ BlockchainEvents<Block>
+		+ RemoteProofsProvider<Block>
 		+ 'static,
 	TxPool: TransactionPool<Block = Block, Hash = <Block as BlockT>::Hash> + 'static,
 	IQ: ImportQueue<Block> + 'static,
@@ -940,23 +953,64 @@ where
 
 	spawn_handle.spawn_blocking("syncing", None, syncing_engine.run());
 
-	build_network_advanced(BuildNetworkAdvancedParams {
-		role: config.role,
-		protocol_id,
-		fork_id,
-		ipfs_server: config.network.ipfs_server,
-		announce_block: config.announce_block,
-		net_config,
-		client,
-		transaction_pool,
-		spawn_handle,
-		import_queue,
-		sync_service,
-		block_announce_config,
-		network_service_provider,
-		metrics_registry,
-		metrics,
-	})
+	let remote_proofs = if config.network.sync_mode.is_light() {
+		Some(
+			client
+				.remote_proofs()
+				.cloned()
+				.ok_or("Light client mode is not enabled in the client configuration.")?,
+		)
+	} else {
+		None
+	};
+	let genesis_hash = client.info().genesis_hash;
+
+	let (network, system_rpc_tx, tx_handler_controller, sync_service) =
+		build_network_advanced(BuildNetworkAdvancedParams {
+			role: config.role,
+			protocol_id,
+			fork_id,
+			ipfs_server: config.network.ipfs_server,
+			announce_block: config.announce_block,
+			net_config,
+			client,
+			transaction_pool,
+			spawn_handle,
+			import_queue,
+			sync_service,
+			block_announce_config,
+			network_service_provider,
+			metrics_registry,
+			metrics,
+		})?;
+
+	if let Some(remote_proofs) = remote_proofs {
+		// Only full nodes keep the state of the recent blocks.
+		let peers = {
+			let sync_service = sync_service.clone();
+			Arc::new(move || {
+				let sync_service = sync_service.clone();
+				async move {
+					sync_service
+						.peers_info()
+						.await
+						.unwrap_or_default()
+						.into_iter()
+						.filter_map(|(peer_id, info)| info.roles.is_full().then_some(peer_id))
+						.collect()
+				}
+				.boxed()
+			})
+		};
+		remote_proofs.set_fetcher(Arc::new(LightClientRequestSender::<Block>::new(
+			Arc::new(network.clone()),
+			genesis_hash,
+			fork_id,
+			peers,
+		)));
+	}
+
+	Ok((network, system_rpc_tx, tx_handler_controller, sync_service))
 }
 
 /// Parameters to pass into [`build_network_advanced`].
@@ -1018,6 +1072,7 @@ where
 		+ BlockBackend<Block>
 		+ BlockIdTo<Block, Error = sp_blockchain::Error>
 		+ ProofProvider<Block>
+		+ AuxStore
 		+ HeaderBackend<Block>
 		+ BlockchainEvents<Block>
 		+ 'static,
@@ -1072,6 +1127,10 @@ where
 			metrics.clone(),
 			net_config.peer_store_handle(),
 		);
+	let transactions_handler_proto = transactions_handler_proto
+		.with_max_pending_transactions_per_peer(
+			net_config.network_config.max_pending_transactions_per_peer,
+		);
 	net_config.add_notification_protocol(transactions_config);
 
 	// Start task for `PeerStore`
@@ -1203,6 +1262,7 @@ where
 		+ BlockBackend<Block>
 		+ HeaderMetadata<Block, Error = sp_blockchain::Error>
 		+ ProofProvider<Block>
+		+ AuxStore
 		+ Send
 		+ Sync
 		+ 'static,
@@ -1316,12 +1376,14 @@ where
 		+ BlockBackend<Block>
 		+ HeaderMetadata<Block, Error = sp_blockchain::Error>
 		+ ProofProvider<Block>
+		+ AuxStore
 		+ Send
 		+ Sync
 		+ 'static,
 	Net: NetworkBackend<Block, <Block as BlockT>::Hash>,
 {
-	if warp_sync_config.is_none() && net_config.network_config.sync_mode.is_warp() {
+	let sync_mode = net_config.network_config.sync_mode;
+	if warp_sync_config.is_none() && (sync_mode.is_warp() || sync_mode.is_light()) {
 		return Err("Warp sync enabled, but no warp sync provider configured.".into())
 	}
 
@@ -1330,6 +1392,8 @@ where
 			SyncMode::LightState { .. } =>
 				return Err("Fast sync doesn't work for archive nodes".into()),
 			SyncMode::Warp => return Err("Warp sync doesn't work for archive nodes".into()),
+			SyncMode::Light =>
+				return Err("Light client mode doesn't work for archive nodes".into()),
 			SyncMode::Full => {},
 		}
 	}
@@ -1377,10 +1441,16 @@ where
 		state_request_protocol_name,
 		block_downloader,
 	};
-	Ok(Box::new(PolkadotSyncingStrategy::new(
+	let mut syncing_strategy = PolkadotSyncingStrategy::new(
 		syncing_config,
-		client,
+		client.clone(),
 		warp_sync_config,
 		warp_sync_protocol_name,
-	)?))
+	)?;
+	// An interrupted state download of the fast sync resumes after a restart.
+	if matches!(sync_mode, SyncMode::LightState { .. }) {
+		syncing_strategy =
+			syncing_strategy.with_state_sync_persistence(StateSyncPersistence::new(client)?);
+	}
+	Ok(Box::new(syncing_strategy))
 }
@@ -171,6 +171,7 @@ fn import_block_to_queue<TBl, TImpQu>(
 			allow_missing_state: false,
 			import_existing: force,
 			state: None,
+			remote_state: false,
 			skip_execution: false,
 		}],
 	);
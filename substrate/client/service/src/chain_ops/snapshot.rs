@@ -0,0 +1,356 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Binary snapshots of the state of a block.
+//!
+//! A snapshot starts with a [`SnapshotHeader`], carrying the header and the justifications of the
+//! block. It is followed by the trie nodes of the state, as a sequence of [`CompactProof`] chunks
+//! as produced for the state sync. Every chunk is verified against the state root of the header
+//! when the snapshot is imported.
+
+use crate::error::Error;
+use codec::{Decode, Encode, IoReader};
+use futures::{future, prelude::*};
+use log::info;
+use parking_lot::Mutex;
+use sc_client_api::{BlockBackend, CompactProof, HeaderBackend, ProofProvider};
+use sc_consensus::import_queue::{
+	BlockImportError, BlockImportStatus, ImportQueue, IncomingBlock, Link,
+};
+use sc_network_sync::{
+	strategy::state_sync::{ImportResult, StateSync, StateSyncProvider},
+	StateResponse,
+};
+use smallvec::SmallVec;
+use sp_consensus::BlockOrigin;
+use sp_runtime::{
+	traits::{Block as BlockT, Header as HeaderT, NumberFor},
+	Justifications,
+};
+use std::{
+	io::{Read, Write},
+	pin::Pin,
+	sync::Arc,
+	task::Poll,
+};
+
+/// Magic bytes at the start of every snapshot.
+const SNAPSHOT_MAGIC: [u8; 8] = *b"substate";
+
+/// Version of the snapshot format.
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Maximal size of the trie nodes of a single chunk.
+const SNAPSHOT_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Header of a snapshot.
+#[derive(Encode, Decode)]
+struct SnapshotHeader<Hash, Header> {
+	/// Always [`SNAPSHOT_MAGIC`].
+	magic: [u8; 8],
+	/// Version of the snapshot format.
+	version: u32,
+	/// Hash of the block.
+	hash: Hash,
+	/// State root of the block.
+	state_root: Hash,
+	/// Header of the block.
+	header: Header,
+	/// Justifications of the block, if any.
+	justifications: Option<Justifications>,
+}
+
+/// Export the state of the block `hash` as a binary snapshot.
+pub fn export_snapshot<B, C>(
+	client: Arc<C>,
+	hash: B::Hash,
+	mut output: impl Write,
+) -> Result<(), Error>
+where
+	B: BlockT,
+	C: HeaderBackend<B> + BlockBackend<B> + ProofProvider<B>,
+{
+	let header = client
+		.header(hash)?
+		.ok_or_else(|| Error::Other(format!("Header of block {hash:?} not found")))?;
+	let state_root = *header.state_root();
+	let snapshot_header = SnapshotHeader {
+		magic: SNAPSHOT_MAGIC,
+		version: SNAPSHOT_VERSION,
+		hash,
+		state_root,
+		header,
+		justifications: client.justifications(hash)?,
+	};
+	output.write_all(&snapshot_header.encode())?;
+
+	let mut last_key = SmallVec::<[Vec<u8>; 2]>::new();
+	let mut chunks = 0;
+	loop {
+		let (proof, _) =
+			client.read_proof_collection(hash, last_key.as_slice(), SNAPSHOT_CHUNK_SIZE)?;
+		// The proof is decoded to find where the next chunk starts.
+		let (values, completed) =
+			client.verify_range_proof(state_root, proof.clone(), last_key.as_slice())?;
+		output.write_all(&proof.encode())?;
+		chunks += 1;
+
+		if completed == 0 {
+			break
+		}
+		if !values.update_last_key(completed, &mut last_key) {
+			return Err(Error::Other(format!("Error updating key cursor, depth: {completed}")))
+		}
+	}
+	output.flush()?;
+
+	info!("📦 Exported the state of block {hash:?} in {chunks} chunks");
+	Ok(())
+}
+
+/// Read a snapshot, importing its chunks one by one into a [`StateSync`] for the block.
+///
+/// Every chunk is verified against the state root of the header. Returns the block along with its
+/// state.
+fn read_snapshot<B, C>(client: Arc<C>, input: impl Read) -> Result<IncomingBlock<B>, Error>
+where
+	B: BlockT,
+	C: ProofProvider<B> + Send + Sync + 'static,
+{
+	let mut input = IoReader(input);
+	let SnapshotHeader { magic, version, hash, state_root, header, justifications } =
+		SnapshotHeader::<B::Hash, B::Header>::decode(&mut input)
+			.map_err(|e| Error::Other(format!("Error decoding the snapshot header: {e}")))?;
+	if magic != SNAPSHOT_MAGIC {
+		return Err(Error::Other("The input is not a state snapshot".into()))
+	}
+	if version != SNAPSHOT_VERSION {
+		return Err(Error::Other(format!("Unsupported snapshot version {version}")))
+	}
+	if header.hash() != hash || *header.state_root() != state_root {
+		return Err(Error::Other(format!("The snapshot header does not match block {hash:?}")))
+	}
+
+	let mut state_sync = StateSync::new(client, header, None, justifications, false);
+	read_chunks(&mut state_sync, &mut input)
+}
+
+/// Import the chunks of a snapshot into `state_sync`, until the state is complete.
+fn read_chunks<B, R>(
+	state_sync: &mut impl StateSyncProvider<B>,
+	input: &mut IoReader<R>,
+) -> Result<IncomingBlock<B>, Error>
+where
+	B: BlockT,
+	R: Read,
+{
+	let mut chunks = 0;
+	let block = loop {
+		let proof = CompactProof::decode(input)
+			.map_err(|e| Error::Other(format!("Error reading snapshot chunk #{chunks}: {e}")))?;
+		match state_sync.import(StateResponse { entries: Vec::new(), proof: proof.encode() }) {
+			ImportResult::Continue => chunks += 1,
+			ImportResult::Import(hash, header, state, body, justifications) =>
+				break IncomingBlock {
+					hash,
+					header: Some(header),
+					body,
+					indexed_body: None,
+					justifications,
+					origin: None,
+					allow_missing_state: true,
+					import_existing: true,
+					state: Some(state),
+					remote_state: false,
+					skip_execution: true,
+				},
+			ImportResult::BadResponse =>
+				return Err(Error::Other(format!("Invalid snapshot chunk #{chunks}"))),
+		}
+	};
+	if input.0.read(&mut [0u8])? != 0 {
+		return Err(Error::Other("Unexpected data after the last snapshot chunk".into()))
+	}
+
+	info!("📦 Verified the state of block {:?} from {} chunks", block.hash, chunks + 1);
+	Ok(block)
+}
+
+/// Import the state of a block from a binary snapshot.
+///
+/// The snapshot is verified chunk by chunk against the state root of the block, and the block is
+/// then imported with its state through the import queue.
+pub fn import_snapshot<B, IQ, C>(
+	client: Arc<C>,
+	mut import_queue: IQ,
+	input: impl Read + Send + 'static,
+) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>
+where
+	C: HeaderBackend<B> + ProofProvider<B> + Send + Sync + 'static,
+	B: BlockT,
+	IQ: ImportQueue<B> + 'static,
+{
+	struct WaitLink {
+		result: Mutex<Option<Result<(), String>>>,
+	}
+
+	impl<B: BlockT> Link<B> for WaitLink {
+		fn blocks_processed(
+			&self,
+			_imported: usize,
+			_num_expected_blocks: usize,
+			results: Vec<(Result<BlockImportStatus<NumberFor<B>>, BlockImportError>, B::Hash)>,
+		) {
+			if let Some((result, hash)) = results.into_iter().next() {
+				*self.result.lock() = Some(result.map(|_| ()).map_err(|err| {
+					format!("There was an error importing block with hash {hash:?}: {err}")
+				}));
+			}
+		}
+	}
+
+	let block = match read_snapshot::<B, _>(client.clone(), input) {
+		Ok(block) => block,
+		Err(e) => return future::ready(Err(e)).boxed(),
+	};
+	let hash = block.hash;
+	import_queue.service_ref().import_blocks(BlockOrigin::File, vec![block]);
+
+	let link = WaitLink { result: Mutex::new(None) };
+	let import = future::poll_fn(move |cx| {
+		import_queue.poll_actions(cx, &link);
+
+		match link.result.lock().take() {
+			Some(Ok(())) => {
+				info!(
+					"🎉 Imported the state of block {hash:?}. Best: #{}",
+					client.info().best_number
+				);
+				Poll::Ready(Ok(()))
+			},
+			Some(Err(e)) => Poll::Ready(Err(Error::Other(e))),
+			None => {
+				cx.waker().wake_by_ref();
+				Poll::Pending
+			},
+		}
+	});
+	Box::pin(import)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::executor::block_on;
+	use sc_block_builder::BlockBuilderBuilder;
+	use sc_client_api::StorageProvider;
+	use sc_consensus::{
+		import_queue::{BasicQueue, Verifier},
+		BlockImportParams, ForkChoiceStrategy,
+	};
+	use sp_core::{
+		storage::{well_known_keys, StorageKey},
+		testing::TaskExecutor,
+	};
+	use substrate_test_runtime_client::{
+		prelude::*, runtime::Block, BlockBuilderExt, ClientBlockImportExt,
+	};
+
+	struct PassThroughVerifier;
+
+	#[async_trait::async_trait]
+	impl Verifier<Block> for PassThroughVerifier {
+		async fn verify(
+			&self,
+			mut block: BlockImportParams<Block>,
+		) -> Result<BlockImportParams<Block>, String> {
+			block.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+			Ok(block)
+		}
+	}
+
+	#[test]
+	fn exported_snapshot_is_verified_when_read() {
+		let client = Arc::new(TestClientBuilder::new().build());
+		let genesis_hash = client.info().genesis_hash;
+		let mut snapshot = Vec::new();
+		export_snapshot(client.clone(), genesis_hash, &mut snapshot).unwrap();
+
+		let (_, block) = read_snapshot::<Block, _>(client.clone(), &snapshot[..]).unwrap();
+		assert_eq!(block.hash, genesis_hash);
+		assert!(block.justifications.is_none());
+		let state = block.state.unwrap();
+		assert_eq!(state.block, genesis_hash);
+		assert!(state
+			.state
+			.0
+			.iter()
+			.any(|level| level.key_values.iter().any(|(key, _)| key == well_known_keys::CODE)));
+
+		// A truncated snapshot is rejected.
+		assert!(read_snapshot::<Block, _>(client.clone(), &snapshot[..snapshot.len() - 1]).is_err());
+
+		// Data after the last chunk is rejected.
+		let mut trailing = snapshot.clone();
+		trailing.push(0);
+		assert!(read_snapshot::<Block, _>(client.clone(), &trailing[..]).is_err());
+
+		// A snapshot with corrupted trie nodes is rejected.
+		let last = snapshot.len() - 1;
+		snapshot[last] ^= 1;
+		assert!(read_snapshot::<Block, _>(client.clone(), &snapshot[..]).is_err());
+	}
+
+	#[test]
+	fn imported_snapshot_matches_exported_state() {
+		let client = Arc::new(TestClientBuilder::new().build());
+		let mut block_builder = BlockBuilderBuilder::new(&*client)
+			.on_parent_block(client.info().genesis_hash)
+			.with_parent_block_number(0)
+			.build()
+			.unwrap();
+		block_builder.push_storage_change(vec![1, 2, 3], Some(vec![4, 5, 6])).unwrap();
+		let block = block_builder.build().unwrap().block;
+		let hash = block.hash();
+		block_on(client.import(BlockOrigin::Own, block)).unwrap();
+
+		let mut snapshot = Vec::new();
+		export_snapshot(client.clone(), hash, &mut snapshot).unwrap();
+
+		let target = Arc::new(TestClientBuilder::new().build());
+		let import_queue = BasicQueue::new(
+			PassThroughVerifier,
+			Box::new(target.clone()),
+			None,
+			&TaskExecutor::new(),
+			None,
+		);
+		block_on(import_snapshot(target.clone(), import_queue, std::io::Cursor::new(snapshot)))
+			.unwrap();
+
+		assert_eq!(target.info().best_hash, hash);
+		assert_eq!(
+			target.storage(hash, &StorageKey(vec![1, 2, 3])).unwrap().map(|data| data.0),
+			Some(vec![4, 5, 6]),
+		);
+		assert_eq!(
+			target.storage(hash, &StorageKey(well_known_keys::CODE.to_vec())).unwrap(),
+			client.storage(hash, &StorageKey(well_known_keys::CODE.to_vec())).unwrap(),
+		);
+	}
+}
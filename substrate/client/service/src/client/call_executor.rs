@@ -18,7 +18,8 @@
 
 use super::{code_provider::CodeProvider, ClientConfig};
 use sc_client_api::{
-	backend, call_executor::CallExecutor, execution_extensions::ExecutionExtensions, HeaderBackend,
+	backend, call_executor::CallExecutor, execution_extensions::ExecutionExtensions,
+	light::RemoteProofs, HeaderBackend,
 };
 use sc_executor::{RuntimeVersion, RuntimeVersionOf};
 use sp_api::ProofRecorder;
@@ -26,18 +27,24 @@ use sp_core::traits::{CallContext, CodeExecutor};
 use sp_externalities::Extensions;
 use sp_runtime::{
 	generic::BlockId,
-	traits::{Block as BlockT, HashingFor},
+	traits::{Block as BlockT, HashingFor, Header as HeaderT},
+};
+use sp_state_machine::{
+	backend::AsTrieBackend, Backend as StateBackend, OverlayedChanges, StateMachine, StorageProof,
 };
-use sp_state_machine::{backend::AsTrieBackend, OverlayedChanges, StateMachine, StorageProof};
 use std::{cell::RefCell, sync::Arc};
 
 /// Call executor that executes methods locally, querying all required
 /// data from local backend.
+///
+/// In light client mode, the state of the blocks that don't have it locally is fetched from
+/// remote peers.
 pub struct LocalCallExecutor<Block: BlockT, B, E> {
 	backend: Arc<B>,
 	executor: E,
 	code_provider: CodeProvider<Block, B, E>,
 	execution_extensions: Arc<ExecutionExtensions<Block>>,
+	remote_proofs: Option<RemoteProofs<Block>>,
 }
 
 impl<Block: BlockT, B, E> LocalCallExecutor<Block, B, E>
@@ -59,40 +66,28 @@ where
 			executor,
 			code_provider,
 			execution_extensions: Arc::new(execution_extensions),
+			remote_proofs: client_config.remote_proofs,
 		})
 	}
-}
 
-impl<Block: BlockT, B, E> Clone for LocalCallExecutor<Block, B, E>
-where
-	E: Clone,
-{
-	fn clone(&self) -> Self {
-		LocalCallExecutor {
-			backend: self.backend.clone(),
-			executor: self.executor.clone(),
-			code_provider: self.code_provider.clone(),
-			execution_extensions: self.execution_extensions.clone(),
+	/// Returns the header of the block and the handle to fetch its state from remote peers, if the
+	/// state of the block is not available locally.
+	fn remote_header(
+		&self,
+		hash: Block::Hash,
+	) -> sp_blockchain::Result<Option<(&RemoteProofs<Block>, Block::Header)>> {
+		let Some(remote_proofs) = &self.remote_proofs else { return Ok(None) };
+		let header = self.backend.blockchain().expect_header(hash)?;
+		if self.backend.have_state_at(hash, *header.number()) {
+			return Ok(None)
 		}
-	}
-}
 
-impl<B, E, Block> CallExecutor<Block> for LocalCallExecutor<Block, B, E>
-where
-	B: backend::Backend<Block>,
-	E: CodeExecutor + RuntimeVersionOf + Clone + 'static,
-	Block: BlockT,
-{
-	type Error = E::Error;
-
-	type Backend = B;
-
-	fn execution_extensions(&self) -> &ExecutionExtensions<Block> {
-		&self.execution_extensions
+		Ok(Some((remote_proofs, header)))
 	}
 
-	fn call(
+	fn call_at_state<S: StateBackend<HashingFor<Block>>>(
 		&self,
+		state: &S,
 		at_hash: Block::Hash,
 		method: &str,
 		call_data: &[u8],
@@ -101,18 +96,17 @@ where
 		let mut changes = OverlayedChanges::default();
 		let at_number =
 			self.backend.blockchain().expect_block_number_from_id(&BlockId::Hash(at_hash))?;
-		let state = self.backend.state_at(at_hash)?;
 
-		let state_runtime_code = sp_state_machine::backend::BackendRuntimeCode::new(&state);
+		let state_runtime_code = sp_state_machine::backend::BackendRuntimeCode::new(state);
 		let runtime_code =
 			state_runtime_code.runtime_code().map_err(sp_blockchain::Error::RuntimeCode)?;
 
-		let runtime_code = self.code_provider.maybe_override_code(runtime_code, &state, at_hash)?.0;
+		let runtime_code = self.code_provider.maybe_override_code(runtime_code, state, at_hash)?.0;
 
 		let mut extensions = self.execution_extensions.extensions(at_hash, at_number);
 
 		let mut sm = StateMachine::new(
-			&state,
+			state,
 			&mut changes,
 			&self.executor,
 			method,
@@ -126,8 +120,9 @@ where
 		sm.execute().map_err(Into::into)
 	}
 
-	fn contextual_call(
+	fn contextual_call_at_state<S>(
 		&self,
+		state: &S,
 		at_hash: Block::Hash,
 		method: &str,
 		call_data: &[u8],
@@ -135,19 +130,20 @@ where
 		recorder: &Option<ProofRecorder<Block>>,
 		call_context: CallContext,
 		extensions: &RefCell<Extensions>,
-	) -> Result<Vec<u8>, sp_blockchain::Error> {
-		let state = self.backend.state_at(at_hash)?;
-
+	) -> Result<Vec<u8>, sp_blockchain::Error>
+	where
+		S: StateBackend<HashingFor<Block>> + AsTrieBackend<HashingFor<Block>>,
+	{
 		let changes = &mut *changes.borrow_mut();
 
 		// It is important to extract the runtime code here before we create the proof
 		// recorder to not record it. We also need to fetch the runtime code from `state` to
 		// make sure we use the caching layers.
-		let state_runtime_code = sp_state_machine::backend::BackendRuntimeCode::new(&state);
+		let state_runtime_code = sp_state_machine::backend::BackendRuntimeCode::new(state);
 
 		let runtime_code =
 			state_runtime_code.runtime_code().map_err(sp_blockchain::Error::RuntimeCode)?;
-		let runtime_code = self.code_provider.maybe_override_code(runtime_code, &state, at_hash)?.0;
+		let runtime_code = self.code_provider.maybe_override_code(runtime_code, state, at_hash)?.0;
 		let mut extensions = extensions.borrow_mut();
 
 		match recorder {
@@ -173,7 +169,7 @@ where
 			},
 			None => {
 				let mut state_machine = StateMachine::new(
-					&state,
+					state,
 					changes,
 					&self.executor,
 					method,
@@ -189,16 +185,112 @@ where
 		.map_err(Into::into)
 	}
 
-	fn runtime_version(&self, at_hash: Block::Hash) -> sp_blockchain::Result<RuntimeVersion> {
-		let state = self.backend.state_at(at_hash)?;
-		let state_runtime_code = sp_state_machine::backend::BackendRuntimeCode::new(&state);
+	fn runtime_version_at_state<S: StateBackend<HashingFor<Block>>>(
+		&self,
+		state: &S,
+		at_hash: Block::Hash,
+	) -> sp_blockchain::Result<RuntimeVersion> {
+		let state_runtime_code = sp_state_machine::backend::BackendRuntimeCode::new(state);
 
 		let runtime_code =
 			state_runtime_code.runtime_code().map_err(sp_blockchain::Error::RuntimeCode)?;
 		self.code_provider
-			.maybe_override_code(runtime_code, &state, at_hash)
+			.maybe_override_code(runtime_code, state, at_hash)
 			.map(|(_, v)| v)
 	}
+}
+
+impl<Block: BlockT, B, E> Clone for LocalCallExecutor<Block, B, E>
+where
+	E: Clone,
+{
+	fn clone(&self) -> Self {
+		LocalCallExecutor {
+			backend: self.backend.clone(),
+			executor: self.executor.clone(),
+			code_provider: self.code_provider.clone(),
+			execution_extensions: self.execution_extensions.clone(),
+			remote_proofs: self.remote_proofs.clone(),
+		}
+	}
+}
+
+impl<B, E, Block> CallExecutor<Block> for LocalCallExecutor<Block, B, E>
+where
+	B: backend::Backend<Block>,
+	E: CodeExecutor + RuntimeVersionOf + Clone + 'static,
+	Block: BlockT,
+{
+	type Error = E::Error;
+
+	type Backend = B;
+
+	fn execution_extensions(&self) -> &ExecutionExtensions<Block> {
+		&self.execution_extensions
+	}
+
+	fn call(
+		&self,
+		at_hash: Block::Hash,
+		method: &str,
+		call_data: &[u8],
+		context: CallContext,
+	) -> sp_blockchain::Result<Vec<u8>> {
+		if let Some((remote_proofs, header)) = self.remote_header(at_hash)? {
+			let state = remote_proofs.call(&header, method, call_data, None)?;
+			return self.call_at_state(&state, at_hash, method, call_data, context)
+		}
+
+		let state = self.backend.state_at(at_hash)?;
+		self.call_at_state(&state, at_hash, method, call_data, context)
+	}
+
+	fn contextual_call(
+		&self,
+		at_hash: Block::Hash,
+		method: &str,
+		call_data: &[u8],
+		changes: &RefCell<OverlayedChanges<HashingFor<Block>>>,
+		recorder: &Option<ProofRecorder<Block>>,
+		call_context: CallContext,
+		extensions: &RefCell<Extensions>,
+	) -> Result<Vec<u8>, sp_blockchain::Error> {
+		if let Some((remote_proofs, header)) = self.remote_header(at_hash)? {
+			let state = remote_proofs.call(&header, method, call_data, Some(&*changes.borrow()))?;
+			return self.contextual_call_at_state(
+				&state,
+				at_hash,
+				method,
+				call_data,
+				changes,
+				recorder,
+				call_context,
+				extensions,
+			)
+		}
+
+		let state = self.backend.state_at(at_hash)?;
+		self.contextual_call_at_state(
+			&state,
+			at_hash,
+			method,
+			call_data,
+			changes,
+			recorder,
+			call_context,
+			extensions,
+		)
+	}
+
+	fn runtime_version(&self, at_hash: Block::Hash) -> sp_blockchain::Result<RuntimeVersion> {
+		if let Some((remote_proofs, header)) = self.remote_header(at_hash)? {
+			let state = remote_proofs.runtime_code(&header)?;
+			return self.runtime_version_at_state(&state, at_hash)
+		}
+
+		let state = self.backend.state_at(at_hash)?;
+		self.runtime_version_at_state(&state, at_hash)
+	}
 
 	fn prove_execution(
 		&self,
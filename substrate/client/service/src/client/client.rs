@@ -39,6 +39,7 @@ use sc_client_api::{
 		PreCommitActions, ProvideUncles,
 	},
 	execution_extensions::ExecutionExtensions,
+	light::{ProofCheckBackend, RemoteProofs, RemoteProofsProvider, RemoteStorageIter},
 	notifications::{StorageEventStream, StorageNotifications},
 	CallExecutor, ExecutorProvider, KeysIter, OnFinalityAction, OnImportAction, PairsIter,
 	ProofProvider, UnpinWorkerMessage, UsageProvider,
@@ -72,8 +73,8 @@ use sp_runtime::{
 	Justification, Justifications, StateVersion,
 };
 use sp_state_machine::{
-	prove_child_read, prove_range_read_with_child_with_size, prove_read,
-	read_range_proof_check_with_child_on_proving_backend, Backend as StateBackend,
+	prove_child_read, prove_range_read_with_child_with_size, prove_range_read_with_size,
+	prove_read, read_range_proof_check_with_child_on_proving_backend, Backend as StateBackend,
 	ChildStorageCollection, KeyValueStates, KeyValueStorageLevel, StorageCollection,
 	MAX_NESTED_TRIE_DEPTH,
 };
@@ -166,6 +167,10 @@ pub struct ClientConfig<Block: BlockT> {
 	pub wasm_runtime_substitutes: HashMap<NumberFor<Block>, Vec<u8>>,
 	/// Enable recording of storage proofs during block import
 	pub enable_import_proof_recording: bool,
+	/// Fetch the state of the blocks that don't have it locally from remote peers.
+	///
+	/// Only set when running in light client mode.
+	pub remote_proofs: Option<RemoteProofs<Block>>,
 }
 
 impl<Block: BlockT> Default for ClientConfig<Block> {
@@ -177,6 +182,7 @@ impl<Block: BlockT> Default for ClientConfig<Block> {
 			no_genesis: false,
 			wasm_runtime_substitutes: HashMap::new(),
 			enable_import_proof_recording: false,
+			remote_proofs: None,
 		}
 	}
 }
@@ -442,6 +448,61 @@ where
 		self.backend.state_at(hash)
 	}
 
+	/// Fetch a proof of `key` at a given block from remote peers.
+	///
+	/// Returns `None` if the state of the block is available locally or if the client doesn't run
+	/// in light client mode.
+	fn remote_read(
+		&self,
+		hash: Block::Hash,
+		child_info: Option<&ChildInfo>,
+		key: &StorageKey,
+	) -> sp_blockchain::Result<Option<ProofCheckBackend<Block>>> {
+		let Some((remote_proofs, header)) = self.remote_header(hash)? else { return Ok(None) };
+		let keys = vec![key.0.clone()];
+		match child_info {
+			Some(child_info) => remote_proofs.read_child(&header, child_info, keys),
+			None => remote_proofs.read(&header, keys),
+		}
+		.map(Some)
+	}
+
+	/// Iterate over the keys and values following `start_key` under `prefix` at a given block,
+	/// fetched from remote peers.
+	///
+	/// Returns `None` if the state of the block is available locally or if the client doesn't run
+	/// in light client mode.
+	fn remote_iter(
+		&self,
+		hash: Block::Hash,
+		child_info: Option<&ChildInfo>,
+		prefix: Option<&StorageKey>,
+		start_key: Option<&StorageKey>,
+	) -> sp_blockchain::Result<Option<RemoteStorageIter<Block>>> {
+		let Some((remote_proofs, header)) = self.remote_header(hash)? else { return Ok(None) };
+		Ok(Some(remote_proofs.iter(
+			header,
+			child_info.cloned(),
+			prefix.map(|prefix| prefix.0.clone()),
+			start_key.map(|start_key| start_key.0.clone()),
+		)))
+	}
+
+	/// Returns the header of the block and the handle to fetch its state from remote peers, if the
+	/// state of the block is not available locally.
+	fn remote_header(
+		&self,
+		hash: Block::Hash,
+	) -> sp_blockchain::Result<Option<(&RemoteProofs<Block>, Block::Header)>> {
+		let Some(remote_proofs) = &self.config.remote_proofs else { return Ok(None) };
+		let header = self.backend.blockchain().expect_header(hash)?;
+		if self.backend.have_state_at(hash, *header.number()) {
+			return Ok(None)
+		}
+
+		Ok(Some((remote_proofs, header)))
+	}
+
 	/// Get the code at a given block.
 	///
 	/// This takes any potential substitutes into account, but ignores overrides.
@@ -610,6 +671,8 @@ where
 
 						Some((main_sc, child_sc))
 					},
+					// The state is fetched from remote peers on demand.
+					sc_consensus::StorageChanges::Remote => None,
 					sc_consensus::StorageChanges::Import(changes) => {
 						let mut storage = sp_storage::Storage::default();
 						for state in changes.state.0.into_iter() {
@@ -1224,6 +1287,23 @@ where
 			.and_then(|state| prove_child_read(state, child_info, keys).map_err(Into::into))
 	}
 
+	fn read_range_proof(
+		&self,
+		hash: Block::Hash,
+		child_info: Option<&ChildInfo>,
+		prefix: Option<&[u8]>,
+		start_at: Option<&[u8]>,
+		size_limit: usize,
+	) -> sp_blockchain::Result<StorageProof> {
+		self.state_at(hash).and_then(|state| {
+			prove_range_read_with_size::<_, HashingFor<Block>>(
+				state, child_info, prefix, size_limit, start_at,
+			)
+			.map(|(proof, _)| proof)
+			.map_err(Into::into)
+		})
+	}
+
 	fn execution_proof(
 		&self,
 		hash: Block::Hash,
@@ -1405,6 +1485,22 @@ where
 	}
 }
 
+/// Read from the state at a given block with `read`, fetching a proof of `key` from remote peers
+/// if the state of the block is not available locally.
+///
+/// A macro, since the remote and local states are of different types.
+macro_rules! read_state {
+	($client:expr, $hash:expr, $child_info:expr, $key:expr, |$state:ident| $read:expr) => {
+		match $client.remote_read($hash, $child_info, $key)? {
+			Some($state) => $read.map_err(|e| sp_blockchain::Error::from_state(Box::new(e))),
+			None => {
+				let $state = $client.state_at($hash)?;
+				$read.map_err(|e| sp_blockchain::Error::from_state(Box::new(e)))
+			},
+		}
+	};
+}
+
 impl<B, E, Block, RA> StorageProvider<Block, B> for Client<B, E, Block, RA>
 where
 	B: backend::Backend<Block>,
@@ -1417,6 +1513,10 @@ where
 		prefix: Option<&StorageKey>,
 		start_key: Option<&StorageKey>,
 	) -> sp_blockchain::Result<KeysIter<B::State, Block>> {
+		if let Some(iter) = self.remote_iter(hash, None, prefix, start_key)? {
+			return Ok(KeysIter::new_remote(iter))
+		}
+
 		let state = self.state_at(hash)?;
 		KeysIter::new(state, prefix, start_key)
 			.map_err(|e| sp_blockchain::Error::from_state(Box::new(e)))
@@ -1429,6 +1529,10 @@ where
 		prefix: Option<&StorageKey>,
 		start_key: Option<&StorageKey>,
 	) -> sp_blockchain::Result<KeysIter<B::State, Block>> {
+		if let Some(iter) = self.remote_iter(hash, Some(&child_info), prefix, start_key)? {
+			return Ok(KeysIter::new_remote(iter))
+		}
+
 		let state = self.state_at(hash)?;
 		KeysIter::new_child(state, child_info, prefix, start_key)
 			.map_err(|e| sp_blockchain::Error::from_state(Box::new(e)))
@@ -1440,6 +1544,10 @@ where
 		prefix: Option<&StorageKey>,
 		start_key: Option<&StorageKey>,
 	) -> sp_blockchain::Result<PairsIter<B::State, Block>> {
+		if let Some(iter) = self.remote_iter(hash, None, prefix, start_key)? {
+			return Ok(PairsIter::new_remote(iter))
+		}
+
 		let state = self.state_at(hash)?;
 		PairsIter::new(state, prefix, start_key)
 			.map_err(|e| sp_blockchain::Error::from_state(Box::new(e)))
@@ -1450,11 +1558,8 @@ where
 		hash: Block::Hash,
 		key: &StorageKey,
 	) -> sp_blockchain::Result<Option<StorageData>> {
-		Ok(self
-			.state_at(hash)?
-			.storage(&key.0)
-			.map_err(|e| sp_blockchain::Error::from_state(Box::new(e)))?
-			.map(StorageData))
+		let value = read_state!(self, hash, None, key, |state| state.storage(&key.0))?;
+		Ok(value.map(StorageData))
 	}
 
 	fn storage_hash(
@@ -1462,9 +1567,7 @@ where
 		hash: <Block as BlockT>::Hash,
 		key: &StorageKey,
 	) -> sp_blockchain::Result<Option<Block::Hash>> {
-		self.state_at(hash)?
-			.storage_hash(&key.0)
-			.map_err(|e| sp_blockchain::Error::from_state(Box::new(e)))
+		read_state!(self, hash, None, key, |state| state.storage_hash(&key.0))
 	}
 
 	fn child_storage(
@@ -1473,11 +1576,9 @@ where
 		child_info: &ChildInfo,
 		key: &StorageKey,
 	) -> sp_blockchain::Result<Option<StorageData>> {
-		Ok(self
-			.state_at(hash)?
-			.child_storage(child_info, &key.0)
-			.map_err(|e| sp_blockchain::Error::from_state(Box::new(e)))?
-			.map(StorageData))
+		let value = read_state!(self, hash, Some(child_info), key, |state| state
+			.child_storage(child_info, &key.0))?;
+		Ok(value.map(StorageData))
 	}
 
 	fn child_storage_hash(
@@ -1486,9 +1587,8 @@ where
 		child_info: &ChildInfo,
 		key: &StorageKey,
 	) -> sp_blockchain::Result<Option<Block::Hash>> {
-		self.state_at(hash)?
-			.child_storage_hash(child_info, &key.0)
-			.map_err(|e| sp_blockchain::Error::from_state(Box::new(e)))
+		read_state!(self, hash, Some(child_info), key, |state| state
+			.child_storage_hash(child_info, &key.0))
 	}
 
 	fn closest_merkle_value(
@@ -1496,9 +1596,7 @@ where
 		hash: <Block as BlockT>::Hash,
 		key: &StorageKey,
 	) -> blockchain::Result<Option<MerkleValue<<Block as BlockT>::Hash>>> {
-		self.state_at(hash)?
-			.closest_merkle_value(&key.0)
-			.map_err(|e| sp_blockchain::Error::from_state(Box::new(e)))
+		read_state!(self, hash, None, key, |state| state.closest_merkle_value(&key.0))
 	}
 
 	fn child_closest_merkle_value(
@@ -1507,9 +1605,17 @@ where
 		child_info: &ChildInfo,
 		key: &StorageKey,
 	) -> blockchain::Result<Option<MerkleValue<<Block as BlockT>::Hash>>> {
-		self.state_at(hash)?
-			.child_closest_merkle_value(child_info, &key.0)
-			.map_err(|e| sp_blockchain::Error::from_state(Box::new(e)))
+		read_state!(self, hash, Some(child_info), key, |state| state
+			.child_closest_merkle_value(child_info, &key.0))
+	}
+}
+
+impl<B, E, Block, RA> RemoteProofsProvider<Block> for Client<B, E, Block, RA>
+where
+	Block: BlockT,
+{
+	fn remote_proofs(&self) -> Option<&RemoteProofs<Block>> {
+		self.config.remote_proofs.as_ref()
 	}
 }
 
@@ -1982,6 +2088,22 @@ where
 		self.backend.blockchain().block_indexed_body(hash)
 	}
 
+	fn extrinsic_locations(
+		&self,
+		hash: Block::Hash,
+	) -> sp_blockchain::Result<Option<Vec<(Block::Hash, u32)>>> {
+		self.backend.blockchain().extrinsic_locations(hash)
+	}
+
+	fn signer_extrinsic_locations(
+		&self,
+		signer: &[u8],
+		start_key: Option<&[u8]>,
+		limit: usize,
+	) -> sp_blockchain::Result<Option<(Vec<(Block::Hash, u32)>, Option<Vec<u8>>)>> {
+		self.backend.blockchain().signer_extrinsic_locations(signer, start_key, limit)
+	}
+
 	fn requires_full_sync(&self) -> bool {
 		self.backend.requires_full_sync()
 	}
@@ -20,8 +20,8 @@ use super::{client::ClientConfig, wasm_override::WasmOverride, wasm_substitutes:
 use sc_client_api::backend;
 use sc_executor::{RuntimeVersion, RuntimeVersionOf};
 use sp_core::traits::{FetchRuntimeCode, RuntimeCode};
-use sp_runtime::traits::Block as BlockT;
-use sp_state_machine::{Ext, OverlayedChanges};
+use sp_runtime::traits::{Block as BlockT, HashingFor};
+use sp_state_machine::{Backend as StateBackend, Ext, OverlayedChanges};
 use std::sync::Arc;
 
 /// Provider for fetching `:code` of a block.
@@ -96,10 +96,10 @@ where
 	/// Maybe override the given `onchain_code`.
 	///
 	/// This takes into account potential overrides/substitutes.
-	pub fn maybe_override_code<'a>(
+	pub fn maybe_override_code<'a, S: StateBackend<HashingFor<Block>>>(
 		&'a self,
 		onchain_code: RuntimeCode<'a>,
-		state: &Backend::State,
+		state: &S,
 		hash: Block::Hash,
 	) -> sp_blockchain::Result<(RuntimeCode<'a>, RuntimeVersion)> {
 		self.maybe_override_code_internal(onchain_code, state, hash, false)
@@ -108,10 +108,10 @@ where
 	/// Maybe override the given `onchain_code`.
 	///
 	/// This takes into account potential overrides(depending on `ignore_overrides`)/substitutes.
-	fn maybe_override_code_internal<'a>(
+	fn maybe_override_code_internal<'a, S: StateBackend<HashingFor<Block>>>(
 		&'a self,
 		onchain_code: RuntimeCode<'a>,
-		state: &Backend::State,
+		state: &S,
 		hash: Block::Hash,
 		ignore_overrides: bool,
 	) -> sp_blockchain::Result<(RuntimeCode<'a>, RuntimeVersion)> {
@@ -148,10 +148,10 @@ where
 	}
 
 	/// Returns the on chain runtime version.
-	fn on_chain_runtime_version(
+	fn on_chain_runtime_version<S: StateBackend<HashingFor<Block>>>(
 		&self,
 		code: &RuntimeCode,
-		state: &Backend::State,
+		state: &S,
 	) -> sp_blockchain::Result<RuntimeVersion> {
 		let mut overlay = OverlayedChanges::default();
 
@@ -21,8 +21,10 @@
 pub use jsonrpsee::server::BatchRequestConfig as RpcBatchRequestConfig;
 use prometheus_endpoint::Registry;
 use sc_chain_spec::ChainSpec;
-pub use sc_client_db::{BlocksPruning, Database, DatabaseSource, PruningMode};
-pub use sc_executor::{WasmExecutionMethod, WasmtimeInstantiationStrategy};
+pub use sc_client_db::{BlocksPruning, Database, DatabaseSource, ExtrinsicSignerFn, PruningMode};
+pub use sc_executor::{
+	PolkaVmBackend, Profiler, WasmExecutionMethod, WasmtimeInstantiationStrategy,
+};
 pub use sc_network::{
 	config::{
 		MultiaddrWithPeerId, NetworkConfiguration, NodeKeyConfig, NonDefaultSetConfig, ProtocolId,
@@ -44,6 +46,7 @@ use std::{
 	net::SocketAddr,
 	num::NonZeroU32,
 	path::{Path, PathBuf},
+	sync::Arc,
 };
 use tempfile::TempDir;
 
@@ -76,6 +79,8 @@ pub struct Configuration {
 	///
 	/// NOTE: only finalized blocks are subject for removal!
 	pub blocks_pruning: BlocksPruning,
+	/// Index the extrinsics of imported blocks by hash.
+	pub extrinsic_index: bool,
 	/// Chain configuration.
 	pub chain_spec: Box<dyn ChainSpec>,
 	/// Runtime executor configuration.
@@ -207,7 +212,10 @@ impl Configuration {
 	/// Returns true if the genesis state writing will be skipped while initializing the genesis
 	/// block.
 	pub fn no_genesis(&self) -> bool {
-		matches!(self.network.sync_mode, SyncMode::LightState { .. } | SyncMode::Warp { .. })
+		matches!(
+			self.network.sync_mode,
+			SyncMode::LightState { .. } | SyncMode::Warp { .. } | SyncMode::Light
+		)
 	}
 
 	/// Returns the database config for creating the backend.
@@ -217,6 +225,8 @@ impl Configuration {
 			state_pruning: self.state_pruning.clone(),
 			source: self.database.clone(),
 			blocks_pruning: self.blocks_pruning,
+			extrinsic_index: self.extrinsic_index,
+			extrinsic_signer: None,
 		}
 	}
 }
@@ -336,6 +346,8 @@ pub struct ExecutorConfiguration {
 	pub default_heap_pages: Option<u64>,
 	/// Maximum number of different runtime versions that can be cached.
 	pub runtime_cache_size: u8,
+	/// Records the time spent in the runtime calls, if set.
+	pub profiler: Option<Arc<Profiler>>,
 }
 
 impl Default for ExecutorConfiguration {
@@ -345,6 +357,7 @@ impl Default for ExecutorConfiguration {
 			max_runtime_instances: 8,
 			default_heap_pages: None,
 			runtime_cache_size: 2,
+			profiler: None,
 		}
 	}
 }
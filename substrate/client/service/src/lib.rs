@@ -77,7 +77,8 @@ pub use sc_chain_spec::{
 };
 
 pub use config::{
-	BasePath, BlocksPruning, Configuration, DatabaseSource, PruningMode, Role, RpcMethods, TaskType,
+	BasePath, BlocksPruning, Configuration, DatabaseSource, ExtrinsicSignerFn, PruningMode, Role,
+	RpcMethods, TaskType,
 };
 pub use sc_chain_spec::{
 	ChainSpec, ChainType, Extension as ChainSpecExtension, GenericChainSpec, NoExtension,
@@ -348,6 +349,34 @@ pub async fn build_system_rpc_future<
 				let _ =
 					sender.send(reserved_peers.iter().map(|peer_id| peer_id.to_base58()).collect());
 			},
+			sc_rpc::system::Request::NetworkBanPeer(peer_id, sender) => {
+				let _ = match peer_id.parse::<PeerId>() {
+					Ok(peer_id) => {
+						network_service.ban_peer(peer_id);
+						sender.send(Ok(()))
+					},
+					Err(e) => sender.send(Err(sc_rpc::system::error::Error::MalformattedPeerArg(
+						e.to_string(),
+					))),
+				};
+			},
+			sc_rpc::system::Request::NetworkUnbanPeer(peer_id, sender) => {
+				let _ = match peer_id.parse::<PeerId>() {
+					Ok(peer_id) => sender.send(Ok(network_service.unban_peer(&peer_id))),
+					Err(e) => sender.send(Err(sc_rpc::system::error::Error::MalformattedPeerArg(
+						e.to_string(),
+					))),
+				};
+			},
+			sc_rpc::system::Request::NetworkBannedPeers(sender) => {
+				let _ = sender.send(
+					network_service
+						.banned_peers()
+						.iter()
+						.map(|peer_id| peer_id.to_base58())
+						.collect(),
+				);
+			},
 			sc_rpc::system::Request::NodeRoles(sender) => {
 				use sc_rpc::system::NodeRole;
 
@@ -0,0 +1,285 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use futures::{
+	executor::block_on,
+	future::{self, BoxFuture},
+	FutureExt,
+};
+use sc_block_builder::BlockBuilderBuilder;
+use sc_client_api::{
+	light::{RemoteProofFetcher, RemoteProofs},
+	HeaderBackend, ProofProvider, StorageProof, StorageProvider,
+};
+use sc_consensus::{BlockImport, BlockImportParams, ForkChoiceStrategy, StateAction};
+use sc_executor::WasmExecutor;
+use sc_service::client::{new_with_backend, Client, LocalCallExecutor};
+use sp_api::{Core, ProvideRuntimeApi};
+use sp_consensus::BlockOrigin;
+use sp_core::testing::TaskExecutor;
+use sp_runtime::traits::Header as HeaderT;
+use sp_storage::{ChildInfo, ChildType, PrefixedStorageKey, StorageKey};
+use std::sync::Arc;
+use substrate_test_runtime::TestAPI;
+use substrate_test_runtime_client::{
+	runtime::{currency::DOLLARS, Block, Hash, Header, RuntimeApi, Transfer},
+	Backend, BlockBuilderExt, ClientBlockImportExt, GenesisInit, Sr25519Keyring, TestClient,
+	TestClientBuilder, TestClientBuilderExt,
+};
+
+type LightClient = Client<
+	Backend,
+	LocalCallExecutor<Block, Backend, WasmExecutor<sp_io::SubstrateHostFunctions>>,
+	Block,
+	RuntimeApi,
+>;
+
+/// Answers the remote requests of the light client with the proofs of a full client.
+struct FullClientFetcher {
+	client: Arc<TestClient>,
+	/// Size limit of the range proofs, small enough to split the iterations in many requests.
+	range_proof_size: usize,
+}
+
+fn child_info(storage_key: &PrefixedStorageKey) -> sp_blockchain::Result<ChildInfo> {
+	match ChildType::from_prefixed_key(storage_key) {
+		Some((ChildType::ParentKeyId, storage_key)) => Ok(ChildInfo::new_default(storage_key)),
+		None => Err(sp_blockchain::Error::InvalidChildStorageKey),
+	}
+}
+
+impl RemoteProofFetcher<Block> for FullClientFetcher {
+	fn read_proof(
+		&self,
+		block: Hash,
+		keys: Vec<Vec<u8>>,
+	) -> BoxFuture<'static, sp_blockchain::Result<StorageProof>> {
+		future::ready(self.client.read_proof(block, &mut keys.iter().map(AsRef::as_ref))).boxed()
+	}
+
+	fn read_child_proof(
+		&self,
+		block: Hash,
+		storage_key: PrefixedStorageKey,
+		keys: Vec<Vec<u8>>,
+	) -> BoxFuture<'static, sp_blockchain::Result<StorageProof>> {
+		let proof = child_info(&storage_key).and_then(|child_info| {
+			self.client
+				.read_child_proof(block, &child_info, &mut keys.iter().map(AsRef::as_ref))
+		});
+		future::ready(proof).boxed()
+	}
+
+	fn read_range_proof(
+		&self,
+		block: Hash,
+		storage_key: Option<PrefixedStorageKey>,
+		prefix: Option<Vec<u8>>,
+		start_at: Option<Vec<u8>>,
+	) -> BoxFuture<'static, sp_blockchain::Result<StorageProof>> {
+		let proof = storage_key.as_ref().map(child_info).transpose().and_then(|child_info| {
+			self.client.read_range_proof(
+				block,
+				child_info.as_ref(),
+				prefix.as_deref(),
+				start_at.as_deref(),
+				self.range_proof_size,
+			)
+		});
+		future::ready(proof).boxed()
+	}
+
+	fn execution_proof(
+		&self,
+		block: Hash,
+		method: String,
+		call_data: Vec<u8>,
+	) -> BoxFuture<'static, sp_blockchain::Result<StorageProof>> {
+		let proof = self.client.execution_proof(block, &method, &call_data).map(|(_, proof)| proof);
+		future::ready(proof).boxed()
+	}
+}
+
+fn test_child_info() -> ChildInfo {
+	ChildInfo::new_default(b"child")
+}
+
+/// Returns a full client with a block on top of genesis, and a light client that only has the
+/// header of that block and fetches its state from the full client, if `remote` is set.
+fn full_and_light_clients(remote: bool) -> (Arc<TestClient>, Arc<LightClient>, Header) {
+	let mut builder = TestClientBuilder::new()
+		.add_extra_child_storage(&test_child_info(), b"first".to_vec(), vec![1u8; 32])
+		.add_extra_child_storage(&test_child_info(), b"second".to_vec(), vec![2u8; 32])
+		.add_extra_child_storage(&test_child_info(), b"third".to_vec(), vec![3u8; 32]);
+	let genesis_storage = builder.genesis_init_mut().genesis_storage();
+	let full = Arc::new(builder.build());
+
+	let mut builder = BlockBuilderBuilder::new(&*full)
+		.on_parent_block(full.info().genesis_hash)
+		.with_parent_block_number(0)
+		.build()
+		.unwrap();
+	builder
+		.push_transfer(Transfer {
+			from: Sr25519Keyring::Alice.into(),
+			to: Sr25519Keyring::Ferdie.into(),
+			amount: 42 * DOLLARS,
+			nonce: 0,
+		})
+		.unwrap();
+	let block = builder.build().unwrap().block;
+	block_on(full.import(BlockOrigin::Own, block.clone())).unwrap();
+
+	let backend = Arc::new(Backend::new_test(1000, 0));
+	let executor = WasmExecutor::<sp_io::SubstrateHostFunctions>::default();
+	let remote_proofs = RemoteProofs::new();
+	if remote {
+		remote_proofs.set_fetcher(Arc::new(FullClientFetcher {
+			client: full.clone(),
+			range_proof_size: 512,
+		}));
+	}
+	let client_config =
+		sc_service::ClientConfig { remote_proofs: Some(remote_proofs), ..Default::default() };
+	let genesis_block_builder = sc_service::GenesisBlockBuilder::new(
+		&genesis_storage,
+		!client_config.no_genesis,
+		backend.clone(),
+		executor.clone(),
+	)
+	.unwrap();
+	let light = new_with_backend::<_, _, Block, _, RuntimeApi>(
+		backend,
+		executor,
+		genesis_block_builder,
+		Box::new(TaskExecutor::new()),
+		None,
+		None,
+		client_config,
+	)
+	.unwrap();
+
+	let mut import = BlockImportParams::new(BlockOrigin::NetworkInitialSync, block.header.clone());
+	import.state_action = StateAction::ApplyChanges(sc_consensus::StorageChanges::Remote);
+	import.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+	block_on(light.import_block(import)).unwrap();
+
+	(full, Arc::new(light), block.header)
+}
+
+#[test]
+fn light_client_reads_storage_from_remote_proofs() {
+	let (full, light, header) = full_and_light_clients(true);
+	let hash = header.hash();
+	assert_eq!(light.info().best_hash, hash);
+
+	let pairs = full.storage_pairs(hash, None, None).unwrap().collect::<Vec<_>>();
+	assert!(pairs.len() > 10);
+	assert_eq!(light.storage_pairs(hash, None, None).unwrap().collect::<Vec<_>>(), pairs);
+
+	for (key, value) in &pairs {
+		assert_eq!(light.storage(hash, key).unwrap().as_ref(), Some(value));
+		assert_eq!(light.storage_hash(hash, key).unwrap(), full.storage_hash(hash, key).unwrap());
+	}
+	let missing = StorageKey(b"missing".to_vec());
+	assert_eq!(light.storage(hash, &missing).unwrap(), None);
+
+	// A prefix shared by some of the keys, and a start key within that prefix.
+	let prefix = StorageKey(pairs[pairs.len() / 2].0 .0[..2].to_vec());
+	let full_keys = full.storage_keys(hash, Some(&prefix), None).unwrap().collect::<Vec<_>>();
+	assert!(!full_keys.is_empty());
+	assert_eq!(
+		light.storage_keys(hash, Some(&prefix), None).unwrap().collect::<Vec<_>>(),
+		full_keys,
+	);
+	assert_eq!(
+		light
+			.storage_keys(hash, Some(&prefix), Some(&full_keys[0]))
+			.unwrap()
+			.collect::<Vec<_>>(),
+		full_keys[1..],
+	);
+	assert_eq!(
+		light.closest_merkle_value(hash, &prefix).unwrap(),
+		full.closest_merkle_value(hash, &prefix).unwrap(),
+	);
+
+	let child_keys = light
+		.child_storage_keys(hash, test_child_info(), None, Some(&StorageKey(b"first".to_vec())))
+		.unwrap()
+		.collect::<Vec<_>>();
+	assert_eq!(child_keys, vec![StorageKey(b"second".to_vec()), StorageKey(b"third".to_vec())]);
+	assert_eq!(
+		light
+			.child_storage(hash, &test_child_info(), &StorageKey(b"third".to_vec()))
+			.unwrap()
+			.map(|value| value.0),
+		Some(vec![3u8; 32]),
+	);
+}
+
+#[test]
+fn light_client_calls_runtime_from_remote_proofs() {
+	let (full, light, header) = full_and_light_clients(true);
+	let hash = header.hash();
+
+	assert_eq!(light.runtime_version_at(hash).unwrap(), full.runtime_version_at(hash).unwrap());
+	assert_eq!(
+		light.runtime_api().balance_of(hash, Sr25519Keyring::Ferdie.into()).unwrap(),
+		full.runtime_api().balance_of(hash, Sr25519Keyring::Ferdie.into()).unwrap(),
+	);
+
+	// The second call runs on top of the changes of the first one.
+	let next_header =
+		Header::new(2, Default::default(), Default::default(), hash, Default::default());
+	let api = light.runtime_api();
+	api.initialize_block(hash, &next_header).unwrap();
+	assert_eq!(api.get_block_number(hash).unwrap(), 2);
+	assert_eq!(
+		api.balance_of(hash, Sr25519Keyring::Alice.into()).unwrap(),
+		full.runtime_api().balance_of(hash, Sr25519Keyring::Alice.into()).unwrap(),
+	);
+}
+
+#[test]
+fn light_client_reads_fail_without_fetcher() {
+	let (_, light, header) = full_and_light_clients(false);
+	let key = StorageKey(sp_storage::well_known_keys::CODE.to_vec());
+
+	assert!(matches!(
+		light.storage(header.hash(), &key),
+		Err(sp_blockchain::Error::RemoteFetchFailed)
+	));
+	assert_eq!(light.storage_keys(header.hash(), None, None).unwrap().count(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn light_client_reads_within_tokio_runtime() {
+	let (full, light, header) = full_and_light_clients(true);
+	let key = StorageKey(sp_storage::well_known_keys::CODE.to_vec());
+
+	let value = tokio::spawn(async move { light.storage(header.hash(), &key) })
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(
+		value,
+		full.storage(header.hash(), &StorageKey(sp_storage::well_known_keys::CODE.to_vec()))
+			.unwrap()
+	);
+}
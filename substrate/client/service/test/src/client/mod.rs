@@ -53,6 +53,7 @@ use substrate_test_runtime_client::{
 };
 
 mod db;
+mod light;
 
 const TEST_ENGINE_ID: ConsensusEngineId = *b"TEST";
 
@@ -1486,6 +1487,8 @@ fn doesnt_import_blocks_that_revert_finality() {
 				state_pruning: Some(PruningMode::ArchiveAll),
 				blocks_pruning: BlocksPruning::KeepAll,
 				source: DatabaseSource::RocksDb { path: tmp.path().into(), cache_size: 1024 },
+				extrinsic_index: false,
+				extrinsic_signer: None,
 			},
 			u64::MAX,
 		)
@@ -1763,6 +1766,8 @@ fn returns_status_for_pruned_blocks() {
 				state_pruning: Some(PruningMode::blocks_pruning(1)),
 				blocks_pruning: BlocksPruning::KeepFinalized,
 				source: DatabaseSource::RocksDb { path: tmp.path().into(), cache_size: 1024 },
+				extrinsic_index: false,
+				extrinsic_signer: None,
 			},
 			u64::MAX,
 		)
@@ -46,7 +46,7 @@ mod pruning;
 #[cfg(test)]
 mod test;
 
-use codec::Codec;
+use codec::{Codec, Decode, Encode};
 use log::trace;
 use noncanonical::NonCanonicalOverlay;
 use parking_lot::RwLock;
@@ -54,6 +54,7 @@ use pruning::{HaveBlock, RefWindow};
 use std::{
 	collections::{hash_map::Entry, HashMap},
 	fmt,
+	num::NonZeroU32,
 };
 
 const LOG_TARGET: &str = "state-db";
@@ -62,6 +63,8 @@ const PRUNING_MODE: &[u8] = b"mode";
 const PRUNING_MODE_ARCHIVE: &[u8] = b"archive";
 const PRUNING_MODE_ARCHIVE_CANON: &[u8] = b"archive_canonical";
 const PRUNING_MODE_CONSTRAINED: &[u8] = b"constrained";
+const PRUNING_MODE_SNAPSHOTS: &[u8] = b"snapshots";
+const SNAPSHOT_PERIOD: &[u8] = b"snapshot_period";
 pub(crate) const DEFAULT_MAX_BLOCK_CONSTRAINT: u32 = 256;
 
 /// Database value type.
@@ -145,6 +148,8 @@ pub enum StateDbError {
 	BlockUnavailable,
 	/// Block record is missing from the pruning window
 	BlockMissing,
+	/// Snapshot pruning mode requires the backend database to count references.
+	SnapshotsRequireRefCounting,
 }
 
 impl<E> From<StateDbError> for Error<E> {
@@ -196,6 +201,9 @@ impl fmt::Debug for StateDbError {
 				write!(f, "Trying to get a block record from db while it is not commit to db yet")
 			},
 			Self::BlockMissing => write!(f, "Block record is missing from the pruning window"),
+			Self::SnapshotsRequireRefCounting => {
+				write!(f, "Snapshot pruning mode requires a database with reference counting")
+			},
 		}
 	}
 }
@@ -235,6 +243,9 @@ pub enum PruningMode {
 	ArchiveAll,
 	/// Canonicalization discards non-canonical nodes. All the canonical nodes are kept in the DB.
 	ArchiveCanonical,
+	/// Maintain a pruning window of `max_blocks` blocks, and keep the state of every `period`th
+	/// canonical block. Requires a database with reference counting.
+	Snapshots { period: NonZeroU32, max_blocks: u32 },
 }
 
 impl PruningMode {
@@ -243,11 +254,24 @@ impl PruningMode {
 		PruningMode::Constrained(Constraints { max_blocks: Some(n) })
 	}
 
+	/// Create a mode that keeps the given number of blocks and every `period`th block.
+	pub fn snapshots_pruning(period: NonZeroU32, max_blocks: u32) -> PruningMode {
+		PruningMode::Snapshots { period, max_blocks }
+	}
+
 	/// Is this an archive (either ArchiveAll or ArchiveCanonical) pruning mode?
 	pub fn is_archive(&self) -> bool {
 		match *self {
 			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => true,
-			PruningMode::Constrained(_) => false,
+			PruningMode::Constrained(_) | PruningMode::Snapshots { .. } => false,
+		}
+	}
+
+	/// Is the state of the canonical block with the given number kept as a snapshot?
+	pub fn is_snapshot(&self, number: u64) -> bool {
+		match *self {
+			PruningMode::Snapshots { period, .. } => number % period.get() as u64 == 0,
+			_ => false,
 		}
 	}
 
@@ -257,9 +281,13 @@ impl PruningMode {
 			PruningMode::ArchiveAll => PRUNING_MODE_ARCHIVE,
 			PruningMode::ArchiveCanonical => PRUNING_MODE_ARCHIVE_CANON,
 			PruningMode::Constrained(_) => PRUNING_MODE_CONSTRAINED,
+			PruningMode::Snapshots { .. } => PRUNING_MODE_SNAPSHOTS,
 		}
 	}
 
+	/// Returns the pruning mode with the given id.
+	///
+	/// The snapshot period is not part of the id, and is stored separately.
 	pub fn from_id(id: &[u8]) -> Option<Self> {
 		match id {
 			PRUNING_MODE_ARCHIVE => Some(Self::ArchiveAll),
@@ -319,6 +347,12 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 		let pruning: Option<RefWindow<BlockHash, Key, D>> = match mode {
 			PruningMode::Constrained(Constraints { max_blocks }) =>
 				Some(RefWindow::new(db, max_blocks.unwrap_or(0), ref_counting)?),
+			PruningMode::Snapshots { period, max_blocks } => {
+				if ref_counting {
+					return Err(StateDbError::SnapshotsRequireRefCounting.into())
+				}
+				Some(RefWindow::new_with_snapshots(db, max_blocks, period)?)
+			},
 			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => None,
 		};
 
@@ -338,7 +372,9 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 				// write changes immediately
 				Ok(CommitSet { data: changeset, meta: Default::default() })
 			},
-			PruningMode::Constrained(_) | PruningMode::ArchiveCanonical => self
+			PruningMode::Constrained(_) |
+			PruningMode::ArchiveCanonical |
+			PruningMode::Snapshots { .. } => self
 				.non_canonical
 				.insert(hash, number, parent_hash, changeset)
 				.map_err(Into::into),
@@ -380,7 +416,9 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 	fn is_pruned(&self, hash: &BlockHash, number: u64) -> IsPruned {
 		match self.mode {
 			PruningMode::ArchiveAll => IsPruned::NotPruned,
-			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) => {
+			PruningMode::ArchiveCanonical |
+			PruningMode::Constrained(_) |
+			PruningMode::Snapshots { .. } => {
 				if self
 					.non_canonical
 					.last_canonicalized_block_number()
@@ -397,6 +435,8 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 						// We don't know for sure.
 						None => IsPruned::MaybePruned,
 						Some(pruning) => match pruning.have_block(hash, number) {
+							// The state of a snapshot is only kept for the canonical block.
+							HaveBlock::No if self.mode.is_snapshot(number) => IsPruned::MaybePruned,
 							HaveBlock::No => IsPruned::Pruned,
 							HaveBlock::Yes => IsPruned::NotPruned,
 							HaveBlock::Maybe => IsPruned::MaybePruned,
@@ -408,11 +448,14 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 	}
 
 	fn prune(&mut self, commit: &mut CommitSet<Key>) -> Result<(), Error<D::Error>> {
-		if let (&mut Some(ref mut pruning), PruningMode::Constrained(constraints)) =
-			(&mut self.pruning, &self.mode)
-		{
+		let max_blocks = match self.mode {
+			PruningMode::Constrained(ref constraints) => constraints.max_blocks.unwrap_or(0),
+			PruningMode::Snapshots { max_blocks, .. } => max_blocks,
+			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => return Ok(()),
+		};
+		if let Some(ref mut pruning) = self.pruning {
 			loop {
-				if pruning.window_size() <= constraints.max_blocks.unwrap_or(0) as u64 {
+				if pruning.window_size() <= max_blocks as u64 {
 					break
 				}
 
@@ -442,16 +485,18 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 	fn revert_one(&mut self) -> Option<CommitSet<Key>> {
 		match self.mode {
 			PruningMode::ArchiveAll => Some(CommitSet::default()),
-			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) =>
-				self.non_canonical.revert_one(),
+			PruningMode::ArchiveCanonical |
+			PruningMode::Constrained(_) |
+			PruningMode::Snapshots { .. } => self.non_canonical.revert_one(),
 		}
 	}
 
 	fn remove(&mut self, hash: &BlockHash) -> Option<CommitSet<Key>> {
 		match self.mode {
 			PruningMode::ArchiveAll => Some(CommitSet::default()),
-			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) =>
-				self.non_canonical.remove(hash),
+			PruningMode::ArchiveCanonical |
+			PruningMode::Constrained(_) |
+			PruningMode::Snapshots { .. } => self.non_canonical.remove(hash),
 		}
 	}
 
@@ -461,11 +506,14 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 	{
 		match self.mode {
 			PruningMode::ArchiveAll => Ok(()),
-			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) => {
+			PruningMode::ArchiveCanonical |
+			PruningMode::Constrained(_) |
+			PruningMode::Snapshots { .. } => {
 				let have_block = self.non_canonical.have_block(hash) ||
 					self.pruning.as_ref().map_or_else(
 						|| hint(),
 						|pruning| match pruning.have_block(hash, number) {
+							HaveBlock::No if self.mode.is_snapshot(number) => hint(),
 							HaveBlock::No => false,
 							HaveBlock::Yes => true,
 							HaveBlock::Maybe => hint(),
@@ -564,6 +612,12 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDb<BlockHash, Key, D> {
 
 			cs.meta.inserted.push((key, value));
 
+			if let PruningMode::Snapshots { period, .. } = selected_mode {
+				cs.meta
+					.inserted
+					.push((to_meta_key(SNAPSHOT_PERIOD, &()), period.get().encode()));
+			}
+
 			cs
 		} else {
 			Default::default()
@@ -674,7 +728,18 @@ pub enum IsPruned {
 fn fetch_stored_pruning_mode<D: MetaDb>(db: &D) -> Result<Option<PruningMode>, Error<D::Error>> {
 	let meta_key_mode = to_meta_key(PRUNING_MODE, &());
 	if let Some(stored_mode) = db.get_meta(&meta_key_mode).map_err(Error::Db)? {
-		if let Some(mode) = PruningMode::from_id(&stored_mode) {
+		if stored_mode == PRUNING_MODE_SNAPSHOTS {
+			let period = db
+				.get_meta(&to_meta_key(SNAPSHOT_PERIOD, &()))
+				.map_err(Error::Db)?
+				.map(|period| u32::decode(&mut period.as_slice()))
+				.transpose()?
+				.and_then(NonZeroU32::new)
+				.ok_or_else(|| {
+					StateDbError::Metadata("Invalid value stored for SNAPSHOT_PERIOD".into())
+				})?;
+			Ok(Some(PruningMode::Snapshots { period, max_blocks: DEFAULT_MAX_BLOCK_CONSTRAINT }))
+		} else if let Some(mode) = PruningMode::from_id(&stored_mode) {
 			Ok(Some(mode))
 		} else {
 			Err(StateDbError::Metadata(format!(
@@ -698,6 +763,10 @@ fn choose_pruning_mode(
 			Ok(PruningMode::ArchiveCanonical),
 		(PruningMode::Constrained(_), PruningMode::Constrained(requested)) =>
 			Ok(PruningMode::Constrained(requested)),
+		(
+			PruningMode::Snapshots { period: stored, .. },
+			PruningMode::Snapshots { period: requested, max_blocks },
+		) if stored == requested => Ok(PruningMode::Snapshots { period: requested, max_blocks }),
 		(stored, requested) => Err(StateDbError::IncompatiblePruningModes { requested, stored }),
 	}
 }
@@ -709,6 +778,7 @@ mod tests {
 		Constraints, Error, IsPruned, PruningMode, StateDb, StateDbError,
 	};
 	use sp_core::H256;
+	use std::num::NonZeroU32;
 
 	fn make_test_db(settings: PruningMode) -> (TestDb, StateDb<H256, H256, TestDb>) {
 		let mut db = make_db(&[91, 921, 922, 93, 94]);
@@ -946,4 +1016,30 @@ mod tests {
 			check_stored_and_requested_mode_compatibility(created, reopened, expected);
 		}
 	}
+
+	#[test]
+	fn snapshots_pruning_mode_compatibility() {
+		let snapshots = |period, max_blocks| {
+			PruningMode::snapshots_pruning(NonZeroU32::new(period).unwrap(), max_blocks)
+		};
+		for (created, reopened, expected) in [
+			(Some(snapshots(100, 256)), None, Ok(snapshots(100, 256))),
+			(Some(snapshots(100, 256)), Some(snapshots(100, 512)), Ok(snapshots(100, 512))),
+			(Some(snapshots(100, 256)), Some(snapshots(200, 256)), Err(())),
+			(Some(snapshots(100, 256)), Some(PruningMode::blocks_pruning(256)), Err(())),
+			(Some(PruningMode::blocks_pruning(256)), Some(snapshots(100, 256)), Err(())),
+		] {
+			check_stored_and_requested_mode_compatibility(created, reopened, expected);
+		}
+	}
+
+	#[test]
+	fn snapshots_require_ref_counting() {
+		let db = make_db(&[]);
+		let mode = PruningMode::snapshots_pruning(NonZeroU32::new(100).unwrap(), 256);
+		assert!(matches!(
+			StateDb::<H256, H256, TestDb>::open(db, Some(mode), true, true),
+			Err(Error::StateDb(StateDbError::SnapshotsRequireRefCounting))
+		));
+	}
 }
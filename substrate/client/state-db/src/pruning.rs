@@ -23,6 +23,12 @@
 //! If a node is re-inserted into the window it gets removed from
 //! the death list.
 //! The changes are journaled in the DB.
+//!
+//! When snapshots are enabled, the state of every `period`th block is never pruned. Such a block
+//! is called a snapshot. A deletion of a pruned block is only applied if it cancels a reference
+//! that was inserted by one of the blocks pruned since the last snapshot. All the other deletions
+//! would remove a node of the snapshot state, and are skipped. This relies on the backend database
+//! counting the references of each node.
 
 use crate::{
 	noncanonical::LAST_CANONICAL, to_meta_key, CommitSet, Error, Hash, MetaDb, StateDbError,
@@ -30,7 +36,10 @@ use crate::{
 };
 use codec::{Decode, Encode};
 use log::trace;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::{
+	collections::{HashMap, HashSet, VecDeque},
+	num::NonZeroU32,
+};
 
 pub(crate) const LAST_PRUNED: &[u8] = b"last_pruned";
 const PRUNING_JOURNAL: &[u8] = b"pruning_journal";
@@ -42,6 +51,50 @@ pub struct RefWindow<BlockHash: Hash, Key: Hash, D: MetaDb> {
 	queue: DeathRowQueue<BlockHash, Key, D>,
 	/// Block number that is next to be pruned.
 	base: u64,
+	/// The snapshots kept by the window, if enabled.
+	snapshots: Option<Snapshots<Key>>,
+}
+
+/// Tracks the references that may be removed without affecting the state of the last snapshot.
+struct Snapshots<Key: Hash> {
+	/// The state of every `period`th block is kept.
+	period: u64,
+	/// The number of references to each key that were inserted by the blocks pruned since the
+	/// last snapshot, and were not removed yet.
+	///
+	/// This is only kept in memory. After a restart, the deletions of the keys inserted before the
+	/// restart are skipped, which leaves some unused nodes in the database until the next
+	/// snapshot.
+	inserted: HashMap<Key, u32>,
+}
+
+impl<Key: Hash> Snapshots<Key> {
+	/// Returns the keys of `deleted` that can be removed from the database when pruning block
+	/// `number`, and records the keys `inserted` by this block.
+	fn prune(&mut self, number: u64, deleted: HashSet<Key>, inserted: Vec<Key>) -> Vec<Key> {
+		let deleted = deleted
+			.into_iter()
+			.filter(|key| match self.inserted.get_mut(key) {
+				Some(refs) => {
+					*refs -= 1;
+					if *refs == 0 {
+						self.inserted.remove(key);
+					}
+					true
+				},
+				None => false,
+			})
+			.collect();
+		if number % self.period == 0 {
+			// The state of this block is kept, including the nodes it inserted.
+			self.inserted.clear();
+		} else {
+			for key in inserted {
+				*self.inserted.entry(key).or_default() += 1;
+			}
+		}
+		deleted
+	}
 }
 
 /// `DeathRowQueue` used to keep track of blocks in the pruning window, there are two flavors:
@@ -136,7 +189,11 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 				// cache.
 				if num == base + cache.len() as u64 && cache.len() < *cache_capacity {
 					trace!(target: LOG_TARGET, "Adding to DB backed cache {:?} (#{})", hash, num);
-					cache.push_back(DeathRow { hash, deleted: deleted.into_iter().collect() });
+					cache.push_back(DeathRow {
+						hash,
+						deleted: deleted.into_iter().collect(),
+						inserted,
+					});
 				}
 				*last = Some(num);
 			},
@@ -152,7 +209,11 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 				for k in deleted.iter() {
 					death_index.insert(k.clone(), imported_block);
 				}
-				death_rows.push_back(DeathRow { hash, deleted: deleted.into_iter().collect() });
+				death_rows.push_back(DeathRow {
+					hash,
+					deleted: deleted.into_iter().collect(),
+					inserted: Vec::new(),
+				});
 			},
 		}
 	}
@@ -255,8 +316,8 @@ fn load_death_row_from_db<BlockHash: Hash, Key: Hash, D: MetaDb>(
 	let journal_key = to_journal_key(block);
 	match db.get_meta(&journal_key).map_err(Error::Db)? {
 		Some(record) => {
-			let JournalRecord { hash, deleted, .. } = Decode::decode(&mut record.as_slice())?;
-			Ok(Some(DeathRow { hash, deleted: deleted.into_iter().collect() }))
+			let JournalRecord { hash, inserted, deleted } = Decode::decode(&mut record.as_slice())?;
+			Ok(Some(DeathRow { hash, deleted: deleted.into_iter().collect(), inserted }))
 		},
 		None => Ok(None),
 	}
@@ -266,6 +327,9 @@ fn load_death_row_from_db<BlockHash: Hash, Key: Hash, D: MetaDb>(
 struct DeathRow<BlockHash: Hash, Key: Hash> {
 	hash: BlockHash,
 	deleted: HashSet<Key>,
+	/// Keys inserted by the block, only tracked by the database-backed queue when snapshots are
+	/// enabled.
+	inserted: Vec<Key>,
 }
 
 #[derive(Encode, Decode, Default)]
@@ -347,7 +411,21 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 			DeathRowQueue::new_db_backed(db, base, last, window_size)?
 		};
 
-		Ok(RefWindow { queue, base })
+		Ok(RefWindow { queue, base, snapshots: None })
+	}
+
+	/// Create a window that keeps the state of every `period`th block.
+	///
+	/// The backend database must support reference counting.
+	pub fn new_with_snapshots(
+		db: D,
+		window_size: u32,
+		period: NonZeroU32,
+	) -> Result<RefWindow<BlockHash, Key, D>, Error<D::Error>> {
+		let mut window = RefWindow::new(db, window_size, false)?;
+		window.snapshots =
+			Some(Snapshots { period: period.get() as u64, inserted: Default::default() });
+		Ok(window)
 	}
 
 	pub fn window_size(&self) -> u64 {
@@ -387,7 +465,14 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 		if let Some(pruned) = self.queue.pop_front(self.base)? {
 			trace!(target: LOG_TARGET, "Pruning {:?} ({} deleted)", pruned.hash, pruned.deleted.len());
 			let index = self.base;
-			commit.data.deleted.extend(pruned.deleted.into_iter());
+			match self.snapshots.as_mut() {
+				Some(snapshots) => commit.data.deleted.extend(snapshots.prune(
+					index,
+					pruned.deleted,
+					pruned.inserted,
+				)),
+				None => commit.data.deleted.extend(pruned.deleted.into_iter()),
+			}
 			commit.meta.inserted.push((to_meta_key(LAST_PRUNED, &()), index.encode()));
 			commit.meta.deleted.push(to_journal_key(self.base));
 			self.base += 1;
@@ -424,11 +509,12 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 			commit.data.inserted.len(),
 			commit.data.deleted.len(),
 		);
-		let inserted = if matches!(self.queue, DeathRowQueue::Mem { .. }) {
-			commit.data.inserted.iter().map(|(k, _)| k.clone()).collect()
-		} else {
-			Default::default()
-		};
+		let inserted =
+			if matches!(self.queue, DeathRowQueue::Mem { .. }) || self.snapshots.is_some() {
+				commit.data.inserted.iter().map(|(k, _)| k.clone()).collect()
+			} else {
+				Default::default()
+			};
 		let deleted = std::mem::take(&mut commit.data.deleted);
 		let journal_record = JournalRecord { hash: hash.clone(), inserted, deleted };
 		commit.meta.inserted.push((to_journal_key(number), journal_record.encode()));
@@ -447,6 +533,7 @@ mod tests {
 	};
 	use codec::Encode;
 	use sp_core::H256;
+	use std::num::NonZeroU32;
 
 	fn check_journal(pruning: &RefWindow<H256, H256, TestDb>, db: &TestDb) {
 		let count_insertions = matches!(pruning.queue, DeathRowQueue::Mem { .. });
@@ -648,6 +735,37 @@ mod tests {
 		assert!(db.data_eq(&make_db(&[1, 3])));
 	}
 
+	#[test]
+	fn snapshots_survive_pruning() {
+		let mut db = make_db(&[1, 2, 3]);
+		let mut pruning: RefWindow<H256, H256, TestDb> = RefWindow::new_with_snapshots(
+			db.clone(),
+			DEFAULT_MAX_BLOCK_CONSTRAINT,
+			NonZeroU32::new(2).unwrap(),
+		)
+		.unwrap();
+		for (number, (inserted, deleted)) in
+			[(&[4][..], &[][..]), (&[5], &[1]), (&[6], &[5]), (&[7], &[6, 4]), (&[], &[7])]
+				.into_iter()
+				.enumerate()
+		{
+			let mut commit = make_commit(inserted, deleted);
+			pruning.note_canonical(&H256::random(), number as u64, &mut commit).unwrap();
+			db.commit(&commit);
+		}
+		assert!(db.data_eq(&make_db(&[1, 2, 3, 4, 5, 6, 7])));
+
+		for _ in 0..5 {
+			let mut commit = CommitSet::default();
+			pruning.prune_one(&mut commit).unwrap();
+			db.commit(&commit);
+		}
+		// The states of the snapshots #0 and #2 are kept, the nodes inserted by #1 and #3 are
+		// removed.
+		assert!(db.data_eq(&make_db(&[1, 2, 3, 4, 6])));
+		assert_eq!(pruning.base, 5);
+	}
+
 	fn push_last_canonicalized<H: Hash>(block: u64, commit: &mut CommitSet<H>) {
 		commit
 			.meta
@@ -70,6 +70,7 @@ impl PoolStatus {
 /// 		- [InBlock](TransactionStatus::InBlock)
 /// 		- [Invalid](TransactionStatus::Invalid)
 /// 		- [Usurped](TransactionStatus::Usurped)
+/// 		- [Replaced](TransactionStatus::Replaced)
 /// 		- [Dropped](TransactionStatus::Dropped)
 /// 	4. Re-entering the pool:
 /// 		- [Retracted](TransactionStatus::Retracted)
@@ -113,6 +114,7 @@ impl PoolStatus {
 /// - [Finalized](TransactionStatus::Finalized)
 /// - [FinalityTimeout](TransactionStatus::FinalityTimeout)
 /// - [Usurped](TransactionStatus::Usurped)
+/// - [Replaced](TransactionStatus::Replaced)
 /// - [Invalid](TransactionStatus::Invalid)
 /// - [Dropped](TransactionStatus::Dropped)
 ///
@@ -150,6 +152,9 @@ pub enum TransactionStatus<Hash, BlockHash> {
 	/// Transaction has been replaced in the pool, by another transaction
 	/// that provides the same tags. (e.g. same (sender, nonce)).
 	Usurped(Hash),
+	/// Transaction has been replaced in the ready queue by the given transaction, which provides
+	/// the same tags with a sufficiently higher priority (e.g. a fee bump).
+	Replaced(Hash),
 	/// Transaction has been dropped from the pool because of the limit.
 	Dropped,
 	/// Transaction is no longer valid in the current state.
@@ -162,6 +167,7 @@ impl<Hash, BlockHash> TransactionStatus<Hash, BlockHash> {
 		// The state must be kept in sync with `crate::graph::Sender`.
 		match self {
 			Self::Usurped(_) |
+			Self::Replaced(_) |
 			Self::Finalized(_) |
 			Self::FinalityTimeout(_) |
 			Self::Invalid |
@@ -293,6 +299,23 @@ pub trait TransactionPool: Send + Sync {
 	/// Remove transactions identified by given hashes (and dependent transactions) from the pool.
 	fn remove_invalid(&self, hashes: &[TxHash<Self>]) -> Vec<Arc<Self::InPoolTransaction>>;
 
+	/// Remove all transactions from the pool.
+	///
+	/// Unlike [`Self::remove_invalid`], the removed transactions should not be banned, so that
+	/// they can be submitted again right away. Meant for development chains whose state gets
+	/// reset.
+	///
+	/// The default implementation removes the ready and future transactions with
+	/// [`Self::remove_invalid`], and thus may ban them.
+	fn clear(&self) -> Vec<Arc<Self::InPoolTransaction>> {
+		let hashes = self
+			.ready()
+			.map(|tx| tx.hash().clone())
+			.chain(self.futures().iter().map(|tx| tx.hash().clone()))
+			.collect::<Vec<_>>();
+		self.remove_invalid(&hashes)
+	}
+
 	// *** logging
 	/// Get futures transaction list.
 	fn futures(&self) -> Vec<Self::InPoolTransaction>;
@@ -19,17 +19,17 @@
 //! Utility for building substrate transaction pool trait object.
 
 use crate::{
-	common::api::FullChainApi,
+	common::api::{FullChainApi, TransactionSenderFn},
 	fork_aware_txpool::ForkAwareTxPool as ForkAwareFullPool,
 	graph::{base_pool::Transaction, ChainApi, ExtrinsicFor, ExtrinsicHash, IsValidator, Options},
-	single_state_txpool::BasicPool as SingleStateFullPool,
+	single_state_txpool::{BasicPool as SingleStateFullPool, RevalidationType},
 	TransactionPoolWrapper, LOG_TARGET,
 };
 use prometheus_endpoint::Registry as PrometheusRegistry;
 use sc_transaction_pool_api::{LocalTransactionPool, MaintainedTransactionPool};
 use sp_core::traits::SpawnEssentialNamed;
-use sp_runtime::traits::Block as BlockT;
-use std::{marker::PhantomData, sync::Arc, time::Duration};
+use sp_runtime::{traits::Block as BlockT, Percent};
+use std::{marker::PhantomData, path::PathBuf, sync::Arc, time::Duration};
 
 /// The type of transaction pool.
 #[derive(Debug, Clone)]
@@ -45,11 +45,16 @@ pub enum TransactionPoolType {
 pub struct TransactionPoolOptions {
 	txpool_type: TransactionPoolType,
 	options: Options,
+	journal_path: Option<PathBuf>,
 }
 
 impl Default for TransactionPoolOptions {
 	fn default() -> Self {
-		Self { txpool_type: TransactionPoolType::SingleState, options: Default::default() }
+		Self {
+			txpool_type: TransactionPoolType::SingleState,
+			options: Default::default(),
+			journal_path: None,
+		}
 	}
 }
 
@@ -81,7 +86,33 @@ impl TransactionPoolOptions {
 			Duration::from_secs(30 * 60)
 		};
 
-		TransactionPoolOptions { options, txpool_type }
+		TransactionPoolOptions { options, txpool_type, journal_path: None }
+	}
+
+	/// Sets the maximal number of future transactions of a single sender.
+	///
+	/// When the future queue is full, the transactions of the sender occupying the largest part of
+	/// it are evicted first. Senders are derived from the tags of the transactions, or identified
+	/// by the function passed to [`Builder::with_transaction_sender`].
+	pub fn with_future_sender_limit(mut self, future_sender_limit: Option<usize>) -> Self {
+		self.options.future_sender_limit = future_sender_limit;
+		self
+	}
+
+	/// Sets the minimal priority increase required to replace transactions providing the same
+	/// tags.
+	pub fn with_replacement_bump(mut self, replacement_bump: Percent) -> Self {
+		self.options.replacement_bump = replacement_bump;
+		self
+	}
+
+	/// Sets the path of the on-disk journal of the pool transactions.
+	///
+	/// The journal allows to restore the pending transactions after a restart. It is only supported
+	/// by the fork-aware transaction pool.
+	pub fn with_journal_path(mut self, journal_path: Option<PathBuf>) -> Self {
+		self.journal_path = journal_path;
+		self
 	}
 
 	/// Creates predefined options for benchmarking
@@ -98,8 +129,11 @@ impl TransactionPoolOptions {
 				},
 				reject_future_transactions: false,
 				ban_time: Duration::from_secs(30 * 60),
+				future_sender_limit: None,
+				replacement_bump: Percent::zero(),
 			},
 			txpool_type: TransactionPoolType::SingleState,
+			journal_path: None,
 		}
 	}
 }
@@ -175,6 +209,7 @@ pub struct Builder<'a, Block, Client> {
 	prometheus: Option<&'a PrometheusRegistry>,
 	client: Arc<Client>,
 	spawner: Box<dyn SpawnEssentialNamed>,
+	transaction_sender: Option<TransactionSenderFn>,
 	_phantom: PhantomData<(Client, Block)>,
 }
 
@@ -207,6 +242,7 @@ where
 			client,
 			is_validator,
 			prometheus: None,
+			transaction_sender: None,
 		}
 	}
 
@@ -222,24 +258,55 @@ where
 		self
 	}
 
+	/// Sets the function identifying the senders of transactions.
+	///
+	/// It overrides the senders derived from the tags of the transactions, which are used to
+	/// enforce the limit of future transactions of a single sender, see
+	/// [`TransactionPoolOptions::with_future_sender_limit`].
+	pub fn with_transaction_sender(
+		mut self,
+		transaction_sender: Option<TransactionSenderFn>,
+	) -> Self {
+		self.transaction_sender = transaction_sender;
+		self
+	}
+
 	/// Creates an instance of transaction pool.
 	pub fn build(self) -> TransactionPoolHandle<Block, Client> {
 		log::info!(target:LOG_TARGET, " creating {:?} txpool {:?}/{:?}.", self.options.txpool_type, self.options.options.ready, self.options.options.future);
+		let pool_api = Arc::new(
+			FullChainApi::new(self.client.clone(), self.prometheus, &self.spawner)
+				.with_transaction_sender(self.transaction_sender),
+		);
+		let chain_info = self.client.usage_info().chain;
 		TransactionPoolWrapper::<Block, Client>(match self.options.txpool_type {
-			TransactionPoolType::SingleState => Box::new(SingleStateFullPool::new_full(
-				self.options.options,
-				self.is_validator,
-				self.prometheus,
-				self.spawner,
-				self.client,
-			)),
-			TransactionPoolType::ForkAware => Box::new(ForkAwareFullPool::new_full(
-				self.options.options,
-				self.is_validator,
-				self.prometheus,
-				self.spawner,
-				self.client,
-			)),
+			TransactionPoolType::SingleState => {
+				if self.options.journal_path.is_some() {
+					log::warn!(target: LOG_TARGET, "The transaction pool journal is only supported by the fork-aware txpool, ignoring.");
+				}
+				Box::new(SingleStateFullPool::with_revalidation_type(
+					self.options.options,
+					self.is_validator,
+					pool_api,
+					self.prometheus,
+					RevalidationType::Full,
+					self.spawner,
+					chain_info.best_number,
+					chain_info.best_hash,
+					chain_info.finalized_hash,
+				))
+			},
+			TransactionPoolType::ForkAware =>
+				Box::new(ForkAwareFullPool::new_with_background_worker_and_journal(
+					self.options.options,
+					self.is_validator,
+					pool_api,
+					self.prometheus,
+					self.spawner,
+					chain_info.best_hash,
+					chain_info.finalized_hash,
+					self.options.journal_path,
+				)),
 		})
 	}
 }
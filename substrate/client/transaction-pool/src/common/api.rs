@@ -46,12 +46,19 @@ use super::{
 };
 use crate::graph;
 
+/// Function returning the identifier of the sender of the given SCALE-encoded extrinsic.
+///
+/// Overrides the sender derived from the tags of the extrinsic, see
+/// [`graph::ChainApi::transaction_sender`].
+pub type TransactionSenderFn = Arc<dyn Fn(&[u8]) -> Option<Vec<u8>> + Send + Sync>;
+
 /// The transaction pool logic for full client.
 pub struct FullChainApi<Client, Block> {
 	client: Arc<Client>,
 	_marker: PhantomData<Block>,
 	metrics: Option<Arc<ApiMetrics>>,
 	validation_pool: mpsc::Sender<Pin<Box<dyn Future<Output = ()> + Send>>>,
+	transaction_sender: Option<TransactionSenderFn>,
 }
 
 /// Spawn a validation task that will be used by the transaction pool to validate transactions.
@@ -101,7 +108,25 @@ impl<Client, Block> FullChainApi<Client, Block> {
 		spawn_validation_pool_task("transaction-pool-task-0", receiver.clone(), spawner);
 		spawn_validation_pool_task("transaction-pool-task-1", receiver, spawner);
 
-		FullChainApi { client, validation_pool: sender, _marker: Default::default(), metrics }
+		FullChainApi {
+			client,
+			validation_pool: sender,
+			_marker: Default::default(),
+			metrics,
+			transaction_sender: None,
+		}
+	}
+
+	/// Sets the function identifying the senders of extrinsics.
+	///
+	/// Without it the senders are derived from the tags of the extrinsics, which identifies the
+	/// senders of extrinsics ordered by nonces.
+	pub fn with_transaction_sender(
+		mut self,
+		transaction_sender: Option<TransactionSenderFn>,
+	) -> Self {
+		self.transaction_sender = transaction_sender;
+		self
 	}
 }
 
@@ -195,6 +220,12 @@ where
 		ex.using_encoded(|x| (<traits::HashingFor<Block> as traits::Hash>::hash(x), x.len()))
 	}
 
+	fn transaction_sender(&self, ex: &graph::RawExtrinsicFor<Self>) -> Option<Vec<u8>> {
+		self.transaction_sender
+			.as_ref()
+			.and_then(|sender| ex.using_encoded(|x| sender(x)))
+	}
+
 	fn block_header(
 		&self,
 		hash: <Self::Block as BlockT>::Hash,
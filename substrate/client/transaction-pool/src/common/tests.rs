@@ -196,6 +196,11 @@ impl ChainApi for TestApi {
 		(Hashing::hash(&encoded), len)
 	}
 
+	/// Returns the sender of the transfer.
+	fn transaction_sender(&self, uxt: &RawExtrinsicFor<Self>) -> Option<Vec<u8>> {
+		TransferData::try_from(uxt).ok().map(|transfer| transfer.from.encode())
+	}
+
 	fn block_body(&self, _id: <Self::Block as BlockT>::Hash) -> Self::BodyFuture {
 		futures::future::ready(Ok(None))
 	}
@@ -57,6 +57,10 @@ impl<Hash> DroppedTransaction<Hash> {
 		Self { reason: DroppedReason::Usurped(by), tx_hash }
 	}
 
+	fn new_replaced(tx_hash: Hash, by: Hash) -> Self {
+		Self { reason: DroppedReason::Replaced(by), tx_hash }
+	}
+
 	fn new_enforced_by_limts(tx_hash: Hash) -> Self {
 		Self { reason: DroppedReason::LimitsEnforced, tx_hash }
 	}
@@ -67,8 +71,13 @@ impl<Hash> DroppedTransaction<Hash> {
 pub enum DroppedReason<Hash> {
 	/// Transaction was replaced by other transaction (e.g. because of higher priority).
 	Usurped(Hash),
+	/// Transaction was replaced in the ready queue by other transaction providing the same tags
+	/// with a sufficiently higher priority.
+	Replaced(Hash),
 	/// Transaction was dropped because of internal pool limits being enforced.
 	LimitsEnforced,
+	/// Transaction was dropped because the whole pool was cleared.
+	Cleared,
 }
 
 /// Dropped-logic related event from the single view.
@@ -281,6 +290,8 @@ where
 			},
 			TransactionStatus::Usurped(by) =>
 				return Some(DroppedTransaction::new_usurped(tx_hash, by)),
+			TransactionStatus::Replaced(by) =>
+				return Some(DroppedTransaction::new_replaced(tx_hash, by)),
 			_ => {},
 		};
 		None
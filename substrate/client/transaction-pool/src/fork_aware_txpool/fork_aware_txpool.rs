@@ -19,7 +19,7 @@
 //! Substrate fork-aware transaction pool implementation.
 
 use super::{
-	dropped_watcher::{MultiViewDroppedWatcherController, StreamOfDropped},
+	dropped_watcher::{DroppedTransaction, MultiViewDroppedWatcherController, StreamOfDropped},
 	import_notification_sink::MultiViewImportNotificationSink,
 	metrics::MetricsLink as PrometheusMetrics,
 	multi_view_listener::MultiViewListener,
@@ -60,6 +60,7 @@ use sp_runtime::{
 };
 use std::{
 	collections::{HashMap, HashSet},
+	path::PathBuf,
 	pin::Pin,
 	sync::Arc,
 	time::Instant,
@@ -247,8 +248,8 @@ where
 	///
 	/// This asynchronous task continuously listens for dropped transaction notifications provided
 	/// within `dropped_stream` and ensures that these transactions are removed from the `mempool`
-	/// and `import_notification_sink` instances. For Usurped events, the transaction is also
-	/// removed from the view_store.
+	/// and `import_notification_sink` instances. For Usurped and Replaced events, the transaction
+	/// is also removed from the view_store.
 	async fn dropped_monitor_task(
 		mut dropped_stream: StreamOfDropped<ChainApi>,
 		mempool: Arc<TxMemPool<ChainApi, Block>>,
@@ -266,7 +267,7 @@ where
 			let dropped_tx_hash = dropped.tx_hash;
 			log::trace!(target: LOG_TARGET, "[{:?}] fatp::dropped notification {:?}, removing", dropped_tx_hash,dropped.reason);
 			match dropped.reason {
-				DroppedReason::Usurped(new_tx_hash) => {
+				DroppedReason::Usurped(new_tx_hash) | DroppedReason::Replaced(new_tx_hash) =>
 					if let Some(new_tx) = mempool.get_by_hash(new_tx_hash) {
 						view_store
 							.replace_transaction(
@@ -282,9 +283,8 @@ where
 							"error: dropped_monitor_task: no entry in mempool for new transaction {:?}",
 							new_tx_hash,
 						);
-					}
-				},
-				DroppedReason::LimitsEnforced => {},
+					},
+				DroppedReason::LimitsEnforced | DroppedReason::Cleared => {},
 			};
 
 			mempool.remove_dropped_transaction(&dropped_tx_hash).await;
@@ -305,6 +305,34 @@ where
 		spawner: impl SpawnEssentialNamed,
 		best_block_hash: Block::Hash,
 		finalized_hash: Block::Hash,
+	) -> Self {
+		Self::new_with_background_worker_and_journal(
+			options,
+			is_validator,
+			pool_api,
+			prometheus,
+			spawner,
+			best_block_hash,
+			finalized_hash,
+			None,
+		)
+	}
+
+	/// Creates new fork aware transaction pool with the background revalidation worker, like
+	/// [`Self::new_with_background_worker`].
+	///
+	/// If `journal_path` is given, the mempool transactions are journaled to the file at this path,
+	/// and the transactions already stored there are restored into the mempool. The journal is
+	/// rotated by an additional blocking task.
+	pub fn new_with_background_worker_and_journal(
+		options: Options,
+		is_validator: IsValidator,
+		pool_api: Arc<ChainApi>,
+		prometheus: Option<&PrometheusRegistry>,
+		spawner: impl SpawnEssentialNamed,
+		best_block_hash: Block::Hash,
+		finalized_hash: Block::Hash,
+		journal_path: Option<PathBuf>,
 	) -> Self {
 		let metrics = PrometheusMetrics::new(prometheus);
 		let listener = Arc::from(MultiViewListener::new());
@@ -314,13 +342,36 @@ where
 		let (import_notification_sink, import_notification_sink_task) =
 			MultiViewImportNotificationSink::new_with_worker();
 
-		let mempool = Arc::from(TxMemPool::new(
-			pool_api.clone(),
-			listener.clone(),
-			metrics.clone(),
-			TXMEMPOOL_TRANSACTION_LIMIT_MULTIPLIER * (options.ready.count + options.future.count),
-			options.ready.total_bytes + options.future.total_bytes,
-		));
+		let mempool_max_transactions_count =
+			TXMEMPOOL_TRANSACTION_LIMIT_MULTIPLIER * (options.ready.count + options.future.count);
+		let mempool_max_transactions_total_bytes =
+			options.ready.total_bytes + options.future.total_bytes;
+		let mempool = match journal_path {
+			Some(journal_path) => {
+				let (mempool, journal_task) = TxMemPool::new_with_journal(
+					pool_api.clone(),
+					listener.clone(),
+					metrics.clone(),
+					mempool_max_transactions_count,
+					mempool_max_transactions_total_bytes,
+					journal_path,
+				);
+				spawner.spawn_essential_blocking(
+					"txpool-journal",
+					Some("transaction-pool"),
+					journal_task.boxed(),
+				);
+				mempool
+			},
+			None => TxMemPool::new(
+				pool_api.clone(),
+				listener.clone(),
+				metrics.clone(),
+				mempool_max_transactions_count,
+				mempool_max_transactions_total_bytes,
+			),
+		};
+		let mempool = Arc::from(mempool);
 
 		let (dropped_stream_controller, dropped_stream) =
 			MultiViewDroppedWatcherController::<ChainApi>::new();
@@ -741,6 +792,21 @@ where
 		Default::default()
 	}
 
+	/// Removes all the transactions from the memory pool and from all the views.
+	///
+	/// The watchers of the removed transactions are notified that they were dropped.
+	fn clear(&self) -> Vec<Arc<Self::InPoolTransaction>> {
+		let hashes = self.mempool.clear();
+		let removed = self.view_store.clear();
+		log::debug!(target: LOG_TARGET, "fatp::clear mempool:{} views:{}", hashes.len(), removed.len());
+		hashes.into_iter().for_each(|tx_hash| {
+			self.view_store
+				.listener
+				.transaction_dropped(DroppedTransaction { tx_hash, reason: DroppedReason::Cleared })
+		});
+		removed
+	}
+
 	// todo [#5491]: api change?
 	// status(Hash) -> Option<PoolStatus>
 	/// Returns the pool status which includes information like the number of ready and future
@@ -1256,9 +1322,23 @@ where
 		}
 
 		self.ready_poll.lock().remove_cancelled();
+		self.rotate_mempool_journal();
 		log::trace!(target: LOG_TARGET, "handle_finalized after views_count:{:?}", self.active_views_count());
 	}
 
+	/// Rewrites the mempool journal (if enabled and due) with the current mempool content.
+	///
+	/// The priorities of the transactions are taken from the most recent view.
+	fn rotate_mempool_journal(&self) {
+		self.mempool.rotate_journal(false, || {
+			self.view_store
+				.ready()
+				.map(|tx| (tx.hash, tx.priority))
+				.chain(self.view_store.futures().into_iter().map(|tx| (tx.hash, tx.priority)))
+				.collect()
+		});
+	}
+
 	/// Computes a hash of the provided transaction
 	fn tx_hash(&self, xt: &TransactionFor<Self>) -> TxHash<Self> {
 		self.api.hash_and_length(xt).0
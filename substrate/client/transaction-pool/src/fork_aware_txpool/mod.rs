@@ -349,6 +349,7 @@ mod metrics;
 mod multi_view_listener;
 mod revalidation_worker;
 mod tx_mem_pool;
+mod tx_mem_pool_journal;
 mod view;
 mod view_store;
 
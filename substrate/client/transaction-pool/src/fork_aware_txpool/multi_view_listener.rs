@@ -221,6 +221,7 @@ where
 				Some(status)
 			},
 			TransactionStatus::Usurped(_) |
+			TransactionStatus::Replaced(_) |
 			TransactionStatus::Dropped |
 			TransactionStatus::Invalid => None,
 		}
@@ -354,7 +355,9 @@ where
 									log::trace!(target: LOG_TARGET, "[{:?}] mvl sending out: Broadcasted", ctx.tx_hash);
 									return Some((TransactionStatus::Broadcast(peers), ctx))
 								},
-								ControllerCommand::TransactionDropped(DroppedReason::LimitsEnforced) => {
+								ControllerCommand::TransactionDropped(
+									DroppedReason::LimitsEnforced | DroppedReason::Cleared,
+								) => {
 									log::trace!(target: LOG_TARGET, "[{:?}] mvl sending out: Dropped", ctx.tx_hash);
 									ctx.terminate = true;
 									return Some((TransactionStatus::Dropped, ctx))
@@ -364,6 +367,11 @@ where
 									ctx.terminate = true;
 									return Some((TransactionStatus::Usurped(by), ctx))
 								},
+								ControllerCommand::TransactionDropped(DroppedReason::Replaced(by)) => {
+									log::trace!(target: LOG_TARGET, "[{:?}] mvl sending out: Replaced({:?})", ctx.tx_hash, by);
+									ctx.terminate = true;
+									return Some((TransactionStatus::Replaced(by), ctx))
+								},
 							}
 						},
 					};
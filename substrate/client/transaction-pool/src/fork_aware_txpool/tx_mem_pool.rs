@@ -26,23 +26,32 @@
 //!   it), while on other forks tx can be valid. Depending on which view is chosen to be cloned,
 //!   such transaction could not be present in the newly created view.
 
-use super::{metrics::MetricsLink as PrometheusMetrics, multi_view_listener::MultiViewListener};
+use super::{
+	metrics::MetricsLink as PrometheusMetrics,
+	multi_view_listener::MultiViewListener,
+	tx_mem_pool_journal::{
+		journal_rotation_worker, JournalEntry, JournalRotation, TxMemPoolJournal,
+	},
+};
 use crate::{
 	common::log_xt::log_xt_trace,
 	graph,
 	graph::{base_pool::TimedTransactionSource, tracked_map::Size, ExtrinsicFor, ExtrinsicHash},
 	LOG_TARGET,
 };
-use futures::FutureExt;
+use codec::{Decode, Encode};
+use futures::{Future, FutureExt};
 use itertools::Itertools;
 use sc_transaction_pool_api::TransactionSource;
+use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedSender};
 use sp_blockchain::HashAndNumber;
 use sp_runtime::{
 	traits::Block as BlockT,
-	transaction_validity::{InvalidTransaction, TransactionValidityError},
+	transaction_validity::{InvalidTransaction, TransactionPriority, TransactionValidityError},
 };
 use std::{
 	collections::HashMap,
+	path::PathBuf,
 	sync::{atomic, atomic::AtomicU64, Arc},
 	time::Instant,
 };
@@ -184,6 +193,12 @@ where
 
 	/// Maximal size of encodings of all transactions in the memory pool.
 	max_transactions_total_bytes: usize,
+
+	/// Optional on-disk journal of the transactions, allowing to restore them after a restart.
+	journal: Option<Arc<TxMemPoolJournal>>,
+
+	/// Sends the rotations of the journal to the worker writing them.
+	journal_rotations: Option<TracingUnboundedSender<JournalRotation>>,
 }
 
 /// Helper structure to encapsulate a result of [`TxMemPool::try_insert`].
@@ -221,9 +236,41 @@ where
 			metrics,
 			max_transactions_count,
 			max_transactions_total_bytes,
+			journal: None,
+			journal_rotations: None,
 		}
 	}
 
+	/// Creates a new `TxMemPool` instance journaling its transactions to the file at
+	/// `journal_path`.
+	///
+	/// The transactions stored in the journal are restored into the memory pool as unwatched
+	/// transactions, and all newly inserted transactions are journaled. Returns the worker
+	/// rotating the journal, which is blocking and shall be spawned as a blocking task.
+	pub(super) fn new_with_journal(
+		api: Arc<ChainApi>,
+		listener: Arc<MultiViewListener<ChainApi>>,
+		metrics: PrometheusMetrics,
+		max_transactions_count: usize,
+		max_transactions_total_bytes: usize,
+		journal_path: PathBuf,
+	) -> (Self, impl Future<Output = ()>) {
+		let (journal, entries) = TxMemPoolJournal::open(journal_path);
+		let journal = Arc::new(journal);
+		let (journal_rotations, rotations) = tracing_unbounded("mpsc_txpool_journal_rotations", 16);
+
+		let mempool =
+			Self::new(api, listener, metrics, max_transactions_count, max_transactions_total_bytes);
+		// Restored transactions are already in the journal, so it is attached afterwards.
+		mempool.restore_from_journal(entries);
+		let mempool = Self {
+			journal: Some(journal.clone()),
+			journal_rotations: Some(journal_rotations),
+			..mempool
+		};
+		(mempool, journal_rotation_worker(journal, rotations))
+	}
+
 	/// Creates a new `TxMemPool` instance for testing purposes.
 	#[allow(dead_code)]
 	fn new_test(
@@ -238,6 +285,8 @@ where
 			metrics: Default::default(),
 			max_transactions_count,
 			max_transactions_total_bytes,
+			journal: None,
+			journal_rotations: None,
 		}
 	}
 
@@ -280,6 +329,13 @@ where
 		hash: ExtrinsicHash<ChainApi>,
 		tx: TxInMemPool<ChainApi, Block>,
 	) -> Result<InsertionInfo<ExtrinsicHash<ChainApi>>, ChainApi::Error> {
+		// The entry is encoded before locking the transactions, and only queued while they are
+		// locked. It is written by `flush_journal` once the lock is released.
+		let journal_entry = self.journal.as_ref().map(|_| JournalEntry {
+			source: tx.source.source,
+			priority: 0,
+			data: tx.tx.encode(),
+		});
 		let bytes = self.transactions.bytes();
 		let mut transactions = self.transactions.write();
 		let result = match (
@@ -288,6 +344,9 @@ where
 		) {
 			(true, false) => {
 				let source = tx.source();
+				if let (Some(journal), Some(entry)) = (&self.journal, journal_entry) {
+					journal.append(&entry);
+				}
 				transactions.insert(hash, Arc::from(tx));
 				Ok(InsertionInfo::new(hash, source))
 			},
@@ -316,9 +375,75 @@ where
 				self.try_insert(hash, TxInMemPool::new_unwatched(source, xt.clone(), length))
 			})
 			.collect::<Vec<_>>();
+		self.flush_journal();
 		result
 	}
 
+	/// Writes the journal entries queued by the insertions to the journal file.
+	fn flush_journal(&self) {
+		if let Some(journal) = &self.journal {
+			journal.flush();
+		}
+	}
+
+	/// Inserts the transactions read from the journal as unwatched transactions.
+	///
+	/// The entries are expected to be ordered by priority, so the most valuable transactions are
+	/// kept if the limits are hit. Transactions that cannot be decoded are skipped.
+	fn restore_from_journal(&self, entries: Vec<JournalEntry>) {
+		let mut restored = 0;
+		for entry in entries {
+			let xt = match Block::Extrinsic::decode(&mut &entry.data[..]) {
+				Ok(xt) => Arc::from(xt),
+				Err(error) => {
+					log::debug!(target: LOG_TARGET, "Skipping undecodable journaled transaction: {error}");
+					continue
+				},
+			};
+			if self.extend_unwatched(entry.source, &[xt]).into_iter().all(|r| r.is_ok()) {
+				restored += 1;
+			}
+		}
+		if restored > 0 {
+			log::info!(target: LOG_TARGET, "Restored {restored} transactions from the txpool journal");
+		}
+	}
+
+	/// Rewrites the journal with the transactions currently kept in the memory pool.
+	///
+	/// The snapshot of the memory pool is written to disk by the journal rotation worker. The
+	/// priorities of the transactions are provided by `priorities`, which is only called if the
+	/// journal is rotated. Does nothing if the journal is disabled, or if `force` is not set and
+	/// the journal was rotated recently.
+	pub(super) fn rotate_journal(
+		&self,
+		force: bool,
+		priorities: impl FnOnce() -> HashMap<ExtrinsicHash<ChainApi>, TransactionPriority>,
+	) {
+		let (Some(journal), Some(journal_rotations)) = (&self.journal, &self.journal_rotations)
+		else {
+			return
+		};
+		if !force && !journal.should_rotate() {
+			return
+		}
+
+		let priorities = priorities();
+		// The transactions inserted after this point are kept by the rotated journal.
+		let id = journal.start_rotation();
+		let entries = self
+			.transactions
+			.read()
+			.iter()
+			.map(|(hash, tx)| JournalEntry {
+				source: tx.source.source,
+				priority: priorities.get(hash).copied().unwrap_or_default(),
+				data: tx.tx.encode(),
+			})
+			.collect::<Vec<_>>();
+		let _ = journal_rotations.unbounded_send(JournalRotation { id, entries });
+	}
+
 	/// Adds a new watched transaction to the memory pool if it does not exceed the maximum allowed
 	/// transaction count.
 	pub(super) fn push_watched(
@@ -327,7 +452,9 @@ where
 		xt: ExtrinsicFor<ChainApi>,
 	) -> Result<InsertionInfo<ExtrinsicHash<ChainApi>>, ChainApi::Error> {
 		let (hash, length) = self.api.hash_and_length(&xt);
-		self.try_insert(hash, TxInMemPool::new_watched(source, xt.clone(), length))
+		let result = self.try_insert(hash, TxInMemPool::new_watched(source, xt.clone(), length));
+		self.flush_journal();
+		result
 	}
 
 	/// Removes transaction from the memory pool which are specified by the given list of hashes.
@@ -367,6 +494,15 @@ where
 		let _ = self.transactions.write().remove(&hash);
 	}
 
+	/// Removes all the transactions from the memory pool and returns their hashes.
+	///
+	/// The journal, if enabled, is rotated so that the removed transactions are not restored.
+	pub(super) fn clear(&self) -> Vec<ExtrinsicHash<ChainApi>> {
+		let hashes = self.transactions.write().clear().into_keys().collect();
+		self.rotate_journal(true, HashMap::new);
+		hashes
+	}
+
 	/// Revalidates a batch of transactions against the provided finalized block.
 	///
 	/// Returns a vector of invalid transaction hashes.
@@ -495,6 +631,74 @@ mod tx_mem_pool_tests {
 		));
 	}
 
+	#[test]
+	fn journaled_transactions_are_restored() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("journal");
+		let api = Arc::from(TestApi::default());
+		let new_mempool = |max| {
+			TxMemPool::new_with_journal(
+				api.clone(),
+				Arc::from(MultiViewListener::new()),
+				Default::default(),
+				max,
+				usize::MAX,
+				path.clone(),
+			)
+		};
+
+		let (mempool, _) = new_mempool(10);
+		let xts = (0..3).map(|x| Arc::from(uxt(x as _))).collect::<Vec<_>>();
+		assert!(mempool
+			.extend_unwatched(TransactionSource::External, &xts[..2])
+			.iter()
+			.all(Result::is_ok));
+		assert!(mempool.push_watched(TransactionSource::Local, xts[2].clone()).is_ok());
+		drop(mempool);
+
+		let (mempool, journal_worker) = new_mempool(10);
+		assert_eq!(mempool.unwatched_and_watched_count(), (3, 0));
+		let hash = api.hash_and_length(&xts[2]).0;
+		assert_eq!(mempool.get_by_hash(hash).unwrap().source().source, TransactionSource::Local);
+
+		// The rotated journal keeps the mempool content, restoring it obeys the limits.
+		mempool.rotate_journal(true, HashMap::new);
+		drop(mempool);
+		futures::executor::block_on(journal_worker);
+		assert_eq!(new_mempool(2).0.len(), 2);
+	}
+
+	#[test]
+	fn cleared_transactions_are_not_restored() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("journal");
+		let api = Arc::from(TestApi::default());
+		let new_mempool = || {
+			TxMemPool::new_with_journal(
+				api.clone(),
+				Arc::from(MultiViewListener::new()),
+				Default::default(),
+				10,
+				usize::MAX,
+				path.clone(),
+			)
+		};
+
+		let (mempool, journal_worker) = new_mempool();
+		let xts = (0..3).map(|x| Arc::from(uxt(x as _))).collect::<Vec<_>>();
+		assert!(mempool
+			.extend_unwatched(TransactionSource::External, &xts)
+			.iter()
+			.all(Result::is_ok));
+		assert_eq!(mempool.clear().len(), 3);
+		assert_eq!(mempool.len(), 0);
+		assert_eq!(mempool.bytes(), 0);
+		drop(mempool);
+		futures::executor::block_on(journal_worker);
+
+		assert_eq!(new_mempool().0.len(), 0);
+	}
+
 	#[test]
 	fn extend_unwatched_detects_already_imported() {
 		sp_tracing::try_init_simple();
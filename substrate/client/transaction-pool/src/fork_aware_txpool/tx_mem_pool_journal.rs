@@ -0,0 +1,381 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! On-disk journal of the transactions kept in the [`TxMemPool`].
+//!
+//! Every transaction inserted into the mempool is appended to the journal. The entries are queued
+//! while the mempool is locked, and written in batches once it is released. As the appended
+//! entries are never removed, the journal is periodically rotated: it is rewritten with the
+//! current content of the mempool, including the priorities of the transactions known to the most
+//! recent view.
+//!
+//! The rotated journal is written by a blocking worker. The entries appended while it is written
+//! are also appended to the rotated journal before it replaces the current one, so they are not
+//! lost.
+//!
+//! On startup the journal is read, and its transactions are submitted to the mempool (ordered by
+//! priority). They are then revalidated when the first view is created, and the invalid ones are
+//! removed from the mempool.
+//!
+//! [`TxMemPool`]: super::tx_mem_pool::TxMemPool
+
+use crate::LOG_TARGET;
+use codec::{Decode, Encode};
+use futures::StreamExt;
+use parking_lot::Mutex;
+use sc_transaction_pool_api::TransactionSource;
+use sc_utils::mpsc::TracingUnboundedReceiver;
+use sp_runtime::transaction_validity::TransactionPriority;
+use std::{
+	fs::{self, File, OpenOptions},
+	io::{self, BufWriter, Read, Write},
+	path::{Path, PathBuf},
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+/// The minimal interval between two rotations of the journal.
+pub(crate) const JOURNAL_ROTATION_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// A transaction stored in the journal.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub(crate) struct JournalEntry {
+	/// The source of the transaction.
+	pub(crate) source: TransactionSource,
+	/// The priority of the transaction, zero if it was not known when the entry was written.
+	pub(crate) priority: TransactionPriority,
+	/// The encoded transaction.
+	pub(crate) data: Vec<u8>,
+}
+
+/// A snapshot of the mempool, replacing the content of the journal once written.
+pub(crate) struct JournalRotation {
+	/// The identifier returned by [`TxMemPoolJournal::start_rotation`].
+	pub(crate) id: u64,
+	/// The entries of the transactions in the mempool when the rotation was started.
+	pub(crate) entries: Vec<JournalEntry>,
+}
+
+/// A rotation whose snapshot is not written yet.
+struct PendingRotation {
+	/// The identifier of the rotation.
+	id: u64,
+	/// The encoded entries appended to the journal since the rotation was started.
+	appended: Vec<u8>,
+}
+
+/// The state of the journal file, shared by appends and rotations.
+#[derive(Default)]
+struct JournalWriter {
+	/// The journal file, opened for appending.
+	///
+	/// `None` if the journal file could not be opened.
+	file: Option<BufWriter<File>>,
+	/// The most recently started rotation, until it completes.
+	rotation: Option<PendingRotation>,
+	/// The identifier of the next rotation.
+	next_rotation_id: u64,
+}
+
+/// On-disk journal of the mempool transactions.
+pub(crate) struct TxMemPoolJournal {
+	/// The path of the journal file.
+	path: PathBuf,
+	/// The journal file and the pending rotation.
+	writer: Mutex<JournalWriter>,
+	/// The encoded entries waiting to be written to the journal file.
+	pending: Mutex<Vec<u8>>,
+	/// When the journal was rotated for the last time.
+	last_rotation: Mutex<Instant>,
+}
+
+impl TxMemPoolJournal {
+	/// Opens the journal at the given path, creating it if it does not exist.
+	///
+	/// Returns the journal and the entries it contains, ordered by priority (highest first).
+	pub(crate) fn open(path: PathBuf) -> (Self, Vec<JournalEntry>) {
+		let mut entries = match read_entries(&path) {
+			Ok(entries) => entries,
+			Err(error) if error.kind() == io::ErrorKind::NotFound => Vec::new(),
+			Err(error) => {
+				log::warn!(target: LOG_TARGET, "Failed to read the txpool journal {path:?}: {error}");
+				Vec::new()
+			},
+		};
+		entries.sort_by(|a, b| b.priority.cmp(&a.priority));
+		log::info!(target: LOG_TARGET, "Loaded {} transactions from the txpool journal", entries.len());
+
+		let file = open_writer(&path)
+			.inspect_err(
+				|error| log::warn!(target: LOG_TARGET, "Failed to open the txpool journal {path:?}: {error}"),
+			)
+			.ok();
+
+		let journal = Self {
+			path,
+			writer: Mutex::new(JournalWriter { file, ..Default::default() }),
+			pending: Default::default(),
+			last_rotation: Mutex::new(Instant::now()),
+		};
+		(journal, entries)
+	}
+
+	/// Queues the given entry, it is appended to the journal file by the next [`Self::flush`].
+	pub(crate) fn append(&self, entry: &JournalEntry) {
+		entry.encode_to(&mut *self.pending.lock());
+	}
+
+	/// Appends the queued entries to the journal file.
+	pub(crate) fn flush(&self) {
+		let pending = std::mem::take(&mut *self.pending.lock());
+		if pending.is_empty() {
+			return
+		}
+
+		let mut writer = self.writer.lock();
+		if let Some(rotation) = &mut writer.rotation {
+			rotation.appended.extend_from_slice(&pending);
+		}
+		let Some(file) = writer.file.as_mut() else { return };
+		if let Err(error) = file.write_all(&pending).and_then(|_| file.flush()) {
+			log::warn!(target: LOG_TARGET, "Failed to append to the txpool journal: {error}");
+		}
+	}
+
+	/// Returns true if the journal was not rotated for [`JOURNAL_ROTATION_INTERVAL`].
+	pub(crate) fn should_rotate(&self) -> bool {
+		self.last_rotation.lock().elapsed() >= JOURNAL_ROTATION_INTERVAL
+	}
+
+	/// Starts a rotation of the journal, and returns its identifier.
+	///
+	/// The snapshot of the mempool must be taken after the rotation is started, and passed to
+	/// [`Self::complete_rotation`]. Starting a rotation supersedes the pending one, if any.
+	pub(crate) fn start_rotation(&self) -> u64 {
+		let mut writer = self.writer.lock();
+		*self.last_rotation.lock() = Instant::now();
+		let id = writer.next_rotation_id;
+		writer.next_rotation_id += 1;
+		writer.rotation = Some(PendingRotation { id, appended: Vec::new() });
+		id
+	}
+
+	/// Replaces the content of the journal with the snapshot of the given rotation, followed by
+	/// the entries appended since the rotation was started.
+	///
+	/// The snapshot is written to a temporary file first, without blocking appends. The file then
+	/// replaces the journal. Does nothing if the rotation was superseded.
+	pub(crate) fn complete_rotation(&self, rotation: JournalRotation) {
+		let JournalRotation { id, entries } = rotation;
+		let is_pending =
+			|writer: &JournalWriter| writer.rotation.as_ref().is_some_and(|r| r.id == id);
+		if !is_pending(&*self.writer.lock()) {
+			return
+		}
+
+		let tmp_path = self.path.with_extension("tmp");
+		let result = (|| {
+			let mut tmp = BufWriter::new(File::create(&tmp_path)?);
+			for entry in &entries {
+				tmp.write_all(&entry.encode())?;
+			}
+
+			let mut writer = self.writer.lock();
+			if !is_pending(&*writer) {
+				return Ok(None)
+			}
+			let appended = writer.rotation.take().map(|r| r.appended).unwrap_or_default();
+			tmp.write_all(&appended)?;
+			tmp.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+			fs::rename(&tmp_path, &self.path)?;
+			// The previous file refers to the replaced journal.
+			writer.file = open_writer(&self.path).ok();
+			Ok::<_, io::Error>(Some(entries.len()))
+		})();
+
+		match result {
+			Ok(Some(count)) => log::debug!(
+				target: LOG_TARGET,
+				"Rotated the txpool journal with {count} transactions"
+			),
+			Ok(None) => {
+				let _ = fs::remove_file(&tmp_path);
+			},
+			Err(error) => {
+				log::warn!(target: LOG_TARGET, "Failed to rotate the txpool journal: {error}");
+				// The current journal is kept, there is no need to record the appended entries.
+				let mut writer = self.writer.lock();
+				if is_pending(&*writer) {
+					writer.rotation = None;
+				}
+			},
+		}
+	}
+}
+
+/// Completes the rotations of the journal sent by the mempool, until the sender is dropped.
+///
+/// Writing the rotated journal is blocking, so this is expected to be spawned as a blocking task.
+pub(crate) async fn journal_rotation_worker(
+	journal: Arc<TxMemPoolJournal>,
+	mut rotations: TracingUnboundedReceiver<JournalRotation>,
+) {
+	while let Some(mut rotation) = rotations.next().await {
+		// Only the most recent rotation is relevant.
+		while let Ok(Some(newer)) = rotations.try_next() {
+			rotation = newer;
+		}
+		journal.complete_rotation(rotation);
+	}
+}
+
+impl Drop for TxMemPoolJournal {
+	fn drop(&mut self) {
+		self.flush();
+	}
+}
+
+/// Opens the journal file for appending, creating the parent directories if needed.
+fn open_writer(path: &Path) -> io::Result<BufWriter<File>> {
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent)?;
+	}
+	Ok(BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?))
+}
+
+/// Reads all the entries of the journal file.
+///
+/// A truncated last entry, for example due to a crash while appending, is ignored.
+fn read_entries(path: &Path) -> io::Result<Vec<JournalEntry>> {
+	let mut data = Vec::new();
+	File::open(path)?.read_to_end(&mut data)?;
+
+	let mut input = &data[..];
+	let mut entries = Vec::new();
+	while !input.is_empty() {
+		match JournalEntry::decode(&mut input) {
+			Ok(entry) => entries.push(entry),
+			Err(error) => {
+				log::warn!(target: LOG_TARGET, "Ignoring corrupted txpool journal entries: {error}");
+				break
+			},
+		}
+	}
+	Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn entry(priority: TransactionPriority, data: &[u8]) -> JournalEntry {
+		JournalEntry { source: TransactionSource::External, priority, data: data.to_vec() }
+	}
+
+	#[test]
+	fn journal_entries_survive_reopening() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("txpool").join("journal");
+
+		let (journal, entries) = TxMemPoolJournal::open(path.clone());
+		assert!(entries.is_empty());
+		journal.append(&entry(1, b"low"));
+		journal.append(&entry(10, b"high"));
+		drop(journal);
+
+		let (_, entries) = TxMemPoolJournal::open(path);
+		assert_eq!(entries, vec![entry(10, b"high"), entry(1, b"low")]);
+	}
+
+	#[test]
+	fn journal_entries_are_written_on_flush() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("journal");
+
+		let (journal, _) = TxMemPoolJournal::open(path.clone());
+		journal.append(&entry(1, b"first"));
+		journal.append(&entry(2, b"second"));
+		assert!(read_entries(&path).unwrap().is_empty());
+
+		journal.flush();
+		assert_eq!(read_entries(&path).unwrap(), vec![entry(1, b"first"), entry(2, b"second")]);
+	}
+
+	#[test]
+	fn journal_rotation_replaces_entries() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("journal");
+
+		let (journal, _) = TxMemPoolJournal::open(path.clone());
+		journal.append(&entry(0, b"removed"));
+		journal.append(&entry(0, b"kept"));
+		journal.flush();
+		let id = journal.start_rotation();
+		journal.complete_rotation(JournalRotation { id, entries: vec![entry(5, b"kept")] });
+		journal.append(&entry(0, b"new"));
+		drop(journal);
+
+		let (_, entries) = TxMemPoolJournal::open(path);
+		assert_eq!(entries, vec![entry(5, b"kept"), entry(0, b"new")]);
+	}
+
+	#[test]
+	fn journal_rotation_keeps_entries_appended_while_in_progress() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("journal");
+
+		let (journal, _) = TxMemPoolJournal::open(path.clone());
+		journal.append(&entry(0, b"removed"));
+		journal.flush();
+		let id = journal.start_rotation();
+		journal.append(&entry(1, b"appended"));
+		journal.flush();
+		journal.complete_rotation(JournalRotation { id, entries: vec![entry(5, b"kept")] });
+		assert_eq!(read_entries(&path).unwrap(), vec![entry(5, b"kept"), entry(1, b"appended")]);
+
+		// A superseded rotation is ignored.
+		let superseded = journal.start_rotation();
+		let id = journal.start_rotation();
+		journal.complete_rotation(JournalRotation { id: superseded, entries: vec![] });
+		assert_eq!(read_entries(&path).unwrap().len(), 2);
+		journal.complete_rotation(JournalRotation { id, entries: vec![entry(5, b"kept")] });
+		assert_eq!(read_entries(&path).unwrap(), vec![entry(5, b"kept")]);
+	}
+
+	#[test]
+	fn journal_ignores_truncated_entry() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("journal");
+
+		let (journal, _) = TxMemPoolJournal::open(path.clone());
+		journal.append(&entry(0, b"complete"));
+		drop(journal);
+
+		let mut truncated = entry(0, b"truncated").encode();
+		truncated.pop();
+		OpenOptions::new()
+			.append(true)
+			.open(&path)
+			.unwrap()
+			.write_all(&truncated)
+			.unwrap();
+
+		let (_, entries) = TxMemPoolJournal::open(path);
+		assert_eq!(entries, vec![entry(0, b"complete")]);
+	}
+}
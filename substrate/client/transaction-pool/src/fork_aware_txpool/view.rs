@@ -210,6 +210,7 @@ where
 			.block_id_to_number(&BlockId::hash(self.at.hash))?
 			.ok_or_else(|| TxPoolError::InvalidBlockId(format!("{:?}", self.at.hash)))?;
 
+		let sender = self.pool.validated_pool().api().transaction_sender(&xt);
 		let validated = ValidatedTransaction::valid_at(
 			block_number.saturated_into::<u64>(),
 			hash,
@@ -217,6 +218,7 @@ where
 			Arc::from(xt),
 			length,
 			validity,
+			sender,
 		);
 
 		self.pool.validated_pool().submit(vec![validated]).remove(0)
@@ -327,6 +329,7 @@ where
 							tx.data.clone(),
 							api.hash_and_length(&tx.data).1,
 							validity,
+							tx.sender.clone(),
 						),
 					);
 				},
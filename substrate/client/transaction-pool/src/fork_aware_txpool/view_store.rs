@@ -240,6 +240,21 @@ where
 		self.active_views.read().is_empty() && self.inactive_views.read().is_empty()
 	}
 
+	/// Removes all the transactions from all the views.
+	///
+	/// Returns the removed transactions, each of them only once.
+	pub(super) fn clear(&self) -> Vec<TransactionFor<ChainApi>> {
+		self.pending_txs_replacements.write().clear();
+		let active_views = self.active_views.read();
+		let inactive_views = self.inactive_views.read();
+		active_views
+			.values()
+			.chain(inactive_views.values())
+			.flat_map(|view| view.pool.validated_pool().clear())
+			.unique_by(|tx| tx.hash)
+			.collect()
+	}
+
 	/// Finds the best existing active view to clone from along the path.
 	///
 	/// ```text
@@ -33,6 +33,7 @@ use sp_runtime::{
 		TransactionLongevity as Longevity, TransactionPriority as Priority, TransactionSource,
 		TransactionTag as Tag,
 	},
+	Percent,
 };
 
 use super::{
@@ -142,6 +143,34 @@ pub struct Transaction<Hash, Extrinsic> {
 	pub propagate: bool,
 	/// Timed source of that transaction.
 	pub source: TimedTransactionSource,
+	/// Identifier of the transaction sender, if known.
+	///
+	/// Derived from the tags of the transaction by [`sender_from_tags`], unless it is reported by
+	/// the [`ChainApi`](crate::graph::ChainApi). Used to enforce the limit of future transactions
+	/// of a single sender.
+	pub sender: Option<Vec<u8>>,
+}
+
+/// Derives the identifier of the sender of a transaction from the tags it provides and requires.
+///
+/// Transactions ordered by nonces, like the ones checked by FRAME's `CheckNonce`, provide the
+/// encoded `(sender, nonce)` tag and require the `(sender, nonce - 1)` one. As nonces are encoded
+/// in little endian, the two tags first differ at the first byte of the nonce, so their longest
+/// common prefix is the encoded sender.
+///
+/// Returns `None` if no provided tag shares a prefix with a required tag of the same length, for
+/// example if the transaction requires no tags.
+pub fn sender_from_tags(provides: &[Tag], requires: &[Tag]) -> Option<Vec<u8>> {
+	requires
+		.iter()
+		.flat_map(|required| provides.iter().map(move |provided| (required, provided)))
+		.filter(|(required, provided)| required.len() == provided.len())
+		.filter_map(|(required, provided)| {
+			let len = required.iter().zip(provided.iter()).take_while(|(a, b)| a == b).count();
+			(len > 0 && len < provided.len()).then(|| &provided[..len])
+		})
+		.max_by_key(|prefix| prefix.len())
+		.map(|prefix| prefix.to_vec())
 }
 
 impl<Hash, Extrinsic> AsRef<Extrinsic> for Transaction<Hash, Extrinsic> {
@@ -200,6 +229,7 @@ impl<Hash: Clone, Extrinsic: Clone> Transaction<Hash, Extrinsic> {
 			requires: self.requires.clone(),
 			provides: self.provides.clone(),
 			propagate: self.propagate,
+			sender: self.sender.clone(),
 		}
 	}
 }
@@ -248,6 +278,8 @@ const RECENTLY_PRUNED_TAGS: usize = 2;
 #[derive(Clone, Debug)]
 pub struct BasePool<Hash: hash::Hash + Eq, Ex> {
 	reject_future_transactions: bool,
+	/// Maximal number of future transactions of a single sender (see [`Transaction::sender`]).
+	future_sender_limit: Option<usize>,
 	future: FutureTransactions<Hash, Ex>,
 	ready: ReadyTransactions<Hash, Ex>,
 	/// Store recently pruned tags (for last two invocations).
@@ -260,17 +292,23 @@ pub struct BasePool<Hash: hash::Hash + Eq, Ex> {
 
 impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> Default for BasePool<Hash, Ex> {
 	fn default() -> Self {
-		Self::new(false)
+		Self::new(false, None, Percent::zero())
 	}
 }
 
 impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash, Ex> {
-	/// Create new pool given reject_future_transactions flag.
-	pub fn new(reject_future_transactions: bool) -> Self {
+	/// Create new pool given reject_future_transactions flag, the limit of future transactions
+	/// of a single sender and the minimal priority bump required for replacements.
+	pub fn new(
+		reject_future_transactions: bool,
+		future_sender_limit: Option<usize>,
+		replacement_bump: Percent,
+	) -> Self {
 		Self {
 			reject_future_transactions,
+			future_sender_limit,
 			future: Default::default(),
-			ready: Default::default(),
+			ready: ReadyTransactions::new(replacement_bump),
 			recently_pruned: Default::default(),
 			recently_pruned_index: 0,
 		}
@@ -330,6 +368,20 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 				return Err(error::Error::RejectedFutureTransaction)
 			}
 
+			if let (Some(limit), Some(sender)) =
+				(self.future_sender_limit, tx.transaction.sender.as_deref())
+			{
+				if self.future.sender_count(sender) >= limit {
+					trace!(
+						target: LOG_TARGET,
+						"[{:?}] Sender {} exceeds the future limit ({limit})",
+						tx.transaction.hash,
+						HexDisplay::from(&sender),
+					);
+					return Err(error::Error::ImmediatelyDropped)
+				}
+			}
+
 			let hash = tx.transaction.hash.clone();
 			self.future.import(tx);
 			return Ok(Imported::Future { hash })
@@ -444,6 +496,9 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 	/// them. Technically the worst transaction should be evaluated by computing the entire pending
 	/// set. We use a simplified approach to remove transactions with the lowest priority first or
 	/// those that occupy the pool for the longest time in case priority is the same.
+	///
+	/// If the future sender limit is set, future transactions of the sender occupying the largest
+	/// part of the future queue are removed first, starting from the most recent one.
 	pub fn enforce_limits(
 		&mut self,
 		ready: &Limit,
@@ -483,6 +538,13 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		}
 
 		while future.is_exceeded(self.future.len(), self.future.bytes()) {
+			if self.future_sender_limit.is_some() {
+				if let Some(worst) = self.future.worst_offender() {
+					removed.append(&mut self.remove_subtree(&[worst]));
+					continue
+				}
+			}
+
 			// find the worst transaction
 			let worst = self.future.fold(|worst, current| match worst {
 				None => Some(current.clone()),
@@ -535,6 +597,15 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		self.future.clear()
 	}
 
+	/// Removes and returns all transactions from the pool, and forgets the recently pruned tags.
+	pub fn clear(&mut self) -> Vec<Arc<Transaction<Hash, Ex>>> {
+		let ready = self.ready.get().map(|tx| tx.hash.clone()).collect::<Vec<_>>();
+		let mut removed = self.ready.remove_subtree(&ready);
+		removed.extend(self.future.clear());
+		self.clear_recently_pruned();
+		removed
+	}
+
 	/// Prunes transactions that provide given list of tags.
 	///
 	/// This will cause all transactions (both ready and future) that provide these tags to be
@@ -631,6 +702,7 @@ mod tests {
 			provides: vec![],
 			propagate: true,
 			source: TimedTransactionSource::new_external(false),
+			sender: None,
 		}
 	}
 
@@ -1184,6 +1256,69 @@ source: TimedTransactionSource { source: TransactionSource::External, timestamp:
 		);
 	}
 
+	fn sender_tx(sender: u8, nonce: u8, hash: Hash) -> Transaction<Hash, Vec<u8>> {
+		Transaction {
+			data: vec![sender, nonce],
+			hash,
+			requires: vec![vec![sender, nonce - 1]],
+			provides: vec![vec![sender, nonce]],
+			sender: Some(vec![sender]),
+			..default_tx().clone()
+		}
+	}
+
+	#[test]
+	fn should_limit_future_transactions_per_sender() {
+		// given
+		let mut pool = BasePool::new(false, Some(2), Percent::zero());
+
+		// when
+		pool.import(sender_tx(1, 2, 1)).unwrap();
+		pool.import(sender_tx(1, 4, 2)).unwrap();
+		let err = pool.import(sender_tx(1, 6, 3)).unwrap_err();
+		pool.import(sender_tx(2, 2, 4)).unwrap();
+
+		// then
+		assert!(matches!(err, error::Error::ImmediatelyDropped));
+		assert_eq!(pool.future.len(), 3);
+	}
+
+	#[test]
+	fn should_release_sender_limit_when_future_transactions_are_promoted() {
+		// given
+		let mut pool = BasePool::new(false, Some(1), Percent::zero());
+		pool.import(sender_tx(1, 2, 1)).unwrap();
+
+		// when
+		pool.import(Transaction { requires: vec![], ..sender_tx(1, 1, 2) }).unwrap();
+
+		// then
+		assert_eq!(pool.future.len(), 0);
+		assert_eq!(pool.future.sender_count(&[1]), 0);
+		pool.import(sender_tx(1, 4, 3)).unwrap();
+		assert_eq!(pool.future.sender_count(&[1]), 1);
+	}
+
+	#[test]
+	fn should_evict_future_transactions_of_worst_offender_first() {
+		// given
+		let mut pool = BasePool::new(false, Some(10), Percent::zero());
+		pool.import(sender_tx(2, 2, 1)).unwrap();
+		pool.import(sender_tx(1, 2, 2)).unwrap();
+		pool.import(sender_tx(1, 4, 3)).unwrap();
+		pool.import(sender_tx(1, 6, 4)).unwrap();
+		pool.import(sender_tx(3, 2, 5)).unwrap();
+		let limit = Limit { count: 3, total_bytes: 1000 };
+
+		// when
+		let removed = pool.enforce_limits(&limit, &limit);
+
+		// then
+		let removed = removed.into_iter().map(|tx| tx.hash).collect::<Vec<_>>();
+		assert_eq!(removed, vec![4, 3]);
+		assert_eq!(pool.future.len(), 3);
+	}
+
 	#[test]
 	fn should_reject_future_transactions() {
 		// given
@@ -1254,4 +1389,22 @@ source: TimedTransactionSource { source: TransactionSource::External, timestamp:
 		assert_eq!(pool.reject_future_transactions, true);
 		assert_eq!(pool.future.len(), 1);
 	}
+
+	#[test]
+	fn should_derive_sender_from_tags() {
+		use codec::Encode;
+		let tag = |sender: u64, nonce: u32| (sender, nonce).encode();
+
+		// The nonces differ in their first byte, even if the later bytes are equal.
+		assert_eq!(sender_from_tags(&[tag(7, 256)], &[tag(7, 255)]), Some(7u64.encode()),);
+		assert_eq!(
+			sender_from_tags(&[vec![1, 2, 3], tag(7, 1)], &[tag(7, 0)]),
+			Some(7u64.encode()),
+		);
+
+		// Transactions without requirements have no known sender.
+		assert_eq!(sender_from_tags(&[tag(7, 0)], &[]), None);
+		// Tags without a common prefix don't identify a sender.
+		assert_eq!(sender_from_tags(&[vec![1, 2]], &[vec![2, 2]]), None);
+	}
 }
@@ -17,11 +17,12 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use std::{
-	collections::{HashMap, HashSet},
+	collections::{BTreeSet, HashMap, HashSet},
 	fmt, hash,
 	sync::Arc,
 };
 
+use indexmap::IndexSet;
 use sp_core::hexdisplay::HexDisplay;
 use sp_runtime::transaction_validity::TransactionTag as Tag;
 use std::time::Instant;
@@ -114,11 +115,20 @@ pub struct FutureTransactions<Hash: hash::Hash + Eq, Ex> {
 	wanted_tags: HashMap<Tag, HashSet<Hash>>,
 	/// Transactions waiting for a particular other transaction
 	waiting: HashMap<Hash, WaitingTransaction<Hash, Ex>>,
+	/// Hashes of the waiting transactions of every known sender, in the order of import.
+	senders: HashMap<Vec<u8>, IndexSet<Hash>>,
+	/// Known senders ordered by the number of their waiting transactions.
+	senders_by_count: BTreeSet<(usize, Vec<u8>)>,
 }
 
 impl<Hash: hash::Hash + Eq, Ex> Default for FutureTransactions<Hash, Ex> {
 	fn default() -> Self {
-		Self { wanted_tags: Default::default(), waiting: Default::default() }
+		Self {
+			wanted_tags: Default::default(),
+			waiting: Default::default(),
+			senders: Default::default(),
+			senders_by_count: Default::default(),
+		}
 	}
 }
 
@@ -151,10 +161,30 @@ impl<Hash: hash::Hash + Eq + Clone + std::fmt::Debug, Ex: std::fmt::Debug>
 			entry.insert(tx.transaction.hash.clone());
 		}
 
+		if let Some(sender) = &tx.transaction.sender {
+			let hashes = self.senders.entry(sender.clone()).or_default();
+			self.senders_by_count.remove(&(hashes.len(), sender.clone()));
+			hashes.insert(tx.transaction.hash.clone());
+			self.senders_by_count.insert((hashes.len(), sender.clone()));
+		}
+
 		// Add the transaction to a by-hash waiting map
 		self.waiting.insert(tx.transaction.hash.clone(), tx);
 	}
 
+	/// Removes the transaction from the per-sender index.
+	fn unindex_sender(&mut self, transaction: &Transaction<Hash, Ex>) {
+		let Some(sender) = &transaction.sender else { return };
+		let Some(hashes) = self.senders.get_mut(sender) else { return };
+		self.senders_by_count.remove(&(hashes.len(), sender.clone()));
+		hashes.shift_remove(&transaction.hash);
+		if hashes.is_empty() {
+			self.senders.remove(sender);
+		} else {
+			self.senders_by_count.insert((hashes.len(), sender.clone()));
+		}
+	}
+
 	/// Returns true if given hash is part of the queue.
 	pub fn contains(&self, hash: &Hash) -> bool {
 		self.waiting.contains_key(hash)
@@ -209,6 +239,7 @@ impl<Hash: hash::Hash + Eq + Clone + std::fmt::Debug, Ex: std::fmt::Debug>
 
 					if is_ready {
 						let tx = self.waiting.remove(&hash).expect(WAITING_PROOF);
+						self.unindex_sender(&tx.transaction);
 						became_ready.push(tx);
 					}
 				}
@@ -225,6 +256,7 @@ impl<Hash: hash::Hash + Eq + Clone + std::fmt::Debug, Ex: std::fmt::Debug>
 		let mut removed = vec![];
 		for hash in hashes {
 			if let Some(waiting_tx) = self.waiting.remove(hash) {
+				self.unindex_sender(&waiting_tx.transaction);
 				// remove from wanted_tags as well
 				for tag in waiting_tx.missing_tags {
 					let remove = if let Some(wanted) = self.wanted_tags.get_mut(&tag) {
@@ -261,9 +293,30 @@ impl<Hash: hash::Hash + Eq + Clone + std::fmt::Debug, Ex: std::fmt::Debug>
 	/// Removes and returns all future transactions.
 	pub fn clear(&mut self) -> Vec<Arc<Transaction<Hash, Ex>>> {
 		self.wanted_tags.clear();
+		self.senders.clear();
+		self.senders_by_count.clear();
 		self.waiting.drain().map(|(_, tx)| tx.transaction).collect()
 	}
 
+	/// Returns the number of transactions of the given sender in the Future queue.
+	///
+	/// See [`Transaction::sender`].
+	pub fn sender_count(&self, sender: &[u8]) -> usize {
+		self.senders.get(sender).map_or(0, |hashes| hashes.len())
+	}
+
+	/// Returns the hash of the most recently imported transaction of the sender having the most
+	/// transactions in the Future queue.
+	///
+	/// `None` is returned if no sender has more than one transaction in the queue.
+	pub fn worst_offender(&self) -> Option<Hash> {
+		let (count, sender) = self.senders_by_count.last()?;
+		if *count < 2 {
+			return None
+		}
+		self.senders.get(sender)?.last().cloned()
+	}
+
 	/// Returns number of transactions in the Future queue.
 	pub fn len(&self) -> usize {
 		self.waiting.len()
@@ -137,14 +137,14 @@ impl<H: hash::Hash + traits::Member + Serialize + Clone, C: ChainApi> Listener<H
 		}
 	}
 
-	/// Transaction was replaced with other extrinsic.
-	pub fn usurped(&mut self, tx: &H, by: &H) {
+	/// Transaction was replaced in the ready queue with other extrinsic.
+	pub fn replaced(&mut self, tx: &H, by: &H) {
 		trace!(target: LOG_TARGET, "[{:?}] Dropped (replaced with {:?})", tx, by);
-		self.fire(tx, |watcher| watcher.usurped(by.clone()));
+		self.fire(tx, |watcher| watcher.replaced(by.clone()));
 
 		if let Some(ref sink) = self.dropped_by_limits_sink {
 			if let Err(e) =
-				sink.unbounded_send((tx.clone(), TransactionStatus::Usurped(by.clone())))
+				sink.unbounded_send((tx.clone(), TransactionStatus::Replaced(by.clone())))
 			{
 				trace!(target: LOG_TARGET, "[{:?}] dropped_sink: send message failed: {:?}", tx, e);
 			}
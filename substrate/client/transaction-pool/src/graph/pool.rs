@@ -27,6 +27,7 @@ use sp_runtime::{
 	transaction_validity::{
 		TransactionSource, TransactionTag as Tag, TransactionValidity, TransactionValidityError,
 	},
+	Percent,
 };
 use std::{
 	collections::HashMap,
@@ -107,6 +108,15 @@ pub trait ChainApi: Send + Sync {
 	/// Returns hash and encoding length of the extrinsic.
 	fn hash_and_length(&self, uxt: &RawExtrinsicFor<Self>) -> (ExtrinsicHash<Self>, usize);
 
+	/// Returns the identifier of the extrinsic sender, if known.
+	///
+	/// Used to enforce the limit of future transactions of a single sender. It overrides the
+	/// sender derived from the tags of the transaction (see [`base::sender_from_tags`]), which the
+	/// default implementation relies on by returning `None`.
+	fn transaction_sender(&self, _uxt: &RawExtrinsicFor<Self>) -> Option<Vec<u8>> {
+		None
+	}
+
 	/// Returns a block body given the block.
 	fn block_body(&self, at: <Self::Block as BlockT>::Hash) -> Self::BodyFuture;
 
@@ -145,6 +155,16 @@ pub struct Options {
 	pub reject_future_transactions: bool,
 	/// How long the extrinsic is banned for.
 	pub ban_time: Duration,
+	/// Maximal number of future transactions of a single sender.
+	///
+	/// The sender is derived from the tags of the transactions, unless it is reported by
+	/// [`ChainApi::transaction_sender`]. Transactions with unknown sender are not limited.
+	pub future_sender_limit: Option<usize>,
+	/// Minimal priority increase required to replace transactions providing the same tags.
+	///
+	/// The priority of the new transaction has to exceed the priority of the replaced ones by
+	/// more than this fraction of it.
+	pub replacement_bump: Percent,
 }
 
 impl Default for Options {
@@ -154,6 +174,8 @@ impl Default for Options {
 			future: base::Limit { count: 512, total_bytes: 1 * 1024 * 1024 },
 			reject_future_transactions: false,
 			ban_time: Duration::from_secs(60 * 30),
+			future_sender_limit: None,
+			replacement_bump: Percent::zero(),
 		}
 	}
 }
@@ -440,6 +462,7 @@ impl<B: ChainApi> Pool<B> {
 				if validity.provides.is_empty() {
 					ValidatedTransaction::Invalid(hash, error::Error::NoTagsProvided.into())
 				} else {
+					let sender = self.validated_pool.api().transaction_sender(&xt);
 					ValidatedTransaction::valid_at(
 						block_number.saturated_into::<u64>(),
 						hash,
@@ -447,6 +470,7 @@ impl<B: ChainApi> Pool<B> {
 						xt,
 						bytes,
 						validity,
+						sender,
 					)
 				},
 			Err(TransactionValidityError::Invalid(e)) =>
@@ -795,6 +819,46 @@ mod tests {
 		assert!(!pool.validated_pool.is_banned(&hash2));
 	}
 
+	#[test]
+	fn should_limit_futures_per_sender() {
+		// given
+		let options = Options { future_sender_limit: Some(1), ..Default::default() };
+		let api = Arc::new(TestApi::default());
+		let pool = Pool::new(options, true.into(), api.clone());
+		let transfer = |from: AccountId, nonce| {
+			uxt(Transfer {
+				from,
+				to: AccountId::from_h256(H256::from_low_u64_be(2)),
+				amount: 5,
+				nonce,
+			})
+		};
+
+		// when
+		block_on(pool.submit_one(
+			&api.expect_hash_and_number(0),
+			SOURCE,
+			transfer(Alice.into(), 1).into(),
+		))
+		.unwrap();
+		let err = block_on(pool.submit_one(
+			&api.expect_hash_and_number(0),
+			SOURCE,
+			transfer(Alice.into(), 2).into(),
+		))
+		.unwrap_err();
+		block_on(pool.submit_one(
+			&api.expect_hash_and_number(0),
+			SOURCE,
+			transfer(Bob.into(), 1).into(),
+		))
+		.unwrap();
+
+		// then
+		assert_matches!(err, error::Error::ImmediatelyDropped);
+		assert_eq!(pool.validated_pool().status().future, 2);
+	}
+
 	#[test]
 	fn should_error_if_reject_immediately() {
 		// given
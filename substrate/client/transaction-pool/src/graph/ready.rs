@@ -27,7 +27,7 @@ use crate::LOG_TARGET;
 use log::trace;
 use sc_transaction_pool_api::error;
 use serde::Serialize;
-use sp_runtime::{traits::Member, transaction_validity::TransactionTag as Tag};
+use sp_runtime::{traits::Member, transaction_validity::TransactionTag as Tag, Percent};
 
 use super::{
 	base_pool::Transaction,
@@ -118,6 +118,9 @@ pub struct ReadyTransactions<Hash: hash::Hash + Eq, Ex> {
 	/// Best transactions that are ready to be included to the block without any other previous
 	/// transaction.
 	best: BTreeSet<TransactionRef<Hash, Ex>>,
+	/// Minimal priority increase, relative to the replaced transactions, required to replace
+	/// transactions providing the same tags.
+	replacement_bump: Percent,
 }
 
 impl<Hash, Ex> tracked_map::Size for ReadyTx<Hash, Ex> {
@@ -128,11 +131,19 @@ impl<Hash, Ex> tracked_map::Size for ReadyTx<Hash, Ex> {
 
 impl<Hash: hash::Hash + Eq, Ex> Default for ReadyTransactions<Hash, Ex> {
 	fn default() -> Self {
+		Self::new(Percent::zero())
+	}
+}
+
+impl<Hash: hash::Hash + Eq, Ex> ReadyTransactions<Hash, Ex> {
+	/// Creates a new empty queue, given the minimal priority bump required for replacements.
+	pub fn new(replacement_bump: Percent) -> Self {
 		Self {
 			insertion_id: Default::default(),
 			provided_tags: Default::default(),
 			ready: Default::default(),
 			best: Default::default(),
+			replacement_bump,
 		}
 	}
 }
@@ -447,7 +458,9 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 			};
 
 			// bail - the transaction has too low priority to replace the old ones
-			if old_priority >= tx.priority {
+			let required_priority =
+				old_priority.saturating_add(self.replacement_bump.mul_ceil(old_priority));
+			if required_priority >= tx.priority {
 				return Err(error::Error::TooLowPriority { old: old_priority, new: tx.priority })
 			}
 
@@ -601,6 +614,7 @@ mod tests {
 			provides: vec![vec![3], vec![4]],
 			propagate: true,
 			source: crate::TimedTransactionSource::new_external(false),
+			sender: None,
 		}
 	}
 
@@ -640,6 +654,31 @@ mod tests {
 		assert_eq!(ready.get().count(), 1);
 	}
 
+	#[test]
+	fn should_require_minimal_priority_bump_for_replacement() {
+		// given
+		let mut ready = ReadyTransactions::new(Percent::from_percent(10));
+		let mut tx1 = tx(1);
+		tx1.requires.clear();
+		tx1.priority = 100;
+		let mut tx2 = tx(2);
+		tx2.requires.clear();
+
+		// when
+		import(&mut ready, tx1).unwrap();
+
+		// then
+		tx2.priority = 110;
+		assert!(matches!(
+			import(&mut ready, tx2.clone()),
+			Err(error::Error::TooLowPriority { old: 100, new: 110 })
+		));
+		tx2.priority = 111;
+		let replaced = import(&mut ready, tx2).unwrap();
+		assert_eq!(replaced.into_iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![1]);
+		assert_eq!(ready.get().count(), 1);
+	}
+
 	#[test]
 	fn should_replace_multiple_transactions_correctly() {
 		// given
@@ -711,6 +750,7 @@ mod tests {
 			provides: vec![],
 			propagate: true,
 			source: crate::TimedTransactionSource::new_external(false),
+			sender: None,
 		};
 
 		// when
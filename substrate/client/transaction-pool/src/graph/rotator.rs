@@ -101,6 +101,11 @@ impl<Hash: hash::Hash + Eq + Clone> PoolRotator<Hash> {
 
 		banned.retain(|_, &mut v| v >= *now);
 	}
+
+	/// Removes all bans.
+	pub fn clear(&self) {
+		self.banned_until.write().clear();
+	}
 }
 
 #[cfg(test)]
@@ -126,6 +131,7 @@ mod tests {
 			provides: vec![],
 			propagate: true,
 			source: crate::TimedTransactionSource::new_external(false),
+			sender: None,
 		};
 
 		(hash, tx)
@@ -192,6 +198,7 @@ mod tests {
 				provides: vec![],
 				propagate: true,
 				source: crate::TimedTransactionSource::new_external(false),
+				sender: None,
 			}
 		}
 
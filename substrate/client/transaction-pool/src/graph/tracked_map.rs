@@ -173,6 +173,13 @@ where
 	pub fn len(&mut self) -> usize {
 		self.inner_guard.len()
 	}
+
+	/// Removes all values and returns them.
+	pub fn clear(&mut self) -> HashMap<K, V> {
+		self.bytes.store(0, AtomicOrdering::Relaxed);
+		self.length.store(0, AtomicOrdering::Relaxed);
+		std::mem::take(&mut *self.inner_guard)
+	}
 }
 
 #[cfg(test)]
@@ -203,5 +210,9 @@ mod tests {
 		map.write().remove(&6);
 		assert_eq!(map.bytes(), 1);
 		assert_eq!(map.len(), 1);
+
+		assert_eq!(map.write().clear(), HashMap::from([(5, 10)]));
+		assert_eq!(map.bytes(), 0);
+		assert_eq!(map.len(), 0);
 	}
 }
@@ -59,6 +59,8 @@ pub enum ValidatedTransaction<Hash, Ex, Error> {
 
 impl<Hash, Ex, Error> ValidatedTransaction<Hash, Ex, Error> {
 	/// Consume validity result, transaction data and produce ValidTransaction.
+	///
+	/// If the `sender` is not known, it is derived from the tags of the transaction.
 	pub fn valid_at(
 		at: u64,
 		hash: Hash,
@@ -66,7 +68,10 @@ impl<Hash, Ex, Error> ValidatedTransaction<Hash, Ex, Error> {
 		data: Ex,
 		bytes: usize,
 		validity: ValidTransaction,
+		sender: Option<Vec<u8>>,
 	) -> Self {
+		let sender =
+			sender.or_else(|| base::sender_from_tags(&validity.provides, &validity.requires));
 		Self::Valid(base::Transaction {
 			data,
 			bytes,
@@ -77,6 +82,7 @@ impl<Hash, Ex, Error> ValidatedTransaction<Hash, Ex, Error> {
 			provides: validity.provides,
 			propagate: validity.propagate,
 			valid_till: at.saturated_into::<u64>().saturating_add(validity.longevity),
+			sender,
 		})
 	}
 }
@@ -129,7 +135,11 @@ impl<B: ChainApi> Clone for ValidatedPool<B> {
 impl<B: ChainApi> ValidatedPool<B> {
 	/// Create a new transaction pool.
 	pub fn new(options: Options, is_validator: IsValidator, api: Arc<B>) -> Self {
-		let base_pool = base::BasePool::new(options.reject_future_transactions);
+		let base_pool = base::BasePool::new(
+			options.reject_future_transactions,
+			options.future_sender_limit,
+			options.replacement_bump,
+		);
 		let ban_time = options.ban_time;
 		Self {
 			is_validator,
@@ -635,6 +645,23 @@ impl<B: ChainApi> ValidatedPool<B> {
 		invalid
 	}
 
+	/// Remove all transactions from the pool and lift all the bans.
+	///
+	/// The watchers of the removed transactions are notified that they were dropped.
+	pub fn clear(&self) -> Vec<TransactionFor<B>> {
+		let removed = self.pool.write().clear();
+		self.rotator.clear();
+
+		log::debug!(target: LOG_TARGET, "Cleared the pool: {:?}", removed.len());
+
+		let mut listener = self.listener.write();
+		for tx in &removed {
+			listener.dropped(&tx.hash);
+		}
+
+		removed
+	}
+
 	/// Get an iterator for ready transactions ordered by priority
 	pub fn ready(&self) -> impl ReadyTransactions<Item = TransactionFor<B>> + Send {
 		self.pool.read().ready()
@@ -697,7 +724,7 @@ where
 		base::Imported::Ready { ref promoted, ref failed, ref removed, ref hash } => {
 			listener.ready(hash, None);
 			failed.iter().for_each(|f| listener.invalid(f));
-			removed.iter().for_each(|r| listener.usurped(&r.hash, hash));
+			removed.iter().for_each(|r| listener.replaced(&r.hash, hash));
 			promoted.iter().for_each(|p| listener.ready(p, None));
 		},
 		base::Imported::Future { ref hash } => listener.future(hash),
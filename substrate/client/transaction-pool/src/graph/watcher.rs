@@ -83,6 +83,12 @@ impl<H: Clone, BH: Clone> Sender<H, BH> {
 		self.is_finalized = true;
 	}
 
+	/// Extrinsic has been replaced in the ready queue by the given extrinsic.
+	pub fn replaced(&mut self, hash: H) {
+		self.send(TransactionStatus::Replaced(hash));
+		self.is_finalized = true;
+	}
+
 	/// Extrinsic has been included in block with given hash.
 	pub fn in_block(&mut self, hash: BH, index: usize) {
 		self.send(TransactionStatus::InBlock((hash, index)));
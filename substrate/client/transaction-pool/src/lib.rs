@@ -32,7 +32,7 @@ mod transaction_pool_wrapper;
 use common::{api, enactment_state};
 use std::sync::Arc;
 
-pub use api::FullChainApi;
+pub use api::{FullChainApi, TransactionSenderFn};
 pub use builder::{Builder, TransactionPoolHandle, TransactionPoolOptions, TransactionPoolType};
 pub use common::notification_future;
 pub use fork_aware_txpool::{ForkAwareTxPool, ForkAwareTxPoolTask};
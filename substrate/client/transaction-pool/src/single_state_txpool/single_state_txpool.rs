@@ -318,6 +318,10 @@ where
 		removed
 	}
 
+	fn clear(&self) -> Vec<Arc<Self::InPoolTransaction>> {
+		self.pool.validated_pool().clear()
+	}
+
 	fn status(&self) -> PoolStatus {
 		self.pool.validated_pool().status()
 	}
@@ -467,6 +471,7 @@ where
 			.block_id_to_number(&BlockId::hash(at))?
 			.ok_or_else(|| error::Error::BlockIdConversion(format!("{:?}", at)))?;
 
+		let sender = self.api.transaction_sender(&xt);
 		let validated = ValidatedTransaction::valid_at(
 			block_number.saturated_into::<u64>(),
 			hash,
@@ -474,6 +479,7 @@ where
 			Arc::from(xt),
 			bytes,
 			validity,
+			sender,
 		);
 
 		self.pool.validated_pool().submit(vec![validated]).remove(0)
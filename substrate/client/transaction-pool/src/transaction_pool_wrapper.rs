@@ -111,6 +111,10 @@ where
 		self.0.remove_invalid(hashes)
 	}
 
+	fn clear(&self) -> Vec<Arc<Self::InPoolTransaction>> {
+		self.0.clear()
+	}
+
 	fn futures(&self) -> Vec<Self::InPoolTransaction> {
 		self.0.futures()
 	}
@@ -82,7 +82,7 @@ fn fatp_prio_watcher_ready_higher_evicts_lower() {
 	let xt0_status = futures::executor::block_on_stream(xt0_watcher).take(2).collect::<Vec<_>>();
 	assert_eq!(
 		xt0_status,
-		vec![TransactionStatus::Ready, TransactionStatus::Usurped(api.hash_and_length(&xt1).0)]
+		vec![TransactionStatus::Ready, TransactionStatus::Replaced(api.hash_and_length(&xt1).0)]
 	);
 	let xt1_status = futures::executor::block_on_stream(xt1_watcher).take(1).collect::<Vec<_>>();
 	assert_eq!(xt1_status, vec![TransactionStatus::Ready]);
@@ -123,7 +123,7 @@ fn fatp_prio_watcher_future_higher_evicts_lower() {
 
 	assert_eq!(
 		xt0_status,
-		vec![TransactionStatus::Future, TransactionStatus::Usurped(api.hash_and_length(&xt2).0)]
+		vec![TransactionStatus::Future, TransactionStatus::Replaced(api.hash_and_length(&xt2).0)]
 	);
 	let xt1_status = futures::executor::block_on_stream(xt1_watcher).take(2).collect::<Vec<_>>();
 	assert_eq!(xt1_status, vec![TransactionStatus::Future, TransactionStatus::Ready]);
@@ -178,7 +178,7 @@ fn fatp_prio_watcher_ready_lower_prio_gets_dropped_from_all_views() {
 	let xt0_status = futures::executor::block_on_stream(xt0_watcher).take(2).collect::<Vec<_>>();
 	assert_eq!(
 		xt0_status,
-		vec![TransactionStatus::Ready, TransactionStatus::Usurped(api.hash_and_length(&xt1).0)]
+		vec![TransactionStatus::Ready, TransactionStatus::Replaced(api.hash_and_length(&xt1).0)]
 	);
 	assert_ready_iterator!(header03a.hash(), pool, [xt1]);
 	assert_ready_iterator!(header03b.hash(), pool, [xt1]);
@@ -235,7 +235,7 @@ fn fatp_prio_watcher_future_lower_prio_gets_dropped_from_all_views() {
 	let xt0_status = futures::executor::block_on_stream(xt0_watcher).take(2).collect::<Vec<_>>();
 	assert_eq!(
 		xt0_status,
-		vec![TransactionStatus::Future, TransactionStatus::Usurped(api.hash_and_length(&xt2).0)]
+		vec![TransactionStatus::Future, TransactionStatus::Replaced(api.hash_and_length(&xt2).0)]
 	);
 	assert_future_iterator!(header03a.hash(), pool, []);
 	assert_future_iterator!(header03b.hash(), pool, []);
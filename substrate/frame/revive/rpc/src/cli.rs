@@ -16,17 +16,19 @@
 // limitations under the License.
 //! The Ethereum JSON-RPC server.
 use crate::{
-	client::Client, EthRpcServer, EthRpcServerImpl, SystemHealthRpcServer,
-	SystemHealthRpcServerImpl,
+	client::Client, DebugRpcServer, DebugRpcServerImpl, EthPubSubServer, EthPubSubServerImpl,
+	EthRpcServer, EthRpcServerImpl, SystemHealthRpcServer, SystemHealthRpcServerImpl,
 };
 use clap::Parser;
 use futures::{pin_mut, FutureExt};
 use jsonrpsee::server::RpcModule;
 use sc_cli::{PrometheusParams, RpcParams, SharedParams, Signals};
+use sc_rpc::SubscriptionTaskExecutor;
 use sc_service::{
 	config::{PrometheusConfig, RpcConfiguration},
 	start_rpc_servers, TaskManager,
 };
+use std::sync::Arc;
 
 // Default port if --prometheus-port is not specified
 const DEFAULT_PROMETHEUS_PORT: u16 = 9616;
@@ -113,6 +115,7 @@ pub fn run(cmd: CliCommand) -> anyhow::Result<()> {
 	let signals = tokio_runtime.block_on(async { Signals::capture() })?;
 	let mut task_manager = TaskManager::new(tokio_handle.clone(), prometheus_registry)?;
 	let essential_spawn_handle = task_manager.spawn_essential_handle();
+	let subscription_executor: SubscriptionTaskExecutor = Arc::new(task_manager.spawn_handle());
 
 	let gen_rpc_module = || {
 		let signals = tokio_runtime.block_on(async { Signals::capture() })?;
@@ -120,7 +123,7 @@ pub fn run(cmd: CliCommand) -> anyhow::Result<()> {
 		pin_mut!(fut);
 
 		match tokio_handle.block_on(signals.try_until_signal(fut)) {
-			Ok(Ok(client)) => rpc_module(is_dev, client),
+			Ok(Ok(client)) => rpc_module(is_dev, client, subscription_executor.clone()),
 			Ok(Err(err)) => {
 				log::error!("Error connecting to the node at {node_rpc_url}: {err}");
 				Err(sc_service::Error::Application(err.into()))
@@ -147,15 +150,25 @@ pub fn run(cmd: CliCommand) -> anyhow::Result<()> {
 }
 
 /// Create the JSON-RPC module.
-fn rpc_module(is_dev: bool, client: Client) -> Result<RpcModule<()>, sc_service::Error> {
+fn rpc_module(
+	is_dev: bool,
+	client: Client,
+	subscription_executor: SubscriptionTaskExecutor,
+) -> Result<RpcModule<()>, sc_service::Error> {
 	let eth_api = EthRpcServerImpl::new(client.clone())
 		.with_accounts(if is_dev { vec![crate::Account::default()] } else { vec![] })
 		.into_rpc();
 
+	let pubsub_api = EthPubSubServerImpl::new(client.clone(), subscription_executor).into_rpc();
+
+	let debug_api = DebugRpcServerImpl::new(client.clone()).into_rpc();
+
 	let health_api = SystemHealthRpcServerImpl::new(client).into_rpc();
 
 	let mut module = RpcModule::new(());
 	module.merge(eth_api).map_err(|e| sc_service::Error::Application(e.into()))?;
+	module.merge(pubsub_api).map_err(|e| sc_service::Error::Application(e.into()))?;
+	module.merge(debug_api).map_err(|e| sc_service::Error::Application(e.into()))?;
 	module.merge(health_api).map_err(|e| sc_service::Error::Application(e.into()))?;
 	Ok(module)
 }
@@ -24,13 +24,15 @@ use crate::{
 	},
 	LOG_TARGET,
 };
+use codec::{Compact, Decode, Encode};
 use futures::{stream, StreamExt};
 use jsonrpsee::types::{error::CALL_EXECUTION_FAILED_CODE, ErrorObjectOwned};
 use pallet_revive::{
 	create1,
 	evm::{
-		Block, BlockNumberOrTag, BlockNumberOrTagOrHash, Bytes256, GenericTransaction, Log,
-		ReceiptInfo, SyncingProgress, SyncingStatus, TransactionSigned, H160, H256, U256,
+		Block, BlockNumberOrTag, BlockNumberOrTagOrHash, BlockTag, Bytes256, FeeHistoryResult,
+		Filter, GenericTransaction, Log, ReceiptInfo, SyncingProgress, SyncingStatus, Trace,
+		TracerType, TransactionSigned, TransactionTrace, H160, H256, U256,
 	},
 	EthTransactError, EthTransactInfo,
 };
@@ -56,7 +58,7 @@ use subxt::{
 };
 use subxt_client::transaction_payment::events::TransactionFeePaid;
 use thiserror::Error;
-use tokio::sync::{watch::Sender, RwLock};
+use tokio::sync::{broadcast, watch::Sender, RwLock};
 
 use crate::subxt_client::{self, system::events::ExtrinsicSuccess, SrcChainConfig};
 
@@ -96,6 +98,9 @@ struct BlockCache<const N: usize> {
 
 	/// A map of receipt hashes by block hash.
 	tx_hashes_by_block_and_index: HashMap<H256, HashMap<U256, H256>>,
+
+	/// A map of the logs emitted in a block, ordered by log index, by block hash.
+	logs_by_block_hash: HashMap<H256, Vec<Log>>,
 }
 
 /// Unwrap the original `jsonrpsee::core::client::Error::Call` error.
@@ -185,14 +190,36 @@ pub enum ClientError {
 	/// The cache is empty.
 	#[error("cache is empty")]
 	CacheEmpty,
+	/// The requested block range is too large.
+	#[error("block range too large, max is {} blocks", MAX_LOGS_BLOCK_RANGE)]
+	BlockRangeTooLarge,
+	/// The transaction was not found.
+	#[error("transaction not found")]
+	TransactionNotFound,
+	/// The runtime did not produce a trace.
+	#[error("trace not found")]
+	TraceNotFound,
 }
 
 const REVERT_CODE: i32 = 3;
+
+/// The error code returned by the node when a transaction has too low priority to replace another
+/// transaction already in the pool.
+const POOL_TOO_LOW_PRIORITY_CODE: i32 = 1014;
+
 impl From<ClientError> for ErrorObjectOwned {
 	fn from(err: ClientError) -> Self {
 		match err {
 			ClientError::SubxtError(subxt::Error::Rpc(err)) | ClientError::RpcError(err) => {
 				if let Some(err) = unwrap_call_err(&err) {
+					// Report failed replacements the way Ethereum clients expect.
+					if err.code() == POOL_TOO_LOW_PRIORITY_CODE {
+						return ErrorObjectOwned::owned::<String>(
+							CALL_EXECUTION_FAILED_CODE,
+							"replacement transaction underpriced",
+							None,
+						);
+					}
 					return err;
 				}
 				ErrorObjectOwned::owned::<Vec<u8>>(
@@ -218,6 +245,67 @@ impl From<ClientError> for ErrorObjectOwned {
 /// For each block in the cache, we also store the EVM transaction receipts.
 pub const CACHE_SIZE: usize = 256;
 
+/// The maximum number of blocks that can be scanned by a single logs query.
+pub const MAX_LOGS_BLOCK_RANGE: SubstrateBlockNumber = 1024;
+
+/// The maximum number of blocks that can be requested with `eth_feeHistory`.
+pub const MAX_FEE_HISTORY_BLOCK_COUNT: SubstrateBlockNumber = 1024;
+
+/// The base fee per gas of every block.
+///
+/// The gas used by a transaction is derived from the fee it paid, which already accounts for the
+/// `NextFeeMultiplier` of `pallet_transaction_payment`. The price of a unit of gas is thus always
+/// [`GAS_PRICE`], the price reported by `eth_gasPrice`.
+fn base_fee_per_gas() -> U256 {
+	U256::from(GAS_PRICE)
+}
+
+/// The ratio of the `consumed` weight of a block to its weight `limit`.
+///
+/// A block is full as soon as one of the dimensions of its weight reaches the limit, so the
+/// highest ratio of the two dimensions is used.
+fn gas_used_ratio(consumed: Weight, limit: Weight) -> f64 {
+	let ratio = |consumed: u64, limit: u64| {
+		if limit == 0 {
+			0.0
+		} else {
+			consumed as f64 / limit as f64
+		}
+	};
+	ratio(consumed.ref_time(), limit.ref_time())
+		.max(ratio(consumed.proof_size(), limit.proof_size()))
+		.min(1.0)
+}
+
+/// Compute the effective priority fees per gas at the given percentiles of the gas used by the
+/// given `(priority fee, gas used)` pairs.
+///
+/// Returns zeroes if `txs` is empty.
+fn reward_percentiles(mut txs: Vec<(U256, U256)>, percentiles: &[f64]) -> Vec<U256> {
+	if txs.is_empty() {
+		return vec![U256::zero(); percentiles.len()];
+	}
+
+	txs.sort_by_key(|(reward, _)| *reward);
+	let total_gas_used = txs.iter().fold(U256::zero(), |acc, (_, gas)| acc.saturating_add(*gas));
+
+	let mut index = 0;
+	let mut cumulative_gas_used = txs[0].1;
+	percentiles
+		.iter()
+		.map(|percentile| {
+			// Percentiles are in [0, 100], with at most 2 decimals of precision that matter.
+			let threshold = total_gas_used.saturating_mul(U256::from((percentile * 100.0) as u64)) /
+				U256::from(10_000u64);
+			while cumulative_gas_used < threshold && index < txs.len() - 1 {
+				index += 1;
+				cumulative_gas_used = cumulative_gas_used.saturating_add(txs[index].1);
+			}
+			txs[index].0
+		})
+		.collect()
+}
+
 impl<const N: usize> BlockCache<N> {
 	fn latest_block(&self) -> Option<&Arc<SubstrateBlock>> {
 		self.buffer.back()
@@ -231,6 +319,7 @@ impl<const N: usize> BlockCache<N> {
 				let hash = block.hash();
 				self.blocks_by_hash.remove(&hash);
 				self.blocks_by_number.remove(&block.number());
+				self.logs_by_block_hash.remove(&hash);
 				if let Some(entries) = self.tx_hashes_by_block_and_index.remove(&hash) {
 					for hash in entries.values() {
 						self.receipts_by_hash.remove(hash);
@@ -261,6 +350,7 @@ struct ClientInner {
 	rpc_client: ReconnectingRpcClient,
 	rpc: LegacyRpcMethods<SrcChainConfig>,
 	cache: Shared<BlockCache<CACHE_SIZE>>,
+	block_notifier: broadcast::Sender<Arc<SubstrateBlock>>,
 	chain_id: u64,
 	max_block_weight: Weight,
 }
@@ -275,13 +365,23 @@ impl ClientInner {
 
 		let api = OnlineClient::<SrcChainConfig>::from_rpc_client(rpc_client.clone()).await?;
 		let cache = Arc::new(RwLock::new(BlockCache::<CACHE_SIZE>::default()));
+		let (block_notifier, _) = broadcast::channel(CACHE_SIZE);
 
 		let rpc = LegacyRpcMethods::<SrcChainConfig>::new(RpcClient::new(rpc_client.clone()));
 
 		let (chain_id, max_block_weight) =
 			tokio::try_join!(chain_id(&api), max_block_weight(&api))?;
 
-		Ok(Self { api, rpc_client, rpc, cache, chain_id, max_block_weight })
+		Ok(Self { api, rpc_client, rpc, cache, block_notifier, chain_id, max_block_weight })
+	}
+
+	/// Get the logs emitted in a block, ordered by log index.
+	async fn block_logs(&self, block: &SubstrateBlock) -> Result<Vec<Log>, ClientError> {
+		let receipts = self.receipt_infos(block).await?;
+		let mut logs =
+			receipts.into_values().flat_map(|(_, receipt)| receipt.logs).collect::<Vec<_>>();
+		logs.sort_by_key(|log| log.log_index);
+		Ok(logs)
 	}
 
 	/// Get the receipt infos from the extrinsics in a block.
@@ -505,6 +605,13 @@ impl Client {
 				})
 				.unwrap_or_default();
 
+			let mut logs = receipts
+				.values()
+				.flat_map(|(_, receipt)| receipt.logs.iter().cloned())
+				.collect::<Vec<_>>();
+			logs.sort_by_key(|log| log.log_index);
+			cache.logs_by_block_hash.insert(block.hash(), logs);
+
 			if !receipts.is_empty() {
 				let values = receipts
 					.iter()
@@ -523,6 +630,13 @@ impl Client {
 			}
 
 			cache.insert(block);
+			let latest = cache.latest_block().cloned();
+			drop(cache);
+
+			if let Some(block) = latest {
+				// An error only means that there are no active subscribers.
+				let _ = inner.block_notifier.send(block);
+			}
 			tx.send_replace(());
 		}
 
@@ -538,6 +652,11 @@ impl Client {
 		Some(block.clone())
 	}
 
+	/// Subscribe to the new best blocks added to the cache.
+	pub fn subscribe_new_blocks(&self) -> broadcast::Receiver<Arc<SubstrateBlock>> {
+		self.inner.block_notifier.subscribe()
+	}
+
 	/// Expose the transaction API.
 	pub async fn submit(
 		&self,
@@ -767,6 +886,256 @@ impl Client {
 		self.block_by_hash(&hash).await
 	}
 
+	/// Get the logs emitted in the given block, ordered by log index.
+	pub async fn block_logs(&self, block: &SubstrateBlock) -> Result<Vec<Log>, ClientError> {
+		let cache = self.inner.cache.read().await;
+		if let Some(logs) = cache.logs_by_block_hash.get(&block.hash()) {
+			return Ok(logs.clone());
+		}
+		drop(cache);
+
+		self.inner.block_logs(block).await
+	}
+
+	/// Resolve a block number or tag to a block number, relative to the given latest block.
+	///
+	/// Block numbers are returned as is, even past the latest block.
+	fn resolve_block_number(
+		block: Option<&BlockNumberOrTag>,
+		latest: SubstrateBlockNumber,
+	) -> Result<SubstrateBlockNumber, ClientError> {
+		match block {
+			Some(BlockNumberOrTag::U256(n)) =>
+				(*n).try_into().map_err(|_| ClientError::ConversionFailed),
+			Some(BlockNumberOrTag::BlockTag(BlockTag::Earliest)) => Ok(0),
+			Some(BlockNumberOrTag::BlockTag(_)) | None => Ok(latest),
+		}
+	}
+
+	/// Get the logs matching the given filter.
+	///
+	/// The filter either targets a single block by hash, or a range of blocks that spans at most
+	/// [`MAX_LOGS_BLOCK_RANGE`] blocks.
+	pub async fn logs(&self, filter: &Filter) -> Result<Vec<Log>, ClientError> {
+		let blocks = if let Some(hash) = filter.block_hash {
+			vec![self.block_by_hash(&hash).await?.ok_or(ClientError::BlockNotFound)?]
+		} else {
+			let latest = self.block_number().await?;
+			let from = Self::resolve_block_number(filter.from_block.as_ref(), latest)?;
+			let to = Self::resolve_block_number(filter.to_block.as_ref(), latest)?.min(latest);
+			// Nothing matches blocks that are not produced yet.
+			if from > to {
+				return Ok(vec![]);
+			}
+
+			if to - from >= MAX_LOGS_BLOCK_RANGE {
+				return Err(ClientError::BlockRangeTooLarge);
+			}
+
+			stream::iter(from..=to)
+				.map(
+					|n| async move { self.block_by_number(n).await?.ok_or(ClientError::BlockNotFound) },
+				)
+				.buffered(10)
+				.collect::<Vec<Result<_, _>>>()
+				.await
+				.into_iter()
+				.collect::<Result<Vec<_>, _>>()?
+		};
+
+		let mut logs = Vec::new();
+		for block in blocks {
+			let block_logs = self.block_logs(&block).await?;
+			logs.extend(block_logs.into_iter().filter(|log| filter.matches(log)));
+		}
+
+		Ok(logs)
+	}
+
+	/// Get the SCALE encoded block with the given hash, as expected by the runtime API.
+	async fn encoded_block(&self, hash: SubstrateBlockHash) -> Result<Vec<u8>, ClientError> {
+		let details = self
+			.inner
+			.rpc
+			.chain_get_block(Some(hash))
+			.await?
+			.ok_or(ClientError::BlockNotFound)?;
+
+		// Each extrinsic is already encoded with its length prefix.
+		let mut encoded = details.block.header.encode();
+		Compact(details.block.extrinsics.len() as u32).encode_to(&mut encoded);
+		for extrinsic in details.block.extrinsics {
+			encoded.extend(extrinsic.0);
+		}
+		Ok(encoded)
+	}
+
+	/// Call the given tracing runtime API on the state of the parent of `block`.
+	async fn trace_on_parent<R: Decode>(
+		&self,
+		block: &SubstrateBlock,
+		function: &str,
+		params: impl Encode,
+	) -> Result<R, ClientError> {
+		let mut call_parameters = self.encoded_block(block.hash()).await?;
+		params.encode_to(&mut call_parameters);
+
+		// TODO: remove once subxt is updated
+		let parent_hash = block.header().parent_hash.0.into();
+		let runtime_api = self.inner.api.runtime_api().at(parent_hash);
+		let result = runtime_api.call_raw(function, Some(&call_parameters)).await?;
+		Ok(result)
+	}
+
+	/// Trace the transactions of the given block, by re-executing it on top of its parent.
+	pub async fn trace_block(
+		&self,
+		block: &SubstrateBlock,
+		tracer_type: TracerType,
+	) -> Result<Vec<TransactionTrace>, ClientError> {
+		let traces: Vec<(u32, Trace)> =
+			self.trace_on_parent(block, "ReviveApi_trace_block", tracer_type).await?;
+
+		let extrinsics = block.extrinsics().await?;
+		let tx_hashes = extrinsics
+			.iter()
+			.filter_map(|ext| {
+				let call = ext.as_extrinsic::<EthTransact>().ok()??;
+				Some((ext.index(), H256(keccak_256(&call.payload))))
+			})
+			.collect::<HashMap<_, _>>();
+
+		Ok(traces
+			.into_iter()
+			.filter_map(|(index, result)| {
+				tx_hashes
+					.get(&index)
+					.map(|tx_hash| TransactionTrace { tx_hash: *tx_hash, result })
+			})
+			.collect())
+	}
+
+	/// Trace the transaction with the given hash, by re-executing its block on top of its
+	/// parent.
+	pub async fn trace_transaction(
+		&self,
+		tx_hash: H256,
+		tracer_type: TracerType,
+	) -> Result<Trace, ClientError> {
+		let receipt = self.receipt(&tx_hash).await.ok_or(ClientError::TransactionNotFound)?;
+		let block = self
+			.block_by_hash(&receipt.block_hash)
+			.await?
+			.ok_or(ClientError::BlockNotFound)?;
+		let transaction_index: u32 = receipt
+			.transaction_index
+			.try_into()
+			.map_err(|_| ClientError::ConversionFailed)?;
+
+		let trace: Option<Trace> = self
+			.trace_on_parent(&block, "ReviveApi_trace_tx", (transaction_index, tracer_type))
+			.await?;
+		trace.ok_or(ClientError::TraceNotFound)
+	}
+
+	/// Dry run and trace the given transaction.
+	pub async fn trace_call(
+		&self,
+		tx: GenericTransaction,
+		block: BlockNumberOrTagOrHash,
+		tracer_type: TracerType,
+	) -> Result<Trace, ClientError> {
+		let runtime_api = self.runtime_api(&block).await?;
+		let call_parameters = (tx, tracer_type).encode();
+		let result: Result<Trace, EthTransactError> =
+			runtime_api.call_raw("ReviveApi_trace_call", Some(&call_parameters)).await?;
+		result.map_err(ClientError::Reverted)
+	}
+
+	/// Get the EVM transaction receipts of the given block.
+	async fn block_receipts(
+		&self,
+		block: &SubstrateBlock,
+	) -> Result<Vec<ReceiptInfo>, ClientError> {
+		let cache = self.inner.cache.read().await;
+		if let Some(tx_hashes) = cache.tx_hashes_by_block_and_index.get(&block.hash()) {
+			return Ok(tx_hashes
+				.values()
+				.filter_map(|hash| cache.receipts_by_hash.get(hash).cloned())
+				.collect());
+		}
+		drop(cache);
+
+		let receipts = self.inner.receipt_infos(block).await?;
+		Ok(receipts.into_values().map(|(_, receipt)| receipt).collect())
+	}
+
+	/// Get the base fee per gas, the gas used ratio and the rewards at the given percentiles of
+	/// the given block.
+	async fn block_fee_info(
+		&self,
+		block: &SubstrateBlock,
+		reward_percentiles: &[f64],
+	) -> Result<(U256, f64, Vec<U256>), ClientError> {
+		let base_fee = base_fee_per_gas();
+
+		let query = subxt_client::storage().system().block_weight();
+		let consumed = self.inner.api.storage().at(block.hash()).fetch_or_default(&query).await?;
+		let consumed = consumed.normal.0 + consumed.operational.0 + consumed.mandatory.0;
+		let gas_used_ratio = gas_used_ratio(consumed, self.max_block_weight());
+
+		let txs = self
+			.block_receipts(block)
+			.await?
+			.into_iter()
+			.map(|receipt| (receipt.effective_gas_price.saturating_sub(base_fee), receipt.gas_used))
+			.collect();
+
+		Ok((base_fee, gas_used_ratio, reward_percentiles(txs, reward_percentiles)))
+	}
+
+	/// Get the fee history of the `block_count` blocks ending with `newest_block`.
+	///
+	/// At most [`MAX_FEE_HISTORY_BLOCK_COUNT`] blocks are returned.
+	pub async fn fee_history(
+		&self,
+		block_count: SubstrateBlockNumber,
+		newest_block: &BlockNumberOrTag,
+		reward_percentiles: &[f64],
+	) -> Result<FeeHistoryResult, ClientError> {
+		let latest = self.block_number().await?;
+		let newest = Self::resolve_block_number(Some(newest_block), latest)?.min(latest);
+		let block_count = block_count.min(MAX_FEE_HISTORY_BLOCK_COUNT);
+		if block_count == 0 {
+			return Ok(FeeHistoryResult::default());
+		}
+		let oldest = newest.saturating_sub(block_count - 1);
+
+		let blocks = stream::iter(oldest..=newest)
+			.map(|n| async move {
+				let block = self.block_by_number(n).await?.ok_or(ClientError::BlockNotFound)?;
+				self.block_fee_info(&block, reward_percentiles).await
+			})
+			.buffered(10)
+			.collect::<Vec<Result<_, _>>>()
+			.await
+			.into_iter()
+			.collect::<Result<Vec<_>, _>>()?;
+
+		let mut result = FeeHistoryResult { oldest_block: oldest.into(), ..Default::default() };
+		for (base_fee, gas_used_ratio, reward) in blocks {
+			result.base_fee_per_gas.push(base_fee);
+			result.gas_used_ratio.push(gas_used_ratio);
+			if !reward_percentiles.is_empty() {
+				result.reward.push(reward);
+			}
+		}
+
+		// The base fee of the block following the newest block is reported as well.
+		result.base_fee_per_gas.push(base_fee_per_gas());
+		Ok(result)
+	}
+
 	/// Get the EVM block for the given hash.
 	pub async fn evm_block(&self, block: Arc<SubstrateBlock>) -> Result<Block, ClientError> {
 		let runtime_api = self.inner.api.runtime_api().at(block.hash());
@@ -819,3 +1188,38 @@ impl Client {
 		self.inner.max_block_weight
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn gas_used_ratio_is_computed_from_weights() {
+		let limit = Weight::from_parts(2_000, 1_000);
+		assert_eq!(gas_used_ratio(Weight::zero(), limit), 0.0);
+		assert_eq!(gas_used_ratio(Weight::from_parts(500, 100), limit), 0.25);
+		// The most consumed dimension is reported.
+		assert_eq!(gas_used_ratio(Weight::from_parts(500, 500), limit), 0.5);
+		assert_eq!(gas_used_ratio(Weight::from_parts(4_000, 0), limit), 1.0);
+		assert_eq!(gas_used_ratio(Weight::from_parts(500, 100), Weight::zero()), 0.0);
+	}
+
+	#[test]
+	fn reward_percentiles_of_a_block_with_known_fees() {
+		let base_fee = base_fee_per_gas();
+		assert_eq!(base_fee, U256::from(GAS_PRICE));
+
+		// `(effective gas price, gas used)` of the transactions of the block.
+		let receipts = [(base_fee + 3, 100u64), (base_fee, 300), (base_fee + 1, 600)];
+		let txs = receipts
+			.iter()
+			.map(|(price, gas)| (price.saturating_sub(base_fee), U256::from(*gas)))
+			.collect::<Vec<_>>();
+
+		assert_eq!(
+			reward_percentiles(txs, &[0.0, 25.0, 50.0, 90.0, 100.0]),
+			vec![0, 0, 1, 1, 3].into_iter().map(U256::from).collect::<Vec<_>>()
+		);
+		assert_eq!(reward_percentiles(vec![], &[50.0]), vec![U256::zero()]);
+	}
+}
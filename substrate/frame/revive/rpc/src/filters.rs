@@ -0,0 +1,157 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Installed filters, polled with `eth_getFilterChanges`.
+use crate::client::SubstrateBlockNumber;
+use pallet_revive::evm::{Filter, U256};
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex},
+	time::{Duration, Instant},
+};
+
+/// The duration after which a filter that has not been polled is removed.
+pub const FILTER_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// The maximum number of filters that can be installed at the same time.
+pub const MAX_FILTERS: usize = 512;
+
+/// The kind of an installed filter.
+#[derive(Debug, Clone)]
+pub enum FilterKind {
+	/// A filter created with `eth_newFilter`, reporting matching logs.
+	Logs(Filter),
+	/// A filter created with `eth_newBlockFilter`, reporting new block hashes.
+	Blocks,
+}
+
+/// An installed filter.
+struct InstalledFilter {
+	/// The kind of filter.
+	kind: FilterKind,
+	/// The last block reported by this filter.
+	last_block: SubstrateBlockNumber,
+	/// The last time the filter was polled.
+	last_poll: Instant,
+}
+
+/// The inner state of [`Filters`].
+#[derive(Default)]
+struct FiltersInner {
+	/// The id of the next installed filter.
+	next_id: u64,
+	/// The installed filters by id.
+	filters: HashMap<U256, InstalledFilter>,
+}
+
+impl FiltersInner {
+	/// Remove the filters that have not been polled for [`FILTER_TIMEOUT`].
+	fn prune_expired(&mut self) {
+		let now = Instant::now();
+		self.filters
+			.retain(|_, filter| now.duration_since(filter.last_poll) < FILTER_TIMEOUT);
+	}
+}
+
+/// The set of filters installed on the server.
+#[derive(Default, Clone)]
+pub struct Filters {
+	inner: Arc<Mutex<FiltersInner>>,
+}
+
+impl Filters {
+	/// Install a new filter, starting after the given block.
+	///
+	/// Returns `None` if the maximum number of filters is reached.
+	pub fn install(&self, kind: FilterKind, current_block: SubstrateBlockNumber) -> Option<U256> {
+		let mut inner = self.inner.lock().expect("lock is not poisoned; qed");
+		inner.prune_expired();
+		if inner.filters.len() >= MAX_FILTERS {
+			return None;
+		}
+
+		let id = U256::from(inner.next_id);
+		inner.next_id += 1;
+		inner.filters.insert(
+			id,
+			InstalledFilter { kind, last_block: current_block, last_poll: Instant::now() },
+		);
+		Some(id)
+	}
+
+	/// Uninstall the filter with the given id.
+	///
+	/// Returns `true` if the filter was installed.
+	pub fn uninstall(&self, id: &U256) -> bool {
+		let mut inner = self.inner.lock().expect("lock is not poisoned; qed");
+		inner.prune_expired();
+		inner.filters.remove(id).is_some()
+	}
+
+	/// Get the kind of the filter with the given id.
+	pub fn get(&self, id: &U256) -> Option<FilterKind> {
+		let mut inner = self.inner.lock().expect("lock is not poisoned; qed");
+		inner.prune_expired();
+		inner.filters.get(id).map(|filter| filter.kind.clone())
+	}
+
+	/// Poll the filter with the given id, up to the given block.
+	///
+	/// Returns the filter kind and the range of blocks that has not been reported yet. The range
+	/// is only marked as reported by [`Filters::advance`], once it has been served.
+	pub fn poll(
+		&self,
+		id: &U256,
+		latest_block: SubstrateBlockNumber,
+	) -> Option<(FilterKind, SubstrateBlockNumber, SubstrateBlockNumber)> {
+		let mut inner = self.inner.lock().expect("lock is not poisoned; qed");
+		inner.prune_expired();
+		let filter = inner.filters.get_mut(id)?;
+		let from = filter.last_block.saturating_add(1);
+		filter.last_poll = Instant::now();
+		Some((filter.kind.clone(), from, latest_block))
+	}
+
+	/// Mark the blocks up to the given block as reported by the filter with the given id.
+	pub fn advance(&self, id: &U256, reported_block: SubstrateBlockNumber) {
+		let mut inner = self.inner.lock().expect("lock is not poisoned; qed");
+		if let Some(filter) = inner.filters.get_mut(id) {
+			filter.last_block = filter.last_block.max(reported_block);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn poll_reports_the_range_until_it_is_advanced() {
+		let filters = Filters::default();
+		let id = filters.install(FilterKind::Blocks, 10).unwrap();
+
+		let (_, from, to) = filters.poll(&id, 15).unwrap();
+		assert_eq!((from, to), (11, 15));
+
+		// The range was not served, so it is reported again.
+		let (_, from, to) = filters.poll(&id, 20).unwrap();
+		assert_eq!((from, to), (11, 20));
+
+		filters.advance(&id, 20);
+		let (_, from, to) = filters.poll(&id, 20).unwrap();
+		assert_eq!((from, to), (21, 20));
+	}
+}
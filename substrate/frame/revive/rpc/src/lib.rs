@@ -18,7 +18,8 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 use crate::runtime::GAS_PRICE;
-use client::ClientError;
+use client::{ClientError, SubstrateBlockNumber};
+use filters::{FilterKind, Filters};
 use jsonrpsee::{
 	core::{async_trait, RpcResult},
 	types::{ErrorCode, ErrorObjectOwned},
@@ -30,19 +31,32 @@ use thiserror::Error;
 pub mod cli;
 pub mod client;
 pub mod example;
+mod filters;
 pub mod subxt_client;
 
 #[cfg(test)]
 mod tests;
 
+mod rpc_debug;
+pub use rpc_debug::*;
+
 mod rpc_health;
 pub use rpc_health::*;
 
 mod rpc_methods_gen;
 pub use rpc_methods_gen::*;
 
+mod rpc_pubsub;
+pub use rpc_pubsub::*;
+
 pub const LOG_TARGET: &str = "eth-rpc";
 
+/// The number of recent blocks considered by `eth_maxPriorityFeePerGas`.
+const MAX_PRIORITY_FEE_BLOCK_COUNT: SubstrateBlockNumber = 20;
+
+/// The percentile of the rewards of each block considered by `eth_maxPriorityFeePerGas`.
+const MAX_PRIORITY_FEE_PERCENTILE: f64 = 60.0;
+
 /// An EVM RPC server implementation.
 pub struct EthRpcServerImpl {
 	/// The client used to interact with the substrate node.
@@ -50,12 +64,15 @@ pub struct EthRpcServerImpl {
 
 	/// The accounts managed by the server.
 	accounts: Vec<Account>,
+
+	/// The filters installed with `eth_newFilter` and `eth_newBlockFilter`.
+	filters: Filters,
 }
 
 impl EthRpcServerImpl {
 	/// Creates a new [`EthRpcServerImpl`].
 	pub fn new(client: client::Client) -> Self {
-		Self { client, accounts: vec![] }
+		Self { client, accounts: vec![], filters: Default::default() }
 	}
 
 	/// Sets the accounts managed by the server.
@@ -89,6 +106,15 @@ pub enum EthRpcError {
 	/// Received an invalid transaction
 	#[error("Invalid transaction {0:?}")]
 	TransactionTypeNotSupported(Byte),
+	/// The filter was not found, or has expired.
+	#[error("Filter not found")]
+	FilterNotFound(U256),
+	/// The maximum number of installed filters is reached.
+	#[error("Too many filters")]
+	TooManyFilters,
+	/// The reward percentiles are not monotonically increasing values within [0, 100].
+	#[error("Invalid reward percentiles")]
+	InvalidRewardPercentiles,
 }
 
 // TODO use https://eips.ethereum.org/EIPS/eip-1474#error-codes
@@ -134,6 +160,40 @@ impl EthRpcServer for EthRpcServerImpl {
 		Ok(dry_run.eth_gas)
 	}
 
+	async fn fee_history(
+		&self,
+		block_count: U256,
+		newest_block: BlockNumberOrTag,
+		reward_percentiles: Option<Vec<f64>>,
+	) -> RpcResult<FeeHistoryResult> {
+		let block_count: SubstrateBlockNumber =
+			block_count.try_into().map_err(|_| EthRpcError::ConversionError)?;
+		let reward_percentiles = reward_percentiles.unwrap_or_default();
+		let is_valid = reward_percentiles.iter().all(|p| (0.0..=100.0).contains(p)) &&
+			reward_percentiles.windows(2).all(|w| w[0] <= w[1]);
+		if !is_valid {
+			return Err(EthRpcError::InvalidRewardPercentiles.into());
+		}
+
+		Ok(self.client.fee_history(block_count, &newest_block, &reward_percentiles).await?)
+	}
+
+	async fn max_priority_fee_per_gas(&self) -> RpcResult<U256> {
+		let history = self
+			.client
+			.fee_history(
+				MAX_PRIORITY_FEE_BLOCK_COUNT,
+				&BlockTag::Latest.into(),
+				&[MAX_PRIORITY_FEE_PERCENTILE],
+			)
+			.await?;
+
+		// Use the median of the rewards of the recent blocks.
+		let mut rewards = history.reward.into_iter().flatten().collect::<Vec<_>>();
+		rewards.sort();
+		Ok(rewards.get(rewards.len() / 2).copied().unwrap_or_default())
+	}
+
 	async fn call(
 		&self,
 		transaction: GenericTransaction,
@@ -347,4 +407,87 @@ impl EthRpcServer for EthRpcServerImpl {
 		let nonce = self.client.nonce(address, block).await?;
 		Ok(nonce)
 	}
+
+	async fn get_logs(&self, filter: Option<Filter>) -> RpcResult<FilterResults> {
+		let logs = self.client.logs(&filter.unwrap_or_default()).await?;
+		Ok(FilterResults::Logs(logs))
+	}
+
+	async fn new_filter(&self, filter: Filter) -> RpcResult<U256> {
+		let current_block = self.client.block_number().await?;
+		let id = self
+			.filters
+			.install(FilterKind::Logs(filter), current_block)
+			.ok_or(EthRpcError::TooManyFilters)?;
+		Ok(id)
+	}
+
+	async fn new_block_filter(&self) -> RpcResult<U256> {
+		let current_block = self.client.block_number().await?;
+		let id = self
+			.filters
+			.install(FilterKind::Blocks, current_block)
+			.ok_or(EthRpcError::TooManyFilters)?;
+		Ok(id)
+	}
+
+	async fn uninstall_filter(&self, filter_id: U256) -> RpcResult<bool> {
+		Ok(self.filters.uninstall(&filter_id))
+	}
+
+	async fn get_filter_changes(&self, filter_id: U256) -> RpcResult<FilterResults> {
+		let latest_block = self.client.block_number().await?;
+		let (kind, from, to) = self
+			.filters
+			.poll(&filter_id, latest_block)
+			.ok_or(EthRpcError::FilterNotFound(filter_id))?;
+
+		let results = match kind {
+			FilterKind::Blocks => {
+				let mut hashes = Vec::new();
+				for n in from..=to {
+					if let Some(hash) = self.client.get_block_hash(n).await? {
+						hashes.push(hash);
+					}
+				}
+				FilterResults::Hashes(hashes)
+			},
+			FilterKind::Logs(filter) => {
+				// Only report the blocks that are within the bounds of the filter.
+				let to_block_number = |block: &Option<BlockNumberOrTag>| match block {
+					Some(BlockNumberOrTag::U256(n)) => Some(
+						SubstrateBlockNumber::try_from(*n).unwrap_or(SubstrateBlockNumber::MAX),
+					),
+					_ => None,
+				};
+				let from = to_block_number(&filter.from_block).map_or(from, |n| from.max(n));
+				let to = to_block_number(&filter.to_block).map_or(to, |n| to.min(n));
+				if from > to {
+					FilterResults::Logs(vec![])
+				} else {
+					let filter = Filter {
+						from_block: Some(U256::from(from).into()),
+						to_block: Some(U256::from(to).into()),
+						..filter
+					};
+					FilterResults::Logs(self.client.logs(&filter).await?)
+				}
+			},
+		};
+
+		// Only move the cursor once the changes were collected, so that a failed poll can be
+		// retried without missing any block.
+		self.filters.advance(&filter_id, to);
+		Ok(results)
+	}
+
+	async fn get_filter_logs(&self, filter_id: U256) -> RpcResult<FilterResults> {
+		match self.filters.get(&filter_id) {
+			Some(FilterKind::Logs(filter)) => {
+				let logs = self.client.logs(&filter).await?;
+				Ok(FilterResults::Logs(logs))
+			},
+			Some(FilterKind::Blocks) | None => Err(EthRpcError::FilterNotFound(filter_id).into()),
+		}
+	}
 }
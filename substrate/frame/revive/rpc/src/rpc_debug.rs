@@ -0,0 +1,106 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Debug JSON-RPC methods.
+
+use super::*;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+#[rpc(server, client)]
+pub trait DebugRpc {
+	/// Returns the tracing of the execution of a specific block using its number.
+	///
+	/// ## References
+	///
+	/// - <https://geth.ethereum.org/docs/interacting-with-geth/rpc/ns-debug#debugtraceblockbynumber>
+	#[method(name = "debug_traceBlockByNumber")]
+	async fn trace_block_by_number(
+		&self,
+		block: BlockNumberOrTag,
+		tracer_config: Option<TracerConfig>,
+	) -> RpcResult<Vec<TransactionTrace>>;
+
+	/// Returns a transaction's traces by replaying it.
+	///
+	/// ## References
+	///
+	/// - <https://geth.ethereum.org/docs/interacting-with-geth/rpc/ns-debug#debugtracetransaction>
+	#[method(name = "debug_traceTransaction")]
+	async fn trace_transaction(
+		&self,
+		transaction_hash: H256,
+		tracer_config: Option<TracerConfig>,
+	) -> RpcResult<Trace>;
+
+	/// Dry run a call and returns the transaction's traces.
+	///
+	/// ## References
+	///
+	/// - <https://geth.ethereum.org/docs/interacting-with-geth/rpc/ns-debug#debugtracecall>
+	#[method(name = "debug_traceCall")]
+	async fn trace_call(
+		&self,
+		transaction: GenericTransaction,
+		block: BlockNumberOrTagOrHash,
+		tracer_config: Option<TracerConfig>,
+	) -> RpcResult<Trace>;
+}
+
+pub struct DebugRpcServerImpl {
+	client: client::Client,
+}
+
+impl DebugRpcServerImpl {
+	pub fn new(client: client::Client) -> Self {
+		Self { client }
+	}
+}
+
+#[async_trait]
+impl DebugRpcServer for DebugRpcServerImpl {
+	async fn trace_block_by_number(
+		&self,
+		block: BlockNumberOrTag,
+		tracer_config: Option<TracerConfig>,
+	) -> RpcResult<Vec<TransactionTrace>> {
+		let block = self
+			.client
+			.block_by_number_or_tag(&block)
+			.await?
+			.ok_or(ClientError::BlockNotFound)?;
+		let tracer_type = tracer_config.unwrap_or_default().tracer_type;
+		Ok(self.client.trace_block(&block, tracer_type).await?)
+	}
+
+	async fn trace_transaction(
+		&self,
+		transaction_hash: H256,
+		tracer_config: Option<TracerConfig>,
+	) -> RpcResult<Trace> {
+		let tracer_type = tracer_config.unwrap_or_default().tracer_type;
+		Ok(self.client.trace_transaction(transaction_hash, tracer_type).await?)
+	}
+
+	async fn trace_call(
+		&self,
+		transaction: GenericTransaction,
+		block: BlockNumberOrTagOrHash,
+		tracer_config: Option<TracerConfig>,
+	) -> RpcResult<Trace> {
+		let tracer_type = tracer_config.unwrap_or_default().tracer_type;
+		Ok(self.client.trace_call(transaction, block, tracer_type).await?)
+	}
+}
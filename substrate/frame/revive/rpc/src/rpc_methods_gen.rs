@@ -52,6 +52,17 @@ pub trait EthRpc {
 		block: Option<BlockNumberOrTag>,
 	) -> RpcResult<U256>;
 
+	/// Transaction fee history
+	/// Returns transaction base fee per gas and effective priority fee per gas for the
+	/// requested/supported block range.
+	#[method(name = "eth_feeHistory")]
+	async fn fee_history(
+		&self,
+		block_count: U256,
+		newest_block: BlockNumberOrTag,
+		reward_percentiles: Option<Vec<f64>>,
+	) -> RpcResult<FeeHistoryResult>;
+
 	/// Returns the current price per gas in wei.
 	#[method(name = "eth_gasPrice")]
 	async fn gas_price(&self) -> RpcResult<U256>;
@@ -95,6 +106,19 @@ pub trait EthRpc {
 	#[method(name = "eth_getCode")]
 	async fn get_code(&self, address: Address, block: BlockNumberOrTagOrHash) -> RpcResult<Bytes>;
 
+	/// Polling method for a filter, which returns an array of logs or block hashes which occurred
+	/// since last poll.
+	#[method(name = "eth_getFilterChanges")]
+	async fn get_filter_changes(&self, filter_id: U256) -> RpcResult<FilterResults>;
+
+	/// Returns an array of all logs matching filter with given id.
+	#[method(name = "eth_getFilterLogs")]
+	async fn get_filter_logs(&self, filter_id: U256) -> RpcResult<FilterResults>;
+
+	/// Returns an array of all logs matching a given filter object.
+	#[method(name = "eth_getLogs")]
+	async fn get_logs(&self, filter: Option<Filter>) -> RpcResult<FilterResults>;
+
 	/// Returns the value from a storage position at a given address.
 	#[method(name = "eth_getStorageAt")]
 	async fn get_storage_at(
@@ -142,6 +166,18 @@ pub trait EthRpc {
 		transaction_hash: H256,
 	) -> RpcResult<Option<ReceiptInfo>>;
 
+	/// Returns the current maxPriorityFeePerGas per gas in wei.
+	#[method(name = "eth_maxPriorityFeePerGas")]
+	async fn max_priority_fee_per_gas(&self) -> RpcResult<U256>;
+
+	/// Creates a filter in the node, to notify when a new block arrives.
+	#[method(name = "eth_newBlockFilter")]
+	async fn new_block_filter(&self) -> RpcResult<U256>;
+
+	/// Creates a filter object, based on filter options, to notify when the state changes (logs).
+	#[method(name = "eth_newFilter")]
+	async fn new_filter(&self, filter: Filter) -> RpcResult<U256>;
+
 	/// Submits a raw transaction. For EIP-4844 transactions, the raw form must be the network form.
 	/// This means it includes the blobs, KZG commitments, and KZG proofs.
 	#[method(name = "eth_sendRawTransaction")]
@@ -155,6 +191,10 @@ pub trait EthRpc {
 	#[method(name = "eth_syncing")]
 	async fn syncing(&self) -> RpcResult<SyncingStatus>;
 
+	/// Uninstalls a filter with given id.
+	#[method(name = "eth_uninstallFilter")]
+	async fn uninstall_filter(&self, filter_id: U256) -> RpcResult<bool>;
+
 	/// The string value of current network id
 	#[method(name = "net_version")]
 	async fn net_version(&self) -> RpcResult<String>;
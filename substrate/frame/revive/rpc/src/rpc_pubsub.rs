@@ -0,0 +1,151 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Pub-sub JSON-RPC methods.
+
+use super::*;
+use crate::client::SubstrateBlock;
+use futures::{stream, StreamExt};
+use jsonrpsee::{proc_macros::rpc, PendingSubscriptionSink};
+use sc_rpc::{
+	utils::{BoundedVecDeque, PendingSubscription},
+	SubscriptionTaskExecutor,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// The kind of an `eth_subscribe` subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SubscriptionKind {
+	/// Notify about the new blocks appended to the chain.
+	NewHeads,
+	/// Notify about the logs included in new blocks that match the given filter.
+	Logs,
+}
+
+/// An item sent to an `eth_subscribe` subscriber.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SubscriptionItem {
+	/// A new block header.
+	Header(Block),
+	/// A log matching the subscription filter.
+	Log(Log),
+}
+
+#[rpc(server, client)]
+pub trait EthPubSub {
+	/// Subscribe to new block headers, or to logs matching a filter.
+	#[subscription(
+		name = "eth_subscribe" => "eth_subscription",
+		unsubscribe = "eth_unsubscribe",
+		item = SubscriptionItem
+	)]
+	fn subscribe(&self, kind: SubscriptionKind, filter: Option<Filter>);
+}
+
+pub struct EthPubSubServerImpl {
+	client: client::Client,
+	executor: SubscriptionTaskExecutor,
+}
+
+impl EthPubSubServerImpl {
+	pub fn new(client: client::Client, executor: SubscriptionTaskExecutor) -> Self {
+		Self { client, executor }
+	}
+}
+
+/// Turn a receiver of new blocks into a stream, skipping the blocks missed by slow subscribers.
+fn new_blocks_stream(
+	receiver: broadcast::Receiver<Arc<SubstrateBlock>>,
+) -> impl futures::Stream<Item = Arc<SubstrateBlock>> {
+	stream::unfold(receiver, |mut receiver| async move {
+		loop {
+			match receiver.recv().await {
+				Ok(block) => return Some((block, receiver)),
+				Err(RecvError::Lagged(skipped)) => {
+					log::debug!(target: LOG_TARGET, "Subscriber lagged behind, skipped {skipped} blocks");
+				},
+				Err(RecvError::Closed) => return None,
+			}
+		}
+	})
+}
+
+impl EthPubSubServer for EthPubSubServerImpl {
+	fn subscribe(
+		&self,
+		pending: PendingSubscriptionSink,
+		kind: SubscriptionKind,
+		filter: Option<Filter>,
+	) {
+		let client = self.client.clone();
+		let blocks = new_blocks_stream(client.subscribe_new_blocks());
+
+		let stream = match kind {
+			SubscriptionKind::NewHeads => blocks
+				.filter_map(move |block| {
+					let client = client.clone();
+					async move {
+						client
+							.evm_block(block)
+							.await
+							.inspect_err(|err| {
+								log::debug!(target: LOG_TARGET, "Failed to get evm block: {err:?}");
+							})
+							.ok()
+							.map(SubscriptionItem::Header)
+					}
+				})
+				.boxed(),
+			SubscriptionKind::Logs => {
+				let filter = filter.unwrap_or_default();
+				blocks
+					.then(move |block| {
+						let client = client.clone();
+						async move {
+							client
+								.block_logs(&block)
+								.await
+								.inspect_err(|err| {
+									log::debug!(target: LOG_TARGET, "Failed to get logs: {err:?}");
+								})
+								.unwrap_or_default()
+						}
+					})
+					.flat_map(move |logs| {
+						let logs = logs
+							.into_iter()
+							.filter(|log| filter.matches(log))
+							.map(SubscriptionItem::Log)
+							.collect::<Vec<_>>();
+						stream::iter(logs)
+					})
+					.boxed()
+			},
+		};
+
+		let fut = async move {
+			PendingSubscription::from(pending)
+				.pipe_from_stream(stream, BoundedVecDeque::default())
+				.await;
+		};
+
+		sc_rpc::utils::spawn_subscription_task(&self.executor, fut);
+	}
+}
@@ -19,14 +19,17 @@
 use crate::{
 	cli::{self, CliCommand},
 	example::{wait_for_successful_receipt, TransactionBuilder},
-	EthRpcClient,
+	DebugRpcClient, EthPubSubClient, EthRpcClient, SubscriptionItem, SubscriptionKind,
 };
 use clap::Parser;
 use ethabi::Token;
 use jsonrpsee::ws_client::{WsClient, WsClientBuilder};
 use pallet_revive::{
 	create1,
-	evm::{Account, BlockTag, U256},
+	evm::{
+		Account, BlockTag, CallTracerConfig, Filter, FilterResults, Trace, TracerConfig,
+		TracerType, H256, U256,
+	},
 };
 use static_init::dynamic;
 use std::thread;
@@ -260,6 +263,168 @@ async fn event_logs() -> anyhow::Result<()> {
 	Ok(())
 }
 
+#[tokio::test]
+async fn get_logs_and_filters() -> anyhow::Result<()> {
+	let _lock = SHARED_RESOURCES.write();
+	let client = SharedResources::client().await;
+	let (bytecode, contract) = get_contract("EventExample")?;
+	let contract_address = TransactionBuilder::default()
+		.input(bytecode)
+		.send_and_wait_for_receipt(&client)
+		.await?
+		.contract_address
+		.unwrap();
+
+	let filter = Filter { address: Some(contract_address.into()), ..Default::default() };
+	let filter_id = client.new_filter(filter.clone()).await?;
+	let block_filter_id = client.new_block_filter().await?;
+
+	let receipt = TransactionBuilder::default()
+		.to(contract_address)
+		.input(contract.function("triggerEvent")?.encode_input(&[])?.to_vec())
+		.send_and_wait_for_receipt(&client)
+		.await?;
+
+	let filter = Filter {
+		from_block: Some(receipt.block_number.into()),
+		to_block: Some(receipt.block_number.into()),
+		..filter
+	};
+	let FilterResults::Logs(logs) = client.get_logs(Some(filter.clone())).await? else {
+		panic!("Expected logs");
+	};
+	assert_eq!(logs, receipt.logs, "eth_getLogs should return the receipt logs.");
+
+	let FilterResults::Logs(logs) = client.get_filter_changes(filter_id).await? else {
+		panic!("Expected logs");
+	};
+	assert_eq!(logs, receipt.logs, "The filter should report the new logs.");
+
+	let FilterResults::Hashes(hashes) = client.get_filter_changes(block_filter_id).await? else {
+		panic!("Expected block hashes");
+	};
+	assert!(hashes.contains(&receipt.block_hash), "The filter should report the new block.");
+
+	let future_block = receipt.block_number + U256::from(1_000u32);
+	let future_filter =
+		Filter { from_block: Some(future_block.into()), to_block: None, ..filter.clone() };
+	let FilterResults::Logs(logs) = client.get_logs(Some(future_filter)).await? else {
+		panic!("Expected logs");
+	};
+	assert!(logs.is_empty(), "No log should match blocks past the latest one.");
+
+	let topic = H256::from([0xffu8; 32]);
+	let filter = Filter { topics: vec![Some(topic.into())], ..filter };
+	let FilterResults::Logs(logs) = client.get_logs(Some(filter)).await? else {
+		panic!("Expected logs");
+	};
+	assert!(logs.is_empty(), "No log should match an unknown topic.");
+
+	assert!(client.uninstall_filter(filter_id).await?);
+	assert!(!client.uninstall_filter(filter_id).await?);
+	assert!(client.get_filter_changes(filter_id).await.is_err());
+	Ok(())
+}
+
+#[tokio::test]
+async fn subscribe_logs() -> anyhow::Result<()> {
+	let _lock = SHARED_RESOURCES.write();
+	let client = SharedResources::client().await;
+	let (bytecode, contract) = get_contract("EventExample")?;
+	let contract_address = TransactionBuilder::default()
+		.input(bytecode)
+		.send_and_wait_for_receipt(&client)
+		.await?
+		.contract_address
+		.unwrap();
+
+	let filter = Filter { address: Some(contract_address.into()), ..Default::default() };
+	let mut sub = EthPubSubClient::subscribe(&client, SubscriptionKind::Logs, Some(filter)).await?;
+
+	let receipt = TransactionBuilder::default()
+		.to(contract_address)
+		.input(contract.function("triggerEvent")?.encode_input(&[])?.to_vec())
+		.send_and_wait_for_receipt(&client)
+		.await?;
+
+	let SubscriptionItem::Log(log) = sub.next().await.expect("subscription is alive")? else {
+		panic!("Expected a log");
+	};
+	assert_eq!(log, receipt.logs[0]);
+	Ok(())
+}
+
+#[tokio::test]
+async fn trace_transaction() -> anyhow::Result<()> {
+	let _lock = SHARED_RESOURCES.write();
+	let client = SharedResources::client().await;
+	let (bytecode, contract) = get_contract("EventExample")?;
+	let contract_address = TransactionBuilder::default()
+		.input(bytecode)
+		.send_and_wait_for_receipt(&client)
+		.await?
+		.contract_address
+		.unwrap();
+
+	let receipt = TransactionBuilder::default()
+		.to(contract_address)
+		.input(contract.function("triggerEvent")?.encode_input(&[])?.to_vec())
+		.send_and_wait_for_receipt(&client)
+		.await?;
+
+	let config = TracerConfig {
+		tracer_type: TracerType::CallTracer(Some(CallTracerConfig {
+			with_log: true,
+			only_top_call: false,
+		})),
+	};
+	let Trace::Call(trace) =
+		client.trace_transaction(receipt.transaction_hash, Some(config)).await?
+	else {
+		panic!("Expected a call trace");
+	};
+
+	assert_eq!(trace.from, receipt.from);
+	assert_eq!(trace.to, contract_address);
+	assert!(trace.error.is_none());
+	assert_eq!(trace.logs.len(), receipt.logs.len(), "The trace should include the logs.");
+	Ok(())
+}
+
+#[tokio::test]
+async fn fee_history() -> anyhow::Result<()> {
+	let _lock = SHARED_RESOURCES.write();
+	let client = SharedResources::client().await;
+	let receipt = TransactionBuilder::default()
+		.value(U256::from(1_000_000_000_000u128))
+		.to(Account::from(subxt_signer::eth::dev::ethan()).address())
+		.send_and_wait_for_receipt(&client)
+		.await?;
+
+	let history = client
+		.fee_history(U256::from(4), receipt.block_number.into(), Some(vec![25.0, 75.0]))
+		.await?;
+	let block_count = history.gas_used_ratio.len();
+	assert!(block_count > 0 && block_count <= 4);
+	assert_eq!(history.oldest_block + block_count - 1, receipt.block_number);
+	assert!(history.gas_used_ratio.iter().all(|ratio| (0.0..=1.0).contains(ratio)));
+	assert!(history.gas_used_ratio[block_count - 1] > 0.0, "The block includes the transfer.");
+
+	// The base fee is the price reported by `eth_gasPrice`, which the transfer paid without tip.
+	let gas_price = client.gas_price().await?;
+	assert_eq!(history.base_fee_per_gas, vec![gas_price; block_count + 1]);
+	assert_eq!(history.reward[block_count - 1], vec![U256::zero(); 2]);
+	assert_eq!(history.reward.len(), block_count);
+
+	assert!(client
+		.fee_history(U256::from(4), receipt.block_number.into(), Some(vec![75.0, 25.0]))
+		.await
+		.is_err());
+
+	assert_eq!(client.max_priority_fee_per_gas().await?, U256::zero());
+	Ok(())
+}
+
 #[tokio::test]
 async fn invalid_transaction() -> anyhow::Result<()> {
 	let _lock = SHARED_RESOURCES.write();
@@ -20,7 +20,10 @@ pub use crate::{
 	primitives::ExecReturnValue,
 };
 use crate::{Config, LOG_TARGET};
-use sp_core::H160;
+use alloc::vec::Vec;
+use frame_support::weights::Weight;
+use sp_core::{H160, H256, U256};
+use sp_runtime::DispatchError;
 
 /// Umbrella trait for all interfaces that serves for debugging.
 pub trait Debugger<T: Config>: Tracing<T> + CallInterceptor<T> {}
@@ -107,3 +110,68 @@ impl<T: Config> CallInterceptor<T> for () {
 		None
 	}
 }
+
+/// Observes the execution of a transaction, frame by frame.
+///
+/// Unlike [`Tracing`], which is configured statically through [`Config::Debug`], a [`Tracer`] is
+/// set for the duration of a single execution with [`trace`]. This is used by the runtime APIs
+/// that re-execute transactions to produce the output of the Ethereum `debug_trace*` methods.
+pub trait Tracer {
+	/// Called before a new call frame is executed.
+	///
+	/// # Arguments
+	///
+	/// * `from` - The address of the caller.
+	/// * `to` - The address of the callee, or of the executed code for a delegate call.
+	/// * `is_delegate_call` - Whether the frame was spawned by a delegate call.
+	/// * `is_read_only` - Whether the frame is not allowed to modify the state.
+	/// * `entry_point` - Describes whether the call is the constructor or a regular call.
+	/// * `value` - The EVM value transferred with the call.
+	/// * `input` - The raw input data of the call.
+	/// * `gas_limit` - The gas available to the call.
+	fn enter_child_span(
+		&mut self,
+		from: H160,
+		to: H160,
+		is_delegate_call: bool,
+		is_read_only: bool,
+		entry_point: ExportedFunction,
+		value: U256,
+		input: &[u8],
+		gas_limit: Weight,
+	);
+
+	/// Called after a call frame returned, whether it reverted or not.
+	fn exit_child_span(&mut self, output: &ExecReturnValue, gas_used: Weight);
+
+	/// Called after a call frame failed with an error.
+	fn exit_child_span_with_error(&mut self, error: DispatchError, gas_used: Weight);
+
+	/// Called when the executing contract reads a fixed sized storage key.
+	fn storage_read(&mut self, _address: &H160, _key: &[u8; 32], _value: Option<&[u8]>) {}
+
+	/// Called when the executing contract writes a fixed sized storage key.
+	fn storage_write(
+		&mut self,
+		_address: &H160,
+		_key: &[u8; 32],
+		_old_value: Option<Vec<u8>>,
+		_new_value: Option<&[u8]>,
+	) {
+	}
+
+	/// Called when the executing contract emits an event.
+	fn log_event(&mut self, _address: H160, _topics: &[H256], _data: &[u8]) {}
+}
+
+environmental::environmental!(tracer: dyn Tracer + 'static);
+
+/// Run the closure `f` with the given `tracer` observing the contract executions it performs.
+pub fn trace<R, F: FnOnce() -> R>(tracer: &mut (dyn Tracer + 'static), f: F) -> R {
+	tracer::using_once(tracer, f)
+}
+
+/// Run the closure `f` with the current tracer, if any.
+pub(crate) fn if_tracing<F: FnOnce(&mut (dyn Tracer + 'static))>(f: F) {
+	tracer::with(f);
+}
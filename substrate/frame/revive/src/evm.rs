@@ -20,3 +20,4 @@
 mod api;
 pub use api::*;
 pub mod runtime;
+pub mod tracing;
@@ -29,6 +29,15 @@ mod rpc_types;
 mod rpc_types_gen;
 pub use rpc_types_gen::*;
 
+mod debug_rpc_types;
+pub use debug_rpc_types::*;
+
+mod fee_rpc_types;
+pub use fee_rpc_types::*;
+
+mod filter_rpc_types;
+pub use filter_rpc_types::*;
+
 #[cfg(feature = "std")]
 mod account;
 
@@ -133,6 +133,13 @@ impl_hex!(Bytes, Vec<u8>, vec![]);
 impl_hex!(Bytes8, [u8; 8], [0u8; 8]);
 impl_hex!(Bytes256, [u8; 256], [0u8; 256]);
 
+impl Bytes {
+	/// Returns `true` if the byte array is empty.
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+}
+
 #[test]
 fn serialize_works() {
 	let a = Byte(42);
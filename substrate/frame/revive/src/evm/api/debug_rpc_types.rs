@@ -0,0 +1,234 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Types used by the `debug_*` JSON-RPC methods, compatible with the Geth built-in tracers.
+
+use super::{Bytes, H160, H256, U256};
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+
+/// The type of tracer to use, along with its configuration.
+#[derive(TypeInfo, Debug, Clone, Encode, Decode, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "tracer", content = "tracerConfig", rename_all = "camelCase")]
+pub enum TracerType {
+	/// A tracer that captures the call frames of a transaction.
+	CallTracer(Option<CallTracerConfig>),
+	/// A tracer that captures the accounts touched by a transaction.
+	PrestateTracer(Option<PrestateTracerConfig>),
+}
+
+impl Default for TracerType {
+	fn default() -> Self {
+		TracerType::CallTracer(None)
+	}
+}
+
+/// The configuration of the `callTracer`.
+#[derive(TypeInfo, Debug, Clone, Default, Encode, Decode, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CallTracerConfig {
+	/// Whether to include the logs emitted by each call frame.
+	pub with_log: bool,
+	/// Whether to only trace the top-level call.
+	pub only_top_call: bool,
+}
+
+/// The configuration of the `prestateTracer`.
+#[derive(TypeInfo, Debug, Clone, Default, Encode, Decode, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PrestateTracerConfig {
+	/// Whether to return the state both before and after the execution.
+	pub diff_mode: bool,
+}
+
+/// The options of the `debug_traceTransaction` and `debug_traceCall` methods.
+#[derive(TypeInfo, Debug, Clone, Default, Encode, Decode, Serialize, Deserialize, PartialEq)]
+pub struct TracerConfig {
+	/// The tracer to use.
+	#[serde(flatten)]
+	pub tracer_type: TracerType,
+}
+
+/// The type of a call frame.
+#[derive(
+	TypeInfo, Default, Encode, Decode, Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq,
+)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum CallType {
+	/// A regular call.
+	#[default]
+	Call,
+	/// A read-only call.
+	StaticCall,
+	/// A delegate call.
+	DelegateCall,
+	/// A contract creation.
+	Create,
+}
+
+/// A log emitted by a call frame.
+#[derive(
+	TypeInfo, Default, Encode, Decode, Serialize, Deserialize, Clone, Debug, Eq, PartialEq,
+)]
+pub struct CallLog {
+	/// The address of the contract that emitted the log.
+	pub address: H160,
+	/// The topics of the log.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub topics: Vec<H256>,
+	/// The data of the log.
+	pub data: Bytes,
+	/// The position of the log relative to the nested calls of the frame.
+	pub position: U256,
+}
+
+/// A call frame, as reported by the `callTracer`.
+#[derive(
+	TypeInfo, Default, Encode, Decode, Serialize, Deserialize, Clone, Debug, Eq, PartialEq,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct CallTrace {
+	/// The address of the caller.
+	pub from: H160,
+	/// The gas provided to the call.
+	pub gas: U256,
+	/// The gas used by the call.
+	pub gas_used: U256,
+	/// The address of the callee.
+	pub to: H160,
+	/// The input data of the call.
+	pub input: Bytes,
+	/// The output data of the call.
+	#[serde(default, skip_serializing_if = "Bytes::is_empty")]
+	pub output: Bytes,
+	/// The error message, if the call failed.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// The nested calls.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub calls: Vec<CallTrace>,
+	/// The logs emitted by the call, only reported if `withLog` is set.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub logs: Vec<CallLog>,
+	/// The value transferred by the call.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub value: Option<U256>,
+	/// The type of call.
+	#[serde(rename = "type")]
+	pub call_type: CallType,
+}
+
+/// The state of an account, as reported by the `prestateTracer`.
+#[derive(
+	TypeInfo, Default, Encode, Decode, Serialize, Deserialize, Clone, Debug, Eq, PartialEq,
+)]
+pub struct PrestateTraceInfo {
+	/// The balance of the account.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub balance: Option<U256>,
+	/// The nonce of the account.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub nonce: Option<u32>,
+	/// The code of the account.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub code: Option<Bytes>,
+	/// The storage slots accessed by the transaction, `None` if a slot is empty.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub storage: BTreeMap<H256, Option<Bytes>>,
+}
+
+/// The output of the `prestateTracer`.
+#[derive(TypeInfo, Encode, Decode, Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum PrestateTrace {
+	/// The state of the touched accounts before the execution.
+	Prestate(BTreeMap<H160, PrestateTraceInfo>),
+	/// The state of the modified accounts before and after the execution.
+	DiffMode {
+		/// The state before the execution.
+		pre: BTreeMap<H160, PrestateTraceInfo>,
+		/// The state after the execution.
+		post: BTreeMap<H160, PrestateTraceInfo>,
+	},
+}
+
+impl Default for PrestateTrace {
+	fn default() -> Self {
+		PrestateTrace::Prestate(Default::default())
+	}
+}
+
+/// The trace of a transaction.
+#[derive(TypeInfo, Encode, Decode, Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum Trace {
+	/// A `callTracer` trace.
+	Call(CallTrace),
+	/// A `prestateTracer` trace.
+	Prestate(PrestateTrace),
+}
+
+/// The trace of a transaction in a block, as returned by `debug_traceBlockByNumber`.
+#[derive(TypeInfo, Encode, Decode, Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionTrace {
+	/// The hash of the transaction.
+	pub tx_hash: H256,
+	/// The trace of the transaction.
+	pub result: Trace,
+}
+
+#[test]
+fn tracer_config_serialization_works() {
+	let tests = [
+		(r#"{"tracer": "callTracer"}"#, TracerType::CallTracer(None)),
+		(
+			r#"{"tracer": "callTracer", "tracerConfig": { "withLog": true }}"#,
+			TracerType::CallTracer(Some(CallTracerConfig { with_log: true, only_top_call: false })),
+		),
+		(
+			r#"{"tracer": "prestateTracer", "tracerConfig": { "diffMode": true }}"#,
+			TracerType::PrestateTracer(Some(PrestateTracerConfig { diff_mode: true })),
+		),
+	];
+
+	for (json_data, expected) in tests {
+		let result: TracerConfig =
+			serde_json::from_str(json_data).expect("Deserialization should succeed");
+		assert_eq!(result.tracer_type, expected);
+	}
+}
+
+#[test]
+fn call_trace_serialization_works() {
+	let trace = CallTrace {
+		from: H160::from([1u8; 20]),
+		to: H160::from([2u8; 20]),
+		gas: 100u32.into(),
+		gas_used: 42u32.into(),
+		input: Bytes(vec![1, 2, 3]),
+		call_type: CallType::StaticCall,
+		..Default::default()
+	};
+
+	let json = serde_json::to_value(&trace).unwrap();
+	assert_eq!(json["type"], "STATICCALL");
+	assert_eq!(json["gasUsed"], "0x2a");
+	assert!(json.get("output").is_none());
+	assert!(json.get("calls").is_none());
+}
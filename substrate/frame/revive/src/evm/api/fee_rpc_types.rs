@@ -0,0 +1,42 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Types used by the `eth_feeHistory` JSON-RPC method.
+
+use super::U256;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// Fee history results
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FeeHistoryResult {
+	/// Lowest number block of returned range.
+	#[serde(rename = "oldestBlock")]
+	pub oldest_block: U256,
+	/// An array of block base fees per gas. This includes the next block after the newest of the
+	/// returned range, because this value can be derived from the newest block. Zeroes are
+	/// returned for pre-EIP-1559 blocks.
+	#[serde(rename = "baseFeePerGas")]
+	pub base_fee_per_gas: Vec<U256>,
+	/// An array of block gas used ratios. These are calculated as the ratio of gasUsed and
+	/// gasLimit.
+	#[serde(rename = "gasUsedRatio")]
+	pub gas_used_ratio: Vec<f64>,
+	/// A two-dimensional array of effective priority fees per gas at the requested block
+	/// percentiles.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub reward: Vec<Vec<U256>>,
+}
@@ -0,0 +1,104 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Types used by the `eth_newFilter`, `eth_getFilterChanges` and `eth_getLogs` JSON-RPC methods.
+
+use super::{Address, BlockNumberOrTag, Log, H256};
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use derive_more::{From, TryInto};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+
+/// Filter object
+#[derive(
+	Debug, Default, Clone, Encode, Decode, TypeInfo, Serialize, Deserialize, Eq, PartialEq,
+)]
+pub struct Filter {
+	/// Address(es)
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub address: Option<AddressOrAddresses>,
+	/// block hash
+	/// Restricts the logs returned to the single block with this hash. Mutually exclusive with
+	/// fromBlock and toBlock.
+	#[serde(rename = "blockHash", skip_serializing_if = "Option::is_none")]
+	pub block_hash: Option<H256>,
+	/// from block
+	#[serde(rename = "fromBlock", skip_serializing_if = "Option::is_none")]
+	pub from_block: Option<BlockNumberOrTag>,
+	/// to block
+	#[serde(rename = "toBlock", skip_serializing_if = "Option::is_none")]
+	pub to_block: Option<BlockNumberOrTag>,
+	/// Topics
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub topics: FilterTopics,
+}
+
+/// Filter results
+#[derive(
+	Debug, Clone, Encode, Decode, TypeInfo, Serialize, Deserialize, From, TryInto, Eq, PartialEq,
+)]
+#[serde(untagged)]
+pub enum FilterResults {
+	/// new block or transaction hashes
+	Hashes(Vec<H256>),
+	/// new logs
+	Logs(Vec<Log>),
+}
+impl Default for FilterResults {
+	fn default() -> Self {
+		FilterResults::Hashes(Default::default())
+	}
+}
+
+/// Address or addresses
+#[derive(
+	Debug, Clone, Encode, Decode, TypeInfo, Serialize, Deserialize, From, TryInto, Eq, PartialEq,
+)]
+#[serde(untagged)]
+pub enum AddressOrAddresses {
+	/// Address
+	Address(Address),
+	/// Addresses
+	Addresses(Vec<Address>),
+}
+impl Default for AddressOrAddresses {
+	fn default() -> Self {
+		AddressOrAddresses::Address(Default::default())
+	}
+}
+
+/// Filter Topics
+/// A `null` entry matches any topic at that position.
+pub type FilterTopics = Vec<Option<FilterTopic>>;
+
+/// Filter Topic
+/// A single topic, or a list of alternatives for a topic position.
+#[derive(
+	Debug, Clone, Encode, Decode, TypeInfo, Serialize, Deserialize, From, TryInto, Eq, PartialEq,
+)]
+#[serde(untagged)]
+pub enum FilterTopic {
+	/// Single topic
+	Single(H256),
+	/// Multiple topics
+	Multiple(Vec<H256>),
+}
+impl Default for FilterTopic {
+	fn default() -> Self {
+		FilterTopic::Single(Default::default())
+	}
+}
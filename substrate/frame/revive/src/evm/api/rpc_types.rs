@@ -161,6 +161,76 @@ fn logs_bloom_works() {
 	assert_eq!(receipt.logs_bloom, ReceiptInfo::logs_bloom(&receipt.logs));
 }
 
+impl AddressOrAddresses {
+	/// Returns `true` if the given address is part of the set.
+	pub fn contains(&self, address: &Address) -> bool {
+		match self {
+			AddressOrAddresses::Address(a) => a == address,
+			AddressOrAddresses::Addresses(addresses) =>
+				addresses.is_empty() || addresses.contains(address),
+		}
+	}
+}
+
+impl FilterTopic {
+	/// Returns `true` if the given topic matches this topic position.
+	pub fn contains(&self, topic: &H256) -> bool {
+		match self {
+			FilterTopic::Single(t) => t == topic,
+			FilterTopic::Multiple(topics) => topics.is_empty() || topics.contains(topic),
+		}
+	}
+}
+
+impl Filter {
+	/// Returns `true` if the given log matches the address and topics of the filter.
+	///
+	/// The block range of the filter is not checked here, it is up to the caller to only
+	/// provide logs from the requested blocks.
+	pub fn matches(&self, log: &Log) -> bool {
+		if let Some(address) = &self.address {
+			if !address.contains(&log.address) {
+				return false
+			}
+		}
+
+		self.topics.iter().enumerate().all(|(i, topic)| match topic {
+			None => true,
+			Some(topic) => log.topics.get(i).map_or(false, |t| topic.contains(t)),
+		})
+	}
+}
+
+#[test]
+fn filter_matches_works() {
+	let address = H160::from([1u8; 20]);
+	let topic_a = H256::from([0xau8; 32]);
+	let topic_b = H256::from([0xbu8; 32]);
+	let log = Log { address, topics: vec![topic_a, topic_b], ..Default::default() };
+
+	assert!(Filter::default().matches(&log));
+	assert!(Filter { address: Some(address.into()), ..Default::default() }.matches(&log));
+	assert!(!Filter { address: Some(H160::zero().into()), ..Default::default() }.matches(&log));
+	assert!(Filter { address: Some(vec![H160::zero(), address].into()), ..Default::default() }
+		.matches(&log));
+
+	// `null` matches any topic at a given position.
+	assert!(Filter { topics: vec![None, Some(topic_b.into())], ..Default::default() }.matches(&log));
+	assert!(!Filter { topics: vec![Some(topic_b.into())], ..Default::default() }.matches(&log));
+	assert!(Filter { topics: vec![Some(vec![topic_b, topic_a].into())], ..Default::default() }
+		.matches(&log));
+
+	// A log with fewer topics than the filter does not match.
+	assert!(!Filter { topics: vec![None, None, Some(topic_a.into())], ..Default::default() }
+		.matches(&log));
+
+	let filter: Filter = serde_json::from_str(
+		r#"{"address":"0x0101010101010101010101010101010101010101","topics":[null,["0x0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b"]]}"#,
+	)
+	.unwrap();
+	assert!(filter.matches(&log));
+}
+
 impl GenericTransaction {
 	/// Create a new [`GenericTransaction`] from a signed transaction.
 	pub fn from_signed(tx: TransactionSigned, from: Option<H160>) -> Self {
@@ -0,0 +1,430 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Implementations of the Geth built-in `callTracer` and `prestateTracer`.
+use crate::{
+	debug::{ExportedFunction, Tracer},
+	evm::{
+		runtime::GAS_PRICE, Bytes, CallLog, CallTrace, CallTracerConfig, CallType, PrestateTrace,
+		PrestateTraceInfo, PrestateTracerConfig, Trace, TracerType,
+	},
+	exec::Key,
+	primitives::ExecReturnValue,
+	AddressMapper, BalanceOf, Config, ContractInfoOf, MomentOf, Pallet, PristineCode,
+};
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::marker::PhantomData;
+use frame_support::{traits::IsType, weights::Weight};
+use sp_core::{H160, H256, U256};
+use sp_runtime::{
+	traits::{Bounded, Convert},
+	DispatchError, SaturatedConversion,
+};
+
+/// Convert the given weight to EVM gas, using the fixed [`GAS_PRICE`].
+fn weight_to_gas<T: Config>(weight: Weight) -> U256
+where
+	BalanceOf<T>: Into<U256>,
+{
+	let fee: U256 = T::WeightPrice::convert(weight).into();
+	fee / U256::from(GAS_PRICE)
+}
+
+/// A tracer producing the output of the given [`TracerType`].
+pub enum EvmTracer<T> {
+	/// A tracer producing a [`CallTrace`].
+	CallTracer(CallTracer<T>),
+	/// A tracer producing a [`PrestateTrace`].
+	PrestateTracer(PrestateTracer<T>),
+}
+
+impl<T: Config> EvmTracer<T>
+where
+	BalanceOf<T>: Into<U256> + TryFrom<U256> + Bounded,
+	MomentOf<T>: Into<U256>,
+	T::Hash: IsType<H256>,
+{
+	/// Create a new tracer for the given tracer type.
+	pub fn new(tracer_type: TracerType) -> Self {
+		match tracer_type {
+			TracerType::CallTracer(config) =>
+				Self::CallTracer(CallTracer::new(config.unwrap_or_default())),
+			TracerType::PrestateTracer(config) =>
+				Self::PrestateTracer(PrestateTracer::new(config.unwrap_or_default())),
+		}
+	}
+
+	/// Take the trace collected so far, resetting the tracer so it can trace another
+	/// transaction.
+	///
+	/// Returns `None` if nothing was traced.
+	pub fn collect_trace(&mut self) -> Option<Trace> {
+		match self {
+			Self::CallTracer(tracer) => tracer.collect_trace().map(Trace::Call),
+			Self::PrestateTracer(tracer) => tracer.collect_trace().map(Trace::Prestate),
+		}
+	}
+}
+
+impl<T: Config> Tracer for EvmTracer<T>
+where
+	BalanceOf<T>: Into<U256> + TryFrom<U256> + Bounded,
+	MomentOf<T>: Into<U256>,
+	T::Hash: IsType<H256>,
+{
+	fn enter_child_span(
+		&mut self,
+		from: H160,
+		to: H160,
+		is_delegate_call: bool,
+		is_read_only: bool,
+		entry_point: ExportedFunction,
+		value: U256,
+		input: &[u8],
+		gas_limit: Weight,
+	) {
+		match self {
+			Self::CallTracer(tracer) => tracer.enter_child_span(
+				from,
+				to,
+				is_delegate_call,
+				is_read_only,
+				entry_point,
+				value,
+				input,
+				gas_limit,
+			),
+			Self::PrestateTracer(tracer) => tracer.enter_child_span(
+				from,
+				to,
+				is_delegate_call,
+				is_read_only,
+				entry_point,
+				value,
+				input,
+				gas_limit,
+			),
+		}
+	}
+
+	fn exit_child_span(&mut self, output: &ExecReturnValue, gas_used: Weight) {
+		match self {
+			Self::CallTracer(tracer) => tracer.exit_child_span(output, gas_used),
+			Self::PrestateTracer(tracer) => tracer.exit_child_span(output, gas_used),
+		}
+	}
+
+	fn exit_child_span_with_error(&mut self, error: DispatchError, gas_used: Weight) {
+		match self {
+			Self::CallTracer(tracer) => tracer.exit_child_span_with_error(error, gas_used),
+			Self::PrestateTracer(tracer) => tracer.exit_child_span_with_error(error, gas_used),
+		}
+	}
+
+	fn storage_read(&mut self, address: &H160, key: &[u8; 32], value: Option<&[u8]>) {
+		match self {
+			Self::CallTracer(tracer) => tracer.storage_read(address, key, value),
+			Self::PrestateTracer(tracer) => tracer.storage_read(address, key, value),
+		}
+	}
+
+	fn storage_write(
+		&mut self,
+		address: &H160,
+		key: &[u8; 32],
+		old_value: Option<Vec<u8>>,
+		new_value: Option<&[u8]>,
+	) {
+		match self {
+			Self::CallTracer(tracer) => tracer.storage_write(address, key, old_value, new_value),
+			Self::PrestateTracer(tracer) =>
+				tracer.storage_write(address, key, old_value, new_value),
+		}
+	}
+
+	fn log_event(&mut self, address: H160, topics: &[H256], data: &[u8]) {
+		match self {
+			Self::CallTracer(tracer) => tracer.log_event(address, topics, data),
+			Self::PrestateTracer(tracer) => tracer.log_event(address, topics, data),
+		}
+	}
+}
+
+/// A tracer that builds the tree of call frames of a transaction.
+pub struct CallTracer<T> {
+	/// The configuration of the tracer.
+	config: CallTracerConfig,
+	/// The frames currently being executed, the outermost first.
+	///
+	/// When a frame exits, it is moved into the `calls` of its parent.
+	stack: Vec<CallTrace>,
+	/// The number of frames entered but not exited yet, including the untraced ones.
+	depth: u32,
+	/// The trace of the top level call, once it exited.
+	completed: Option<CallTrace>,
+	_phantom: PhantomData<T>,
+}
+
+impl<T: Config> CallTracer<T>
+where
+	BalanceOf<T>: Into<U256>,
+{
+	/// Create a new call tracer with the given configuration.
+	pub fn new(config: CallTracerConfig) -> Self {
+		Self { config, stack: Vec::new(), depth: 0, completed: None, _phantom: PhantomData }
+	}
+
+	/// Take the trace of the top level call, if it completed.
+	pub fn collect_trace(&mut self) -> Option<CallTrace> {
+		self.stack.clear();
+		self.depth = 0;
+		self.completed.take()
+	}
+
+	/// Whether the frame at the current depth is traced.
+	fn is_traced(&self) -> bool {
+		!self.config.only_top_call || self.depth <= 1
+	}
+
+	/// Complete the current frame and move it into its parent.
+	fn exit(&mut self, f: impl FnOnce(&mut CallTrace)) {
+		let traced = self.is_traced();
+		self.depth = self.depth.saturating_sub(1);
+		if !traced {
+			return;
+		}
+
+		let Some(mut trace) = self.stack.pop() else { return };
+		f(&mut trace);
+		match self.stack.last_mut() {
+			Some(parent) => parent.calls.push(trace),
+			None => self.completed = Some(trace),
+		}
+	}
+}
+
+impl<T: Config> Tracer for CallTracer<T>
+where
+	BalanceOf<T>: Into<U256>,
+{
+	fn enter_child_span(
+		&mut self,
+		from: H160,
+		to: H160,
+		is_delegate_call: bool,
+		is_read_only: bool,
+		entry_point: ExportedFunction,
+		value: U256,
+		input: &[u8],
+		gas_limit: Weight,
+	) {
+		self.depth += 1;
+		if !self.is_traced() {
+			return;
+		}
+
+		let call_type = if is_read_only {
+			CallType::StaticCall
+		} else if is_delegate_call {
+			CallType::DelegateCall
+		} else if matches!(entry_point, ExportedFunction::Constructor) {
+			CallType::Create
+		} else {
+			CallType::Call
+		};
+
+		self.stack.push(CallTrace {
+			from,
+			to,
+			gas: weight_to_gas::<T>(gas_limit),
+			input: Bytes(input.to_vec()),
+			value: (!is_delegate_call).then_some(value),
+			call_type,
+			..Default::default()
+		});
+	}
+
+	fn exit_child_span(&mut self, output: &ExecReturnValue, gas_used: Weight) {
+		self.exit(|trace| {
+			trace.gas_used = weight_to_gas::<T>(gas_used);
+			trace.output = Bytes(output.data.clone());
+			if output.did_revert() {
+				trace.error = Some("execution reverted".into());
+			}
+		});
+	}
+
+	fn exit_child_span_with_error(&mut self, error: DispatchError, gas_used: Weight) {
+		self.exit(|trace| {
+			trace.gas_used = weight_to_gas::<T>(gas_used);
+			trace.error = Some(String::from(<&'static str>::from(error)));
+		});
+	}
+
+	fn log_event(&mut self, address: H160, topics: &[H256], data: &[u8]) {
+		if !self.config.with_log || !self.is_traced() {
+			return;
+		}
+
+		if let Some(trace) = self.stack.last_mut() {
+			trace.logs.push(CallLog {
+				address,
+				topics: topics.to_vec(),
+				data: Bytes(data.to_vec()),
+				position: trace.calls.len().into(),
+			});
+		}
+	}
+}
+
+/// A tracer that records the state of the accounts touched by a transaction.
+pub struct PrestateTracer<T> {
+	/// The configuration of the tracer.
+	config: PrestateTracerConfig,
+	/// The state of the touched accounts, as it was when they were first touched.
+	pre: BTreeMap<H160, PrestateTraceInfo>,
+	_phantom: PhantomData<T>,
+}
+
+impl<T: Config> PrestateTracer<T>
+where
+	BalanceOf<T>: Into<U256> + TryFrom<U256> + Bounded,
+	MomentOf<T>: Into<U256>,
+	T::Hash: IsType<H256>,
+{
+	/// Create a new prestate tracer with the given configuration.
+	pub fn new(config: PrestateTracerConfig) -> Self {
+		Self { config, pre: BTreeMap::new(), _phantom: PhantomData }
+	}
+
+	/// Take the state recorded so far.
+	///
+	/// In diff mode, the current state of the touched accounts is read and only the accounts
+	/// and fields that changed are reported.
+	pub fn collect_trace(&mut self) -> Option<PrestateTrace> {
+		let pre = core::mem::take(&mut self.pre);
+		if pre.is_empty() {
+			return None;
+		}
+
+		if !self.config.diff_mode {
+			return Some(PrestateTrace::Prestate(pre));
+		}
+
+		let mut pre_diff = BTreeMap::new();
+		let mut post_diff = BTreeMap::new();
+		for (address, mut pre_info) in pre {
+			let mut post_info = Self::account_info(&address);
+			post_info.storage = pre_info
+				.storage
+				.keys()
+				.map(|key| (*key, Self::storage_value(&address, key)))
+				.collect();
+
+			pre_info.storage.retain(|key, value| post_info.storage.get(key) != Some(value));
+			post_info.storage.retain(|key, _| pre_info.storage.contains_key(key));
+			if post_info.balance == pre_info.balance {
+				post_info.balance = None;
+			}
+			if post_info.nonce == pre_info.nonce {
+				post_info.nonce = None;
+			}
+			if post_info.code == pre_info.code {
+				post_info.code = None;
+			}
+
+			if post_info != PrestateTraceInfo::default() {
+				pre_diff.insert(address, pre_info);
+				post_diff.insert(address, post_info);
+			}
+		}
+
+		Some(PrestateTrace::DiffMode { pre: pre_diff, post: post_diff })
+	}
+
+	/// Read the current balance, nonce and code of the given address.
+	fn account_info(address: &H160) -> PrestateTraceInfo {
+		let account_id = T::AddressMapper::to_account_id(address);
+		let code = ContractInfoOf::<T>::get(address)
+			.and_then(|info| PristineCode::<T>::get(info.code_hash))
+			.map(|code| Bytes(code.into_inner()));
+
+		PrestateTraceInfo {
+			balance: Some(Pallet::<T>::evm_balance(address)),
+			nonce: Some(frame_system::Pallet::<T>::account_nonce(account_id).saturated_into()),
+			code,
+			storage: BTreeMap::new(),
+		}
+	}
+
+	/// Read the current value of the given storage slot.
+	fn storage_value(address: &H160, key: &H256) -> Option<Bytes> {
+		ContractInfoOf::<T>::get(address)
+			.and_then(|info| info.read(&Key::from_fixed(key.0)))
+			.map(Bytes)
+	}
+
+	/// Record the state of the given address, if it was not touched before.
+	fn touch(&mut self, address: H160) -> &mut PrestateTraceInfo {
+		self.pre.entry(address).or_insert_with(|| Self::account_info(&address))
+	}
+}
+
+impl<T: Config> Tracer for PrestateTracer<T>
+where
+	BalanceOf<T>: Into<U256> + TryFrom<U256> + Bounded,
+	MomentOf<T>: Into<U256>,
+	T::Hash: IsType<H256>,
+{
+	fn enter_child_span(
+		&mut self,
+		from: H160,
+		to: H160,
+		_is_delegate_call: bool,
+		_is_read_only: bool,
+		_entry_point: ExportedFunction,
+		_value: U256,
+		_input: &[u8],
+		_gas_limit: Weight,
+	) {
+		self.touch(from);
+		self.touch(to);
+	}
+
+	fn exit_child_span(&mut self, _output: &ExecReturnValue, _gas_used: Weight) {}
+
+	fn exit_child_span_with_error(&mut self, _error: DispatchError, _gas_used: Weight) {}
+
+	fn storage_read(&mut self, address: &H160, key: &[u8; 32], value: Option<&[u8]>) {
+		self.touch(*address)
+			.storage
+			.entry(H256(*key))
+			.or_insert_with(|| value.map(|v| Bytes(v.to_vec())));
+	}
+
+	fn storage_write(
+		&mut self,
+		address: &H160,
+		key: &[u8; 32],
+		old_value: Option<Vec<u8>>,
+		_new_value: Option<&[u8]>,
+	) {
+		self.touch(*address)
+			.storage
+			.entry(H256(*key))
+			.or_insert_with(|| old_value.map(Bytes));
+	}
+}
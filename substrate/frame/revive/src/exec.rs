@@ -17,7 +17,7 @@
 
 use crate::{
 	address::{self, AddressMapper},
-	debug::{CallInterceptor, CallSpan, Tracing},
+	debug::{if_tracing, CallInterceptor, CallSpan, Tracing},
 	gas::GasMeter,
 	limits,
 	primitives::{ExecReturnValue, StorageDeposit},
@@ -168,6 +168,11 @@ impl<T: Config> Origin<T> {
 		}
 	}
 
+	/// Returns the address of a Signed Origin, or the zero address if the origin is Root.
+	fn address(&self) -> H160 {
+		self.account_id().map(T::AddressMapper::to_address).unwrap_or_default()
+	}
+
 	/// Make sure that this origin is mapped.
 	///
 	/// We require an origin to be mapped in order to be used in a `Stack`. Otherwise
@@ -795,7 +800,7 @@ where
 		)? {
 			stack.run(executable, input_data).map(|_| stack.first_frame.last_frame_output)
 		} else {
-			Self::transfer_from_origin(&origin, &origin, &dest, value)
+			Self::traced_transfer(&origin, &origin, &dest, value, &input_data, Weight::zero())
 		}
 	}
 
@@ -1054,6 +1059,28 @@ where
 		let delegated_code_hash =
 			if frame.delegate.is_some() { Some(*executable.code_hash()) } else { None };
 
+		if_tracing(|tracer| {
+			let from = self
+				.frames()
+				.nth(1)
+				.map(|f| T::AddressMapper::to_address(&f.account_id))
+				.unwrap_or_else(|| self.origin.address());
+			let to = frame.delegate.as_ref().map_or_else(
+				|| T::AddressMapper::to_address(&frame.account_id),
+				|delegate| delegate.callee,
+			);
+			tracer.enter_child_span(
+				from,
+				to,
+				frame.delegate.is_some(),
+				frame.read_only,
+				entry_point,
+				frame.value_transferred,
+				&input_data,
+				frame.nested_gas.gas_left(),
+			);
+		});
+
 		// The output of the caller frame will be replaced by the output of this run.
 		// It is also not accessible from nested frames.
 		// Hence we drop it early to save the memory.
@@ -1201,6 +1228,14 @@ where
 			self.transient_storage.rollback_transaction();
 		}
 
+		if_tracing(|tracer| {
+			let gas_used = self.top_frame().nested_gas.gas_consumed();
+			match &output {
+				Ok(output) => tracer.exit_child_span(output, gas_used),
+				Err(e) => tracer.exit_child_span_with_error(e.error, gas_used),
+			}
+		});
+
 		self.pop_frame(success);
 		output.map(|output| {
 			self.top_frame_mut().last_frame_output = output;
@@ -1344,6 +1379,39 @@ where
 		Self::transfer(origin, from, to, value)
 	}
 
+	/// Same as [`Self::transfer_from_origin`], but reports the transfer as a call frame to the
+	/// current tracer, if any.
+	fn traced_transfer(
+		origin: &Origin<T>,
+		from: &Origin<T>,
+		to: &T::AccountId,
+		value: U256,
+		input_data: &[u8],
+		gas_limit: Weight,
+	) -> ExecResult {
+		if_tracing(|tracer| {
+			tracer.enter_child_span(
+				from.address(),
+				T::AddressMapper::to_address(to),
+				false,
+				false,
+				ExportedFunction::Call,
+				value,
+				input_data,
+				gas_limit,
+			);
+		});
+
+		let result = Self::transfer_from_origin(origin, from, to, value);
+
+		if_tracing(|tracer| match &result {
+			Ok(output) => tracer.exit_child_span(output, Weight::zero()),
+			Err(e) => tracer.exit_child_span_with_error(e.error, Weight::zero()),
+		});
+
+		result
+	}
+
 	/// Reference to the current (top) frame.
 	fn top_frame(&self) -> &Frame<T> {
 		top_frame!(self)
@@ -1469,11 +1537,13 @@ where
 			)? {
 				self.run(executable, input_data)
 			} else {
-				Self::transfer_from_origin(
+				Self::traced_transfer(
 					&self.origin,
 					&Origin::from_account_id(self.account_id().clone()),
 					&dest,
 					value,
+					&input_data,
+					gas_limit,
 				)?;
 				Ok(())
 			}
@@ -1587,7 +1657,11 @@ where
 	}
 
 	fn get_storage(&mut self, key: &Key) -> Option<Vec<u8>> {
-		self.top_frame_mut().contract_info().read(key)
+		let value = self.top_frame_mut().contract_info().read(key);
+		if let Key::Fix(fixed) = key {
+			if_tracing(|tracer| tracer.storage_read(&self.address(), fixed, value.as_deref()));
+		}
+		value
 	}
 
 	fn get_storage_size(&mut self, key: &Key) -> Option<u32> {
@@ -1601,6 +1675,13 @@ where
 		take_old: bool,
 	) -> Result<WriteOutcome, DispatchError> {
 		let frame = self.top_frame_mut();
+		if let Key::Fix(fixed) = key {
+			if_tracing(|tracer| {
+				let address = T::AddressMapper::to_address(&frame.account_id);
+				let old_value = frame.contract_info().read(key);
+				tracer.storage_write(&address, fixed, old_value, value.as_deref());
+			});
+		}
 		frame.contract_info.get(&frame.account_id).write(
 			key.into(),
 			value,
@@ -1734,11 +1815,9 @@ where
 	}
 
 	fn deposit_event(&mut self, topics: Vec<H256>, data: Vec<u8>) {
-		Contracts::<Self::T>::deposit_event(Event::ContractEmitted {
-			contract: T::AddressMapper::to_address(self.account_id()),
-			data,
-			topics,
-		});
+		let contract = T::AddressMapper::to_address(self.account_id());
+		if_tracing(|tracer| tracer.log_event(contract, &topics, &data));
+		Contracts::<Self::T>::deposit_event(Event::ContractEmitted { contract, data, topics });
 	}
 
 	fn block_number(&self) -> U256 {
@@ -41,7 +41,7 @@ pub mod test_utils;
 pub mod weights;
 
 use crate::{
-	evm::{runtime::GAS_PRICE, GenericTransaction},
+	evm::{runtime::GAS_PRICE, GenericTransaction, Trace, TracerType},
 	exec::{AccountIdOf, ExecError, Executable, Ext, Key, Origin, Stack as ExecStack},
 	gas::GasMeter,
 	storage::{meter::Meter as StorageMeter, ContractInfo, DeletionQueueManager},
@@ -74,7 +74,7 @@ use pallet_transaction_payment::OnChargeTransaction;
 use scale_info::TypeInfo;
 use sp_core::{H160, H256, U256};
 use sp_runtime::{
-	traits::{BadOrigin, Bounded, Convert, Dispatchable, Saturating, Zero},
+	traits::{BadOrigin, Block as BlockT, Bounded, Convert, Dispatchable, Saturating, Zero},
 	DispatchError,
 };
 
@@ -1432,6 +1432,87 @@ where
 		Ok(result)
 	}
 
+	/// Create a tracer producing the output of the given `tracer_type`.
+	///
+	/// Use it with [`debug::trace`] to trace the contract executions of a closure.
+	pub fn evm_tracer(tracer_type: TracerType) -> evm::tracing::EvmTracer<T> {
+		evm::tracing::EvmTracer::new(tracer_type)
+	}
+
+	/// Re-execute the extrinsics of `block` and trace the contract executions of each of them.
+	///
+	/// `initialize_block` and `apply_extrinsic` are expected to be the `Executive` functions of
+	/// the runtime. Returns the index of each extrinsic that executed a contract, along with its
+	/// trace.
+	pub fn trace_block<Block: BlockT, I, A>(
+		block: Block,
+		tracer_type: TracerType,
+		initialize_block: impl FnOnce(&Block::Header) -> I,
+		mut apply_extrinsic: impl FnMut(Block::Extrinsic) -> A,
+	) -> Vec<(u32, Trace)> {
+		let mut tracer = Self::evm_tracer(tracer_type);
+		let mut traces = Vec::new();
+		let (header, extrinsics) = block.deconstruct();
+
+		initialize_block(&header);
+		for (index, ext) in extrinsics.into_iter().enumerate() {
+			debug::trace(&mut tracer, || {
+				let _ = apply_extrinsic(ext);
+			});
+
+			if let Some(tx_trace) = tracer.collect_trace() {
+				traces.push((index as u32, tx_trace));
+			}
+		}
+
+		traces
+	}
+
+	/// Re-execute the extrinsics of `block` up to the one at `tx_index`, and trace it.
+	///
+	/// See [`Self::trace_block`]. Returns `None` if the extrinsic does not exist or did not
+	/// execute any contract.
+	pub fn trace_tx<Block: BlockT, I, A>(
+		block: Block,
+		tx_index: u32,
+		tracer_type: TracerType,
+		initialize_block: impl FnOnce(&Block::Header) -> I,
+		mut apply_extrinsic: impl FnMut(Block::Extrinsic) -> A,
+	) -> Option<Trace> {
+		let mut tracer = Self::evm_tracer(tracer_type);
+		let (header, extrinsics) = block.deconstruct();
+
+		initialize_block(&header);
+		for (index, ext) in extrinsics.into_iter().enumerate() {
+			if index as u32 == tx_index {
+				debug::trace(&mut tracer, || {
+					let _ = apply_extrinsic(ext);
+				});
+				break;
+			} else {
+				let _ = apply_extrinsic(ext);
+			}
+		}
+
+		tracer.collect_trace()
+	}
+
+	/// Dry-run the given Ethereum call with `eth_transact`, and trace it.
+	///
+	/// `eth_transact` is expected to be the `eth_transact` runtime API implementation.
+	pub fn trace_call<R>(
+		tx: GenericTransaction,
+		tracer_type: TracerType,
+		eth_transact: impl FnOnce(GenericTransaction) -> Result<R, EthTransactError>,
+	) -> Result<Trace, EthTransactError> {
+		let mut tracer = Self::evm_tracer(tracer_type);
+		debug::trace(&mut tracer, || eth_transact(tx))?;
+
+		tracer
+			.collect_trace()
+			.ok_or(EthTransactError::Message("Failed to trace the call".into()))
+	}
+
 	/// Get the balance with EVM decimals of the given `address`.
 	pub fn evm_balance(address: &H160) -> U256 {
 		let account = T::AddressMapper::to_account_id(&address);
@@ -1529,7 +1610,7 @@ environmental!(executing_contract: bool);
 
 sp_api::decl_runtime_apis! {
 	/// The API used to dry-run contract interactions.
-	#[api_version(1)]
+	#[api_version(3)]
 	pub trait ReviveApi<AccountId, Balance, Nonce, BlockNumber, EventRecord> where
 		AccountId: Codec,
 		Balance: Codec,
@@ -1592,5 +1673,32 @@ sp_api::decl_runtime_apis! {
 			address: H160,
 			key: [u8; 32],
 		) -> GetStorageResult;
+
+		/// Re-execute the given block, tracing all the contract executions it contains.
+		///
+		/// The block is executed on top of the state of its parent. Returns the index of each
+		/// extrinsic that executed a contract, along with its trace.
+		#[api_version(3)]
+		fn trace_block(
+			block: Block,
+			config: TracerType,
+		) -> Vec<(u32, Trace)>;
+
+		/// Re-execute the given block up to the extrinsic at `tx_index`, and trace it.
+		///
+		/// The block is executed on top of the state of its parent. Returns `None` if the
+		/// extrinsic does not exist or did not execute any contract.
+		#[api_version(3)]
+		fn trace_tx(
+			block: Block,
+			tx_index: u32,
+			config: TracerType,
+		) -> Option<Trace>;
+
+		/// Dry-run and trace the given Ethereum call.
+		///
+		/// See [`crate::Pallet::bare_eth_transact`].
+		#[api_version(3)]
+		fn trace_call(tx: GenericTransaction, config: TracerType) -> Result<Trace, EthTransactError>;
 	}
 }
@@ -18,14 +18,15 @@
 use super::*;
 
 use crate::{
-	debug::{CallInterceptor, CallSpan, ExecResult, ExportedFunction, Tracing},
+	debug::{trace, CallInterceptor, CallSpan, ExecResult, ExportedFunction, Tracer, Tracing},
 	primitives::ExecReturnValue,
 	test_utils::*,
 	DepositLimit,
 };
 use frame_support::traits::Currency;
 use pretty_assertions::assert_eq;
-use sp_core::H160;
+use sp_core::{H160, U256};
+use sp_runtime::DispatchError;
 use std::cell::RefCell;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -237,3 +238,105 @@ fn call_interception_works() {
 		);
 	});
 }
+
+/// A [`Tracer`] recording the frames it observes, in the same format as [`TestDebug`].
+#[derive(Default)]
+struct TestTracer {
+	frames: Vec<DebugFrame>,
+	open_frames: Vec<DebugFrame>,
+}
+
+impl Tracer for TestTracer {
+	fn enter_child_span(
+		&mut self,
+		_from: H160,
+		to: H160,
+		_is_delegate_call: bool,
+		_is_read_only: bool,
+		entry_point: ExportedFunction,
+		_value: U256,
+		input: &[u8],
+		_gas_limit: Weight,
+	) {
+		let frame = DebugFrame {
+			contract_address: to,
+			call: entry_point,
+			input: input.to_vec(),
+			result: None,
+		};
+		self.frames.push(frame.clone());
+		self.open_frames.push(frame);
+	}
+
+	fn exit_child_span(&mut self, output: &ExecReturnValue, _gas_used: Weight) {
+		let frame = self.open_frames.pop().expect("every exited frame was entered; qed");
+		self.frames.push(DebugFrame { result: Some(output.data.clone()), ..frame });
+	}
+
+	fn exit_child_span_with_error(&mut self, _error: DispatchError, _gas_used: Weight) {
+		self.open_frames.pop();
+	}
+}
+
+#[test]
+fn tracer_works() {
+	let (wasm_caller, _) = compile_module("call").unwrap();
+	let (wasm_callee, _) = compile_module("store_call").unwrap();
+
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		let deploy = |wasm| {
+			Contracts::bare_instantiate(
+				RuntimeOrigin::signed(ALICE),
+				0,
+				GAS_LIMIT,
+				DepositLimit::Balance(deposit_limit::<Test>()),
+				Code::Upload(wasm),
+				vec![],
+				// some salt to ensure that the address of this contract is unique among all tests
+				Some([0x42; 32]),
+				DebugInfo::Skip,
+				CollectEvents::Skip,
+			)
+			.result
+			.unwrap()
+			.addr
+		};
+		let addr_caller = deploy(wasm_caller);
+		let addr_callee = deploy(wasm_callee);
+
+		let main_args = (100u32, &addr_callee.clone()).encode();
+		let inner_args = (100u32).encode();
+		let frame = |contract_address, input: &Vec<u8>, result: Option<Vec<u8>>| DebugFrame {
+			contract_address,
+			call: ExportedFunction::Call,
+			input: input.clone(),
+			result,
+		};
+
+		let mut tracer = TestTracer::default();
+		trace(&mut tracer, || {
+			assert_ok!(Contracts::call(
+				RuntimeOrigin::signed(ALICE),
+				addr_caller,
+				0,
+				GAS_LIMIT,
+				deposit_limit::<Test>(),
+				main_args.clone()
+			));
+		});
+
+		// Only the frames executed while the tracer is set are observed.
+		assert_eq!(
+			tracer.frames,
+			vec![
+				frame(addr_caller, &main_args, None),
+				frame(addr_callee, &inner_args, None),
+				frame(addr_callee, &inner_args, Some(vec![])),
+				frame(addr_caller, &main_args, Some(vec![])),
+			]
+		);
+		assert!(tracer.open_frames.is_empty());
+	});
+}
@@ -48,6 +48,7 @@ pub fn expand_runtime_metadata(
 			let calls = expand_pallet_metadata_calls(&filtered_names, runtime, decl);
 			let event = expand_pallet_metadata_events(&filtered_names, runtime, decl);
 			let constants = expand_pallet_metadata_constants(runtime, decl);
+			let view_functions = expand_pallet_metadata_view_functions(runtime, decl);
 			let errors = expand_pallet_metadata_errors(runtime, decl);
 			let associated_types = expand_pallet_metadata_associated_types(runtime, decl);
 			let docs = expand_pallet_metadata_docs(runtime, decl);
@@ -69,6 +70,7 @@ pub fn expand_runtime_metadata(
 					calls: #calls,
 					event: #event,
 					constants: #constants,
+					view_functions: #view_functions,
 					error: #errors,
 					docs: #docs,
 					associated_types: #associated_types,
@@ -252,6 +254,15 @@ fn expand_pallet_metadata_constants(runtime: &Ident, decl: &Pallet) -> TokenStre
 	}
 }
 
+fn expand_pallet_metadata_view_functions(runtime: &Ident, decl: &Pallet) -> TokenStream {
+	let path = &decl.path;
+	let instance = decl.instance.as_ref().into_iter();
+
+	quote! {
+		#path::Pallet::<#runtime #(, #path::#instance)*>::pallet_view_functions_metadata()
+	}
+}
+
 fn expand_pallet_metadata_errors(runtime: &Ident, decl: &Pallet) -> TokenStream {
 	let path = &decl.path;
 	let instance = decl.instance.as_ref().into_iter();
@@ -105,7 +105,7 @@ pub fn expand_outer_task(
 
 		#[automatically_derived]
 		impl #scrate::traits::Task for RuntimeTask {
-			type Enumeration = #prelude::IntoIter<RuntimeTask>;
+			type Enumeration = #prelude::Box<dyn #prelude::Iterator<Item = RuntimeTask>>;
 
 			fn is_valid(&self) -> bool {
 				match self {
@@ -148,12 +148,13 @@ pub fn expand_outer_task(
 			}
 
 			fn iter() -> Self::Enumeration {
-				let mut all_tasks = Vec::new();
+				let all_tasks: Self::Enumeration = #prelude::Box::new(#prelude::iter::empty());
 				#(
 					#cfg_attrs
-					all_tasks.extend(<#task_types>::iter().map(RuntimeTask::from).collect::<Vec<_>>());
+					let all_tasks: Self::Enumeration =
+						#prelude::Box::new(all_tasks.chain(<#task_types>::iter().map(RuntimeTask::from)));
 				)*
-				all_tasks.into_iter()
+				all_tasks
 			}
 		}
 
@@ -157,6 +157,7 @@ impl TasksDef {
 		let task_arg_names = self.tasks.iter().map(|task| &task.arg_names).collect::<Vec<_>>();
 
 		let impl_generics = &self.item_impl.generics;
+		let prelude = quote!(#frame_support::traits::tasks::__private);
 		quote! {
 			impl #impl_generics #enum_use
 			{
@@ -165,15 +166,14 @@ impl TasksDef {
 
 			impl #impl_generics #frame_support::traits::Task for #enum_use
 			{
-				type Enumeration = #frame_support::__private::IntoIter<#enum_use>;
+				type Enumeration = #prelude::Box<dyn #prelude::Iterator<Item = #enum_use>>;
 
 				fn iter() -> Self::Enumeration {
-					let mut all_tasks = #frame_support::__private::vec![];
-					#(all_tasks
-						.extend(#task_iters.map(|(#(#task_arg_names),*)| #enum_ident::#task_fn_idents { #(#task_arg_names: #task_arg_names.clone()),* })
-						.collect::<#frame_support::__private::Vec<_>>());
+					let all_tasks: Self::Enumeration = #prelude::Box::new(#prelude::iter::empty());
+					#(let all_tasks: Self::Enumeration = #prelude::Box::new(all_tasks
+						.chain(#task_iters.map(|(#(#task_arg_names),*)| #enum_ident::#task_fn_idents { #(#task_arg_names: #task_arg_names.clone()),* })));
 					)*
-					all_tasks.into_iter()
+					all_tasks
 				}
 
 				fn task_index(&self) -> u32 {
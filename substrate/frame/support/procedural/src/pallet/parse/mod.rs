@@ -36,6 +36,7 @@ pub mod storage;
 pub mod tasks;
 pub mod type_value;
 pub mod validate_unsigned;
+pub mod view_functions;
 
 #[cfg(test)]
 pub mod tests;
@@ -65,6 +66,7 @@ pub struct Def {
 	pub genesis_build: Option<genesis_build::GenesisBuildDef>,
 	pub validate_unsigned: Option<validate_unsigned::ValidateUnsignedDef>,
 	pub extra_constants: Option<extra_constants::ExtraConstantsDef>,
+	pub view_functions: Option<view_functions::ViewFunctionsImplDef>,
 	pub composites: Vec<composite::CompositeDef>,
 	pub type_values: Vec<type_value::TypeValueDef>,
 	pub frame_system: syn::Path,
@@ -100,6 +102,7 @@ impl Def {
 		let mut genesis_build = None;
 		let mut validate_unsigned = None;
 		let mut extra_constants = None;
+		let mut view_functions = None;
 		let mut storages = vec![];
 		let mut type_values = vec![];
 		let mut composites: Vec<CompositeDef> = vec![];
@@ -179,6 +182,8 @@ impl Def {
 				Some(PalletAttr::ExtraConstants(_)) =>
 					extra_constants =
 						Some(extra_constants::ExtraConstantsDef::try_from(item)?),
+				Some(PalletAttr::ViewFunctions(_)) if view_functions.is_none() =>
+					view_functions = Some(view_functions::ViewFunctionsImplDef::try_from(item)?),
 				Some(PalletAttr::Composite(span)) => {
 					let composite =
 						composite::CompositeDef::try_from(span, &frame_support, item)?;
@@ -237,6 +242,7 @@ impl Def {
 			tasks,
 			task_enum,
 			extra_constants,
+			view_functions,
 			genesis_config,
 			genesis_build,
 			validate_unsigned,
@@ -404,6 +410,9 @@ impl Def {
 		if let Some(extra_constants) = &self.extra_constants {
 			instances.extend_from_slice(&extra_constants.instances[..]);
 		}
+		if let Some(view_functions) = &self.view_functions {
+			instances.extend_from_slice(&view_functions.instances[..]);
+		}
 		if let Some(task_enum) = &self.task_enum {
 			instances.push(task_enum.instance_usage.clone());
 		}
@@ -562,6 +571,7 @@ mod keyword {
 	syn::custom_keyword!(type_value);
 	syn::custom_keyword!(pallet);
 	syn::custom_keyword!(extra_constants);
+	syn::custom_keyword!(view_functions);
 	syn::custom_keyword!(composite_enum);
 }
 
@@ -651,6 +661,7 @@ enum PalletAttr {
 	ValidateUnsigned(proc_macro2::Span),
 	TypeValue(proc_macro2::Span),
 	ExtraConstants(proc_macro2::Span),
+	ViewFunctions(proc_macro2::Span),
 	Composite(proc_macro2::Span),
 }
 
@@ -676,6 +687,7 @@ impl PalletAttr {
 			Self::ValidateUnsigned(span) => *span,
 			Self::TypeValue(span) => *span,
 			Self::ExtraConstants(span) => *span,
+			Self::ViewFunctions(span) => *span,
 			Self::Composite(span) => *span,
 		}
 	}
@@ -776,6 +788,8 @@ impl syn::parse::Parse for PalletAttr {
 			Ok(PalletAttr::TypeValue(content.parse::<keyword::type_value>()?.span()))
 		} else if lookahead.peek(keyword::extra_constants) {
 			Ok(PalletAttr::ExtraConstants(content.parse::<keyword::extra_constants>()?.span()))
+		} else if lookahead.peek(keyword::view_functions) {
+			Ok(PalletAttr::ViewFunctions(content.parse::<keyword::view_functions>()?.span()))
 		} else if lookahead.peek(keyword::composite_enum) {
 			Ok(PalletAttr::Composite(content.parse::<keyword::composite_enum>()?.span()))
 		} else {
@@ -0,0 +1,170 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::helper;
+use frame_support_procedural_tools::get_doc_literals;
+use inflector::Inflector;
+use syn::spanned::Spanned;
+
+/// Definition of view functions, typically `impl<T: Config> Pallet<T> { ... }`
+pub struct ViewFunctionsImplDef {
+	/// The where_clause used.
+	pub where_clause: Option<syn::WhereClause>,
+	/// A set of usage of instance, must be check for consistency with trait.
+	pub instances: Vec<helper::InstanceUsage>,
+	/// The view functions defined.
+	pub view_functions: Vec<ViewFunctionDef>,
+}
+
+/// Input definition for a view function in pallet.
+pub struct ViewFunctionDef {
+	/// Name of the function
+	pub name: syn::Ident,
+	/// The doc associated
+	pub docs: Vec<syn::Expr>,
+	/// The arguments of the function, in order
+	pub args: Vec<syn::PatType>,
+	/// The type returned by the function
+	pub return_type: syn::Type,
+	/// Attributes
+	pub attrs: Vec<syn::Attribute>,
+}
+
+impl ViewFunctionsImplDef {
+	pub fn try_from(item: &mut syn::Item) -> syn::Result<Self> {
+		let item = if let syn::Item::Impl(item) = item {
+			item
+		} else {
+			return Err(syn::Error::new(
+				item.span(),
+				"Invalid pallet::view_functions, expected item impl",
+			));
+		};
+
+		let instances = vec![
+			helper::check_impl_gen(&item.generics, item.impl_token.span())?,
+			helper::check_pallet_struct_usage(&item.self_ty)?,
+		];
+
+		if let Some((_, _, for_)) = item.trait_ {
+			let msg = "Invalid pallet::view_functions, expected no trait ident as in \
+				`impl<..> Pallet<..> { .. }`";
+			return Err(syn::Error::new(for_.span(), msg));
+		}
+
+		let mut view_functions = vec![];
+		for impl_item in &item.items {
+			let method = if let syn::ImplItem::Fn(method) = impl_item {
+				method
+			} else {
+				let msg = "Invalid pallet::view_functions, only method accepted";
+				return Err(syn::Error::new(impl_item.span(), msg));
+			};
+
+			if !method.sig.generics.params.is_empty() {
+				let msg = "Invalid pallet::view_functions, method must have 0 generics";
+				return Err(syn::Error::new(method.sig.generics.params[0].span(), msg));
+			}
+
+			if method.sig.generics.where_clause.is_some() {
+				let msg = "Invalid pallet::view_functions, method must have no where clause";
+				return Err(syn::Error::new(method.sig.generics.where_clause.span(), msg));
+			}
+
+			if method.sig.asyncness.is_some() || method.sig.unsafety.is_some() {
+				let msg = "Invalid pallet::view_functions, method must not be async or unsafe";
+				return Err(syn::Error::new(method.sig.span(), msg));
+			}
+
+			let mut args = vec![];
+			for input in &method.sig.inputs {
+				match input {
+					syn::FnArg::Receiver(receiver) => {
+						let msg = "Invalid pallet::view_functions, method must not have a \
+							`self` receiver";
+						return Err(syn::Error::new(receiver.span(), msg));
+					},
+					syn::FnArg::Typed(pat_type) => {
+						if !matches!(*pat_type.pat, syn::Pat::Ident(_)) {
+							let msg = "Invalid pallet::view_functions, argument must be a \
+								simple identifier";
+							return Err(syn::Error::new(pat_type.pat.span(), msg));
+						}
+						args.push(pat_type.clone());
+					},
+				}
+			}
+
+			let return_type = match &method.sig.output {
+				syn::ReturnType::Default => {
+					let msg = "Invalid pallet::view_functions, method must have a return type";
+					return Err(syn::Error::new(method.span(), msg));
+				},
+				syn::ReturnType::Type(_, type_) => *type_.clone(),
+			};
+
+			view_functions.push(ViewFunctionDef {
+				name: method.sig.ident.clone(),
+				docs: get_doc_literals(&method.attrs),
+				args,
+				return_type,
+				attrs: method.attrs.clone(),
+			});
+		}
+
+		Ok(Self { instances, where_clause: item.generics.where_clause.clone(), view_functions })
+	}
+}
+
+impl ViewFunctionDef {
+	/// The name of the struct generated to hold the arguments of this view function.
+	pub fn view_function_struct_ident(&self) -> syn::Ident {
+		syn::Ident::new(
+			&format!("{}ViewFunction", self.name.to_string().to_pascal_case()),
+			self.name.span(),
+		)
+	}
+
+	/// The identifier of each argument, in order.
+	pub fn args_names(&self) -> Vec<&syn::Ident> {
+		self.args
+			.iter()
+			.map(|arg| match &*arg.pat {
+				syn::Pat::Ident(pat_ident) => &pat_ident.ident,
+				_ => unreachable!("checked during parsing; qed"),
+			})
+			.collect()
+	}
+
+	/// The type of each argument, in order.
+	pub fn args_types(&self) -> Vec<&syn::Type> {
+		self.args.iter().map(|arg| &*arg.ty).collect()
+	}
+
+	/// The signature of the view function, used to compute its id suffix.
+	pub fn signature(&self) -> String {
+		let args = self
+			.args_types()
+			.iter()
+			.map(|ty| quote::quote!(#ty).to_string().replace(' ', ""))
+			.collect::<Vec<_>>()
+			.join(",");
+		let return_type = &self.return_type;
+		let return_type = quote::quote!(#return_type).to_string().replace(' ', "");
+		format!("{}({}) -> {}", self.name, args, return_type)
+	}
+}
@@ -86,6 +86,7 @@ pub mod storage;
 #[cfg(test)]
 mod tests;
 pub mod traits;
+pub mod view_functions;
 pub mod weights;
 #[doc(hidden)]
 pub mod unsigned {
@@ -1419,6 +1420,51 @@ pub mod pallet_macros {
 	/// args, 0 generics, and some return type.
 	pub use frame_support_procedural::extra_constants;
 
+	/// Declares read-only queries of the pallet, exposed to clients through the metadata and
+	/// the [`RuntimeViewFunction`](frame_support::view_functions::runtime_api::RuntimeViewFunction)
+	/// runtime API.
+	///
+	/// Must be defined like:
+	///
+	/// ```
+	/// #[frame_support::pallet]
+	/// mod pallet {
+	/// # 	use frame_support::pallet_prelude::*;
+	/// #
+	/// 	#[pallet::pallet]
+	/// 	pub struct Pallet<T>(_);
+	///
+	/// # 	#[pallet::config]
+	/// # 	pub trait Config: frame_system::Config {}
+	/// #
+	/// 	#[pallet::storage]
+	/// 	pub type SomeMap<T> = StorageMap<_, Twox64Concat, u32, u32>;
+	///
+	/// 	#[pallet::view_functions]
+	/// 	impl<T: Config> Pallet<T> // $optional_where_clause
+	/// 	{
+	/// 		/// Get a value from the map.
+	/// 		pub fn get_value_with_arg(key: u32) -> Option<u32> {
+	/// 			SomeMap::<T>::get(key)
+	/// 		}
+	/// 	}
+	/// }
+	/// ```
+	///
+	/// I.e. a regular rust `impl` block with some optional where clause and functions with no
+	/// `self` receiver, 0 generics, and some return type. Arguments and return type must
+	/// implement `Encode`, `Decode` and `TypeInfo`.
+	///
+	/// For each function a struct named `{FunctionName}ViewFunction` (in pascal case) is
+	/// generated holding its arguments. It implements
+	/// [`ViewFunction`](frame_support::view_functions::ViewFunction), whose id is the
+	/// `twox_128` hash of the pallet name followed by the `twox_128` hash of the function
+	/// signature. The runtime aggregates all pallets with
+	/// [`DispatchViewFunction`](frame_support::view_functions::DispatchViewFunction) and
+	/// exposes an `execute_view_function` function that can be used to implement the runtime
+	/// API.
+	pub use frame_support_procedural::view_functions;
+
 	#[rustfmt::skip]
 	/// Allows bypassing the `frame_system::Config` supertrait check.
 	///
@@ -29,9 +29,12 @@ use sp_weights::Weight;
 /// expansion of `RuntimeTask`.
 #[doc(hidden)]
 pub mod __private {
-	pub use alloc::{vec, vec::IntoIter};
+	pub use alloc::{boxed::Box, vec, vec::IntoIter};
 	pub use codec::FullCodec;
-	pub use core::{fmt::Debug, iter::Iterator};
+	pub use core::{
+		fmt::Debug,
+		iter::{self, Iterator},
+	};
 	pub use scale_info::TypeInfo;
 	pub use sp_runtime::DispatchError;
 	pub use sp_weights::Weight;
@@ -42,6 +45,8 @@ pub mod __private {
 /// tasks of this type.
 pub trait Task: Sized + FullCodec + TypeInfo + Clone + Debug + PartialEq + Eq {
 	/// An [`Iterator`] over tasks of this type used as the return type for `enumerate`.
+	///
+	/// Should enumerate lazily, so that callers only pay for the tasks they consume.
 	type Enumeration: Iterator;
 
 	/// Inspects the pallet's state and enumerates tasks of this type.
@@ -180,6 +180,22 @@ pub mod pallet {
 		}
 	}
 
+	#[pallet::view_functions]
+	impl<T: Config> Pallet<T>
+	where
+		T::AccountId: From<SomeType1> + SomeAssociation1,
+	{
+		/// Query value no args.
+		pub fn get_value() -> Option<u32> {
+			Value::<T>::get()
+		}
+
+		/// Query value with args.
+		pub fn get_value_with_arg(key: u16) -> Option<u32> {
+			Map2::<T>::get(key)
+		}
+	}
+
 	#[pallet::pallet]
 	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
@@ -2622,3 +2638,123 @@ fn pallet_metadata() {
 		assert_eq!(DeprecationInfoIR::NotDeprecated, meta.deprecation_info);
 	}
 }
+
+#[test]
+fn pallet_view_functions_work() {
+	use codec::{DecodeAll, Encode};
+	use frame_support::view_functions::{
+		DispatchViewFunction, ViewFunction, ViewFunctionDispatchError, ViewFunctionId,
+		ViewFunctionIdPrefix,
+	};
+
+	TestExternalities::default().execute_with(|| {
+		pallet::Value::<Runtime>::put(42);
+		pallet::Map2::<Runtime>::insert(1, 2);
+
+		let query = pallet::GetValueViewFunction::<Runtime>::new();
+		assert_eq!(query.clone().invoke(), Some(42));
+
+		let id = pallet::GetValueViewFunction::<Runtime>::id();
+		assert_eq!(id.prefix, <pallet::Pallet<Runtime> as ViewFunctionIdPrefix>::prefix());
+		assert_eq!(id.prefix, twox_128(b"Example"));
+
+		let output = Runtime::execute_view_function(id, query.encode()).unwrap();
+		assert_eq!(Option::<u32>::decode_all(&mut &output[..]).unwrap(), Some(42));
+
+		let query = pallet::GetValueWithArgViewFunction::<Runtime>::new(1);
+		let id = pallet::GetValueWithArgViewFunction::<Runtime>::id();
+		let output = Runtime::execute_view_function(id.clone(), query.encode()).unwrap();
+		assert_eq!(Option::<u32>::decode_all(&mut &output[..]).unwrap(), Some(2));
+
+		// Trailing input is rejected.
+		let mut input = query.encode();
+		input.push(0);
+		assert_eq!(
+			Runtime::execute_view_function(id.clone(), input),
+			Err(ViewFunctionDispatchError::Codec)
+		);
+
+		// Unknown suffix in a known pallet.
+		let unknown = ViewFunctionId { prefix: id.prefix, suffix: [0; 16] };
+		let mut output = Vec::new();
+		assert_eq!(
+			<pallet::Pallet<Runtime> as DispatchViewFunction>::dispatch_view_function(
+				&unknown,
+				&mut &[][..],
+				&mut output
+			),
+			Err(ViewFunctionDispatchError::NotFound(unknown.clone()))
+		);
+
+		// Unknown pallet.
+		let unknown = ViewFunctionId { prefix: [0; 16], suffix: id.suffix };
+		assert_eq!(
+			Runtime::execute_view_function(unknown.clone(), Vec::new()),
+			Err(ViewFunctionDispatchError::NotFound(unknown))
+		);
+	});
+}
+
+#[test]
+fn pallet_view_functions_metadata() {
+	use frame_support::view_functions::ViewFunction;
+	use sp_metadata_ir::{DeprecationStatusIR, PalletViewFunctionParamMetadataIR};
+
+	let metadata = Runtime::metadata_ir();
+	let pallet = metadata.pallets.iter().find(|pallet| pallet.name == "Example").unwrap();
+	let view_functions = &pallet.view_functions;
+	assert_eq!(view_functions.len(), 2);
+
+	assert_eq!(view_functions[0].name, "get_value");
+	assert_eq!(
+		view_functions[0].id,
+		<[u8; 32]>::from(pallet::GetValueViewFunction::<Runtime>::id())
+	);
+	assert!(view_functions[0].inputs.is_empty());
+	assert_eq!(view_functions[0].output, meta_type::<Option<u32>>());
+	assert_eq!(view_functions[0].docs, maybe_docs(vec![" Query value no args."]));
+	assert_eq!(view_functions[0].deprecation_info, DeprecationStatusIR::NotDeprecated);
+
+	assert_eq!(view_functions[1].name, "get_value_with_arg");
+	assert_eq!(
+		view_functions[1].inputs,
+		vec![PalletViewFunctionParamMetadataIR { name: "key", ty: meta_type::<u16>() }]
+	);
+	assert_eq!(view_functions[1].output, meta_type::<Option<u32>>());
+
+	// Pallets without view functions still expose an empty list.
+	let other = metadata.pallets.iter().find(|pallet| pallet.name == "Example2").unwrap();
+	assert!(other.view_functions.is_empty());
+}
+
+#[test]
+fn pallet_view_functions_unstable_metadata() {
+	use frame_metadata::{v16::PalletViewFunctionParamMetadata, RuntimeMetadata};
+	use frame_support::view_functions::ViewFunction;
+
+	let metadata = sp_metadata_ir::into_version(Runtime::metadata_ir(), u32::MAX).unwrap();
+	let RuntimeMetadata::V16(metadata) = metadata.1 else { panic!("expected metadata v16") };
+	let pallet = metadata.pallets.iter().find(|pallet| pallet.name == "Example").unwrap();
+	let view_functions = &pallet.view_functions;
+	assert_eq!(view_functions.len(), 2);
+
+	assert_eq!(view_functions[0].name, "get_value");
+	assert_eq!(
+		view_functions[0].id,
+		<[u8; 32]>::from(pallet::GetValueViewFunction::<Runtime>::id())
+	);
+	assert!(view_functions[0].inputs.is_empty());
+
+	assert_eq!(view_functions[1].name, "get_value_with_arg");
+	assert_eq!(
+		view_functions[1].id,
+		<[u8; 32]>::from(pallet::GetValueWithArgViewFunction::<Runtime>::id())
+	);
+	assert!(matches!(
+		&view_functions[1].inputs[..],
+		[PalletViewFunctionParamMetadata { name, .. }] if name == "key"
+	));
+
+	let other = metadata.pallets.iter().find(|pallet| pallet.name == "Example2").unwrap();
+	assert!(other.view_functions.is_empty());
+}
@@ -200,6 +200,7 @@ fn runtime_metadata() {
 				" Documentation on multiline.",
 			]),
 			deprecation_info: DeprecationStatusIR::DeprecatedWithoutNote,
+			version: 1,
 
 		},
 		RuntimeApiMetadataIR {
@@ -238,6 +239,7 @@ fn runtime_metadata() {
 				" The `Core` runtime api that every Substrate runtime needs to implement.",
 			]),
 			deprecation_info: DeprecationStatusIR::NotDeprecated,
+			version: 5,
 		},
 	];
 
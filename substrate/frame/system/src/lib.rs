@@ -1467,6 +1467,36 @@ impl<T: Config> Pallet<T> {
 		Account::<T>::contains_key(who)
 	}
 
+	/// Returns up to `limit` tasks of the runtime that are currently valid.
+	///
+	/// Meant to be used by the runtime API that lets the node discover tasks to submit.
+	pub fn valid_tasks(limit: u32) -> Vec<T::RuntimeTask>
+	where
+		<T::RuntimeTask as frame_support::traits::Task>::Enumeration:
+			Iterator<Item = T::RuntimeTask>,
+	{
+		use frame_support::traits::Task;
+
+		T::RuntimeTask::iter()
+			.filter(|task| task.is_valid())
+			.take(limit as usize)
+			.collect()
+	}
+
+	/// Returns the [`Call::do_task`] call executing `task`.
+	///
+	/// Returns `None` if the `experimental` feature, which provides `do_task`, is disabled.
+	pub fn task_call(task: T::RuntimeTask) -> Option<Call<T>> {
+		#[cfg(feature = "experimental")]
+		return Some(Call::do_task { task });
+
+		#[cfg(not(feature = "experimental"))]
+		{
+			let _ = task;
+			None
+		}
+	}
+
 	/// Write code to the storage and emit related events and digest items.
 	///
 	/// Note this function almost never should be used directly. It is exposed
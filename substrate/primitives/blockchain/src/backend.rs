@@ -248,6 +248,31 @@ pub trait Backend<Block: BlockT>:
 
 	fn block_indexed_body(&self, hash: Block::Hash) -> Result<Option<Vec<Vec<u8>>>>;
 
+	/// Get the blocks that include the extrinsic with the given hash, as `(block hash, extrinsic
+	/// index)` pairs. Only blocks whose body is still stored are returned.
+	///
+	/// Returns `None` if the backend does not index extrinsics.
+	fn extrinsic_locations(&self, _hash: Block::Hash) -> Result<Option<Vec<(Block::Hash, u32)>>> {
+		Ok(None)
+	}
+
+	/// Get up to `limit` of the blocks that include extrinsics signed by the given encoded signer,
+	/// as `(block hash, extrinsic index)` pairs, in ascending order of block numbers. Only blocks
+	/// whose body is still stored are returned.
+	///
+	/// The pairs are read after the opaque `start_key`. If more pairs follow the returned ones,
+	/// the key to read them from is returned as well.
+	///
+	/// Returns `None` if the backend does not index extrinsics by signer.
+	fn signer_extrinsic_locations(
+		&self,
+		_signer: &[u8],
+		_start_key: Option<&[u8]>,
+		_limit: usize,
+	) -> Result<Option<(Vec<(Block::Hash, u32)>, Option<Vec<u8>>)>> {
+		Ok(None)
+	}
+
 	/// Returns all leaves that will be displaced after the block finalization.
 	fn displaced_leaves_after_finalizing(
 		&self,
@@ -105,6 +105,9 @@ pub enum Error {
 	#[error("This method is not currently available when running in light client mode")]
 	NotAvailableOnLightClient,
 
+	#[error("Operation not supported: {0}")]
+	UnsupportedOperation(&'static str),
+
 	#[error("Remote node has responded with invalid header proof")]
 	InvalidCHTProof,
 
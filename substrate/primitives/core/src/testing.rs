@@ -45,6 +45,9 @@ pub const ECDSA_BLS381: KeyTypeId = KeyTypeId(*b"ecb8");
 /// The functions are feature-gated with `#[cfg(not(feature = "std"))]`, so they are only available
 /// from within wasm.
 ///
+/// When building for PolkaVM the functions are exported through `sp-runtime-interface`, so the
+/// calling crate needs to depend on it.
+///
 /// # Example
 ///
 /// ```
@@ -86,6 +89,12 @@ macro_rules! wasm_export_functions {
 		#[no_mangle]
 		#[allow(unreachable_code)]
 		#[cfg(not(feature = "std"))]
+		#[cfg_attr(
+			any(target_arch = "riscv32", target_arch = "riscv64"),
+			::sp_runtime_interface::polkavm::polkavm_export(
+				abi = ::sp_runtime_interface::polkavm::polkavm_abi
+			)
+		)]
 		pub fn $name(input_data: *mut u8, input_len: usize) -> u64 {
 			let input: &[u8] = if input_len == 0 {
 				&[0u8; 0]
@@ -114,6 +123,12 @@ macro_rules! wasm_export_functions {
 		#[no_mangle]
 		#[allow(unreachable_code)]
 		#[cfg(not(feature = "std"))]
+		#[cfg_attr(
+			any(target_arch = "riscv32", target_arch = "riscv64"),
+			::sp_runtime_interface::polkavm::polkavm_export(
+				abi = ::sp_runtime_interface::polkavm::polkavm_abi
+			)
+		)]
 		pub fn $name(input_data: *mut u8, input_len: usize) -> u64 {
 			let input: &[u8] = if input_len == 0 {
 				&[0u8; 0]
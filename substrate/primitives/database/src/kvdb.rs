@@ -115,4 +115,19 @@ impl<D: KeyValueDB, H: Clone + AsRef<[u8]>> Database<H> for DbAdapter<D> {
 	fn contains(&self, col: ColumnId, key: &[u8]) -> bool {
 		handle_err(self.0.has_key(col, key))
 	}
+
+	fn iter_with_prefix(
+		&self,
+		col: ColumnId,
+		prefix: &[u8],
+		f: &mut dyn FnMut(&[u8], &[u8]) -> bool,
+	) -> error::Result<()> {
+		for item in self.0.iter_with_prefix(col, prefix) {
+			let (key, value) = item.map_err(|e| error::DatabaseError(Box::new(e)))?;
+			if !f(&key[..], &value[..]) {
+				break
+			}
+		}
+		Ok(())
+	}
 }
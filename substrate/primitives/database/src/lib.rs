@@ -117,6 +117,23 @@ pub trait Database<H: Clone + AsRef<[u8]>>: Send + Sync {
 	///
 	/// Not all database implementations use a prefix for keys, so this function may be a noop.
 	fn sanitize_key(&self, _key: &mut Vec<u8>) {}
+
+	/// Call `f` with the keys and values of `col` that start with `prefix`, in ascending order
+	/// of keys, until `f` returns `false`.
+	///
+	/// Returns an error if the database or the column doesn't support ordered iteration, which is
+	/// the default.
+	fn iter_with_prefix(
+		&self,
+		_col: ColumnId,
+		_prefix: &[u8],
+		_f: &mut dyn FnMut(&[u8], &[u8]) -> bool,
+	) -> error::Result<()> {
+		Err(error::DatabaseError(Box::new(std::io::Error::new(
+			std::io::ErrorKind::Unsupported,
+			"Iterating the database is not supported",
+		))))
+	}
 }
 
 impl<H> std::fmt::Debug for dyn Database<H> {
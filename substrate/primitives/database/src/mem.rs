@@ -73,6 +73,32 @@ where
 		let s = self.0.read();
 		s.get(&col).and_then(|c| c.get(key).map(|(_, v)| v.clone()))
 	}
+
+	fn iter_with_prefix(
+		&self,
+		col: ColumnId,
+		prefix: &[u8],
+		f: &mut dyn FnMut(&[u8], &[u8]) -> bool,
+	) -> error::Result<()> {
+		let mut entries: Vec<_> = {
+			let s = self.0.read();
+			s.get(&col)
+				.map(|c| {
+					c.iter()
+						.filter(|(k, _)| k.starts_with(prefix))
+						.map(|(k, (_, v))| (k.clone(), v.clone()))
+						.collect()
+				})
+				.unwrap_or_default()
+		};
+		entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+		for (key, value) in entries {
+			if !f(&key, &value) {
+				break
+			}
+		}
+		Ok(())
+	}
 }
 
 impl MemDb {
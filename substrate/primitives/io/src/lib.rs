@@ -131,15 +131,12 @@ use sp_externalities::{Externalities, ExternalitiesExt};
 
 pub use sp_externalities::MultiRemovalResults;
 
-#[cfg(all(not(feature = "disable_allocator"), substrate_runtime, target_family = "wasm"))]
-mod global_alloc_wasm;
-
 #[cfg(all(
 	not(feature = "disable_allocator"),
 	substrate_runtime,
-	any(target_arch = "riscv32", target_arch = "riscv64")
+	any(target_family = "wasm", target_arch = "riscv32", target_arch = "riscv64")
 ))]
-mod global_alloc_riscv;
+mod global_alloc;
 
 #[cfg(feature = "std")]
 const LOG_TARGET: &str = "runtime::io";
@@ -20,6 +20,8 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 
+extern crate alloc;
+
 /// Re-export of parent module scope storage prefix.
 pub use sp_core::offchain::STORAGE_PREFIX;
 
@@ -34,4 +36,15 @@ sp_api::decl_runtime_apis! {
 		/// Starts the off-chain task for given block header.
 		fn offchain_worker(header: &Block::Header);
 	}
+
+	/// The api used by the node to discover and submit `#[pallet::tasks]` work.
+	pub trait TasksApi<Task: sp_runtime::codec::Codec> {
+		/// Returns up to `limit` tasks that are currently valid.
+		fn valid_tasks(limit: u32) -> alloc::vec::Vec<Task>;
+
+		/// Returns the unsigned extrinsic executing `task`.
+		///
+		/// Returns `None` if the runtime can't execute tasks.
+		fn task_extrinsic(task: Task) -> Option<Block::Extrinsic>;
+	}
 }
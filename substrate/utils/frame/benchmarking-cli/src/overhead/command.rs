@@ -490,6 +490,8 @@ impl OverheadCmd {
 			state_pruning: None,
 			blocks_pruning: BlocksPruning::KeepAll,
 			source: database_source,
+			extrinsic_index: false,
+			extrinsic_signer: None,
 		})?;
 
 		let genesis_block_builder = GenesisBlockBuilder::new_with_storage(
@@ -520,6 +522,7 @@ impl OverheadCmd {
 				no_genesis: false,
 				wasm_runtime_substitutes: Default::default(),
 				enable_import_proof_recording: chain_type.requires_proof_recording(),
+				remote_proofs: None,
 			},
 		)?);
 
@@ -60,6 +60,7 @@ impl WasmBuilderSelectProject {
 			disable_runtime_version_section_check: false,
 			export_heap_base: false,
 			import_memory: false,
+			target: None,
 			#[cfg(feature = "metadata-hash")]
 			enable_metadata_hash: None,
 		}
@@ -80,6 +81,7 @@ impl WasmBuilderSelectProject {
 				disable_runtime_version_section_check: false,
 				export_heap_base: false,
 				import_memory: false,
+				target: None,
 				#[cfg(feature = "metadata-hash")]
 				enable_metadata_hash: None,
 			})
@@ -120,6 +122,9 @@ pub struct WasmBuilder {
 	/// Whether `--import-memory` should be added to the link args (WASM-only).
 	import_memory: bool,
 
+	/// The target to build for, overriding `SUBSTRATE_RUNTIME_TARGET`.
+	target: Option<RuntimeTarget>,
+
 	/// Whether to enable the metadata hash generation.
 	#[cfg(feature = "metadata-hash")]
 	enable_metadata_hash: Option<MetadataExtraInfo>,
@@ -222,6 +227,15 @@ impl WasmBuilder {
 		self
 	}
 
+	/// Build the binary for the given `target`.
+	///
+	/// If this function is not called, the target is selected by the `SUBSTRATE_RUNTIME_TARGET`
+	/// environment variable.
+	pub fn set_target(mut self, target: RuntimeTarget) -> Self {
+		self.target = Some(target);
+		self
+	}
+
 	/// Disable the check for the `runtime_version` wasm section.
 	///
 	/// By default the `wasm-builder` will ensure that the `runtime_version` section will
@@ -235,7 +249,7 @@ impl WasmBuilder {
 
 	/// Build the WASM binary.
 	pub fn build(mut self) {
-		let target = RuntimeTarget::new();
+		let target = self.target.unwrap_or_else(RuntimeTarget::new);
 
 		if target == RuntimeTarget::Wasm {
 			if self.export_heap_base {
@@ -378,14 +378,17 @@ fn get_bool_environment_variable(name: &str) -> Option<bool> {
 	}
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
-enum RuntimeTarget {
+/// The target a runtime is built for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RuntimeTarget {
+	/// WebAssembly, executed by wasmtime.
 	Wasm,
+	/// RISC-V, executed by PolkaVM.
 	Riscv,
 }
 
 impl RuntimeTarget {
-	/// Creates a new instance.
+	/// Creates a new instance from the `SUBSTRATE_RUNTIME_TARGET` environment variable.
 	fn new() -> Self {
 		let Some(value) = env::var_os(RUNTIME_TARGET) else {
 			return Self::Wasm;
@@ -403,6 +406,13 @@ impl RuntimeTarget {
 		}
 	}
 
+	/// Returns whether a toolchain that can build runtimes for this target is installed.
+	///
+	/// Useful to build an additional binary for a target only where its toolchain is available.
+	pub fn is_toolchain_installed(self) -> bool {
+		prerequisites::check(self).is_ok()
+	}
+
 	/// Figures out the target parameter value for rustc.
 	fn rustc_target(self) -> String {
 		match self {
@@ -69,6 +69,7 @@ pub(crate) fn check(target: RuntimeTarget) -> Result<CargoCommandVersioned, Stri
 			}
 
 			let dummy_crate = DummyCrate::new(&cargo_command, target);
+			check_rust_src_installed(&dummy_crate, target)?;
 			let version = dummy_crate.get_rustc_version();
 			Ok(CargoCommandVersioned::new(cargo_command, version))
 		},
@@ -200,22 +201,32 @@ fn check_wasm_toolchain_installed(
 	}
 
 	let version = dummy_crate.get_rustc_version();
+	check_rust_src_installed(&dummy_crate, RuntimeTarget::Wasm)?;
 
-	let target = RuntimeTarget::new();
-	assert!(target == RuntimeTarget::Wasm);
-	if target.rustc_target_build_std().is_some() {
-		if let Some(sysroot) = dummy_crate.get_sysroot() {
-			let src_path =
-				Path::new(sysroot.trim()).join("lib").join("rustlib").join("src").join("rust");
-			if !src_path.exists() {
-				let toolchain = dummy_crate.get_toolchain().unwrap_or("<toolchain>".to_string());
-				return Err(colorize_error_message(
-					&format!("Cannot compile the WASM runtime: no standard library sources found at {}!\n\
-					 You can install them with `rustup component add rust-src --toolchain {toolchain}` if you're using `rustup`.", src_path.display()),
-				))
-			}
+	Ok(CargoCommandVersioned::new(cargo_command, version))
+}
+
+/// Checks that the standard library sources are installed if `target` builds them.
+fn check_rust_src_installed(dummy_crate: &DummyCrate, target: RuntimeTarget) -> Result<(), String> {
+	if target.rustc_target_build_std().is_none() {
+		return Ok(())
+	}
+
+	if let Some(sysroot) = dummy_crate.get_sysroot() {
+		let src_path =
+			Path::new(sysroot.trim()).join("lib").join("rustlib").join("src").join("rust");
+		if !src_path.exists() {
+			let toolchain = dummy_crate.get_toolchain().unwrap_or("<toolchain>".to_string());
+			let runtime = match target {
+				RuntimeTarget::Wasm => "WASM",
+				RuntimeTarget::Riscv => "RISC-V",
+			};
+			return Err(colorize_error_message(
+				&format!("Cannot compile the {runtime} runtime: no standard library sources found at {}!\n\
+				 You can install them with `rustup component add rust-src --toolchain {toolchain}` if you're using `rustup`.", src_path.display()),
+			))
 		}
 	}
 
-	Ok(CargoCommandVersioned::new(cargo_command, version))
+	Ok(())
 }
@@ -188,7 +188,7 @@ pub fn new_full<Network: sc_network::NetworkBackend<Block, <Block as BlockT>::Ha
 		task_manager: &mut task_manager,
 		transaction_pool: transaction_pool.clone(),
 		rpc_builder: rpc_extensions_builder,
-		backend,
+		backend: backend.clone(),
 		system_rpc_tx,
 		tx_handler_controller,
 		sync_service,
@@ -253,7 +253,8 @@ pub fn new_full<Network: sc_network::NetworkBackend<Block, <Block as BlockT>::Ha
 					Ok(sp_timestamp::InherentDataProvider::from_system_time())
 				},
 			};
-			let authorship_future = sc_consensus_manual_seal::run_manual_seal(params);
+			let authorship_future =
+				sc_consensus_manual_seal::run_manual_seal_with_backend(params, backend);
 
 			task_manager.spawn_essential_handle().spawn_blocking(
 				"manual-seal",